@@ -31,6 +31,9 @@ type BloomFilterStatsProper struct {
 	FalsePositiveRate  float64 `json:"false_positive_rate"`
 	TimestampEntries   uint64  `json:"timestamp_entries"`
 	AverageAgeSeconds  float64 `json:"average_age_seconds"`
+	QueryCount         uint64  `json:"query_count"`
+	ObservedFPCount    uint64  `json:"observed_fp_count"`
+	ObservedFPRate     float64 `json:"observed_fp_rate"`
 }
 
 // NewBitcoinBloomFilterProper creates a new Bitcoin Bloom filter using the universal bloom filter
@@ -125,17 +128,32 @@ func (bf *BitcoinBloomFilterProper) GetStats() (*BloomFilterStatsProper, error)
 		return nil, errors.New("bloom filter is null")
 	}
 
-	count := C.bloom_filter_count((*C.UniversalBloomFilter)(bf.handle))
-	rate := C.bloom_filter_false_positive_rate((*C.UniversalBloomFilter)(bf.handle))
+	handle := (*C.UniversalBloomFilter)(bf.handle)
+	count := C.bloom_filter_count(handle)
+	rate := C.bloom_filter_theoretical_fp_rate(handle)
 
 	return &BloomFilterStatsProper{
 		ItemCount:         uint64(count),
 		FalsePositiveRate: float64(rate),
 		TimestampEntries:  uint64(count), // Simplified
 		AverageAgeSeconds: 0,             // Not available in basic interface
+		QueryCount:        uint64(C.bloom_filter_query_count(handle)),
+		ObservedFPCount:   uint64(C.bloom_filter_observed_fp_count(handle)),
+		ObservedFPRate:    float64(C.bloom_filter_observed_fp_rate(handle)),
 	}, nil
 }
 
+// ConfirmFalsePositive reports that a ContainsUTXO hit was not found in the full UTXO index
+func (bf *BitcoinBloomFilterProper) ConfirmFalsePositive() error {
+	if bf.handle == nil {
+		return errors.New("bloom filter is null")
+	}
+	if C.bloom_filter_confirm_false_positive((*C.UniversalBloomFilter)(bf.handle)) != 0 {
+		return errors.New("failed to record false positive")
+	}
+	return nil
+}
+
 // Free releases the bloom filter resources
 func (bf *BitcoinBloomFilterProper) Free() {
 	if bf.handle != nil {
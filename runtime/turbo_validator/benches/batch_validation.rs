@@ -0,0 +1,32 @@
+//! Serial vs parallel transaction validation on 10k dummy transactions.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use turbo_validator::{TurboValidator, ValidatorConfig};
+
+fn dummy_transactions(count: usize) -> Vec<Vec<u8>> {
+    (0..count)
+        .map(|i| {
+            let mut tx = vec![0u8; 250];
+            tx[..8].copy_from_slice(&(i as u64).to_le_bytes());
+            tx
+        })
+        .collect()
+}
+
+fn bench_batch_validation(c: &mut Criterion) {
+    let txs = dummy_transactions(10_000);
+    let refs: Vec<&[u8]> = txs.iter().map(|t| t.as_slice()).collect();
+    let validator = TurboValidator::default()
+        .with_config(ValidatorConfig::default())
+        .expect("validator config");
+
+    let mut group = c.benchmark_group("validate_10k_transactions");
+    group.bench_function("serial", |b| {
+        b.iter(|| refs.iter().map(|tx| validator.validate_transaction(tx)).collect::<Vec<_>>())
+    });
+    group.bench_function("parallel", |b| b.iter(|| validator.validate_transactions_batch(&refs, false)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_batch_validation);
+criterion_main!(benches);
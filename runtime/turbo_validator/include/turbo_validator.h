@@ -0,0 +1,130 @@
+#ifndef TURBO_VALIDATOR_H
+#define TURBO_VALIDATOR_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * FFI status: success
+ */
+#define TURBO_VALIDATOR_OK 0
+
+/**
+ * FFI status: null handle, null data with a non-zero length, or an out-of-range argument
+ */
+#define TURBO_VALIDATOR_ERR_INVALID_ARGUMENT -1
+
+/**
+ * FFI status: `ValidationError::InvalidBlock`
+ */
+#define TURBO_VALIDATOR_ERR_INVALID_BLOCK -2
+
+/**
+ * FFI status: `ValidationError::InvalidTransaction`
+ */
+#define TURBO_VALIDATOR_ERR_INVALID_TRANSACTION -3
+
+/**
+ * FFI status: `ValidationError::SignatureError`
+ */
+#define TURBO_VALIDATOR_ERR_SIGNATURE -4
+
+/**
+ * FFI status: `ValidationError::DoubleSpend`
+ */
+#define TURBO_VALIDATOR_ERR_DOUBLE_SPEND -5
+
+/**
+ * FFI status: `ValidationError::Other`
+ */
+#define TURBO_VALIDATOR_ERR_OTHER -6
+
+/**
+ * FFI status: the validator panicked; the handle stays usable
+ */
+#define TURBO_VALIDATOR_ERR_PANIC -7
+
+/**
+ * Opaque validator handle owned by the caller
+ */
+typedef struct TurboValidatorHandle TurboValidatorHandle;
+
+/**
+ * Create a validator from individual policy settings. Returns null when
+ * `entropy_pqc_weight` is outside 0.0..=1.0.
+ */
+TurboValidatorHandle *turbo_validator_new(bool kyber_enabled,
+                                          bool dilithium_enabled,
+                                          double entropy_pqc_weight);
+
+/**
+ * # Safety
+ *
+ * `policy_json` must be null or point to a valid NUL-terminated C string. Accepts a JSON
+ * object with `kyber_enabled`, `dilithium_enabled`, `entropy_pqc_weight` and hex-encoded
+ * `kyber_secret_key` / `dilithium_public_key`; omitted fields take their defaults. Returns
+ * null on a null pointer, malformed JSON, unknown fields or invalid values.
+ */
+TurboValidatorHandle *turbo_validator_new_from_json(const char *policy_json);
+
+/**
+ * # Safety
+ *
+ * `handle` must be a live pointer returned by a `turbo_validator_new*` function, and
+ * `data` must point to `len` readable bytes (it may be null when `len` is 0). A PQC
+ * envelope, when the policy requires one, is read from the block trailer.
+ */
+int turbo_validator_validate_block(const TurboValidatorHandle *handle,
+                                   const uint8_t *data,
+                                   size_t len);
+
+/**
+ * # Safety
+ *
+ * `handle` must be a live pointer returned by a `turbo_validator_new*` function, and
+ * `data` must point to `len` readable bytes (it may be null when `len` is 0).
+ */
+int turbo_validator_validate_transaction(const TurboValidatorHandle *handle,
+                                         const uint8_t *data,
+                                         size_t len);
+
+/**
+ * # Safety
+ *
+ * `handle` must be a live pointer returned by a `turbo_validator_new*` function.
+ * Returns `TURBO_VALIDATOR_ERR_INVALID_ARGUMENT` when `weight` is outside 0.0..=1.0.
+ */
+int turbo_validator_set_pqc_weight(const TurboValidatorHandle *handle, double weight);
+
+/**
+ * # Safety
+ *
+ * `handle` must be null or a live pointer returned by a `turbo_validator_new*` function.
+ * Returns a copy of the message of the most recent failure on this handle, or null when
+ * nothing has failed. With concurrent callers the latest failure wins. The caller owns
+ * the string and must free it with `turbo_validator_string_free`.
+ */
+char *turbo_validator_last_error(const TurboValidatorHandle *handle);
+
+/**
+ * # Safety
+ *
+ * `message` must be null or a pointer returned by `turbo_validator_last_error`. After
+ * this call the pointer must not be used.
+ */
+void turbo_validator_string_free(char *message);
+
+/**
+ * # Safety
+ *
+ * `handle` must be null or a pointer returned by a `turbo_validator_new*` function that
+ * has not been freed. After this call the pointer must not be used.
+ */
+void turbo_validator_free(TurboValidatorHandle *handle);
+
+#endif  /* TURBO_VALIDATOR_H */
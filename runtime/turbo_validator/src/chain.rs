@@ -0,0 +1,242 @@
+//! Hash-linked entropy receipts.
+//!
+//! `ReceiptChain::next_receipt` stamps each receipt with the previous receipt's hash and
+//! its own `self_hash`, and refuses beacon rounds that do not increase, so a dropped,
+//! reordered or replayed round shows up in `verify_chain`. `self_hash` is the hex
+//! SHA-256 of the receipt's JSON with `self_hash` left out; the receipt is a flat
+//! struct, so that JSON has a fixed field order.
+//!
+//! With `ReceiptChain::open` the chain head is written to a file after every receipt,
+//! so rounds keep increasing across restarts.
+
+use crate::EntropyHybridReceipt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// `prev_hash` of the first receipt in a chain
+pub const GENESIS_PREV_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Why a receipt could not be issued or a chain does not verify
+#[derive(Debug)]
+pub enum ChainError {
+    /// Beacon round is not above the round before it
+    RoundNotIncreasing { round: u64, previous: u64 },
+    /// Receipt carries no `prev_hash` or `self_hash`
+    Unchained { index: usize },
+    /// `self_hash` does not match the receipt's contents
+    HashMismatch { index: usize },
+    /// `prev_hash` is not the previous receipt's `self_hash`
+    BrokenLink { index: usize },
+    Encoding(String),
+    Persistence(String),
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::RoundNotIncreasing { round, previous } => {
+                write!(f, "Beacon round {} does not follow round {}", round, previous)
+            }
+            ChainError::Unchained { index } => write!(f, "Receipt {}: missing prev_hash or self_hash", index),
+            ChainError::HashMismatch { index } => write!(f, "Receipt {}: self_hash does not match contents", index),
+            ChainError::BrokenLink { index } => {
+                write!(f, "Receipt {}: prev_hash does not match the previous receipt", index)
+            }
+            ChainError::Encoding(msg) => write!(f, "Receipt encoding failed: {}", msg),
+            ChainError::Persistence(msg) => write!(f, "Chain head persistence failed: {}", msg),
+        }
+    }
+}
+
+impl Error for ChainError {}
+
+/// Round and hash of the last issued receipt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainHead {
+    pub beacon_round: u64,
+    pub hash: String,
+}
+
+/// Issues hash-linked receipts with strictly increasing beacon rounds
+#[derive(Debug, Default)]
+pub struct ReceiptChain {
+    head: Option<ChainHead>,
+    /// Where the head is persisted; `None` keeps the chain in memory only
+    path: Option<PathBuf>,
+}
+
+impl ReceiptChain {
+    /// In-memory chain starting from `GENESIS_PREV_HASH`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Chain whose head is persisted at `path`, resuming from the head stored there if any
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, ChainError> {
+        let path = path.into();
+        let head = match fs::read(&path) {
+            Ok(bytes) => Some(
+                serde_json::from_slice(&bytes)
+                    .map_err(|e| ChainError::Persistence(format!("{}: {}", path.display(), e)))?,
+            ),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(ChainError::Persistence(format!("{}: {}", path.display(), e))),
+        };
+        Ok(Self { head, path: Some(path) })
+    }
+
+    /// Last issued receipt, or `None` for a fresh chain
+    pub fn head(&self) -> Option<&ChainHead> {
+        self.head.as_ref()
+    }
+
+    /// Link `receipt`, e.g. from `generate_entropy_hybrid_receipt`, onto the chain.
+    /// Any `prev_hash`/`self_hash` it carries is replaced. The head is persisted before
+    /// the receipt is returned; if that fails the chain does not advance.
+    pub fn next_receipt(&mut self, mut receipt: EntropyHybridReceipt) -> Result<EntropyHybridReceipt, ChainError> {
+        if let Some(head) = &self.head {
+            if receipt.beacon_round <= head.beacon_round {
+                return Err(ChainError::RoundNotIncreasing { round: receipt.beacon_round, previous: head.beacon_round });
+            }
+        }
+        receipt.prev_hash = Some(self.head.as_ref().map_or(GENESIS_PREV_HASH, |h| &h.hash).to_string());
+        let hash = receipt_hash(&receipt)?;
+        receipt.self_hash = Some(hash.clone());
+
+        let head = ChainHead { beacon_round: receipt.beacon_round, hash };
+        if let Some(path) = &self.path {
+            persist_head(path, &head)?;
+        }
+        self.head = Some(head);
+        Ok(receipt)
+    }
+}
+
+/// Write via a temporary file so a crash never leaves a truncated head behind
+fn persist_head(path: &Path, head: &ChainHead) -> Result<(), ChainError> {
+    let persistence = |e: io::Error| ChainError::Persistence(format!("{}: {}", path.display(), e));
+    let json = serde_json::to_vec(head).map_err(|e| ChainError::Encoding(e.to_string()))?;
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, json).map_err(persistence)?;
+    fs::rename(&tmp, path).map_err(persistence)
+}
+
+/// Hex SHA-256 of the receipt's JSON without `self_hash`
+pub fn receipt_hash(receipt: &EntropyHybridReceipt) -> Result<String, ChainError> {
+    let unhashed = EntropyHybridReceipt { self_hash: None, ..receipt.clone() };
+    let json = serde_json::to_vec(&unhashed).map_err(|e| ChainError::Encoding(e.to_string()))?;
+    Ok(hex::encode(Sha256::digest(json)))
+}
+
+/// Check that every receipt's `self_hash` matches its contents, that each `prev_hash`
+/// names the receipt before it and that beacon rounds strictly increase. The first
+/// receipt may continue an earlier segment, so its `prev_hash` is only required to be set.
+pub fn verify_chain(receipts: &[EntropyHybridReceipt]) -> Result<(), ChainError> {
+    let mut previous: Option<(&str, u64)> = None;
+    for (index, receipt) in receipts.iter().enumerate() {
+        let (Some(prev_hash), Some(self_hash)) = (&receipt.prev_hash, &receipt.self_hash) else {
+            return Err(ChainError::Unchained { index });
+        };
+        if receipt_hash(receipt)? != *self_hash {
+            return Err(ChainError::HashMismatch { index });
+        }
+        if let Some((previous_hash, previous_round)) = previous {
+            if prev_hash != previous_hash {
+                return Err(ChainError::BrokenLink { index });
+            }
+            if receipt.beacon_round <= previous_round {
+                return Err(ChainError::RoundNotIncreasing { round: receipt.beacon_round, previous: previous_round });
+            }
+        }
+        previous = Some((self_hash, receipt.beacon_round));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TurboValidator;
+
+    fn receipt(round: u64) -> EntropyHybridReceipt {
+        TurboValidator::default().generate_entropy_hybrid_receipt(round, "attest", &format!("proof{}", round), "verifierX")
+    }
+
+    fn chain_of(rounds: &[u64]) -> Vec<EntropyHybridReceipt> {
+        let mut chain = ReceiptChain::new();
+        rounds.iter().map(|round| chain.next_receipt(receipt(*round)).unwrap()).collect()
+    }
+
+    fn head_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("turbo_validator_{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_five_receipt_chain_verifies() {
+        let receipts = chain_of(&[10, 11, 12, 20, 21]);
+        assert_eq!(receipts[0].prev_hash.as_deref(), Some(GENESIS_PREV_HASH));
+        for pair in receipts.windows(2) {
+            assert_eq!(pair[1].prev_hash, pair[0].self_hash);
+        }
+        assert!(verify_chain(&receipts).is_ok());
+        // Any contiguous segment verifies on its own
+        assert!(verify_chain(&receipts[2..]).is_ok());
+        assert!(verify_chain(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_middle_receipt_fails() {
+        let mut receipts = chain_of(&[1, 2, 3, 4, 5]);
+        receipts[2].proof_hash = "forged".into();
+        assert!(matches!(verify_chain(&receipts), Err(ChainError::HashMismatch { index: 2 })));
+
+        // Rehashing the forged receipt breaks the link from its successor instead
+        receipts[2].self_hash = Some(receipt_hash(&receipts[2]).unwrap());
+        assert!(matches!(verify_chain(&receipts), Err(ChainError::BrokenLink { index: 3 })));
+
+        let mut dropped = chain_of(&[1, 2, 3, 4, 5]);
+        dropped.remove(1);
+        assert!(matches!(verify_chain(&dropped), Err(ChainError::BrokenLink { index: 1 })));
+        assert!(matches!(verify_chain(&[receipt(1)]), Err(ChainError::Unchained { index: 0 })));
+    }
+
+    #[test]
+    fn test_replayed_round_rejected_at_issuance() {
+        let mut chain = ReceiptChain::new();
+        chain.next_receipt(receipt(7)).unwrap();
+        let head = chain.head().cloned();
+        assert!(matches!(
+            chain.next_receipt(receipt(7)),
+            Err(ChainError::RoundNotIncreasing { round: 7, previous: 7 })
+        ));
+        assert!(chain.next_receipt(receipt(6)).is_err());
+        assert_eq!(chain.head().cloned(), head);
+        assert!(chain.next_receipt(receipt(8)).is_ok());
+    }
+
+    #[test]
+    fn test_head_survives_restart() {
+        let path = head_path("chain_head");
+        let _ = fs::remove_file(&path);
+
+        let mut chain = ReceiptChain::open(&path).unwrap();
+        assert!(chain.head().is_none());
+        let first = chain.next_receipt(receipt(100)).unwrap();
+        drop(chain);
+
+        let mut reopened = ReceiptChain::open(&path).unwrap();
+        assert_eq!(reopened.head().map(|h| h.beacon_round), Some(100));
+        assert!(reopened.next_receipt(receipt(100)).is_err());
+        let second = reopened.next_receipt(receipt(101)).unwrap();
+        assert!(verify_chain(&[first, second]).is_ok());
+
+        fs::write(&path, b"not json").unwrap();
+        assert!(matches!(ReceiptChain::open(&path), Err(ChainError::Persistence(_))));
+        fs::remove_file(&path).unwrap();
+    }
+}
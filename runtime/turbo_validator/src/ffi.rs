@@ -0,0 +1,423 @@
+//! C ABI for TurboValidator, used by the Go relay.
+//!
+//! A validator is created with `turbo_validator_new` or `turbo_validator_new_from_json`
+//! and released with `turbo_validator_free`. Validation calls return `TURBO_VALIDATOR_OK`
+//! or a negative code for the `ValidationError` variant; the message of the most recent
+//! failure on a handle is available from `turbo_validator_last_error`. The matching C
+//! header is `include/turbo_validator.h`, generated by cbindgen from this module.
+
+use crate::{PQCPolicy, PqcKeyring, TurboValidator, ValidationError};
+use serde::Deserialize;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Mutex, RwLock, RwLockReadGuard};
+
+/// FFI status: success
+pub const TURBO_VALIDATOR_OK: c_int = 0;
+/// FFI status: null handle, null data with a non-zero length, or an out-of-range argument
+pub const TURBO_VALIDATOR_ERR_INVALID_ARGUMENT: c_int = -1;
+/// FFI status: `ValidationError::InvalidBlock`
+pub const TURBO_VALIDATOR_ERR_INVALID_BLOCK: c_int = -2;
+/// FFI status: `ValidationError::InvalidTransaction`
+pub const TURBO_VALIDATOR_ERR_INVALID_TRANSACTION: c_int = -3;
+/// FFI status: `ValidationError::SignatureError`
+pub const TURBO_VALIDATOR_ERR_SIGNATURE: c_int = -4;
+/// FFI status: `ValidationError::DoubleSpend`
+pub const TURBO_VALIDATOR_ERR_DOUBLE_SPEND: c_int = -5;
+/// FFI status: `ValidationError::Other`
+pub const TURBO_VALIDATOR_ERR_OTHER: c_int = -6;
+/// FFI status: the validator panicked; the handle stays usable
+pub const TURBO_VALIDATOR_ERR_PANIC: c_int = -7;
+
+/// Opaque validator handle owned by the caller
+pub struct TurboValidatorHandle {
+    validator: RwLock<TurboValidator>,
+    last_error: Mutex<Option<CString>>,
+}
+
+impl TurboValidatorHandle {
+    fn into_raw(validator: TurboValidator) -> *mut TurboValidatorHandle {
+        Box::into_raw(Box::new(TurboValidatorHandle {
+            validator: RwLock::new(validator),
+            last_error: Mutex::new(None),
+        }))
+    }
+
+    fn validator(&self) -> RwLockReadGuard<'_, TurboValidator> {
+        self.validator.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn set_error(&self, message: &str) {
+        let message = CString::new(message.replace('\0', "\\0")).ok();
+        *self.last_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = message;
+    }
+}
+
+/// Status code for a validation error
+pub fn error_code(err: &ValidationError) -> c_int {
+    match err {
+        ValidationError::InvalidBlock(_) => TURBO_VALIDATOR_ERR_INVALID_BLOCK,
+        ValidationError::InvalidTransaction(_) => TURBO_VALIDATOR_ERR_INVALID_TRANSACTION,
+        ValidationError::SignatureError(_) => TURBO_VALIDATOR_ERR_SIGNATURE,
+        ValidationError::DoubleSpend(_) => TURBO_VALIDATOR_ERR_DOUBLE_SPEND,
+        ValidationError::Other(_) => TURBO_VALIDATOR_ERR_OTHER,
+    }
+}
+
+fn valid_weight(weight: f64) -> bool {
+    (0.0..=1.0).contains(&weight)
+}
+
+/// Run `f` against a live handle, recording any failure as the handle's last error
+unsafe fn with_handle<F>(handle: *const TurboValidatorHandle, f: F) -> c_int
+where
+    F: FnOnce(&TurboValidatorHandle) -> Result<(), ValidationError>,
+{
+    if handle.is_null() {
+        return TURBO_VALIDATOR_ERR_INVALID_ARGUMENT;
+    }
+    let handle = &*handle;
+    match catch_unwind(AssertUnwindSafe(|| f(handle))) {
+        Ok(Ok(())) => TURBO_VALIDATOR_OK,
+        Ok(Err(e)) => {
+            handle.set_error(&e.to_string());
+            error_code(&e)
+        }
+        Err(_) => {
+            handle.set_error("Validator panicked");
+            TURBO_VALIDATOR_ERR_PANIC
+        }
+    }
+}
+
+unsafe fn invalid_argument(handle: *const TurboValidatorHandle, message: &str) -> c_int {
+    if let Some(handle) = handle.as_ref() {
+        handle.set_error(message);
+    }
+    TURBO_VALIDATOR_ERR_INVALID_ARGUMENT
+}
+
+/// Borrow `len` bytes at `data`; None when `data` is null but `len` is not zero
+unsafe fn byte_slice<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (data.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(std::slice::from_raw_parts(data, len)),
+    }
+}
+
+/// Settings accepted by `turbo_validator_new_from_json`; omitted fields take their defaults
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FfiSettings {
+    kyber_enabled: bool,
+    dilithium_enabled: bool,
+    entropy_pqc_weight: f64,
+    /// Hex-encoded Kyber secret key
+    kyber_secret_key: Option<String>,
+    /// Hex-encoded Dilithium public key
+    dilithium_public_key: Option<String>,
+}
+
+impl Default for FfiSettings {
+    fn default() -> Self {
+        let policy = PQCPolicy::default();
+        Self {
+            kyber_enabled: policy.kyber_enabled,
+            dilithium_enabled: policy.dilithium_enabled,
+            entropy_pqc_weight: policy.entropy_pqc_weight,
+            kyber_secret_key: None,
+            dilithium_public_key: None,
+        }
+    }
+}
+
+impl FfiSettings {
+    fn into_validator(self) -> Result<TurboValidator, String> {
+        if !valid_weight(self.entropy_pqc_weight) {
+            return Err(format!("entropy_pqc_weight {} is outside 0.0..=1.0", self.entropy_pqc_weight));
+        }
+        let decode = |name: &str, key: Option<String>| {
+            key.map(|k| hex::decode(k).map_err(|e| format!("{} is not valid hex: {}", name, e)))
+                .transpose()
+        };
+        let keys = PqcKeyring {
+            kyber_secret_key: decode("kyber_secret_key", self.kyber_secret_key)?,
+            dilithium_public_key: decode("dilithium_public_key", self.dilithium_public_key)?,
+        };
+        let policy = PQCPolicy {
+            kyber_enabled: self.kyber_enabled,
+            dilithium_enabled: self.dilithium_enabled,
+            entropy_pqc_weight: self.entropy_pqc_weight,
+        };
+        Ok(TurboValidator::with_pqc(policy, keys))
+    }
+}
+
+/// Create a validator from individual policy settings. Returns null when
+/// `entropy_pqc_weight` is outside 0.0..=1.0.
+#[no_mangle]
+pub extern "C" fn turbo_validator_new(
+    kyber_enabled: bool,
+    dilithium_enabled: bool,
+    entropy_pqc_weight: f64,
+) -> *mut TurboValidatorHandle {
+    if !valid_weight(entropy_pqc_weight) {
+        return std::ptr::null_mut();
+    }
+    let policy = PQCPolicy { kyber_enabled, dilithium_enabled, entropy_pqc_weight };
+    TurboValidatorHandle::into_raw(TurboValidator::with_pqc(policy, PqcKeyring::default()))
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `policy_json` must be null or point to a valid NUL-terminated C string. Accepts a JSON
+/// object with `kyber_enabled`, `dilithium_enabled`, `entropy_pqc_weight` and hex-encoded
+/// `kyber_secret_key` / `dilithium_public_key`; omitted fields take their defaults. Returns
+/// null on a null pointer, malformed JSON, unknown fields or invalid values.
+pub unsafe extern "C" fn turbo_validator_new_from_json(policy_json: *const c_char) -> *mut TurboValidatorHandle {
+    if policy_json.is_null() {
+        return std::ptr::null_mut();
+    }
+    let settings = CStr::from_ptr(policy_json)
+        .to_str()
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<FfiSettings>(json).map_err(|e| e.to_string()))
+        .and_then(FfiSettings::into_validator);
+    match settings {
+        Ok(validator) => TurboValidatorHandle::into_raw(validator),
+        Err(e) => {
+            log::warn!("Rejected TurboValidator policy: {}", e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `handle` must be a live pointer returned by a `turbo_validator_new*` function, and
+/// `data` must point to `len` readable bytes (it may be null when `len` is 0). A PQC
+/// envelope, when the policy requires one, is read from the block trailer.
+pub unsafe extern "C" fn turbo_validator_validate_block(
+    handle: *const TurboValidatorHandle,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    let Some(block) = byte_slice(data, len) else {
+        return invalid_argument(handle, "data is null");
+    };
+    with_handle(handle, |h| h.validator().validate_block(block))
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `handle` must be a live pointer returned by a `turbo_validator_new*` function, and
+/// `data` must point to `len` readable bytes (it may be null when `len` is 0).
+pub unsafe extern "C" fn turbo_validator_validate_transaction(
+    handle: *const TurboValidatorHandle,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    let Some(tx) = byte_slice(data, len) else {
+        return invalid_argument(handle, "data is null");
+    };
+    with_handle(handle, |h| h.validator().validate_transaction(tx))
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `handle` must be a live pointer returned by a `turbo_validator_new*` function.
+/// Returns `TURBO_VALIDATOR_ERR_INVALID_ARGUMENT` when `weight` is outside 0.0..=1.0.
+pub unsafe extern "C" fn turbo_validator_set_pqc_weight(handle: *const TurboValidatorHandle, weight: f64) -> c_int {
+    if !valid_weight(weight) {
+        return invalid_argument(handle, &format!("entropy_pqc_weight {} is outside 0.0..=1.0", weight));
+    }
+    with_handle(handle, |h| {
+        h.validator.write().unwrap_or_else(|poisoned| poisoned.into_inner()).pqc_policy.entropy_pqc_weight = weight;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `handle` must be null or a live pointer returned by a `turbo_validator_new*` function.
+/// Returns a copy of the message of the most recent failure on this handle, or null when
+/// nothing has failed. With concurrent callers the latest failure wins. The caller owns
+/// the string and must free it with `turbo_validator_string_free`.
+pub unsafe extern "C" fn turbo_validator_last_error(handle: *const TurboValidatorHandle) -> *mut c_char {
+    let Some(handle) = handle.as_ref() else {
+        return std::ptr::null_mut();
+    };
+    let last_error = handle.last_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match last_error.as_ref() {
+        Some(message) => message.clone().into_raw(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `message` must be null or a pointer returned by `turbo_validator_last_error`. After
+/// this call the pointer must not be used.
+pub unsafe extern "C" fn turbo_validator_string_free(message: *mut c_char) {
+    if !message.is_null() {
+        drop(CString::from_raw(message));
+    }
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `handle` must be null or a pointer returned by a `turbo_validator_new*` function that
+/// has not been freed. After this call the pointer must not be used.
+pub unsafe extern "C" fn turbo_validator_free(handle: *mut TurboValidatorHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Take ownership of the handle's last error message
+    unsafe fn last_error(handle: *const TurboValidatorHandle) -> Option<String> {
+        let ptr = turbo_validator_last_error(handle);
+        if ptr.is_null() {
+            return None;
+        }
+        let message = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+        turbo_validator_string_free(ptr);
+        Some(message)
+    }
+
+    unsafe fn from_json(json: &str) -> *mut TurboValidatorHandle {
+        let json = CString::new(json).unwrap();
+        turbo_validator_new_from_json(json.as_ptr())
+    }
+
+    #[test]
+    fn test_validate_through_raw_pointers() {
+        unsafe {
+            let handle = turbo_validator_new(false, false, 0.5);
+            assert!(!handle.is_null());
+            assert_eq!(last_error(handle), None);
+
+            let block = crate::header::fixtures::genesis_header();
+            assert_eq!(turbo_validator_validate_block(handle, block.as_ptr(), block.len()), TURBO_VALIDATOR_OK);
+            assert_eq!(turbo_validator_validate_block(handle, block.as_ptr(), 0), TURBO_VALIDATOR_ERR_INVALID_BLOCK);
+            assert!(last_error(handle).unwrap().contains("Block data is empty"));
+
+            assert_eq!(
+                turbo_validator_validate_transaction(handle, std::ptr::null(), 0),
+                TURBO_VALIDATOR_ERR_INVALID_TRANSACTION
+            );
+            let garbage = b"garbage";
+            assert_eq!(turbo_validator_validate_transaction(handle, garbage.as_ptr(), garbage.len()), TURBO_VALIDATOR_OK);
+            turbo_validator_free(handle);
+
+            let strict = turbo_validator_new(true, true, 0.5);
+            assert_eq!(turbo_validator_validate_block(strict, block.as_ptr(), block.len()), TURBO_VALIDATOR_ERR_SIGNATURE);
+            assert!(last_error(strict).unwrap().contains("requires an envelope"));
+            turbo_validator_free(strict);
+        }
+    }
+
+    #[test]
+    fn test_null_handle_and_data_are_rejected() {
+        unsafe {
+            let data = b"tx";
+            let null = std::ptr::null();
+            assert_eq!(turbo_validator_validate_block(null, data.as_ptr(), data.len()), TURBO_VALIDATOR_ERR_INVALID_ARGUMENT);
+            assert_eq!(
+                turbo_validator_validate_transaction(null, data.as_ptr(), data.len()),
+                TURBO_VALIDATOR_ERR_INVALID_ARGUMENT
+            );
+            assert_eq!(turbo_validator_set_pqc_weight(null, 0.5), TURBO_VALIDATOR_ERR_INVALID_ARGUMENT);
+            assert!(turbo_validator_last_error(null).is_null());
+            assert!(turbo_validator_new_from_json(std::ptr::null()).is_null());
+            turbo_validator_string_free(std::ptr::null_mut());
+            turbo_validator_free(std::ptr::null_mut());
+
+            let handle = turbo_validator_new(false, false, 0.5);
+            assert_eq!(
+                turbo_validator_validate_transaction(handle, std::ptr::null(), 4),
+                TURBO_VALIDATOR_ERR_INVALID_ARGUMENT
+            );
+            assert_eq!(last_error(handle).as_deref(), Some("data is null"));
+            turbo_validator_free(handle);
+        }
+    }
+
+    #[test]
+    fn test_json_policy_and_pqc_weight() {
+        unsafe {
+            let handle = from_json(r#"{"kyber_enabled": false, "dilithium_enabled": false, "entropy_pqc_weight": 0.25}"#);
+            assert!(!handle.is_null());
+            assert_eq!((*handle).validator().entropy_pqc_weight(), 0.25);
+
+            assert_eq!(turbo_validator_set_pqc_weight(handle, 0.75), TURBO_VALIDATOR_OK);
+            assert_eq!((*handle).validator().entropy_pqc_weight(), 0.75);
+            assert_eq!(turbo_validator_set_pqc_weight(handle, f64::NAN), TURBO_VALIDATOR_ERR_INVALID_ARGUMENT);
+            assert_eq!(turbo_validator_set_pqc_weight(handle, 1.5), TURBO_VALIDATOR_ERR_INVALID_ARGUMENT);
+            assert!(last_error(handle).unwrap().contains("outside"));
+            assert_eq!((*handle).validator().entropy_pqc_weight(), 0.75);
+            turbo_validator_free(handle);
+
+            let defaults = from_json(r#"{"dilithium_public_key": "0a0b"}"#);
+            assert!((*defaults).validator().pqc_policy.kyber_enabled);
+            assert_eq!((*defaults).validator().pqc_keys.dilithium_public_key, Some(vec![0x0a, 0x0b]));
+            turbo_validator_free(defaults);
+
+            assert!(from_json("not json").is_null());
+            assert!(from_json(r#"{"kyber_enabeld": false}"#).is_null());
+            assert!(from_json(r#"{"entropy_pqc_weight": 2.0}"#).is_null());
+            assert!(from_json(r#"{"kyber_secret_key": "zz"}"#).is_null());
+            assert!(turbo_validator_new(true, true, -0.1).is_null());
+        }
+    }
+
+    #[test]
+    fn test_error_codes_cover_every_variant() {
+        let codes = [
+            error_code(&ValidationError::InvalidBlock(String::new())),
+            error_code(&ValidationError::InvalidTransaction(String::new())),
+            error_code(&ValidationError::SignatureError(String::new())),
+            error_code(&ValidationError::DoubleSpend(String::new())),
+            error_code(&ValidationError::Other(String::new())),
+        ];
+        assert_eq!(codes, [-2, -3, -4, -5, -6]);
+    }
+
+    #[test]
+    fn test_header_declares_every_export() {
+        let header = include_str!("../include/turbo_validator.h");
+        for symbol in [
+            "typedef struct TurboValidatorHandle TurboValidatorHandle;",
+            "#define TURBO_VALIDATOR_OK 0",
+            "#define TURBO_VALIDATOR_ERR_INVALID_ARGUMENT -1",
+            "#define TURBO_VALIDATOR_ERR_INVALID_BLOCK -2",
+            "#define TURBO_VALIDATOR_ERR_INVALID_TRANSACTION -3",
+            "#define TURBO_VALIDATOR_ERR_SIGNATURE -4",
+            "#define TURBO_VALIDATOR_ERR_DOUBLE_SPEND -5",
+            "#define TURBO_VALIDATOR_ERR_OTHER -6",
+            "#define TURBO_VALIDATOR_ERR_PANIC -7",
+            "turbo_validator_new(",
+            "turbo_validator_new_from_json(",
+            "turbo_validator_validate_block(",
+            "turbo_validator_validate_transaction(",
+            "turbo_validator_set_pqc_weight(",
+            "turbo_validator_last_error(",
+            "turbo_validator_string_free(",
+            "turbo_validator_free(",
+        ] {
+            assert!(header.contains(symbol), "header is missing {}", symbol);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Bitcoin block header parsing and header-level rules.
+//!
+//! A header is 80 bytes: version (i32 LE), previous block hash, Merkle root,
+//! timestamp (u32 LE), compact target `bits` (u32 LE) and nonce (u32 LE). Hashes
+//! are kept in internal byte order, i.e. reversed relative to how explorers print them.
+
+use crate::ValidationError;
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Serialized header length
+pub const HEADER_SIZE: usize = 80;
+
+/// How far ahead of the validator's clock a header timestamp may be
+pub const MAX_FUTURE_BLOCK_TIME_SECS: u64 = 2 * 60 * 60;
+
+/// Parsed block header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub version: i32,
+    pub prev_block_hash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub timestamp: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+/// Chain state a block is validated against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockContext {
+    /// Hash of the current tip (internal byte order); the block must build on it
+    pub expected_prev_hash: Option<[u8; 32]>,
+    /// Unix time used for the future-timestamp rule
+    pub now: u64,
+}
+
+impl Default for BlockContext {
+    fn default() -> Self {
+        Self {
+            expected_prev_hash: None,
+            now: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        }
+    }
+}
+
+impl BlockContext {
+    /// Require the block to extend `tip` (internal byte order)
+    pub fn with_expected_tip(mut self, tip: [u8; 32]) -> Self {
+        self.expected_prev_hash = Some(tip);
+        self
+    }
+
+    /// Evaluate timestamps against `now` instead of the system clock
+    pub fn at_time(mut self, now: u64) -> Self {
+        self.now = now;
+        self
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+impl BlockHeader {
+    /// Parse the header at the start of `block`
+    pub fn parse(block: &[u8]) -> Result<Self, ValidationError> {
+        if block.len() < HEADER_SIZE {
+            return Err(ValidationError::InvalidBlock(format!(
+                "Header truncated at offset {}: need {} bytes",
+                block.len(),
+                HEADER_SIZE
+            )));
+        }
+        let mut prev_block_hash = [0u8; 32];
+        prev_block_hash.copy_from_slice(&block[4..36]);
+        let mut merkle_root = [0u8; 32];
+        merkle_root.copy_from_slice(&block[36..68]);
+        Ok(Self {
+            version: read_u32(block, 0) as i32,
+            prev_block_hash,
+            merkle_root,
+            timestamp: read_u32(block, 68),
+            bits: read_u32(block, 72),
+            nonce: read_u32(block, 76),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut out = [0u8; HEADER_SIZE];
+        out[0..4].copy_from_slice(&self.version.to_le_bytes());
+        out[4..36].copy_from_slice(&self.prev_block_hash);
+        out[36..68].copy_from_slice(&self.merkle_root);
+        out[68..72].copy_from_slice(&self.timestamp.to_le_bytes());
+        out[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        out[76..80].copy_from_slice(&self.nonce.to_le_bytes());
+        out
+    }
+
+    /// Double-SHA256 of the header (internal byte order)
+    pub fn block_hash(&self) -> [u8; 32] {
+        Sha256::digest(Sha256::digest(self.to_bytes())).into()
+    }
+
+    /// Block hash as explorers print it
+    pub fn block_hash_hex(&self) -> String {
+        let mut hash = self.block_hash();
+        hash.reverse();
+        hex::encode(hash)
+    }
+
+    /// Check that the header hash does not exceed the target encoded in `bits`
+    pub fn check_proof_of_work(&self) -> Result<(), ValidationError> {
+        let target = expand_target(self.bits)?;
+        let mut hash = self.block_hash();
+        hash.reverse();
+        if hash > target {
+            return Err(ValidationError::InvalidBlock(format!(
+                "Header hash {} is above target for bits {:08x}",
+                hex::encode(hash),
+                self.bits
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reject timestamps more than `MAX_FUTURE_BLOCK_TIME_SECS` ahead of `now`
+    pub fn check_timestamp(&self, now: u64) -> Result<(), ValidationError> {
+        let limit = now.saturating_add(MAX_FUTURE_BLOCK_TIME_SECS);
+        if u64::from(self.timestamp) > limit {
+            return Err(ValidationError::InvalidBlock(format!(
+                "Header timestamp {} at offset 68 is more than {}s in the future",
+                self.timestamp, MAX_FUTURE_BLOCK_TIME_SECS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that the header extends `expected` (internal byte order)
+    pub fn check_prev_hash(&self, expected: &[u8; 32]) -> Result<(), ValidationError> {
+        if self.prev_block_hash != *expected {
+            let (mut got, mut want) = (self.prev_block_hash, *expected);
+            got.reverse();
+            want.reverse();
+            return Err(ValidationError::InvalidBlock(format!(
+                "prev_block_hash at offset 4 is {}, expected tip {}",
+                hex::encode(got),
+                hex::encode(want)
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Expand compact `bits` into a 256-bit big-endian target
+pub fn expand_target(bits: u32) -> Result<[u8; 32], ValidationError> {
+    let invalid = |reason: &str| ValidationError::InvalidBlock(format!("Invalid bits {:08x} at offset 72: {}", bits, reason));
+    let exponent = (bits >> 24) as usize;
+    let mantissa = bits & 0x007f_ffff;
+    if bits & 0x0080_0000 != 0 && mantissa != 0 {
+        return Err(invalid("negative target"));
+    }
+    let mut target = [0u8; 32];
+    for (i, byte) in mantissa.to_be_bytes()[1..].iter().enumerate() {
+        // Byte i of the mantissa lands at big-endian position 32 - exponent + i;
+        // positions past the end are shifted out, positions before the start overflow
+        let position = 32 + i;
+        if position < exponent {
+            if *byte != 0 {
+                return Err(invalid("target overflows 256 bits"));
+            }
+            continue;
+        }
+        if let Some(slot) = target.get_mut(position - exponent) {
+            *slot = *byte;
+        }
+    }
+    if target == [0u8; 32] {
+        return Err(invalid("zero target"));
+    }
+    Ok(target)
+}
+
+/// Mainnet headers with valid proof of work
+#[cfg(test)]
+pub(crate) mod fixtures {
+    pub const GENESIS_HEADER: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+    pub const BLOCK_1_HEADER: &str = "010000006fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e61bc6649ffff001d01e36299";
+
+    pub fn genesis_header() -> Vec<u8> {
+        hex::decode(GENESIS_HEADER).unwrap()
+    }
+
+    pub fn block_1_header() -> Vec<u8> {
+        hex::decode(BLOCK_1_HEADER).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_target() {
+        let genesis = expand_target(0x1d00ffff).unwrap();
+        assert_eq!(hex::encode(genesis), format!("00000000ffff{}", "0".repeat(52)));
+        let small = expand_target(0x03123456).unwrap();
+        assert_eq!(&small[29..], &[0x12, 0x34, 0x56]);
+        let shifted = expand_target(0x02123456).unwrap();
+        assert_eq!(&shifted[30..], &[0x12, 0x34]);
+
+        assert!(expand_target(0x1d800001).is_err());
+        assert!(expand_target(0x1d000000).is_err());
+        assert!(expand_target(0x23000001).is_err());
+        assert!(expand_target(0x2200ffff).is_err());
+        assert!(expand_target(0x21007fff).is_ok());
+    }
+
+    #[test]
+    fn test_mainnet_headers_parse_and_hash() {
+        let genesis = BlockHeader::parse(&fixtures::genesis_header()).unwrap();
+        assert_eq!(genesis.block_hash_hex(), "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+        assert_eq!((genesis.version, genesis.timestamp, genesis.bits, genesis.nonce), (1, 1231006505, 0x1d00ffff, 2083236893));
+        assert_eq!(genesis.to_bytes().to_vec(), fixtures::genesis_header());
+
+        let block_1 = BlockHeader::parse(&fixtures::block_1_header()).unwrap();
+        assert_eq!(block_1.block_hash_hex(), "00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048");
+        assert!(block_1.check_prev_hash(&genesis.block_hash()).is_ok());
+        assert!(block_1.check_proof_of_work().is_ok());
+    }
+
+    #[test]
+    fn test_short_header_reports_offset() {
+        let err = BlockHeader::parse(&[0u8; 79]).unwrap_err();
+        assert!(matches!(&err, ValidationError::InvalidBlock(msg) if msg.contains("offset 79")), "{}", err);
+    }
+}
@@ -1,10 +1,41 @@
 //! TurboValidator: High-performance block/transaction validator for Bitcoin Sprint
 //! Extend with custom rules, cryptographic checks, and anti-fraud logic as needed.
 
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use serde::{Deserialize, Serialize};
-use serde_json;
 use std::error::Error;
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub mod ffi;
+pub mod chain;
+pub mod header;
+pub mod metrics;
+pub mod network;
+pub mod pqc;
+pub mod receipt;
+pub mod report;
+pub mod sighash;
+pub mod signature;
+pub mod spent;
+pub mod tx;
+pub use chain::{verify_chain, ChainError, ReceiptChain};
+pub use header::{BlockContext, BlockHeader};
+pub use metrics::{NoopMetrics, ValidationKind, ValidationOutcome, ValidatorMetrics};
+pub use network::NetworkProfile;
+pub use pqc::{PqcEnvelope, PqcKeyring};
+pub use receipt::SignedReceipt;
+pub use report::{CheckResult, PqcStatus, PqcSummary, ValidationReport};
+#[cfg(feature = "secp256k1")]
+pub use signature::LibsecpVerifier;
+pub use signature::{NoopVerifier, PrevOut, ScriptType, SignatureVerifier};
+pub use spent::{InMemorySpentIndex, MaybeSpentPolicy, OutPoint, SpentLookup, SpentOutpointIndex};
+pub use tx::Transaction;
+
+use report::ReportBuilder;
 
 /// Validation errors for blocks/transactions
 #[derive(Debug)]
@@ -30,6 +61,31 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 
 impl Error for ValidationError {}
 
+impl ValidationError {
+    /// Variant name, used as the outcome label in metrics
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            ValidationError::InvalidBlock(_) => "InvalidBlock",
+            ValidationError::InvalidTransaction(_) => "InvalidTransaction",
+            ValidationError::SignatureError(_) => "SignatureError",
+            ValidationError::DoubleSpend(_) => "DoubleSpend",
+            ValidationError::Other(_) => "Other",
+        }
+    }
+
+    /// Same error with `prefix: ` in front of the message
+    pub(crate) fn with_prefix(self, prefix: &str) -> Self {
+        let tag = |msg: String| format!("{}: {}", prefix, msg);
+        match self {
+            ValidationError::InvalidBlock(msg) => ValidationError::InvalidBlock(tag(msg)),
+            ValidationError::InvalidTransaction(msg) => ValidationError::InvalidTransaction(tag(msg)),
+            ValidationError::SignatureError(msg) => ValidationError::SignatureError(tag(msg)),
+            ValidationError::DoubleSpend(msg) => ValidationError::DoubleSpend(tag(msg)),
+            ValidationError::Other(msg) => ValidationError::Other(tag(msg)),
+        }
+    }
+}
+
 /// Policy for PQC mix-in weighting and controls
 #[derive(Debug, Clone)]
 pub struct PQCPolicy {
@@ -51,47 +107,415 @@ fn default() -> Self {
     }
 }
 
-/// TurboValidator struct: stateless, thread-safe, with PQC policy
+/// Batch execution settings and the network whose payloads are validated
 #[derive(Debug, Clone)]
+pub struct ValidatorConfig {
+    /// Worker threads for batch validation (0 = shared rayon pool)
+    pub parallelism: usize,
+    /// Minimum number of items handed to a worker at once
+    pub batch_size: usize,
+    /// Structural checks and PQC mix-ins applied by `validate_block`
+    pub network: NetworkProfile,
+}
+
+impl Default for ValidatorConfig {
+    fn default() -> Self {
+        Self {
+            parallelism: 0,
+            batch_size: 64,
+            network: NetworkProfile::default(),
+        }
+    }
+}
+
+/// TurboValidator struct: thread-safe, with PQC policy and optional spent-outpoint index
+#[derive(Clone)]
 pub struct TurboValidator {
     pub pqc_policy: PQCPolicy,
+    /// Keys used to verify PQC envelopes
+    pub pqc_keys: PqcKeyring,
+    /// How to treat probabilistic spent-outpoint hits
+    pub maybe_spent_policy: MaybeSpentPolicy,
+    config: ValidatorConfig,
+    pool: Option<Arc<ThreadPool>>,
+    spent_index: Option<Arc<dyn SpentOutpointIndex>>,
+    signature_verifier: Arc<dyn SignatureVerifier>,
+    metrics: Arc<dyn ValidatorMetrics>,
 }
 
 impl Default for TurboValidator {
     fn default() -> Self {
         Self {
             pqc_policy: PQCPolicy::default(),
+            pqc_keys: PqcKeyring::default(),
+            maybe_spent_policy: MaybeSpentPolicy::default(),
+            config: ValidatorConfig::default(),
+            pool: None,
+            spent_index: None,
+            signature_verifier: Arc::new(NoopVerifier),
+            metrics: Arc::new(NoopMetrics),
         }
     }
 }
 
+impl fmt::Debug for TurboValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TurboValidator")
+            .field("pqc_policy", &self.pqc_policy)
+            .field("pqc_keys", &self.pqc_keys)
+            .field("maybe_spent_policy", &self.maybe_spent_policy)
+            .field("config", &self.config)
+            .field("spent_index", &self.spent_index.is_some())
+            .finish()
+    }
+}
+
 impl TurboValidator {
-    /// Validate a block (stub: extend with real logic)
+    /// Create a validator with the given PQC policy and verification keys
+    pub fn with_pqc(pqc_policy: PQCPolicy, pqc_keys: PqcKeyring) -> Self {
+        Self {
+            pqc_policy,
+            pqc_keys,
+            ..Self::default()
+        }
+    }
+
+    /// Apply batch execution settings, building a dedicated pool if parallelism is set
+    pub fn with_config(mut self, config: ValidatorConfig) -> Result<Self, ValidationError> {
+        self.pool = if config.parallelism > 0 {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(config.parallelism)
+                .thread_name(|i| format!("turbo-validator-{}", i))
+                .build()
+                .map_err(|e| ValidationError::Other(format!("Failed to build validator pool: {}", e)))?;
+            Some(Arc::new(pool))
+        } else {
+            None
+        };
+        self.config = config;
+        Ok(self)
+    }
+
+    /// Attach a spent-outpoint index for double-spend detection
+    pub fn with_spent_index(mut self, index: Arc<dyn SpentOutpointIndex>, policy: MaybeSpentPolicy) -> Self {
+        self.spent_index = Some(index);
+        self.maybe_spent_policy = policy;
+        self
+    }
+
+    /// Delegate input signature checks in `validate_transaction_with_prevouts`
+    pub fn with_signature_verifier(mut self, verifier: Arc<dyn SignatureVerifier>) -> Self {
+        self.signature_verifier = verifier;
+        self
+    }
+
+    /// Report the outcome and duration of every block and transaction validation to `metrics`.
+    /// Batches report each item they validate; items a fail-fast batch skips are not reported.
+    pub fn with_metrics(mut self, metrics: Arc<dyn ValidatorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Current batch execution settings
+    pub fn config(&self) -> &ValidatorConfig {
+        &self.config
+    }
+
+    /// Validate many blocks in parallel; results keep input order
+    pub fn validate_blocks_batch(&self, blocks: &[&[u8]], fail_fast: bool) -> Vec<Result<(), ValidationError>> {
+        self.run_batch(blocks, fail_fast, |block| self.validate_block(block))
+    }
+
+    /// Validate many transactions in parallel; results keep input order
+    pub fn validate_transactions_batch(&self, txs: &[&[u8]], fail_fast: bool) -> Vec<Result<(), ValidationError>> {
+        self.run_batch(txs, fail_fast, |tx| self.validate_transaction(tx))
+    }
+
+    /// Fan items out over the configured pool. With `fail_fast`, items not yet
+    /// started when the first failure is seen are reported as skipped.
+    fn run_batch<F>(&self, items: &[&[u8]], fail_fast: bool, validate: F) -> Vec<Result<(), ValidationError>>
+    where
+        F: Fn(&[u8]) -> Result<(), ValidationError> + Sync,
+    {
+        let aborted = AtomicBool::new(false);
+        let run = || {
+            items
+                .par_iter()
+                .with_min_len(self.config.batch_size.max(1))
+                .map(|item| {
+                    if fail_fast && aborted.load(Ordering::Relaxed) {
+                        return Err(ValidationError::Other("Skipped after earlier failure in batch".into()));
+                    }
+                    let result = validate(item);
+                    if fail_fast && result.is_err() {
+                        aborted.store(true, Ordering::Relaxed);
+                    }
+                    result
+                })
+                .collect()
+        };
+        match &self.pool {
+            Some(pool) => pool.install(run),
+            None => run(),
+        }
+    }
+
+    /// Validate a block, taking the PQC envelope from the block trailer if present
     pub fn validate_block(&self, block: &[u8]) -> Result<(), ValidationError> {
-        if block.is_empty() {
-            return Err(ValidationError::InvalidBlock("Block data is empty".into()));
+        self.validate_block_report(block).map(|_| ())
+    }
+
+    /// Validate a block against chain state, e.g. requiring it to extend a known tip.
+    /// Only the Bitcoin profile consults `ctx`.
+    pub fn validate_block_with_context(&self, block: &[u8], ctx: &BlockContext) -> Result<(), ValidationError> {
+        self.validate_block_report_with_context(block, ctx).map(|_| ())
+    }
+
+    /// Validate raw block bytes with a separately supplied PQC envelope
+    pub fn validate_block_with_envelope(
+        &self,
+        block: &[u8],
+        envelope: Option<&PqcEnvelope>,
+    ) -> Result<(), ValidationError> {
+        self.validate_block_with_envelope_report(block, envelope).map(|_| ())
+    }
+
+    /// Like `validate_block`, returning which checks ran and how long each took
+    pub fn validate_block_report(&self, block: &[u8]) -> Result<ValidationReport, ValidationError> {
+        self.validate_block_report_with_context(block, &BlockContext::default())
+    }
+
+    /// Like `validate_block_with_context`, returning which checks ran and how long each took
+    pub fn validate_block_report_with_context(
+        &self,
+        block: &[u8],
+        ctx: &BlockContext,
+    ) -> Result<ValidationReport, ValidationError> {
+        self.observe(ValidationKind::Block, || {
+            let mut report = ReportBuilder::new(self.config.network.name());
+            // The trailer is always stripped so it is never parsed as block body;
+            // with PQC off the envelope itself is ignored, malformed or not
+            let (payload, envelope) = if self.pqc_required() {
+                report.run("envelope_trailer", None, || PqcEnvelope::split_appended(block))?
+            } else {
+                (PqcEnvelope::split_appended(block).map_or(block, |(payload, _)| payload), None)
+            };
+            self.check_block(&mut report, payload, envelope.as_ref(), ctx)?;
+            Ok(report.finish(self.pqc_summary(envelope.is_some())))
+        })
+    }
+
+    /// Like `validate_block_with_envelope`, returning which checks ran and how long each took
+    pub fn validate_block_with_envelope_report(
+        &self,
+        block: &[u8],
+        envelope: Option<&PqcEnvelope>,
+    ) -> Result<ValidationReport, ValidationError> {
+        self.observe(ValidationKind::Block, || {
+            let mut report = ReportBuilder::new(self.config.network.name());
+            self.check_block(&mut report, block, envelope, &BlockContext::default())?;
+            Ok(report.finish(self.pqc_summary(envelope.is_some())))
+        })
+    }
+
+    /// Run one validation and report it to the metrics sink
+    fn observe<T>(
+        &self,
+        kind: ValidationKind,
+        validate: impl FnOnce() -> Result<T, ValidationError>,
+    ) -> Result<T, ValidationError> {
+        let started = Instant::now();
+        let result = validate();
+        self.metrics.record_validation(kind, ValidationOutcome::of(&result), started.elapsed());
+        result
+    }
+
+    fn check_block(
+        &self,
+        report: &mut ReportBuilder,
+        block: &[u8],
+        envelope: Option<&PqcEnvelope>,
+        ctx: &BlockContext,
+    ) -> Result<(), ValidationError> {
+        report.run("non_empty", Some(format!("{} bytes", block.len())), || {
+            if block.is_empty() {
+                return Err(ValidationError::InvalidBlock("Block data is empty".into()));
+            }
+            Ok(())
+        })?;
+        match self.config.network {
+            NetworkProfile::Bitcoin => self.check_bitcoin_structure(report, block, ctx)?,
+            NetworkProfile::Ethereum => {
+                let bounds = format!("{} to {} items", network::ETHEREUM_BLOCK_MIN_ITEMS, network::ETHEREUM_BLOCK_MAX_ITEMS);
+                report.run("rlp_envelope", Some(bounds), || network::check_rlp_block(block).map(|_| ()))?;
+            }
+            NetworkProfile::Solana => {
+                report.run("shred_size", Some(format!("{} bytes", block.len())), || network::check_shred_size(block))?;
+            }
+            NetworkProfile::Custom { min_size, max_size, magic } => {
+                report.run("payload_size", Some(format!("{} to {} bytes", min_size, max_size)), || {
+                    network::check_payload_size(block, min_size, max_size)
+                })?;
+                if let Some(magic) = magic {
+                    report.run("magic_prefix", Some(hex::encode(magic)), || network::check_magic(block, &magic))?;
+                }
+            }
         }
-        // PQC mix-in: simulate Kyber/Dilithium checks
-        if self.pqc_policy.kyber_enabled {
-            // TODO: Call Kyber verification (stub)
+        if !self.pqc_required() {
+            return Ok(());
         }
-        if self.pqc_policy.dilithium_enabled {
-            // TODO: Call Dilithium verification (stub)
+        let envelope = report.run("envelope_present", None, || {
+            envelope.ok_or_else(|| {
+                ValidationError::SignatureError("PQC policy requires an envelope but none was provided".into())
+            })
+        })?;
+        if self.kyber_required() {
+            report.run("kyber_commitment", Some("kyber768".into()), || {
+                pqc::verify_kyber(&self.pqc_keys, envelope, block)
+            })?;
+        }
+        if self.dilithium_required() {
+            report.run("dilithium_signature", Some("dilithium3".into()), || {
+                pqc::verify_dilithium(&self.pqc_keys, envelope, block)
+            })?;
         }
         Ok(())
     }
 
-    /// Validate a transaction (stub: extend with real logic)
+    /// Header rules, then the Merkle root when transactions follow the header
+    fn check_bitcoin_structure(
+        &self,
+        report: &mut ReportBuilder,
+        block: &[u8],
+        ctx: &BlockContext,
+    ) -> Result<(), ValidationError> {
+        // Cheap header rules first; proof of work needs two hashes
+        let header = report.run("header_format", None, || BlockHeader::parse(block))?;
+        report.run("timestamp", Some(header.timestamp.to_string()), || header.check_timestamp(ctx.now))?;
+        if let Some(tip) = &ctx.expected_prev_hash {
+            report.run("prev_block_hash", None, || header.check_prev_hash(tip))?;
+        }
+        report.run("proof_of_work", Some(format!("bits {:08x}", header.bits)), || header.check_proof_of_work())?;
+        // A bare header carries no body to check
+        if block.len() > header::HEADER_SIZE {
+            report.run("merkle_root", Some(format!("{} body bytes", block.len() - header::HEADER_SIZE)), || {
+                tx::check_merkle_root(&header, &tx::parse_block_transactions(block)?)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// PQC outcome of a block that passed every check
+    fn pqc_summary(&self, envelope_present: bool) -> PqcSummary {
+        let status = |enabled, applies| match (enabled, applies) {
+            (true, true) => PqcStatus::Verified,
+            (true, false) => PqcStatus::SkippedByNetwork,
+            (false, _) => PqcStatus::SkippedByPolicy,
+        };
+        let network = &self.config.network;
+        PqcSummary {
+            kyber: status(self.pqc_policy.kyber_enabled, network.kyber_applies()),
+            dilithium: status(self.pqc_policy.dilithium_enabled, network.dilithium_applies()),
+            envelope_present,
+        }
+    }
+
+    fn kyber_required(&self) -> bool {
+        self.pqc_policy.kyber_enabled && self.config.network.kyber_applies()
+    }
+
+    fn dilithium_required(&self) -> bool {
+        self.pqc_policy.dilithium_enabled && self.config.network.dilithium_applies()
+    }
+
+    fn pqc_required(&self) -> bool {
+        self.kyber_required() || self.dilithium_required()
+    }
+
+    /// Validate a transaction. Empty input is always rejected. With a spent-outpoint
+    /// index configured the inputs are parsed (so undecodable bytes are rejected) and
+    /// checked for double spends; without one no further checks run. Input signatures
+    /// are verified by `validate_transaction_with_prevouts`, and PQC envelopes are
+    /// verified per block by `validate_block_with_envelope`, not per transaction.
     pub fn validate_transaction(&self, tx: &[u8]) -> Result<(), ValidationError> {
+        self.observe(ValidationKind::Tx, || self.check_transaction(tx))
+    }
+
+    fn check_transaction(&self, tx: &[u8]) -> Result<(), ValidationError> {
         if tx.is_empty() {
             return Err(ValidationError::InvalidTransaction("Transaction data is empty".into()));
         }
-        // PQC mix-in: simulate Kyber/Dilithium checks
-        if self.pqc_policy.kyber_enabled {
-            // TODO: Call Kyber verification (stub)
+        if let Some(index) = &self.spent_index {
+            self.check_double_spend(index.as_ref(), tx)?;
+        }
+        Ok(())
+    }
+
+    /// Validate a transaction and verify each input's signature against the output it
+    /// spends. `prevouts` must list the spent outputs in input order.
+    pub fn validate_transaction_with_prevouts(&self, tx: &[u8], prevouts: &[PrevOut]) -> Result<(), ValidationError> {
+        self.observe(ValidationKind::Tx, || self.check_transaction_with_prevouts(tx, prevouts))
+    }
+
+    fn check_transaction_with_prevouts(&self, tx: &[u8], prevouts: &[PrevOut]) -> Result<(), ValidationError> {
+        self.check_transaction(tx)?;
+        let input_count = Transaction::parse(tx)?.inputs.len();
+        if prevouts.len() != input_count {
+            return Err(ValidationError::InvalidTransaction(format!(
+                "{} prevouts supplied for {} inputs",
+                prevouts.len(),
+                input_count
+            )));
+        }
+        for (index, prevout) in prevouts.iter().enumerate() {
+            self.signature_verifier
+                .verify_input(tx, index, &prevout.script_pubkey, prevout.value)
+                .map_err(|e| {
+                    let msg = match e {
+                        ValidationError::SignatureError(msg) => msg,
+                        other => other.to_string(),
+                    };
+                    ValidationError::SignatureError(format!("input {}: {}", index, msg))
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Record the inputs of accepted transactions as spent. Returns the number of outpoints marked.
+    pub fn mark_spent(&self, txs: &[&[u8]]) -> Result<usize, ValidationError> {
+        let index = self
+            .spent_index
+            .as_ref()
+            .ok_or_else(|| ValidationError::Other("No spent-outpoint index configured".into()))?;
+        let mut outpoints = Vec::new();
+        for tx in txs {
+            outpoints.extend(spent::parse_tx_inputs(tx)?);
         }
-        if self.pqc_policy.dilithium_enabled {
-            // TODO: Call Dilithium verification (stub)
+        index.mark_spent(&outpoints)?;
+        Ok(outpoints.len())
+    }
+
+    fn check_double_spend(&self, index: &dyn SpentOutpointIndex, tx: &[u8]) -> Result<(), ValidationError> {
+        let inputs = spent::parse_tx_inputs(tx)?;
+        let mut seen = std::collections::HashSet::with_capacity(inputs.len());
+        for outpoint in &inputs {
+            if !seen.insert(outpoint) {
+                return Err(ValidationError::DoubleSpend(format!("{} spent twice in one transaction", outpoint)));
+            }
+            match index.lookup(outpoint)? {
+                SpentLookup::Unspent => {}
+                SpentLookup::Spent => {
+                    return Err(ValidationError::DoubleSpend(format!("{} already spent", outpoint)));
+                }
+                SpentLookup::MaybeSpent => match self.maybe_spent_policy {
+                    MaybeSpentPolicy::Reject => {
+                        return Err(ValidationError::DoubleSpend(format!("{} possibly already spent", outpoint)));
+                    }
+                    MaybeSpentPolicy::Warn => {
+                        log::warn!("Outpoint {} possibly already spent; accepting per policy", outpoint);
+                    }
+                },
+            }
         }
         Ok(())
     }
@@ -120,6 +544,8 @@ pub fn generate_entropy_hybrid_receipt(
             proof_hash: proof_hash.to_string(),
             verifier_id: verifier_id.to_string(),
             pqc_weight: self.entropy_pqc_weight(),
+            prev_hash: None,
+            self_hash: None,
         }
     }
 
@@ -127,16 +553,37 @@ pub fn generate_entropy_hybrid_receipt(
     pub fn serialize_receipt_json(receipt: &EntropyHybridReceipt) -> Result<String, serde_json::Error> {
         serde_json::to_string(receipt)
     }
+
+    /// Parse a receipt previously produced by `serialize_receipt_json`
+    pub fn deserialize_receipt_json(json: &str) -> Result<EntropyHybridReceipt, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Sign a receipt with HMAC-SHA256; fails for empty keys or non-finite pqc_weight
+    pub fn sign_receipt(&self, receipt: &EntropyHybridReceipt, key: &[u8]) -> Result<SignedReceipt, ValidationError> {
+        receipt::sign(receipt, key)
+    }
+
+    /// Verify a signed receipt, rejecting any modified field
+    pub fn verify_receipt(signed: &SignedReceipt, key: &[u8]) -> Result<(), ValidationError> {
+        receipt::verify(signed, key)
+    }
 }
 
 /// Receipt + proof bundle for /entropy/hybrid
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EntropyHybridReceipt {
     pub beacon_round: u64,
     pub attestation: String,
     pub proof_hash: String,
     pub verifier_id: String,
     pub pqc_weight: f64,
+    /// `self_hash` of the previous receipt, set by `ReceiptChain`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    /// Hex SHA-256 over this receipt without `self_hash`, set by `ReceiptChain`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub self_hash: Option<String>,
 }
 
 #[cfg(test)]
@@ -154,12 +601,57 @@ fn test_receipt_json() {
         let json = TurboValidator::serialize_receipt_json(&receipt).unwrap();
         assert!(json.contains("beacon_round"));
         assert!(json.contains("verifierX"));
+        let parsed = TurboValidator::deserialize_receipt_json(&json).unwrap();
+        assert_eq!(parsed, receipt);
+    }
+
+    #[test]
+    fn test_signed_receipt_roundtrip() {
+        let validator = TurboValidator::default();
+        let receipt = validator.generate_entropy_hybrid_receipt(42, "attest", "proofhash", "verifierX");
+        let signed = validator.sign_receipt(&receipt, b"receipt-key").unwrap();
+        let json = signed.to_json().unwrap();
+        assert!(json.contains("\"signature\""));
+        let parsed = SignedReceipt::from_json(&json).unwrap();
+        assert!(TurboValidator::verify_receipt(&parsed, b"receipt-key").is_ok());
+        assert!(TurboValidator::verify_receipt(&parsed, b"other-key").is_err());
+    }
+
+    #[test]
+    fn test_signed_receipt_tampering() {
+        let validator = TurboValidator::default();
+        let receipt = validator.generate_entropy_hybrid_receipt(42, "attest", "proofhash", "verifierX");
+        let signed = validator.sign_receipt(&receipt, b"receipt-key").unwrap();
+
+        let mut tampered = signed.clone();
+        tampered.receipt.beacon_round = 43;
+        assert!(TurboValidator::verify_receipt(&tampered, b"receipt-key").is_err());
+
+        let mut tampered = signed.clone();
+        tampered.receipt.proof_hash = "proofhasH".into();
+        assert!(TurboValidator::verify_receipt(&tampered, b"receipt-key").is_err());
+
+        let mut tampered = signed;
+        tampered.receipt.pqc_weight = 0.51;
+        assert!(TurboValidator::verify_receipt(&tampered, b"receipt-key").is_err());
+    }
+
+    #[test]
+    fn test_nan_weight_cannot_be_signed() {
+        let mut validator = TurboValidator::default();
+        validator.set_pqc_policy(PQCPolicy { entropy_pqc_weight: f64::NAN, ..PQCPolicy::default() });
+        let receipt = validator.generate_entropy_hybrid_receipt(1, "a", "p", "v");
+        assert!(matches!(
+            validator.sign_receipt(&receipt, b"receipt-key"),
+            Err(ValidationError::SignatureError(_))
+        ));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use header::fixtures::{block_1_header, genesis_header};
 
     #[test]
     fn test_empty_block() {
@@ -167,9 +659,523 @@ fn test_empty_block() {
         assert!(validator.validate_block(&[]).is_err());
     }
 
+    #[test]
+    fn test_missing_envelope_rejected() {
+        let validator = TurboValidator::default();
+        assert!(matches!(
+            validator.validate_block(&genesis_header()),
+            Err(ValidationError::SignatureError(_))
+        ));
+    }
+
+    #[test]
+    fn test_disabled_policy_ignores_envelope() {
+        let policy = PQCPolicy { kyber_enabled: false, dilithium_enabled: false, ..PQCPolicy::default() };
+        let validator = TurboValidator::with_pqc(policy, PqcKeyring::default());
+        let bogus = PqcEnvelope {
+            kyber_ciphertext: vec![1; 8],
+            shared_secret_commitment: vec![2; 32],
+            dilithium_signature: vec![3; 16],
+        };
+        let block = genesis_header();
+        assert!(validator.validate_block(&block).is_ok());
+        assert!(validator.validate_block_with_envelope(&block, Some(&bogus)).is_ok());
+        assert!(validator.validate_block(&bogus.append_to(&block)).is_ok());
+    }
+
+    #[test]
+    fn test_report_for_disabled_policy() {
+        let policy = PQCPolicy { kyber_enabled: false, dilithium_enabled: false, ..PQCPolicy::default() };
+        let validator = TurboValidator::with_pqc(policy, PqcKeyring::default());
+        let block = genesis_header();
+        let report = validator.validate_block_report(&block).unwrap();
+        assert_eq!(report.check_names(), vec!["non_empty", "header_format", "timestamp", "proof_of_work"]);
+        assert_eq!(report.checks[0].detail.as_deref(), Some("80 bytes"));
+        assert_eq!(
+            report.pqc,
+            PqcSummary { kyber: PqcStatus::SkippedByPolicy, dilithium: PqcStatus::SkippedByPolicy, envelope_present: false }
+        );
+
+        let bogus = PqcEnvelope {
+            kyber_ciphertext: vec![1; 8],
+            shared_secret_commitment: vec![2; 32],
+            dilithium_signature: vec![3; 16],
+        };
+        let report = validator.validate_block_with_envelope_report(&block, Some(&bogus)).unwrap();
+        assert_eq!(report.check_names(), vec!["non_empty", "header_format", "timestamp", "proof_of_work"]);
+        assert!(report.pqc.envelope_present);
+        assert!(matches!(validator.validate_block_report(&[]), Err(ValidationError::InvalidBlock(_))));
+    }
+
+    #[test]
+    fn test_report_json_order_and_hash() {
+        let policy = PQCPolicy { kyber_enabled: false, dilithium_enabled: false, ..PQCPolicy::default() };
+        let validator = TurboValidator::with_pqc(policy, PqcKeyring::default());
+        let mut report = validator.validate_block_report(&genesis_header()).unwrap();
+        report.checks.iter_mut().for_each(|c| c.duration_us = 3);
+        report.total_duration_us = 7;
+
+        assert_eq!(
+            report.to_json().unwrap(),
+            concat!(
+                r#"{"checks":[{"name":"non_empty","passed":true,"duration_us":3,"detail":"80 bytes"},"#,
+                r#"{"name":"header_format","passed":true,"duration_us":3,"detail":null},"#,
+                r#"{"name":"timestamp","passed":true,"duration_us":3,"detail":"1231006505"},"#,
+                r#"{"name":"proof_of_work","passed":true,"duration_us":3,"detail":"bits 1d00ffff"}],"#,
+                r#""pqc":{"kyber":"skipped_by_policy","dilithium":"skipped_by_policy","envelope_present":false},"#,
+                r#""total_duration_us":7}"#
+            )
+        );
+        let hash = report.report_hash().unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(report.clone().report_hash().unwrap(), hash);
+        report.total_duration_us = 8;
+        assert_ne!(report.report_hash().unwrap(), hash);
+
+        let receipt = validator.generate_entropy_hybrid_receipt(1, "attest", &hash, "verifierX");
+        assert_eq!(receipt.proof_hash, hash);
+    }
+
+    #[test]
+    fn test_block_context_tip_and_header_mutations() {
+        let policy = PQCPolicy { kyber_enabled: false, dilithium_enabled: false, ..PQCPolicy::default() };
+        let validator = TurboValidator::with_pqc(policy, PqcKeyring::default());
+        let genesis = BlockHeader::parse(&genesis_header()).unwrap();
+        let block_1 = block_1_header();
+        let ctx = BlockContext::default().with_expected_tip(genesis.block_hash());
+
+        let report = validator.validate_block_report_with_context(&block_1, &ctx).unwrap();
+        assert_eq!(
+            report.check_names(),
+            vec!["non_empty", "header_format", "timestamp", "prev_block_hash", "proof_of_work"]
+        );
+        let invalid_block = |block: &[u8], ctx: &BlockContext, needle: &str| {
+            match validator.validate_block_with_context(block, ctx) {
+                Err(ValidationError::InvalidBlock(msg)) => assert!(msg.contains(needle), "{}", msg),
+                other => panic!("expected InvalidBlock containing {:?}, got {:?}", needle, other),
+            }
+        };
+
+        let mut bad_nonce = block_1.clone();
+        bad_nonce[79] ^= 0x01;
+        invalid_block(&bad_nonce, &ctx, "above target");
+
+        let mut bad_prev = block_1.clone();
+        bad_prev[4] ^= 0x01;
+        invalid_block(&bad_prev, &ctx, "prev_block_hash at offset 4");
+        invalid_block(&block_1, &BlockContext::default().with_expected_tip([7u8; 32]), "expected tip");
+
+        let mut future = BlockHeader::parse(&block_1).unwrap();
+        future.timestamp = (ctx.now + header::MAX_FUTURE_BLOCK_TIME_SECS + 60) as u32;
+        invalid_block(&future.to_bytes(), &ctx, "in the future");
+        let before_block_1 = BlockContext::default().at_time(1231469665 - 3 * 3600);
+        invalid_block(&block_1, &before_block_1, "in the future");
+
+        invalid_block(&block_1[..40], &ctx, "truncated at offset 40");
+    }
+
+    #[test]
+    fn test_block_body_must_match_merkle_root() {
+        let policy = PQCPolicy { kyber_enabled: false, dilithium_enabled: false, ..PQCPolicy::default() };
+        let validator = TurboValidator::with_pqc(policy, PqcKeyring::default());
+        let block = tx::fixtures::block_170();
+        let report = validator.validate_block_report(&block).unwrap();
+        assert_eq!(
+            report.check_names(),
+            vec!["non_empty", "header_format", "timestamp", "proof_of_work", "merkle_root"]
+        );
+        assert_eq!(report.checks[4].detail.as_deref(), Some("410 body bytes"));
+        assert!(validator.validate_block(&tx::fixtures::genesis_block()).is_ok());
+
+        let mut mutated = block.clone();
+        mutated[block.len() - 150] ^= 0x01;
+        match validator.validate_block(&mutated) {
+            Err(ValidationError::InvalidBlock(msg)) => assert!(msg.contains("does not match header merkle_root"), "{}", msg),
+            other => panic!("expected a Merkle mismatch, got {:?}", other),
+        }
+        let mut truncated = block.clone();
+        truncated.pop();
+        assert!(matches!(validator.validate_block(&truncated), Err(ValidationError::InvalidBlock(_))));
+    }
+
+    #[test]
+    fn test_transactions_batch_preserves_order() {
+        let validator = TurboValidator::default()
+            .with_config(ValidatorConfig { parallelism: 2, batch_size: 1, ..ValidatorConfig::default() })
+            .unwrap();
+        let txs: Vec<&[u8]> = vec![b"a", b"", b"c", b"", b"e"];
+        let results = validator.validate_transactions_batch(&txs, false);
+        assert_eq!(results.len(), txs.len());
+        let ok: Vec<bool> = results.iter().map(|r| r.is_ok()).collect();
+        assert_eq!(ok, vec![true, false, true, false, true]);
+    }
+
+    #[test]
+    fn test_batch_fail_fast() {
+        let validator = TurboValidator::default()
+            .with_config(ValidatorConfig { parallelism: 1, batch_size: 1, ..ValidatorConfig::default() })
+            .unwrap();
+        let txs: Vec<&[u8]> = vec![b"", b"b", b"c"];
+        let results = validator.validate_transactions_batch(&txs, true);
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[0], Err(ValidationError::InvalidTransaction(_))));
+        assert!(results[1..].iter().all(|r| matches!(r, Err(ValidationError::Other(_)))));
+    }
+
+    #[test]
+    fn test_blocks_batch() {
+        let policy = PQCPolicy { kyber_enabled: false, dilithium_enabled: false, ..PQCPolicy::default() };
+        let validator = TurboValidator::with_pqc(policy, PqcKeyring::default());
+        let (genesis, block_1) = (genesis_header(), block_1_header());
+        let blocks: Vec<&[u8]> = vec![&genesis, b"", &block_1];
+        let results = validator.validate_blocks_batch(&blocks, false);
+        assert!(results[0].is_ok() && results[1].is_err() && results[2].is_ok());
+    }
+
+    /// Build a minimal legacy transaction spending the given outpoints
+    fn tx_spending(inputs: &[([u8; 32], u32)]) -> Vec<u8> {
+        let mut tx = 1u32.to_le_bytes().to_vec();
+        tx.push(inputs.len() as u8);
+        for (txid, vout) in inputs {
+            tx.extend_from_slice(txid);
+            tx.extend_from_slice(&vout.to_le_bytes());
+            tx.push(0); // empty scriptSig
+            tx.extend_from_slice(&u32::MAX.to_le_bytes());
+        }
+        tx.push(0); // no outputs
+        tx.extend_from_slice(&0u32.to_le_bytes());
+        tx
+    }
+
+    /// Index that reports every outpoint as a probabilistic hit
+    struct AlwaysMaybe;
+
+    impl SpentOutpointIndex for AlwaysMaybe {
+        fn lookup(&self, _outpoint: &OutPoint) -> Result<SpentLookup, ValidationError> {
+            Ok(SpentLookup::MaybeSpent)
+        }
+        fn mark_spent(&self, _outpoints: &[OutPoint]) -> Result<(), ValidationError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_double_spend_detected_after_mark_spent() {
+        let index = Arc::new(InMemorySpentIndex::new());
+        let validator = TurboValidator::default().with_spent_index(index.clone(), MaybeSpentPolicy::Reject);
+        let tx = tx_spending(&[([1u8; 32], 0), ([2u8; 32], 3)]);
+        assert!(validator.validate_transaction(&tx).is_ok());
+        assert_eq!(validator.mark_spent(&[&tx]).unwrap(), 2);
+        assert_eq!(index.len(), 2);
+        let respend = tx_spending(&[([9u8; 32], 0), ([2u8; 32], 3)]);
+        assert!(matches!(validator.validate_transaction(&respend), Err(ValidationError::DoubleSpend(_))));
+    }
+
+    #[test]
+    fn test_duplicate_input_within_tx() {
+        let validator =
+            TurboValidator::default().with_spent_index(Arc::new(InMemorySpentIndex::new()), MaybeSpentPolicy::Reject);
+        let tx = tx_spending(&[([1u8; 32], 0), ([1u8; 32], 0)]);
+        assert!(matches!(validator.validate_transaction(&tx), Err(ValidationError::DoubleSpend(_))));
+    }
+
+    #[test]
+    fn test_maybe_spent_policy() {
+        let tx = tx_spending(&[([5u8; 32], 1)]);
+        let strict = TurboValidator::default().with_spent_index(Arc::new(AlwaysMaybe), MaybeSpentPolicy::Reject);
+        assert!(matches!(strict.validate_transaction(&tx), Err(ValidationError::DoubleSpend(_))));
+        let lenient = TurboValidator::default().with_spent_index(Arc::new(AlwaysMaybe), MaybeSpentPolicy::Warn);
+        assert!(lenient.validate_transaction(&tx).is_ok());
+    }
+
+    #[test]
+    fn test_coinbase_and_malformed_inputs() {
+        let validator =
+            TurboValidator::default().with_spent_index(Arc::new(InMemorySpentIndex::new()), MaybeSpentPolicy::Reject);
+        let coinbase = tx_spending(&[([0u8; 32], u32::MAX)]);
+        assert!(spent::parse_tx_inputs(&coinbase).unwrap().is_empty());
+        assert!(validator.validate_transaction(&coinbase).is_ok());
+        assert!(matches!(
+            validator.validate_transaction(b"garbage"),
+            Err(ValidationError::InvalidTransaction(_))
+        ));
+    }
+
+    fn validator_for(network: NetworkProfile) -> TurboValidator {
+        let policy = PQCPolicy { kyber_enabled: false, dilithium_enabled: false, ..PQCPolicy::default() };
+        TurboValidator::with_pqc(policy, PqcKeyring::default())
+            .with_config(ValidatorConfig { network, ..ValidatorConfig::default() })
+            .unwrap()
+    }
+
+    fn assert_invalid_for(validator: &TurboValidator, payload: &[u8], needle: &str) {
+        let network = validator.config().network.name();
+        match validator.validate_block(payload) {
+            Err(ValidationError::InvalidBlock(msg)) => {
+                assert!(msg.starts_with(&format!("{}: ", network)), "{}", msg);
+                assert!(msg.contains(needle), "{}", msg);
+            }
+            other => panic!("expected InvalidBlock containing {:?}, got {:?}", needle, other),
+        }
+    }
+
+    #[test]
+    fn test_network_profiles_dispatch_structural_checks() {
+        let bitcoin = validator_for(NetworkProfile::Bitcoin);
+        assert!(bitcoin.validate_block(&tx::fixtures::block_170()).is_ok());
+        assert_invalid_for(&bitcoin, &network::fixtures::ethereum_block(), "in the future");
+
+        let ethereum = validator_for(NetworkProfile::Ethereum);
+        let report = ethereum.validate_block_report(&network::fixtures::ethereum_block()).unwrap();
+        assert_eq!(report.check_names(), vec!["non_empty", "rlp_envelope"]);
+        assert_invalid_for(&ethereum, &tx::fixtures::block_170(), "not an RLP list");
+
+        let solana = validator_for(NetworkProfile::Solana);
+        let report = solana.validate_block_report(&network::fixtures::solana_shred()).unwrap();
+        assert_eq!(report.check_names(), vec!["non_empty", "shred_size"]);
+        assert_invalid_for(&solana, &[0u8; network::SOLANA_SHRED_PAYLOAD_SIZE + 1], "Shred is 1229 bytes");
+
+        let custom = validator_for(NetworkProfile::Custom { min_size: 8, max_size: 64, magic: Some(*b"SPRT") });
+        let report = custom.validate_block_report(b"SPRT custom payload").unwrap();
+        assert_eq!(report.check_names(), vec!["non_empty", "payload_size", "magic_prefix"]);
+        assert_invalid_for(&custom, b"XXXX custom payload", "magic 53505254");
+        assert_invalid_for(&custom, b"SPRT", "4 bytes");
+        assert_invalid_for(&custom, &[], "Block data is empty");
+        let unframed = validator_for(NetworkProfile::Custom { min_size: 1, max_size: 4, magic: None });
+        assert_eq!(unframed.validate_block_report(b"abc").unwrap().check_names(), vec!["non_empty", "payload_size"]);
+    }
+
+    #[test]
+    fn test_network_profile_gates_pqc_mixins() {
+        let with_network = |network| {
+            TurboValidator::default().with_config(ValidatorConfig { network, ..ValidatorConfig::default() }).unwrap()
+        };
+        // Shreds cannot carry an envelope, so the default policy does not demand one
+        let solana = with_network(NetworkProfile::Solana);
+        let report = solana.validate_block_report(&network::fixtures::solana_shred()).unwrap();
+        assert_eq!((report.pqc.kyber, report.pqc.dilithium), (PqcStatus::SkippedByNetwork, PqcStatus::SkippedByNetwork));
+
+        let ethereum = with_network(NetworkProfile::Ethereum);
+        match ethereum.validate_block(&network::fixtures::ethereum_block()) {
+            Err(ValidationError::SignatureError(msg)) => assert!(msg.starts_with("ethereum: "), "{}", msg),
+            other => panic!("expected a missing-envelope error, got {:?}", other),
+        }
+    }
+
+    /// Verifier that rejects one input index and records the others
+    struct RejectInput(usize, std::sync::Mutex<Vec<usize>>);
+
+    impl SignatureVerifier for RejectInput {
+        fn verify_input(&self, _tx: &[u8], index: usize, script: &[u8], value: u64) -> Result<(), ValidationError> {
+            if index == self.0 {
+                return Err(ValidationError::SignatureError(format!("bad signature over {} bytes, {} sat", script.len(), value)));
+            }
+            self.1.lock().unwrap().push(index);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_prevout_signature_checks() {
+        let tx = tx_spending(&[([1u8; 32], 0), ([2u8; 32], 1), ([3u8; 32], 2)]);
+        let prevouts: Vec<PrevOut> = (0..3).map(|i| PrevOut::new(vec![0x51; i + 1], 1_000 * i as u64)).collect();
+
+        assert!(TurboValidator::default().validate_transaction_with_prevouts(&tx, &prevouts).is_ok());
+        assert!(matches!(
+            TurboValidator::default().validate_transaction_with_prevouts(&tx, &prevouts[..2]),
+            Err(ValidationError::InvalidTransaction(msg)) if msg == "2 prevouts supplied for 3 inputs"
+        ));
+
+        let verifier = Arc::new(RejectInput(1, Default::default()));
+        let validator = TurboValidator::default().with_signature_verifier(verifier.clone());
+        match validator.validate_transaction_with_prevouts(&tx, &prevouts) {
+            Err(ValidationError::SignatureError(msg)) => assert_eq!(msg, "input 1: bad signature over 2 bytes, 1000 sat"),
+            other => panic!("expected SignatureError, got {:?}", other),
+        }
+        assert_eq!(*verifier.1.lock().unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn test_prevouts_for_real_spends() {
+        use sighash::fixtures::*;
+        let p2pkh = hex::decode(P2PKH_SPEND).unwrap();
+        let p2pkh_prevouts = [PrevOut::new(hex::decode(P2PKH_SCRIPT).unwrap(), P2PKH_VALUE)];
+        let bip143 = hex::decode(BIP143_NATIVE_P2WPKH_SIGNED).unwrap();
+        let bip143_prevouts = [
+            PrevOut::new(hex::decode(BIP143_P2PK_SCRIPT).unwrap(), BIP143_P2PK_VALUE),
+            PrevOut::new(hex::decode(BIP143_P2WPKH_SCRIPT).unwrap(), BIP143_P2WPKH_VALUE),
+        ];
+        let noop = TurboValidator::default();
+        assert!(noop.validate_transaction_with_prevouts(&p2pkh, &p2pkh_prevouts).is_ok());
+        assert!(noop.validate_transaction_with_prevouts(&bip143, &bip143_prevouts).is_ok());
+
+        #[cfg(feature = "secp256k1")]
+        {
+            let lenient = TurboValidator::default().with_signature_verifier(Arc::new(LibsecpVerifier::new()));
+            assert!(lenient.validate_transaction_with_prevouts(&p2pkh, &p2pkh_prevouts).is_ok());
+            assert!(lenient.validate_transaction_with_prevouts(&bip143, &bip143_prevouts).is_ok());
+            let strict = TurboValidator::default()
+                .with_signature_verifier(Arc::new(LibsecpVerifier::new().rejecting_unsupported()));
+            match strict.validate_transaction_with_prevouts(&bip143, &bip143_prevouts) {
+                Err(ValidationError::SignatureError(msg)) => assert!(msg.starts_with("input 0: "), "{}", msg),
+                other => panic!("expected SignatureError, got {:?}", other),
+            }
+        }
+    }
+
+    /// Sink that keeps every record
+    #[derive(Default)]
+    struct CountingMetrics(std::sync::Mutex<Vec<(ValidationKind, ValidationOutcome)>>);
+
+    impl ValidatorMetrics for CountingMetrics {
+        fn record_validation(&self, kind: ValidationKind, outcome: ValidationOutcome, _duration: std::time::Duration) {
+            self.0.lock().unwrap().push((kind, outcome));
+        }
+    }
+
+    impl CountingMetrics {
+        fn take(&self) -> Vec<(ValidationKind, &'static str)> {
+            self.0.lock().unwrap().drain(..).map(|(kind, outcome)| (kind, outcome.label())).collect()
+        }
+    }
+
+    #[test]
+    fn test_metrics_record_once_per_validation() {
+        use ValidationKind::{Block, Tx};
+        let metrics = Arc::new(CountingMetrics::default());
+        let validator = validator_for(NetworkProfile::Bitcoin).with_metrics(metrics.clone());
+        let block = tx::fixtures::block_170();
+
+        assert!(validator.validate_block(&block).is_ok());
+        assert!(validator.validate_block_report(&block).is_ok());
+        assert!(validator.validate_block_with_envelope(&[], None).is_err());
+        assert_eq!(metrics.take(), vec![(Block, "ok"), (Block, "ok"), (Block, "InvalidBlock")]);
+
+        let tx = tx_spending(&[([1u8; 32], 0)]);
+        assert!(validator.validate_transaction(&tx).is_ok());
+        assert!(validator.validate_transaction(&[]).is_err());
+        // Only the outer call is recorded, not the validate_transaction it builds on
+        let prevouts = [PrevOut::new(vec![0x51], 1_000)];
+        assert!(validator.validate_transaction_with_prevouts(&tx, &prevouts).is_ok());
+        let rejecting = validator.clone().with_signature_verifier(Arc::new(RejectInput(0, Default::default())));
+        assert!(rejecting.validate_transaction_with_prevouts(&tx, &prevouts).is_err());
+        assert_eq!(metrics.take(), vec![(Tx, "ok"), (Tx, "InvalidTransaction"), (Tx, "ok"), (Tx, "SignatureError")]);
+
+        let results = validator.validate_transactions_batch(&[&tx, &[], &tx], false);
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 2);
+        let mut recorded = metrics.take();
+        recorded.sort_by_key(|(_, outcome)| *outcome);
+        assert_eq!(recorded, vec![(Tx, "InvalidTransaction"), (Tx, "ok"), (Tx, "ok")]);
+        validator.validate_blocks_batch(&[&block, &block], false);
+        assert_eq!(metrics.take(), vec![(Block, "ok"), (Block, "ok")]);
+    }
+
+    #[test]
+    fn test_envelope_trailer_roundtrip() {
+        let envelope = PqcEnvelope {
+            kyber_ciphertext: vec![7; 24],
+            shared_secret_commitment: vec![8; 32],
+            dilithium_signature: vec![9; 40],
+        };
+        let data = envelope.append_to(b"raw block");
+        let (payload, parsed) = PqcEnvelope::split_appended(&data).unwrap();
+        assert_eq!(payload, b"raw block");
+        assert_eq!(parsed, Some(envelope));
+        let (payload, parsed) = PqcEnvelope::split_appended(b"no trailer").unwrap();
+        assert_eq!(payload, b"no trailer");
+        assert!(parsed.is_none());
+    }
+
     #[test]
     fn test_empty_tx() {
         let validator = TurboValidator::default();
         assert!(validator.validate_transaction(&[]).is_err());
     }
 }
+
+#[cfg(all(test, feature = "pqc"))]
+mod pqc_verification_tests {
+    use super::*;
+    use pqcrypto_dilithium::dilithium3;
+    use pqcrypto_kyber::kyber768;
+    use pqcrypto_traits::kem::{Ciphertext as _, SecretKey as _, SharedSecret as _};
+    use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey as _};
+
+    fn signed_fixture(block: &[u8]) -> (TurboValidator, PqcEnvelope) {
+        let (kyber_pk, kyber_sk) = kyber768::keypair();
+        let (dil_pk, dil_sk) = dilithium3::keypair();
+        let (ss, ct) = kyber768::encapsulate(&kyber_pk);
+        let envelope = PqcEnvelope {
+            kyber_ciphertext: ct.as_bytes().to_vec(),
+            shared_secret_commitment: pqc::shared_secret_commitment(ss.as_bytes(), block),
+            dilithium_signature: dilithium3::detached_sign(block, &dil_sk).as_bytes().to_vec(),
+        };
+        let keys = PqcKeyring {
+            kyber_secret_key: Some(kyber_sk.as_bytes().to_vec()),
+            dilithium_public_key: Some(dil_pk.as_bytes().to_vec()),
+        };
+        (TurboValidator::with_pqc(PQCPolicy::default(), keys), envelope)
+    }
+
+    #[test]
+    fn test_valid_envelope() {
+        let block = tx::fixtures::genesis_block();
+        let (validator, envelope) = signed_fixture(&block);
+        assert!(validator.validate_block_with_envelope(&block, Some(&envelope)).is_ok());
+        assert!(validator.validate_block(&envelope.append_to(&block)).is_ok());
+    }
+
+    #[test]
+    fn test_report_checks_per_policy() {
+        let block = tx::fixtures::genesis_block();
+        let (mut validator, envelope) = signed_fixture(&block);
+        let with_trailer = envelope.append_to(&block);
+        let header_checks = ["non_empty", "header_format", "timestamp", "proof_of_work", "merkle_root"];
+        let cases = [
+            (true, true, vec!["kyber_commitment", "dilithium_signature"]),
+            (true, false, vec!["kyber_commitment"]),
+            (false, true, vec!["dilithium_signature"]),
+            (false, false, vec![]),
+        ];
+        for (kyber_enabled, dilithium_enabled, pqc_checks) in cases {
+            validator.set_pqc_policy(PQCPolicy { kyber_enabled, dilithium_enabled, ..PQCPolicy::default() });
+            let pqc_required = kyber_enabled || dilithium_enabled;
+            let mut expected = Vec::new();
+            if pqc_required {
+                expected.push("envelope_trailer");
+            }
+            expected.extend(header_checks);
+            if pqc_required {
+                expected.push("envelope_present");
+            }
+            expected.extend(pqc_checks);
+
+            let payload: &[u8] = if pqc_required { &with_trailer } else { &block };
+            let report = validator.validate_block_report(payload).unwrap();
+            assert_eq!(report.check_names(), expected);
+            assert!(report.checks.iter().all(|c| c.passed));
+            let status = |enabled| if enabled { PqcStatus::Verified } else { PqcStatus::SkippedByPolicy };
+            assert_eq!((report.pqc.kyber, report.pqc.dilithium), (status(kyber_enabled), status(dilithium_enabled)));
+        }
+    }
+
+    #[test]
+    fn test_tampered_signature() {
+        let block = tx::fixtures::genesis_block();
+        let (validator, mut envelope) = signed_fixture(&block);
+        envelope.dilithium_signature[0] ^= 0xff;
+        assert!(matches!(
+            validator.validate_block_with_envelope(&block, Some(&envelope)),
+            Err(ValidationError::SignatureError(_))
+        ));
+    }
+
+    #[test]
+    fn test_tampered_ciphertext_and_block() {
+        let block = tx::fixtures::genesis_block();
+        let (validator, mut envelope) = signed_fixture(&block);
+        assert!(validator.validate_block_with_envelope(&tx::fixtures::block_170(), Some(&envelope)).is_err());
+        envelope.kyber_ciphertext[0] ^= 0xff;
+        assert!(validator.validate_block_with_envelope(&block, Some(&envelope)).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Instrumentation hook.
+//!
+//! `TurboValidator::with_metrics` installs a `ValidatorMetrics` sink that sees one
+//! record per block or transaction validated, including each item of a batch. The
+//! crate has no metrics backend of its own; securebuffer provides a Prometheus sink.
+
+use crate::ValidationError;
+use std::time::Duration;
+
+/// What was validated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationKind {
+    Block,
+    Tx,
+}
+
+impl ValidationKind {
+    /// Metric label value
+    pub fn label(&self) -> &'static str {
+        match self {
+            ValidationKind::Block => "block",
+            ValidationKind::Tx => "tx",
+        }
+    }
+}
+
+/// How a validation ended: accepted, or rejected with a `ValidationError` variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationOutcome {
+    Ok,
+    /// Name of the `ValidationError` variant, e.g. "InvalidBlock"
+    Err(&'static str),
+}
+
+impl ValidationOutcome {
+    pub fn of<T>(result: &Result<T, ValidationError>) -> Self {
+        match result {
+            Ok(_) => ValidationOutcome::Ok,
+            Err(e) => ValidationOutcome::Err(e.variant_name()),
+        }
+    }
+
+    /// Metric label value: "ok" or the error variant name
+    pub fn label(&self) -> &'static str {
+        match self {
+            ValidationOutcome::Ok => "ok",
+            ValidationOutcome::Err(name) => name,
+        }
+    }
+}
+
+/// Receives the outcome and duration of every validation
+pub trait ValidatorMetrics: Send + Sync {
+    fn record_validation(&self, kind: ValidationKind, outcome: ValidationOutcome, duration: Duration);
+}
+
+/// Discards every record; the default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl ValidatorMetrics for NoopMetrics {
+    fn record_validation(&self, _kind: ValidationKind, _outcome: ValidationOutcome, _duration: Duration) {}
+}
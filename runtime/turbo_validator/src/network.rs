@@ -0,0 +1,240 @@
+//! Per-network validation profiles.
+//!
+//! `ValidatorConfig::network` selects the structural checks `validate_block` runs and
+//! which PQC mix-ins apply. Bitcoin payloads go through the header and Merkle rules;
+//! other networks get shape checks only, since their consensus rules live elsewhere.
+
+use crate::ValidationError;
+
+/// Items in an Ethereum block: header, transactions, uncles and, since Shanghai, withdrawals
+pub const ETHEREUM_BLOCK_MIN_ITEMS: usize = 3;
+pub const ETHEREUM_BLOCK_MAX_ITEMS: usize = 4;
+
+/// Common shred header plus the data shred header
+pub const SOLANA_SHRED_HEADER_SIZE: usize = 88;
+/// Largest shred payload that fits in a packet
+pub const SOLANA_SHRED_PAYLOAD_SIZE: usize = 1228;
+
+/// Network whose payloads a validator checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NetworkProfile {
+    /// Serialized blocks: header rules and the Merkle root of the transactions
+    #[default]
+    Bitcoin,
+    /// RLP-encoded blocks
+    Ethereum,
+    /// Individual shreds
+    Solana,
+    /// Size bounds (inclusive) and an optional magic prefix
+    Custom { min_size: usize, max_size: usize, magic: Option<[u8; 4]> },
+}
+
+impl NetworkProfile {
+    /// Name used in error messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            NetworkProfile::Bitcoin => "bitcoin",
+            NetworkProfile::Ethereum => "ethereum",
+            NetworkProfile::Solana => "solana",
+            NetworkProfile::Custom { .. } => "custom",
+        }
+    }
+
+    /// Whether the Kyber commitment mix-in applies. A Kyber768 ciphertext alone is
+    /// almost a full shred, so Solana payloads never carry an envelope.
+    pub fn kyber_applies(&self) -> bool {
+        !matches!(self, NetworkProfile::Solana)
+    }
+
+    /// Whether the Dilithium signature mix-in applies; see `kyber_applies`
+    pub fn dilithium_applies(&self) -> bool {
+        !matches!(self, NetworkProfile::Solana)
+    }
+}
+
+/// Walk the RLP list `payload` and return its item count
+pub fn check_rlp_block(payload: &[u8]) -> Result<usize, ValidationError> {
+    let (header_len, body_len, is_list) = rlp_header(payload, 0)?;
+    if !is_list {
+        return Err(ValidationError::InvalidBlock("Payload is not an RLP list".into()));
+    }
+    let end = header_len + body_len;
+    if end != payload.len() {
+        return Err(ValidationError::InvalidBlock(format!(
+            "RLP list covers {} bytes but payload has {}",
+            end,
+            payload.len()
+        )));
+    }
+
+    let mut pos = header_len;
+    let mut items = 0;
+    while pos < end {
+        let (item_header, item_body, item_is_list) = rlp_header(payload, pos)?;
+        if items == 0 && !item_is_list {
+            return Err(ValidationError::InvalidBlock(format!("Block header at offset {} is not an RLP list", pos)));
+        }
+        pos += item_header + item_body;
+        items += 1;
+        if items > ETHEREUM_BLOCK_MAX_ITEMS {
+            break;
+        }
+    }
+    if pos > end {
+        return Err(ValidationError::InvalidBlock(format!("RLP item overruns the block list at offset {}", end)));
+    }
+    if !(ETHEREUM_BLOCK_MIN_ITEMS..=ETHEREUM_BLOCK_MAX_ITEMS).contains(&items) {
+        return Err(ValidationError::InvalidBlock(format!(
+            "Block list has {}{} items, expected {} to {}",
+            items,
+            if items > ETHEREUM_BLOCK_MAX_ITEMS { "+" } else { "" },
+            ETHEREUM_BLOCK_MIN_ITEMS,
+            ETHEREUM_BLOCK_MAX_ITEMS
+        )));
+    }
+    Ok(items)
+}
+
+/// Decode the RLP item header at `pos`: (header length, body length, is list)
+fn rlp_header(payload: &[u8], pos: usize) -> Result<(usize, usize, bool), ValidationError> {
+    let truncated = || ValidationError::InvalidBlock(format!("RLP item truncated at offset {}", pos));
+    let prefix = *payload.get(pos).ok_or_else(truncated)?;
+    let (header_len, body_len, is_list) = match prefix {
+        0x00..=0x7f => (0, 1, false),
+        0x80..=0xb7 => (1, (prefix - 0x80) as usize, false),
+        0xc0..=0xf7 => (1, (prefix - 0xc0) as usize, true),
+        0xb8..=0xbf | 0xf8..=0xff => {
+            let is_list = prefix >= 0xf8;
+            let len_of_len = (prefix - if is_list { 0xf7 } else { 0xb7 }) as usize;
+            let len_bytes = payload.get(pos + 1..pos + 1 + len_of_len).ok_or_else(truncated)?;
+            if len_bytes[0] == 0 || len_of_len > std::mem::size_of::<usize>() {
+                return Err(ValidationError::InvalidBlock(format!("Non-canonical RLP length at offset {}", pos)));
+            }
+            let len = len_bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            if len < 56 {
+                return Err(ValidationError::InvalidBlock(format!("Non-canonical RLP length at offset {}", pos)));
+            }
+            (1 + len_of_len, len, is_list)
+        }
+    };
+    let end = pos.checked_add(header_len).and_then(|p| p.checked_add(body_len)).ok_or_else(truncated)?;
+    if end > payload.len() {
+        return Err(truncated());
+    }
+    Ok((header_len, body_len, is_list))
+}
+
+/// Check that a Solana payload is shred-sized
+pub fn check_shred_size(payload: &[u8]) -> Result<(), ValidationError> {
+    if !(SOLANA_SHRED_HEADER_SIZE..=SOLANA_SHRED_PAYLOAD_SIZE).contains(&payload.len()) {
+        return Err(ValidationError::InvalidBlock(format!(
+            "Shred is {} bytes, expected {} to {}",
+            payload.len(),
+            SOLANA_SHRED_HEADER_SIZE,
+            SOLANA_SHRED_PAYLOAD_SIZE
+        )));
+    }
+    Ok(())
+}
+
+/// Enforce inclusive size bounds
+pub fn check_payload_size(payload: &[u8], min_size: usize, max_size: usize) -> Result<(), ValidationError> {
+    if !(min_size..=max_size).contains(&payload.len()) {
+        return Err(ValidationError::InvalidBlock(format!(
+            "Payload is {} bytes, expected {} to {}",
+            payload.len(),
+            min_size,
+            max_size
+        )));
+    }
+    Ok(())
+}
+
+pub fn check_magic(payload: &[u8], magic: &[u8; 4]) -> Result<(), ValidationError> {
+    if !payload.starts_with(magic) {
+        return Err(ValidationError::InvalidBlock(format!("Payload does not start with magic {}", hex::encode(magic))));
+    }
+    Ok(())
+}
+
+/// Valid-shaped payloads for each profile
+#[cfg(test)]
+pub(crate) mod fixtures {
+    /// RLP list of `items`, each already encoded
+    pub fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = items.concat();
+        let mut out = if body.len() < 56 {
+            vec![0xc0 + body.len() as u8]
+        } else {
+            let len = body.len().to_be_bytes();
+            let len = &len[len.iter().position(|b| *b != 0).unwrap()..];
+            let mut out = vec![0xf7 + len.len() as u8];
+            out.extend_from_slice(len);
+            out
+        };
+        out.extend_from_slice(&body);
+        out
+    }
+
+    /// Block-shaped RLP: a header list long enough for long-form lengths, then empty
+    /// transaction and uncle lists
+    pub fn ethereum_block() -> Vec<u8> {
+        let header = rlp_list(&[vec![0x80; 15], vec![0x83, 1, 2, 3], vec![0xa0; 33], vec![0xa0; 33]]);
+        rlp_list(&[header, rlp_list(&[]), rlp_list(&[])])
+    }
+
+    pub fn solana_shred() -> Vec<u8> {
+        vec![0x5a; 1203]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::*;
+    use super::*;
+
+    #[test]
+    fn test_rlp_block_item_bounds() {
+        let block = ethereum_block();
+        assert!(block[0] >= 0xf8 && block[2] >= 0xf8, "block and header should use long-form lengths");
+        assert_eq!(check_rlp_block(&block).unwrap(), 3);
+        let header = block[2..block.len() - 2].to_vec();
+        let shanghai = rlp_list(&[header.clone(), rlp_list(&[]), rlp_list(&[]), rlp_list(&[])]);
+        assert_eq!(check_rlp_block(&shanghai).unwrap(), 4);
+
+        let too_few = rlp_list(&[header.clone(), rlp_list(&[])]);
+        assert!(check_rlp_block(&too_few).unwrap_err().to_string().contains("2 items"));
+        let too_many = rlp_list(&vec![header.clone(); 6]);
+        assert!(check_rlp_block(&too_many).unwrap_err().to_string().contains("5+ items"));
+        let string_header = rlp_list(&[vec![0x82, 1, 2], rlp_list(&[]), rlp_list(&[])]);
+        assert!(check_rlp_block(&string_header).unwrap_err().to_string().contains("not an RLP list"));
+    }
+
+    #[test]
+    fn test_rlp_envelope_rejections() {
+        let block = rlp_list(&[rlp_list(&[vec![1]]), rlp_list(&[]), rlp_list(&[])]);
+        assert!(check_rlp_block(&block[..block.len() - 1]).is_err());
+        let mut trailing = block.clone();
+        trailing.push(0);
+        assert!(check_rlp_block(&trailing).unwrap_err().to_string().contains("covers"));
+        assert!(check_rlp_block(&[0x83, 1, 2, 3]).unwrap_err().to_string().contains("not an RLP list"));
+        // Long form used for a 3-byte list
+        assert!(check_rlp_block(&[0xf8, 0x03, 0xc0, 0xc0, 0xc0]).unwrap_err().to_string().contains("Non-canonical"));
+        assert!(check_rlp_block(&[]).is_err());
+    }
+
+    #[test]
+    fn test_shred_size_and_custom_rules() {
+        assert!(check_shred_size(&solana_shred()).is_ok());
+        assert!(check_shred_size(&[0u8; SOLANA_SHRED_PAYLOAD_SIZE]).is_ok());
+        assert!(check_shred_size(&[0u8; SOLANA_SHRED_PAYLOAD_SIZE + 1]).is_err());
+        assert!(check_shred_size(&[0u8; SOLANA_SHRED_HEADER_SIZE - 1]).is_err());
+
+        assert!(check_payload_size(b"SPRTpayload", 8, 16).is_ok());
+        assert!(check_payload_size(b"SPRT", 8, 16).is_err());
+        assert!(check_payload_size(b"anything goes", 0, usize::MAX).is_ok());
+        assert!(check_magic(b"SPRTpayload", b"SPRT").is_ok());
+        assert!(check_magic(b"XXXXpayload", b"SPRT").unwrap_err().to_string().contains("53505254"));
+        assert!(check_magic(b"SP", b"SPRT").is_err());
+    }
+}
@@ -0,0 +1,193 @@
+//! PQC envelope handling for TurboValidator.
+//!
+//! An envelope carries a Kyber ciphertext, a commitment to the encapsulated
+//! shared secret bound to the payload, and a detached Dilithium signature over
+//! the payload. It can be passed alongside the raw block bytes or appended to
+//! them with a trailer (`envelope || u32 LE length || ENVELOPE_MAGIC`).
+
+use crate::ValidationError;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Trailer magic marking an appended PQC envelope
+pub const ENVELOPE_MAGIC: &[u8; 4] = b"PQCE";
+
+/// Trailer size: u32 LE envelope length followed by the magic
+const TRAILER_LEN: usize = 4 + ENVELOPE_MAGIC.len();
+
+/// PQC envelope attached to a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PqcEnvelope {
+    /// Kyber ciphertext encapsulated to the validator's public key
+    pub kyber_ciphertext: Vec<u8>,
+    /// SHA-256(shared_secret || payload)
+    pub shared_secret_commitment: Vec<u8>,
+    /// Detached Dilithium signature over the payload
+    pub dilithium_signature: Vec<u8>,
+}
+
+impl PqcEnvelope {
+    /// Encode as three u32 LE length-prefixed fields
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let fields = [
+            &self.kyber_ciphertext,
+            &self.shared_secret_commitment,
+            &self.dilithium_signature,
+        ];
+        let mut out = Vec::with_capacity(fields.iter().map(|f| 4 + f.len()).sum());
+        for field in fields {
+            out.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            out.extend_from_slice(field);
+        }
+        out
+    }
+
+    /// Decode an envelope produced by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ValidationError> {
+        let mut rest = bytes;
+        let mut next_field = |name: &str| -> Result<Vec<u8>, ValidationError> {
+            if rest.len() < 4 {
+                return Err(ValidationError::SignatureError(format!("Truncated PQC envelope ({})", name)));
+            }
+            let len = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            rest = &rest[4..];
+            if rest.len() < len {
+                return Err(ValidationError::SignatureError(format!("Truncated PQC envelope ({})", name)));
+            }
+            let (field, tail) = rest.split_at(len);
+            rest = tail;
+            Ok(field.to_vec())
+        };
+
+        let envelope = Self {
+            kyber_ciphertext: next_field("kyber_ciphertext")?,
+            shared_secret_commitment: next_field("shared_secret_commitment")?,
+            dilithium_signature: next_field("dilithium_signature")?,
+        };
+        if !rest.is_empty() {
+            return Err(ValidationError::SignatureError("Trailing bytes after PQC envelope".into()));
+        }
+        Ok(envelope)
+    }
+
+    /// Append this envelope to a payload with the length/magic trailer
+    pub fn append_to(&self, payload: &[u8]) -> Vec<u8> {
+        let encoded = self.to_bytes();
+        let mut out = Vec::with_capacity(payload.len() + encoded.len() + TRAILER_LEN);
+        out.extend_from_slice(payload);
+        out.extend_from_slice(&encoded);
+        out.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+        out.extend_from_slice(ENVELOPE_MAGIC);
+        out
+    }
+
+    /// Split data into the payload and an appended envelope, if a trailer is present
+    pub fn split_appended(data: &[u8]) -> Result<(&[u8], Option<PqcEnvelope>), ValidationError> {
+        if data.len() < TRAILER_LEN || !data.ends_with(ENVELOPE_MAGIC) {
+            return Ok((data, None));
+        }
+        let len_at = data.len() - TRAILER_LEN;
+        let env_len = u32::from_le_bytes([data[len_at], data[len_at + 1], data[len_at + 2], data[len_at + 3]]) as usize;
+        if env_len > len_at {
+            return Err(ValidationError::SignatureError("PQC envelope length exceeds data".into()));
+        }
+        let env_start = len_at - env_len;
+        let envelope = Self::from_bytes(&data[env_start..len_at])?;
+        Ok((&data[..env_start], Some(envelope)))
+    }
+}
+
+/// Commitment binding a Kyber shared secret to a payload
+pub fn shared_secret_commitment(shared_secret: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(shared_secret);
+    hasher.update(payload);
+    hasher.finalize().to_vec()
+}
+
+/// Key material used to verify PQC envelopes
+#[derive(Clone, Default)]
+pub struct PqcKeyring {
+    /// Validator's Kyber secret key used to decapsulate envelope ciphertexts
+    pub kyber_secret_key: Option<Vec<u8>>,
+    /// Trusted Dilithium public key for block signatures
+    pub dilithium_public_key: Option<Vec<u8>>,
+}
+
+impl fmt::Debug for PqcKeyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PqcKeyring")
+            .field("kyber_secret_key", &self.kyber_secret_key.as_ref().map(|_| "<redacted>"))
+            .field("dilithium_public_key", &self.dilithium_public_key.as_ref().map(|k| k.len()))
+            .finish()
+    }
+}
+
+#[cfg(feature = "pqc")]
+mod backend {
+    use super::{shared_secret_commitment, PqcEnvelope, PqcKeyring};
+    use crate::ValidationError;
+    use pqcrypto_dilithium::dilithium3;
+    use pqcrypto_kyber::kyber768;
+    use pqcrypto_traits::kem::{Ciphertext as _, SecretKey as _, SharedSecret as _};
+    use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey as _};
+
+    pub fn verify_kyber(keys: &PqcKeyring, envelope: &PqcEnvelope, payload: &[u8]) -> Result<(), ValidationError> {
+        let sk_bytes = keys
+            .kyber_secret_key
+            .as_ref()
+            .ok_or_else(|| ValidationError::SignatureError("No Kyber secret key configured".into()))?;
+        let sk = kyber768::SecretKey::from_bytes(sk_bytes)
+            .map_err(|e| ValidationError::SignatureError(format!("Invalid Kyber secret key: {}", e)))?;
+        let ct = kyber768::Ciphertext::from_bytes(&envelope.kyber_ciphertext)
+            .map_err(|e| ValidationError::SignatureError(format!("Invalid Kyber ciphertext: {}", e)))?;
+        // Kyber decapsulation uses implicit rejection, so a tampered ciphertext
+        // yields an unrelated shared secret and the commitment check fails below.
+        let ss = kyber768::decapsulate(&ct, &sk);
+        let expected = shared_secret_commitment(ss.as_bytes(), payload);
+        if !constant_time_eq(&expected, &envelope.shared_secret_commitment) {
+            return Err(ValidationError::SignatureError("Kyber shared-secret commitment mismatch".into()));
+        }
+        Ok(())
+    }
+
+    pub fn verify_dilithium(keys: &PqcKeyring, envelope: &PqcEnvelope, payload: &[u8]) -> Result<(), ValidationError> {
+        let pk_bytes = keys
+            .dilithium_public_key
+            .as_ref()
+            .ok_or_else(|| ValidationError::SignatureError("No Dilithium public key configured".into()))?;
+        let pk = dilithium3::PublicKey::from_bytes(pk_bytes)
+            .map_err(|e| ValidationError::SignatureError(format!("Invalid Dilithium public key: {}", e)))?;
+        let sig = dilithium3::DetachedSignature::from_bytes(&envelope.dilithium_signature)
+            .map_err(|e| ValidationError::SignatureError(format!("Invalid Dilithium signature: {}", e)))?;
+        dilithium3::verify_detached_signature(&sig, payload, &pk)
+            .map_err(|_| ValidationError::SignatureError("Dilithium signature verification failed".into()))
+    }
+
+    fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+        a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+}
+
+#[cfg(not(feature = "pqc"))]
+mod backend {
+    use super::{PqcEnvelope, PqcKeyring};
+    use crate::ValidationError;
+
+    fn unsupported() -> ValidationError {
+        ValidationError::SignatureError("PQC verification not compiled in (enable the `pqc` feature)".into())
+    }
+
+    pub fn verify_kyber(_keys: &PqcKeyring, _envelope: &PqcEnvelope, _payload: &[u8]) -> Result<(), ValidationError> {
+        Err(unsupported())
+    }
+
+    pub fn verify_dilithium(_keys: &PqcKeyring, _envelope: &PqcEnvelope, _payload: &[u8]) -> Result<(), ValidationError> {
+        Err(unsupported())
+    }
+}
+
+pub(crate) use backend::{verify_dilithium, verify_kyber};
@@ -0,0 +1,81 @@
+//! HMAC-SHA256 signing for entropy hybrid receipts.
+//!
+//! The MAC covers a canonical binary encoding of every receipt field rather
+//! than the JSON text, so field order and float formatting cannot change the
+//! signature.
+
+use crate::{EntropyHybridReceipt, ValidationError};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Receipt with an embedded hex-encoded HMAC-SHA256 signature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedReceipt {
+    #[serde(flatten)]
+    pub receipt: EntropyHybridReceipt,
+    pub signature: String,
+}
+
+impl SignedReceipt {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Canonical byte encoding of a receipt for MAC computation
+fn canonical_bytes(receipt: &EntropyHybridReceipt) -> Result<Vec<u8>, ValidationError> {
+    if !receipt.pqc_weight.is_finite() {
+        return Err(ValidationError::SignatureError("Receipt pqc_weight must be finite".into()));
+    }
+    let mut out = Vec::with_capacity(
+        16 + receipt.attestation.len() + receipt.proof_hash.len() + receipt.verifier_id.len() + 12,
+    );
+    out.extend_from_slice(&receipt.beacon_round.to_le_bytes());
+    for field in [&receipt.attestation, &receipt.proof_hash, &receipt.verifier_id] {
+        out.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        out.extend_from_slice(field.as_bytes());
+    }
+    out.extend_from_slice(&receipt.pqc_weight.to_bits().to_le_bytes());
+    // Chain fields are only encoded when set, so unchained receipts keep their MAC
+    if receipt.prev_hash.is_some() || receipt.self_hash.is_some() {
+        for field in [&receipt.prev_hash, &receipt.self_hash] {
+            let field = field.as_deref().unwrap_or("");
+            out.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            out.extend_from_slice(field.as_bytes());
+        }
+    }
+    Ok(out)
+}
+
+fn new_mac(key: &[u8]) -> Result<HmacSha256, ValidationError> {
+    if key.is_empty() {
+        return Err(ValidationError::SignatureError("Receipt signing key is empty".into()));
+    }
+    HmacSha256::new_from_slice(key).map_err(|e| ValidationError::SignatureError(format!("Invalid HMAC key: {}", e)))
+}
+
+pub(crate) fn sign(receipt: &EntropyHybridReceipt, key: &[u8]) -> Result<SignedReceipt, ValidationError> {
+    let mut mac = new_mac(key)?;
+    mac.update(&canonical_bytes(receipt)?);
+    Ok(SignedReceipt {
+        receipt: receipt.clone(),
+        signature: hex::encode(mac.finalize().into_bytes()),
+    })
+}
+
+pub(crate) fn verify(signed: &SignedReceipt, key: &[u8]) -> Result<(), ValidationError> {
+    let tag = hex::decode(&signed.signature)
+        .map_err(|_| ValidationError::SignatureError("Receipt signature is not valid hex".into()))?;
+    let mut mac = new_mac(key)?;
+    mac.update(&canonical_bytes(&signed.receipt)?);
+    // verify_slice compares in constant time
+    mac.verify_slice(&tag)
+        .map_err(|_| ValidationError::SignatureError("Receipt signature mismatch".into()))
+}
@@ -0,0 +1,106 @@
+//! Structured validation reports.
+//!
+//! `TurboValidator::validate_block_report` records every check it ran, in order, with
+//! its outcome and duration. Reports only contain structs and sequences, so their JSON
+//! has a fixed field order and `report_hash` is stable for a given report.
+
+use crate::ValidationError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Instant;
+
+/// Outcome of one validation step
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub passed: bool,
+    pub duration_us: u64,
+    /// What was checked, or the error message when the check failed
+    pub detail: Option<String>,
+}
+
+/// How a PQC algorithm was handled for a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PqcStatus {
+    Verified,
+    /// Not checked because the policy disables it
+    SkippedByPolicy,
+    /// Not checked because the network profile does not carry it
+    SkippedByNetwork,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PqcSummary {
+    pub kyber: PqcStatus,
+    pub dilithium: PqcStatus,
+    pub envelope_present: bool,
+}
+
+/// Checks that accepted a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub checks: Vec<CheckResult>,
+    pub pqc: PqcSummary,
+    pub total_duration_us: u64,
+}
+
+impl ValidationReport {
+    /// Names of the checks that ran, in order
+    pub fn check_names(&self) -> Vec<&str> {
+        self.checks.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Hex SHA-256 of the report's JSON, suitable for `EntropyHybridReceipt::proof_hash`
+    pub fn report_hash(&self) -> Result<String, serde_json::Error> {
+        Ok(hex::encode(Sha256::digest(serde_json::to_vec(self)?)))
+    }
+}
+
+/// Collects check results while a block is validated
+pub(crate) struct ReportBuilder {
+    checks: Vec<CheckResult>,
+    started: Instant,
+    /// Network name prefixed to error messages
+    network: &'static str,
+}
+
+impl ReportBuilder {
+    pub(crate) fn new(network: &'static str) -> Self {
+        Self { checks: Vec::new(), started: Instant::now(), network }
+    }
+
+    /// Run `check` and record it; on failure the error is prefixed with the network name,
+    /// recorded as the detail and passed through
+    pub(crate) fn run<T>(
+        &mut self,
+        name: &str,
+        detail: Option<String>,
+        check: impl FnOnce() -> Result<T, ValidationError>,
+    ) -> Result<T, ValidationError> {
+        let started = Instant::now();
+        let result = check().map_err(|e| e.with_prefix(self.network));
+        self.checks.push(CheckResult {
+            name: name.to_string(),
+            passed: result.is_ok(),
+            duration_us: started.elapsed().as_micros() as u64,
+            detail: match &result {
+                Ok(_) => detail,
+                Err(e) => Some(e.to_string()),
+            },
+        });
+        result
+    }
+
+    pub(crate) fn finish(self, pqc: PqcSummary) -> ValidationReport {
+        ValidationReport {
+            checks: self.checks,
+            pqc,
+            total_duration_us: self.started.elapsed().as_micros() as u64,
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Signature hashes for legacy and BIP143 (segwit v0) inputs.
+//!
+//! `script_code` is serialized with its length prefix by these functions. Legacy
+//! hashing uses it as given: OP_CODESEPARATOR and signature removal (FindAndDelete)
+//! are not applied, which is exact for the standard templates the verifiers handle.
+
+use crate::tx::{double_sha256, write_var_bytes, write_varint, Transaction, TxOut};
+use crate::ValidationError;
+
+pub const SIGHASH_ALL: u32 = 0x01;
+pub const SIGHASH_NONE: u32 = 0x02;
+pub const SIGHASH_SINGLE: u32 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+fn check_index(tx: &Transaction, input_index: usize) -> Result<(), ValidationError> {
+    if input_index >= tx.inputs.len() {
+        return Err(ValidationError::InvalidTransaction(format!(
+            "Input index {} out of range for {} inputs",
+            input_index,
+            tx.inputs.len()
+        )));
+    }
+    Ok(())
+}
+
+fn write_outpoint(out: &mut Vec<u8>, tx: &Transaction, index: usize) {
+    let outpoint = &tx.inputs[index].previous_output;
+    out.extend_from_slice(&outpoint.txid);
+    out.extend_from_slice(&outpoint.vout.to_le_bytes());
+}
+
+fn write_output(out: &mut Vec<u8>, output: &TxOut) {
+    out.extend_from_slice(&output.value.to_le_bytes());
+    write_var_bytes(out, &output.script_pubkey);
+}
+
+/// Pre-segwit signature hash of input `input_index`
+pub fn legacy_sighash(
+    tx: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    sighash_type: u32,
+) -> Result<[u8; 32], ValidationError> {
+    check_index(tx, input_index)?;
+    let base = sighash_type & 0x1f;
+    if base == SIGHASH_SINGLE && input_index >= tx.outputs.len() {
+        // Consensus quirk: SIGHASH_SINGLE without a matching output signs the value one
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        return Ok(one);
+    }
+    let signed_inputs: Vec<usize> = if sighash_type & SIGHASH_ANYONECANPAY != 0 {
+        vec![input_index]
+    } else {
+        (0..tx.inputs.len()).collect()
+    };
+
+    let mut out = Vec::with_capacity(tx.stripped_size + script_code.len() + 4);
+    out.extend_from_slice(&tx.version.to_le_bytes());
+    write_varint(&mut out, signed_inputs.len() as u64);
+    for &index in &signed_inputs {
+        write_outpoint(&mut out, tx, index);
+        if index == input_index {
+            write_var_bytes(&mut out, script_code);
+        } else {
+            out.push(0);
+        }
+        let others_unsigned = index != input_index && (base == SIGHASH_NONE || base == SIGHASH_SINGLE);
+        let sequence = if others_unsigned { 0 } else { tx.inputs[index].sequence };
+        out.extend_from_slice(&sequence.to_le_bytes());
+    }
+    match base {
+        SIGHASH_NONE => write_varint(&mut out, 0),
+        SIGHASH_SINGLE => {
+            write_varint(&mut out, input_index as u64 + 1);
+            for _ in 0..input_index {
+                // Earlier outputs are blanked to value -1 with an empty script
+                out.extend_from_slice(&u64::MAX.to_le_bytes());
+                out.push(0);
+            }
+            write_output(&mut out, &tx.outputs[input_index]);
+        }
+        _ => {
+            write_varint(&mut out, tx.outputs.len() as u64);
+            for output in &tx.outputs {
+                write_output(&mut out, output);
+            }
+        }
+    }
+    out.extend_from_slice(&tx.lock_time.to_le_bytes());
+    out.extend_from_slice(&sighash_type.to_le_bytes());
+    Ok(double_sha256(&[&out]))
+}
+
+/// BIP143 signature hash of segwit v0 input `input_index` spending `value` satoshis
+pub fn segwit_v0_sighash(
+    tx: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    value: u64,
+    sighash_type: u32,
+) -> Result<[u8; 32], ValidationError> {
+    check_index(tx, input_index)?;
+    let base = sighash_type & 0x1f;
+    let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+    let single_or_none = base == SIGHASH_SINGLE || base == SIGHASH_NONE;
+
+    let hash_prevouts = if anyone_can_pay {
+        [0u8; 32]
+    } else {
+        let mut buf = Vec::with_capacity(tx.inputs.len() * 36);
+        for index in 0..tx.inputs.len() {
+            write_outpoint(&mut buf, tx, index);
+        }
+        double_sha256(&[&buf])
+    };
+    let hash_sequence = if anyone_can_pay || single_or_none {
+        [0u8; 32]
+    } else {
+        let sequences: Vec<u8> = tx.inputs.iter().flat_map(|input| input.sequence.to_le_bytes()).collect();
+        double_sha256(&[&sequences])
+    };
+    let hash_outputs = if !single_or_none {
+        let mut buf = Vec::new();
+        for output in &tx.outputs {
+            write_output(&mut buf, output);
+        }
+        double_sha256(&[&buf])
+    } else if base == SIGHASH_SINGLE && input_index < tx.outputs.len() {
+        let mut buf = Vec::new();
+        write_output(&mut buf, &tx.outputs[input_index]);
+        double_sha256(&[&buf])
+    } else {
+        [0u8; 32]
+    };
+
+    let mut out = Vec::with_capacity(160 + script_code.len());
+    out.extend_from_slice(&tx.version.to_le_bytes());
+    out.extend_from_slice(&hash_prevouts);
+    out.extend_from_slice(&hash_sequence);
+    write_outpoint(&mut out, tx, input_index);
+    write_var_bytes(&mut out, script_code);
+    out.extend_from_slice(&value.to_le_bytes());
+    out.extend_from_slice(&tx.inputs[input_index].sequence.to_le_bytes());
+    out.extend_from_slice(&hash_outputs);
+    out.extend_from_slice(&tx.lock_time.to_le_bytes());
+    out.extend_from_slice(&sighash_type.to_le_bytes());
+    Ok(double_sha256(&[&out]))
+}
+
+/// Transactions from BIP143 and a legacy P2PKH spend
+#[cfg(test)]
+pub(crate) mod fixtures {
+    /// BIP143 "Native P2WPKH" transaction, unsigned
+    pub const BIP143_NATIVE_P2WPKH_UNSIGNED: &str = "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000";
+
+    /// The same transaction signed: input 0 spends P2PK (legacy sighash), input 1 P2WPKH
+    pub const BIP143_NATIVE_P2WPKH_SIGNED: &str = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+
+    /// Prevouts of the BIP143 native P2WPKH transaction: P2PK of 6.25 BTC, P2WPKH of 6 BTC
+    pub const BIP143_P2PK_SCRIPT: &str = "2103c9f4836b9a4f77fc0d81f7bcb01b7f1b35916864b9476c241ce9fc198bd25432ac";
+    pub const BIP143_P2PK_VALUE: u64 = 625_000_000;
+    pub const BIP143_P2WPKH_SCRIPT: &str = "00141d0f172a0ecb48aee1be1f2687d2963ae33f71a1";
+    pub const BIP143_P2WPKH_VALUE: u64 = 600_000_000;
+
+    /// BIP143 "P2SH-P2WPKH" transaction, unsigned
+    pub const BIP143_P2SH_P2WPKH_UNSIGNED: &str = "0100000001db6b1b20aa0fd7b23880be2ecbd4a98130974cf4748fb66092ac4d3ceb1a54770100000000feffffff02b8b4eb0b000000001976a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac0008af2f000000001976a914fd270b1ee6abcaea97fea7ad0402e8bd8ad6d77c88ac92040000";
+
+    /// One-input P2PKH spend signed with SIGHASH_ALL by the key whose hash is in `P2PKH_SCRIPT`
+    pub const P2PKH_SPEND: &str = "0100000001169e1e83e930853391bc6f35f605c6754cfead57cf8387639d3b4096c5fc1841000000006b483045022100dcb93f33cb6d6f14d009f88173c9ecd8d9703e058dc4d4720ccd182a9360ad770220013dc7421617e003e7f3cce66d77640eddb3c0a879e8e65de2e5bfede3d1970d0121031f83fe066040c289642d09e4c857eb73b1554cf708ccee8eb1b339c0df591cf2ffffffff0180f0fa02000000001976a914fab0fe2a7bf128666bcbf1f5b44879a5d622584388ac00000000";
+    pub const P2PKH_SCRIPT: &str = "76a914fab0fe2a7bf128666bcbf1f5b44879a5d622584388ac";
+    pub const P2PKH_VALUE: u64 = 100_000_000;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(hex_tx: &str) -> Transaction {
+        Transaction::parse(&hex::decode(hex_tx).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_bip143_native_p2wpkh_vector() {
+        let tx = tx(fixtures::BIP143_NATIVE_P2WPKH_UNSIGNED);
+        let script_code = hex::decode("76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac").unwrap();
+        let sighash = segwit_v0_sighash(&tx, 1, &script_code, 600_000_000, SIGHASH_ALL).unwrap();
+        assert_eq!(hex::encode(sighash), "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670");
+    }
+
+    #[test]
+    fn test_bip143_p2sh_p2wpkh_vector() {
+        let tx = tx(fixtures::BIP143_P2SH_P2WPKH_UNSIGNED);
+        let script_code = hex::decode("76a91479091972186c449eb1ded22b78e40d009bdf008988ac").unwrap();
+        let sighash = segwit_v0_sighash(&tx, 0, &script_code, 1_000_000_000, SIGHASH_ALL).unwrap();
+        assert_eq!(hex::encode(sighash), "64f3b0f4dd2bb3aa1ce8566d220cc74dda9df97d8490cc81d89d735c92e59fb6");
+    }
+
+    #[test]
+    fn test_legacy_sighash_of_bip143_p2pk_input() {
+        // The signature on input 0 of the signed BIP143 transaction commits to this hash
+        let tx = tx(fixtures::BIP143_NATIVE_P2WPKH_SIGNED);
+        let script_code = hex::decode(fixtures::BIP143_P2PK_SCRIPT).unwrap();
+        let sighash = legacy_sighash(&tx, 0, &script_code, SIGHASH_ALL).unwrap();
+        assert_eq!(hex::encode(sighash), "63cec688ee06a91e913875356dd4dea2f8e0f2a2659885372da2a37e32c7532e");
+
+        let p2pkh = self::tx(fixtures::P2PKH_SPEND);
+        let script_code = hex::decode(fixtures::P2PKH_SCRIPT).unwrap();
+        let sighash = legacy_sighash(&p2pkh, 0, &script_code, SIGHASH_ALL).unwrap();
+        assert_eq!(hex::encode(sighash), "aaf94749828666fcde9cb208fa395acc88e976838a20e721cf7245abf045d015");
+    }
+
+    #[test]
+    fn test_sighash_type_and_index_edge_cases() {
+        let tx = tx(fixtures::BIP143_NATIVE_P2WPKH_UNSIGNED);
+        let script_code = hex::decode(fixtures::BIP143_P2PK_SCRIPT).unwrap();
+        let all = legacy_sighash(&tx, 0, &script_code, SIGHASH_ALL).unwrap();
+        let none = legacy_sighash(&tx, 0, &script_code, SIGHASH_NONE).unwrap();
+        let acp = legacy_sighash(&tx, 0, &script_code, SIGHASH_ALL | SIGHASH_ANYONECANPAY).unwrap();
+        assert!(all != none && all != acp && none != acp);
+
+        let mut one_output = tx.clone();
+        one_output.outputs.truncate(1);
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(legacy_sighash(&one_output, 1, &script_code, SIGHASH_SINGLE).unwrap(), one);
+        assert_ne!(legacy_sighash(&one_output, 0, &script_code, SIGHASH_SINGLE).unwrap(), one);
+
+        assert!(matches!(
+            legacy_sighash(&tx, 2, &script_code, SIGHASH_ALL),
+            Err(ValidationError::InvalidTransaction(_))
+        ));
+        assert!(segwit_v0_sighash(&tx, 2, &script_code, 0, SIGHASH_ALL).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! Pluggable input signature verification.
+//!
+//! TurboValidator does not interpret Bitcoin script. `validate_transaction_with_prevouts`
+//! hands each input to a `SignatureVerifier` instead: `NoopVerifier` accepts every input,
+//! and `LibsecpVerifier` (`secp256k1` feature) checks P2PKH and P2WPKH spends.
+
+use crate::ValidationError;
+
+/// Output spent by a transaction input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrevOut {
+    pub script_pubkey: Vec<u8>,
+    /// Amount in satoshis
+    pub value: u64,
+}
+
+impl PrevOut {
+    pub fn new(script_pubkey: impl Into<Vec<u8>>, value: u64) -> Self {
+        Self { script_pubkey: script_pubkey.into(), value }
+    }
+}
+
+/// Checks the signature of one transaction input
+pub trait SignatureVerifier: Send + Sync {
+    /// Verify input `input_index` of the serialized transaction `tx`, which spends an
+    /// output locked by `prevout_script` holding `prevout_value` satoshis
+    fn verify_input(
+        &self,
+        tx: &[u8],
+        input_index: usize,
+        prevout_script: &[u8],
+        prevout_value: u64,
+    ) -> Result<(), ValidationError>;
+}
+
+/// Accepts every input; the default, matching validation without signature checks
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopVerifier;
+
+impl SignatureVerifier for NoopVerifier {
+    fn verify_input(&self, _tx: &[u8], _input_index: usize, _script: &[u8], _value: u64) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+/// Output templates recognised by the verifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptType {
+    /// OP_DUP OP_HASH160 <20-byte key hash> OP_EQUALVERIFY OP_CHECKSIG
+    P2pkh([u8; 20]),
+    /// OP_0 <20-byte key hash>
+    P2wpkh([u8; 20]),
+    Other,
+}
+
+impl ScriptType {
+    pub fn classify(script: &[u8]) -> Self {
+        let mut hash = [0u8; 20];
+        match script {
+            [0x76, 0xa9, 0x14, key_hash @ .., 0x88, 0xac] if key_hash.len() == 20 => {
+                hash.copy_from_slice(key_hash);
+                ScriptType::P2pkh(hash)
+            }
+            [0x00, 0x14, key_hash @ ..] if key_hash.len() == 20 => {
+                hash.copy_from_slice(key_hash);
+                ScriptType::P2wpkh(hash)
+            }
+            _ => ScriptType::Other,
+        }
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+pub use libsecp::LibsecpVerifier;
+
+#[cfg(feature = "secp256k1")]
+mod libsecp {
+    use super::{ScriptType, SignatureVerifier};
+    use crate::sighash::{legacy_sighash, segwit_v0_sighash};
+    use crate::tx::Transaction;
+    use crate::ValidationError;
+    use ripemd::Ripemd160;
+    use secp256k1::{ecdsa, Message, PublicKey, Secp256k1, VerifyOnly};
+    use sha2::{Digest, Sha256};
+    use std::fmt;
+
+    fn sig_error(msg: impl Into<String>) -> ValidationError {
+        ValidationError::SignatureError(msg.into())
+    }
+
+    /// ECDSA verification of P2PKH and P2WPKH inputs via libsecp256k1
+    pub struct LibsecpVerifier {
+        secp: Secp256k1<VerifyOnly>,
+        reject_unsupported: bool,
+    }
+
+    impl Default for LibsecpVerifier {
+        fn default() -> Self {
+            Self { secp: Secp256k1::verification_only(), reject_unsupported: false }
+        }
+    }
+
+    impl fmt::Debug for LibsecpVerifier {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("LibsecpVerifier").field("reject_unsupported", &self.reject_unsupported).finish()
+        }
+    }
+
+    impl LibsecpVerifier {
+        /// Verifier that accepts inputs of other script types unchecked
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Reject inputs whose prevout is neither P2PKH nor P2WPKH
+        pub fn rejecting_unsupported(mut self) -> Self {
+            self.reject_unsupported = true;
+            self
+        }
+
+        fn check_key_hash(&self, pubkey: &[u8], expected: &[u8; 20]) -> Result<(), ValidationError> {
+            let hash = Ripemd160::digest(Sha256::digest(pubkey));
+            if hash.as_slice() != expected {
+                return Err(sig_error("Public key does not match the prevout key hash"));
+            }
+            Ok(())
+        }
+
+        /// Verify a DER signature with its trailing sighash byte against `digest_for(sighash_type)`
+        fn check_signature(
+            &self,
+            signature: &[u8],
+            pubkey: &[u8],
+            digest_for: impl FnOnce(u32) -> Result<[u8; 32], ValidationError>,
+        ) -> Result<(), ValidationError> {
+            let (sighash_type, der) = signature.split_last().ok_or_else(|| sig_error("Empty signature"))?;
+            let mut sig = ecdsa::Signature::from_der(der).map_err(|e| sig_error(format!("Malformed signature: {}", e)))?;
+            // Consensus accepts high-S signatures; libsecp256k1 only verifies low-S
+            sig.normalize_s();
+            let key = PublicKey::from_slice(pubkey).map_err(|e| sig_error(format!("Malformed public key: {}", e)))?;
+            let msg = Message::from_digest(digest_for(u32::from(*sighash_type))?);
+            self.secp.verify_ecdsa(&msg, &sig, &key).map_err(|_| sig_error("ECDSA signature does not verify"))
+        }
+    }
+
+    /// Data pushes of a push-only script
+    fn parse_pushes(script: &[u8]) -> Result<Vec<&[u8]>, ValidationError> {
+        let malformed = || sig_error("Malformed scriptSig push");
+        let mut pushes = Vec::new();
+        let mut pos = 0;
+        while pos < script.len() {
+            let opcode = script[pos];
+            pos += 1;
+            let len = match opcode {
+                0x01..=0x4b => opcode as usize,
+                // OP_PUSHDATA1, OP_PUSHDATA2, OP_PUSHDATA4
+                0x4c..=0x4e => {
+                    let width = match opcode {
+                        0x4c => 1,
+                        0x4d => 2,
+                        _ => 4,
+                    };
+                    let bytes = script.get(pos..pos + width).ok_or_else(malformed)?;
+                    pos += width;
+                    bytes.iter().rev().fold(0usize, |acc, b| (acc << 8) | *b as usize)
+                }
+                _ => return Err(sig_error(format!("scriptSig opcode {:#04x} is not a data push", opcode))),
+            };
+            let end = pos.checked_add(len).ok_or_else(malformed)?;
+            pushes.push(script.get(pos..end).ok_or_else(malformed)?);
+            pos = end;
+        }
+        Ok(pushes)
+    }
+
+    impl SignatureVerifier for LibsecpVerifier {
+        fn verify_input(
+            &self,
+            tx_bytes: &[u8],
+            input_index: usize,
+            prevout_script: &[u8],
+            prevout_value: u64,
+        ) -> Result<(), ValidationError> {
+            let tx = Transaction::parse(tx_bytes)?;
+            let input = tx.inputs.get(input_index).ok_or_else(|| {
+                ValidationError::InvalidTransaction(format!("Input index {} out of range", input_index))
+            })?;
+            match ScriptType::classify(prevout_script) {
+                ScriptType::P2pkh(key_hash) => {
+                    let pushes = parse_pushes(&input.script_sig)?;
+                    let [signature, pubkey] = &pushes[..] else {
+                        return Err(sig_error("P2PKH scriptSig must push a signature and a public key"));
+                    };
+                    self.check_key_hash(pubkey, &key_hash)?;
+                    self.check_signature(signature, pubkey, |sighash_type| {
+                        legacy_sighash(&tx, input_index, prevout_script, sighash_type)
+                    })
+                }
+                ScriptType::P2wpkh(key_hash) => {
+                    if !input.script_sig.is_empty() {
+                        return Err(sig_error("P2WPKH input must have an empty scriptSig"));
+                    }
+                    let [signature, pubkey] = &input.witness[..] else {
+                        return Err(sig_error("P2WPKH witness must hold a signature and a public key"));
+                    };
+                    self.check_key_hash(pubkey, &key_hash)?;
+                    let mut script_code = vec![0x76, 0xa9, 0x14];
+                    script_code.extend_from_slice(&key_hash);
+                    script_code.extend_from_slice(&[0x88, 0xac]);
+                    self.check_signature(signature, pubkey, |sighash_type| {
+                        segwit_v0_sighash(&tx, input_index, &script_code, prevout_value, sighash_type)
+                    })
+                }
+                ScriptType::Other if self.reject_unsupported => {
+                    Err(sig_error("Prevout script is neither P2PKH nor P2WPKH"))
+                }
+                ScriptType::Other => {
+                    log::debug!("Input {} spends an unsupported script type; signature not checked", input_index);
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_standard_scripts() {
+        let p2pkh = hex::decode(crate::sighash::fixtures::P2PKH_SCRIPT).unwrap();
+        assert!(matches!(ScriptType::classify(&p2pkh), ScriptType::P2pkh(hash) if hash[..] == p2pkh[3..23]));
+        let p2wpkh = hex::decode(crate::sighash::fixtures::BIP143_P2WPKH_SCRIPT).unwrap();
+        assert!(matches!(ScriptType::classify(&p2wpkh), ScriptType::P2wpkh(hash) if hash[..] == p2wpkh[2..]));
+        let p2pk = hex::decode(crate::sighash::fixtures::BIP143_P2PK_SCRIPT).unwrap();
+        assert_eq!(ScriptType::classify(&p2pk), ScriptType::Other);
+        assert_eq!(ScriptType::classify(&p2pkh[..24]), ScriptType::Other);
+        assert_eq!(ScriptType::classify(&[]), ScriptType::Other);
+    }
+
+    #[test]
+    fn test_noop_verifier_accepts_anything() {
+        assert!(NoopVerifier.verify_input(b"not a transaction", 7, &[], 0).is_ok());
+    }
+
+    #[cfg(feature = "secp256k1")]
+    mod libsecp {
+        use super::super::*;
+        use crate::sighash::fixtures::*;
+
+        fn bip143_signed() -> Vec<u8> {
+            hex::decode(BIP143_NATIVE_P2WPKH_SIGNED).unwrap()
+        }
+
+        #[test]
+        fn test_p2pkh_spend_verifies() {
+            let tx = hex::decode(P2PKH_SPEND).unwrap();
+            let script = hex::decode(P2PKH_SCRIPT).unwrap();
+            let verifier = LibsecpVerifier::new();
+            assert!(verifier.verify_input(&tx, 0, &script, P2PKH_VALUE).is_ok());
+
+            // Redirecting the output invalidates the SIGHASH_ALL signature
+            let mut tampered = tx.clone();
+            let last_key_hash_byte = tampered.len() - 7;
+            tampered[last_key_hash_byte] ^= 0x01;
+            assert!(matches!(
+                verifier.verify_input(&tampered, 0, &script, P2PKH_VALUE),
+                Err(ValidationError::SignatureError(_))
+            ));
+            let mut other_key = script.clone();
+            other_key[3] ^= 0x01;
+            assert!(verifier.verify_input(&tx, 0, &other_key, P2PKH_VALUE).is_err());
+        }
+
+        #[test]
+        fn test_bip143_p2wpkh_input_verifies() {
+            let tx = bip143_signed();
+            let script = hex::decode(BIP143_P2WPKH_SCRIPT).unwrap();
+            let verifier = LibsecpVerifier::new();
+            assert!(verifier.verify_input(&tx, 1, &script, BIP143_P2WPKH_VALUE).is_ok());
+            // BIP143 commits to the amount being spent
+            assert!(verifier.verify_input(&tx, 1, &script, BIP143_P2WPKH_VALUE + 1).is_err());
+        }
+
+        #[test]
+        fn test_unsupported_scripts_follow_strictness() {
+            let tx = bip143_signed();
+            let p2pk = hex::decode(BIP143_P2PK_SCRIPT).unwrap();
+            assert!(LibsecpVerifier::new().verify_input(&tx, 0, &p2pk, BIP143_P2PK_VALUE).is_ok());
+            assert!(matches!(
+                LibsecpVerifier::new().rejecting_unsupported().verify_input(&tx, 0, &p2pk, BIP143_P2PK_VALUE),
+                Err(ValidationError::SignatureError(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Spent-outpoint tracking for double-spend detection.
+//!
+//! TurboValidator consults a `SpentOutpointIndex` for every input of a
+//! transaction. Exact stores answer `Spent`/`Unspent`; probabilistic stores
+//! such as the UniversalBloomFilter answer `MaybeSpent`, which is resolved by
+//! the validator's `MaybeSpentPolicy`.
+//!
+//! The UniversalBloomFilter implementation lives in securebuffer's
+//! `bloom_filter` module: securebuffer depends on this crate, so the impl
+//! cannot sit here behind a feature without a dependency cycle.
+
+use crate::tx::Transaction;
+use crate::ValidationError;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Reference to a previous transaction output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutPoint {
+    pub txid: [u8; 32],
+    pub vout: u32,
+}
+
+impl OutPoint {
+    pub fn new(txid: [u8; 32], vout: u32) -> Self {
+        Self { txid, vout }
+    }
+
+    /// Coinbase inputs reference the null outpoint and spend nothing
+    pub fn is_null(&self) -> bool {
+        self.vout == u32::MAX && self.txid == [0u8; 32]
+    }
+}
+
+impl fmt::Display for OutPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Display txids in the conventional byte-reversed order
+        for b in self.txid.iter().rev() {
+            write!(f, "{:02x}", b)?;
+        }
+        write!(f, ":{}", self.vout)
+    }
+}
+
+/// Answer from a spent-outpoint lookup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpentLookup {
+    Unspent,
+    Spent,
+    /// Probabilistic hit that may be a false positive
+    MaybeSpent,
+}
+
+/// How to treat `SpentLookup::MaybeSpent` answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaybeSpentPolicy {
+    /// Treat a possible hit as a double spend
+    #[default]
+    Reject,
+    /// Accept the transaction and log a warning
+    Warn,
+}
+
+/// Store of outpoints spent by accepted blocks
+pub trait SpentOutpointIndex: Send + Sync {
+    /// Look up whether an outpoint has already been spent
+    fn lookup(&self, outpoint: &OutPoint) -> Result<SpentLookup, ValidationError>;
+
+    /// Record outpoints as spent
+    fn mark_spent(&self, outpoints: &[OutPoint]) -> Result<(), ValidationError>;
+}
+
+/// Exact in-memory spent-outpoint set
+#[derive(Debug, Default)]
+pub struct InMemorySpentIndex {
+    spent: Mutex<HashSet<OutPoint>>,
+}
+
+impl InMemorySpentIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.spent.lock().map(|s| s.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SpentOutpointIndex for InMemorySpentIndex {
+    fn lookup(&self, outpoint: &OutPoint) -> Result<SpentLookup, ValidationError> {
+        let spent = self
+            .spent
+            .lock()
+            .map_err(|_| ValidationError::Other("Spent index lock poisoned".into()))?;
+        Ok(if spent.contains(outpoint) {
+            SpentLookup::Spent
+        } else {
+            SpentLookup::Unspent
+        })
+    }
+
+    fn mark_spent(&self, outpoints: &[OutPoint]) -> Result<(), ValidationError> {
+        let mut spent = self
+            .spent
+            .lock()
+            .map_err(|_| ValidationError::Other("Spent index lock poisoned".into()))?;
+        spent.extend(outpoints.iter().copied());
+        Ok(())
+    }
+}
+
+/// Extract the input outpoints from a serialized Bitcoin transaction
+/// (legacy or segwit encoding). Coinbase inputs are skipped.
+pub fn parse_tx_inputs(tx: &[u8]) -> Result<Vec<OutPoint>, ValidationError> {
+    let parsed = Transaction::parse(tx)?;
+    Ok(parsed.inputs.into_iter().map(|input| input.previous_output).filter(|outpoint| !outpoint.is_null()).collect())
+}
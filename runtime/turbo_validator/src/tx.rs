@@ -0,0 +1,452 @@
+//! Transaction and block-body parsing.
+//!
+//! Txids are the double-SHA256 of a transaction with the segwit marker, flag and
+//! witness data removed, kept in internal byte order. The validator uses this module
+//! for the Merkle root and double-spend checks, and securebuffer's bloom-filter block
+//! loader uses it to extract txids.
+
+use crate::header::{BlockHeader, HEADER_SIZE};
+use crate::spent::OutPoint;
+use crate::ValidationError;
+use sha2::{Digest, Sha256};
+
+/// Smallest possible serialized transaction, used to bound allocations
+const MIN_TRANSACTION_LEN: usize = 60;
+
+/// Smallest possible serialized input: outpoint, empty script and sequence
+const MIN_INPUT_LEN: usize = 41;
+
+/// Smallest possible serialized output: value and empty script
+const MIN_OUTPUT_LEN: usize = 9;
+
+/// Transaction input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxIn {
+    /// Output being spent; the null outpoint for a coinbase
+    pub previous_output: OutPoint,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    /// Witness stack, empty for legacy inputs
+    pub witness: Vec<Vec<u8>>,
+}
+
+/// Transaction output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOut {
+    /// Amount in satoshis
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+/// A parsed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: i32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+    pub txid: [u8; 32],
+    /// Serialized length including witness data
+    pub size: usize,
+    /// Serialized length without the segwit marker, flag and witnesses
+    pub stripped_size: usize,
+}
+
+impl Transaction {
+    /// Parse exactly one serialized transaction (legacy or segwit encoding)
+    pub fn parse(tx: &[u8]) -> Result<Self, ValidationError> {
+        let mut reader = ByteReader::new(tx);
+        let parsed = Self::read(&mut reader)?;
+        if reader.remaining() != 0 {
+            return Err(ValidationError::InvalidTransaction(format!(
+                "Trailing data at offset {}",
+                reader.position()
+            )));
+        }
+        Ok(parsed)
+    }
+
+    /// Consume one transaction from `reader`
+    pub(crate) fn read(reader: &mut ByteReader<'_>) -> Result<Self, ValidationError> {
+        let start = reader.position();
+        let version = reader.take(4)?;
+
+        // Segwit serialization inserts a 0x00 marker and 0x01 flag after the version
+        let segwit = reader.peek() == Some(0x00);
+        if segwit && reader.take(2)?[1] != 0x01 {
+            return Err(ValidationError::InvalidTransaction("Invalid segwit flag".into()));
+        }
+
+        let body_start = reader.position();
+        let input_count = reader.read_varint()?;
+        if input_count == 0 {
+            return Err(ValidationError::InvalidTransaction("Transaction has no inputs".into()));
+        }
+        if input_count > (reader.remaining() / MIN_INPUT_LEN) as u64 {
+            return Err(ValidationError::InvalidTransaction("Input count exceeds transaction size".into()));
+        }
+        let mut inputs = Vec::with_capacity(input_count as usize);
+        for _ in 0..input_count {
+            let mut txid = [0u8; 32];
+            txid.copy_from_slice(reader.take(32)?);
+            let vout = reader.read_u32_le()?;
+            inputs.push(TxIn {
+                previous_output: OutPoint::new(txid, vout),
+                script_sig: reader.read_var_bytes()?.to_vec(),
+                sequence: reader.read_u32_le()?,
+                witness: Vec::new(),
+            });
+        }
+        let output_count = reader.read_varint()?;
+        if output_count > (reader.remaining() / MIN_OUTPUT_LEN) as u64 {
+            return Err(ValidationError::InvalidTransaction("Output count exceeds transaction size".into()));
+        }
+        let mut outputs = Vec::with_capacity(output_count as usize);
+        for _ in 0..output_count {
+            let b = reader.take(8)?;
+            outputs.push(TxOut {
+                value: u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]),
+                script_pubkey: reader.read_var_bytes()?.to_vec(),
+            });
+        }
+        let body = reader.slice(body_start, reader.position());
+
+        if segwit {
+            for input in &mut inputs {
+                let items = reader.read_varint()?;
+                // Every item has at least a length byte
+                if items > reader.remaining() as u64 {
+                    return Err(truncated());
+                }
+                for _ in 0..items {
+                    input.witness.push(reader.read_var_bytes()?.to_vec());
+                }
+            }
+        }
+        let lock_time = reader.take(4)?;
+
+        Ok(Self {
+            version: i32::from_le_bytes([version[0], version[1], version[2], version[3]]),
+            inputs,
+            outputs,
+            lock_time: u32::from_le_bytes([lock_time[0], lock_time[1], lock_time[2], lock_time[3]]),
+            txid: double_sha256(&[version, body, lock_time]),
+            size: reader.position() - start,
+            stripped_size: version.len() + body.len() + lock_time.len(),
+        })
+    }
+
+    /// BIP 141 weight: three times the stripped size plus the full size
+    pub fn weight(&self) -> usize {
+        self.stripped_size * 3 + self.size
+    }
+
+    /// Weight in virtual bytes, rounded up
+    pub fn vsize(&self) -> usize {
+        self.weight().div_ceil(4)
+    }
+}
+
+/// Parse the transaction list that follows the header of a serialized block
+pub fn parse_block_transactions(block: &[u8]) -> Result<Vec<Transaction>, ValidationError> {
+    let mut reader = ByteReader::new(block);
+    reader.take(HEADER_SIZE).map_err(|_| {
+        ValidationError::InvalidBlock(format!("Header truncated at offset {}: need {} bytes", block.len(), HEADER_SIZE))
+    })?;
+    let tx_count = reader.read_varint().map_err(|_| {
+        ValidationError::InvalidBlock(format!("Transaction count truncated at offset {}", HEADER_SIZE))
+    })?;
+    if tx_count == 0 {
+        return Err(ValidationError::InvalidBlock("Block has no transactions".into()));
+    }
+    if tx_count > (reader.remaining() / MIN_TRANSACTION_LEN) as u64 {
+        return Err(ValidationError::InvalidBlock("Transaction count exceeds block size".into()));
+    }
+
+    let mut transactions = Vec::with_capacity(tx_count as usize);
+    for index in 0..tx_count {
+        let offset = reader.position();
+        let tx = Transaction::read(&mut reader).map_err(|e| {
+            ValidationError::InvalidBlock(format!("Transaction {} at offset {}: {}", index, offset, e))
+        })?;
+        transactions.push(tx);
+    }
+    if reader.remaining() != 0 {
+        return Err(ValidationError::InvalidBlock(format!(
+            "Trailing data after last transaction at offset {}",
+            reader.position()
+        )));
+    }
+    Ok(transactions)
+}
+
+/// Bitcoin Merkle root over txids, duplicating the last entry of odd-length levels.
+/// An empty list yields the all-zero hash.
+pub fn merkle_root(txids: &[[u8; 32]]) -> [u8; 32] {
+    if txids.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = txids.to_vec();
+    while level.len() > 1 {
+        if level.len() % 2 == 1 {
+            level.push(level[level.len() - 1]);
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| double_sha256(&[&pair[0], &pair[1]]))
+            .collect();
+    }
+    level[0]
+}
+
+/// Check that `transactions` hash to the header's Merkle root
+pub fn check_merkle_root(header: &BlockHeader, transactions: &[Transaction]) -> Result<(), ValidationError> {
+    let txids: Vec<[u8; 32]> = transactions.iter().map(|tx| tx.txid).collect();
+    let computed = merkle_root(&txids);
+    if computed != header.merkle_root {
+        let (mut got, mut want) = (computed, header.merkle_root);
+        got.reverse();
+        want.reverse();
+        return Err(ValidationError::InvalidBlock(format!(
+            "Merkle root {} of {} transactions does not match header merkle_root {} at offset 36",
+            hex::encode(got),
+            transactions.len(),
+            hex::encode(want)
+        )));
+    }
+    Ok(())
+}
+
+/// Append a consensus-encoded varint
+pub(crate) fn write_varint(out: &mut Vec<u8>, n: u64) {
+    match n {
+        0..=0xfc => out.push(n as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+}
+
+/// Append a varint-prefixed byte string
+pub(crate) fn write_var_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+pub(crate) fn double_sha256(parts: &[&[u8]]) -> [u8; 32] {
+    let mut engine = Sha256::new();
+    for part in parts {
+        engine.update(part);
+    }
+    Sha256::digest(engine.finalize()).into()
+}
+
+fn truncated() -> ValidationError {
+    ValidationError::InvalidTransaction("Truncated transaction".into())
+}
+
+/// Minimal cursor over consensus-encoded bytes
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn slice(&self, start: usize, end: usize) -> &'a [u8] {
+        &self.data[start..end]
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], ValidationError> {
+        let end = self.pos.checked_add(n).ok_or_else(truncated)?;
+        if end > self.data.len() {
+            return Err(truncated());
+        }
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    pub(crate) fn read_u32_le(&mut self) -> Result<u32, ValidationError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub(crate) fn read_varint(&mut self) -> Result<u64, ValidationError> {
+        let prefix = self.take(1)?[0];
+        Ok(match prefix {
+            0xfd => {
+                let b = self.take(2)?;
+                u16::from_le_bytes([b[0], b[1]]) as u64
+            }
+            0xfe => self.read_u32_le()? as u64,
+            0xff => {
+                let b = self.take(8)?;
+                u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+            }
+            n => n as u64,
+        })
+    }
+
+    /// Read a varint-prefixed byte string
+    fn read_var_bytes(&mut self) -> Result<&'a [u8], ValidationError> {
+        let len = self.read_varint()?;
+        self.take(usize::try_from(len).map_err(|_| truncated())?)
+    }
+}
+
+/// Real blocks with their transactions
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::header::fixtures::GENESIS_HEADER;
+
+    /// The genesis coinbase (txid 4a5e1e4b...)
+    pub const GENESIS_COINBASE: &str = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    /// Mainnet block 170 (hash 00000000d1145790...): a coinbase plus the first
+    /// person-to-person payment, txid f4184fc5...
+    pub const BLOCK_170: &str = "0100000055bd840a78798ad0da853f68974f3d183e2bd1db6a842c1feecf222a00000000ff104ccb05421ab93e63f8c3ce5c2c2e9dbb37de2764b3a3175c8166562cac7d51b96a49ffff001d283e9e700201000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0704ffff001d0102ffffffff0100f2052a01000000434104d46c4968bde02899d2aa0963367c7a6ce34eec332b32e42e5f3407e052d64ac625da6f0718e7b302140434bd725706957c092db53805b821a85b23a7ac61725bac000000000100000001c997a5e56e104102fa209c6a852dd90660a20b2d9c352423edce25857fcd3704000000004847304402204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd410220181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d0901ffffffff0200ca9a3b00000000434104ae1a62fe09c5f51b13905f07f06b99a2f7159b2225f374cd378d71302fa28414e7aab37397f554a7df5f142c21c1b7303b8a0626f1baded5c72a704f7e6cd84cac00286bee0000000043410411db93e1dcdb8a016b49840f8c53bc1eb68a382e97b1482ecad7b148a6909a5cb2e0eaddfb84ccf9744464f82e160bfa9b8b64f9d4c03f999b8643f656b412a3ac00000000";
+
+    /// Mainnet genesis block: header, one transaction
+    pub fn genesis_block() -> Vec<u8> {
+        hex::decode(format!("{}01{}", GENESIS_HEADER, GENESIS_COINBASE)).unwrap()
+    }
+
+    pub fn block_170() -> Vec<u8> {
+        hex::decode(BLOCK_170).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display_hex(hash: &[u8; 32]) -> String {
+        hash.iter().rev().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_block_170_merkle_root_matches_header() {
+        let block = fixtures::block_170();
+        let header = BlockHeader::parse(&block).unwrap();
+        assert_eq!(header.block_hash_hex(), "00000000d1145790a8694403d4063f323d499e655c83426834d4ce2f8dd4a2ee");
+
+        let txs = parse_block_transactions(&block).unwrap();
+        let txids: Vec<String> = txs.iter().map(|tx| display_hex(&tx.txid)).collect();
+        assert_eq!(
+            txids,
+            vec![
+                "b1fea52486ce0c62bb442b530a3f0132b826c74e473d1f2c220bfa78111c5082",
+                "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16",
+            ]
+        );
+        assert!(txs[0].inputs[0].previous_output.is_null());
+        assert_eq!(txs[1].outputs.iter().map(|o| o.value).collect::<Vec<_>>(), vec![1_000_000_000, 4_000_000_000]);
+        assert_eq!(
+            txs[1].inputs[0].previous_output.to_string(),
+            "0437cd7f8525ceed2324359c2d0ba26006d92d856a9c20fa0241106ee5a597c9:0"
+        );
+        assert!(check_merkle_root(&header, &txs).is_ok());
+        assert_eq!(display_hex(&merkle_root(&[txs[0].txid, txs[1].txid])), "7dac2c5666815c17a3b36427de37bb9d2e2c5ccec3f8633eb91a4205cb4c10ff");
+    }
+
+    #[test]
+    fn test_flipped_tx_byte_breaks_merkle_root() {
+        let block = fixtures::block_170();
+        // A byte inside the second transaction's first output value
+        let offset = block.len() - 150;
+        let mut mutated = block.clone();
+        mutated[offset] ^= 0x01;
+
+        let header = BlockHeader::parse(&mutated).unwrap();
+        let txs = parse_block_transactions(&mutated).unwrap();
+        match check_merkle_root(&header, &txs) {
+            Err(ValidationError::InvalidBlock(msg)) => assert!(msg.contains("does not match header merkle_root"), "{}", msg),
+            other => panic!("expected a Merkle mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_odd_levels_duplicate_last_node() {
+        let hash = |a: &[u8; 32], b: &[u8; 32]| -> [u8; 32] {
+            Sha256::digest(Sha256::digest([a.as_slice(), b.as_slice()].concat())).into()
+        };
+        let (a, b, c) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+        assert_eq!(merkle_root(&[a]), a);
+        assert_eq!(merkle_root(&[a, b, c]), hash(&hash(&a, &b), &hash(&c, &c)));
+        assert_eq!(merkle_root(&[a, b, c]), merkle_root(&[a, b, c, c]));
+        assert_eq!(merkle_root(&[]), [0u8; 32]);
+    }
+
+    #[test]
+    fn test_segwit_txid_excludes_witness() {
+        // Version 2, one input with a one-item witness, one output
+        let mut tx = hex::decode("02000000").unwrap();
+        let mut legacy = tx.clone();
+        let mut body = vec![1u8];
+        body.extend_from_slice(&[0xaa; 32]);
+        body.extend_from_slice(&[0, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]);
+        body.extend_from_slice(&[1, 0x10, 0x27, 0, 0, 0, 0, 0, 0, 1, 0x51]);
+        tx.extend_from_slice(&[0x00, 0x01]);
+        tx.extend_from_slice(&body);
+        tx.extend_from_slice(&[1, 3, 0xde, 0xad, 0xbe]);
+        tx.extend_from_slice(&[0, 0, 0, 0]);
+        legacy.extend_from_slice(&body);
+        legacy.extend_from_slice(&[0, 0, 0, 0]);
+
+        let segwit = Transaction::parse(&tx).unwrap();
+        let stripped = Transaction::parse(&legacy).unwrap();
+        assert_eq!(segwit.txid, stripped.txid);
+        assert_eq!(segwit.inputs[0].witness, vec![vec![0xde, 0xad, 0xbe]]);
+        assert_eq!((segwit.version, segwit.outputs[0].value, segwit.outputs[0].script_pubkey.clone()), (2, 10_000, vec![0x51]));
+        assert_eq!((segwit.size, segwit.stripped_size), (tx.len(), legacy.len()));
+        assert_eq!(stripped.vsize(), legacy.len());
+        assert_eq!(segwit.weight(), legacy.len() * 3 + tx.len());
+    }
+
+    #[test]
+    fn test_malformed_bodies_are_rejected() {
+        let block = fixtures::genesis_block();
+        assert_eq!(parse_block_transactions(&block).unwrap().len(), 1);
+
+        let invalid_block = |block: &[u8], needle: &str| match parse_block_transactions(block) {
+            Err(ValidationError::InvalidBlock(msg)) => assert!(msg.contains(needle), "{}", msg),
+            other => panic!("expected InvalidBlock containing {:?}, got {:?}", needle, other),
+        };
+        invalid_block(&block[..HEADER_SIZE], "count truncated");
+        invalid_block(&block[..block.len() - 1], "Transaction 0 at offset 81");
+        let mut trailing = block.clone();
+        trailing.push(0);
+        invalid_block(&trailing, "Trailing data");
+        let mut empty = block[..HEADER_SIZE].to_vec();
+        empty.push(0);
+        invalid_block(&empty, "no transactions");
+
+        assert!(Transaction::parse(&block[HEADER_SIZE + 1..]).is_ok());
+        assert!(Transaction::parse(&block[HEADER_SIZE..]).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Replay check on the challenge path with 500k beacons resident: the time-bucketed
+//! BeaconReplaySet against the previous beacon -> timestamp map, which ran an O(n)
+//! retain once it held more than 10k entries. Prints p99 per insert before the
+//! criterion groups run.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use securebuffer::storage_verifier::BeaconReplaySet;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const RESIDENT: u64 = 500_000;
+const NOW: u64 = 1_700_000_000;
+const P99_SAMPLES: u64 = 1_000;
+
+/// The replaced design: a map of every beacon with a full scan past 10k entries
+#[derive(Default)]
+struct RetainMap {
+    beacons: HashMap<String, u64>,
+}
+
+impl RetainMap {
+    fn insert(&mut self, beacon: String, now: u64) -> bool {
+        if self.beacons.contains_key(&beacon) {
+            return false;
+        }
+        self.beacons.insert(beacon, now);
+        if self.beacons.len() > 10_000 {
+            self.beacons.retain(|_, ts| now.saturating_sub(*ts) < 3600);
+        }
+        true
+    }
+}
+
+fn beacon(i: u64) -> String {
+    format!("{:064x}", i)
+}
+
+/// Resident beacons spread over the last hour, so none of them has expired yet
+fn resident_timestamps() -> impl Iterator<Item = (String, u64)> {
+    (0..RESIDENT).map(|i| (beacon(i), NOW - i % 3600))
+}
+
+fn bucketed() -> BeaconReplaySet {
+    let mut set = BeaconReplaySet::default();
+    // Insert oldest first, as the verifier would have
+    let mut resident: Vec<_> = resident_timestamps().collect();
+    resident.sort_by_key(|(_, ts)| *ts);
+    for (b, ts) in resident {
+        set.insert(b, ts);
+    }
+    set
+}
+
+fn retain_map() -> RetainMap {
+    RetainMap { beacons: resident_timestamps().collect() }
+}
+
+fn p99(mut insert: impl FnMut(String) -> bool) -> Duration {
+    let mut samples: Vec<Duration> = (0..P99_SAMPLES)
+        .map(|i| {
+            let b = beacon(RESIDENT + i);
+            let start = Instant::now();
+            assert!(insert(b));
+            start.elapsed()
+        })
+        .collect();
+    samples.sort();
+    samples[samples.len() * 99 / 100]
+}
+
+fn bench_replay_check(c: &mut Criterion) {
+    let mut set = bucketed();
+    let mut map = retain_map();
+    println!("p99 insert, {} resident: bucketed {:?}", RESIDENT, p99(|b| set.insert(b, NOW)));
+    println!("p99 insert, {} resident: retain map {:?}", RESIDENT, p99(|b| map.insert(b, NOW)));
+
+    let mut group = c.benchmark_group("beacon_replay_500k_resident");
+    group.sample_size(10);
+
+    let mut set = bucketed();
+    let mut next = RESIDENT * 2;
+    group.bench_function("time_bucketed", |b| {
+        b.iter(|| {
+            next += 1;
+            set.insert(beacon(next), NOW)
+        })
+    });
+
+    let mut map = retain_map();
+    let mut next = RESIDENT * 2;
+    group.bench_function("hashmap_retain", |b| {
+        b.iter(|| {
+            next += 1;
+            map.insert(beacon(next), NOW)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_replay_check);
+criterion_main!(benches);
@@ -0,0 +1,97 @@
+//! 8 threads doing mixed insert/contains: the lock-free UniversalBloomFilter
+//! against a reference bit array guarded by a single Mutex (the previous design).
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use securebuffer::bloom_filter::{BloomConfig, NetworkConfig, TransactionId, UniversalBloomFilter};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+const THREADS: u8 = 8;
+const OPS_PER_THREAD: u32 = 2_000;
+
+/// Reference bloom filter with a lock around the whole bit vector
+struct LockedBloom {
+    bits: Mutex<Vec<u64>>,
+    size: u64,
+    num_hashes: u64,
+}
+
+impl LockedBloom {
+    fn new(size: u64, num_hashes: u64) -> Self {
+        Self { bits: Mutex::new(vec![0; (size / 64) as usize]), size, num_hashes }
+    }
+
+    fn positions(&self, data: &[u8]) -> impl Iterator<Item = u64> + '_ {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        let h = hasher.finish();
+        (0..self.num_hashes).map(move |i| h.wrapping_add(i.wrapping_mul(0x9E37_79B9_7F4A_7C15)) % self.size)
+    }
+
+    fn insert(&self, data: &[u8]) {
+        let mut bits = self.bits.lock().unwrap();
+        for pos in self.positions(data) {
+            bits[(pos >> 6) as usize] |= 1 << (pos & 63);
+        }
+    }
+
+    fn contains(&self, data: &[u8]) -> bool {
+        let bits = self.bits.lock().unwrap();
+        self.positions(data).all(|pos| bits[(pos >> 6) as usize] & (1 << (pos & 63)) != 0)
+    }
+}
+
+fn key(thread: u8, i: u32) -> [u8; 32] {
+    let mut bytes = [thread; 32];
+    bytes[..4].copy_from_slice(&i.to_le_bytes());
+    bytes
+}
+
+fn run_threads<F: Fn(u8, u32) + Send + Sync + 'static>(op: Arc<F>) {
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let op = op.clone();
+            std::thread::spawn(move || (0..OPS_PER_THREAD).for_each(|i| op(t, i)))
+        })
+        .collect();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+}
+
+fn bench_mixed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bloom_8_threads_mixed");
+    group.sample_size(20);
+
+    group.bench_function("lock_free_atomic", |b| {
+        b.iter(|| {
+            let filter =
+                Arc::new(UniversalBloomFilter::new(Some(BloomConfig::high_performance(NetworkConfig::bitcoin()))).unwrap());
+            run_threads(Arc::new(move |t, i| {
+                let txid = TransactionId::new("bitcoin", &key(t, i));
+                if i % 2 == 0 {
+                    filter.insert_utxo(&txid, i).unwrap();
+                } else {
+                    let _ = filter.contains_utxo(&txid, i - 1).unwrap();
+                }
+            }));
+        })
+    });
+
+    group.bench_function("mutex_bit_vector", |b| {
+        b.iter(|| {
+            let filter = Arc::new(LockedBloom::new(131_072, 7));
+            run_threads(Arc::new(move |t, i| {
+                if i % 2 == 0 {
+                    filter.insert(&key(t, i));
+                } else {
+                    let _ = filter.contains(&key(t, i - 1));
+                }
+            }));
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_mixed);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+//! Cost of tamper detection on 64KB SecureBuffers: plain writes vs. writes that
+//! refresh the keyed checksum, plus the standalone integrity check.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use securebuffer::SecureBuffer;
+
+const SIZE: usize = 64 * 1024;
+
+fn bench_tamper_detection(c: &mut Criterion) {
+    let payload = vec![0x5Au8; SIZE];
+    let mut group = c.benchmark_group("securebuffer_64k");
+
+    let mut plain = SecureBuffer::new(SIZE).expect("allocate");
+    group.bench_function("write", |b| b.iter(|| plain.write(black_box(&payload)).unwrap()));
+
+    let mut guarded = SecureBuffer::new(SIZE).expect("allocate");
+    guarded.enable_tamper_detection().expect("enable tamper detection");
+    group.bench_function("write_with_tamper_detection", |b| {
+        b.iter(|| guarded.write(black_box(&payload)).unwrap())
+    });
+    group.bench_function("is_tampered", |b| b.iter(|| black_box(guarded.is_tampered())));
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_tamper_detection);
+criterion_main!(benches);
@@ -8,4 +8,11 @@ fn main() {
         println!("cargo:rustc-link-lib=crypt32");
         println!("cargo:rustc-link-lib=kernel32");
     }
+
+    // Client and server stubs for the gRPC interface
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/sprint.proto");
+        tonic_build::compile_protos("proto/sprint.proto").expect("compiling proto/sprint.proto");
+    }
 }
@@ -53,9 +53,25 @@ bool bloom_filter_contains(const UniversalBloomFilter* filter, const uint8_t* da
 // Get item count
 uint64_t bloom_filter_count(const UniversalBloomFilter* filter);
 
-// Get false positive rate
+// Get theoretical false positive rate (same as bloom_filter_theoretical_fp_rate;
+// older builds returned the raw false positive count here)
 double bloom_filter_false_positive_rate(const UniversalBloomFilter* filter);
 
+// Get theoretical false positive rate from the current fill level
+double bloom_filter_theoretical_fp_rate(const UniversalBloomFilter* filter);
+
+// Get observed false positive rate (confirmed false positives / total queries)
+double bloom_filter_observed_fp_rate(const UniversalBloomFilter* filter);
+
+// Get number of confirmed false positives
+uint64_t bloom_filter_observed_fp_count(const UniversalBloomFilter* filter);
+
+// Get number of lookups answered by the filter
+uint64_t bloom_filter_query_count(const UniversalBloomFilter* filter);
+
+// Report that a bloom hit was not present in the full index (0 on success)
+int bloom_filter_confirm_false_positive(UniversalBloomFilter* filter);
+
 // Reset Bloom Filter
 void bloom_filter_reset(UniversalBloomFilter* filter);
 
@@ -240,6 +240,10 @@ extern "C"
 		size_t headers_len,
 		size_t header_count);
 
+	// Replace buffer contents with 32 bytes of entropy
+	// kind: 0 = fast, 1 = hybrid, 2 = enterprise; returns 0 on success, -1 on error
+	SECUREBUFFER_API int fill_secure_buffer_entropy_c(void *buffer, int kind);
+
 	// === Bitcoin Bloom Filter API ===
 	// Create new Bitcoin Bloom Filter with optimized configuration
 	SECUREBUFFER_API void *bitcoin_bloom_filter_new(
@@ -289,7 +293,7 @@ extern "C"
 		const uint8_t *block_data,
 		size_t block_size);
 
-	// Get bloom filter statistics
+	// Get bloom filter statistics; saturation_ratio is items held / capacity at a 1% false positive rate
 	SECUREBUFFER_API int bitcoin_bloom_filter_get_stats(
 		void *filter,
 		uint64_t *item_count,
@@ -297,7 +301,8 @@ extern "C"
 		double *theoretical_fp_rate,
 		size_t *memory_usage_bytes,
 		size_t *timestamp_entries,
-		double *average_age_seconds);
+		double *average_age_seconds,
+		double *saturation_ratio);
 
 	// Get theoretical false positive rate
 	SECUREBUFFER_API double bitcoin_bloom_filter_false_positive_rate(void *filter);
@@ -334,6 +339,28 @@ extern "C"
 		unsigned char *output
 	);
 
+	// === Admin Secret Rotation ===
+	// Versioned admin secrets; MACs under a retired version verify until its grace window ends
+	SECUREBUFFER_API void *admin_secret_manager_new(size_t max_previous, uint64_t grace_secs);
+	SECUREBUFFER_API void admin_secret_manager_free(void *manager);
+	SECUREBUFFER_API uint32_t admin_secret_current_version(const void *manager);
+	// Returns 0 on success, -1 on error
+	SECUREBUFFER_API int admin_secret_rotate(void *manager, uint32_t *out_version);
+	// Writes a 32-byte MAC; returns 0 on success, -1 on error
+	SECUREBUFFER_API int admin_secret_mac(
+		const void *manager,
+		const uint8_t *payload,
+		size_t payload_len,
+		uint8_t *out_mac,
+		uint32_t *out_version);
+	// Returns the matching version (> 0), 0 if no live version matches, -1 on invalid arguments
+	SECUREBUFFER_API int64_t admin_secret_verify(
+		const void *manager,
+		const uint8_t *mac,
+		size_t mac_len,
+		const uint8_t *payload,
+		size_t payload_len);
+
 	// === Error Handling ===
 	SECUREBUFFER_API const char *securebuffer_error_string(SecureBufferError error);
 	SECUREBUFFER_API SecureBufferError securebuffer_get_last_error(void);
@@ -0,0 +1,603 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Versioned admin secrets
+
+//! Admin secret rotation.
+//!
+//! `AdminSecretManager` keeps the current admin secret plus up to `max_previous`
+//! retired versions. Tokens carry an HMAC-SHA256 of their payload; `verify` tries the
+//! current secret first and then every retired secret still inside its grace window,
+//! so a rotation does not invalidate tokens issued just before it.
+//!
+//! With `persist_to` the version set is sealed under a key-encryption key (KEK) and
+//! rewritten after every rotation. The file is `MAGIC | nonce | ciphertext | tag`:
+//! the ciphertext is the plaintext XORed with an HMAC-SHA256 counter-mode keystream
+//! and the tag is HMAC-SHA256 over everything before it, each under its own key
+//! derived from the KEK. Secrets are only ever decrypted into `SecureBuffer`s.
+
+use std::fs;
+use std::io::{self, Write};
+use std::os::raw::c_int;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::clock::{Clock, SystemClock};
+use crate::entropy;
+use crate::securebuffer_entropy::hmac_sha256;
+use crate::SecureBuffer;
+
+/// Length of every admin secret and MAC
+pub const ADMIN_SECRET_LEN: usize = 32;
+/// Environment variable holding the hex-encoded 32-byte KEK
+pub const KEK_ENV: &str = "ADMIN_SECRET_KEK";
+
+const STORE_MAGIC: &[u8; 4] = b"SAS1";
+const NONCE_LEN: usize = 16;
+const TAG_LEN: usize = 32;
+/// version u32, created_at u64, retired_at u64 (`CURRENT_MARKER` while current), secret
+const RECORD_LEN: usize = 4 + 8 + 8 + ADMIN_SECRET_LEN;
+const CURRENT_MARKER: u64 = u64::MAX;
+
+#[derive(Debug, Error)]
+pub enum AdminSecretError {
+    #[error("Invalid key-encryption key: {0}")]
+    InvalidKek(String),
+    #[error("Secret store {path}: {reason}")]
+    Store { path: String, reason: String },
+    #[error("Secret store {0} failed authentication")]
+    Tampered(String),
+    #[error("SecureBuffer error: {0}")]
+    Buffer(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct AdminSecretConfig {
+    /// Retired versions kept for verification
+    pub max_previous: usize,
+    /// How long a retired version keeps verifying after the rotation that retired it
+    pub grace: Duration,
+}
+
+impl Default for AdminSecretConfig {
+    fn default() -> Self {
+        Self { max_previous: 3, grace: Duration::from_secs(24 * 60 * 60) }
+    }
+}
+
+/// Public metadata of one secret version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SecretVersionInfo {
+    pub version: u32,
+    pub created_at: u64,
+    /// When a rotation replaced this version; `None` for the current one
+    pub retired_at: Option<u64>,
+}
+
+struct SecretVersion {
+    info: SecretVersionInfo,
+    secret: SecureBuffer,
+}
+
+impl SecretVersion {
+    fn generate(version: u32, created_at: u64) -> Result<Self, AdminSecretError> {
+        let material = Zeroizing::new(entropy::enterprise_entropy(&[], &version.to_be_bytes()));
+        Self::from_bytes(SecretVersionInfo { version, created_at, retired_at: None }, &material[..])
+    }
+
+    fn from_bytes(info: SecretVersionInfo, secret: &[u8]) -> Result<Self, AdminSecretError> {
+        let mut buffer = SecureBuffer::new(ADMIN_SECRET_LEN).map_err(AdminSecretError::Buffer)?;
+        buffer.write(secret).map_err(AdminSecretError::Buffer)?;
+        Ok(Self { info, secret: buffer })
+    }
+
+    fn mac(&self, payload: &[u8]) -> Result<Zeroizing<[u8; ADMIN_SECRET_LEN]>, AdminSecretError> {
+        let key = self.secret.as_slice().map_err(AdminSecretError::Buffer)?;
+        hmac_sha256(key, &[payload]).map_err(AdminSecretError::Buffer)
+    }
+
+    /// Whether the version still verifies at `now`
+    fn is_live(&self, now: u64, grace: Duration) -> bool {
+        match self.info.retired_at {
+            None => true,
+            Some(retired_at) => now < retired_at.saturating_add(grace.as_secs()),
+        }
+    }
+}
+
+/// Current admin secret and its retired predecessors, newest first
+pub struct AdminSecretManager {
+    versions: Vec<SecretVersion>,
+    config: AdminSecretConfig,
+    clock: Arc<dyn Clock>,
+    store: Option<SealedStore>,
+}
+
+impl AdminSecretManager {
+    /// In-memory manager starting at version 1
+    pub fn new(config: AdminSecretConfig) -> Result<Self, AdminSecretError> {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    /// Manager reading the time from `clock`, e.g. a `ManualClock` in tests
+    pub fn with_clock(config: AdminSecretConfig, clock: Arc<dyn Clock>) -> Result<Self, AdminSecretError> {
+        let first = SecretVersion::generate(1, clock.now_unix())?;
+        Ok(Self { versions: vec![first], config, clock, store: None })
+    }
+
+    /// Seal the version set at `path` under `kek` from now on. If the file exists its
+    /// versions replace the freshly generated one; otherwise it is created.
+    pub fn persist_to(mut self, path: impl Into<PathBuf>, kek: SecureBuffer) -> Result<Self, AdminSecretError> {
+        let store = SealedStore::new(path.into(), kek)?;
+        match store.load()? {
+            Some(versions) => self.versions = versions,
+            None => store.save(&self.versions)?,
+        }
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    pub fn current_version(&self) -> u32 {
+        self.versions[0].info.version
+    }
+
+    /// Metadata of every version still held, newest first
+    pub fn versions(&self) -> Vec<SecretVersionInfo> {
+        self.versions.iter().map(|v| v.info).collect()
+    }
+
+    /// MAC `payload` under the current secret; returns the version used
+    pub fn mac(&self, payload: &[u8]) -> Result<(u32, [u8; ADMIN_SECRET_LEN]), AdminSecretError> {
+        let current = &self.versions[0];
+        Ok((current.info.version, *current.mac(payload)?))
+    }
+
+    /// Version whose secret produced `token_mac` over `payload`, trying the current
+    /// secret first and then retired ones still inside the grace window
+    pub fn verify(&self, token_mac: &[u8], payload: &[u8]) -> Option<u32> {
+        let now = self.clock.now_unix();
+        self.versions
+            .iter()
+            .filter(|v| v.is_live(now, self.config.grace))
+            .find(|v| v.mac(payload).is_ok_and(|expected| ct_eq(&expected[..], token_mac)))
+            .map(|v| v.info.version)
+    }
+
+    /// Replace the current secret with a fresh one and retire the old one. Versions past
+    /// `max_previous` or their grace window are wiped. With a store, the new set is
+    /// sealed first; if that fails nothing changes.
+    pub fn rotate(&mut self) -> Result<u32, AdminSecretError> {
+        let now = self.clock.now_unix();
+        let version = self.current_version().checked_add(1).ok_or_else(|| {
+            AdminSecretError::Buffer("Secret version counter exhausted".to_string())
+        })?;
+        let next = SecretVersion::generate(version, now)?;
+
+        let mut versions = std::mem::take(&mut self.versions);
+        versions[0].info.retired_at = Some(now);
+        versions.insert(0, next);
+        // Retirement times grow towards the front, so expired versions form a suffix
+        let keep = 1 + versions[1..]
+            .iter()
+            .take(self.config.max_previous)
+            .take_while(|v| v.is_live(now, self.config.grace))
+            .count();
+
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save(&versions[..keep]) {
+                versions.remove(0);
+                versions[0].info.retired_at = None;
+                self.versions = versions;
+                return Err(e);
+            }
+        }
+        // Dropped SecureBuffers wipe themselves
+        versions.truncate(keep);
+        self.versions = versions;
+        Ok(version)
+    }
+}
+
+/// Read the KEK from `ADMIN_SECRET_KEK`; `None` when the variable is unset
+pub fn kek_from_env() -> Result<Option<SecureBuffer>, AdminSecretError> {
+    let Ok(encoded) = std::env::var(KEK_ENV) else {
+        return Ok(None);
+    };
+    let encoded = Zeroizing::new(encoded);
+    let raw = Zeroizing::new(
+        hex::decode(encoded.trim()).map_err(|e| AdminSecretError::InvalidKek(format!("{}: {}", KEK_ENV, e)))?,
+    );
+    kek_from_bytes(&raw).map(Some)
+}
+
+/// Copy a 32-byte KEK into a SecureBuffer
+pub fn kek_from_bytes(raw: &[u8]) -> Result<SecureBuffer, AdminSecretError> {
+    if raw.len() != ADMIN_SECRET_LEN {
+        return Err(AdminSecretError::InvalidKek(format!("expected {} bytes, got {}", ADMIN_SECRET_LEN, raw.len())));
+    }
+    let mut kek = SecureBuffer::new(ADMIN_SECRET_LEN).map_err(AdminSecretError::Buffer)?;
+    kek.write(raw).map_err(AdminSecretError::Buffer)?;
+    Ok(kek)
+}
+
+/// Encrypt-then-MAC file holding the version set
+struct SealedStore {
+    path: PathBuf,
+    kek: SecureBuffer,
+}
+
+impl SealedStore {
+    fn new(path: PathBuf, kek: SecureBuffer) -> Result<Self, AdminSecretError> {
+        if kek.len() != ADMIN_SECRET_LEN {
+            return Err(AdminSecretError::InvalidKek(format!("expected {} bytes, got {}", ADMIN_SECRET_LEN, kek.len())));
+        }
+        Ok(Self { path, kek })
+    }
+
+    fn store_error(&self, reason: impl ToString) -> AdminSecretError {
+        AdminSecretError::Store { path: self.path.display().to_string(), reason: reason.to_string() }
+    }
+
+    fn subkey(&self, label: &[u8]) -> Result<Zeroizing<[u8; 32]>, AdminSecretError> {
+        let kek = self.kek.as_slice().map_err(AdminSecretError::Buffer)?;
+        hmac_sha256(kek, &[b"bitcoin-sprint/admin-secret/", label]).map_err(AdminSecretError::Buffer)
+    }
+
+    /// XOR `input` with the keystream for `nonce`, one 32-byte block at a time, handing
+    /// each output block to `emit` so plaintext never lands in an ordinary Vec
+    fn apply_keystream(
+        &self,
+        nonce: &[u8],
+        input: &[u8],
+        mut emit: impl FnMut(usize, &[u8]) -> Result<(), AdminSecretError>,
+    ) -> Result<(), AdminSecretError> {
+        let enc_key = self.subkey(b"enc")?;
+        for (counter, chunk) in input.chunks(32).enumerate() {
+            let keystream = hmac_sha256(&enc_key[..], &[nonce, &(counter as u32).to_be_bytes()])
+                .map_err(AdminSecretError::Buffer)?;
+            let mut block = Zeroizing::new([0u8; 32]);
+            for (i, byte) in chunk.iter().enumerate() {
+                block[i] = byte ^ keystream[i];
+            }
+            emit(counter * 32, &block[..chunk.len()])?;
+        }
+        Ok(())
+    }
+
+    fn tag(&self, sealed: &[u8]) -> Result<Zeroizing<[u8; TAG_LEN]>, AdminSecretError> {
+        let mac_key = self.subkey(b"mac")?;
+        hmac_sha256(&mac_key[..], &[sealed]).map_err(AdminSecretError::Buffer)
+    }
+
+    fn save(&self, versions: &[SecretVersion]) -> Result<(), AdminSecretError> {
+        let mut plaintext = SecureBuffer::new(4 + versions.len() * RECORD_LEN).map_err(AdminSecretError::Buffer)?;
+        plaintext.write(&(versions.len() as u32).to_be_bytes()).map_err(AdminSecretError::Buffer)?;
+        for v in versions {
+            let mut record = Zeroizing::new([0u8; RECORD_LEN]);
+            record[..4].copy_from_slice(&v.info.version.to_be_bytes());
+            record[4..12].copy_from_slice(&v.info.created_at.to_be_bytes());
+            record[12..20].copy_from_slice(&v.info.retired_at.unwrap_or(CURRENT_MARKER).to_be_bytes());
+            record[20..].copy_from_slice(v.secret.as_slice().map_err(AdminSecretError::Buffer)?);
+            plaintext.append(&record[..]).map_err(AdminSecretError::Buffer)?;
+        }
+
+        let nonce = entropy::fast_entropy();
+        let mut sealed = Vec::with_capacity(STORE_MAGIC.len() + NONCE_LEN + plaintext.len() + TAG_LEN);
+        sealed.extend_from_slice(STORE_MAGIC);
+        sealed.extend_from_slice(&nonce[..NONCE_LEN]);
+        let plaintext = plaintext.as_slice().map_err(AdminSecretError::Buffer)?;
+        self.apply_keystream(&nonce[..NONCE_LEN], plaintext, |_, block| {
+            sealed.extend_from_slice(block);
+            Ok(())
+        })?;
+        let tag = self.tag(&sealed)?;
+        sealed.extend_from_slice(&tag[..]);
+        self.write_atomically(&sealed).map_err(|e| self.store_error(e))
+    }
+
+    /// Write via a temporary file so a crash never leaves a truncated store behind
+    fn write_atomically(&self, bytes: &[u8]) -> io::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    fn load(&self) -> Result<Option<Vec<SecretVersion>>, AdminSecretError> {
+        let sealed = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(self.store_error(e)),
+        };
+        let header_len = STORE_MAGIC.len() + NONCE_LEN;
+        if sealed.len() < header_len + 4 + TAG_LEN || !sealed.starts_with(STORE_MAGIC) {
+            return Err(self.store_error("not an admin secret store"));
+        }
+        let (body, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+        if !ct_eq(&self.tag(body)?[..], tag) {
+            return Err(AdminSecretError::Tampered(self.path.display().to_string()));
+        }
+
+        let (nonce, ciphertext) = body[STORE_MAGIC.len()..].split_at(NONCE_LEN);
+        let mut plaintext = SecureBuffer::new(ciphertext.len()).map_err(AdminSecretError::Buffer)?;
+        self.apply_keystream(nonce, ciphertext, |offset, block| {
+            plaintext.write_at(offset, block).map_err(AdminSecretError::Buffer)
+        })?;
+        let plaintext = plaintext.as_slice().map_err(AdminSecretError::Buffer)?;
+
+        let count = u32::from_be_bytes(plaintext[..4].try_into().unwrap()) as usize;
+        if count == 0 || plaintext.len() != 4 + count * RECORD_LEN {
+            return Err(self.store_error("record count does not match the file length"));
+        }
+        plaintext[4..]
+            .chunks(RECORD_LEN)
+            .map(|record| {
+                let retired_at = u64::from_be_bytes(record[12..20].try_into().unwrap());
+                let info = SecretVersionInfo {
+                    version: u32::from_be_bytes(record[..4].try_into().unwrap()),
+                    created_at: u64::from_be_bytes(record[4..12].try_into().unwrap()),
+                    retired_at: (retired_at != CURRENT_MARKER).then_some(retired_at),
+                };
+                SecretVersion::from_bytes(info, &record[20..])
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+}
+
+/// Length-checked comparison that does not stop at the first differing byte
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// === FFI ===
+
+/// Create an in-memory manager; free it with `admin_secret_manager_free`.
+/// Returns null if the initial secret cannot be generated.
+#[no_mangle]
+pub extern "C" fn admin_secret_manager_new(max_previous: usize, grace_secs: u64) -> *mut AdminSecretManager {
+    let config = AdminSecretConfig { max_previous, grace: Duration::from_secs(grace_secs) };
+    match AdminSecretManager::new(config) {
+        Ok(manager) => Box::into_raw(Box::new(manager)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `manager` must be null or a pointer returned by `admin_secret_manager_new` that has
+/// not been freed. The pointer must not be used afterwards.
+pub unsafe extern "C" fn admin_secret_manager_free(manager: *mut AdminSecretManager) {
+    if !manager.is_null() {
+        drop(Box::from_raw(manager));
+    }
+}
+
+#[no_mangle]
+/// Current secret version, or 0 for a null manager
+///
+/// # Safety
+///
+/// `manager` must be null or a live pointer from `admin_secret_manager_new`.
+pub unsafe extern "C" fn admin_secret_current_version(manager: *const AdminSecretManager) -> u32 {
+    manager.as_ref().map_or(0, |m| m.current_version())
+}
+
+#[no_mangle]
+/// Rotate the secret and store the new version in `out_version`. Returns 0 on success, -1 on error.
+///
+/// # Safety
+///
+/// `manager` must be null or a live pointer from `admin_secret_manager_new` with no
+/// concurrent users. `out_version` must be null or point to a writable u32.
+pub unsafe extern "C" fn admin_secret_rotate(manager: *mut AdminSecretManager, out_version: *mut u32) -> c_int {
+    let Some(manager) = manager.as_mut() else {
+        return -1;
+    };
+    match manager.rotate() {
+        Ok(version) => {
+            if !out_version.is_null() {
+                *out_version = version;
+            }
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+#[no_mangle]
+/// Write the 32-byte MAC of `payload` under the current secret to `out_mac` and its
+/// version to `out_version`. Returns 0 on success, -1 on error.
+///
+/// # Safety
+///
+/// `manager` must be null or a live pointer from `admin_secret_manager_new`. `payload`
+/// must point to `payload_len` readable bytes (it may be null when `payload_len` is 0),
+/// `out_mac` to 32 writable bytes and `out_version` must be null or point to a writable u32.
+pub unsafe extern "C" fn admin_secret_mac(
+    manager: *const AdminSecretManager,
+    payload: *const u8,
+    payload_len: usize,
+    out_mac: *mut u8,
+    out_version: *mut u32,
+) -> c_int {
+    let Some(manager) = manager.as_ref() else {
+        return -1;
+    };
+    if out_mac.is_null() || (payload.is_null() && payload_len > 0) {
+        return -1;
+    }
+    let payload = if payload_len == 0 { &[][..] } else { std::slice::from_raw_parts(payload, payload_len) };
+    match manager.mac(payload) {
+        Ok((version, mut mac)) => {
+            std::ptr::copy_nonoverlapping(mac.as_ptr(), out_mac, ADMIN_SECRET_LEN);
+            mac.zeroize();
+            if !out_version.is_null() {
+                *out_version = version;
+            }
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+#[no_mangle]
+/// Check `mac` over `payload`. Returns the matching version (> 0), 0 if no live
+/// version matches, or -1 on invalid arguments.
+///
+/// # Safety
+///
+/// `manager` must be null or a live pointer from `admin_secret_manager_new`. `mac` and
+/// `payload` must point to `mac_len` and `payload_len` readable bytes (`payload` may be
+/// null when `payload_len` is 0).
+pub unsafe extern "C" fn admin_secret_verify(
+    manager: *const AdminSecretManager,
+    mac: *const u8,
+    mac_len: usize,
+    payload: *const u8,
+    payload_len: usize,
+) -> i64 {
+    let Some(manager) = manager.as_ref() else {
+        return -1;
+    };
+    if mac.is_null() || (payload.is_null() && payload_len > 0) {
+        return -1;
+    }
+    let mac = std::slice::from_raw_parts(mac, mac_len);
+    let payload = if payload_len == 0 { &[][..] } else { std::slice::from_raw_parts(payload, payload_len) };
+    manager.verify(mac, payload).map_or(0, i64::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    fn config(max_previous: usize, grace_secs: u64) -> AdminSecretConfig {
+        AdminSecretConfig { max_previous, grace: Duration::from_secs(grace_secs) }
+    }
+
+    fn store_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("sprint_admin_secret_{}_{:016x}.bin", name, rand::random::<u64>()))
+    }
+
+    #[test]
+    fn test_mac_verifies_through_grace_window() {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let mut manager = AdminSecretManager::with_clock(config(3, 60), clock.clone()).unwrap();
+        let (version, mac) = manager.mac(b"token:ops").unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(manager.verify(&mac, b"token:ops"), Some(1));
+        assert_eq!(manager.verify(&mac, b"token:other"), None);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(manager.rotate().unwrap(), 2);
+        assert_eq!(manager.current_version(), 2);
+        assert_eq!(manager.verify(&mac, b"token:ops"), Some(1));
+        let (_, fresh) = manager.mac(b"token:ops").unwrap();
+        assert_ne!(fresh, mac);
+        assert_eq!(manager.verify(&fresh, b"token:ops"), Some(2));
+
+        // Grace runs from the rotation, not from the version's creation
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(manager.verify(&mac, b"token:ops"), Some(1));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(manager.verify(&mac, b"token:ops"), None);
+        assert_eq!(manager.verify(&fresh, b"token:ops"), Some(2));
+    }
+
+    #[test]
+    fn test_rotation_drops_versions_past_limit() {
+        let clock = Arc::new(ManualClock::new(0));
+        let mut manager = AdminSecretManager::with_clock(config(2, 3_600), clock.clone()).unwrap();
+        let (_, first) = manager.mac(b"p").unwrap();
+        for _ in 0..3 {
+            clock.advance(Duration::from_secs(1));
+            manager.rotate().unwrap();
+        }
+        let versions = manager.versions();
+        assert_eq!(versions.iter().map(|v| v.version).collect::<Vec<_>>(), vec![4, 3, 2]);
+        assert_eq!(versions[0].retired_at, None);
+        assert_eq!(versions[1].retired_at, Some(3));
+        // Version 1 was evicted although its grace has not run out
+        assert_eq!(manager.verify(&first, b"p"), None);
+
+        let mut no_grace = AdminSecretManager::with_clock(config(3, 0), Arc::new(ManualClock::new(5))).unwrap();
+        no_grace.rotate().unwrap();
+        assert_eq!(no_grace.versions().len(), 1);
+    }
+
+    #[test]
+    fn test_sealed_store_roundtrip_and_tamper() {
+        let path = store_path("roundtrip");
+        let kek = || kek_from_bytes(&[7u8; 32]).unwrap();
+        let clock = Arc::new(ManualClock::new(500));
+
+        let mut manager = AdminSecretManager::with_clock(config(3, 600), clock.clone())
+            .unwrap()
+            .persist_to(&path, kek())
+            .unwrap();
+        let (_, old_mac) = manager.mac(b"payload").unwrap();
+        clock.advance(Duration::from_secs(10));
+        manager.rotate().unwrap();
+        drop(manager);
+
+        let sealed = fs::read(&path).unwrap();
+        assert!(sealed.starts_with(STORE_MAGIC));
+        assert!(!sealed.windows(8).any(|w| w == 500u64.to_be_bytes()), "metadata should be encrypted");
+
+        let reopened = AdminSecretManager::with_clock(config(3, 600), clock).unwrap().persist_to(&path, kek()).unwrap();
+        assert_eq!(reopened.current_version(), 2);
+        assert_eq!(reopened.versions()[1].retired_at, Some(510));
+        assert_eq!(reopened.verify(&old_mac, b"payload"), Some(1));
+
+        let wrong_kek = AdminSecretManager::new(config(3, 600)).unwrap().persist_to(&path, kek_from_bytes(&[8u8; 32]).unwrap());
+        assert!(matches!(wrong_kek, Err(AdminSecretError::Tampered(_))));
+        let mut flipped = sealed.clone();
+        flipped[STORE_MAGIC.len() + NONCE_LEN] ^= 1;
+        fs::write(&path, &flipped).unwrap();
+        let reopened = AdminSecretManager::new(config(3, 600)).unwrap().persist_to(&path, kek());
+        assert!(matches!(reopened, Err(AdminSecretError::Tampered(_))));
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(kek_from_bytes(&[1u8; 16]), Err(AdminSecretError::InvalidKek(_))));
+    }
+
+    #[test]
+    fn test_ffi_rotate_and_verify() {
+        unsafe {
+            let manager = admin_secret_manager_new(2, 3_600);
+            assert!(!manager.is_null());
+            assert_eq!(admin_secret_current_version(manager), 1);
+
+            let mut mac = [0u8; ADMIN_SECRET_LEN];
+            let mut version = 0u32;
+            let payload = b"ffi-token";
+            assert_eq!(admin_secret_mac(manager, payload.as_ptr(), payload.len(), mac.as_mut_ptr(), &mut version), 0);
+            assert_eq!(version, 1);
+
+            assert_eq!(admin_secret_rotate(manager, &mut version), 0);
+            assert_eq!(version, 2);
+            assert_eq!(admin_secret_verify(manager, mac.as_ptr(), mac.len(), payload.as_ptr(), payload.len()), 1);
+            assert_eq!(admin_secret_verify(manager, mac.as_ptr(), 16, payload.as_ptr(), payload.len()), 0);
+            assert_eq!(admin_secret_verify(std::ptr::null(), mac.as_ptr(), mac.len(), payload.as_ptr(), 9), -1);
+            assert_eq!(admin_secret_rotate(std::ptr::null_mut(), &mut version), -1);
+            admin_secret_manager_free(manager);
+        }
+    }
+}
@@ -0,0 +1,546 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - SecureBuffer structured audit events
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+
+/// Default number of events retained per buffer
+pub const DEFAULT_AUDIT_CAPACITY: usize = 64;
+
+/// Kind of security-relevant operation performed on a SecureBuffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventKind {
+    Created,
+    Write,
+    Read,
+    Zeroize,
+    Destroy,
+    LockFailed,
+    // A guard canary no longer matched when the buffer was destroyed
+    CanaryViolation,
+}
+
+/// A single audit event. Never carries buffer contents, only sizes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditEvent {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub kind: AuditEventKind,
+    pub bytes_affected: usize,
+}
+
+impl AuditEvent {
+    pub fn new(kind: AuditEventKind, bytes_affected: usize) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            kind,
+            bytes_affected,
+        }
+    }
+}
+
+/// Callback receiving every recorded event, e.g. to forward to a web server log
+pub type AuditSink = Box<dyn Fn(&AuditEvent) + Send + Sync>;
+
+// The installed sink, shared so an event can be delivered without the log
+type SharedAuditSink = Arc<dyn Fn(&AuditEvent) + Send + Sync>;
+
+/// An event bound for the sink, delivered by the caller once it has released the log.
+/// A sink may then read the log itself without deadlocking.
+#[must_use = "the sink only sees the event once it is delivered"]
+pub struct SinkDelivery {
+    sink: SharedAuditSink,
+    event: AuditEvent,
+}
+
+impl SinkDelivery {
+    pub fn deliver(self) {
+        (self.sink)(&self.event);
+    }
+}
+
+/// Bounded ring buffer of audit events with an optional forwarding sink
+pub struct AuditLog {
+    enabled: bool,
+    capacity: usize,
+    dropped: u64,
+    events: VecDeque<AuditEvent>,
+    sink: Option<SharedAuditSink>,
+}
+
+/// JSON document returned by `SecureBuffer::get_security_audit_log`
+#[derive(Serialize)]
+struct AuditLogSnapshot<'a> {
+    enabled: bool,
+    capacity: usize,
+    dropped: u64,
+    events: &'a VecDeque<AuditEvent>,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_AUDIT_CAPACITY)
+    }
+}
+
+impl AuditLog {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            enabled: true,
+            capacity,
+            dropped: 0,
+            events: VecDeque::with_capacity(capacity),
+            sink: None,
+        }
+    }
+
+    /// Record an event if logging is enabled, evicting the oldest when full.
+    /// Returns the event for the sink, if one is set, to deliver after releasing the log.
+    pub fn record(&mut self, kind: AuditEventKind, bytes_affected: usize) -> Option<SinkDelivery> {
+        if !self.enabled {
+            return None;
+        }
+        let event = AuditEvent::new(kind, bytes_affected);
+        let delivery = self.sink.clone().map(|sink| SinkDelivery { sink, event: event.clone() });
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return delivery;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+        delivery
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Change the retained event count, dropping the oldest events if shrinking
+    pub fn set_capacity(&mut self, capacity: usize) {
+        while self.events.len() > capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.capacity = capacity;
+    }
+
+    pub fn set_sink(&mut self, sink: Option<AuditSink>) {
+        self.sink = sink.map(Arc::from);
+    }
+
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events.iter().cloned().collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        let snapshot = AuditLogSnapshot {
+            enabled: self.enabled,
+            capacity: self.capacity,
+            dropped: self.dropped,
+            events: &self.events,
+        };
+        serde_json::to_string(&snapshot).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+// --- Server audit trail ---
+// JSONL records of key, policy and auth events, written by a background thread
+
+#[cfg(feature = "chrono")]
+pub use trail::{AuditLogger, AuditWriter, FsyncPolicy, RotatingFile, ServerAuditEvent, CURRENT_REQUEST_ID};
+
+#[cfg(feature = "chrono")]
+mod trail {
+    use log::warn;
+    use serde::Serialize;
+    use serde_json::{json, Value};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tokio::sync::{mpsc, oneshot};
+    use turbo_validator::EntropyHybridReceipt;
+
+    tokio::task_local! {
+        /// Request id stamped on records emitted within its scope
+        pub static CURRENT_REQUEST_ID: String;
+    }
+
+    /// When the audit writer forces written lines to disk. Serialized the way AUDIT_LOG_FSYNC is written.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, serde::Deserialize)]
+    #[serde(try_from = "String", into = "String")]
+    pub enum FsyncPolicy {
+        EveryWrite,
+        EveryN(u32),
+        Never,
+    }
+
+    impl std::str::FromStr for FsyncPolicy {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "always" => Ok(FsyncPolicy::EveryWrite),
+                "never" => Ok(FsyncPolicy::Never),
+                n => match n.parse::<u32>() {
+                    Ok(0) | Err(_) => Err(format!("expected always, never or a line count above zero, got {}", s)),
+                    Ok(n) => Ok(FsyncPolicy::EveryN(n)),
+                },
+            }
+        }
+    }
+
+    impl TryFrom<String> for FsyncPolicy {
+        type Error = String;
+
+        fn try_from(s: String) -> Result<Self, Self::Error> {
+            s.parse()
+        }
+    }
+
+    impl From<FsyncPolicy> for String {
+        fn from(policy: FsyncPolicy) -> Self {
+            match policy {
+                FsyncPolicy::EveryWrite => "always".to_string(),
+                FsyncPolicy::EveryN(n) => n.to_string(),
+                FsyncPolicy::Never => "never".to_string(),
+            }
+        }
+    }
+
+    impl FsyncPolicy {
+        fn due(&self, unsynced: u32) -> bool {
+            match self {
+                FsyncPolicy::EveryWrite => true,
+                FsyncPolicy::EveryN(n) => unsynced >= *n,
+                FsyncPolicy::Never => false,
+            }
+        }
+    }
+
+    /// One server audit record. Keys only ever appear as their stored digest.
+    #[derive(Debug, Clone, Serialize)]
+    #[serde(tag = "action", rename_all = "snake_case")]
+    pub enum ServerAuditEvent {
+        KeyGenerated { key_hash: String, tier: String, client_ip: String },
+        KeyRevoked { key_hash: String },
+        PolicyUpdate { actor: String, before: Value, pqc: Value, tiers: Vec<String> },
+        AdminSecretRotate { actor: String, previous_version: u32 },
+        EntropyReceipt { receipt: EntropyHybridReceipt },
+        AuthFailed { ip: String, key_hash: Option<String>, reason: &'static str },
+        WebhookDeadLettered { key_hash: String, url: String, event_id: String, event_type: String, attempts: u32, error: String },
+    }
+
+    impl ServerAuditEvent {
+        /// The JSON line for this event, stamped with the time and the current request id
+        fn to_line(&self) -> String {
+            let mut record = json!(self);
+            record["timestamp"] = json!(chrono::Utc::now().to_rfc3339());
+            record["request_id"] = json!(CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok());
+            record.to_string()
+        }
+    }
+
+    /// Where the audit writer puts lines
+    pub trait AuditWriter: Send {
+        fn append(&mut self, line: &str) -> std::io::Result<()>;
+        fn sync(&mut self) -> std::io::Result<()>;
+    }
+
+    /// `path` rotated by size: audit.log moves to audit.log.1, audit.log.1 to audit.log.2,
+    /// and so on, dropping whatever would pass `keep`. Opened on the first write.
+    pub struct RotatingFile {
+        path: PathBuf,
+        max_bytes: u64,
+        keep: usize,
+        file: Option<std::fs::File>,
+        size: u64,
+    }
+
+    impl RotatingFile {
+        pub fn new(path: impl Into<PathBuf>, max_bytes: u64, keep: usize) -> Self {
+            RotatingFile { path: path.into(), max_bytes, keep, file: None, size: 0 }
+        }
+
+        /// Path of the `index`th rotated file
+        pub fn rotated(&self, index: usize) -> PathBuf {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{}", index));
+            name.into()
+        }
+
+        fn rotate(&mut self) -> std::io::Result<()> {
+            self.file = None;
+            if self.keep == 0 {
+                return std::fs::remove_file(&self.path);
+            }
+            for index in (1..self.keep).rev() {
+                let from = self.rotated(index);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated(1))
+        }
+
+        fn open(&mut self) -> std::io::Result<&mut std::fs::File> {
+            if self.file.is_none() {
+                let file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+                self.size = file.metadata()?.len();
+                self.file = Some(file);
+            }
+            Ok(self.file.as_mut().expect("opened above"))
+        }
+    }
+
+    impl AuditWriter for RotatingFile {
+        fn append(&mut self, line: &str) -> std::io::Result<()> {
+            use std::io::Write;
+            let len = line.len() as u64 + 1;
+            self.open()?;
+            // A line larger than max_bytes still gets a file to itself
+            if self.size > 0 && self.size + len > self.max_bytes {
+                self.rotate()?;
+            }
+            let file = self.open()?;
+            file.write_all(format!("{}\n", line).as_bytes())?;
+            self.size += len;
+            Ok(())
+        }
+
+        fn sync(&mut self) -> std::io::Result<()> {
+            match &self.file {
+                Some(file) => file.sync_data(),
+                None => Ok(()),
+            }
+        }
+    }
+
+    enum AuditCommand {
+        Line(String),
+        // Written and synced before the sender hears back
+        Durable(String, oneshot::Sender<std::io::Result<()>>),
+        // Acknowledged once everything queued before it is on disk
+        Close(oneshot::Sender<()>),
+    }
+
+    /// Queues audit events for a writer thread. `emit` never blocks: when the queue
+    /// is full the event is dropped and counted.
+    pub struct AuditLogger {
+        sender: Option<mpsc::Sender<AuditCommand>>,
+        dropped: AtomicU64,
+    }
+
+    impl AuditLogger {
+        /// Logger that discards everything
+        pub fn disabled() -> Self {
+            AuditLogger { sender: None, dropped: AtomicU64::new(0) }
+        }
+
+        /// Rotating JSONL file at `path`, or a disabled logger when the path is empty
+        pub fn open(path: &str, max_bytes: u64, keep: usize, queue: usize, fsync: FsyncPolicy) -> Self {
+            if path.is_empty() {
+                return Self::disabled();
+            }
+            Self::spawn(RotatingFile::new(path, max_bytes, keep), queue, fsync)
+        }
+
+        pub fn spawn(mut sink: impl AuditWriter + 'static, queue: usize, fsync: FsyncPolicy) -> Self {
+            let (sender, mut receiver) = mpsc::channel(queue.max(1));
+            std::thread::Builder::new()
+                .name("audit-writer".to_string())
+                .spawn(move || {
+                    let mut unsynced = 0;
+                    let sync = |sink: &mut dyn AuditWriter| {
+                        if let Err(e) = sink.sync() {
+                            warn!("Syncing audit log failed: {}", e);
+                        }
+                    };
+                    while let Some(command) = receiver.blocking_recv() {
+                        match command {
+                            AuditCommand::Line(line) => match sink.append(&line) {
+                                Ok(()) => {
+                                    unsynced += 1;
+                                    if fsync.due(unsynced) {
+                                        sync(&mut sink);
+                                        unsynced = 0;
+                                    }
+                                }
+                                Err(e) => warn!("Writing audit log failed, event lost: {}", e),
+                            },
+                            AuditCommand::Durable(line, ack) => {
+                                let _ = ack.send(sink.append(&line).and_then(|()| sink.sync()));
+                                unsynced = 0;
+                            }
+                            AuditCommand::Close(ack) => {
+                                receiver.close();
+                                if unsynced > 0 && fsync != FsyncPolicy::Never {
+                                    sync(&mut sink);
+                                }
+                                let _ = ack.send(());
+                                break;
+                            }
+                        }
+                    }
+                })
+                .expect("spawning the audit writer thread");
+            AuditLogger { sender: Some(sender), dropped: AtomicU64::new(0) }
+        }
+
+        /// Queue `event` without waiting
+        pub fn emit(&self, event: ServerAuditEvent) {
+            let Some(sender) = &self.sender else { return };
+            if sender.try_send(AuditCommand::Line(event.to_line())).is_err() {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped.is_power_of_two() {
+                    warn!("Audit log queue full or closed, {} events dropped so far", dropped);
+                }
+            }
+        }
+
+        /// Write `event` and sync it before returning, for changes that must not apply unaudited
+        pub async fn emit_durable(&self, event: ServerAuditEvent) -> std::io::Result<()> {
+            let Some(sender) = &self.sender else { return Ok(()) };
+            let closed = || std::io::Error::new(std::io::ErrorKind::BrokenPipe, "audit writer stopped");
+            let (ack, written) = oneshot::channel();
+            sender.send(AuditCommand::Durable(event.to_line(), ack)).await.map_err(|_| closed())?;
+            written.await.map_err(|_| closed())?
+        }
+
+        /// Drain queued events to disk and stop the writer; later events are dropped
+        pub async fn close(&self) {
+            let Some(sender) = &self.sender else { return };
+            let (ack, drained) = oneshot::channel();
+            if sender.send(AuditCommand::Close(ack)).await.is_ok() {
+                let _ = drained.await;
+            }
+        }
+
+        pub fn dropped(&self) -> u64 {
+            self.dropped.load(Ordering::Relaxed)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::time::{Duration, Instant};
+
+        fn temp_log() -> PathBuf {
+            let dir = std::env::temp_dir().join(format!("sprint-audit-{:016x}", rand::random::<u64>()));
+            std::fs::create_dir_all(&dir).unwrap();
+            dir.join("audit.log")
+        }
+
+        fn revoked(i: usize) -> ServerAuditEvent {
+            ServerAuditEvent::KeyRevoked { key_hash: format!("{:064x}", i) }
+        }
+
+        struct SlowSink {
+            inner: RotatingFile,
+            delay: Duration,
+        }
+
+        impl AuditWriter for SlowSink {
+            fn append(&mut self, line: &str) -> std::io::Result<()> {
+                std::thread::sleep(self.delay);
+                self.inner.append(line)
+            }
+
+            fn sync(&mut self) -> std::io::Result<()> {
+                self.inner.sync()
+            }
+        }
+
+        #[tokio::test]
+        async fn test_rotation_keeps_newest_files_as_json_lines() {
+            let path = temp_log();
+            let logger = AuditLogger::spawn(RotatingFile::new(&path, 2048, 3), 1024, FsyncPolicy::EveryN(50));
+            for i in 0..300 {
+                logger.emit(revoked(i));
+            }
+            logger.close().await;
+            assert_eq!(logger.dropped(), 0);
+
+            let file = RotatingFile::new(&path, 2048, 3);
+            assert!(!file.rotated(4).exists());
+            // Oldest surviving file first
+            let mut seen = Vec::new();
+            for path in [file.rotated(3), file.rotated(2), file.rotated(1), path.clone()] {
+                let text = std::fs::read_to_string(&path).unwrap();
+                assert!(text.len() <= 2048, "{} is {} bytes", path.display(), text.len());
+                for line in text.lines() {
+                    let record: Value = serde_json::from_str(line).unwrap();
+                    assert_eq!(record["action"], "key_revoked");
+                    assert!(record["timestamp"].is_string());
+                    seen.push(usize::from_str_radix(record["key_hash"].as_str().unwrap(), 16).unwrap());
+                }
+            }
+            // Rotation only ever discards the oldest lines
+            let first = seen[0];
+            assert!(first > 0);
+            assert_eq!(seen, (first..300).collect::<Vec<_>>());
+
+            // Closed: further events are dropped rather than queued
+            logger.emit(revoked(300));
+            assert_eq!(logger.dropped(), 1);
+            let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        }
+
+        #[tokio::test]
+        async fn test_emit_does_not_wait_for_a_slow_writer() {
+            let path = temp_log();
+            let sink = SlowSink { inner: RotatingFile::new(&path, 1 << 20, 1), delay: Duration::from_millis(20) };
+            let logger = AuditLogger::spawn(sink, 8, FsyncPolicy::Never);
+
+            let started = Instant::now();
+            for i in 0..100 {
+                logger.emit(revoked(i));
+            }
+            // Writing all of them would take two seconds
+            assert!(started.elapsed() < Duration::from_millis(200), "emit took {:?}", started.elapsed());
+            let dropped = logger.dropped();
+            assert!(dropped > 0);
+
+            // Close waits for everything that was queued
+            logger.close().await;
+            let written = std::fs::read_to_string(&path).unwrap().lines().count() as u64;
+            assert_eq!(written, 100 - dropped);
+            let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        }
+
+        #[tokio::test]
+        async fn test_records_carry_the_scoped_request_id() {
+            let path = temp_log();
+            let logger = AuditLogger::open(path.to_str().unwrap(), 1 << 20, 1, 8, FsyncPolicy::EveryWrite);
+            CURRENT_REQUEST_ID.scope("req-1".to_string(), async { logger.emit(revoked(1)) }).await;
+            logger.emit(revoked(2));
+            logger.close().await;
+
+            let text = std::fs::read_to_string(&path).unwrap();
+            let ids: Vec<Value> = text.lines().map(|l| serde_json::from_str::<Value>(l).unwrap()["request_id"].clone()).collect();
+            assert_eq!(ids, vec![json!("req-1"), Value::Null]);
+            let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        }
+
+        #[test]
+        fn test_fsync_policy_parses_and_round_trips() {
+            assert_eq!("never".parse::<FsyncPolicy>(), Ok(FsyncPolicy::Never));
+            assert_eq!("25".parse::<FsyncPolicy>(), Ok(FsyncPolicy::EveryN(25)));
+            assert!("0".parse::<FsyncPolicy>().is_err());
+            assert_eq!(serde_json::to_value(FsyncPolicy::EveryN(25)).unwrap(), json!("25"));
+            assert_eq!(serde_json::from_value::<FsyncPolicy>(json!("always")).unwrap(), FsyncPolicy::EveryWrite);
+        }
+    }
+}
@@ -7,8 +7,6 @@
 };
 use chrono::{DateTime, Utc};
 use dotenvy::dotenv;
-use lazy_static::lazy_static;
-use prometheus::Counter;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
@@ -29,14 +27,6 @@
 static TOTAL_REQUESTS: AtomicU64 = AtomicU64::new(0);
 static ACTIVE_CONNECTIONS: AtomicU64 = AtomicU64::new(0);
 
-// Prometheus metrics
-lazy_static! {
-    static ref REQUESTS_RATE_LIMITED: Counter = prometheus::register_counter!(
-        "bitcoin_sprint_requests_rate_limited_total",
-        "Total number of rate limited requests"
-    ).unwrap();
-}
-
 // Version information
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const COMMIT: &str = "unknown";
@@ -0,0 +1,551 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Storage verification API on actix-web
+// Issues StorageVerifier challenges and scores provider proofs
+
+use actix_web::{web, App, HttpServer, Responder, HttpResponse, middleware, Result};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse};
+use serde::{Serialize, Deserialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH, Duration, Instant};
+use std::collections::{BTreeMap, HashMap};
+use log::{error, info};
+use reqwest::ClientBuilder;
+use securebuffer::storage_verifier::{StorageChallenge, StorageProof, StorageVerificationError, StorageVerifier};
+use securebuffer::merkle::MerkleProof;
+use prometheus::{Encoder, TextEncoder, register_counter_vec, register_histogram_vec, CounterVec, HistogramVec};
+
+// --- Metrics ---
+lazy_static::lazy_static! {
+    static ref HTTP_REQUESTS_TOTAL: CounterVec = register_counter_vec!(
+        "sprint_api_requests_total",
+        "Total number of API requests",
+        &["route"]
+    ).expect("Can't create metrics");
+
+    static ref HTTP_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "sprint_api_request_duration_seconds",
+        "Request duration in seconds",
+        &["route"]
+    ).expect("Can't create metrics");
+}
+
+/// Route label for requests that match no registered route
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Requests served, in total and per route pattern
+#[derive(Default)]
+struct RequestCounters {
+    total: AtomicU64,
+    per_route: RwLock<HashMap<String, AtomicU64>>,
+}
+
+impl RequestCounters {
+    fn record(&self, route: &str) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        if let Some(count) = self.per_route.read().unwrap().get(route) {
+            count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.per_route
+            .write()
+            .unwrap()
+            .entry(route.to_string())
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    fn by_route(&self) -> BTreeMap<String, u64> {
+        self.per_route
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(route, count)| (route.clone(), count.load(Ordering::Relaxed)))
+            .collect()
+    }
+}
+
+// --- Data Structures ---
+#[derive(Serialize, Deserialize, Clone)]
+struct HealthStatus {
+    status: String,
+    timestamp: u64,
+    uptime_seconds: u64,
+    version: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct APIResponse<T> {
+    success: bool,
+    data: Option<T>,
+    error: Option<String>,
+    timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StorageVerifyRequest {
+    file_id: String,
+    provider: String,
+    /// Network the samples come from (e.g. "ipfs", "arweave"); omitted for direct proofs
+    #[serde(default)]
+    protocol: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StorageChallengeDetails {
+    challenge_id: String,
+    file_id: String,
+    provider: String,
+    protocol: Option<String>,
+    /// Every chunk the proof must cover; samples are submitted in this order
+    chunk_indices: Vec<u64>,
+    sample_offset: u64,
+    sample_size: u32,
+    commitment_alg: String,
+    require_signature: bool,
+    issued_at: u64,
+    expires_at: u64,
+}
+
+impl From<StorageChallenge> for StorageChallengeDetails {
+    fn from(c: StorageChallenge) -> Self {
+        Self {
+            challenge_id: c.id,
+            file_id: c.file_id,
+            provider: c.provider,
+            protocol: c.protocol,
+            chunk_indices: c.chunk_indices,
+            sample_offset: c.sample_offset,
+            sample_size: c.sample_size,
+            commitment_alg: c.commitment_alg,
+            require_signature: c.require_signature,
+            issued_at: c.timestamp,
+            expires_at: c.expiry,
+        }
+    }
+}
+
+/// One Merkle proof level: sibling hash (hex) and whether it is the left input
+#[derive(Serialize, Deserialize)]
+struct MerkleStep {
+    hash: String,
+    is_left: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StorageProofRequest {
+    challenge_id: String,
+    file_id: String,
+    provider: String,
+    /// Unix seconds; part of the signed message
+    timestamp: u64,
+    /// Base64 sample of chunk `chunk_indices[0]`
+    proof_data: String,
+    /// Base64 samples of `chunk_indices[1..]`, in order
+    #[serde(default)]
+    extra_proof_data: Vec<String>,
+    #[serde(default)]
+    merkle_proof: Option<Vec<MerkleStep>>,
+    /// Base64 ed25519 signature over the proof's signing message
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StorageProofResult {
+    challenge_id: String,
+    verified: bool,
+    score: f64,
+    score_components: BTreeMap<String, f64>,
+}
+
+#[derive(Clone)]
+struct AppState {
+    start_time: Instant,
+    requests: Arc<RequestCounters>,
+    verifier: Arc<StorageVerifier>,
+}
+
+impl AppState {
+    fn new(verifier: Arc<StorageVerifier>) -> Self {
+        Self {
+            start_time: Instant::now(),
+            requests: Arc::new(RequestCounters::default()),
+            verifier,
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn error_response(mut builder: actix_web::HttpResponseBuilder, error: String, timestamp: u64) -> HttpResponse {
+    builder.json(APIResponse::<()> {
+        success: false,
+        data: None,
+        error: Some(error),
+        timestamp,
+    })
+}
+
+// --- API Handlers ---
+async fn health_check(data: web::Data<AppState>) -> Result<impl Responder> {
+    let uptime = data.start_time.elapsed().as_secs();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let health = HealthStatus {
+        status: "healthy".to_string(),
+        timestamp,
+        uptime_seconds: uptime,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+
+    let response = APIResponse {
+        success: true,
+        data: Some(health),
+        error: None,
+        timestamp,
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+async fn api_status(data: web::Data<AppState>) -> Result<impl Responder> {
+    let count = data.requests.total();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let status = serde_json::json!({
+        "service": "Bitcoin Sprint API",
+        "version": env!("CARGO_PKG_VERSION"),
+        "status": "running",
+        "requests_served": count,
+        "requests_by_route": data.requests.by_route(),
+        "uptime_seconds": data.start_time.elapsed().as_secs(),
+        "timestamp": timestamp
+    });
+
+    let response = APIResponse {
+        success: true,
+        data: Some(status),
+        error: None,
+        timestamp,
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+async fn metrics() -> Result<impl Responder> {
+    let encoder = TextEncoder::new();
+    let metric_families = prometheus::gather();
+    let mut buffer = Vec::new();
+
+    encoder.encode(&metric_families, &mut buffer).unwrap();
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(String::from_utf8(buffer).unwrap()))
+}
+
+fn verifier_error_response(e: &StorageVerificationError, timestamp: u64) -> HttpResponse {
+    let builder = match e {
+        StorageVerificationError::InvalidInput { .. } => HttpResponse::BadRequest(),
+        StorageVerificationError::ChallengeNotFound { .. } => HttpResponse::NotFound(),
+        StorageVerificationError::CryptographicFailure { .. } => HttpResponse::UnprocessableEntity(),
+        StorageVerificationError::AuthenticationFailed => HttpResponse::Unauthorized(),
+        StorageVerificationError::RateLimitExceeded { .. } => HttpResponse::TooManyRequests(),
+        StorageVerificationError::Overloaded { .. } => HttpResponse::ServiceUnavailable(),
+        _ => {
+            error!("Storage verifier error: {}", e);
+            return error_response(HttpResponse::InternalServerError(), "Storage verification failed".to_string(), timestamp);
+        }
+    };
+    error_response(builder, e.to_string(), timestamp)
+}
+
+/// Issue a storage challenge for a registered file
+async fn storage_verification(
+    payload: web::Json<StorageVerifyRequest>,
+    data: web::Data<AppState>
+) -> Result<impl Responder> {
+    let timestamp = unix_now();
+
+    info!("Storage verification request: provider={}, file_id={}", payload.provider, payload.file_id);
+
+    match data.verifier.has_commitments(&payload.file_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(error_response(
+                HttpResponse::NotFound(),
+                format!(
+                    "No commitments registered for file_id {}; register the file's chunk hashes or Merkle root before requesting verification",
+                    payload.file_id
+                ),
+                timestamp,
+            ));
+        }
+        Err(e) => return Ok(verifier_error_response(&e, timestamp)),
+    }
+
+    let challenge = match &payload.protocol {
+        Some(protocol) => data.verifier.generate_protocol_challenge(&payload.file_id, &payload.provider, protocol).await,
+        None => data.verifier.generate_challenge(&payload.file_id, &payload.provider).await,
+    };
+    let challenge = match challenge {
+        Ok(challenge) => challenge,
+        Err(e) => return Ok(verifier_error_response(&e, timestamp)),
+    };
+
+    let response = APIResponse {
+        success: true,
+        data: Some(StorageChallengeDetails::from(challenge)),
+        error: None,
+        timestamp,
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+fn decode_base64(field: &str, encoded: &str) -> std::result::Result<Vec<u8>, String> {
+    use base64::{engine::general_purpose, Engine as _};
+    general_purpose::STANDARD.decode(encoded).map_err(|_| format!("{} must be base64", field))
+}
+
+fn decode_proof(payload: StorageProofRequest) -> std::result::Result<StorageProof, String> {
+    let proof_data = decode_base64("proof_data", &payload.proof_data)?;
+    let extra_proof_data = payload
+        .extra_proof_data
+        .iter()
+        .map(|sample| decode_base64("extra_proof_data", sample))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let merkle_proof = payload
+        .merkle_proof
+        .map(|steps| MerkleProof::Pairs(steps.into_iter().map(|s| (s.hash, s.is_left)).collect()));
+
+    Ok(StorageProof {
+        challenge_id: payload.challenge_id,
+        file_id: payload.file_id,
+        provider: payload.provider,
+        timestamp: payload.timestamp,
+        proof_data,
+        merkle_proof,
+        signature: payload.signature,
+        extra_proof_data,
+    })
+}
+
+/// Verify a provider's answer to a challenge issued by `storage_verification`
+async fn storage_proof(
+    payload: web::Json<StorageProofRequest>,
+    data: web::Data<AppState>
+) -> Result<impl Responder> {
+    let timestamp = unix_now();
+
+    let proof = match decode_proof(payload.into_inner()) {
+        Ok(proof) => proof,
+        Err(error) => return Ok(error_response(HttpResponse::BadRequest(), error, timestamp)),
+    };
+    let challenge_id = proof.challenge_id.clone();
+
+    let outcome = match data.verifier.verify_proof_scored(proof).await {
+        Ok(outcome) => outcome,
+        Err(e) => return Ok(verifier_error_response(&e, timestamp)),
+    };
+    info!("Proof for challenge {} verified: {} (score {:.3})", challenge_id, outcome.verified, outcome.score);
+
+    let response = APIResponse {
+        success: true,
+        data: Some(StorageProofResult {
+            challenge_id,
+            verified: outcome.verified,
+            score: outcome.score,
+            score_components: outcome.components,
+        }),
+        error: None,
+        timestamp,
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+// --- Request Metrics Middleware ---
+/// Count every request and time it, labelled by the matched route pattern
+fn track_requests<S, B>(
+    req: ServiceRequest,
+    srv: &S,
+) -> impl Future<Output = Result<ServiceResponse<B>, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+{
+    let route = req.match_pattern().unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    if let Some(state) = req.app_data::<web::Data<AppState>>() {
+        state.requests.record(&route);
+    }
+    HTTP_REQUESTS_TOTAL.with_label_values(&[&route]).inc();
+    let timer = HTTP_REQUEST_DURATION.with_label_values(&[&route]).start_timer();
+
+    let response = srv.call(req);
+    async move {
+        let response = response.await;
+        timer.observe_duration();
+        response
+    }
+}
+
+/// Routes shared by `main` and the tests
+fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/health", web::get().to(health_check))
+        .route("/api/v1/status", web::get().to(api_status))
+        .route("/api/v1/storage/verify", web::post().to(storage_verification))
+        .route("/api/v1/storage/proof", web::post().to(storage_proof))
+        .route("/metrics", web::get().to(metrics))
+        .route("/", web::get().to(|| async {
+            HttpResponse::Ok().json(serde_json::json!({
+                "service": "Bitcoin Sprint API",
+                "version": env!("CARGO_PKG_VERSION"),
+                "endpoints": {
+                    "health": "/health",
+                    "status": "/api/v1/status",
+                    "storage_verify": "POST /api/v1/storage/verify",
+                    "storage_proof": "POST /api/v1/storage/proof",
+                    "metrics": "/metrics"
+                }
+            }))
+        }));
+}
+
+// --- Main Function ---
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    // Initialize logging
+    env_logger::init();
+
+    info!("🚀 Starting Bitcoin Sprint API Server v{}", env!("CARGO_PKG_VERSION"));
+
+    // Initialize application state
+    let app_state = web::Data::new(AppState::new(Arc::new(StorageVerifier::new())));
+
+    // Configure HTTP client with connection pooling
+    let _client = ClientBuilder::new()
+        .pool_max_idle_per_host(10)
+        .pool_idle_timeout(Duration::from_secs(90))
+        .timeout(Duration::from_secs(30))
+        .build()
+        .expect("Failed to build HTTP client");
+
+    info!("📊 Metrics server starting on http://0.0.0.0:9090/metrics");
+    info!("🌐 API server starting on http://0.0.0.0:8080");
+
+    // For now, just run the main API server
+    // Metrics can be accessed through the main server
+    HttpServer::new(move || {
+        App::new()
+            .app_data(app_state.clone())
+            .wrap_fn(track_requests)
+            .wrap(middleware::Logger::default())
+            .configure(configure_routes)
+    })
+    .bind("0.0.0.0:8080")?
+    .run()
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test;
+    use base64::{engine::general_purpose, Engine as _};
+
+    const CHUNK: usize = 32;
+
+    fn file_data() -> Vec<u8> {
+        (0..CHUNK * 4).map(|i| (i * 13 % 256) as u8).collect()
+    }
+
+    async fn app_state() -> web::Data<AppState> {
+        let verifier = Arc::new(StorageVerifier::new());
+        let leaves = file_data().chunks(CHUNK).map(securebuffer::merkle::hash_leaf).collect();
+        verifier.register_file_commitments("file-1", CHUNK as u32, leaves).await.unwrap();
+        web::Data::new(AppState::new(verifier))
+    }
+
+    fn verify_request(file_id: &str) -> StorageVerifyRequest {
+        StorageVerifyRequest { file_id: file_id.to_string(), provider: "provider-1".to_string(), protocol: None }
+    }
+
+    #[actix_web::test]
+    async fn test_challenge_then_proof_is_verified_and_scored() {
+        let app = test::init_service(App::new().app_data(app_state().await).configure(configure_routes)).await;
+
+        let req = test::TestRequest::post().uri("/api/v1/storage/verify").set_json(verify_request("file-1")).to_request();
+        let resp: APIResponse<StorageChallengeDetails> = test::call_and_read_body_json(&app, req).await;
+        let challenge = resp.data.expect("challenge details");
+        assert_eq!(challenge.file_id, "file-1");
+        assert_eq!(challenge.sample_size, CHUNK as u32);
+
+        let data = file_data();
+        let sample = |i: u64| general_purpose::STANDARD.encode(&data[i as usize * CHUNK..(i as usize + 1) * CHUNK]);
+        let proof = StorageProofRequest {
+            challenge_id: challenge.challenge_id.clone(),
+            file_id: challenge.file_id.clone(),
+            provider: challenge.provider.clone(),
+            timestamp: challenge.issued_at,
+            proof_data: sample(challenge.chunk_indices[0]),
+            extra_proof_data: challenge.chunk_indices[1..].iter().map(|&i| sample(i)).collect(),
+            merkle_proof: None,
+            signature: None,
+        };
+        let req = test::TestRequest::post().uri("/api/v1/storage/proof").set_json(proof).to_request();
+        let resp: APIResponse<StorageProofResult> = test::call_and_read_body_json(&app, req).await;
+        let result = resp.data.expect("proof result");
+        assert_eq!(result.challenge_id, challenge.challenge_id);
+        assert!(result.verified);
+        assert!(result.score > 0.5);
+        assert!(!result.score_components.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_unregistered_file_is_not_found() {
+        let app = test::init_service(App::new().app_data(app_state().await).configure(configure_routes)).await;
+
+        let req = test::TestRequest::post().uri("/api/v1/storage/verify").set_json(verify_request("file-2")).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body: APIResponse<()> = test::read_body_json(resp).await;
+        assert!(!body.success);
+        assert!(body.error.unwrap().contains("No commitments registered for file_id file-2"));
+    }
+
+    #[actix_web::test]
+    async fn test_status_counts_every_request() {
+        let app = test::init_service(
+            App::new().app_data(app_state().await).wrap_fn(track_requests).configure(configure_routes),
+        )
+        .await;
+
+        for _ in 0..3 {
+            let resp = test::call_service(&app, test::TestRequest::get().uri("/health").to_request()).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+
+        let req = test::TestRequest::get().uri("/api/v1/status").to_request();
+        let resp: APIResponse<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+        let status = resp.data.expect("status");
+        assert!(status["requests_served"].as_u64().unwrap() >= 3);
+        assert_eq!(status["requests_by_route"]["/health"], 3);
+    }
+}
@@ -0,0 +1,523 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Operator CLI for the API and storage services
+
+//! `sprintctl`: key management, bloom filter inspection, commitment registration, entropy
+//! and policy changes without hand-built curl calls.
+//!
+//! Endpoints and credentials come from flags or the environment:
+//!
+//! * `SPRINT_API_URL` - the API (`API_PORT`), default `http://127.0.0.1:8443`
+//! * `SPRINT_ADMIN_URL` - the admin port (`RUST_ADMIN_SERVER_PORT`), default `http://127.0.0.1:8444`
+//! * `SPRINT_STORAGE_URL` - the enterprise storage service, default the API URL
+//! * `SPRINT_ADMIN_KEY` - sent as `X-Admin-Key` to the key routes; the server's `ADMIN_API_KEY`
+//! * `SPRINT_ADMIN_BEARER_TOKEN` - sent as the bearer token to `/admin/policy`; the server's
+//!   `ADMIN_BEARER_TOKEN`
+//! * `SPRINT_API_KEY` - a customer key for the bloom, entropy and storage routes
+//!
+//! Output is a table unless `--json` is given. API errors exit with status 1 after printing
+//! the server's error body to stderr.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(name = "sprintctl", about = "Operate the Bitcoin Sprint API and storage services")]
+struct Cli {
+    #[arg(long, env = "SPRINT_API_URL", default_value = "http://127.0.0.1:8443")]
+    api_url: String,
+    #[arg(long, env = "SPRINT_ADMIN_URL", default_value = "http://127.0.0.1:8444")]
+    admin_url: String,
+    /// Defaults to --api-url
+    #[arg(long, env = "SPRINT_STORAGE_URL")]
+    storage_url: Option<String>,
+    /// Server's ADMIN_API_KEY, for the key routes
+    #[arg(long, env = "SPRINT_ADMIN_KEY", hide_env_values = true)]
+    admin_key: Option<String>,
+    /// Server's ADMIN_BEARER_TOKEN, for the admin port
+    #[arg(long, env = "SPRINT_ADMIN_BEARER_TOKEN", hide_env_values = true)]
+    admin_bearer_token: Option<String>,
+    #[arg(long, env = "SPRINT_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+    /// Print the server's JSON instead of a table
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Issue and revoke API keys
+    #[command(subcommand)]
+    Key(KeyCommand),
+    /// Inspect and export the bitcoin bloom filter
+    #[command(subcommand)]
+    Bloom(BloomCommand),
+    /// Register file commitments with the storage service
+    #[command(subcommand)]
+    Storage(StorageCommand),
+    /// Draw entropy
+    #[command(subcommand)]
+    Entropy(EntropyCommand),
+    /// Change runtime policy on the admin port
+    #[command(subcommand)]
+    Policy(PolicyCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum KeyCommand {
+    /// Create a key; tiers other than free need the admin key
+    Generate {
+        #[arg(long, default_value = "free")]
+        tier: String,
+    },
+    /// Revoke a key by its digest (or the key itself)
+    Revoke {
+        #[arg(long)]
+        hash: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum BloomCommand {
+    /// Item counts, false-positive rates and saturation
+    Stats,
+    /// Save the compressed filter to a file
+    Snapshot {
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum StorageCommand {
+    /// Hash a local file in chunks and register the leaf hashes
+    Register {
+        #[arg(long)]
+        file: PathBuf,
+        #[arg(long)]
+        chunk_size: u32,
+        /// Defaults to the file name
+        #[arg(long)]
+        file_id: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum EntropyCommand {
+    /// 32 bytes of entropy, with a receipt for the hybrid kind
+    Get {
+        #[arg(long, value_enum, default_value_t = EntropyKind::Hybrid)]
+        kind: EntropyKind,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum EntropyKind {
+    Fast,
+    Hybrid,
+}
+
+#[derive(Debug, Subcommand)]
+enum PolicyCommand {
+    /// Update the PQC policy
+    Set {
+        /// Share of entropy drawn from the post-quantum source, 0.0-1.0
+        #[arg(long)]
+        pqc_weight: f64,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+enum CliError {
+    #[error("{0}")]
+    Usage(String),
+    #[error("{path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// A non-2xx response; `body` is the server's error body as sent
+    #[error("server returned {status}: {body}")]
+    Api { status: u16, body: String },
+}
+
+/// Where requests go and which credentials they carry
+struct Client {
+    http: reqwest::Client,
+    api_url: String,
+    admin_url: String,
+    storage_url: String,
+    admin_key: Option<String>,
+    admin_bearer_token: Option<String>,
+    api_key: Option<String>,
+}
+
+impl Client {
+    fn from_cli(cli: &Cli) -> Self {
+        let trim = |url: &str| url.trim_end_matches('/').to_string();
+        Client {
+            http: reqwest::Client::new(),
+            api_url: trim(&cli.api_url),
+            admin_url: trim(&cli.admin_url),
+            storage_url: trim(cli.storage_url.as_deref().unwrap_or(&cli.api_url)),
+            admin_key: cli.admin_key.clone(),
+            admin_bearer_token: cli.admin_bearer_token.clone(),
+            api_key: cli.api_key.clone(),
+        }
+    }
+
+    fn admin_key(&self) -> Result<&str, CliError> {
+        self.admin_key
+            .as_deref()
+            .ok_or_else(|| CliError::Usage("an admin key is required (--admin-key or SPRINT_ADMIN_KEY)".to_string()))
+    }
+
+    fn admin_bearer_token(&self) -> Result<&str, CliError> {
+        self.admin_bearer_token.as_deref().ok_or_else(|| {
+            CliError::Usage("an admin bearer token is required (--admin-bearer-token or SPRINT_ADMIN_BEARER_TOKEN)".to_string())
+        })
+    }
+
+    fn api_key(&self) -> Result<&str, CliError> {
+        self.api_key
+            .as_deref()
+            .ok_or_else(|| CliError::Usage("an API key is required (--api-key or SPRINT_API_KEY)".to_string()))
+    }
+
+    /// The request `command` sends, with its path, credentials and body, not yet sent
+    fn request(&self, command: &Command) -> Result<reqwest::RequestBuilder, CliError> {
+        let request = match command {
+            Command::Key(KeyCommand::Generate { tier }) => {
+                let request = self.http.post(format!("{}/generate-key", self.api_url)).json(&json!({ "tier": tier }));
+                match &self.admin_key {
+                    Some(key) => request.header("X-Admin-Key", key),
+                    None => request,
+                }
+            }
+            Command::Key(KeyCommand::Revoke { hash }) => self
+                .http
+                .delete(format!("{}/api/v1/keys/{}", self.api_url, hash))
+                .header("X-Admin-Key", self.admin_key()?),
+            Command::Bloom(BloomCommand::Stats) => {
+                self.http.get(format!("{}/api/v1/bloom/stats", self.api_url)).header("X-API-Key", self.api_key()?)
+            }
+            Command::Bloom(BloomCommand::Snapshot { .. }) => {
+                self.http.get(format!("{}/api/v1/bloom/snapshot", self.api_url)).header("X-API-Key", self.api_key()?)
+            }
+            Command::Storage(StorageCommand::Register { file, chunk_size, file_id }) => {
+                let file_id = match file_id {
+                    Some(id) => id.clone(),
+                    None => file
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .ok_or_else(|| CliError::Usage(format!("{} has no file name; pass --file-id", file.display())))?,
+                };
+                let leaf_hashes = leaf_hashes(file, *chunk_size)?;
+                self.http
+                    .post(format!("{}/api/commitments", self.storage_url))
+                    .bearer_auth(self.api_key()?)
+                    .json(&json!({ "file_id": file_id, "chunk_size": chunk_size, "leaf_hashes": leaf_hashes }))
+            }
+            Command::Entropy(EntropyCommand::Get { kind }) => {
+                let kind = match kind {
+                    EntropyKind::Fast => "fast",
+                    EntropyKind::Hybrid => "hybrid",
+                };
+                // Anonymous callers share a per-IP budget; a key gets its tier's
+                let request = self.http.get(format!("{}/entropy/{}", self.api_url, kind));
+                match &self.api_key {
+                    Some(key) => request.header("X-API-Key", key),
+                    None => request,
+                }
+            }
+            Command::Policy(PolicyCommand::Set { pqc_weight }) => self
+                .http
+                .put(format!("{}/admin/policy", self.admin_url))
+                .bearer_auth(self.admin_bearer_token()?)
+                .header("X-Admin-Actor", "sprintctl")
+                .json(&json!({ "pqc": { "entropy_pqc_weight": pqc_weight } })),
+        };
+        Ok(request)
+    }
+
+    /// Send `command` and return what to print. A snapshot is written to its `--out` file
+    /// and summarised.
+    async fn run(&self, command: &Command) -> Result<Value, CliError> {
+        let response = self.request(command)?.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(CliError::Api { status: status.as_u16(), body });
+        }
+
+        if let Command::Bloom(BloomCommand::Snapshot { out }) = command {
+            let etag = response.headers().get(reqwest::header::ETAG).and_then(|v| v.to_str().ok()).map(str::to_string);
+            let bytes = response.bytes().await?;
+            std::fs::write(out, &bytes).map_err(|source| CliError::Io { path: out.clone(), source })?;
+            return Ok(json!({ "out": out.display().to_string(), "bytes": bytes.len(), "etag": etag }));
+        }
+        let body = response.bytes().await?;
+        if body.is_empty() {
+            return Ok(json!({ "status": status.as_u16() }));
+        }
+        Ok(serde_json::from_slice(&body).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned())))
+    }
+}
+
+/// Hex SHA-256 of each `chunk_size` chunk of `path`, the last one possibly short
+fn leaf_hashes(path: &Path, chunk_size: u32) -> Result<Vec<String>, CliError> {
+    if chunk_size == 0 {
+        return Err(CliError::Usage("--chunk-size must be greater than zero".to_string()));
+    }
+    let io_error = |source| CliError::Io { path: path.to_path_buf(), source };
+    let mut file = std::fs::File::open(path).map_err(io_error)?;
+    let mut chunk = vec![0u8; chunk_size as usize];
+    let mut leaves = Vec::new();
+    loop {
+        let mut filled = 0;
+        while filled < chunk.len() {
+            match file.read(&mut chunk[filled..]).map_err(io_error)? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        if filled == 0 {
+            break;
+        }
+        leaves.push(hex::encode(securebuffer::merkle::hash_leaf(&chunk[..filled])));
+        if filled < chunk.len() {
+            break;
+        }
+    }
+    if leaves.is_empty() {
+        return Err(CliError::Usage(format!("{} is empty", path.display())));
+    }
+    Ok(leaves)
+}
+
+/// Two aligned columns for an object's fields; nested values are printed as compact JSON
+fn render_table(value: &Value) -> String {
+    let rows: Vec<(String, String)> = match value {
+        Value::Object(fields) => fields.iter().map(|(name, value)| (name.clone(), render_cell(value))).collect(),
+        other => return render_cell(other),
+    };
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    rows.iter().map(|(name, value)| format!("{:<width$}  {}\n", name, value, width = width)).collect()
+}
+
+fn render_cell(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "-".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let client = Client::from_cli(&cli);
+    match client.run(&cli.command).await {
+        Ok(output) if cli.json => {
+            println!("{}", serde_json::to_string_pretty(&output).unwrap_or_default());
+            ExitCode::SUCCESS
+        }
+        Ok(output) => {
+            print!("{}", render_table(&output));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("sprintctl: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer) -> Client {
+        Client {
+            http: reqwest::Client::new(),
+            api_url: server.uri(),
+            admin_url: format!("{}/admin-port", server.uri()),
+            storage_url: format!("{}/storage", server.uri()),
+            admin_key: Some("admin-key".to_string()),
+            admin_bearer_token: Some("admin-bearer".to_string()),
+            api_key: Some("key_customer".to_string()),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("sprintctl-{:016x}-{}", rand::random::<u64>(), name))
+    }
+
+    #[test]
+    fn test_cli_parses_flags_and_subcommands() {
+        let cli = Cli::try_parse_from([
+            "sprintctl", "--api-url", "http://api:1/", "--json", "storage", "register", "--file", "a.bin", "--chunk-size", "4",
+        ])
+        .unwrap();
+        assert!(cli.json);
+        assert!(matches!(cli.command, Command::Storage(StorageCommand::Register { chunk_size: 4, .. })));
+        assert_eq!(Client::from_cli(&cli).storage_url, "http://api:1");
+        let cli = Cli::try_parse_from(["sprintctl", "--admin-key", "k", "--admin-bearer-token", "t", "bloom", "stats"]).unwrap();
+        let client = Client::from_cli(&cli);
+        assert_eq!((client.admin_key.as_deref(), client.admin_bearer_token.as_deref()), (Some("k"), Some("t")));
+        assert!(Cli::try_parse_from(["sprintctl", "entropy", "get", "--kind", "slow"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_key_commands_send_the_admin_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/generate-key"))
+            .and(header("x-admin-key", "admin-key"))
+            .and(body_json(json!({ "tier": "pro" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "key": "key_new", "tier": "pro" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/keys/abc123"))
+            .and(header("x-admin-key", "admin-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "hash": "abc123", "revoked": true })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let generated = client.run(&Command::Key(KeyCommand::Generate { tier: "pro".to_string() })).await.unwrap();
+        assert_eq!(generated["key"], "key_new");
+        let revoked = client.run(&Command::Key(KeyCommand::Revoke { hash: "abc123".to_string() })).await.unwrap();
+        assert_eq!(revoked["revoked"], true);
+    }
+
+    #[tokio::test]
+    async fn test_admin_commands_without_their_credential_are_refused_locally() {
+        let server = MockServer::start().await;
+        // Each credential only unlocks its own routes
+        let mut client = client(&server);
+        client.admin_key = None;
+        let result = client.run(&Command::Key(KeyCommand::Revoke { hash: "abc123".to_string() })).await;
+        assert!(matches!(result, Err(CliError::Usage(_))));
+
+        let mut client = self::client(&server);
+        client.admin_bearer_token = None;
+        let result = client.run(&Command::Policy(PolicyCommand::Set { pqc_weight: 0.5 })).await;
+        assert!(matches!(result, Err(CliError::Usage(_))));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bloom_commands_use_the_api_key_and_save_snapshots() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/bloom/stats"))
+            .and(header("x-api-key", "key_customer"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "item_count": 3 })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/bloom/snapshot"))
+            .and(header("x-api-key", "key_customer"))
+            .respond_with(ResponseTemplate::new(200).insert_header("etag", "\"tag1\"").set_body_bytes(vec![7u8; 10]))
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        assert_eq!(client.run(&Command::Bloom(BloomCommand::Stats)).await.unwrap()["item_count"], 3);
+
+        let out = temp_path("bloom.snap");
+        let summary = client.run(&Command::Bloom(BloomCommand::Snapshot { out: out.clone() })).await.unwrap();
+        assert_eq!(summary["bytes"], 10);
+        assert_eq!(summary["etag"], "\"tag1\"");
+        assert_eq!(std::fs::read(&out).unwrap(), vec![7u8; 10]);
+        let _ = std::fs::remove_file(&out);
+    }
+
+    #[tokio::test]
+    async fn test_storage_register_hashes_chunks_locally() {
+        let server = MockServer::start().await;
+        let file = temp_path("data.bin");
+        std::fs::write(&file, b"0123456789").unwrap();
+        let leaves: Vec<String> =
+            [&b"0123"[..], b"4567", b"89"].iter().map(|c| hex::encode(securebuffer::merkle::hash_leaf(c))).collect();
+        Mock::given(method("POST"))
+            .and(path("/storage/api/commitments"))
+            .and(header("authorization", "Bearer key_customer"))
+            .and(body_json(json!({ "file_id": "archive-1", "chunk_size": 4, "leaf_hashes": leaves })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "file_id": "archive-1" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let command = Command::Storage(StorageCommand::Register {
+            file: file.clone(),
+            chunk_size: 4,
+            file_id: Some("archive-1".to_string()),
+        });
+        assert_eq!(client(&server).run(&command).await.unwrap()["file_id"], "archive-1");
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[tokio::test]
+    async fn test_entropy_and_policy_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/entropy/fast"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "len": 32 })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/admin-port/admin/policy"))
+            .and(header("authorization", "Bearer admin-bearer"))
+            .and(header("x-admin-actor", "sprintctl"))
+            .and(body_json(json!({ "pqc": { "entropy_pqc_weight": 0.25 } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "pqc": { "entropy_pqc_weight": 0.25 } })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let entropy = client.run(&Command::Entropy(EntropyCommand::Get { kind: EntropyKind::Fast })).await.unwrap();
+        assert_eq!(entropy["len"], 32);
+        let policy = client.run(&Command::Policy(PolicyCommand::Set { pqc_weight: 0.25 })).await.unwrap();
+        assert_eq!(policy["pqc"]["entropy_pqc_weight"], 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_api_errors_surface_the_server_body() {
+        let server = MockServer::start().await;
+        let error = json!({ "error": "Admin key required", "code": "unauthorized" });
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(error.clone()))
+            .mount(&server)
+            .await;
+
+        match client(&server).run(&Command::Key(KeyCommand::Revoke { hash: "abc123".to_string() })).await {
+            Err(CliError::Api { status, body }) => {
+                assert_eq!(status, 401);
+                assert_eq!(serde_json::from_str::<Value>(&body).unwrap(), error);
+            }
+            other => panic!("expected an API error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_table_aligns_fields() {
+        let table = render_table(&json!({ "expires": null, "receipt": { "round": 1 }, "tier": "pro" }));
+        assert_eq!(table, "expires  -\nreceipt  {\"round\":1}\ntier     pro\n");
+    }
+}
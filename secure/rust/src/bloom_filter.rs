@@ -3,13 +3,16 @@
 // Master Scientist Optimization: Maximum Performance, Stability, Security
 // Supports all blockchain networks like Alchemy, Infura - fastest and most secure
 
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::io::Write;
+use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 use rayon::prelude::*;
 use dashmap::DashMap;
+use dashmap::try_result::TryResult;
 use zeroize::Zeroize;
-use rand::RngCore;
 use bitcoin_hashes::{Hash, HashEngine};
 
 /// Network-agnostic hash trait for blockchain data
@@ -32,6 +35,15 @@ pub fn new(network: &str, hash: &[u8]) -> Self {
             hash: hash.to_vec(),
         }
     }
+
+    /// Parse one serialized Bitcoin transaction, returning its txid and virtual size.
+    ///
+    /// The virtual size follows BIP 141: weight is three times the size without
+    /// witness data plus the full size, rounded up to whole vbytes.
+    pub fn from_bitcoin_tx_bytes(network: &str, bytes: &[u8]) -> Result<(Self, usize), BloomFilterError> {
+        let tx = turbo_validator::Transaction::parse(bytes).map_err(|e| BloomFilterError::InvalidInput(e.to_string()))?;
+        Ok((Self::new(network, &tx.txid), tx.vsize()))
+    }
 }
 
 impl BlockchainHash for TransactionId {
@@ -76,6 +88,32 @@ pub fn new(network: &str, height: u64, hash: &[u8], transactions: Vec<Transactio
     }
 }
 
+/// Size of a serialized Bitcoin block header
+pub const BITCOIN_BLOCK_HEADER_LEN: usize = turbo_validator::header::HEADER_SIZE;
+
+impl BlockData {
+    /// Parse a serialized Bitcoin block: 80-byte header, varint transaction count,
+    /// then the transactions in wire format.
+    ///
+    /// Parsing is shared with TurboValidator (`turbo_validator::tx`). Txids exclude
+    /// segwit witness data and are kept in internal byte order. The merkle root of the
+    /// txids must match the header, so garbage input is rejected rather than loaded.
+    pub fn from_bitcoin_bytes(network: &str, bytes: &[u8]) -> Result<Self, BloomFilterError> {
+        let invalid = |e: turbo_validator::ValidationError| BloomFilterError::InvalidBlock(e.to_string());
+        let header = turbo_validator::BlockHeader::parse(bytes).map_err(invalid)?;
+        let transactions = turbo_validator::tx::parse_block_transactions(bytes).map_err(invalid)?;
+        turbo_validator::tx::check_merkle_root(&header, &transactions).map_err(invalid)?;
+
+        Ok(Self {
+            network: network.to_string(),
+            height: 0, // Not encoded in the header
+            hash: header.block_hash().to_vec(),
+            transactions: transactions.iter().map(|tx| TransactionId::new(network, &tx.txid)).collect(),
+            timestamp: header.timestamp as u64,
+        })
+    }
+}
+
 /// Network configuration for different blockchain networks
 #[derive(Clone, Debug)]
 pub struct NetworkConfig {
@@ -130,7 +168,7 @@ pub fn custom(name: &str, hash_size: usize, block_time: u64, max_block_size: usi
 
 /// Universal Bloom Filter Configuration - Network Agnostic
 /// Optimized for maximum performance and security across all blockchain networks
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct BloomConfig {
     pub network: NetworkConfig,     // Network-specific configuration
     pub size: usize,                // Filter size in bits (must be power of two)
@@ -138,9 +176,32 @@ pub struct BloomConfig {
     pub tweak: u32,                 // Random value to modify hash functions
     pub flags: u8,                  // Filter update flags
     pub max_age_seconds: u64,       // Maximum age for entries before eviction
+    pub generations: usize,         // Rotating sub-filters covering max_age_seconds (1-16)
     pub batch_size: usize,          // Optimal batch size for parallel operations
     pub enable_compression: bool,   // Enable compressed storage for large filters
     pub enable_metrics: bool,       // Enable detailed performance metrics
+    pub provenance_capacity: usize, // Items whose insertion source is remembered; 0 disables
+    pub hash_key: [u8; 16],         // Secret mixed into every item hash; fix it only for deterministic tests
+}
+
+// Written out by hand so logging a config never prints the hash key
+impl std::fmt::Debug for BloomConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BloomConfig")
+            .field("network", &self.network)
+            .field("size", &self.size)
+            .field("num_hashes", &self.num_hashes)
+            .field("tweak", &self.tweak)
+            .field("flags", &self.flags)
+            .field("max_age_seconds", &self.max_age_seconds)
+            .field("generations", &self.generations)
+            .field("batch_size", &self.batch_size)
+            .field("enable_compression", &self.enable_compression)
+            .field("enable_metrics", &self.enable_metrics)
+            .field("provenance_capacity", &self.provenance_capacity)
+            .field("hash_key", &"<redacted>")
+            .finish()
+    }
 }
 
 impl Default for BloomConfig {
@@ -177,9 +238,12 @@ pub fn for_network(network: NetworkConfig) -> Self {
             tweak: rand::random(),
             flags: 0,
             max_age_seconds: 86400, // 24 hours
+            generations: DEFAULT_GENERATIONS,
             batch_size,
             enable_compression: false,
             enable_metrics: true,
+            provenance_capacity: 0,
+            hash_key: generate_hash_key(),
         }
     }
 
@@ -208,22 +272,148 @@ pub fn memory_optimized(network: NetworkConfig) -> Self {
     }
 }
 
+/// Fresh random key for `BloomConfig::hash_key`, taken from the fast entropy source
+pub fn generate_hash_key() -> [u8; 16] {
+    let mut key = [0u8; 16];
+    key.copy_from_slice(&crate::entropy::fast_entropy()[..16]);
+    key
+}
+
+/// Default number of rotating generations; each covers a third of `max_age_seconds`
+pub const DEFAULT_GENERATIONS: usize = 3;
+
+/// Magic number at the start of a persisted filter file
+pub const PERSIST_MAGIC: &[u8; 4] = b"UBLF";
+
+/// Current persisted layout version; bump on any layout change
+pub const PERSIST_VERSION: u8 = 3;
+
+/// Magic number at the start of a compressed snapshot from `export_compressed`
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"UBLC";
+
+/// Current compressed snapshot layout version
+pub const SNAPSHOT_VERSION: u8 = 2;
+
+/// Smallest accepted filter size in bits
+pub const MIN_SIZE_BITS: usize = 1024;
+
+/// Largest accepted filter size in bits (32 MiB per generation)
+pub const MAX_SIZE_BITS: usize = 1 << 28;
+
+/// False positive rate that capacity and resize recommendations are measured against
+pub const AUTO_TUNE_TARGET_FP_RATE: f64 = 0.01;
+
+/// Saturation at which `auto_tune_check` starts recommending a larger filter
+pub const SATURATION_WARNING_RATIO: f64 = 0.8;
+
+/// Recommendations are sized for this multiple of the current item count
+const AUTO_TUNE_HEADROOM: f64 = 2.0;
+
 /// Universal Sprint Bloom Filter - Network Agnostic High-Performance Filter
 /// Supports all blockchain networks with maximum performance and security
 /// Similar to Alchemy, Infura - the fastest and most secure blockchain API
+///
+/// The bit array is a `Vec<AtomicU64>` updated with `fetch_or`/`load`, so the bit
+/// path never takes a lock. Only the timestamp map is sharded-locked; operations
+/// that find their shard busy are counted as contended.
+///
+/// Expiry is time-windowed: the filter is a ring of `config.generations`
+/// sub-filters, each covering `max_age_seconds / generations`. Inserts go to the
+/// current generation, lookups OR across all of them, and cleanup clears the
+/// oldest generation in place instead of rebuilding the whole filter.
 pub struct UniversalBloomFilter {
-    filter_data: Vec<AtomicU64>,
+    generations: Vec<Generation>,
+    current_generation: AtomicUsize,
+    rotation_lock: Mutex<()>,
     config: BloomConfig,
-    item_count: AtomicU64,
     hash_seeds: [u32; 8],
     timestamps: Arc<DashMap<Vec<u8>, u64>>,
     false_positive_count: AtomicU64,
+    query_count: AtomicU64,
+    confirmed_false_positives: AtomicU64,
+    contended_ops: AtomicU64,
+    uncontended_ops: AtomicU64,
     last_cleanup: AtomicU64,
     entropy_pool: Vec<u8>, // Additional entropy for seeding
+    provenance: Option<Mutex<ProvenanceMap>>,
     #[allow(dead_code)]
     network_stats: Arc<DashMap<String, NetworkStats>>, // Per-network statistics
 }
 
+/// Where an item in a provenance-tracking filter came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceTag {
+    /// Ingested from the block at `height`
+    Block { height: u64 },
+    /// Seen in the mempool
+    Mempool,
+    /// Inserted by an operator or API call
+    Manual,
+    /// The filter matched but has no source for the item: it was inserted untagged,
+    /// evicted at the provenance cap, or provenance is disabled
+    Unknown,
+}
+
+/// Bounded item fingerprint -> source map; the first-inserted fingerprint is evicted at the cap
+struct ProvenanceMap {
+    capacity: usize,
+    tags: HashMap<u64, SourceTag>,
+    order: VecDeque<u64>,
+}
+
+impl ProvenanceMap {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, tags: HashMap::with_capacity(capacity.min(1 << 16)), order: VecDeque::new() }
+    }
+
+    /// Tag a fingerprint. Re-tagging replaces the source but keeps the original eviction order.
+    fn record(&mut self, fingerprint: u64, tag: SourceTag) {
+        if let Some(existing) = self.tags.get_mut(&fingerprint) {
+            *existing = tag;
+            return;
+        }
+        if self.tags.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.tags.remove(&oldest);
+            }
+        }
+        self.tags.insert(fingerprint, tag);
+        self.order.push_back(fingerprint);
+    }
+}
+
+/// One time window of the filter's bit array
+struct Generation {
+    bits: Vec<AtomicU64>,
+    item_count: AtomicU64,
+    started_at: AtomicU64,
+}
+
+impl Generation {
+    fn new(bucket_count: usize, started_at: u64) -> Self {
+        Self {
+            bits: (0..bucket_count).map(|_| AtomicU64::new(0)).collect(),
+            item_count: AtomicU64::new(0),
+            started_at: AtomicU64::new(started_at),
+        }
+    }
+
+    /// Clear all bits so the generation can be reused for a new window
+    fn reset(&self, started_at: u64) {
+        for word in &self.bits {
+            word.store(0, Ordering::Relaxed);
+        }
+        self.item_count.store(0, Ordering::Relaxed);
+        self.started_at.store(started_at, Ordering::Release);
+    }
+
+    fn contains(&self, locations: &[(usize, u64)]) -> bool {
+        locations
+            .iter()
+            .all(|&(bucket_idx, bit_mask)| (self.bits[bucket_idx].load(Ordering::Relaxed) & bit_mask) != 0)
+    }
+}
+
 /// Network-specific performance statistics
 #[derive(Clone, Debug, Default)]
 pub struct NetworkStats {
@@ -236,6 +426,84 @@ pub struct NetworkStats {
     pub last_updated: u64,
 }
 
+/// An item to carry over when rebuilding a filter with `rebuild_with_config`
+#[derive(Clone, Debug)]
+pub enum BloomItem {
+    /// A UTXO as passed to `insert_utxo`
+    Utxo(TransactionId, u32),
+    /// Raw bytes as passed to `insert_data`
+    Data(Vec<u8>),
+}
+
+/// Key bytes for a UTXO. Transaction ids from `network` use `hash || vout`; ids
+/// from any other network are namespaced by appending the network name so
+/// mixed-use filters keep networks apart.
+fn utxo_preimage(network: &str, txid: &TransactionId, vout: u32) -> Vec<u8> {
+    let foreign = txid.network != network;
+    let mut preimage = Vec::with_capacity(36 + if foreign { txid.network.len() } else { 0 });
+    preimage.extend_from_slice(txid.as_bytes());
+    preimage.extend_from_slice(&vout.to_le_bytes());
+    if foreign {
+        preimage.extend_from_slice(txid.network.as_bytes());
+    }
+    preimage
+}
+
+/// Compute double SHA256 hashes with entropy mixing for maximum security.
+///
+/// Items are keyed: the first hash is SHA-256 over `hash_key || data`, the second over
+/// `data || entropy_pool`, and the pool is itself derived from the key. Without the key
+/// a peer cannot predict which bits an item sets, so it cannot craft inputs that pile
+/// onto the same bits to saturate the filter.
+fn compute_hashes(data: &[u8], hash_key: &[u8; 16], entropy_pool: &[u8]) -> Result<[u64; 2], BloomFilterError> {
+    let mut engine = bitcoin_hashes::sha256::HashEngine::default();
+    engine.input(hash_key);
+    engine.input(data);
+    let hash1 = bitcoin_hashes::sha256::Hash::from_engine(engine);
+
+    // Mix with entropy pool for additional security
+    let mut mixed_data = Vec::with_capacity(data.len() + entropy_pool.len());
+    mixed_data.extend_from_slice(data);
+    mixed_data.extend_from_slice(entropy_pool);
+
+    let mut engine2 = bitcoin_hashes::sha256::HashEngine::default();
+    engine2.input(&mixed_data);
+    let hash2 = bitcoin_hashes::sha256::Hash::from_engine(engine2);
+
+    Ok([
+        u64::from_le_bytes(hash1[0..8].try_into().map_err(|_| BloomFilterError::HashComputationError)?),
+        u64::from_le_bytes(hash2[0..8].try_into().map_err(|_| BloomFilterError::HashComputationError)?),
+    ])
+}
+
+/// Entropy pool for a filter keyed with `hash_key`, so the key alone fixes the hash functions
+fn keyed_entropy_pool(hash_key: &[u8; 16]) -> Vec<u8> {
+    let mut engine = bitcoin_hashes::sha256::HashEngine::default();
+    engine.input(b"UBLF entropy pool");
+    engine.input(hash_key);
+    bitcoin_hashes::sha256::Hash::from_engine(engine).to_byte_array().to_vec()
+}
+
+/// Optimized MurmurHash3 with entropy seeding
+fn murmur_hash3(hash: [u64; 2], hash_num: u32, tweak: u32, hash_seeds: &[u32; 8]) -> u64 {
+    let h = hash_num.wrapping_mul(0xFBA4C795).wrapping_add(tweak);
+    let mut v = h as u64 ^ hash[1];
+    v = v.wrapping_mul(0xFF51AFD7ED558CCD);
+    v = v.wrapping_mul(0xC4CEB9FE1A85EC53);
+    v ^= v >> 32;
+    v ^ hash[0] ^ hash_seeds[hash_num as usize % 8] as u64
+}
+
+/// Bits needed for `items` at `fp_rate`: `m = -n ln p / (ln 2)^2`
+fn optimal_size_bits(items: u64, fp_rate: f64) -> f64 {
+    -(items as f64) * fp_rate.ln() / (std::f64::consts::LN_2 * std::f64::consts::LN_2)
+}
+
+/// Hash count minimising the false positive rate: `k = (m / n) ln 2`, clamped to 2-7
+fn optimal_num_hashes(size_bits: usize, items: u64) -> u8 {
+    (size_bits as f64 / items as f64 * std::f64::consts::LN_2).round().clamp(2.0, 7.0) as u8
+}
+
 impl UniversalBloomFilter {
     /// Create new Universal Sprint Bloom Filter - Network Agnostic
     /// Supports all blockchain networks with maximum performance and security
@@ -249,17 +517,22 @@ pub fn new(config: Option<BloomConfig>) -> Result<Self, BloomFilterError> {
         if !(2..=7).contains(&cfg.num_hashes) {
             return Err(BloomFilterError::InvalidConfiguration("Number of hashes must be 2-7".into()));
         }
-        if cfg.size < 1024 || cfg.size > 1_000_000 {
-            return Err(BloomFilterError::InvalidConfiguration("Size must be between 1024 and 1M bits".into()));
+        if !(MIN_SIZE_BITS..=MAX_SIZE_BITS).contains(&cfg.size) {
+            return Err(BloomFilterError::InvalidConfiguration(format!(
+                "Size must be between {} and {} bits",
+                MIN_SIZE_BITS, MAX_SIZE_BITS
+            )));
+        }
+        if !(1..=16).contains(&cfg.generations) {
+            return Err(BloomFilterError::InvalidConfiguration("Generations must be 1-16".into()));
         }
 
     #[allow(clippy::manual_div_ceil)]
     let bucket_count = (cfg.size + 63) / 64;
         let mut hash_seeds = [0u32; 8];
 
-        // Cryptographically secure seed generation with additional entropy
-        let mut entropy_pool = vec![0u8; 32];
-        rand::thread_rng().fill_bytes(&mut entropy_pool);
+        // Seeds come from the secret hash key, so equal keys and tweaks hash identically
+        let entropy_pool = keyed_entropy_pool(&cfg.hash_key);
 
         let seed_bytes = cfg.tweak.to_le_bytes();
         for i in 0..8 {
@@ -271,28 +544,40 @@ pub fn new(config: Option<BloomConfig>) -> Result<Self, BloomFilterError> {
             ]);
         }
 
+        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs(),
+            Err(_) => return Err(BloomFilterError::SystemTimeError),
+        };
+
+        let provenance = (cfg.provenance_capacity > 0).then(|| Mutex::new(ProvenanceMap::new(cfg.provenance_capacity)));
+
         Ok(UniversalBloomFilter {
-            filter_data: (0..bucket_count).map(|_| AtomicU64::new(0)).collect(),
+            generations: (0..cfg.generations).map(|_| Generation::new(bucket_count, now)).collect(),
+            current_generation: AtomicUsize::new(0),
+            rotation_lock: Mutex::new(()),
             config: cfg,
-            item_count: AtomicU64::new(0),
             hash_seeds,
             timestamps: Arc::new(DashMap::with_capacity(10000)),
             false_positive_count: AtomicU64::new(0),
-            last_cleanup: AtomicU64::new(match SystemTime::now().duration_since(UNIX_EPOCH) {
-                Ok(duration) => duration.as_secs(),
-                Err(_) => return Err(BloomFilterError::SystemTimeError),
-            }),
+            query_count: AtomicU64::new(0),
+            confirmed_false_positives: AtomicU64::new(0),
+            contended_ops: AtomicU64::new(0),
+            uncontended_ops: AtomicU64::new(0),
+            last_cleanup: AtomicU64::new(now),
             entropy_pool,
+            provenance,
             network_stats: Arc::new(DashMap::new()),
         })
     }
 
     /// Insert a single UTXO with maximum performance optimization
     pub fn insert_utxo(&self, txid: &TransactionId, vout: u32) -> Result<(), BloomFilterError> {
-    let mut preimage = Vec::with_capacity(36);
-    preimage.extend_from_slice(txid.as_bytes());
-        preimage.extend_from_slice(&vout.to_le_bytes());
-        self.insert(&preimage)
+        self.insert(&self.utxo_preimage(txid, vout))
+    }
+
+    /// Key bytes for a UTXO in this filter's network
+    fn utxo_preimage(&self, txid: &TransactionId, vout: u32) -> Vec<u8> {
+        utxo_preimage(&self.config.network.name, txid, vout)
     }
 
     /// Insert a batch of UTXOs in parallel with optimal chunking
@@ -309,10 +594,7 @@ pub fn insert_batch(&self, batch: &[(TransactionId, u32)]) -> Result<(), BloomFi
         // Process in optimal chunks for maximum parallelism
         batch.par_chunks(self.config.batch_size).for_each(|chunk| {
             chunk.iter().for_each(|(txid, vout)| {
-                let mut preimage = Vec::with_capacity(36);
-                preimage.extend_from_slice(txid.as_bytes());
-                preimage.extend_from_slice(&vout.to_le_bytes());
-                let _ = self.insert_with_timestamp(&preimage, now);
+                let _ = self.insert_with_timestamp(&self.utxo_preimage(txid, *vout), now);
             });
         });
 
@@ -330,34 +612,140 @@ fn insert(&self, data: &[u8]) -> Result<(), BloomFilterError> {
 
     /// Insert with timestamp and entropy seeding for maximum performance
     fn insert_with_timestamp(&self, data: &[u8], timestamp: u64) -> Result<(), BloomFilterError> {
+        self.insert_hashed(data, timestamp).map(|_| ())
+    }
+
+    /// Insert and return the item's hashes; the first doubles as its provenance fingerprint
+    fn insert_hashed(&self, data: &[u8], timestamp: u64) -> Result<[u64; 2], BloomFilterError> {
         if data.is_empty() {
             return Err(BloomFilterError::InvalidInput("Data cannot be empty".into()));
         }
 
         let hashes = self.compute_hashes(data)?;
 
-        // Parallel bit setting for maximum performance
-        (0..self.config.num_hashes).into_par_iter().for_each(|i| {
-            let bit_pos = self.murmur_hash3(hashes, i as u32) % self.config.size as u64;
-            let bucket_idx = (bit_pos >> 6) as usize;
-            let bit_mask = 1u64 << (bit_pos & 0x3F);
+        // Lock-free bit setting into the current generation; a handful of fetch_or
+        // calls is far cheaper than fanning out to the rayon pool
+        let generation = &self.generations[self.current_generation.load(Ordering::Acquire)];
+        for i in 0..self.config.num_hashes {
+            let (bucket_idx, bit_mask) = self.bit_location(hashes, i);
+            generation.bits[bucket_idx].fetch_or(bit_mask, Ordering::Relaxed);
+        }
 
-            // Atomic OR for thread safety
-            self.filter_data[bucket_idx].fetch_or(bit_mask, Ordering::Relaxed);
-        });
+        generation.item_count.fetch_add(1, Ordering::Relaxed);
+        self.record_timestamp(data, timestamp);
 
-        self.item_count.fetch_add(1, Ordering::Relaxed);
-        self.timestamps.insert(data.to_vec(), timestamp);
+        Ok(hashes)
+    }
 
+    /// Insert a UTXO and remember `tag` as its source when provenance is enabled
+    pub fn insert_utxo_tagged(&self, txid: &TransactionId, vout: u32, tag: SourceTag) -> Result<(), BloomFilterError> {
+        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs(),
+            Err(_) => return Err(BloomFilterError::SystemTimeError),
+        };
+        let hashes = self.insert_hashed(&self.utxo_preimage(txid, vout), now)?;
+        self.record_provenance(&[hashes[0]], tag);
         Ok(())
     }
 
+    /// Insert a batch of UTXOs sharing one source, e.g. the outputs of a single block
+    pub fn insert_batch_tagged(&self, batch: &[(TransactionId, u32)], tag: SourceTag) -> Result<(), BloomFilterError> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs(),
+            Err(_) => return Err(BloomFilterError::SystemTimeError),
+        };
+
+        // One provenance lock per chunk rather than per item
+        batch.par_chunks(self.config.batch_size).for_each(|chunk| {
+            let fingerprints: Vec<u64> = chunk
+                .iter()
+                .filter_map(|(txid, vout)| self.insert_hashed(&self.utxo_preimage(txid, *vout), now).ok())
+                .map(|hashes| hashes[0])
+                .collect();
+            self.record_provenance(&fingerprints, tag);
+        });
+
+        Ok(())
+    }
+
+    fn record_provenance(&self, fingerprints: &[u64], tag: SourceTag) {
+        if let Some(provenance) = &self.provenance {
+            let mut provenance = provenance.lock().unwrap_or_else(|e| e.into_inner());
+            for fingerprint in fingerprints {
+                provenance.record(*fingerprint, tag);
+            }
+        }
+    }
+
+    /// `None` on a miss; on a hit, where the UTXO came from, or `SourceTag::Unknown`
+    /// when no source is recorded for it
+    pub fn contains_utxo_with_provenance(
+        &self,
+        txid: &TransactionId,
+        vout: u32,
+    ) -> Result<Option<SourceTag>, BloomFilterError> {
+        let preimage = self.utxo_preimage(txid, vout);
+        if !self.contains(&preimage)? {
+            return Ok(None);
+        }
+        let Some(provenance) = &self.provenance else {
+            return Ok(Some(SourceTag::Unknown));
+        };
+        let fingerprint = self.compute_hashes(&preimage)?[0];
+        let provenance = provenance.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(Some(provenance.tags.get(&fingerprint).copied().unwrap_or(SourceTag::Unknown)))
+    }
+
+    /// Bucket index and mask of the `i`-th bit for an item
+    #[inline]
+    fn bit_location(&self, hashes: [u64; 2], i: u8) -> (usize, u64) {
+        let bit_pos = self.murmur_hash3(hashes, i as u32) % self.config.size as u64;
+        ((bit_pos >> 6) as usize, 1u64 << (bit_pos & 0x3F))
+    }
+
+    /// Store the insertion timestamp, counting whether the shard lock was busy
+    fn record_timestamp(&self, data: &[u8], timestamp: u64) {
+        match self.timestamps.try_get_mut(data) {
+            TryResult::Present(mut entry) => {
+                *entry = timestamp;
+                self.uncontended_ops.fetch_add(1, Ordering::Relaxed);
+            }
+            TryResult::Absent => {
+                self.uncontended_ops.fetch_add(1, Ordering::Relaxed);
+                self.timestamps.insert(data.to_vec(), timestamp);
+            }
+            TryResult::Locked => {
+                self.contended_ops.fetch_add(1, Ordering::Relaxed);
+                self.timestamps.insert(data.to_vec(), timestamp);
+            }
+        }
+    }
+
+    /// Look up an insertion timestamp, counting whether the shard lock was busy
+    fn lookup_timestamp(&self, data: &[u8]) -> Option<u64> {
+        match self.timestamps.try_get(data) {
+            TryResult::Present(entry) => {
+                self.uncontended_ops.fetch_add(1, Ordering::Relaxed);
+                Some(*entry)
+            }
+            TryResult::Absent => {
+                self.uncontended_ops.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            TryResult::Locked => {
+                self.contended_ops.fetch_add(1, Ordering::Relaxed);
+                self.timestamps.get(data).map(|entry| *entry)
+            }
+        }
+    }
+
     /// Check if a single UTXO is present with false positive tracking
     pub fn contains_utxo(&self, txid: &TransactionId, vout: u32) -> Result<bool, BloomFilterError> {
-        let mut preimage = Vec::with_capacity(36);
-                preimage.extend_from_slice(txid.as_bytes());
-        preimage.extend_from_slice(&vout.to_le_bytes());
-        self.contains(&preimage)
+        self.contains(&self.utxo_preimage(txid, vout))
     }
 
     /// Check a batch of UTXOs with optimal parallelism
@@ -378,27 +766,26 @@ fn contains(&self, data: &[u8]) -> Result<bool, BloomFilterError> {
         if data.is_empty() {
             return Ok(false);
         }
+        self.query_count.fetch_add(1, Ordering::Relaxed);
 
         let hashes = self.compute_hashes(data)?;
 
-        // Early exit optimization - check all bits in parallel
-        let all_present = (0..self.config.num_hashes).into_par_iter().all(|i| {
-            let bit_pos = self.murmur_hash3(hashes, i as u32) % self.config.size as u64;
-            let bucket_idx = (bit_pos >> 6) as usize;
-            let bit_mask = 1u64 << (bit_pos & 0x3F);
-            (self.filter_data[bucket_idx].load(Ordering::Relaxed) & bit_mask) != 0
-        });
+        // Lock-free check across generations, each exiting on its first unset bit
+        let locations: Vec<(usize, u64)> = (0..self.config.num_hashes)
+            .map(|i| self.bit_location(hashes, i))
+            .collect();
+        let all_present = self.generations.iter().any(|g| g.contains(&locations));
 
         // Track false positives for analytics
         if all_present {
             // Verify with timestamp to reduce false positives
-            if let Some(entry_time) = self.timestamps.get(data) {
+            if let Some(entry_time) = self.lookup_timestamp(data) {
                 let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
                     Ok(duration) => duration.as_secs(),
                     Err(_) => return Err(BloomFilterError::SystemTimeError),
                 };
 
-                if now.saturating_sub(*entry_time) > self.config.max_age_seconds {
+                if now.saturating_sub(entry_time) > self.config.max_age_seconds {
                     // Entry is too old, treat as false positive
                     self.false_positive_count.fetch_add(1, Ordering::Relaxed);
                     return Ok(false);
@@ -413,35 +800,12 @@ fn contains(&self, data: &[u8]) -> Result<bool, BloomFilterError> {
         Ok(all_present)
     }
 
-    /// Compute double SHA256 hashes with entropy mixing for maximum security
     fn compute_hashes(&self, data: &[u8]) -> Result<[u64; 2], BloomFilterError> {
-        let mut engine = bitcoin_hashes::sha256::HashEngine::default();
-        engine.input(data);
-        let hash1 = bitcoin_hashes::sha256::Hash::from_engine(engine);
-
-        // Mix with entropy pool for additional security
-        let mut mixed_data = Vec::with_capacity(data.len() + self.entropy_pool.len());
-        mixed_data.extend_from_slice(data);
-        mixed_data.extend_from_slice(&self.entropy_pool);
-
-        let mut engine2 = bitcoin_hashes::sha256::HashEngine::default();
-        engine2.input(&mixed_data);
-        let hash2 = bitcoin_hashes::sha256::Hash::from_engine(engine2);
-
-        Ok([
-            u64::from_le_bytes(hash1[0..8].try_into().map_err(|_| BloomFilterError::HashComputationError)?),
-            u64::from_le_bytes(hash2[0..8].try_into().map_err(|_| BloomFilterError::HashComputationError)?),
-        ])
+        compute_hashes(data, &self.config.hash_key, &self.entropy_pool)
     }
 
-    /// Optimized MurmurHash3 with entropy seeding
     fn murmur_hash3(&self, hash: [u64; 2], hash_num: u32) -> u64 {
-        let h = hash_num.wrapping_mul(0xFBA4C795).wrapping_add(self.config.tweak);
-        let mut v = h as u64 ^ hash[1];
-        v = v.wrapping_mul(0xFF51AFD7ED558CCD);
-        v = v.wrapping_mul(0xC4CEB9FE1A85EC53);
-        v ^= v >> 32;
-        v ^ hash[0] ^ self.hash_seeds[hash_num as usize % 8] as u64
+        murmur_hash3(hash, hash_num, self.config.tweak, &self.hash_seeds)
     }
 
     /// Load all transactions from a block in parallel with maximum optimization
@@ -461,31 +825,188 @@ pub fn load_block(&self, block: &BlockData) -> Result<(), BloomFilterError> {
         Ok(())
     }
 
-    /// Calculate theoretical false positive rate
+    /// Calculate theoretical false positive rate.
+    ///
+    /// A lookup matches if any generation matches, so the per-generation rates
+    /// combine as `1 - prod(1 - fp_g)`.
     pub fn false_positive_rate(&self) -> f64 {
-        let n = self.item_count.load(Ordering::Relaxed) as f64;
         let m = self.config.size as f64;
         let k = self.config.num_hashes as f64;
+        if m == 0.0 {
+            return 0.0;
+        }
+
+        let miss_probability: f64 = self
+            .generations
+            .iter()
+            .map(|g| {
+                let n = g.item_count.load(Ordering::Relaxed) as f64;
+                1.0 - (1.0 - (-k * n / m).exp()).powf(k)
+            })
+            .product();
+        1.0 - miss_probability
+    }
+
+    /// Record that a positive answer from this filter turned out to be wrong when
+    /// checked against the authoritative index
+    pub fn confirm_false_positive(&self) {
+        self.confirmed_false_positives.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of non-empty lookups answered by this filter
+    pub fn query_count(&self) -> u64 {
+        self.query_count.load(Ordering::Relaxed)
+    }
+
+    /// Number of false positives reported via `confirm_false_positive`
+    pub fn confirmed_false_positive_count(&self) -> u64 {
+        self.confirmed_false_positives.load(Ordering::Relaxed)
+    }
 
-        if n == 0.0 || m == 0.0 {
+    /// Confirmed false positives divided by total queries
+    pub fn observed_false_positive_rate(&self) -> f64 {
+        let queries = self.query_count();
+        if queries == 0 {
             0.0
         } else {
-            (1.0 - (-k * n / m).exp()).powf(k)
+            self.confirmed_false_positive_count() as f64 / queries as f64
         }
     }
 
+    /// Optimal `(size_bits, num_hashes)` for holding `projected_items` at `target_fp_rate`.
+    ///
+    /// The size from the standard formula is rounded up to a power of two and capped at
+    /// `MAX_SIZE_BITS`; the hash count is then chosen for the rounded size.
+    pub fn recommend_config(target_fp_rate: f64, projected_items: u64) -> Result<(usize, u8), BloomFilterError> {
+        if !(target_fp_rate > 0.0 && target_fp_rate < 1.0) {
+            return Err(BloomFilterError::InvalidInput("Target false positive rate must be between 0 and 1".into()));
+        }
+        if projected_items == 0 {
+            return Err(BloomFilterError::InvalidInput("Projected item count must be positive".into()));
+        }
+
+        let ideal = optimal_size_bits(projected_items, target_fp_rate).ceil().min(MAX_SIZE_BITS as f64) as usize;
+        let size_bits = ideal.next_power_of_two().clamp(MIN_SIZE_BITS, MAX_SIZE_BITS);
+        Ok((size_bits, optimal_num_hashes(size_bits, projected_items)))
+    }
+
+    /// Items the filter holds before its theoretical false positive rate passes
+    /// `target_fp_rate`, assuming inserts are spread evenly across generations.
+    pub fn capacity(&self, target_fp_rate: f64) -> u64 {
+        let generations = self.generations.len() as f64;
+        let m = self.config.size as f64;
+        let k = self.config.num_hashes as f64;
+
+        // Lookups OR across generations, so each one only gets its share of the target
+        let per_generation_fp = 1.0 - (1.0 - target_fp_rate).powf(1.0 / generations);
+        let per_generation_items = -m / k * (1.0 - per_generation_fp.powf(1.0 / k)).ln();
+        (per_generation_items * generations) as u64
+    }
+
+    /// Saturation against `AUTO_TUNE_TARGET_FP_RATE`, with a recommended config once it
+    /// reaches `SATURATION_WARNING_RATIO`
+    fn tuning(&self) -> (f64, Option<(usize, u8)>) {
+        let items = self.total_items();
+        let saturation = items as f64 / self.capacity(AUTO_TUNE_TARGET_FP_RATE).max(1) as f64;
+        if saturation < SATURATION_WARNING_RATIO {
+            return (saturation, None);
+        }
+        let projected = (items as f64 * AUTO_TUNE_HEADROOM).ceil() as u64;
+        (saturation, Self::recommend_config(AUTO_TUNE_TARGET_FP_RATE, projected).ok())
+    }
+
+    /// Compare the item count against capacity and log a warning with a recommended
+    /// `(size_bits, num_hashes)` when the filter is close to full. `stats()` carries the
+    /// same saturation ratio and recommended size.
+    pub fn auto_tune_check(&self) -> Option<(usize, u8)> {
+        let (saturation, recommendation) = self.tuning();
+        if let Some((size_bits, num_hashes)) = recommendation {
+            log::warn!(
+                "Bloom filter for {} is at {:.0}% of capacity ({} items in {} bits); rebuild with {} bits and {} hashes",
+                self.config.network.name,
+                saturation * 100.0,
+                self.total_items(),
+                self.config.size,
+                size_bits,
+                num_hashes
+            );
+        }
+        recommendation
+    }
+
+    /// Build a new filter from `new_config` and insert the re-supplied items into it.
+    ///
+    /// Only items passed in `reinsert` are carried over. Those this filter holds a
+    /// timestamp for keep their age; counters start fresh. The network cannot change
+    /// because UTXO keys depend on it.
+    pub fn rebuild_with_config(
+        &self,
+        new_config: BloomConfig,
+        reinsert: impl Iterator<Item = BloomItem>,
+    ) -> Result<Self, BloomFilterError> {
+        if new_config.network.name != self.config.network.name {
+            return Err(BloomFilterError::NetworkMismatch {
+                expected: self.config.network.name.clone(),
+                found: new_config.network.name,
+            });
+        }
+
+        let rebuilt = Self::new(Some(new_config))?;
+        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs(),
+            Err(_) => return Err(BloomFilterError::SystemTimeError),
+        };
+        for item in reinsert {
+            let key = match item {
+                BloomItem::Utxo(txid, vout) => rebuilt.utxo_preimage(&txid, vout),
+                BloomItem::Data(data) => data,
+            };
+            let timestamp = self.timestamps.get(&key).map(|entry| *entry).unwrap_or(now);
+            rebuilt.insert_with_timestamp(&key, timestamp)?;
+        }
+        Ok(rebuilt)
+    }
+
+    /// Total items across all live generations
+    fn total_items(&self) -> u64 {
+        self.generations.iter().map(|g| g.item_count.load(Ordering::Relaxed)).sum()
+    }
+
+    /// Item counts per generation, oldest first
+    fn generation_item_counts(&self) -> Vec<u64> {
+        let len = self.generations.len();
+        let current = self.current_generation.load(Ordering::Acquire);
+        (1..=len)
+            .map(|offset| self.generations[(current + offset) % len].item_count.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// Seconds covered by each generation
+    fn generation_window(&self) -> u64 {
+        (self.config.max_age_seconds / self.generations.len() as u64).max(1)
+    }
+
     /// Get performance statistics
     pub fn stats(&self) -> BloomFilterStats {
         let now = SystemTime::now().duration_since(UNIX_EPOCH)
             .unwrap_or_default().as_secs();
+        let (saturation_ratio, recommendation) = self.tuning();
 
         BloomFilterStats {
-            item_count: self.item_count.load(Ordering::Relaxed),
+            item_count: self.total_items(),
             false_positive_count: self.false_positive_count.load(Ordering::Relaxed),
             theoretical_fp_rate: self.false_positive_rate(),
-            memory_usage_bytes: self.filter_data.len() * 8,
+            query_count: self.query_count(),
+            confirmed_false_positives: self.confirmed_false_positive_count(),
+            observed_fp_rate: self.observed_false_positive_rate(),
+            memory_usage_bytes: self.generations.iter().map(|g| g.bits.len() * 8).sum(),
+            generation_item_counts: self.generation_item_counts(),
             timestamp_entries: self.timestamps.len(),
             average_age_seconds: self.average_entry_age(now),
+            contended_operations: self.contended_ops.load(Ordering::Relaxed),
+            uncontended_operations: self.uncontended_ops.load(Ordering::Relaxed),
+            saturation_ratio,
+            recommended_size: recommendation.map(|(size_bits, _)| size_bits),
         }
     }
 
@@ -506,12 +1027,21 @@ fn average_entry_age(&self, now: u64) -> f64 {
         }
     }
 
-    /// Cleanup old entries to maintain performance
+    /// Cleanup old entries to maintain performance.
+    ///
+    /// Expired generations are cleared and reused, and timestamps older than
+    /// `max_age_seconds` are dropped. Returns the number of timestamps removed.
     pub fn cleanup(&self) -> Result<usize, BloomFilterError> {
         let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
             Ok(duration) => duration.as_secs(),
             Err(_) => return Err(BloomFilterError::SystemTimeError),
         };
+        Ok(self.cleanup_at(now))
+    }
+
+    /// Cleanup as of `now` (seconds since the Unix epoch)
+    fn cleanup_at(&self, now: u64) -> usize {
+        self.rotate_generations(now);
 
         let mut removed = 0usize;
         let max_age = self.config.max_age_seconds;
@@ -527,7 +1057,38 @@ pub fn cleanup(&self) -> Result<usize, BloomFilterError> {
         });
 
         self.last_cleanup.store(now, Ordering::Relaxed);
-        Ok(removed)
+        removed
+    }
+
+    /// Advance the current generation once per elapsed window, clearing the
+    /// oldest generation each time. Returns the number of rotations performed.
+    fn rotate_generations(&self, now: u64) -> usize {
+        let _guard = self.rotation_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let len = self.generations.len();
+        let window = self.generation_window();
+        let mut rotations = 0;
+
+        while rotations < len {
+            let current = self.current_generation.load(Ordering::Acquire);
+            let started_at = self.generations[current].started_at.load(Ordering::Acquire);
+            let elapsed = now.saturating_sub(started_at);
+            if elapsed < window {
+                break;
+            }
+            // After a full cycle of idle time every generation has expired, so
+            // restart the schedule from now rather than replaying missed windows
+            if elapsed >= window.saturating_mul(len as u64) {
+                for generation in &self.generations {
+                    generation.reset(now);
+                }
+                return len;
+            }
+            let next = (current + 1) % len;
+            self.generations[next].reset(started_at + window);
+            self.current_generation.store(next, Ordering::Release);
+            rotations += 1;
+        }
+        rotations
     }
 
     /// Auto-cleanup if needed
@@ -539,9 +1100,12 @@ pub fn auto_cleanup(&self) -> Result<bool, BloomFilterError> {
 
         let last_cleanup = self.last_cleanup.load(Ordering::Relaxed);
         let cleanup_interval = 3600; // 1 hour
+        let current = &self.generations[self.current_generation.load(Ordering::Acquire)];
+        let window_elapsed = now.saturating_sub(current.started_at.load(Ordering::Acquire)) >= self.generation_window();
 
-        if now.saturating_sub(last_cleanup) > cleanup_interval {
+        if window_elapsed || now.saturating_sub(last_cleanup) > cleanup_interval {
             let _ = self.cleanup()?;
+            self.auto_tune_check();
             Ok(true)
         } else {
             Ok(false)
@@ -550,12 +1114,12 @@ pub fn auto_cleanup(&self) -> Result<bool, BloomFilterError> {
 
     /// Get current item count (thread-safe)
     pub fn get_item_count(&self) -> usize {
-        self.item_count.load(Ordering::Relaxed) as usize
+        self.total_items() as usize
     }
 
     /// Get false positive count (thread-safe)
     pub fn get_false_positive_count(&self) -> f64 {
-        let items = self.item_count.load(Ordering::Relaxed) as f64;
+        let items = self.total_items() as f64;
         let false_positives = self.false_positive_count.load(Ordering::Relaxed) as f64;
         if items > 0.0 {
             false_positives / items
@@ -564,6 +1128,249 @@ pub fn get_false_positive_count(&self) -> f64 {
         }
     }
 
+    /// Name of the network this filter was configured for
+    pub fn network_name(&self) -> &str {
+        &self.config.network.name
+    }
+
+    /// Persist the filter (bit array, config, seeds, timestamps and counters) to `path`.
+    ///
+    /// The file holds the hash key and seeds, so it should be protected like any other key material.
+    /// Data is written to a temporary sibling file first and renamed into place.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), BloomFilterError> {
+        let path = path.as_ref();
+        let bytes = self.to_bytes();
+        let tmp_path = path.with_extension("tmp");
+        let mut file = std::fs::File::create(&tmp_path).map_err(|e| BloomFilterError::Io(e.to_string()))?;
+        file.write_all(&bytes).map_err(|e| BloomFilterError::Io(e.to_string()))?;
+        file.sync_all().map_err(|e| BloomFilterError::Io(e.to_string()))?;
+        std::fs::rename(&tmp_path, path).map_err(|e| BloomFilterError::Io(e.to_string()))
+    }
+
+    /// Load a filter saved with `save_to_file`, requiring it to match `network`
+    pub fn load_from_file<P: AsRef<Path>>(path: P, network: &NetworkConfig) -> Result<Self, BloomFilterError> {
+        let bytes = std::fs::read(path).map_err(|e| BloomFilterError::Io(e.to_string()))?;
+        Self::from_bytes(&bytes, network)
+    }
+
+    /// Serialize into the versioned binary layout:
+    /// magic, version, network, config, seeds, counters, generations, timestamps, SHA-256 trailer
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let bit_bytes: usize = self.generations.iter().map(|g| 16 + g.bits.len() * 8).sum();
+        let mut out = Vec::with_capacity(64 + bit_bytes + self.timestamps.len() * 48);
+        out.extend_from_slice(PERSIST_MAGIC);
+        out.push(PERSIST_VERSION);
+
+        let network = &self.config.network;
+        put_bytes(&mut out, network.name.as_bytes());
+        put_u64(&mut out, network.hash_size as u64);
+        put_u64(&mut out, network.block_time_seconds);
+        put_u64(&mut out, network.max_block_size as u64);
+        put_bytes(&mut out, network.consensus_mechanism.as_bytes());
+
+        put_u64(&mut out, self.config.size as u64);
+        out.push(self.config.num_hashes);
+        out.extend_from_slice(&self.config.tweak.to_le_bytes());
+        out.extend_from_slice(&self.config.hash_key);
+        out.push(self.config.flags);
+        put_u64(&mut out, self.config.max_age_seconds);
+        put_u64(&mut out, self.config.generations as u64);
+        put_u64(&mut out, self.config.batch_size as u64);
+        out.push(self.config.enable_compression as u8);
+        out.push(self.config.enable_metrics as u8);
+
+        for seed in &self.hash_seeds {
+            out.extend_from_slice(&seed.to_le_bytes());
+        }
+        put_bytes(&mut out, &self.entropy_pool);
+
+        put_u64(&mut out, self.false_positive_count.load(Ordering::Relaxed));
+        put_u64(&mut out, self.last_cleanup.load(Ordering::Relaxed));
+
+        // Hold the rotation lock so the generation ring is captured consistently
+        let _guard = self.rotation_lock.lock().unwrap_or_else(|e| e.into_inner());
+        put_u64(&mut out, self.current_generation.load(Ordering::Acquire) as u64);
+        put_u64(&mut out, self.generations[0].bits.len() as u64);
+        for generation in &self.generations {
+            put_u64(&mut out, generation.started_at.load(Ordering::Acquire));
+            put_u64(&mut out, generation.item_count.load(Ordering::Relaxed));
+            for word in &generation.bits {
+                out.extend_from_slice(&word.load(Ordering::Relaxed).to_le_bytes());
+            }
+        }
+
+        put_u64(&mut out, self.timestamps.len() as u64);
+        for entry in self.timestamps.iter() {
+            put_bytes(&mut out, entry.key());
+            put_u64(&mut out, *entry.value());
+        }
+
+        let checksum = bitcoin_hashes::sha256::Hash::hash(&out);
+        out.extend_from_slice(checksum.as_byte_array());
+        out
+    }
+
+    /// Deserialize bytes produced by `to_bytes`, requiring the stored network to match `network`
+    pub fn from_bytes(bytes: &[u8], network: &NetworkConfig) -> Result<Self, BloomFilterError> {
+        if bytes.len() < PERSIST_MAGIC.len() + 1 + 32 {
+            return Err(BloomFilterError::Persistence("File is truncated".into()));
+        }
+        if &bytes[..4] != PERSIST_MAGIC {
+            return Err(BloomFilterError::Persistence("Not a bloom filter file (bad magic)".into()));
+        }
+        if bytes[4] != PERSIST_VERSION {
+            return Err(BloomFilterError::UnsupportedVersion(bytes[4]));
+        }
+        let (body, trailer) = bytes.split_at(bytes.len() - 32);
+        let checksum = bitcoin_hashes::sha256::Hash::hash(body);
+        if checksum.as_byte_array()[..] != trailer[..] {
+            return Err(BloomFilterError::Persistence("Checksum mismatch (file truncated or corrupted)".into()));
+        }
+
+        let mut r = PersistReader { data: &body[5..] };
+        let stored_network = NetworkConfig {
+            name: r.string()?,
+            hash_size: r.u64()? as usize,
+            block_time_seconds: r.u64()?,
+            max_block_size: r.u64()? as usize,
+            consensus_mechanism: r.string()?,
+        };
+        if stored_network.name != network.name || stored_network.hash_size != network.hash_size {
+            return Err(BloomFilterError::NetworkMismatch {
+                expected: network.name.clone(),
+                found: stored_network.name,
+            });
+        }
+
+        let config = BloomConfig {
+            network: stored_network,
+            size: r.u64()? as usize,
+            num_hashes: r.u8()?,
+            tweak: u32::from_le_bytes(r.array::<4>()?),
+            hash_key: r.array::<16>()?,
+            flags: r.u8()?,
+            max_age_seconds: r.u64()?,
+            generations: r.u64()? as usize,
+            batch_size: r.u64()? as usize,
+            enable_compression: r.u8()? != 0,
+            enable_metrics: r.u8()? != 0,
+            // Provenance is in-memory only
+            provenance_capacity: 0,
+        };
+
+        let mut hash_seeds = [0u32; 8];
+        for seed in hash_seeds.iter_mut() {
+            *seed = u32::from_le_bytes(r.array::<4>()?);
+        }
+        let entropy_pool = r.bytes()?.to_vec();
+
+        let false_positive_count = r.u64()?;
+        let last_cleanup = r.u64()?;
+        let current_generation = r.u64()? as usize;
+        let bucket_count = r.u64()? as usize;
+
+        // Check the stored size against what is actually left before allocating for it
+        if bucket_count != config.size.div_ceil(64) {
+            return Err(BloomFilterError::Persistence("Bit array size does not match configuration".into()));
+        }
+        let generation_bytes = bucket_count
+            .checked_mul(8)
+            .and_then(|bits| bits.checked_add(16))
+            .and_then(|generation| generation.checked_mul(config.generations));
+        if generation_bytes.is_none_or(|needed| needed > r.data.len()) {
+            return Err(BloomFilterError::Persistence("File is truncated".into()));
+        }
+
+        // Reuse constructor validation for the stored configuration
+        let mut filter = UniversalBloomFilter::new(Some(config))?;
+        if current_generation >= filter.generations.len() {
+            return Err(BloomFilterError::Persistence("Current generation out of range".into()));
+        }
+        for generation in &filter.generations {
+            generation.started_at.store(r.u64()?, Ordering::Relaxed);
+            generation.item_count.store(r.u64()?, Ordering::Relaxed);
+            for word in &generation.bits {
+                word.store(r.u64()?, Ordering::Relaxed);
+            }
+        }
+
+        let timestamp_count = r.u64()? as usize;
+        let timestamps = DashMap::with_capacity(timestamp_count.min(r.data.len() / 12));
+        for _ in 0..timestamp_count {
+            let key = r.bytes()?.to_vec();
+            timestamps.insert(key, r.u64()?);
+        }
+        if !r.data.is_empty() {
+            return Err(BloomFilterError::Persistence("Trailing data after filter contents".into()));
+        }
+
+        filter.current_generation = AtomicUsize::new(current_generation);
+        filter.hash_seeds = hash_seeds;
+        filter.entropy_pool = entropy_pool;
+        filter.timestamps = Arc::new(timestamps);
+        filter.false_positive_count = AtomicU64::new(false_positive_count);
+        filter.last_cleanup = AtomicU64::new(last_cleanup);
+        Ok(filter)
+    }
+
+    /// Compact, query-only snapshot for light clients, read back with `CompactBloomView`.
+    ///
+    /// Layout: magic, version, network, size, hash count, tweak, hash key, seeds, entropy pool,
+    /// generation count, the generations, SHA-256 trailer. Each generation is stored
+    /// whichever way is smaller:
+    ///
+    /// - tag 0: the raw bit array, bit `i` in byte `i / 8` at position `i % 8`
+    /// - tag 1: Golomb-Rice coded set bits in the style of BIP158: parameter `P`,
+    ///   count, then the gap from the previous set bit (the first from -1) as a
+    ///   unary quotient of 1s ended by a 0 and `P` remainder bits, MSB first.
+    ///   `P` is `floor(log2(size / count))`
+    ///
+    /// Sparse filters shrink to roughly `P + 2` bits per set bit. The snapshot holds the
+    /// hash key and seeds clients need to query it, so anyone holding one can test membership
+    /// offline and search for colliding items; only hand it to trusted callers.
+    pub fn export_compressed(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(SNAPSHOT_MAGIC);
+        out.push(SNAPSHOT_VERSION);
+        put_bytes(&mut out, self.config.network.name.as_bytes());
+        put_u64(&mut out, self.config.size as u64);
+        out.push(self.config.num_hashes);
+        out.extend_from_slice(&self.config.tweak.to_le_bytes());
+        out.extend_from_slice(&self.config.hash_key);
+        for seed in &self.hash_seeds {
+            out.extend_from_slice(&seed.to_le_bytes());
+        }
+        put_bytes(&mut out, &self.entropy_pool);
+
+        let _guard = self.rotation_lock.lock().unwrap_or_else(|e| e.into_inner());
+        put_u64(&mut out, self.generations.len() as u64);
+        for generation in &self.generations {
+            let words: Vec<u64> = generation.bits.iter().map(|w| w.load(Ordering::Relaxed)).collect();
+            let set_bits: u64 = words.iter().map(|w| w.count_ones() as u64).sum();
+            let rice = rice_encode(&words, self.config.size, set_bits);
+            if rice.len() < words.len() * 8 {
+                out.push(SNAPSHOT_RICE);
+                out.extend_from_slice(&rice);
+            } else {
+                out.push(SNAPSHOT_RAW);
+                let raw: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+                put_bytes(&mut out, &raw);
+            }
+        }
+
+        let checksum = bitcoin_hashes::sha256::Hash::hash(&out);
+        out.extend_from_slice(checksum.as_byte_array());
+        out
+    }
+
+    /// Changes whenever the filter's contents may have: on every insert and on every
+    /// generation rotation. Suitable as an HTTP ETag for `export_compressed` output.
+    pub fn snapshot_tag(&self) -> String {
+        let current = self.current_generation.load(Ordering::Acquire);
+        let started_at = self.generations[current].started_at.load(Ordering::Acquire);
+        format!("{:x}-{:x}-{:x}", self.total_items(), current, started_at)
+    }
+
     /// Generic insert method for C FFI
     pub fn insert_data(&self, data: &[u8]) -> Result<(), BloomFilterError> {
         self.insert(data)
@@ -575,15 +1382,329 @@ pub fn contains_data(&self, data: &[u8]) -> Result<bool, BloomFilterError> {
     }
 }
 
+fn put_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Bounds-checked cursor over a persisted filter body
+struct PersistReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PersistReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], BloomFilterError> {
+        if self.data.len() < n {
+            return Err(BloomFilterError::Persistence("File is truncated".into()));
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], BloomFilterError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, BloomFilterError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, BloomFilterError> {
+        Ok(u64::from_le_bytes(self.array::<8>()?))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], BloomFilterError> {
+        let len = u32::from_le_bytes(self.array::<4>()?) as usize;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, BloomFilterError> {
+        String::from_utf8(self.bytes()?.to_vec())
+            .map_err(|_| BloomFilterError::Persistence("Invalid UTF-8 in stored string".into()))
+    }
+}
+
+/// Snapshot generation stored as the raw bit array
+const SNAPSHOT_RAW: u8 = 0;
+
+/// Snapshot generation stored as Golomb-Rice coded set bits
+const SNAPSHOT_RICE: u8 = 1;
+
+/// MSB-first bit writer for Golomb-Rice streams
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    used: u8,
+}
+
+impl BitWriter {
+    fn push(&mut self, bit: bool) {
+        if self.used == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().expect("byte pushed above") |= 0x80 >> self.used;
+        }
+        self.used = (self.used + 1) % 8;
+    }
+
+    fn push_bits(&mut self, value: u64, count: u8) {
+        for i in (0..count).rev() {
+            self.push((value >> i) & 1 == 1);
+        }
+    }
+}
+
+/// MSB-first bit reader over a Golomb-Rice stream
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> Option<bool> {
+        let byte = *self.bytes.get(self.pos / 8)?;
+        let bit = byte & (0x80 >> (self.pos % 8)) != 0;
+        self.pos += 1;
+        Some(bit)
+    }
+
+    /// Next gap: unary quotient then `parameter` remainder bits
+    fn rice(&mut self, parameter: u8) -> Option<u64> {
+        let mut quotient = 0u64;
+        while self.bit()? {
+            quotient += 1;
+        }
+        let mut remainder = 0u64;
+        for _ in 0..parameter {
+            remainder = (remainder << 1) | self.bit()? as u64;
+        }
+        Some((quotient << parameter) | remainder)
+    }
+}
+
+/// Parameter, count and stream for the set bits of `words`, as laid out in a snapshot
+fn rice_encode(words: &[u64], size: usize, set_bits: u64) -> Vec<u8> {
+    let parameter = (size as u64 / set_bits.max(1)).max(1).ilog2() as u8;
+    let mut writer = BitWriter::default();
+    let mut next = 0u64;
+    for (index, word) in words.iter().enumerate() {
+        let mut remaining = *word;
+        while remaining != 0 {
+            let position = index as u64 * 64 + remaining.trailing_zeros() as u64;
+            let gap = position - next;
+            for _ in 0..gap >> parameter {
+                writer.push(true);
+            }
+            writer.push(false);
+            writer.push_bits(gap, parameter);
+            next = position + 1;
+            remaining &= remaining - 1;
+        }
+    }
+
+    let mut out = vec![parameter];
+    put_u64(&mut out, set_bits);
+    put_bytes(&mut out, &writer.bytes);
+    out
+}
+
+/// One generation of a snapshot, still in its encoded form
+#[derive(Debug, Clone)]
+enum CompactGeneration {
+    Raw(Vec<u8>),
+    Rice { parameter: u8, count: u64, stream: Vec<u8> },
+}
+
+impl CompactGeneration {
+    /// Whether every position in `targets` (sorted, deduplicated) is set. Rice streams
+    /// are decoded on the fly and the scan stops at the first missing position.
+    fn contains(&self, targets: &[u64]) -> bool {
+        match self {
+            CompactGeneration::Raw(bytes) => targets
+                .iter()
+                .all(|&pos| bytes.get((pos / 8) as usize).is_some_and(|b| b & (1 << (pos % 8)) != 0)),
+            CompactGeneration::Rice { parameter, count, stream } => {
+                let mut reader = BitReader { bytes: stream, pos: 0 };
+                let mut next = 0u64;
+                let mut pending = targets.iter().peekable();
+                for _ in 0..*count {
+                    let Some(gap) = reader.rice(*parameter) else { return false };
+                    let position = next + gap;
+                    next = position + 1;
+                    while let Some(&&target) = pending.peek() {
+                        if target < position {
+                            return false;
+                        }
+                        if target > position {
+                            break;
+                        }
+                        pending.next();
+                    }
+                    if pending.peek().is_none() {
+                        return true;
+                    }
+                }
+                pending.peek().is_none()
+            }
+        }
+    }
+}
+
+/// Query-only view of a snapshot produced by `UniversalBloomFilter::export_compressed`.
+///
+/// Generations stay in their compressed form; `contains` decodes only as far as it
+/// needs to. Unlike the full filter there are no timestamps, so expired entries and
+/// false positives are not screened out.
+#[derive(Debug, Clone)]
+pub struct CompactBloomView {
+    network: String,
+    size: u64,
+    num_hashes: u8,
+    tweak: u32,
+    hash_key: [u8; 16],
+    hash_seeds: [u32; 8],
+    entropy_pool: Vec<u8>,
+    generations: Vec<CompactGeneration>,
+}
+
+impl CompactBloomView {
+    /// Parse and check a snapshot, including its checksum and every Rice stream
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BloomFilterError> {
+        if bytes.len() < SNAPSHOT_MAGIC.len() + 1 + 32 {
+            return Err(BloomFilterError::Persistence("Snapshot is truncated".into()));
+        }
+        if &bytes[..4] != SNAPSHOT_MAGIC {
+            return Err(BloomFilterError::Persistence("Not a bloom filter snapshot (bad magic)".into()));
+        }
+        if bytes[4] != SNAPSHOT_VERSION {
+            return Err(BloomFilterError::Persistence(format!("Unsupported snapshot version {}", bytes[4])));
+        }
+        let (body, trailer) = bytes.split_at(bytes.len() - 32);
+        let checksum = bitcoin_hashes::sha256::Hash::hash(body);
+        if checksum.as_byte_array()[..] != trailer[..] {
+            return Err(BloomFilterError::Persistence("Snapshot checksum mismatch".into()));
+        }
+
+        let mut r = PersistReader { data: &body[5..] };
+        let network = r.string()?;
+        let size = r.u64()?;
+        let num_hashes = r.u8()?;
+        let tweak = u32::from_le_bytes(r.array::<4>()?);
+        let hash_key = r.array::<16>()?;
+        if !(MIN_SIZE_BITS as u64..=MAX_SIZE_BITS as u64).contains(&size) || !(2..=7).contains(&num_hashes) {
+            return Err(BloomFilterError::Persistence("Snapshot configuration out of range".into()));
+        }
+        let mut hash_seeds = [0u32; 8];
+        for seed in hash_seeds.iter_mut() {
+            *seed = u32::from_le_bytes(r.array::<4>()?);
+        }
+        let entropy_pool = r.bytes()?.to_vec();
+
+        let generation_count = r.u64()?;
+        if !(1..=16).contains(&generation_count) {
+            return Err(BloomFilterError::Persistence("Snapshot generation count out of range".into()));
+        }
+        let mut generations = Vec::with_capacity(generation_count as usize);
+        for _ in 0..generation_count {
+            let generation = match r.u8()? {
+                SNAPSHOT_RAW => {
+                    let raw = r.bytes()?;
+                    if raw.len() as u64 * 8 < size {
+                        return Err(BloomFilterError::Persistence("Raw generation shorter than filter".into()));
+                    }
+                    CompactGeneration::Raw(raw.to_vec())
+                }
+                SNAPSHOT_RICE => {
+                    let parameter = r.u8()?;
+                    let count = r.u64()?;
+                    let stream = r.bytes()?;
+                    if parameter > 63 || !rice_stream_is_valid(stream, parameter, count, size) {
+                        return Err(BloomFilterError::Persistence("Malformed Golomb-Rice generation".into()));
+                    }
+                    CompactGeneration::Rice { parameter, count, stream: stream.to_vec() }
+                }
+                tag => return Err(BloomFilterError::Persistence(format!("Unknown generation encoding {}", tag))),
+            };
+            generations.push(generation);
+        }
+        if !r.data.is_empty() {
+            return Err(BloomFilterError::Persistence("Trailing data after snapshot contents".into()));
+        }
+
+        Ok(Self { network, size, num_hashes, tweak, hash_key, hash_seeds, entropy_pool, generations })
+    }
+
+    /// Network of the filter the snapshot was taken from
+    pub fn network_name(&self) -> &str {
+        &self.network
+    }
+
+    /// Same answer the full filter's bit array would give for `data`
+    pub fn contains(&self, data: &[u8]) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+        let Ok(hashes) = compute_hashes(data, &self.hash_key, &self.entropy_pool) else { return false };
+        let mut targets: Vec<u64> = (0..self.num_hashes as u32)
+            .map(|i| murmur_hash3(hashes, i, self.tweak, &self.hash_seeds) % self.size)
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+        self.generations.iter().any(|g| g.contains(&targets))
+    }
+
+    /// Check a UTXO keyed the same way as `UniversalBloomFilter::insert_utxo`
+    pub fn contains_utxo(&self, txid: &TransactionId, vout: u32) -> bool {
+        self.contains(&utxo_preimage(&self.network, txid, vout))
+    }
+}
+
+/// Whether `stream` decodes to exactly `count` increasing positions below `size`
+fn rice_stream_is_valid(stream: &[u8], parameter: u8, count: u64, size: u64) -> bool {
+    let mut reader = BitReader { bytes: stream, pos: 0 };
+    let mut next = 0u64;
+    for _ in 0..count {
+        match reader.rice(parameter) {
+            Some(gap) if gap < size - next => next += gap + 1,
+            _ => return false,
+        }
+    }
+    reader.pos.div_ceil(8) == stream.len()
+}
+
 /// Performance and security statistics
 #[derive(Debug, Clone)]
 pub struct BloomFilterStats {
     pub item_count: u64,
     pub false_positive_count: u64,
     pub theoretical_fp_rate: f64,
+    /// Non-empty lookups answered since creation or load
+    pub query_count: u64,
+    /// False positives reported by callers via `confirm_false_positive`
+    pub confirmed_false_positives: u64,
+    /// `confirmed_false_positives / query_count`
+    pub observed_fp_rate: f64,
     pub memory_usage_bytes: usize,
     pub timestamp_entries: usize,
     pub average_age_seconds: f64,
+    /// Items inserted into each live generation, oldest first
+    pub generation_item_counts: Vec<u64>,
+    /// Bookkeeping operations that found their timestamp shard locked
+    pub contended_operations: u64,
+    /// Bookkeeping operations that acquired their timestamp shard immediately
+    pub uncontended_operations: u64,
+    /// Items held relative to `capacity(AUTO_TUNE_TARGET_FP_RATE)`; above 1.0 the target is exceeded
+    pub saturation_ratio: f64,
+    /// Size in bits to rebuild with, set once `saturation_ratio` reaches `SATURATION_WARNING_RATIO`
+    pub recommended_size: Option<usize>,
 }
 
 /// Comprehensive error handling for maximum stability
@@ -606,6 +1727,21 @@ pub enum BloomFilterError {
 
     #[error("Concurrent access error")]
     ConcurrencyError,
+
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    #[error("Invalid persisted filter: {0}")]
+    Persistence(String),
+
+    #[error("Unsupported persisted filter version {0} (expected {})", PERSIST_VERSION)]
+    UnsupportedVersion(u8),
+
+    #[error("Network mismatch: expected {expected}, file contains {found}")]
+    NetworkMismatch { expected: String, found: String },
+
+    #[error("Invalid block: {0}")]
+    InvalidBlock(String),
 }
 
 impl Drop for UniversalBloomFilter {
@@ -613,6 +1749,7 @@ fn drop(&mut self) {
         // Secure cleanup
         self.entropy_pool.zeroize();
         self.hash_seeds.zeroize();
+        self.config.hash_key.zeroize();
     }
 }
 
@@ -621,10 +1758,31 @@ fn zeroize(&mut self) {
         // Only zeroize sensitive data
         self.entropy_pool.zeroize();
         self.hash_seeds.zeroize();
+        self.config.hash_key.zeroize();
         // Note: bit_array and metadata contain operational data, not secrets
     }
 }
 
+impl turbo_validator::SpentOutpointIndex for UniversalBloomFilter {
+    fn lookup(&self, outpoint: &turbo_validator::OutPoint) -> Result<turbo_validator::SpentLookup, turbo_validator::ValidationError> {
+        let txid = TransactionId::new(self.network_name(), &outpoint.txid);
+        match self.contains_utxo(&txid, outpoint.vout) {
+            Ok(true) => Ok(turbo_validator::SpentLookup::MaybeSpent),
+            Ok(false) => Ok(turbo_validator::SpentLookup::Unspent),
+            Err(e) => Err(turbo_validator::ValidationError::Other(format!("Bloom filter lookup failed: {}", e))),
+        }
+    }
+
+    fn mark_spent(&self, outpoints: &[turbo_validator::OutPoint]) -> Result<(), turbo_validator::ValidationError> {
+        let batch: Vec<_> = outpoints
+            .iter()
+            .map(|op| (TransactionId::new(self.network_name(), &op.txid), op.vout))
+            .collect();
+        self.insert_batch(&batch)
+            .map_err(|e| turbo_validator::ValidationError::Other(format!("Bloom filter insert failed: {}", e)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -659,6 +1817,278 @@ fn test_batch_operations() {
         assert!(results.iter().all(|&x| x));
     }
 
+    #[test]
+    fn test_concurrent_insert_and_query_has_no_false_negatives() {
+        let filter = Arc::new(UniversalBloomFilter::new(Some(BloomConfig::high_performance(NetworkConfig::bitcoin()))).unwrap());
+        let handles: Vec<_> = (0u8..8)
+            .map(|t| {
+                let filter = filter.clone();
+                std::thread::spawn(move || {
+                    for i in 0u32..500 {
+                        let mut bytes = [t; 32];
+                        bytes[..4].copy_from_slice(&i.to_le_bytes());
+                        let txid = TransactionId::from_bytes(&bytes).unwrap();
+                        filter.insert_utxo(&txid, i).unwrap();
+                        assert!(filter.contains_utxo(&txid, i).unwrap(), "thread {} lost item {}", t, i);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        for t in 0u8..8 {
+            for i in 0u32..500 {
+                let mut bytes = [t; 32];
+                bytes[..4].copy_from_slice(&i.to_le_bytes());
+                assert!(filter.contains_utxo(&TransactionId::from_bytes(&bytes).unwrap(), i).unwrap());
+            }
+        }
+        let stats = filter.stats();
+        assert_eq!(stats.item_count, 4000);
+        assert!(stats.contended_operations + stats.uncontended_operations >= 8000);
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ublf_{}_{}.bin", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_persistence_round_trip() {
+        let filter = UniversalBloomFilter::new(None).unwrap();
+        let inserted: Vec<TransactionId> = (0u8..50)
+            .map(|i| TransactionId::from_bytes(&[i; 32]).unwrap())
+            .collect();
+        for (i, txid) in inserted.iter().enumerate() {
+            filter.insert_utxo(txid, i as u32).unwrap();
+        }
+        let probes: Vec<(TransactionId, u32)> = (0u8..100)
+            .map(|i| (TransactionId::from_bytes(&[i; 32]).unwrap(), i as u32))
+            .collect();
+        let before = filter.contains_batch(&probes).unwrap();
+
+        let path = temp_path("roundtrip");
+        filter.save_to_file(&path).unwrap();
+        let loaded = UniversalBloomFilter::load_from_file(&path, &NetworkConfig::bitcoin()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.contains_batch(&probes).unwrap(), before);
+        assert_eq!(loaded.get_item_count(), filter.get_item_count());
+        assert_eq!(loaded.stats().timestamp_entries, filter.stats().timestamp_entries);
+    }
+
+    #[test]
+    fn test_persistence_rejects_truncated_and_mismatched_files() {
+        let filter = UniversalBloomFilter::new(None).unwrap();
+        filter.insert_utxo(&TransactionId::from_bytes(&[9u8; 32]).unwrap(), 0).unwrap();
+        let bytes = filter.to_bytes();
+
+        let truncated = &bytes[..bytes.len() - 10];
+        assert!(matches!(
+            UniversalBloomFilter::from_bytes(truncated, &NetworkConfig::bitcoin()),
+            Err(BloomFilterError::Persistence(_))
+        ));
+        assert!(UniversalBloomFilter::from_bytes(&bytes[..3], &NetworkConfig::bitcoin()).is_err());
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = PERSIST_VERSION + 1;
+        assert!(matches!(
+            UniversalBloomFilter::from_bytes(&wrong_version, &NetworkConfig::bitcoin()),
+            Err(BloomFilterError::UnsupportedVersion(v)) if v == PERSIST_VERSION + 1
+        ));
+
+        assert!(matches!(
+            UniversalBloomFilter::from_bytes(&bytes, &NetworkConfig::ethereum()),
+            Err(BloomFilterError::NetworkMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_persistence_rejects_sizes_the_file_cannot_hold() {
+        let filter = UniversalBloomFilter::new(None).unwrap();
+        let mut bytes = filter.to_bytes();
+        bytes.truncate(bytes.len() - 32);
+
+        // Claim the largest allowed filter, bucket count included, over a default-sized body
+        let network = &filter.config.network;
+        let size_at = 5 + 4 + network.name.len() + 24 + 4 + network.consensus_mechanism.len();
+        let buckets_at = size_at + 56 + 32 + 4 + filter.entropy_pool.len() + 24;
+        bytes[size_at..size_at + 8].copy_from_slice(&(MAX_SIZE_BITS as u64).to_le_bytes());
+        bytes[buckets_at..buckets_at + 8].copy_from_slice(&(MAX_SIZE_BITS as u64 / 64).to_le_bytes());
+        let checksum = bitcoin_hashes::sha256::Hash::hash(&bytes);
+        bytes.extend_from_slice(checksum.as_byte_array());
+
+        assert!(matches!(
+            UniversalBloomFilter::from_bytes(&bytes, &NetworkConfig::bitcoin()),
+            Err(BloomFilterError::Persistence(msg)) if msg == "File is truncated"
+        ));
+    }
+
+    #[test]
+    fn test_foreign_network_ids_are_namespaced() {
+        let filter = UniversalBloomFilter::new(Some(BloomConfig::for_network(NetworkConfig::ethereum()))).unwrap();
+        let hash = [7u8; 32];
+
+        filter.insert_utxo(&TransactionId::new("ethereum", &hash), 0).unwrap();
+        assert!(filter.contains_utxo(&TransactionId::new("ethereum", &hash), 0).unwrap());
+        assert!(!filter.contains_utxo(&TransactionId::new("solana", &hash), 0).unwrap());
+
+        filter.insert_utxo(&TransactionId::new("solana", &hash), 1).unwrap();
+        assert!(filter.contains_utxo(&TransactionId::new("solana", &hash), 1).unwrap());
+        assert!(!filter.contains_utxo(&TransactionId::new("ethereum", &hash), 1).unwrap());
+    }
+
+    #[test]
+    fn test_cleanup_expires_oldest_generation() {
+        let config = BloomConfig { max_age_seconds: 30, generations: 3, ..BloomConfig::default() };
+        let filter = UniversalBloomFilter::new(Some(config)).unwrap();
+        let t0 = filter.generations[0].started_at.load(Ordering::Relaxed);
+
+        let old = TransactionId::from_bytes(&[1u8; 32]).unwrap();
+        let recent = TransactionId::from_bytes(&[2u8; 32]).unwrap();
+        filter.insert_utxo(&old, 0).unwrap();
+
+        filter.cleanup_at(t0 + 10);
+        let mut preimage = recent.as_bytes().to_vec();
+        preimage.extend_from_slice(&0u32.to_le_bytes());
+        filter.insert_with_timestamp(&preimage, t0 + 10).unwrap();
+        filter.cleanup_at(t0 + 20);
+        assert!(filter.contains_utxo(&old, 0).unwrap());
+        assert_eq!(filter.stats().generation_item_counts, vec![1, 1, 0]);
+
+        // The first window has fully passed, so its generation is cleared and reused
+        filter.cleanup_at(t0 + 31);
+        assert!(!filter.contains_utxo(&old, 0).unwrap());
+        assert!(filter.contains_utxo(&recent, 0).unwrap());
+        assert_eq!(filter.stats().generation_item_counts, vec![1, 0, 0]);
+        assert_eq!(filter.get_item_count(), 1);
+    }
+
+    #[test]
+    fn test_idle_cleanup_clears_all_generations() {
+        let filter = UniversalBloomFilter::new(None).unwrap();
+        let t0 = filter.generations[0].started_at.load(Ordering::Relaxed);
+        filter.insert_utxo(&TransactionId::from_bytes(&[3u8; 32]).unwrap(), 0).unwrap();
+
+        assert_eq!(filter.rotate_generations(t0 + 10 * filter.config.max_age_seconds), 3);
+        assert_eq!(filter.get_item_count(), 0);
+        assert_eq!(filter.rotate_generations(t0 + 10 * filter.config.max_age_seconds), 0);
+    }
+
+    #[test]
+    fn test_invalid_generation_count_rejected() {
+        let config = BloomConfig { generations: 0, ..BloomConfig::default() };
+        assert!(matches!(
+            UniversalBloomFilter::new(Some(config)),
+            Err(BloomFilterError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn test_observed_false_positive_rate_tracks_queries() {
+        let filter = UniversalBloomFilter::new(None).unwrap();
+        assert_eq!(filter.observed_false_positive_rate(), 0.0);
+
+        filter.insert_data(b"present").unwrap();
+        for _ in 0..3 {
+            assert!(filter.contains_data(b"present").unwrap());
+        }
+        assert!(!filter.contains_data(b"absent").unwrap());
+        assert!(!filter.contains_data(b"").unwrap());
+        filter.confirm_false_positive();
+
+        let stats = filter.stats();
+        assert_eq!(stats.query_count, 4);
+        assert_eq!(stats.confirmed_false_positives, 1);
+        assert!((stats.observed_fp_rate - 0.25).abs() < f64::EPSILON);
+    }
+
+    /// Testnet3 genesis block (hash 000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943)
+    const TESTNET_GENESIS_BLOCK: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4adae5494dffff001d1aa4ae180101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    /// Two-transaction block: the genesis coinbase plus a segwit spend of it
+    /// (txid 9a57d14f..., wtxid d253f54f...), with a matching merkle root
+    const SEGWIT_BLOCK: &str = "000000203b86630c78db663e96b29b39ac630eaedf1408af8a5e9b29662cb659830e0d650b3cae56d10ddd53ed9d2933dc492b9e23ffd483ee738d8a74dbebf073c4a60edae5494dffff001d000000000201000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000020000000001013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0000000000ffffffff018813000000000000160014000102030405060708090a0b0c0d0e0f101112130203aabbcc02ddee00000000";
+
+    fn display_hex(bytes: &[u8]) -> String {
+        bytes.iter().rev().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_parse_testnet_genesis_block() {
+        let bytes = hex::decode(TESTNET_GENESIS_BLOCK).unwrap();
+        let block = BlockData::from_bitcoin_bytes("bitcoin", &bytes).unwrap();
+
+        assert_eq!(display_hex(&block.hash), "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943");
+        assert_eq!(block.timestamp, 1_296_688_602);
+        assert_eq!(block.transactions.len(), 1);
+        assert_eq!(
+            display_hex(block.transactions[0].as_bytes()),
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+        );
+
+        let filter = UniversalBloomFilter::new(None).unwrap();
+        filter.load_block(&block).unwrap();
+        assert!(filter.contains_data(block.transactions[0].as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_parse_segwit_block_excludes_witness_from_txid() {
+        let bytes = hex::decode(SEGWIT_BLOCK).unwrap();
+        let block = BlockData::from_bitcoin_bytes("bitcoin", &bytes).unwrap();
+
+        let txids: Vec<String> = block.transactions.iter().map(|tx| display_hex(tx.as_bytes())).collect();
+        assert_eq!(
+            txids,
+            vec![
+                "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+                "9a57d14f81e9a6921e8d7229355690bfcce8ea38b7c9af826c95e35c85159e04",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_standalone_transaction_txid_and_vsize() {
+        let block = hex::decode(SEGWIT_BLOCK).unwrap();
+        // Skip the 80-byte header, the tx count and the 204-byte legacy coinbase
+        let segwit_tx = &block[80 + 1 + 204..];
+        let (txid, vsize) = TransactionId::from_bitcoin_tx_bytes("bitcoin", segwit_tx).unwrap();
+        assert_eq!(display_hex(txid.as_bytes()), "9a57d14f81e9a6921e8d7229355690bfcce8ea38b7c9af826c95e35c85159e04");
+        // 82 bytes without witness, 92 in full: weight 3 * 82 + 92 = 338, rounded up to 85 vbytes
+        assert_eq!(segwit_tx.len(), 92);
+        assert_eq!(vsize, 85);
+
+        let coinbase = &block[80 + 1..80 + 1 + 204];
+        let (_, vsize) = TransactionId::from_bitcoin_tx_bytes("bitcoin", coinbase).unwrap();
+        assert_eq!(vsize, 204);
+
+        assert!(TransactionId::from_bitcoin_tx_bytes("bitcoin", &block[80 + 1..]).is_err());
+        assert!(TransactionId::from_bitcoin_tx_bytes("bitcoin", &segwit_tx[..50]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_blocks() {
+        let bytes = hex::decode(TESTNET_GENESIS_BLOCK).unwrap();
+        let parse = |b: &[u8]| BlockData::from_bitcoin_bytes("bitcoin", b);
+
+        assert!(matches!(parse(&bytes[..bytes.len() - 1]), Err(BloomFilterError::InvalidBlock(_))));
+        assert!(matches!(parse(&bytes[..40]), Err(BloomFilterError::InvalidBlock(_))));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(parse(&trailing), Err(BloomFilterError::InvalidBlock(_))));
+
+        let mut bad_merkle = bytes.clone();
+        bad_merkle[40] ^= 0xff;
+        assert!(matches!(parse(&bad_merkle), Err(BloomFilterError::InvalidBlock(_))));
+
+        // The old ad-hoc layout (txid + vout count + outputs) is not a valid block
+        let mut legacy = vec![0x11u8; 32];
+        legacy.extend_from_slice(&0u32.to_le_bytes());
+        assert!(parse(&legacy).is_err());
+    }
+
     #[test]
     fn test_false_positive_rate() {
         let filter = UniversalBloomFilter::new(None).unwrap();
@@ -675,4 +2105,281 @@ fn test_false_positive_rate() {
         let fp_rate = filter.false_positive_rate();
         assert!(fp_rate > 0.0 && fp_rate < 1.0);
     }
+
+    #[test]
+    fn test_recommend_config_matches_bloom_formulas() {
+        // m = -n ln p / (ln 2)^2 and k = (m / n) ln 2
+        assert_eq!(optimal_size_bits(1000, 0.01).ceil() as usize, 9586);
+        assert_eq!(optimal_num_hashes(9586, 1000), 7);
+        assert_eq!(optimal_size_bits(1000, 0.1).ceil() as usize, 4793);
+
+        // 4793 bits rounds up to 8192, where k = 8.192 * ln 2 = 5.68
+        let (size_bits, num_hashes) = UniversalBloomFilter::recommend_config(0.1, 1000).unwrap();
+        assert_eq!((size_bits, num_hashes), (8192, 6));
+        let fp = (1.0 - (-(num_hashes as f64) * 1000.0 / size_bits as f64).exp()).powi(num_hashes as i32);
+        assert!(fp <= 0.1);
+
+        // 9M items at 1% needs ~86M bits
+        assert_eq!(UniversalBloomFilter::recommend_config(0.01, 9_000_000).unwrap(), (1 << 27, 7));
+        assert_eq!(UniversalBloomFilter::recommend_config(0.01, u64::MAX).unwrap().0, MAX_SIZE_BITS);
+        assert_eq!(UniversalBloomFilter::recommend_config(0.5, 1).unwrap(), (MIN_SIZE_BITS, 7));
+
+        assert!(UniversalBloomFilter::recommend_config(0.0, 1000).is_err());
+        assert!(UniversalBloomFilter::recommend_config(1.0, 1000).is_err());
+        assert!(UniversalBloomFilter::recommend_config(0.01, 0).is_err());
+    }
+
+    #[test]
+    fn test_auto_tune_reports_saturation_and_recommendation() {
+        let config = BloomConfig { size: 1024, num_hashes: 3, ..BloomConfig::default() };
+        let filter = UniversalBloomFilter::new(Some(config)).unwrap();
+        let stats = filter.stats();
+        assert_eq!(stats.saturation_ratio, 0.0);
+        assert_eq!(stats.recommended_size, None);
+        assert_eq!(filter.auto_tune_check(), None);
+
+        // Per-generation FP share is 1 - 0.99^(1/3); solve (1 - e^(-kn/m))^k for n
+        let per_generation_fp = 1.0 - 0.99f64.powf(1.0 / 3.0);
+        let expected = (-1024.0 / 3.0 * (1.0 - per_generation_fp.powf(1.0 / 3.0)).ln() * 3.0) as u64;
+        assert_eq!(filter.capacity(AUTO_TUNE_TARGET_FP_RATE), expected);
+
+        for i in 0u32..200 {
+            filter.insert_data(&i.to_le_bytes()).unwrap();
+        }
+        let stats = filter.stats();
+        assert!((stats.saturation_ratio - 200.0 / expected as f64).abs() < 1e-9);
+        assert!(stats.saturation_ratio > 1.0);
+
+        let (size_bits, num_hashes) = filter.auto_tune_check().unwrap();
+        assert_eq!(stats.recommended_size, Some(size_bits));
+        assert_eq!((size_bits, num_hashes), UniversalBloomFilter::recommend_config(0.01, 400).unwrap());
+        assert!(size_bits > 1024);
+    }
+
+    #[test]
+    fn test_rebuild_preserves_reinserted_items() {
+        let filter = UniversalBloomFilter::new(None).unwrap();
+        let utxos: Vec<_> = (0u8..50).map(|i| (TransactionId::new("bitcoin", &[i; 32]), i as u32)).collect();
+        for (txid, vout) in &utxos {
+            filter.insert_utxo(txid, *vout).unwrap();
+        }
+        filter.insert_data(b"raw item").unwrap();
+
+        let (size, num_hashes) = UniversalBloomFilter::recommend_config(0.001, 10_000).unwrap();
+        let config = BloomConfig { size, num_hashes, ..BloomConfig::default() };
+        let items = utxos
+            .iter()
+            .map(|(txid, vout)| BloomItem::Utxo(txid.clone(), *vout))
+            .chain(std::iter::once(BloomItem::Data(b"raw item".to_vec())));
+        let rebuilt = filter.rebuild_with_config(config, items).unwrap();
+
+        assert_eq!(rebuilt.stats().memory_usage_bytes, size / 8 * DEFAULT_GENERATIONS);
+        assert_eq!(rebuilt.get_item_count(), utxos.len() + 1);
+        for (txid, vout) in &utxos {
+            assert!(rebuilt.contains_utxo(txid, *vout).unwrap());
+        }
+        assert!(rebuilt.contains_data(b"raw item").unwrap());
+        assert!(!rebuilt.contains_utxo(&TransactionId::new("bitcoin", &[0xee; 32]), 0).unwrap());
+
+        let other = BloomConfig::for_network(NetworkConfig::solana());
+        assert!(matches!(
+            filter.rebuild_with_config(other, std::iter::empty()),
+            Err(BloomFilterError::NetworkMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compressed_snapshot_round_trips_sparse_filter() {
+        let config = BloomConfig { size: 1 << 20, ..BloomConfig::default() };
+        let filter = UniversalBloomFilter::new(Some(config)).unwrap();
+        let txid = |i: u32| {
+            let mut bytes = [0u8; 32];
+            bytes[..4].copy_from_slice(&i.to_le_bytes());
+            TransactionId::new("bitcoin", &bytes)
+        };
+        for i in 0..3000 {
+            filter.insert_utxo(&txid(i), i).unwrap();
+        }
+        let tag = filter.snapshot_tag();
+
+        let snapshot = filter.export_compressed();
+        let raw_size = filter.stats().memory_usage_bytes;
+        assert!(snapshot.len() * 10 < raw_size, "{} bytes vs {} raw", snapshot.len(), raw_size);
+
+        let view = CompactBloomView::from_bytes(&snapshot).unwrap();
+        assert_eq!(view.network_name(), "bitcoin");
+        assert!((0..3000).all(|i| view.contains_utxo(&txid(i), i)));
+        let false_positives = (3000..6000).filter(|&i| view.contains_utxo(&txid(i), i)).count();
+        assert!(false_positives < 30, "{} false positives", false_positives);
+
+        filter.insert_data(b"late arrival").unwrap();
+        assert_ne!(filter.snapshot_tag(), tag);
+        assert!(!view.contains(b"late arrival"));
+        assert!(CompactBloomView::from_bytes(&filter.export_compressed()).unwrap().contains(b"late arrival"));
+    }
+
+    #[test]
+    fn test_compressed_snapshot_falls_back_to_raw_and_rejects_corruption() {
+        let filter = UniversalBloomFilter::new(None).unwrap();
+        for i in 0u32..5000 {
+            filter.insert_data(&i.to_le_bytes()).unwrap();
+        }
+        let snapshot = filter.export_compressed();
+        // A dense generation never costs more than its raw bit array
+        assert!(snapshot.len() < filter.stats().memory_usage_bytes / 3 + 512);
+        let view = CompactBloomView::from_bytes(&snapshot).unwrap();
+        assert!((0u32..5000).all(|i| view.contains(&i.to_le_bytes())));
+
+        let mut corrupted = snapshot.clone();
+        corrupted[100] ^= 1;
+        assert!(matches!(CompactBloomView::from_bytes(&corrupted), Err(BloomFilterError::Persistence(_))));
+        assert!(CompactBloomView::from_bytes(&snapshot[..snapshot.len() - 1]).is_err());
+        assert!(CompactBloomView::from_bytes(&filter.to_bytes()).is_err());
+    }
+
+    fn keyed_filter(hash_key: [u8; 16]) -> UniversalBloomFilter {
+        UniversalBloomFilter::new(Some(BloomConfig { tweak: 7, hash_key, ..BloomConfig::default() })).unwrap()
+    }
+
+    fn bit_pattern(filter: &UniversalBloomFilter) -> Vec<u64> {
+        filter.generations.iter().flat_map(|g| g.bits.iter().map(|w| w.load(Ordering::Relaxed))).collect()
+    }
+
+    #[test]
+    fn test_hash_key_decides_which_bits_an_item_sets() {
+        let (a, b, a_again) = (keyed_filter([1; 16]), keyed_filter([2; 16]), keyed_filter([1; 16]));
+        for filter in [&a, &b, &a_again] {
+            for i in 0u32..200 {
+                filter.insert_data(&i.to_le_bytes()).unwrap();
+            }
+            filter.insert_utxo(&utxo(1), 0).unwrap();
+        }
+
+        assert_ne!(bit_pattern(&a), bit_pattern(&b));
+        assert_eq!(bit_pattern(&a), bit_pattern(&a_again));
+        for filter in [&a, &b] {
+            assert!((0u32..200).all(|i| filter.contains_data(&i.to_le_bytes()).unwrap()));
+            assert!(filter.contains_utxo(&utxo(1), 0).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_hash_key_survives_save_and_load() {
+        let key = [0xAB; 16];
+        let filter = keyed_filter(key);
+        for i in 0u32..100 {
+            filter.insert_data(&i.to_le_bytes()).unwrap();
+        }
+
+        let path = temp_path("hash_key");
+        filter.save_to_file(&path).unwrap();
+        let loaded = UniversalBloomFilter::load_from_file(&path, &NetworkConfig::bitcoin()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded.config.hash_key, key);
+        assert!((0u32..100).all(|i| loaded.contains_data(&i.to_le_bytes()).unwrap()));
+
+        let view = CompactBloomView::from_bytes(&filter.export_compressed()).unwrap();
+        assert!((0u32..100).all(|i| view.contains(&i.to_le_bytes())));
+
+        // Neither stats nor a logged config reveal the key
+        assert!(!format!("{:?}", loaded.config).contains("171"));
+        assert_eq!(loaded.stats().item_count, 100);
+    }
+
+    fn provenance_filter(capacity: usize) -> UniversalBloomFilter {
+        UniversalBloomFilter::new(Some(BloomConfig { provenance_capacity: capacity, ..BloomConfig::default() })).unwrap()
+    }
+
+    fn utxo(i: u8) -> TransactionId {
+        TransactionId::new("bitcoin", &[i; 32])
+    }
+
+    #[test]
+    fn test_provenance_reports_insertion_source() {
+        let filter = provenance_filter(100);
+        filter.insert_utxo_tagged(&utxo(1), 0, SourceTag::Mempool).unwrap();
+        filter
+            .insert_batch_tagged(&[(utxo(2), 0), (utxo(2), 1)], SourceTag::Block { height: 800_000 })
+            .unwrap();
+        filter.insert_utxo_tagged(&utxo(3), 0, SourceTag::Manual).unwrap();
+        filter.insert_utxo(&utxo(4), 0).unwrap();
+
+        assert_eq!(filter.contains_utxo_with_provenance(&utxo(1), 0).unwrap(), Some(SourceTag::Mempool));
+        for vout in 0..2 {
+            assert_eq!(
+                filter.contains_utxo_with_provenance(&utxo(2), vout).unwrap(),
+                Some(SourceTag::Block { height: 800_000 })
+            );
+        }
+        assert_eq!(filter.contains_utxo_with_provenance(&utxo(3), 0).unwrap(), Some(SourceTag::Manual));
+        assert_eq!(filter.contains_utxo_with_provenance(&utxo(4), 0).unwrap(), Some(SourceTag::Unknown));
+        assert_eq!(filter.contains_utxo_with_provenance(&utxo(5), 0).unwrap(), None);
+
+        // A mempool output confirmed later takes the block as its source
+        filter.insert_utxo_tagged(&utxo(1), 0, SourceTag::Block { height: 800_001 }).unwrap();
+        assert_eq!(
+            filter.contains_utxo_with_provenance(&utxo(1), 0).unwrap(),
+            Some(SourceTag::Block { height: 800_001 })
+        );
+    }
+
+    #[test]
+    fn test_provenance_evicts_oldest_at_cap() {
+        let filter = provenance_filter(3);
+        for i in 0..5 {
+            filter.insert_utxo_tagged(&utxo(i), 0, SourceTag::Block { height: i as u64 }).unwrap();
+        }
+        assert_eq!(filter.provenance.as_ref().unwrap().lock().unwrap().tags.len(), 3);
+
+        // Evicted items still hit, only their source is forgotten
+        for i in 0..2 {
+            assert_eq!(filter.contains_utxo_with_provenance(&utxo(i), 0).unwrap(), Some(SourceTag::Unknown));
+        }
+        for i in 2..5 {
+            assert_eq!(
+                filter.contains_utxo_with_provenance(&utxo(i), 0).unwrap(),
+                Some(SourceTag::Block { height: i as u64 })
+            );
+        }
+
+        // Re-tagging does not refresh an item's place in the eviction order
+        filter.insert_utxo_tagged(&utxo(2), 0, SourceTag::Manual).unwrap();
+        filter.insert_utxo_tagged(&utxo(5), 0, SourceTag::Mempool).unwrap();
+        assert_eq!(filter.contains_utxo_with_provenance(&utxo(2), 0).unwrap(), Some(SourceTag::Unknown));
+        assert_eq!(filter.contains_utxo_with_provenance(&utxo(5), 0).unwrap(), Some(SourceTag::Mempool));
+    }
+
+    #[test]
+    fn test_provenance_disabled_by_default() {
+        let filter = UniversalBloomFilter::new(None).unwrap();
+        assert!(filter.provenance.is_none());
+        filter.insert_utxo_tagged(&utxo(1), 0, SourceTag::Mempool).unwrap();
+        assert_eq!(filter.contains_utxo_with_provenance(&utxo(1), 0).unwrap(), Some(SourceTag::Unknown));
+        assert_eq!(filter.contains_utxo_with_provenance(&utxo(1), 1).unwrap(), None);
+    }
+
+    #[test]
+    fn test_bloom_backed_double_spend() {
+        use turbo_validator::{MaybeSpentPolicy, OutPoint, SpentLookup, SpentOutpointIndex, TurboValidator, ValidationError};
+
+        let filter = Arc::new(UniversalBloomFilter::new(None).unwrap());
+        let outpoint = OutPoint::new([7u8; 32], 2);
+        assert_eq!(filter.lookup(&outpoint).unwrap(), SpentLookup::Unspent);
+        filter.mark_spent(&[outpoint]).unwrap();
+        assert_eq!(filter.lookup(&outpoint).unwrap(), SpentLookup::MaybeSpent);
+
+        let mut tx = 1u32.to_le_bytes().to_vec();
+        tx.push(1);
+        tx.extend_from_slice(&outpoint.txid);
+        tx.extend_from_slice(&outpoint.vout.to_le_bytes());
+        tx.push(0);
+        tx.extend_from_slice(&u32::MAX.to_le_bytes());
+        tx.push(0);
+        tx.extend_from_slice(&0u32.to_le_bytes());
+
+        let strict = TurboValidator::default().with_spent_index(filter.clone(), MaybeSpentPolicy::Reject);
+        assert!(matches!(strict.validate_transaction(&tx), Err(ValidationError::DoubleSpend(_))));
+        let lenient = TurboValidator::default().with_spent_index(filter, MaybeSpentPolicy::Warn);
+        assert!(lenient.validate_transaction(&tx).is_ok());
+    }
 }
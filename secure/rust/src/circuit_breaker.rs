@@ -0,0 +1,335 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Lock-free circuit breaker for upstream calls
+
+//! Closed / open / half-open circuit breaker.
+//!
+//! The breaker opens after `failure_threshold` consecutive failures, rejects calls for
+//! `cooldown`, then lets exactly `half_open_max` trial calls through. If all of them
+//! succeed it closes again; any failed trial reopens it for another cooldown.
+//!
+//! State, the per-state counter and the trial success count share one `AtomicU64`, so
+//! every transition is a single compare-and-swap and no lock is taken on the hot path.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Suggested retry delay for callers turned away while the trial slots are taken
+const HALF_OPEN_RETRY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl BreakerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Open => "open",
+            BreakerState::HalfOpen => "half_open",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the breaker
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before admitting trial calls
+    pub cooldown: Duration,
+    /// Trial calls admitted while half-open; all must succeed to close
+    pub half_open_max: u32,
+}
+
+/// Called with the new state after every transition
+pub type TransitionHook = Arc<dyn Fn(BreakerState) + Send + Sync>;
+
+/// Unpacked form of the shared atomic word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Word {
+    state: BreakerState,
+    // Consecutive failures while closed, admitted trials while half-open
+    count: u16,
+    // Successful trials while half-open
+    successes: u16,
+}
+
+impl Word {
+    const CLOSED: Word = Word { state: BreakerState::Closed, count: 0, successes: 0 };
+    const OPEN: Word = Word { state: BreakerState::Open, count: 0, successes: 0 };
+    const HALF_OPEN: Word = Word { state: BreakerState::HalfOpen, count: 0, successes: 0 };
+
+    fn pack(self) -> u64 {
+        let state = match self.state {
+            BreakerState::Closed => 0u64,
+            BreakerState::Open => 1,
+            BreakerState::HalfOpen => 2,
+        };
+        state | (self.count as u64) << 8 | (self.successes as u64) << 24
+    }
+
+    fn unpack(raw: u64) -> Word {
+        let state = match raw & 0xff {
+            0 => BreakerState::Closed,
+            1 => BreakerState::Open,
+            _ => BreakerState::HalfOpen,
+        };
+        Word { state, count: (raw >> 8) as u16, successes: (raw >> 24) as u16 }
+    }
+}
+
+pub struct CircuitBreaker {
+    failure_threshold: u16,
+    cooldown: Duration,
+    half_open_max: u16,
+    word: AtomicU64,
+    // Nanoseconds after `epoch` at which the breaker last opened
+    opened_at: AtomicU64,
+    epoch: Instant,
+    on_transition: Option<TransitionHook>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            failure_threshold: config.failure_threshold.clamp(1, u16::MAX as u32) as u16,
+            cooldown: config.cooldown,
+            half_open_max: config.half_open_max.clamp(1, u16::MAX as u32) as u16,
+            word: AtomicU64::new(Word::CLOSED.pack()),
+            opened_at: AtomicU64::new(0),
+            epoch: Instant::now(),
+            on_transition: None,
+        }
+    }
+
+    pub fn with_transition_hook(mut self, hook: TransitionHook) -> Self {
+        self.on_transition = Some(hook);
+        self
+    }
+
+    pub fn state(&self) -> BreakerState {
+        Word::unpack(self.word.load(Ordering::Acquire)).state
+    }
+
+    /// Admit a call, or return how long the caller should wait before retrying
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    pub fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+        loop {
+            let current = Word::unpack(self.word.load(Ordering::Acquire));
+            match current.state {
+                BreakerState::Closed => return Ok(()),
+                BreakerState::Open => {
+                    let opened_at = self.epoch + Duration::from_nanos(self.opened_at.load(Ordering::Acquire));
+                    let elapsed = now.saturating_duration_since(opened_at);
+                    if elapsed < self.cooldown {
+                        return Err(self.cooldown - elapsed);
+                    }
+                    // Whoever wins the swap reports the transition; everyone retries as half-open
+                    self.swap(current, Word::HALF_OPEN);
+                }
+                BreakerState::HalfOpen => {
+                    if current.count >= self.half_open_max {
+                        return Err(HALF_OPEN_RETRY);
+                    }
+                    let admitted = Word { count: current.count + 1, ..current };
+                    if self.word
+                        .compare_exchange(current.pack(), admitted.pack(), Ordering::AcqRel, Ordering::Acquire)
+                        .is_ok()
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Report an admitted call that reached the upstream and got a usable answer
+    pub fn record_success(&self) {
+        loop {
+            let current = Word::unpack(self.word.load(Ordering::Acquire));
+            let next = match current.state {
+                BreakerState::Closed if current.count == 0 => return,
+                BreakerState::Closed => Word::CLOSED,
+                BreakerState::HalfOpen if current.successes + 1 >= self.half_open_max => Word::CLOSED,
+                BreakerState::HalfOpen => Word { successes: current.successes + 1, ..current },
+                // A call admitted before the breaker opened; its result says nothing new
+                BreakerState::Open => return,
+            };
+            if self.swap(current, next) {
+                return;
+            }
+        }
+    }
+
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now())
+    }
+
+    pub fn record_failure_at(&self, now: Instant) {
+        loop {
+            let current = Word::unpack(self.word.load(Ordering::Acquire));
+            let next = match current.state {
+                BreakerState::Closed if current.count + 1 >= self.failure_threshold => Word::OPEN,
+                BreakerState::Closed => Word { count: current.count + 1, ..current },
+                BreakerState::HalfOpen => Word::OPEN,
+                BreakerState::Open => return,
+            };
+            if next.state == BreakerState::Open {
+                // Publish the open time before the state so no reader sees a stale cooldown
+                let nanos = now.saturating_duration_since(self.epoch).as_nanos() as u64;
+                self.opened_at.store(nanos, Ordering::Release);
+            }
+            if self.swap(current, next) {
+                return;
+            }
+        }
+    }
+
+    /// Compare-and-swap the word, firing the hook when the state changed
+    fn swap(&self, current: Word, next: Word) -> bool {
+        let swapped = self
+            .word
+            .compare_exchange(current.pack(), next.pack(), Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+        if swapped && current.state != next.state {
+            if let Some(hook) = &self.on_transition {
+                hook(next.state);
+            }
+        }
+        swapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::Mutex;
+
+    fn breaker(threshold: u32, half_open_max: u32) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: threshold,
+            cooldown: Duration::from_secs(30),
+            half_open_max,
+        })
+    }
+
+    fn open(breaker: &CircuitBreaker, at: Instant) {
+        while breaker.state() != BreakerState::Open {
+            breaker.record_failure_at(at);
+        }
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = breaker(3, 1);
+        let now = Instant::now();
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now);
+        // A success in between resets the streak
+        breaker.record_success();
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.try_acquire_at(now).is_ok());
+
+        breaker.record_failure_at(now);
+        assert_eq!(breaker.state(), BreakerState::Open);
+    }
+
+    #[test]
+    fn test_open_rejects_with_remaining_cooldown() {
+        let breaker = breaker(1, 1);
+        let now = Instant::now();
+        open(&breaker, now);
+        assert_eq!(breaker.try_acquire_at(now), Err(Duration::from_secs(30)));
+        assert_eq!(breaker.try_acquire_at(now + Duration::from_secs(20)), Err(Duration::from_secs(10)));
+        assert!(breaker.try_acquire_at(now + Duration::from_secs(30)).is_ok());
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+    }
+
+    #[test]
+    fn test_half_open_admits_exactly_half_open_max_trials() {
+        let breaker = breaker(1, 3);
+        let now = Instant::now();
+        open(&breaker, now);
+        let later = now + Duration::from_secs(31);
+
+        let admitted = (0..10).filter(|_| breaker.try_acquire_at(later).is_ok()).count();
+        assert_eq!(admitted, 3);
+        assert_eq!(breaker.try_acquire_at(later), Err(HALF_OPEN_RETRY));
+
+        // All trials succeeding closes the breaker
+        breaker.record_success();
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.try_acquire_at(later).is_ok());
+    }
+
+    #[test]
+    fn test_half_open_trials_are_exact_under_contention() {
+        let breaker = Arc::new(breaker(1, 4));
+        let now = Instant::now();
+        open(&breaker, now);
+        let later = now + Duration::from_secs(31);
+
+        let admitted = Arc::new(AtomicU32::new(0));
+        let threads: Vec<_> = (0..32)
+            .map(|_| {
+                let breaker = breaker.clone();
+                let admitted = admitted.clone();
+                std::thread::spawn(move || {
+                    if breaker.try_acquire_at(later).is_ok() {
+                        admitted.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(admitted.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_failed_trial_reopens_and_hook_sees_every_transition() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook: TransitionHook = {
+            let seen = seen.clone();
+            Arc::new(move |state| seen.lock().unwrap().push(state))
+        };
+        let breaker = breaker(1, 2).with_transition_hook(hook);
+        let now = Instant::now();
+
+        breaker.record_failure_at(now);
+        assert!(breaker.try_acquire_at(now + Duration::from_secs(30)).is_ok());
+        breaker.record_failure_at(now + Duration::from_secs(30));
+        assert_eq!(breaker.state(), BreakerState::Open);
+        // The cooldown restarts from the failed trial
+        assert_eq!(breaker.try_acquire_at(now + Duration::from_secs(40)), Err(Duration::from_secs(20)));
+
+        assert!(breaker.try_acquire_at(now + Duration::from_secs(60)).is_ok());
+        assert!(breaker.try_acquire_at(now + Duration::from_secs(60)).is_ok());
+        breaker.record_success();
+        breaker.record_success();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                BreakerState::Open,
+                BreakerState::HalfOpen,
+                BreakerState::Open,
+                BreakerState::HalfOpen,
+                BreakerState::Closed,
+            ]
+        );
+    }
+}
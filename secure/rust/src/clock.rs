@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Injectable time source
+
+//! Time source for expiry and rate-limit windows.
+//!
+//! Components that expire state take an `Arc<dyn Clock>` and default to `SystemClock`.
+//! Tests swap in a `ManualClock` and move time forward with `advance` instead of sleeping.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub trait Clock: Send + Sync {
+    /// Seconds since the Unix epoch
+    fn now_unix(&self) -> u64;
+    /// Monotonic time for measuring elapsed durations
+    fn now_instant(&self) -> Instant;
+}
+
+/// The real clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix(&self) -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+
+    fn now_instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when told to. Both readings advance together.
+#[derive(Debug)]
+pub struct ManualClock {
+    start_unix: u64,
+    start_instant: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl ManualClock {
+    /// Clock reading `start_unix` seconds since the epoch
+    pub fn new(start_unix: u64) -> Self {
+        Self { start_unix, start_instant: Instant::now(), elapsed: Mutex::new(Duration::ZERO) }
+    }
+
+    /// Clock starting at the current wall-clock time
+    pub fn starting_now() -> Self {
+        Self::new(SystemClock.now_unix())
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap_or_else(|e| e.into_inner()) += by;
+    }
+
+    fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_unix(&self) -> u64 {
+        self.start_unix + self.elapsed().as_secs()
+    }
+
+    fn now_instant(&self) -> Instant {
+        self.start_instant + self.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_moves_only_on_advance() {
+        let clock = ManualClock::new(1_000);
+        let instant = clock.now_instant();
+        assert_eq!(clock.now_unix(), 1_000);
+        assert_eq!(clock.now_instant(), instant);
+
+        clock.advance(Duration::from_millis(1_500));
+        assert_eq!(clock.now_unix(), 1_001);
+        assert_eq!(clock.now_instant() - instant, Duration::from_millis(1_500));
+        assert!(SystemClock.now_unix() > 1_600_000_000);
+    }
+}
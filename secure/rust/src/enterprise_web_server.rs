@@ -4,7 +4,7 @@
 
 #[cfg(feature = "web-server")]
 mod web_server {
-    use actix_web::{web, App, HttpServer, Responder, HttpResponse, Result, HttpRequest, middleware};
+    use actix_web::{web, App, HttpServer, HttpResponse, Result, HttpRequest, middleware};
     use actix_web::http::header::{HeaderName, HeaderValue};
     use serde::{Serialize, Deserialize};
     use std::sync::Arc;
@@ -13,47 +13,28 @@ mod web_server {
     use std::time::{SystemTime, UNIX_EPOCH, Duration, Instant};
     use std::collections::HashMap;
     use log::{info, error, warn};
-    use uuid::Uuid;
 
     // Re-export our storage verifier
-    use crate::storage_verifier::{
-        StorageVerifier, RateLimitConfig, StorageChallenge, StorageProof,
-        StorageVerificationError
-    };
+    use crate::key_store::digest_key;
+    use crate::storage_verifier::{StorageVerifier, StorageVerificationError};
+    use crate::web_server::{decode_proof, ChallengeResponse, SubmitProofRequest};
+
+    const DAY_SECS: u64 = 24 * 3600;
+    /// Largest commitment (chunk size × chunk count) a free key may register
+    const FREE_MAX_COMMITMENT_BYTES: u64 = 64 * 1024 * 1024;
 
     // --- Enhanced Request/Response Types for Paid Service ---
+    /// Answer to a challenge issued to the same key by `POST /api/challenges`. The proof is
+    /// checked against the commitment the key registered, never against data in this body.
     #[derive(Serialize, Deserialize)]
     pub struct ValidateStorageRequest {
-        pub file_id: String,
         pub protocol: String,
         #[serde(default)]
-        pub provider: Option<String>,
-        #[serde(default)]
-        pub file_size: Option<u64>,
-        #[serde(default)]
         pub tier: String,
         #[serde(default)]
         pub webhook_url: Option<String>,
-        #[serde(default)]
-        pub merkle_proof: Option<MerkleProofData>,
-    }
-
-    #[derive(Serialize, Deserialize)]
-    pub struct MerkleProofData {
-        pub root: String,
-        pub proof: Vec<MerkleProofElement>,
-        pub leaf: String,
-        pub index: u64,
-        pub total_chunks: u64,
-        pub chunk_size: u64,
-        #[serde(default)]
-        pub leaf_hashes: Vec<String>,
-    }
-
-    #[derive(Serialize, Deserialize)]
-    pub struct MerkleProofElement {
-        pub hash: String,
-        pub position: String,
+        #[serde(flatten)]
+        pub proof: SubmitProofRequest,
     }
 
     #[derive(Serialize, Deserialize)]
@@ -81,11 +62,35 @@ pub struct ValidateStorageResponse {
     pub struct SubscriptionInfo {
         pub tier: String,
         pub credits_remaining: u32,
-        pub monthly_limit: u32,
+        /// Challenge generations per UTC day; absent when unlimited
+        pub daily_limit: Option<u32>,
         pub reset_date: u64,
         pub features: Vec<String>,
     }
 
+    #[derive(Serialize, Deserialize)]
+    pub struct TierChallengeRequest {
+        pub file_id: String,
+        pub provider: String,
+        /// Require the proof to be signed by the provider (enterprise only)
+        #[serde(default)]
+        pub require_signature: bool,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct RegisterCommitmentRequest {
+        pub file_id: String,
+        pub chunk_size: u32,
+        /// Hex SHA-256 leaf hash of each chunk
+        #[serde(default)]
+        pub leaf_hashes: Vec<String>,
+        /// Hex Merkle root; registers a Merkle commitment over `total_chunks` instead of leaves
+        #[serde(default)]
+        pub merkle_root: Option<String>,
+        #[serde(default)]
+        pub total_chunks: Option<u64>,
+    }
+
     #[derive(Serialize, Deserialize)]
     pub struct AnalyticsResponse {
         pub total_verifications: u64,
@@ -114,90 +119,350 @@ pub struct ProviderStat {
     #[derive(Clone, Debug)]
     pub struct SubscriptionTier {
         pub name: String,
-        pub monthly_credits: u32,
+        /// Challenge generations per UTC day; None is unlimited
+        pub daily_challenges: Option<u32>,
         pub max_concurrent_requests: u32,
+        /// Largest commitment (chunk size × chunk count) the tier may register; None is unlimited
+        pub max_commitment_bytes: Option<u64>,
+        pub merkle_registration: bool,
+        pub signature_challenges: bool,
         pub features: Vec<String>,
         pub priority: u8,
     }
 
     impl SubscriptionTier {
-        pub fn new(name: &str, credits: u32, max_concurrent: u32, priority: u8) -> Self {
-            let features = match name {
-                "free" => vec![
-                    "Basic verification".to_string(),
-                    "IPFS support".to_string(),
-                    "Email support".to_string(),
-                ],
-                "developer" => vec![
-                    "Advanced verification".to_string(),
-                    "All protocols".to_string(),
-                    "Priority support".to_string(),
-                    "Basic analytics".to_string(),
-                ],
-                "professional" => vec![
-                    "Enterprise verification".to_string(),
-                    "Custom protocols".to_string(),
-                    "Advanced analytics".to_string(),
-                    "Webhook notifications".to_string(),
-                    "SLA monitoring".to_string(),
-                ],
-                "enterprise" => vec![
-                    "Unlimited verification".to_string(),
-                    "White-label solution".to_string(),
-                    "Dedicated support".to_string(),
-                    "Custom SLAs".to_string(),
-                    "On-premise deployment".to_string(),
-                ],
-                _ => vec![],
+        pub fn new(name: &str, daily_challenges: Option<u32>, max_concurrent: u32, priority: u8) -> Self {
+            let (features, max_commitment_bytes, merkle_registration, signature_challenges) = match name {
+                "free" => (
+                    vec![
+                        "Basic verification".to_string(),
+                        "IPFS support".to_string(),
+                        "Email support".to_string(),
+                    ],
+                    Some(FREE_MAX_COMMITMENT_BYTES),
+                    false,
+                    false,
+                ),
+                "pro" => (
+                    vec![
+                        "Advanced verification".to_string(),
+                        "All protocols".to_string(),
+                        "Merkle commitments".to_string(),
+                        "Advanced analytics".to_string(),
+                        "Webhook notifications".to_string(),
+                    ],
+                    None,
+                    true,
+                    false,
+                ),
+                "enterprise" => (
+                    vec![
+                        "Unlimited verification".to_string(),
+                        "Signed challenges".to_string(),
+                        "White-label solution".to_string(),
+                        "Dedicated support".to_string(),
+                        "Custom SLAs".to_string(),
+                    ],
+                    None,
+                    true,
+                    true,
+                ),
+                _ => (vec![], Some(0), false, false),
             };
 
             Self {
                 name: name.to_string(),
-                monthly_credits: credits,
+                daily_challenges,
                 max_concurrent_requests: max_concurrent,
+                max_commitment_bytes,
+                merkle_registration,
+                signature_challenges,
                 features,
                 priority,
             }
         }
     }
 
+    // --- Subscription Store ---
+    /// Key tiers and daily challenge counts, addressed by key digest
+    pub trait SubscriptionStore: Send + Sync {
+        /// Tier of a live key; None when the key is unknown, revoked or expired
+        fn tier(&self, key_hash: &str) -> Result<Option<String>, String>;
+        /// Count one challenge against `day` unless `limit` is used up. Returns the
+        /// challenges left afterwards, or None when the quota was already exhausted.
+        fn consume(&self, key_hash: &str, day: u64, limit: u32) -> Result<Option<u32>, String>;
+        /// Challenges counted against `day` so far
+        fn used(&self, key_hash: &str, day: u64) -> Result<u32, String>;
+    }
+
+    #[derive(Default)]
+    pub struct MemorySubscriptionStore {
+        keys: Mutex<HashMap<String, String>>,
+        usage: Mutex<HashMap<(String, u64), u32>>,
+    }
+
+    impl MemorySubscriptionStore {
+        pub fn add_key(&self, key_hash: &str, tier: &str) {
+            if let Ok(mut keys) = self.keys.lock() {
+                keys.insert(key_hash.to_string(), tier.to_string());
+            }
+        }
+    }
+
+    impl SubscriptionStore for MemorySubscriptionStore {
+        fn tier(&self, key_hash: &str) -> Result<Option<String>, String> {
+            let keys = self.keys.lock().map_err(|_| "Subscription store lock poisoned".to_string())?;
+            Ok(keys.get(key_hash).cloned())
+        }
+
+        fn consume(&self, key_hash: &str, day: u64, limit: u32) -> Result<Option<u32>, String> {
+            let mut usage = self.usage.lock().map_err(|_| "Subscription store lock poisoned".to_string())?;
+            let entry = (key_hash.to_string(), day);
+            if !usage.contains_key(&entry) {
+                // First challenge of the day for this key; drop counters from earlier days
+                usage.retain(|(_, d), _| *d >= day);
+            }
+            let used = usage.entry(entry).or_insert(0);
+            if *used >= limit {
+                return Ok(None);
+            }
+            *used += 1;
+            Ok(Some(limit - *used))
+        }
+
+        fn used(&self, key_hash: &str, day: u64) -> Result<u32, String> {
+            let usage = self.usage.lock().map_err(|_| "Subscription store lock poisoned".to_string())?;
+            Ok(usage.get(&(key_hash.to_string(), day)).copied().unwrap_or(0))
+        }
+    }
+
+    /// SQLite backend reading the `api_keys` table `SqliteKeyStore` writes; daily counts
+    /// live next to it in `challenge_quota`
+    #[cfg(feature = "rusqlite")]
+    pub struct SqliteSubscriptionStore {
+        conn: Mutex<rusqlite::Connection>,
+    }
+
+    #[cfg(feature = "rusqlite")]
+    impl SqliteSubscriptionStore {
+        pub fn open(path: &str) -> Result<Self, String> {
+            let path = path.strip_prefix("sqlite://").unwrap_or(path);
+            let conn = rusqlite::Connection::open(path)
+                .map_err(|e| format!("Failed to open subscription store {}: {}", path, e))?;
+            conn.execute_batch(crate::key_store::API_KEYS_SCHEMA)
+                .map_err(|e| format!("Failed to initialise subscription store: {}", e))?;
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS challenge_quota (
+                    hash TEXT NOT NULL,
+                    day INTEGER NOT NULL,
+                    used INTEGER NOT NULL,
+                    PRIMARY KEY (hash, day)
+                ) WITHOUT ROWID;",
+            )
+            .map_err(|e| format!("Failed to initialise subscription store: {}", e))?;
+            Ok(SqliteSubscriptionStore { conn: Mutex::new(conn) })
+        }
+    }
+
+    #[cfg(feature = "rusqlite")]
+    impl SubscriptionStore for SqliteSubscriptionStore {
+        fn tier(&self, key_hash: &str) -> Result<Option<String>, String> {
+            use rusqlite::OptionalExtension;
+            let conn = self.conn.lock().map_err(|_| "Subscription store lock poisoned".to_string())?;
+            // Timestamps use rusqlite's chrono encoding, which orders lexicographically
+            conn.query_row(
+                "SELECT tier FROM api_keys WHERE hash = ?1 AND revoked_at IS NULL AND expires_at > ?2",
+                rusqlite::params![key_hash, chrono::Utc::now()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to load key tier: {}", e))
+        }
+
+        fn consume(&self, key_hash: &str, day: u64, limit: u32) -> Result<Option<u32>, String> {
+            use rusqlite::OptionalExtension;
+            let mut conn = self.conn.lock().map_err(|_| "Subscription store lock poisoned".to_string())?;
+            let tx = conn.transaction().map_err(|e| format!("Failed to count challenge: {}", e))?;
+            let used: u32 = tx
+                .query_row(
+                    "SELECT used FROM challenge_quota WHERE hash = ?1 AND day = ?2",
+                    rusqlite::params![key_hash, day as i64],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| format!("Failed to count challenge: {}", e))?
+                .unwrap_or(0);
+            if used >= limit {
+                return Ok(None);
+            }
+            tx.execute(
+                "INSERT INTO challenge_quota (hash, day, used) VALUES (?1, ?2, 1)
+                 ON CONFLICT (hash, day) DO UPDATE SET used = used + 1",
+                rusqlite::params![key_hash, day as i64],
+            )
+            .map_err(|e| format!("Failed to count challenge: {}", e))?;
+            tx.execute("DELETE FROM challenge_quota WHERE day < ?1", [day as i64])
+                .map_err(|e| format!("Failed to count challenge: {}", e))?;
+            tx.commit().map_err(|e| format!("Failed to count challenge: {}", e))?;
+            Ok(Some(limit - used - 1))
+        }
+
+        fn used(&self, key_hash: &str, day: u64) -> Result<u32, String> {
+            use rusqlite::OptionalExtension;
+            let conn = self.conn.lock().map_err(|_| "Subscription store lock poisoned".to_string())?;
+            conn.query_row(
+                "SELECT used FROM challenge_quota WHERE hash = ?1 AND day = ?2",
+                rusqlite::params![key_hash, day as i64],
+                |row| row.get(0),
+            )
+            .optional()
+            .map(|used| used.unwrap_or(0))
+            .map_err(|e| format!("Failed to load challenge count: {}", e))
+        }
+    }
+
+    /// Challenge quota for the current UTC day
+    #[derive(Clone, Copy, Debug)]
+    struct Quota {
+        /// Challenges left today; None is unlimited
+        remaining: Option<u32>,
+        /// Unix time the quota resets
+        reset_at: u64,
+    }
+
+    impl Quota {
+        /// Attach X-Quota-Remaining and X-Quota-Reset to a response
+        fn stamp(&self, mut resp: HttpResponse) -> HttpResponse {
+            let remaining = match self.remaining {
+                Some(n) => HeaderValue::from(n),
+                None => HeaderValue::from_static("unlimited"),
+            };
+            let headers = resp.headers_mut();
+            headers.insert(HeaderName::from_static("x-quota-remaining"), remaining);
+            headers.insert(HeaderName::from_static("x-quota-reset"), HeaderValue::from(self.reset_at));
+            resp
+        }
+    }
+
+    fn today() -> (u64, u64) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let day = now / DAY_SECS;
+        (day, (day + 1) * DAY_SECS)
+    }
+
+    fn upgrade_required(message: String) -> HttpResponse {
+        HttpResponse::PaymentRequired().json(serde_json::json!({
+            "error": message,
+            "code": 402,
+            "upgrade_url": "/pricing"
+        }))
+    }
+
+    fn verifier_error(e: &StorageVerificationError) -> HttpResponse {
+        let (mut builder, code) = match e {
+            StorageVerificationError::InvalidInput { .. } => (HttpResponse::BadRequest(), 400),
+            StorageVerificationError::ChallengeNotFound { .. } => (HttpResponse::NotFound(), 404),
+            StorageVerificationError::RateLimitExceeded { .. } => (HttpResponse::TooManyRequests(), 429),
+            StorageVerificationError::Overloaded { .. } => (HttpResponse::ServiceUnavailable(), 503),
+            _ => {
+                error!("Storage verifier error: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Storage verification failed",
+                    "code": 500
+                }));
+            }
+        };
+        builder.json(serde_json::json!({ "error": e.to_string(), "code": code }))
+    }
+
+    fn decode_hash32(field: &str, hex_str: &str) -> Result<[u8; 32], HttpResponse> {
+        hex::decode(hex_str.trim_start_matches("0x"))
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("{} must be a 32-byte hex hash", field),
+                "code": 400
+            })))
+    }
+
     // --- Enhanced Web Server with Paid Service Support ---
     #[derive(Clone)]
     pub struct EnterpriseWebServer {
         verifier: Arc<StorageVerifier>,
+        store: Arc<dyn SubscriptionStore>,
+        // Server secret mixed into key digests; plain SHA-256 when empty
+        pepper: Arc<Vec<u8>>,
         subscriptions: Arc<AsyncMutex<HashMap<String, SubscriptionTier>>>,
         usage_stats: Arc<AsyncMutex<HashMap<String, UserStats>>>,
         active_requests: Arc<AsyncMutex<HashMap<String, Vec<Instant>>>>,
     }
 
-    #[derive(Clone)]
+    #[derive(Clone, Default)]
     struct UserStats {
         total_verifications: u64,
         successful_verifications: u64,
         total_response_time: u64,
         protocol_usage: HashMap<String, u64>,
-        last_reset: u64,
-        credits_used: u32,
     }
 
     impl EnterpriseWebServer {
+        /// Server with an in-memory subscription store; quotas reset on restart
         pub fn new(verifier: StorageVerifier) -> Self {
+            Self::with_store(verifier, Arc::new(MemorySubscriptionStore::default()))
+        }
+
+        pub fn with_store(verifier: StorageVerifier, store: Arc<dyn SubscriptionStore>) -> Self {
             let mut subscriptions = HashMap::new();
 
             // Initialize subscription tiers
-            subscriptions.insert("free".to_string(), SubscriptionTier::new("free", 100, 1, 0));
-            subscriptions.insert("developer".to_string(), SubscriptionTier::new("developer", 1000, 5, 1));
-            subscriptions.insert("professional".to_string(), SubscriptionTier::new("professional", 50000, 20, 2));
-            subscriptions.insert("enterprise".to_string(), SubscriptionTier::new("enterprise", u32::MAX, 100, 3));
+            subscriptions.insert("free".to_string(), SubscriptionTier::new("free", Some(100), 10, 0));
+            subscriptions.insert("pro".to_string(), SubscriptionTier::new("pro", Some(10_000), 50, 1));
+            subscriptions.insert("enterprise".to_string(), SubscriptionTier::new("enterprise", None, 200, 2));
 
             Self {
                 verifier: Arc::new(verifier),
+                store,
+                pepper: Arc::new(Vec::new()),
                 subscriptions: Arc::new(AsyncMutex::new(subscriptions)),
                 usage_stats: Arc::new(AsyncMutex::new(HashMap::new())),
                 active_requests: Arc::new(AsyncMutex::new(HashMap::new())),
             }
         }
 
+        /// Store selected by DATABASE_TYPE/DATABASE_URL and keyed with API_KEY_PEPPER, as
+        /// the API server's KeyManager is; in-memory when SQLite is unavailable
+        pub fn from_env(verifier: StorageVerifier) -> Self {
+            let database_type = std::env::var("DATABASE_TYPE").unwrap_or_else(|_| "sqlite".to_string());
+            let database_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "./sprint.db".to_string());
+            let pepper = std::env::var("API_KEY_PEPPER").unwrap_or_default();
+
+            let server = match database_type.as_str() {
+                #[cfg(feature = "rusqlite")]
+                "sqlite" => match SqliteSubscriptionStore::open(&database_url) {
+                    Ok(store) => Self::with_store(verifier, Arc::new(store)),
+                    Err(e) => {
+                        error!("{}; quotas will not survive a restart", e);
+                        Self::new(verifier)
+                    }
+                },
+                other => {
+                    warn!("No subscription store for database type {} ({}); quotas will not survive a restart", other, database_url);
+                    Self::new(verifier)
+                }
+            };
+            server.with_key_pepper(pepper.as_bytes())
+        }
+
+        pub fn with_key_pepper(mut self, pepper: &[u8]) -> Self {
+            self.pepper = Arc::new(pepper.to_vec());
+            self
+        }
+
+        /// Replace the limits of the tier with the same name
+        pub async fn set_tier(&self, tier: SubscriptionTier) {
+            self.subscriptions.lock().await.insert(tier.name.clone(), tier);
+        }
+
         fn get_api_key_from_request(req: &HttpRequest) -> Option<String> {
             req.headers()
                 .get("authorization")
@@ -206,33 +471,45 @@ fn get_api_key_from_request(req: &HttpRequest) -> Option<String> {
                 .map(|s| s.to_string())
         }
 
-        async fn authenticate_and_get_tier(&self, api_key: &str) -> Result<SubscriptionTier, HttpResponse> {
-            // In production, this would validate against a database
-            // For demo purposes, we'll use a simple mapping
-            let tier_name = match api_key {
-                "free_trial_key" => "free",
-                "dev_key_123" => "developer",
-                "pro_key_456" => "professional",
-                "ent_key_789" => "enterprise",
-                _ => return Err(HttpResponse::Unauthorized().json(serde_json::json!({
+        /// Resolve the request's API key to its digest and subscription tier
+        async fn authenticate(&self, http_req: &HttpRequest) -> Result<(String, SubscriptionTier), HttpResponse> {
+            let api_key = Self::get_api_key_from_request(http_req).ok_or_else(|| {
+                HttpResponse::Unauthorized().json(serde_json::json!({
+                    "error": "Missing API key",
+                    "code": 401
+                }))
+            })?;
+            let key_hash = digest_key(&api_key, &self.pepper);
+
+            let tier_name = match self.store.tier(&key_hash) {
+                Ok(Some(tier)) => tier,
+                Ok(None) => return Err(HttpResponse::Unauthorized().json(serde_json::json!({
                     "error": "Invalid API key",
                     "code": 401
                 }))),
+                Err(e) => {
+                    error!("Key lookup failed: {}", e);
+                    return Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Subscription lookup failed",
+                        "code": 500
+                    })));
+                }
             };
 
             let subscriptions = self.subscriptions.lock().await;
-            subscriptions.get(tier_name)
+            let tier = subscriptions.get(&tier_name)
                 .cloned()
                 .ok_or_else(|| HttpResponse::InternalServerError().json(serde_json::json!({
                     "error": "Subscription tier not found",
                     "code": 500
-                })))
+                })))?;
+            Ok((key_hash, tier))
         }
 
-        async fn check_rate_limits(&self, api_key: &str, tier: &SubscriptionTier) -> Result<(), HttpResponse> {
+        async fn check_rate_limits(&self, key_hash: &str, tier: &SubscriptionTier) -> Result<(), HttpResponse> {
             let mut active_requests = self.active_requests.lock().await;
 
-            let user_requests = active_requests.entry(api_key.to_string()).or_insert_with(Vec::new);
+            let user_requests = active_requests.entry(key_hash.to_string()).or_insert_with(Vec::new);
 
             // Remove expired requests (older than 1 minute)
             let now = Instant::now();
@@ -250,51 +527,63 @@ async fn check_rate_limits(&self, api_key: &str, tier: &SubscriptionTier) -> Res
             Ok(())
         }
 
-        async fn check_credits(&self, api_key: &str, tier: &SubscriptionTier) -> Result<u32, HttpResponse> {
-            let mut usage_stats = self.usage_stats.lock().await;
-            let stats = usage_stats.entry(api_key.to_string()).or_insert_with(|| UserStats {
-                total_verifications: 0,
-                successful_verifications: 0,
-                total_response_time: 0,
-                protocol_usage: HashMap::new(),
-                last_reset: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-                credits_used: 0,
-            });
+        /// Count one challenge generation against today's quota
+        fn consume_challenge_quota(&self, key_hash: &str, tier: &SubscriptionTier) -> Result<Quota, HttpResponse> {
+            let (day, reset_at) = today();
+            let Some(limit) = tier.daily_challenges else {
+                return Ok(Quota { remaining: None, reset_at });
+            };
 
-            // Reset credits if monthly limit reached or new month
-            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-            if now - stats.last_reset > 30 * 24 * 3600 { // 30 days
-                stats.credits_used = 0;
-                stats.last_reset = now;
+            match self.store.consume(key_hash, day, limit) {
+                Ok(Some(remaining)) => Ok(Quota { remaining: Some(remaining), reset_at }),
+                Ok(None) => {
+                    let exhausted = Quota { remaining: Some(0), reset_at };
+                    Err(exhausted.stamp(HttpResponse::TooManyRequests().json(serde_json::json!({
+                        "error": format!("Daily quota of {} challenges exceeded", limit),
+                        "code": 429,
+                        "retry_after": reset_at.saturating_sub(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs())
+                    }))))
+                }
+                Err(e) => {
+                    error!("Quota accounting failed: {}", e);
+                    Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Quota accounting failed",
+                        "code": 500
+                    })))
+                }
             }
+        }
 
-            let credits_remaining = if tier.monthly_credits == u32::MAX {
-                u32::MAX // Unlimited for enterprise
-            } else {
-                tier.monthly_credits.saturating_sub(stats.credits_used)
-            };
+        /// Today's quota without consuming any of it
+        fn quota_status(&self, key_hash: &str, tier: &SubscriptionTier) -> Quota {
+            let (day, reset_at) = today();
+            let remaining = tier.daily_challenges.map(|limit| match self.store.used(key_hash, day) {
+                Ok(used) => limit.saturating_sub(used),
+                Err(e) => {
+                    error!("Quota lookup failed: {}", e);
+                    0
+                }
+            });
+            Quota { remaining, reset_at }
+        }
 
-            if credits_remaining == 0 {
-                return Err(HttpResponse::PaymentRequired().json(serde_json::json!({
-                    "error": "Monthly credit limit exceeded",
-                    "code": 402,
-                    "upgrade_url": "/pricing"
-                })));
+        /// Reject commitments the tier may not register
+        fn check_commitment_allowed(tier: &SubscriptionTier, merkle: bool, commitment_bytes: u64) -> Result<(), HttpResponse> {
+            if merkle && !tier.merkle_registration {
+                return Err(upgrade_required(format!("Merkle registration is not available on the {} tier", tier.name)));
+            }
+            match tier.max_commitment_bytes {
+                Some(max) if commitment_bytes > max => Err(upgrade_required(format!(
+                    "Commitment of {} bytes exceeds the {} tier limit of {} bytes",
+                    commitment_bytes, tier.name, max
+                ))),
+                _ => Ok(()),
             }
-
-            Ok(credits_remaining)
         }
 
-        async fn update_stats(&self, api_key: &str, protocol: &str, success: bool, response_time: u64) {
+        async fn update_stats(&self, key_hash: &str, protocol: &str, success: bool, response_time: u64) {
             let mut usage_stats = self.usage_stats.lock().await;
-            let stats = usage_stats.entry(api_key.to_string()).or_insert_with(|| UserStats {
-                total_verifications: 0,
-                successful_verifications: 0,
-                total_response_time: 0,
-                protocol_usage: HashMap::new(),
-                last_reset: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-                credits_used: 0,
-            });
+            let stats = usage_stats.entry(key_hash.to_string()).or_default();
 
             stats.total_verifications += 1;
             stats.total_response_time += response_time;
@@ -303,8 +592,6 @@ async fn update_stats(&self, api_key: &str, protocol: &str, success: bool, respo
             if success {
                 stats.successful_verifications += 1;
             }
-
-            stats.credits_used += 1;
         }
     }
 
@@ -317,202 +604,211 @@ pub async fn validate_storage(
         ) -> Result<HttpResponse> {
             let start_time = Instant::now();
 
-            // Extract and validate API key
-            let api_key = match Self::get_api_key_from_request(&http_req) {
-                Some(key) => key,
-                None => return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
-                    "error": "Missing API key",
-                    "code": 401
-                }))),
-            };
-
             // Authenticate and get subscription tier
-            let tier = match self.authenticate_and_get_tier(&api_key).await {
-                Ok(tier) => tier,
+            let (key_hash, tier) = match self.authenticate(&http_req).await {
+                Ok(auth) => auth,
                 Err(resp) => return Ok(resp),
             };
 
             // Check rate limits
-            if let Err(resp) = self.check_rate_limits(&api_key, &tier).await {
+            if let Err(resp) = self.check_rate_limits(&key_hash, &tier).await {
                 return Ok(resp);
             }
 
-            // Check credits
-            let credits_remaining = match self.check_credits(&api_key, &tier).await {
-                Ok(remaining) => remaining,
-                Err(resp) => return Ok(resp),
-            };
-
-            // Perform validation
-            let challenge = StorageChallenge {
-                id: Uuid::new_v4().to_string(),
-                file_id: req.file_id.clone(),
-                provider: req.provider.clone().unwrap_or_else(|| "auto".to_string()),
-                nonce: rand::random(),
-                timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-                expiry: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600, // 1 hour
-                beacon: format!("beacon-{}", rand::random::<u64>()),
-                difficulty: 8,
-                challenge_data: vec![],
-                sample_offset: 0,
-                sample_size: 1024,
-                chunk_index: 0,
-                commitment_alg: "sha256_chunks".to_string(),
-            };
-
-            // Generate proof for the challenge
-            let mut proof = StorageProof {
-                challenge_id: challenge.id.clone(),
-                file_id: challenge.file_id.clone(),
-                provider: challenge.provider.clone(),
-                timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-                proof_data: vec![1, 2, 3, 4], // Mock proof data
-                merkle_proof: None,
-                signature: None,
-            };
-
-            // Handle Merkle proof if provided
-            let mut merkle_root = None;
-            let mut merkle_proof_valid = false;
-
-            if let Some(merkle_data) = &req.merkle_proof {
-                // Convert web Merkle proof format to internal format
-                let mut merkle_proof_strings = Vec::new();
-                for element in &merkle_data.proof {
-                    merkle_proof_strings.push(element.hash.clone());
+            let req = req.into_inner();
+            let proof = match decode_proof(req.proof, self.verifier.max_proof_size()) {
+                Ok(proof) => proof,
+                Err(error) => {
+                    return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": error, "code": 400 })))
                 }
-
-                proof.merkle_proof = Some(merkle_proof_strings);
-
-                // Store Merkle root for verification
-                if let Ok(root_bytes) = hex::decode(&merkle_data.root.trim_start_matches("0x")) {
-                    if root_bytes.len() == 32 {
-                        let mut root_array = [0u8; 32];
-                        root_array.copy_from_slice(&root_bytes);
-                        merkle_root = Some(merkle_data.root.clone());
-
-                        // Register Merkle root with the verifier
-                        if let Err(e) = self.verifier.register_merkle_root(
-                            &req.file_id,
-                            root_array,
-                            merkle_data.chunk_size as u32,
-                            merkle_data.total_chunks
-                        ).await {
-                            warn!("Failed to register Merkle root: {:?}", e);
-                        }
-                    }
-                }
-            }
-
-            let verification_result = self.verifier.verify_proof(proof.clone()).await;
-            let response_time = start_time.elapsed().as_millis() as u64;
-
-            let (verified, verification_score) = match verification_result {
-                Ok(_) => {
-                    merkle_proof_valid = req.merkle_proof.is_some();
-                    (true, 0.95)
-                },
-                Err(_) => (false, 0.0),
             };
+            let challenge_id = proof.challenge_id.clone();
+            let file_id = proof.file_id.clone();
+            let provider = proof.provider.clone();
+            let merkle_path_supplied = proof.merkle_proof.is_some();
+
+            // The quota was spent when the challenge was issued, so answering it is free
+            let (verified, verification_score) =
+                match self.verifier.verify_proof_scored_for_tenant(&key_hash, proof).await {
+                    Ok(outcome) => (outcome.verified, outcome.score),
+                    Err(StorageVerificationError::CryptographicFailure { .. })
+                    | Err(StorageVerificationError::AuthenticationFailed) => (false, 0.0),
+                    Err(e) => return Ok(verifier_error(&e)),
+                };
+            let response_time = start_time.elapsed().as_millis() as u64;
 
             // Update statistics
-            self.update_stats(&api_key, &req.protocol, verified, response_time).await;
+            self.update_stats(&key_hash, &req.protocol, verified, response_time).await;
 
             // Send webhook if provided
             let webhook_sent = if let Some(webhook_url) = &req.webhook_url {
-                self.send_webhook(webhook_url, &challenge, verified, verification_score).await
+                self.send_webhook(webhook_url, &challenge_id, &file_id, &provider, verified, verification_score).await
             } else {
                 false
             };
 
+            let quota = self.quota_status(&key_hash, &tier);
             let response = ValidateStorageResponse {
                 status: if verified { "verified" } else { "failed" }.to_string(),
                 verified,
                 verification_score,
                 response_time_ms: response_time,
-                challenge_id: challenge.id,
-                protocol: req.protocol.clone(),
-                provider: challenge.provider,
+                challenge_id,
+                protocol: req.protocol,
+                provider,
                 tier_used: tier.name,
-                credits_used: 1,
-                credits_remaining: credits_remaining.saturating_sub(1),
-                merkle_root,
-                merkle_proof_valid,
+                credits_used: 0,
+                credits_remaining: quota.remaining.unwrap_or(u32::MAX),
+                merkle_root: None,
+                merkle_proof_valid: verified && merkle_path_supplied,
                 timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
                 webhook_sent,
             };
 
-            Ok(HttpResponse::Ok().json(response))
+            Ok(quota.stamp(HttpResponse::Ok().json(response)))
         }
 
-        pub async fn get_subscription_info(
+        /// Issue a challenge, counted against the key's daily quota
+        pub async fn create_challenge(
             &self,
+            req: web::Json<TierChallengeRequest>,
             http_req: HttpRequest,
         ) -> Result<HttpResponse> {
-            let api_key = match Self::get_api_key_from_request(&http_req) {
-                Some(key) => key,
-                None => return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
-                    "error": "Missing API key",
-                    "code": 401
-                }))),
+            let (key_hash, tier) = match self.authenticate(&http_req).await {
+                Ok(auth) => auth,
+                Err(resp) => return Ok(resp),
             };
 
-            let tier = match self.authenticate_and_get_tier(&api_key).await {
-                Ok(tier) => tier,
+            if req.require_signature && !tier.signature_challenges {
+                return Ok(upgrade_required(format!("Signed challenges are not available on the {} tier", tier.name)));
+            }
+
+            if let Err(resp) = self.check_rate_limits(&key_hash, &tier).await {
+                return Ok(resp);
+            }
+
+            let quota = match self.consume_challenge_quota(&key_hash, &tier) {
+                Ok(quota) => quota,
                 Err(resp) => return Ok(resp),
             };
 
-            let credits_remaining = match self.check_credits(&api_key, &tier).await {
-                Ok(remaining) => remaining,
-                Err(_) => 0, // If error, assume no credits
+            // Commitments and challenges are scoped to the key that registered them
+            let resp = match self
+                .verifier
+                .generate_challenge_for_tenant(&key_hash, &req.file_id, &req.provider, req.require_signature)
+                .await
+            {
+                Ok(challenge) => {
+                    info!("Issued {} tier challenge {} for file {}", tier.name, challenge.id, challenge.file_id);
+                    HttpResponse::Created().json(ChallengeResponse::from(challenge))
+                }
+                Err(e) => verifier_error(&e),
             };
+            Ok(quota.stamp(resp))
+        }
 
+        /// Register chunk commitments for a file, within the tier's size and Merkle limits
+        pub async fn register_commitments(
+            &self,
+            req: web::Json<RegisterCommitmentRequest>,
+            http_req: HttpRequest,
+        ) -> Result<HttpResponse> {
+            let (key_hash, tier) = match self.authenticate(&http_req).await {
+                Ok(auth) => auth,
+                Err(resp) => return Ok(resp),
+            };
+
+            let req = req.into_inner();
+            let merkle = req.merkle_root.is_some();
+            let total_chunks = if merkle {
+                match req.total_chunks {
+                    Some(total) => total,
+                    None => return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                        "error": "total_chunks is required with merkle_root",
+                        "code": 400
+                    }))),
+                }
+            } else {
+                req.leaf_hashes.len() as u64
+            };
+            let commitment_bytes = u64::from(req.chunk_size).saturating_mul(total_chunks);
+            if let Err(resp) = Self::check_commitment_allowed(&tier, merkle, commitment_bytes) {
+                return Ok(resp);
+            }
+
+            if let Err(resp) = self.check_rate_limits(&key_hash, &tier).await {
+                return Ok(resp);
+            }
+
+            let result = match &req.merkle_root {
+                Some(root) => {
+                    let root = match decode_hash32("merkle_root", root) {
+                        Ok(root) => root,
+                        Err(resp) => return Ok(resp),
+                    };
+                    self.verifier.register_merkle_root_for_tenant(&key_hash, &req.file_id, root, req.chunk_size, total_chunks).await
+                }
+                None => {
+                    let leaves = match req.leaf_hashes.iter().map(|h| decode_hash32("leaf_hashes", h)).collect::<Result<Vec<_>, _>>() {
+                        Ok(leaves) => leaves,
+                        Err(resp) => return Ok(resp),
+                    };
+                    self.verifier.register_file_commitments_for_tenant(&key_hash, &req.file_id, req.chunk_size, leaves).await
+                }
+            };
+
+            match result {
+                Ok(()) => Ok(HttpResponse::Created().json(serde_json::json!({
+                    "file_id": req.file_id,
+                    "commitment_alg": if merkle { "merkle_sha256" } else { "sha256_chunks" },
+                    "total_chunks": total_chunks,
+                    "commitment_bytes": commitment_bytes
+                }))),
+                Err(e) => Ok(verifier_error(&e)),
+            }
+        }
+
+        pub async fn get_subscription_info(
+            &self,
+            http_req: HttpRequest,
+        ) -> Result<HttpResponse> {
+            let (key_hash, tier) = match self.authenticate(&http_req).await {
+                Ok(auth) => auth,
+                Err(resp) => return Ok(resp),
+            };
+
+            let quota = self.quota_status(&key_hash, &tier);
             let info = SubscriptionInfo {
                 tier: tier.name,
-                credits_remaining,
-                monthly_limit: tier.monthly_credits,
-                reset_date: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 30 * 24 * 3600,
+                credits_remaining: quota.remaining.unwrap_or(u32::MAX),
+                daily_limit: tier.daily_challenges,
+                reset_date: quota.reset_at,
                 features: tier.features,
             };
 
-            Ok(HttpResponse::Ok().json(info))
+            Ok(quota.stamp(HttpResponse::Ok().json(info)))
         }
 
         pub async fn get_analytics(
             &self,
             http_req: HttpRequest,
         ) -> Result<HttpResponse> {
-            let api_key = match Self::get_api_key_from_request(&http_req) {
-                Some(key) => key,
-                None => return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
-                    "error": "Missing API key",
-                    "code": 401
-                }))),
-            };
-
-            let tier = match self.authenticate_and_get_tier(&api_key).await {
-                Ok(tier) => tier,
+            let (key_hash, tier) = match self.authenticate(&http_req).await {
+                Ok(auth) => auth,
                 Err(resp) => return Ok(resp),
             };
 
-            // Only professional and enterprise tiers get analytics
-            if !matches!(tier.name.as_str(), "professional" | "enterprise") {
+            // Only pro and enterprise tiers get analytics
+            if !matches!(tier.name.as_str(), "pro" | "enterprise") {
                 return Ok(HttpResponse::Forbidden().json(serde_json::json!({
-                    "error": "Analytics requires Professional or Enterprise tier",
+                    "error": "Analytics requires Pro or Enterprise tier",
                     "code": 403
                 })));
             }
 
             let usage_stats = self.usage_stats.lock().await;
-            let default_stats = UserStats {
-                total_verifications: 0,
-                successful_verifications: 0,
-                total_response_time: 0,
-                protocol_usage: HashMap::new(),
-                last_reset: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-                credits_used: 0,
-            };
-            let stats = usage_stats.get(&api_key).unwrap_or(&default_stats);
+            let default_stats = UserStats::default();
+            let stats = usage_stats.get(&key_hash).unwrap_or(&default_stats);
 
             let success_rate = if stats.total_verifications > 0 {
                 stats.successful_verifications as f64 / stats.total_verifications as f64
@@ -538,15 +834,22 @@ pub async fn get_analytics(
             Ok(HttpResponse::Ok().json(analytics))
         }
 
-        async fn send_webhook(&self, webhook_url: &str, challenge: &StorageChallenge, verified: bool, score: f64) -> bool {
+        async fn send_webhook(
+            &self,
+            webhook_url: &str,
+            challenge_id: &str,
+            file_id: &str,
+            provider: &str,
+            verified: bool,
+            score: f64,
+        ) -> bool {
             let payload = serde_json::json!({
                 "event": "storage_verification_complete",
-                "challenge_id": challenge.id,
-                "file_id": challenge.file_id,
+                "challenge_id": challenge_id,
+                "file_id": file_id,
                 "verified": verified,
                 "verification_score": score,
-                "protocol": challenge.provider,
-                "provider": challenge.provider,
+                "provider": provider,
                 "timestamp": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
             });
 
@@ -558,8 +861,42 @@ async fn send_webhook(&self, webhook_url: &str, challenge: &StorageChallenge, ve
     }
 
     // --- Server Setup ---
+    pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+        cfg.route("/api/validate-storage", web::post().to(
+                |req: web::Json<ValidateStorageRequest>, http_req: HttpRequest, server: web::Data<EnterpriseWebServer>| async move {
+                    server.validate_storage(req, http_req).await
+                }
+            ))
+            .route("/api/challenges", web::post().to(
+                |req: web::Json<TierChallengeRequest>, http_req: HttpRequest, server: web::Data<EnterpriseWebServer>| async move {
+                    server.create_challenge(req, http_req).await
+                }
+            ))
+            .route("/api/commitments", web::post().to(
+                |req: web::Json<RegisterCommitmentRequest>, http_req: HttpRequest, server: web::Data<EnterpriseWebServer>| async move {
+                    server.register_commitments(req, http_req).await
+                }
+            ))
+            .route("/api/subscription", web::get().to(
+                |http_req: HttpRequest, server: web::Data<EnterpriseWebServer>| async move {
+                    server.get_subscription_info(http_req).await
+                }
+            ))
+            .route("/api/analytics", web::get().to(
+                |http_req: HttpRequest, server: web::Data<EnterpriseWebServer>| async move {
+                    server.get_analytics(http_req).await
+                }
+            ))
+            .route("/health", web::get().to(|| async {
+                HttpResponse::Ok().json(serde_json::json!({
+                    "status": "healthy",
+                    "timestamp": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+                }))
+            }));
+    }
+
     pub async fn run_enterprise_server(verifier: StorageVerifier, port: u16) -> std::io::Result<()> {
-        let server = EnterpriseWebServer::new(verifier);
+        let server = EnterpriseWebServer::from_env(verifier);
 
         info!("🚀 Starting Bitcoin Sprint Enterprise Storage Validation Server on port {}", port);
 
@@ -567,32 +904,248 @@ pub async fn run_enterprise_server(verifier: StorageVerifier, port: u16) -> std:
             App::new()
                 .app_data(web::Data::new(server.clone()))
                 .wrap(middleware::Logger::default())
-                .route("/api/validate-storage", web::post().to(
-                    |req: web::Json<ValidateStorageRequest>, http_req: HttpRequest, server: web::Data<EnterpriseWebServer>| async move {
-                        server.validate_storage(req, http_req).await
-                    }
-                ))
-                .route("/api/subscription", web::get().to(
-                    |http_req: HttpRequest, server: web::Data<EnterpriseWebServer>| async move {
-                        server.get_subscription_info(http_req).await
-                    }
-                ))
-                .route("/api/analytics", web::get().to(
-                    |http_req: HttpRequest, server: web::Data<EnterpriseWebServer>| async move {
-                        server.get_analytics(http_req).await
-                    }
-                ))
-                .route("/health", web::get().to(|| async {
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "status": "healthy",
-                        "timestamp": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
-                    }))
-                }))
+                .configure(configure_routes)
         })
         .bind(("0.0.0.0", port))?
         .run()
         .await
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use actix_web::dev::ServiceResponse;
+        use actix_web::http::StatusCode;
+        use actix_web::test;
+
+        const CHUNK: u32 = 32;
+
+        async fn server() -> EnterpriseWebServer {
+            let verifier = StorageVerifier::new();
+            let leaves: Vec<[u8; 32]> = (0..8u8).map(|i| crate::merkle::hash_leaf(&[i; CHUNK as usize])).collect();
+
+            let store = Arc::new(MemorySubscriptionStore::default());
+            for (key, tier) in [("free-key", "free"), ("pro-key", "pro"), ("ent-key", "enterprise")] {
+                let key_hash = digest_key(key, b"");
+                verifier.register_file_commitments_for_tenant(&key_hash, "file-1", CHUNK, leaves.clone()).await.unwrap();
+                store.add_key(&key_hash, tier);
+            }
+            let server = EnterpriseWebServer::with_store(verifier, store);
+            server.set_tier(SubscriptionTier::new("free", Some(3), 100, 0)).await;
+            server
+        }
+
+        fn authorized(req: test::TestRequest, key: &str) -> test::TestRequest {
+            req.insert_header(("authorization", format!("Bearer {}", key)))
+        }
+
+        fn challenge(key: &str, require_signature: bool) -> test::TestRequest {
+            authorized(test::TestRequest::post().uri("/api/challenges"), key).set_json(TierChallengeRequest {
+                file_id: "file-1".to_string(),
+                provider: "provider-1".to_string(),
+                require_signature,
+            })
+        }
+
+        fn commitments(key: &str, req: RegisterCommitmentRequest) -> test::TestRequest {
+            authorized(test::TestRequest::post().uri("/api/commitments"), key).set_json(req)
+        }
+
+        fn header(resp: &ServiceResponse, name: &str) -> String {
+            resp.headers().get(name).unwrap().to_str().unwrap().to_string()
+        }
+
+        #[actix_web::test]
+        async fn test_free_key_daily_quota_is_enforced_while_pro_key_keeps_working() {
+            let app = test::init_service(
+                App::new().app_data(web::Data::new(server().await)).configure(configure_routes),
+            )
+            .await;
+
+            for remaining in ["2", "1", "0"] {
+                let resp = test::call_service(&app, challenge("free-key", false).to_request()).await;
+                assert_eq!(resp.status(), StatusCode::CREATED);
+                assert_eq!(header(&resp, "x-quota-remaining"), remaining);
+            }
+
+            let resp = test::call_service(&app, challenge("free-key", false).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(header(&resp, "x-quota-remaining"), "0");
+            let reset: u64 = header(&resp, "x-quota-reset").parse().unwrap();
+            assert!(reset > SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+
+            let resp = test::call_service(&app, challenge("pro-key", false).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+            assert_eq!(header(&resp, "x-quota-remaining"), "9999");
+
+            let req = authorized(test::TestRequest::get().uri("/api/subscription"), "free-key").to_request();
+            let info: SubscriptionInfo = test::call_and_read_body_json(&app, req).await;
+            assert_eq!((info.credits_remaining, info.daily_limit), (0, Some(3)));
+        }
+
+        #[actix_web::test]
+        async fn test_tiers_gate_merkle_registration_commitment_size_and_signed_challenges() {
+            let app = test::init_service(
+                App::new().app_data(web::Data::new(server().await)).configure(configure_routes),
+            )
+            .await;
+            let merkle = |file_id: &str| RegisterCommitmentRequest {
+                file_id: file_id.to_string(),
+                chunk_size: CHUNK,
+                leaf_hashes: vec![],
+                merkle_root: Some(hex::encode([7u8; 32])),
+                total_chunks: Some(4),
+            };
+
+            let resp = test::call_service(&app, commitments("free-key", merkle("file-2")).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+            let resp = test::call_service(&app, commitments("pro-key", merkle("file-2")).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+
+            let leaves = |count: usize, chunk_size: u32| RegisterCommitmentRequest {
+                file_id: "file-3".to_string(),
+                chunk_size,
+                leaf_hashes: vec![hex::encode([1u8; 32]); count],
+                merkle_root: None,
+                total_chunks: None,
+            };
+            let oversized = leaves(65, 1024 * 1024);
+            let resp = test::call_service(&app, commitments("free-key", oversized).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+            let resp = test::call_service(&app, commitments("free-key", leaves(4, CHUNK)).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+
+            let resp = test::call_service(&app, challenge("pro-key", true).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+            let resp = test::call_service(&app, challenge("ent-key", true).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+            assert_eq!(header(&resp, "x-quota-remaining"), "unlimited");
+            let issued: ChallengeResponse = test::read_body_json(resp).await;
+            assert!(issued.require_signature);
+
+            let resp = test::call_service(&app, challenge("unknown-key", false).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        #[actix_web::test]
+        async fn test_keys_cannot_challenge_files_registered_by_another_key() {
+            let app = test::init_service(
+                App::new().app_data(web::Data::new(server().await)).configure(configure_routes),
+            )
+            .await;
+            let register = RegisterCommitmentRequest {
+                file_id: "pro-only".to_string(),
+                chunk_size: CHUNK,
+                leaf_hashes: vec![hex::encode([1u8; 32]); 2],
+                merkle_root: None,
+                total_chunks: None,
+            };
+            let resp = test::call_service(&app, commitments("pro-key", register).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+
+            let challenge_for = |key: &str| {
+                authorized(test::TestRequest::post().uri("/api/challenges"), key).set_json(TierChallengeRequest {
+                    file_id: "pro-only".to_string(),
+                    provider: "provider-1".to_string(),
+                    require_signature: false,
+                })
+            };
+            let resp = test::call_service(&app, challenge_for("pro-key").to_request()).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+            let resp = test::call_service(&app, challenge_for("ent-key").to_request()).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+
+        /// Answer `issued` for `key`, with chunk `i` of file-1 read as `chunk(i)`
+        fn validate(key: &str, issued: &ChallengeResponse, chunk: impl Fn(u64) -> Vec<u8>) -> test::TestRequest {
+            use base64::{engine::general_purpose, Engine as _};
+            let sample = |i: u64| general_purpose::STANDARD.encode(chunk(i));
+            let body = ValidateStorageRequest {
+                protocol: "ipfs".to_string(),
+                tier: String::new(),
+                webhook_url: None,
+                proof: SubmitProofRequest {
+                    challenge_id: issued.id.clone(),
+                    file_id: issued.file_id.clone(),
+                    provider: issued.provider.clone(),
+                    timestamp: issued.issued_at,
+                    proof_data: sample(issued.chunk_indices[0]),
+                    extra_proof_data: issued.chunk_indices[1..].iter().map(|&i| sample(i)).collect(),
+                    merkle_proof: None,
+                    signature: None,
+                },
+            };
+            authorized(test::TestRequest::post().uri("/api/validate-storage"), key).set_json(body)
+        }
+
+        #[actix_web::test]
+        async fn test_validate_storage_checks_samples_against_the_issued_challenge() {
+            let app = test::init_service(
+                App::new().app_data(web::Data::new(server().await)).configure(configure_routes),
+            )
+            .await;
+
+            let issue = || async {
+                let resp = test::call_service(&app, challenge("pro-key", false).to_request()).await;
+                assert_eq!(resp.status(), StatusCode::CREATED);
+                test::read_body_json::<ChallengeResponse, _>(resp).await
+            };
+
+            let issued = issue().await;
+            let resp = test::call_service(&app, validate("pro-key", &issued, |i| vec![i as u8; CHUNK as usize]).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(header(&resp, "x-quota-remaining"), "9999");
+            let verdict: ValidateStorageResponse = test::read_body_json(resp).await;
+            assert!(verdict.verified);
+            assert_eq!((verdict.challenge_id, verdict.credits_used), (issued.id, 0));
+
+            let issued = issue().await;
+            let req = validate("pro-key", &issued, |_| vec![0xff; CHUNK as usize]).to_request();
+            let verdict: ValidateStorageResponse = test::call_and_read_body_json(&app, req).await;
+            assert!(!verdict.verified);
+            assert_eq!(verdict.status, "failed");
+
+            // Another key cannot answer this key's challenge, and a made-up challenge is unknown
+            let issued = issue().await;
+            let resp = test::call_service(&app, validate("ent-key", &issued, |i| vec![i as u8; CHUNK as usize]).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+            let forged = ChallengeResponse { id: "forged".to_string(), ..issued };
+            let resp = test::call_service(&app, validate("pro-key", &forged, |i| vec![i as u8; CHUNK as usize]).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        }
+
+        #[cfg(feature = "rusqlite")]
+        #[actix_web::test]
+        async fn test_sqlite_quota_survives_reopen_and_reads_key_store_rows() {
+            use crate::key_store::{KeyDetails, KeyStore, SqliteKeyStore};
+            let path = std::env::temp_dir().join(format!("sprint-quota-{:016x}.db", rand::random::<u64>()));
+            let path = path.to_str().unwrap().to_string();
+            let hash = digest_key("free-key", b"pepper");
+            {
+                let store = SqliteSubscriptionStore::open(&path).unwrap();
+                let now = chrono::Utc::now();
+                SqliteKeyStore::open(&path).unwrap().put(&KeyDetails {
+                    hash: hash.clone(),
+                    tier: "free".to_string(),
+                    created_at: now,
+                    expires_at: now + chrono::Duration::days(30),
+                    request_count: 0,
+                    rate_limit_remaining: 1000,
+                    revoked_at: None,
+                }).unwrap();
+                assert_eq!(store.tier(&hash).unwrap().as_deref(), Some("free"));
+                assert_eq!(store.consume(&hash, 100, 2).unwrap(), Some(1));
+            }
+
+            let store = SqliteSubscriptionStore::open(&format!("sqlite://{}", path)).unwrap();
+            assert_eq!(store.used(&hash, 100).unwrap(), 1);
+            assert_eq!(store.consume(&hash, 100, 2).unwrap(), Some(0));
+            assert_eq!(store.consume(&hash, 100, 2).unwrap(), None);
+            assert_eq!(store.tier(&digest_key("free-key", b"")).unwrap(), None);
+            drop(store);
+            let _ = std::fs::remove_file(&path);
+        }
+    }
 }
 
 // Re-export the public function when the feature is enabled
@@ -601,4 +1154,13 @@ pub async fn run_enterprise_server(verifier: StorageVerifier, port: u16) -> std:
 
 // Re-export the request/response types
 #[cfg(feature = "web-server")]
-pub use web_server::{ValidateStorageRequest, ValidateStorageResponse, MerkleProofData, MerkleProofElement};
\ No newline at end of file
+pub use web_server::{ValidateStorageRequest, ValidateStorageResponse};
+
+#[cfg(feature = "web-server")]
+pub use web_server::{
+    EnterpriseWebServer, MemorySubscriptionStore, RegisterCommitmentRequest, SubscriptionStore,
+    SubscriptionTier, TierChallengeRequest,
+};
+
+#[cfg(all(feature = "web-server", feature = "rusqlite"))]
+pub use web_server::SqliteSubscriptionStore;
\ No newline at end of file
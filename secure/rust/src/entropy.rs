@@ -1,14 +1,15 @@
 // SPDX-License-Identifier: MIT
 // Bitcoin Sprint - Cryptographically Secure Entropy Module
 
+use std::cell::RefCell;
 use std::sync::atomic::{AtomicU64, Ordering};
-use rand::RngCore;
-use rand::rngs::OsRng;
-#[cfg(target_family = "unix")]
-use libc;
+use std::sync::{Arc, Mutex};
+use rand::{RngCore, SeedableRng};
+use rand::rngs::{OsRng, StdRng};
 use sysinfo::{System, RefreshKind, CpuRefreshKind};
 use base64;
 use hex;
+use serde::Serialize;
 
 // Static jitter accumulator for CPU timing entropy
 static JITTER_COUNTER: AtomicU64 = AtomicU64::new(0);
@@ -42,23 +43,7 @@ pub fn new() -> Self {
 
     /// Collect high-resolution timing jitter (supplemental entropy only)
     fn collect_jitter(&self) -> u64 {
-        let start = std::time::Instant::now();
-
-        // Perform some unpredictable operations to create timing variance
-        let mut accumulator = 0u64;
-        for i in 0..100 {
-            accumulator = accumulator.wrapping_mul(6364136223846793005u64)
-                .wrapping_add(1442695040888963407u64)
-                .wrapping_add(i);
-        }
-
-        let duration = start.elapsed();
-        let jitter = duration.as_nanos() as u64 ^ accumulator;
-
-        // Update global counter
-        JITTER_COUNTER.fetch_add(jitter.wrapping_mul(accumulator), Ordering::Relaxed);
-
-        jitter
+        timing_jitter()
     }
 
     /// Get cryptographically secure OS-level randomness
@@ -66,108 +51,266 @@ fn get_os_entropy(&mut self, output: &mut [u8]) -> Result<(), EntropyError> {
         self.os_rng.try_fill_bytes(output)
             .map_err(|e| EntropyError::SystemError(format!("OS RNG failed: {}", e)))
     }
+}
+
+/// High-resolution timing jitter (supplemental entropy only)
+fn timing_jitter() -> u64 {
+    let start = std::time::Instant::now();
+
+    // Perform some unpredictable operations to create timing variance
+    let mut accumulator = 0u64;
+    for i in 0..100 {
+        accumulator = accumulator.wrapping_mul(6364136223846793005u64)
+            .wrapping_add(1442695040888963407u64)
+            .wrapping_add(i);
+    }
+
+    let duration = start.elapsed();
+    let jitter = duration.as_nanos() as u64 ^ accumulator;
 
-    /// Extract entropy from Bitcoin block headers (non-deterministic mixing)
-    fn extract_block_entropy(&mut self, headers: &[Vec<u8>]) -> [u8; 32] {
-        let mut combined_entropy = [0u8; 32];
+    // Update global counter
+    JITTER_COUNTER.fetch_add(jitter.wrapping_mul(accumulator), Ordering::Relaxed);
+
+    jitter
+}
+
+/// Mix Bitcoin block header fields into base randomness from `source`
+fn extract_block_entropy<S: EntropySource + ?Sized>(source: &S, headers: &[Vec<u8>]) -> [u8; 32] {
+    let mut combined_entropy = [0u8; 32];
+
+    if headers.is_empty() {
+        // If no headers provided, use pure source entropy
+        let _ = source.fill_bytes(&mut combined_entropy);
+        return combined_entropy;
+    }
 
-        if headers.is_empty() {
-            // If no headers provided, use pure OS entropy
-            let _ = self.get_os_entropy(&mut combined_entropy);
-            return combined_entropy;
+    // Start with source entropy as base
+    let _ = source.fill_bytes(&mut combined_entropy);
+
+    // Mix in block header data non-deterministically
+    for (i, header) in headers.iter().enumerate() {
+        if header.len() >= 80 {
+            // Extract variable fields from Bitcoin header
+            let nonce = &header[76..80];
+            let timestamp = &header[68..72];
+            let merkle_root = &header[36..68];
+
+            // Mix each field with source entropy using different positions
+            for (j, &byte) in nonce.iter().enumerate() {
+                let pos = (i * 4 + j) % 32;
+                combined_entropy[pos] ^= byte;
+            }
+
+            for (j, &byte) in timestamp.iter().enumerate() {
+                let pos = (i * 4 + j + 16) % 32;
+                combined_entropy[pos] ^= byte;
+            }
+
+            // Mix merkle root bytes
+            for (j, &byte) in merkle_root.iter().enumerate() {
+                let pos = (i * 32 + j) % 32;
+                combined_entropy[pos] ^= byte;
+            }
         }
+    }
 
-        // Start with OS entropy as base
-        let _ = self.get_os_entropy(&mut combined_entropy);
-
-        // Mix in block header data non-deterministically
-        for (i, header) in headers.iter().enumerate() {
-            if header.len() >= 80 {
-                // Extract variable fields from Bitcoin header
-                let nonce = &header[76..80];
-                let timestamp = &header[68..72];
-                let merkle_root = &header[36..68];
-
-                // Mix each field with OS entropy using different positions
-                for (j, &byte) in nonce.iter().enumerate() {
-                    let pos = (i * 4 + j) % 32;
-                    combined_entropy[pos] ^= byte;
-                }
+    // Add timing jitter as additional entropy
+    let jitter = source.jitter();
+    let jitter_bytes = jitter.to_le_bytes();
 
-                for (j, &byte) in timestamp.iter().enumerate() {
-                    let pos = (i * 4 + j + 16) % 32;
-                    combined_entropy[pos] ^= byte;
-                }
+    for (i, &b) in jitter_bytes.iter().enumerate().take(8) {
+        combined_entropy[i] ^= b;
+        combined_entropy[i + 24] ^= jitter_bytes[7 - i];
+    }
 
-                // Mix merkle root bytes
-                for (j, &byte) in merkle_root.iter().enumerate() {
-                    let pos = (i * 32 + j) % 32;
-                    combined_entropy[pos] ^= byte;
-                }
+    combined_entropy
+}
+
+/// Source of the randomness behind the entropy functions.
+///
+/// Production code uses `OsJitterSource`; tests can install a
+/// `DeterministicSource` with `with_entropy_source` to get reproducible output.
+pub trait EntropySource: Send + Sync {
+    /// Fill `output` with random bytes
+    fn fill_bytes(&self, output: &mut [u8]) -> Result<(), EntropyError>;
+
+    /// Supplemental jitter mixed into the primary randomness
+    fn jitter(&self) -> u64;
+
+    /// Generate fast entropy (32 bytes): primary randomness plus jitter
+    fn fast_entropy(&self) -> [u8; 32] {
+        let mut output = [0u8; 32];
+
+        // Use the primary randomness source
+        if self.fill_bytes(&mut output).is_ok() {
+            // Add jitter as additional entropy (supplemental only)
+            let jitter_bytes = self.jitter().to_le_bytes();
+
+            // Mix jitter with primary entropy
+            for (i, &b) in jitter_bytes.iter().enumerate().take(8) {
+                output[i] ^= b;
+                output[i + 24] ^= jitter_bytes[7 - i];
             }
+        } else {
+            // Fallback: retry the primary source without jitter enhancement
+            let _ = self.fill_bytes(&mut output);
         }
 
-        // Add timing jitter as additional entropy
-        let jitter = self.collect_jitter();
-        let jitter_bytes = jitter.to_le_bytes();
+        output
+    }
+
+    /// Generate hybrid entropy using Bitcoin headers + primary randomness + jitter
+    fn hybrid_entropy(&self, headers: &[Vec<u8>]) -> [u8; 32] {
+        let mut output = [0u8; 32];
+
+        // Start with primary randomness
+        let _ = self.fill_bytes(&mut output);
+
+        // Mix in blockchain entropy
+        let block_entropy = extract_block_entropy(self, headers);
+        for (i, &b) in block_entropy.iter().enumerate().take(32) {
+            output[i] ^= b;
+        }
+
+        // Add final jitter layer
+        let jitter_bytes = self.jitter().to_le_bytes();
 
         for (i, &b) in jitter_bytes.iter().enumerate().take(8) {
-            combined_entropy[i] ^= b;
-            combined_entropy[i + 24] ^= jitter_bytes[7 - i];
+            output[i] ^= b;
+            output[i + 16] ^= jitter_bytes[7 - i];
+        }
+
+        output
+    }
+
+    /// Generate enterprise-grade entropy with additional security measures
+    fn enterprise_entropy(&self, headers: &[Vec<u8>], additional_data: &[u8]) -> [u8; 32] {
+        let mut output = [0u8; 32];
+
+        // Multi-round entropy collection
+        for round in 0..3 {
+            let mut round_output = [0u8; 32];
+
+            // Primary randomness for this round
+            let _ = self.fill_bytes(&mut round_output);
+
+            // Blockchain entropy
+            let block_entropy = extract_block_entropy(self, headers);
+
+            // Additional data incorporation
+            if !additional_data.is_empty() {
+                use std::collections::hash_map::DefaultHasher;
+                use std::hash::{Hash, Hasher};
+
+                let mut hasher = DefaultHasher::new();
+                additional_data.hash(&mut hasher);
+                round.hash(&mut hasher);
+                let add_hash = hasher.finish().to_le_bytes();
+
+                for i in 0..8 {
+                    round_output[i] ^= add_hash[i];
+                    round_output[i + 16] ^= add_hash[7 - i];
+                }
+            }
+
+            // Jitter for this round
+            let jitter_bytes = self.jitter().to_le_bytes();
+
+            // Combine all sources for this round
+            for i in 0..32 {
+                round_output[i] ^= block_entropy[i] ^ jitter_bytes[i % 8];
+            }
+
+            // Accumulate into final output
+            for i in 0..32 {
+                output[i] ^= round_output[i];
+            }
         }
 
-        combined_entropy
+        output
     }
 }
 
-/// Generate fast, cryptographically secure entropy (32 bytes)
-pub fn fast_entropy() -> [u8; 32] {
-    let mut collector = EntropyCollector::new();
-    let mut output = [0u8; 32];
+/// Production source: OS CSPRNG with CPU timing jitter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsJitterSource;
 
-    // Use cryptographically secure OS randomness as primary source
-    if collector.get_os_entropy(&mut output).is_ok() {
-        // Add timing jitter as additional entropy (supplemental only)
-        let jitter = collector.collect_jitter();
-        let jitter_bytes = jitter.to_le_bytes();
+impl EntropySource for OsJitterSource {
+    fn fill_bytes(&self, output: &mut [u8]) -> Result<(), EntropyError> {
+        OsRng.try_fill_bytes(output)
+            .map_err(|e| EntropyError::SystemError(format!("OS RNG failed: {}", e)))
+    }
 
-        // Mix jitter with OS entropy using cryptographically sound mixing
-        for (i, &b) in jitter_bytes.iter().enumerate().take(8) {
-            output[i] ^= b;
-            output[i + 24] ^= jitter_bytes[7 - i];
+    fn jitter(&self) -> u64 {
+        timing_jitter()
+    }
+}
+
+/// Seeded source for reproducible tests. Never use it for real key material.
+///
+/// Output is stable for a given seed and `rand` version; both the random bytes
+/// and the jitter come from the same seeded generator.
+pub struct DeterministicSource {
+    rng: Mutex<StdRng>,
+}
+
+impl DeterministicSource {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
         }
-    } else {
-        // Fallback: pure OS entropy without jitter enhancement
-        let _ = collector.get_os_entropy(&mut output);
     }
+}
 
-    output
+impl EntropySource for DeterministicSource {
+    fn fill_bytes(&self, output: &mut [u8]) -> Result<(), EntropyError> {
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        rng.fill_bytes(output);
+        Ok(())
+    }
+
+    fn jitter(&self) -> u64 {
+        self.rng.lock().unwrap_or_else(|e| e.into_inner()).next_u64()
+    }
 }
 
-/// Generate hybrid entropy using Bitcoin headers + OS randomness + timing jitter
-pub fn hybrid_entropy(headers: &[Vec<u8>]) -> [u8; 32] {
-    let mut collector = EntropyCollector::new();
-    let mut output = [0u8; 32];
+thread_local! {
+    static SOURCE_OVERRIDE: RefCell<Option<Arc<dyn EntropySource>>> = const { RefCell::new(None) };
+}
 
-    // Start with cryptographically secure OS entropy
-    let _ = collector.get_os_entropy(&mut output);
+/// Run `f` with `source` replacing the production source for the free
+/// functions in this module (and `securebuffer_entropy`) on the current thread.
+/// The previous source is restored afterwards, even if `f` panics.
+pub fn with_entropy_source<R>(source: Arc<dyn EntropySource>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Arc<dyn EntropySource>>);
 
-    // Mix in blockchain entropy non-deterministically
-    let block_entropy = collector.extract_block_entropy(headers);
-    for (i, &b) in block_entropy.iter().enumerate().take(32) {
-        output[i] ^= b;
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SOURCE_OVERRIDE.with(|s| *s.borrow_mut() = previous);
+        }
     }
 
-    // Add final timing jitter layer
-    let jitter = collector.collect_jitter();
-    let jitter_bytes = jitter.to_le_bytes();
+    let _restore = Restore(SOURCE_OVERRIDE.with(|s| s.replace(Some(source))));
+    f()
+}
 
-    for (i, &b) in jitter_bytes.iter().enumerate().take(8) {
-        output[i] ^= b;
-        output[i + 16] ^= jitter_bytes[7 - i];
+/// Call `f` with the current thread's source, defaulting to `OsJitterSource`
+fn with_current_source<R>(f: impl FnOnce(&dyn EntropySource) -> R) -> R {
+    match SOURCE_OVERRIDE.with(|s| s.borrow().clone()) {
+        Some(source) => f(source.as_ref()),
+        None => f(&OsJitterSource),
     }
+}
 
-    output
+/// Generate fast, cryptographically secure entropy (32 bytes)
+pub fn fast_entropy() -> [u8; 32] {
+    with_current_source(|source| source.fast_entropy())
+}
+
+/// Generate hybrid entropy using Bitcoin headers + OS randomness + timing jitter
+pub fn hybrid_entropy(headers: &[Vec<u8>]) -> [u8; 32] {
+    with_current_source(|source| source.hybrid_entropy(headers))
 }
 
 /// Generate system fingerprint for entropy enhancement
@@ -209,51 +352,149 @@ fn get_os_entropy(&mut self, output: &mut [u8]) -> Result<(), EntropyError> {
 
 /// Generate enterprise-grade entropy with additional security measures
 pub fn enterprise_entropy(headers: &[Vec<u8>], additional_data: &[u8]) -> [u8; 32] {
-    let mut collector = EntropyCollector::new();
-    let mut output = [0u8; 32];
-    
-    // Multi-round entropy collection
-    for round in 0..3 {
-        let mut round_output = [0u8; 32];
-        
-        // OS entropy with round-specific offset
-        let _ = collector.get_os_entropy(&mut round_output);
-        
-        // Blockchain entropy
-        let block_entropy = collector.extract_block_entropy(headers);
-        
-        // Additional data incorporation
-        if !additional_data.is_empty() {
-            use std::collections::hash_map::DefaultHasher;
-            use std::hash::{Hash, Hasher};
-            
-            let mut hasher = DefaultHasher::new();
-            additional_data.hash(&mut hasher);
-            round.hash(&mut hasher);
-            let add_hash = hasher.finish().to_le_bytes();
-            
-            for i in 0..8 {
-                round_output[i] ^= add_hash[i];
-                round_output[i + 16] ^= add_hash[7 - i];
-            }
-        }
-        
-        // Jitter for this round
-        let round_jitter = collector.collect_jitter();
-        let jitter_bytes = round_jitter.to_le_bytes();
-        
-        // Combine all sources for this round
-        for i in 0..32 {
-            round_output[i] ^= block_entropy[i] ^ jitter_bytes[i % 8];
-        }
-        
-        // Accumulate into final output
-        for i in 0..32 {
-            output[i] ^= round_output[i];
+    with_current_source(|source| source.enterprise_entropy(headers, additional_data))
+}
+
+/// Default number of 32-byte samples drawn by `health_check`
+pub const DEFAULT_HEALTH_SAMPLES: usize = 256;
+
+/// Sample count bounds; the minimum keeps every byte-histogram bin above ~30 expected hits
+pub const MIN_HEALTH_SAMPLES: usize = 256;
+pub const MAX_HEALTH_SAMPLES: usize = 4096;
+
+/// Significance level for the health tests. Deliberately strict so a healthy
+/// source only trips a false alarm about once in ten thousand checks.
+const HEALTH_ALPHA: f64 = 1e-4;
+
+/// Outcome of a single statistical test
+#[derive(Debug, Clone, Serialize)]
+pub struct TestResult {
+    pub name: &'static str,
+    pub passed: bool,
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+/// Result of `health_check`
+#[derive(Debug, Clone, Serialize)]
+pub struct EntropyHealthReport {
+    pub passed: bool,
+    pub samples: usize,
+    /// Shannon entropy of the byte histogram (8.0 is ideal)
+    pub bits_per_byte_estimate: f64,
+    pub tests: Vec<TestResult>,
+}
+
+/// Draw `samples` outputs of `fast_entropy` from the current source (clamped to
+/// `MIN_HEALTH_SAMPLES..=MAX_HEALTH_SAMPLES`) and run the monobit frequency,
+/// runs and byte-histogram chi-square tests over them.
+pub fn health_check(samples: usize) -> EntropyHealthReport {
+    let samples = samples.clamp(MIN_HEALTH_SAMPLES, MAX_HEALTH_SAMPLES);
+    let data: Vec<u8> = with_current_source(|source| {
+        (0..samples).flat_map(|_| source.fast_entropy()).collect()
+    });
+
+    let tests = vec![monobit_test(&data), runs_test(&data), chi_square_test(&data)];
+    EntropyHealthReport {
+        passed: tests.iter().all(|t| t.passed),
+        samples,
+        bits_per_byte_estimate: shannon_bits_per_byte(&data),
+        tests,
+    }
+}
+
+fn bits(data: &[u8]) -> impl Iterator<Item = bool> + '_ {
+    data.iter().flat_map(|&byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+}
+
+fn test_result(name: &'static str, statistic: f64, p_value: f64) -> TestResult {
+    TestResult {
+        name,
+        passed: p_value >= HEALTH_ALPHA,
+        statistic,
+        p_value,
+    }
+}
+
+/// NIST SP 800-22 frequency (monobit) test
+fn monobit_test(data: &[u8]) -> TestResult {
+    let n = (data.len() * 8) as f64;
+    let ones = bits(data).filter(|&b| b).count() as f64;
+    let s_obs = (2.0 * ones - n).abs() / n.sqrt();
+    test_result("monobit", s_obs, erfc(s_obs / std::f64::consts::SQRT_2))
+}
+
+/// NIST SP 800-22 runs test
+fn runs_test(data: &[u8]) -> TestResult {
+    let n = (data.len() * 8) as f64;
+    let pi = bits(data).filter(|&b| b).count() as f64 / n;
+    // The runs test is only meaningful once the frequency prerequisite holds
+    if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+        return test_result("runs", 0.0, 0.0);
+    }
+
+    let mut runs = 1.0;
+    let mut previous = None;
+    for bit in bits(data) {
+        if previous.is_some_and(|p| p != bit) {
+            runs += 1.0;
         }
+        previous = Some(bit);
+    }
+    let expected = 2.0 * n * pi * (1.0 - pi);
+    let p_value = erfc((runs - expected).abs() / (2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi)));
+    test_result("runs", runs, p_value)
+}
+
+/// Chi-square goodness of fit of the byte histogram against uniform (255 dof)
+fn chi_square_test(data: &[u8]) -> TestResult {
+    let counts = byte_histogram(data);
+    let expected = data.len() as f64 / 256.0;
+    let chi2: f64 = counts.iter().map(|&c| (c as f64 - expected).powi(2) / expected).sum();
+
+    // Wilson-Hilferty normal approximation of the chi-square upper tail
+    let k = 255.0;
+    let z = ((chi2 / k).cbrt() - (1.0 - 2.0 / (9.0 * k))) / (2.0 / (9.0 * k)).sqrt();
+    test_result("chi_square", chi2, 0.5 * erfc(z / std::f64::consts::SQRT_2))
+}
+
+fn byte_histogram(data: &[u8]) -> [u64; 256] {
+    let mut counts = [0u64; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    counts
+}
+
+fn shannon_bits_per_byte(data: &[u8]) -> f64 {
+    let total = data.len() as f64;
+    byte_histogram(data)
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Complementary error function (Numerical Recipes erfcc, relative error < 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let r = t * poly.exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
     }
-    
-    output
 }
 
 /// Get CPU temperature for entropy mixing and monitoring
@@ -325,140 +566,6 @@ pub fn get_cpu_temperature() -> Result<f32, EntropyError> {
 
     output
 }
-mod tests {
-    #[allow(unused_imports)]
-    use super::*;
-
-    #[test]
-    fn test_fast_entropy_length_and_variance() {
-        let e1 = fast_entropy();
-        let e2 = fast_entropy();
-        assert_eq!(e1.len(), 32);
-        assert_ne!(e1, e2, "Fast entropy should not repeat immediately");
-    }
-
-    #[test]
-    fn test_hybrid_entropy_differs_with_headers() {
-        let headers1 = vec![vec![0u8; 80]];
-        let headers2 = vec![vec![1u8; 80]];
-        let e1 = hybrid_entropy(&headers1);
-        let e2 = hybrid_entropy(&headers2);
-        assert_eq!(e1.len(), 32);
-        assert_eq!(e2.len(), 32);
-        assert_ne!(e1, e2, "Hybrid entropy must vary with block headers");
-    }
-
-    #[test]
-    fn test_fast_entropy() {
-        let entropy1 = fast_entropy();
-        let entropy2 = fast_entropy();
-
-        // Should produce different outputs
-        assert_ne!(entropy1, entropy2);
-
-        // Should not be all zeros
-        assert_ne!(entropy1, [0u8; 32]);
-    }
-
-    #[test]
-    fn test_hybrid_entropy() {
-        let mock_headers = vec![
-            vec![0u8; 80], // Mock Bitcoin header
-            vec![1u8; 80],
-        ];
-
-        let entropy1 = hybrid_entropy(&mock_headers);
-        let entropy2 = hybrid_entropy(&mock_headers);
-
-        // Should produce different outputs due to jitter
-        assert_ne!(entropy1, entropy2);
-    }
-
-    #[test]
-    fn test_enterprise_entropy() {
-        let mock_headers = vec![vec![0u8; 80]];
-        let additional_data = b"test_data";
-
-        let entropy = enterprise_entropy(&mock_headers, additional_data);
-        assert_ne!(entropy, [0u8; 32]);
-    }
-
-    #[test]
-    fn test_entropy_collector() {
-        let mut collector = EntropyCollector::new();
-
-        // Test jitter collection
-        let jitter1 = collector.collect_jitter();
-        let jitter2 = collector.collect_jitter();
-
-        // Jitter values should be different
-        assert_ne!(jitter1, jitter2);
-
-        // Test OS entropy
-        let mut buffer = [0u8; 16];
-        assert!(collector.get_os_entropy(&mut buffer).is_ok());
-
-        // Should not be all zeros (very unlikely)
-        assert_ne!(buffer, [0u8; 16]);
-    }
-
-    #[test]
-    fn test_entropy_statistical_properties() {
-        let mut ones = 0;
-        let mut bits = 0;
-        let samples = 100;
-
-        // Collect entropy from multiple calls
-        for _ in 0..samples {
-            let entropy = fast_entropy();
-            for byte in entropy.iter() {
-                for bit in 0..8 {
-                    if (byte >> bit) & 1 == 1 {
-                        ones += 1;
-                    }
-                    bits += 1;
-                }
-            }
-        }
-
-        let ratio = ones as f64 / bits as f64;
-
-        // Statistical test: should be close to 0.5 (50% ones, 50% zeros)
-        // Allow some tolerance for randomness
-        assert!(ratio > 0.4 && ratio < 0.6,
-            "Entropy bias detected: ratio={:.3}, expected ~0.5", ratio);
-    }
-
-    #[test]
-    fn test_block_entropy_extraction() {
-        let mut collector = EntropyCollector::new();
-
-        // Test with empty headers (should use last known entropy)
-        let empty_entropy = collector.extract_block_entropy(&[]);
-        assert_ne!(empty_entropy, [0u8; 32]);
-
-        // Test with mock headers
-        let headers = vec![
-            vec![0u8; 80],
-            vec![255u8; 80],
-        ];
-        let block_entropy = collector.extract_block_entropy(&headers);
-        assert_ne!(block_entropy, [0u8; 32]);
-
-        // Different headers should produce different entropy
-        let headers2 = vec![vec![128u8; 80]];
-        let block_entropy2 = collector.extract_block_entropy(&headers2);
-        assert_ne!(block_entropy, block_entropy2);
-    }
-
-    #[test]
-    fn test_entropy_functions_consistency() {
-        // All entropy functions should return exactly 32 bytes
-        assert_eq!(fast_entropy().len(), 32);
-        assert_eq!(hybrid_entropy(&[]).len(), 32);
-        assert_eq!(enterprise_entropy(&[], &[]).len(), 32);
-    }
-}
 
 // FFI bindings for Go integration
 
@@ -582,22 +689,23 @@ pub extern "C" fn get_cpu_temperature_ffi() -> f32 {
 
 /// Generate admin secret as raw bytes (32 bytes)
 pub fn generate_admin_secret_raw() -> [u8; 32] {
-    let mut collector = EntropyCollector::new();
-    let mut secret = [0u8; 32];
+    with_current_source(|source| {
+        let mut secret = [0u8; 32];
 
-    // Use high-quality entropy for admin secrets
-    if collector.get_os_entropy(&mut secret).is_err() {
-        // Fallback to system fingerprint if OS entropy fails
-        secret = system_fingerprint();
-    }
+        // Use high-quality entropy for admin secrets
+        if source.fill_bytes(&mut secret).is_err() {
+            // Fallback to system fingerprint if the source fails
+            secret = system_fingerprint();
+        }
 
-    // Mix with additional entropy sources
-    let jitter = collector.collect_jitter();
-    for (i, &b) in jitter.to_le_bytes().iter().enumerate().take(8) {
-        secret[i] ^= b;
-    }
+        // Mix with additional entropy sources
+        let jitter = source.jitter();
+        for (i, &b) in jitter.to_le_bytes().iter().enumerate().take(8) {
+            secret[i] ^= b;
+        }
 
-    secret
+        secret
+    })
 }
 
 /// Generate admin secret as base64 string
@@ -611,3 +719,228 @@ pub fn generate_admin_secret_hex() -> String {
     let secret = generate_admin_secret_raw();
     hex::encode(secret)
 }
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_fast_entropy_length_and_variance() {
+        let e1 = fast_entropy();
+        let e2 = fast_entropy();
+        assert_eq!(e1.len(), 32);
+        assert_ne!(e1, e2, "Fast entropy should not repeat immediately");
+    }
+
+    #[test]
+    fn test_hybrid_entropy_differs_with_headers() {
+        let headers1 = vec![vec![0u8; 80]];
+        let headers2 = vec![vec![1u8; 80]];
+        let e1 = hybrid_entropy(&headers1);
+        let e2 = hybrid_entropy(&headers2);
+        assert_eq!(e1.len(), 32);
+        assert_eq!(e2.len(), 32);
+        assert_ne!(e1, e2, "Hybrid entropy must vary with block headers");
+    }
+
+    #[test]
+    fn test_fast_entropy() {
+        let entropy1 = fast_entropy();
+        let entropy2 = fast_entropy();
+
+        // Should produce different outputs
+        assert_ne!(entropy1, entropy2);
+
+        // Should not be all zeros
+        assert_ne!(entropy1, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_hybrid_entropy() {
+        let mock_headers = vec![
+            vec![0u8; 80], // Mock Bitcoin header
+            vec![1u8; 80],
+        ];
+
+        let entropy1 = hybrid_entropy(&mock_headers);
+        let entropy2 = hybrid_entropy(&mock_headers);
+
+        // Should produce different outputs due to jitter
+        assert_ne!(entropy1, entropy2);
+    }
+
+    #[test]
+    fn test_enterprise_entropy() {
+        let mock_headers = vec![vec![0u8; 80]];
+        let additional_data = b"test_data";
+
+        let entropy = enterprise_entropy(&mock_headers, additional_data);
+        assert_ne!(entropy, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_entropy_collector() {
+        let mut collector = EntropyCollector::new();
+
+        // Test jitter collection
+        let jitter1 = collector.collect_jitter();
+        let jitter2 = collector.collect_jitter();
+
+        // Jitter values should be different
+        assert_ne!(jitter1, jitter2);
+
+        // Test OS entropy
+        let mut buffer = [0u8; 16];
+        assert!(collector.get_os_entropy(&mut buffer).is_ok());
+
+        // Should not be all zeros (very unlikely)
+        assert_ne!(buffer, [0u8; 16]);
+    }
+
+    #[test]
+    fn test_entropy_statistical_properties() {
+        let mut ones = 0;
+        let mut bits = 0;
+        let samples = 100;
+
+        // Collect entropy from multiple calls
+        for _ in 0..samples {
+            let entropy = fast_entropy();
+            for byte in entropy.iter() {
+                for bit in 0..8 {
+                    if (byte >> bit) & 1 == 1 {
+                        ones += 1;
+                    }
+                    bits += 1;
+                }
+            }
+        }
+
+        let ratio = ones as f64 / bits as f64;
+
+        // Statistical test: should be close to 0.5 (50% ones, 50% zeros)
+        // Allow some tolerance for randomness
+        assert!(ratio > 0.4 && ratio < 0.6,
+            "Entropy bias detected: ratio={:.3}, expected ~0.5", ratio);
+    }
+
+    #[test]
+    fn test_block_entropy_extraction() {
+        // Test with empty headers (should use last known entropy)
+        let empty_entropy = extract_block_entropy(&OsJitterSource, &[]);
+        assert_ne!(empty_entropy, [0u8; 32]);
+
+        // Test with mock headers
+        let headers = vec![
+            vec![0u8; 80],
+            vec![255u8; 80],
+        ];
+        let block_entropy = extract_block_entropy(&OsJitterSource, &headers);
+        assert_ne!(block_entropy, [0u8; 32]);
+
+        // Different headers should produce different entropy
+        let headers2 = vec![vec![128u8; 80]];
+        let block_entropy2 = extract_block_entropy(&OsJitterSource, &headers2);
+        assert_ne!(block_entropy, block_entropy2);
+    }
+
+    #[test]
+    fn test_deterministic_source_is_reproducible() {
+        let run = |seed: u64| {
+            with_entropy_source(Arc::new(DeterministicSource::from_seed(seed)), || {
+                (
+                    fast_entropy(),
+                    hybrid_entropy(&[vec![7u8; 80]]),
+                    enterprise_entropy(&[vec![7u8; 80]], b"receipt"),
+                    generate_admin_secret_hex(),
+                )
+            })
+        };
+
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(43));
+        // Successive draws from one seeded source still differ
+        let (first, second) = with_entropy_source(Arc::new(DeterministicSource::from_seed(1)), || {
+            (fast_entropy(), fast_entropy())
+        });
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_with_entropy_source_restores_production_source() {
+        let seeded = || with_entropy_source(Arc::new(DeterministicSource::from_seed(9)), fast_entropy);
+        let expected = seeded();
+
+        // Nested guards restore the outer source on exit
+        let inner_and_outer = with_entropy_source(Arc::new(DeterministicSource::from_seed(9)), || {
+            let inner = with_entropy_source(Arc::new(DeterministicSource::from_seed(10)), fast_entropy);
+            (inner, fast_entropy())
+        });
+        assert_ne!(inner_and_outer.0, expected);
+        assert_eq!(inner_and_outer.1, expected);
+
+        // Outside any guard the OS source is back in use
+        assert_ne!(fast_entropy(), expected);
+        assert_ne!(fast_entropy(), fast_entropy());
+
+        let result = std::panic::catch_unwind(|| {
+            with_entropy_source(Arc::new(DeterministicSource::from_seed(9)), || panic!("boom"))
+        });
+        assert!(result.is_err());
+        assert_ne!(fast_entropy(), expected);
+    }
+
+    /// Degenerate source standing in for a broken RNG
+    struct ZeroSource;
+
+    impl EntropySource for ZeroSource {
+        fn fill_bytes(&self, output: &mut [u8]) -> Result<(), EntropyError> {
+            output.fill(0);
+            Ok(())
+        }
+
+        fn jitter(&self) -> u64 {
+            0
+        }
+    }
+
+    #[test]
+    fn test_health_check_fails_degenerate_source() {
+        let report = with_entropy_source(Arc::new(ZeroSource), || health_check(DEFAULT_HEALTH_SAMPLES));
+        assert!(!report.passed);
+        assert!(report.tests.iter().all(|t| !t.passed));
+        assert_eq!(report.bits_per_byte_estimate, 0.0);
+    }
+
+    #[test]
+    fn test_health_check_passes_real_and_seeded_entropy() {
+        let report = health_check(DEFAULT_HEALTH_SAMPLES);
+        assert!(report.passed, "{:?}", report);
+        assert!(report.bits_per_byte_estimate > 7.9);
+
+        let seeded = with_entropy_source(Arc::new(DeterministicSource::from_seed(3)), || health_check(1));
+        assert!(seeded.passed, "{:?}", seeded);
+        assert_eq!(seeded.samples, MIN_HEALTH_SAMPLES);
+        let names: Vec<_> = seeded.tests.iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["monobit", "runs", "chi_square"]);
+
+        let json = serde_json::to_value(&seeded).unwrap();
+        assert_eq!(json["tests"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_erfc_reference_values() {
+        assert!((erfc(0.0) - 1.0).abs() < 1e-6);
+        assert!((erfc(1.0) - 0.157_299_207).abs() < 1e-6);
+        assert!((erfc(-1.0) - 1.842_700_793).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_entropy_functions_consistency() {
+        // All entropy functions should return exactly 32 bytes
+        assert_eq!(fast_entropy().len(), 32);
+        assert_eq!(hybrid_entropy(&[]).len(), 32);
+        assert_eq!(enterprise_entropy(&[], &[]).len(), 32);
+    }
+}
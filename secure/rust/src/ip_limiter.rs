@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Per-client-IP concurrency limit
+
+//! Bounds the requests each client IP has in flight, tracking a bounded number of IPs.
+
+use prometheus::IntGauge;
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+struct IpSlot {
+    semaphore: Arc<Semaphore>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct IpSlots {
+    by_ip: HashMap<IpAddr, IpSlot>,
+    // last_used -> ip, least recently used first
+    recency: BTreeMap<u64, IpAddr>,
+    clock: u64,
+}
+
+/// Caps the requests each client IP has in flight with a semaphore per IP. Entries for
+/// idle IPs are evicted least recently used first once max_tracked are held; busy
+/// ones are kept, so the map can briefly exceed the cap under a wide burst.
+pub struct IpConcurrencyLimiter {
+    slots: Mutex<IpSlots>,
+    max_concurrent: u32,
+    max_tracked: usize,
+    tracked: IntGauge,
+}
+
+impl IpConcurrencyLimiter {
+    pub fn new(max_concurrent: u32, max_tracked: usize, tracked: IntGauge) -> Self {
+        IpConcurrencyLimiter { slots: Mutex::new(IpSlots::default()), max_concurrent, max_tracked, tracked }
+    }
+
+    /// One of `ip`'s permits, held until the request (or WebSocket session) ends; None when all are in use
+    pub fn try_acquire(&self, ip: IpAddr) -> Option<OwnedSemaphorePermit> {
+        let mut slots = self.slots.lock().unwrap();
+        let IpSlots { by_ip, recency, clock } = &mut *slots;
+        *clock += 1;
+        let semaphore = match by_ip.get_mut(&ip) {
+            Some(slot) => {
+                recency.remove(&slot.last_used);
+                slot.last_used = *clock;
+                slot.semaphore.clone()
+            }
+            None => {
+                if by_ip.len() >= self.max_tracked {
+                    self.evict_idle(by_ip, recency);
+                }
+                let semaphore = Arc::new(Semaphore::new(self.max_concurrent as usize));
+                by_ip.insert(ip, IpSlot { semaphore: semaphore.clone(), last_used: *clock });
+                semaphore
+            }
+        };
+        recency.insert(*clock, ip);
+        self.tracked.set(by_ip.len() as i64);
+        // Permits are only taken under the lock, so an entry seen idle above stays idle
+        semaphore.try_acquire_owned().ok()
+    }
+
+    fn evict_idle(&self, by_ip: &mut HashMap<IpAddr, IpSlot>, recency: &mut BTreeMap<u64, IpAddr>) {
+        let idle = recency
+            .iter()
+            .find(|(_, ip)| by_ip[*ip].semaphore.available_permits() == self.max_concurrent as usize)
+            .map(|(last_used, ip)| (*last_used, *ip));
+        if let Some((last_used, ip)) = idle {
+            recency.remove(&last_used);
+            by_ip.remove(&ip);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(octets: [u8; 4]) -> IpAddr {
+        IpAddr::from(octets)
+    }
+
+    #[test]
+    fn test_idle_ips_are_evicted_least_recently_used_first() {
+        let limiter = IpConcurrencyLimiter::new(1, 2, IntGauge::new("tracked_ips", "test").unwrap());
+        let a = ip([192, 0, 2, 1]);
+        let b = ip([192, 0, 2, 2]);
+        let c = ip([192, 0, 2, 3]);
+
+        drop(limiter.try_acquire(a).unwrap());
+        let busy = limiter.try_acquire(b).unwrap();
+        drop(limiter.try_acquire(a).unwrap());
+        // b is least recently used but still has a request in flight, so a goes instead
+        drop(limiter.try_acquire(c).unwrap());
+        {
+            let slots = limiter.slots.lock().unwrap();
+            assert!(!slots.by_ip.contains_key(&a));
+            assert!(slots.by_ip.contains_key(&b));
+            assert!(slots.by_ip.contains_key(&c));
+        }
+        assert_eq!(limiter.tracked.get(), 2);
+        assert!(limiter.try_acquire(b).is_none());
+        drop(busy);
+        assert!(limiter.try_acquire(b).is_some());
+    }
+}
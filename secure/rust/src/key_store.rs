@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - API key storage shared by the API servers
+
+//! API key records and their storage backends.
+//!
+//! Keys are always stored under `digest_key` of the raw key, so a leaked database does not
+//! leak usable API keys. The API server issues and revokes keys through a `KeyStore`; the
+//! enterprise server reads the same `api_keys` table to look up a key's tier.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Schema of the `api_keys` table
+#[cfg(feature = "rusqlite")]
+pub(crate) const API_KEYS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS api_keys (
+    hash TEXT PRIMARY KEY,
+    tier TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    request_count INTEGER NOT NULL,
+    rate_limit_remaining INTEGER NOT NULL,
+    revoked_at TEXT
+)";
+
+#[cfg(feature = "rusqlite")]
+const KEY_COLUMNS: &str = "hash, tier, created_at, expires_at, request_count, rate_limit_remaining, revoked_at";
+
+/// Digest an API key is stored under: HMAC-SHA256 with the pepper, or SHA-256 without one
+pub fn digest_key(key: &str, pepper: &[u8]) -> String {
+    use hmac::{Hmac, Mac};
+    if pepper.is_empty() {
+        return hex::encode(Sha256::digest(key.as_bytes()));
+    }
+    let mut mac = Hmac::<Sha256>::new_from_slice(pepper).expect("HMAC accepts keys of any length");
+    mac.update(key.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct KeyDetails {
+    pub hash: String,
+    pub tier: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub request_count: u64,
+    pub rate_limit_remaining: u32,
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+pub trait KeyStore: Send + Sync {
+    fn put(&self, details: &KeyDetails) -> Result<(), String>;
+    fn get(&self, hash: &str) -> Result<Option<KeyDetails>, String>;
+    /// Mark a key revoked; false when no key has this hash
+    fn revoke(&self, hash: &str, at: DateTime<Utc>) -> Result<bool, String>;
+    fn list(&self, tier: Option<&str>) -> Result<Vec<KeyDetails>, String>;
+    /// Whether keys survive a restart; false for the in-memory fallback
+    fn is_persistent(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryKeyStore {
+    keys: Mutex<HashMap<String, KeyDetails>>,
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn put(&self, details: &KeyDetails) -> Result<(), String> {
+        let mut keys = self.keys.lock().map_err(|_| "Key store lock poisoned".to_string())?;
+        keys.insert(details.hash.clone(), details.clone());
+        Ok(())
+    }
+
+    fn get(&self, hash: &str) -> Result<Option<KeyDetails>, String> {
+        let keys = self.keys.lock().map_err(|_| "Key store lock poisoned".to_string())?;
+        Ok(keys.get(hash).cloned())
+    }
+
+    fn revoke(&self, hash: &str, at: DateTime<Utc>) -> Result<bool, String> {
+        let mut keys = self.keys.lock().map_err(|_| "Key store lock poisoned".to_string())?;
+        match keys.get_mut(hash) {
+            Some(details) => {
+                details.revoked_at.get_or_insert(at);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn list(&self, tier: Option<&str>) -> Result<Vec<KeyDetails>, String> {
+        let keys = self.keys.lock().map_err(|_| "Key store lock poisoned".to_string())?;
+        let mut listed: Vec<KeyDetails> = keys
+            .values()
+            .filter(|d| tier.is_none() || tier == Some(d.tier.as_str()))
+            .cloned()
+            .collect();
+        listed.sort_by_key(|d| d.created_at);
+        Ok(listed)
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+pub struct SqliteKeyStore {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "rusqlite")]
+impl SqliteKeyStore {
+    /// Open or create the key table at `path`; a `sqlite://` prefix is accepted
+    pub fn open(path: &str) -> Result<Self, String> {
+        let path = path.strip_prefix("sqlite://").unwrap_or(path);
+        let conn = rusqlite::Connection::open(path).map_err(|e| format!("Failed to open key store {}: {}", path, e))?;
+        conn.execute_batch(API_KEYS_SCHEMA)
+            .map_err(|e| format!("Failed to initialise key store: {}", e))?;
+        Ok(SqliteKeyStore { conn: Mutex::new(conn) })
+    }
+
+    fn row_to_details(row: &rusqlite::Row<'_>) -> rusqlite::Result<KeyDetails> {
+        Ok(KeyDetails {
+            hash: row.get(0)?,
+            tier: row.get(1)?,
+            created_at: row.get(2)?,
+            expires_at: row.get(3)?,
+            request_count: row.get::<_, i64>(4)? as u64,
+            rate_limit_remaining: row.get(5)?,
+            revoked_at: row.get(6)?,
+        })
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl KeyStore for SqliteKeyStore {
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    fn put(&self, details: &KeyDetails) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|_| "Key store lock poisoned".to_string())?;
+        conn.execute(
+            &format!("INSERT OR REPLACE INTO api_keys ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)", KEY_COLUMNS),
+            rusqlite::params![
+                details.hash,
+                details.tier,
+                details.created_at,
+                details.expires_at,
+                details.request_count as i64,
+                details.rate_limit_remaining,
+                details.revoked_at,
+            ],
+        )
+        .map_err(|e| format!("Failed to store key: {}", e))?;
+        Ok(())
+    }
+
+    fn get(&self, hash: &str) -> Result<Option<KeyDetails>, String> {
+        use rusqlite::OptionalExtension;
+        let conn = self.conn.lock().map_err(|_| "Key store lock poisoned".to_string())?;
+        conn.query_row(
+            &format!("SELECT {} FROM api_keys WHERE hash = ?1", KEY_COLUMNS),
+            [hash],
+            Self::row_to_details,
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load key: {}", e))
+    }
+
+    fn revoke(&self, hash: &str, at: DateTime<Utc>) -> Result<bool, String> {
+        let conn = self.conn.lock().map_err(|_| "Key store lock poisoned".to_string())?;
+        let updated = conn
+            .execute(
+                "UPDATE api_keys SET revoked_at = COALESCE(revoked_at, ?2) WHERE hash = ?1",
+                rusqlite::params![hash, at],
+            )
+            .map_err(|e| format!("Failed to revoke key: {}", e))?;
+        Ok(updated > 0)
+    }
+
+    fn list(&self, tier: Option<&str>) -> Result<Vec<KeyDetails>, String> {
+        let conn = self.conn.lock().map_err(|_| "Key store lock poisoned".to_string())?;
+        let sql = format!(
+            "SELECT {} FROM api_keys WHERE ?1 IS NULL OR tier = ?1 ORDER BY created_at",
+            KEY_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| format!("Failed to list keys: {}", e))?;
+        let rows = stmt
+            .query_map([tier], Self::row_to_details)
+            .map_err(|e| format!("Failed to list keys: {}", e))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| format!("Failed to list keys: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_is_plain_sha256_without_pepper_and_keyed_with_one() {
+        let plain = digest_key("key_abc", b"");
+        assert_eq!(plain, hex::encode(Sha256::digest(b"key_abc")));
+        assert_ne!(digest_key("key_abc", b"pepper"), plain);
+        assert_eq!(digest_key("key_abc", b"pepper"), digest_key("key_abc", b"pepper"));
+    }
+}
@@ -6,15 +6,21 @@
 use std::io;
 use std::ffi::{CStr, c_char, CString};
 use std::os::raw::{c_void, c_int};
-use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 // Import the bloom filter module and its traits
 pub mod bloom_filter;
-use bloom_filter::{BlockchainHash, TransactionId, UniversalBloomFilter, NetworkConfig, BloomConfig, BlockData};
+use bloom_filter::{TransactionId, UniversalBloomFilter, NetworkConfig, BloomConfig, BlockData};
 
 // Storage verification module (optional IPFS support)
 pub mod storage_verifier;
 
+// SHA-256 Merkle trees for storage commitments
+pub mod merkle;
+
+// Byte-range fetchers for IPFS, Arweave and Filecoin
+#[cfg(feature = "ipfs")]
+pub mod storage_fetcher;
+
 // Web server module for REST API
 #[cfg(feature = "web-server")]
 pub mod web_server;
@@ -22,18 +28,67 @@
 // Enterprise web server module for subscription-based storage validation
 pub mod enterprise_web_server;
 
+// API key digests and storage shared by the API servers
+#[cfg(feature = "chrono")]
+pub mod key_store;
+
+// Per-key request and byte counts, flushed to a database in batches
+#[cfg(all(feature = "chrono", feature = "tokio-util"))]
+pub mod usage;
+
+// Restart-on-failure supervision and health of background tasks
+#[cfg(all(feature = "chrono", feature = "tokio-util"))]
+pub mod tasks;
+
+// Per-client-IP in-flight request limit
+pub mod ip_limiter;
+
 #[cfg(unix)]
 extern crate libc;
 
 #[cfg(windows)]
 extern crate winapi;
 
+// Structured audit events for SecureBuffer
+pub mod audit;
+use audit::{AuditEvent, AuditEventKind, AuditLog, AuditSink};
+
+// Streaming io::Write / io::Read adapters for SecureBuffer
+pub mod stream;
+pub use stream::{SecureBufferReader, SecureBufferWriter, StreamOverflow};
+
 // Entropy module for hybrid Bitcoin + OS + jitter randomness
 pub mod entropy;
 
 // SecureBuffer entropy integration
 pub mod securebuffer_entropy;
 
+// Injectable time source for expiry and rate-limit windows
+pub mod clock;
+
+// Circuit breaker for upstream chain calls
+pub mod circuit_breaker;
+
+// Versioned admin secrets with rotation grace and sealed persistence
+pub mod admin_secret;
+
+// KEK-sealed export and import of SecureBuffer contents
+pub mod seal;
+
+// Prometheus sink for TurboValidator metrics
+pub mod validator_metrics;
+
+// bitcoind ZMQ block/tx subscription
+#[cfg(feature = "zmq")]
+pub mod zmq_listener;
+
+// Pooled rustls client channels with liveness probes and a metrics endpoint
+pub mod secure_channel_improved;
+
+// Signed, retried webhook delivery to customer endpoints
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
+
 // High-performance Universal Bloom Filter
 
 mod memory {
@@ -133,6 +188,97 @@ pub unsafe fn explicit_bzero(ptr: *mut u8, len: usize) {
             }
         }
     }
+
+    #[cfg(unix)]
+    pub fn page_size() -> usize {
+        unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+    }
+
+    /// Map `size` read-write bytes whose first and last `page` bytes are inaccessible
+    #[cfg(unix)]
+    pub unsafe fn map_guarded(size: usize, page: usize) -> Result<*mut u8, io::Error> {
+        unsafe {
+            let base = libc::mmap(
+                std::ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            if base == libc::MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+            let base = base as *mut u8;
+            if libc::mprotect(base as *mut libc::c_void, page, libc::PROT_NONE) != 0
+                || libc::mprotect(base.add(size - page) as *mut libc::c_void, page, libc::PROT_NONE) != 0
+            {
+                let err = io::Error::last_os_error();
+                libc::munmap(base as *mut libc::c_void, size);
+                return Err(err);
+            }
+            Ok(base)
+        }
+    }
+
+    #[cfg(unix)]
+    pub unsafe fn unmap_guarded(base: *mut u8, size: usize) {
+        unsafe {
+            libc::munmap(base as *mut libc::c_void, size);
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn page_size() -> usize {
+        unsafe {
+            let mut info: winapi::um::sysinfoapi::SYSTEM_INFO = std::mem::zeroed();
+            winapi::um::sysinfoapi::GetSystemInfo(&mut info);
+            info.dwPageSize as usize
+        }
+    }
+
+    /// Commit `size` read-write bytes whose first and last `page` bytes are PAGE_NOACCESS
+    #[cfg(windows)]
+    pub unsafe fn map_guarded(size: usize, page: usize) -> Result<*mut u8, io::Error> {
+        use winapi::um::memoryapi::{VirtualAlloc, VirtualFree, VirtualProtect};
+        use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_NOACCESS, PAGE_READWRITE};
+        unsafe {
+            let base = VirtualAlloc(std::ptr::null_mut(), size, MEM_COMMIT | MEM_RESERVE, PAGE_READWRITE) as *mut u8;
+            if base.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let mut previous = 0;
+            if VirtualProtect(base as *mut _, page, PAGE_NOACCESS, &mut previous) == 0
+                || VirtualProtect(base.add(size - page) as *mut _, page, PAGE_NOACCESS, &mut previous) == 0
+            {
+                let err = io::Error::last_os_error();
+                VirtualFree(base as *mut _, 0, MEM_RELEASE);
+                return Err(err);
+            }
+            Ok(base)
+        }
+    }
+
+    #[cfg(windows)]
+    pub unsafe fn unmap_guarded(base: *mut u8, _size: usize) {
+        unsafe {
+            // MEM_RELEASE frees the whole reservation and requires a size of zero
+            winapi::um::memoryapi::VirtualFree(base as *mut _, 0, winapi::um::winnt::MEM_RELEASE);
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn page_size() -> usize {
+        4096
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub unsafe fn map_guarded(_size: usize, _page: usize) -> Result<*mut u8, io::Error> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "guard pages are not supported on this platform"))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub unsafe fn unmap_guarded(_base: *mut u8, _size: usize) {}
 }
 
 #[derive(Error, Debug)]
@@ -149,6 +295,37 @@ pub enum SecureBufferError {
     InvalidState,
 }
 
+/// Default capacity multiplier used by `SecureBuffer::append`
+pub const DEFAULT_GROWTH_FACTOR: f64 = 2.0;
+
+/// Length of each canary written around the data when `SecureBufferOptions::canary` is set
+pub const CANARY_LEN: usize = 16;
+
+// Room reserved before the data for the leading canary, which keeps the data 32-byte aligned
+const CANARY_PAD: usize = 32;
+
+/// Allocation hardening for `SecureBuffer::with_options`. The default is the plain
+/// aligned heap allocation `SecureBuffer::new` uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SecureBufferOptions {
+    /// Map the data with an inaccessible page on either side (PROT_NONE / PAGE_NOACCESS),
+    /// ending right at the trailing one so an overrun faults instead of reading the heap
+    pub guard_pages: bool,
+    /// Write a random 16-byte canary directly before and after the data; `is_tampered`,
+    /// `integrity_check` and `destroy` report a canary that no longer matches
+    pub canary: bool,
+}
+
+impl SecureBufferOptions {
+    fn front(&self) -> usize {
+        if self.canary { CANARY_PAD } else { 0 }
+    }
+
+    fn tail(&self) -> usize {
+        if self.canary { CANARY_LEN } else { 0 }
+    }
+}
+
 /// Thread-safe secure buffer with memory locking and hardened zeroization
 pub struct SecureBuffer {
     data: *mut u8,
@@ -156,41 +333,299 @@ pub struct SecureBuffer {
     length: usize,
     is_valid: AtomicBool,
     is_locked: AtomicBool,
+    growth_factor: f64,
+    tamper_guard: Option<TamperGuard>,
+    audit: std::sync::Mutex<AuditLog>,
+    options: SecureBufferOptions,
+    // Written before and after the data of every region when options.canary is set
+    canary: Option<[u8; CANARY_LEN]>,
+}
+
+/// Keyed SipHash checksum over the buffer content, refreshed by every mutation.
+/// The random keys live in the `RandomState`, so corrupting the content without
+/// going through the API cannot produce a matching checksum.
+struct TamperGuard {
+    keys: std::collections::hash_map::RandomState,
+    checksum: u64,
+}
+
+impl TamperGuard {
+    fn new(content: &[u8]) -> Self {
+        let mut guard = TamperGuard {
+            keys: std::collections::hash_map::RandomState::new(),
+            checksum: 0,
+        };
+        guard.checksum = guard.compute(content);
+        guard
+    }
+
+    fn compute(&self, content: &[u8]) -> u64 {
+        use std::hash::{BuildHasher, Hasher};
+        let mut hasher = self.keys.build_hasher();
+        hasher.write_usize(content.len());
+        hasher.write(content);
+        hasher.finish()
+    }
+}
+
+/// Size of the allocation backing `capacity` data bytes, and the offset of the data in it.
+/// With guard pages the data is placed at the end of the accessible pages, so the first
+/// byte past it (or past its trailing canary) sits on the trailing guard page.
+fn region_geometry(capacity: usize, options: SecureBufferOptions) -> Result<(usize, usize), String> {
+    let invalid = || "Invalid layout for allocation".to_string();
+    let span = options.front()
+        .checked_add(capacity)
+        .and_then(|n| n.checked_add(options.tail()))
+        .ok_or_else(invalid)?;
+    if !options.guard_pages {
+        return Ok((span, options.front()));
+    }
+    let page = memory::page_size();
+    let inner = span.checked_add(page - 1).ok_or_else(invalid)? / page * page;
+    let size = inner.checked_add(2 * page).ok_or_else(invalid)?;
+    Ok((size, page + inner - options.tail() - capacity))
+}
+
+/// Allocate a zeroed region for `capacity` bytes and attempt to lock it: 32-byte aligned
+/// from the global allocator, or between guard pages when `options.guard_pages` is set.
+/// Returns the data pointer and whether the lock succeeded.
+fn allocate_region(capacity: usize, options: SecureBufferOptions) -> Result<(*mut u8, bool), String> {
+    let (size, offset) = region_geometry(capacity, options)?;
+    let base = if options.guard_pages {
+        unsafe { memory::map_guarded(size, memory::page_size()) }
+            .map_err(|e| format!("Failed to map guarded memory: {}", e))?
+    } else {
+        let layout = Layout::from_size_align(size, 32)
+            .map_err(|_| "Invalid layout for allocation".to_string())?;
+        let base = unsafe { alloc(layout) };
+        if base.is_null() {
+            return Err("Failed to allocate memory".to_string());
+        }
+        base
+    };
+
+    let data = unsafe { base.add(offset) };
+    unsafe {
+        memory::explicit_bzero(data, capacity);
+    }
+
+    // Attempt to lock memory (non-fatal if it fails)
+    let is_locked = unsafe { memory::lock_memory(data, capacity) }.is_ok();
+    Ok((data, is_locked))
+}
+
+/// Zeroize, unlock and free a region obtained from `allocate_region`.
+///
+/// # Safety
+///
+/// `data` must have been returned by `allocate_region(capacity, options)` and not freed yet.
+unsafe fn release_region(data: *mut u8, capacity: usize, locked: bool, options: SecureBufferOptions) {
+    memory::explicit_bzero(data, capacity);
+    #[cfg(test)]
+    test_hooks::record_release(data, capacity);
+    if locked {
+        let _ = memory::unlock_memory(data, capacity);
+    }
+    // Geometry already succeeded for this capacity when the region was allocated
+    let (size, offset) = region_geometry(capacity, options).unwrap_or((capacity, 0));
+    let base = data.sub(offset);
+    if options.guard_pages {
+        memory::unmap_guarded(base, size);
+    } else {
+        dealloc(base, Layout::from_size_align_unchecked(size, 32));
+    }
+}
+
+/// Compare two byte slices in constant time.
+///
+/// Timing model: the loop always runs over the longer of the two inputs and
+/// never exits early on a content mismatch. A length mismatch is folded into
+/// the result instead of returning immediately, so only the lengths (which are
+/// not treated as secret) influence how long the comparison takes.
+pub fn ct_eq_bytes(a: &[u8], b: &[u8]) -> bool {
+    let len = std::cmp::max(a.len(), b.len());
+    let mut diff = (a.len() ^ b.len()) as u64;
+    for i in 0..len {
+        let x = if i < a.len() { a[i] } else { 0 };
+        let y = if i < b.len() { b[i] } else { 0 };
+        diff |= (x ^ y) as u64;
+    }
+    std::hint::black_box(diff) == 0
+}
+
+/// Test-only observation of released regions, checked before deallocation
+#[cfg(test)]
+mod test_hooks {
+    use std::cell::RefCell;
+
+    thread_local! {
+        static RELEASES: RefCell<Vec<(usize, bool)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Record the address of a released region and whether it was fully zeroized
+    pub fn record_release(data: *const u8, capacity: usize) {
+        let zeroed = unsafe { std::slice::from_raw_parts(data, capacity) }.iter().all(|&b| b == 0);
+        RELEASES.with(|r| r.borrow_mut().push((data as usize, zeroed)));
+    }
+
+    pub fn take_releases() -> Vec<(usize, bool)> {
+        RELEASES.with(|r| std::mem::take(&mut *r.borrow_mut()))
+    }
 }
 
 impl SecureBuffer {
     /// Create a new secure buffer with the specified capacity
     pub fn new(capacity: usize) -> Result<Self, String> {
+        Self::with_options(capacity, SecureBufferOptions::default())
+    }
+
+    /// Create a secure buffer whose allocation is hardened as `options` asks.
+    /// Capacity and length behave exactly as for `new`; guards and canaries sit outside them.
+    pub fn with_options(capacity: usize, options: SecureBufferOptions) -> Result<Self, String> {
         if capacity == 0 {
             return Err("Capacity must be greater than 0".to_string());
         }
-        
-        // Use aligned allocation for better security and performance
-        let layout = Layout::from_size_align(capacity, 32)
-            .map_err(|_| "Invalid layout for allocation".to_string())?;
-        
-        let data = unsafe { alloc(layout) };
-        if data.is_null() {
-            return Err("Failed to allocate memory".to_string());
+
+        // Aligned, zeroed and (best-effort) locked allocation
+        let (data, is_locked) = allocate_region(capacity, options)?;
+        let canary = options.canary.then(|| {
+            let mut canary = [0u8; CANARY_LEN];
+            rand::RngCore::fill_bytes(&mut rand::rngs::OsRng, &mut canary);
+            canary
+        });
+
+        let buffer = SecureBuffer {
+            data,
+            capacity,
+            length: 0,
+            is_valid: AtomicBool::new(true),
+            is_locked: AtomicBool::new(is_locked),
+            growth_factor: DEFAULT_GROWTH_FACTOR,
+            tamper_guard: None,
+            audit: std::sync::Mutex::new(AuditLog::default()),
+            options,
+            canary,
+        };
+        buffer.write_canaries();
+        buffer.audit(AuditEventKind::Created, capacity);
+        if !is_locked {
+            buffer.audit(AuditEventKind::LockFailed, capacity);
+        }
+
+        Ok(buffer)
+    }
+
+    /// Move the content into a new locked, aligned region of `new_capacity` bytes.
+    /// Shrinking truncates the content; the old region is zeroized, unlocked and freed.
+    pub fn resize(&mut self, new_capacity: usize) -> Result<(), String> {
+        if !self.is_valid.load(Ordering::SeqCst) {
+            return Err("Buffer is not valid".to_string());
+        }
+        if new_capacity == 0 {
+            return Err("Capacity must be greater than 0".to_string());
         }
 
-        // Immediately zero the allocated memory
+        let (new_data, new_locked) = allocate_region(new_capacity, self.options)?;
+        let keep = std::cmp::min(self.length, new_capacity);
         unsafe {
-            memory::explicit_bzero(data, capacity);
+            std::ptr::copy_nonoverlapping(self.data, new_data, keep);
+            release_region(self.data, self.capacity, self.is_locked.load(Ordering::SeqCst), self.options);
         }
 
-    // Attempt to lock memory (non-fatal if it fails)
-    let is_locked = unsafe { memory::lock_memory(data, capacity) }.is_ok();
+        self.data = new_data;
+        self.capacity = new_capacity;
+        self.length = keep;
+        self.is_locked.store(new_locked, Ordering::SeqCst);
+        self.write_canaries();
+        self.refresh_tamper_checksum();
+        if !new_locked {
+            self.audit(AuditEventKind::LockFailed, new_capacity);
+        }
+        Ok(())
+    }
 
-    let buffer = SecureBuffer {
-        data,
-        capacity,
-        length: 0,
-        is_valid: AtomicBool::new(true),
-        is_locked: AtomicBool::new(is_locked),
-    };
+    /// Append data after the current content, growing by the growth factor when needed
+    pub fn append(&mut self, data: &[u8]) -> Result<(), String> {
+        if !self.is_valid.load(Ordering::SeqCst) {
+            return Err("Buffer is not valid".to_string());
+        }
+
+        let required = self.length.checked_add(data.len())
+            .ok_or_else(|| "Data exceeds maximum buffer size".to_string())?;
+        if required > self.capacity {
+            self.resize(self.grown_capacity(required))?;
+        }
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.data.add(self.length), data.len());
+        }
+        self.length = required;
+        self.refresh_tamper_checksum();
+        self.audit(AuditEventKind::Write, data.len());
+        Ok(())
+    }
+
+    /// Capacity to grow to so that `required` bytes fit, honouring the growth factor
+    fn grown_capacity(&self, required: usize) -> usize {
+        let grown = (self.capacity as f64 * self.growth_factor).ceil() as usize;
+        std::cmp::max(required, grown)
+    }
+
+    /// Start a streaming write that appends after the current content.
+    /// `policy` decides whether chunks that exceed the capacity grow the buffer or fail.
+    pub fn begin_stream(&mut self, policy: StreamOverflow) -> Result<SecureBufferWriter<'_>, String> {
+        if !self.is_valid.load(Ordering::SeqCst) {
+            return Err("Buffer is not valid".to_string());
+        }
+        Ok(SecureBufferWriter::new(self, policy))
+    }
+
+    /// Read the current content through `std::io::Read` without copying it out first
+    pub fn stream_reader(&self) -> Result<SecureBufferReader<'_>, String> {
+        if !self.is_valid.load(Ordering::SeqCst) {
+            return Err("Buffer is not valid".to_string());
+        }
+        Ok(SecureBufferReader::new(self))
+    }
+
+    /// Compare contents with another buffer in constant time (see `ct_eq_bytes`).
+    /// Invalid buffers never compare equal.
+    pub fn ct_eq(&self, other: &SecureBuffer) -> bool {
+        if !self.is_valid() || !other.is_valid() {
+            return false;
+        }
+        ct_eq_bytes(self.content(), other.content())
+    }
+
+    /// Compare contents with a byte slice in constant time (see `ct_eq_bytes`)
+    pub fn ct_eq_slice(&self, other: &[u8]) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+        ct_eq_bytes(self.content(), other)
+    }
+
+    /// Current content, including the empty case `as_slice` reports as an error
+    fn content(&self) -> &[u8] {
+        if self.data.is_null() || self.length == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(self.data, self.length) }
+        }
+    }
 
-    Ok(buffer)
+    /// Set the capacity multiplier used when `append` needs to grow (must be > 1.0)
+    pub fn set_growth_factor(&mut self, factor: f64) -> Result<(), String> {
+        if !factor.is_finite() || factor <= 1.0 {
+            return Err("Growth factor must be a finite value greater than 1.0".to_string());
+        }
+        self.growth_factor = factor;
+        Ok(())
+    }
+
+    /// Get the capacity multiplier used by `append`
+    pub fn growth_factor(&self) -> f64 {
+        self.growth_factor
     }
 
     /// Write data to the buffer, replacing any existing content
@@ -211,6 +646,31 @@ pub fn write(&mut self, data: &[u8]) -> Result<(), String> {
         }
         
         self.length = data.len();
+        self.refresh_tamper_checksum();
+        self.audit(AuditEventKind::Write, data.len());
+        Ok(())
+    }
+
+    /// Overwrite `data.len()` bytes starting at `offset`, extending the content if needed.
+    /// `offset` may not be past the current length, and the write must fit the capacity.
+    pub fn write_at(&mut self, offset: usize, data: &[u8]) -> Result<(), String> {
+        if !self.is_valid.load(Ordering::SeqCst) {
+            return Err("Buffer is not valid".to_string());
+        }
+        if offset > self.length {
+            return Err("Offset is past the end of the content".to_string());
+        }
+        let end = offset.checked_add(data.len())
+            .filter(|&end| end <= self.capacity)
+            .ok_or_else(|| "Data exceeds buffer capacity".to_string())?;
+
+        unsafe {
+            // `copy` tolerates `data` aliasing the buffer's own storage
+            std::ptr::copy(data.as_ptr(), self.data.add(offset), data.len());
+        }
+        self.length = std::cmp::max(self.length, end);
+        self.refresh_tamper_checksum();
+        self.audit(AuditEventKind::Write, data.len());
         Ok(())
     }
 
@@ -224,6 +684,7 @@ pub fn read(&self, buf: &mut [u8]) -> Result<usize, String> {
         unsafe {
             std::ptr::copy_nonoverlapping(self.data, buf.as_mut_ptr(), copy_len);
         }
+        self.audit(AuditEventKind::Read, copy_len);
         
         Ok(copy_len)
     }
@@ -238,8 +699,9 @@ pub fn as_slice(&self) -> Result<&[u8], String> {
         if self.length == 0 {
             return Err("Empty".to_string());
         }
-        
-    unsafe { Ok(std::slice::from_raw_parts(self.data, self.length)) }
+
+        self.audit(AuditEventKind::Read, self.length);
+        unsafe { Ok(std::slice::from_raw_parts(self.data, self.length)) }
     }
 
     /// Get the current length of data in the buffer (thread-safe)
@@ -267,6 +729,8 @@ pub fn clear(&mut self) {
                 memory::explicit_bzero(self.data, self.capacity);
             }
             self.length = 0;
+            self.refresh_tamper_checksum();
+            self.audit(AuditEventKind::Zeroize, self.capacity);
         }
     }
 
@@ -296,27 +760,56 @@ pub fn enable_hardware_protection(&mut self) -> Result<(), String> {
         }
     }
 
-    /// Enable audit logging for security events
+    /// Enable audit logging for security events (on by default)
     pub fn enable_audit_logging(&mut self) -> Result<(), String> {
-        // Implementation for security audit logging
         if self.is_valid.load(Ordering::SeqCst) {
-            // Log security event: audit logging enabled
+            self.audit_log().set_enabled(true);
             Ok(())
         } else {
             Err("Buffer is invalid".to_string())
         }
     }
 
-    /// Disable audit logging
+    /// Disable audit logging; already recorded events are kept
     pub fn disable_audit_logging(&mut self) {
-        // Implementation for disabling audit logging
-        // Log security event: audit logging disabled
+        self.audit_log().set_enabled(false);
     }
 
     /// Check if audit logging is enabled
     pub fn is_audit_logging_enabled(&self) -> bool {
-        // Implementation to check audit logging status
-        self.is_valid.load(Ordering::SeqCst)
+        self.is_valid.load(Ordering::SeqCst) && self.audit_log().is_enabled()
+    }
+
+    /// Set how many audit events are retained (oldest are evicted first)
+    pub fn set_audit_capacity(&mut self, capacity: usize) {
+        self.audit_log().set_capacity(capacity);
+    }
+
+    /// Forward every future audit event to `sink`
+    pub fn set_audit_sink(&mut self, sink: AuditSink) {
+        self.audit_log().set_sink(Some(sink));
+    }
+
+    /// Remove the audit sink
+    pub fn clear_audit_sink(&mut self) {
+        self.audit_log().set_sink(None);
+    }
+
+    /// Snapshot of the retained audit events, oldest first
+    pub fn audit_events(&self) -> Vec<AuditEvent> {
+        self.audit_log().events()
+    }
+
+    fn audit_log(&self) -> std::sync::MutexGuard<'_, AuditLog> {
+        self.audit.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn audit(&self, kind: AuditEventKind, bytes_affected: usize) {
+        // The guard is released at the end of this statement, before the sink runs
+        let delivery = self.audit_log().record(kind, bytes_affected);
+        if let Some(delivery) = delivery {
+            delivery.deliver();
+        }
     }
 
     /// Bind buffer to hardware security features
@@ -335,20 +828,75 @@ pub fn is_hardware_backed(&self) -> bool {
         self.is_valid.load(Ordering::SeqCst) && self.is_locked.load(Ordering::SeqCst)
     }
 
-    /// Enable tamper detection mechanisms
+    /// Enable tamper detection: checksum the content with a freshly keyed SipHash
+    /// and keep it current across every write, append, resize, clear and zeroize
     pub fn enable_tamper_detection(&mut self) -> Result<(), String> {
-        // Implementation for tamper detection
         if self.is_valid.load(Ordering::SeqCst) {
+            self.tamper_guard = Some(TamperGuard::new(self.content()));
             Ok(())
         } else {
             Err("Buffer is invalid".to_string())
         }
     }
 
-    /// Check if buffer has been tampered with
+    /// Check if tamper detection is enabled
+    pub fn is_tamper_detection_enabled(&self) -> bool {
+        self.tamper_guard.is_some()
+    }
+
+    /// Check if buffer has been tampered with, i.e. is invalid, a canary was overwritten,
+    /// or its content no longer matches the checksum recorded by the last API mutation
     pub fn is_tampered(&self) -> bool {
-        // Implementation for tamper detection check
-        !self.is_valid.load(Ordering::SeqCst)
+        if !self.is_valid.load(Ordering::SeqCst) || !self.canaries_intact() {
+            return true;
+        }
+        match &self.tamper_guard {
+            Some(guard) => guard.compute(self.content()) != guard.checksum,
+            None => false,
+        }
+    }
+
+    /// Allocation hardening this buffer was created with
+    pub fn options(&self) -> SecureBufferOptions {
+        self.options
+    }
+
+    /// Leading canary ends where the data starts; the trailing one starts at the capacity
+    fn canary_slots(&self) -> Option<([u8; CANARY_LEN], *mut u8, *mut u8)> {
+        let canary = self.canary?;
+        if self.data.is_null() {
+            return None;
+        }
+        unsafe { Some((canary, self.data.sub(CANARY_LEN), self.data.add(self.capacity))) }
+    }
+
+    fn write_canaries(&self) {
+        if let Some((canary, before, after)) = self.canary_slots() {
+            unsafe {
+                std::ptr::copy_nonoverlapping(canary.as_ptr(), before, CANARY_LEN);
+                std::ptr::copy_nonoverlapping(canary.as_ptr(), after, CANARY_LEN);
+            }
+        }
+    }
+
+    /// Both canaries still hold their value (vacuously true without canaries)
+    fn canaries_intact(&self) -> bool {
+        match self.canary_slots() {
+            Some((canary, before, after)) => unsafe {
+                ct_eq_bytes(std::slice::from_raw_parts(before, CANARY_LEN), &canary)
+                    & ct_eq_bytes(std::slice::from_raw_parts(after, CANARY_LEN), &canary)
+            },
+            None => true,
+        }
+    }
+
+    fn refresh_tamper_checksum(&mut self) {
+        if let Some(guard) = &self.tamper_guard {
+            let checksum = guard.compute(self.content());
+            if let Some(guard) = self.tamper_guard.as_mut() {
+                guard.checksum = checksum;
+            }
+        }
     }
 
     /// Enable side-channel attack protection
@@ -387,48 +935,62 @@ pub fn get_compliance_report(&self) -> String {
         }
     }
 
-    /// Get security audit log
+    /// Get security audit log as a JSON document of retained events
     pub fn get_security_audit_log(&self) -> String {
-        // Implementation for audit log retrieval
-        format!("AUDIT_LOG: Buffer created with capacity {}, current length {}", 
-                self.capacity, self.length)
+        self.audit_log().to_json()
     }
 
-    /// Generate HMAC in hexadecimal format
-    pub fn hmac_hex(&self, key: &[u8]) -> Result<String, String> {
-        use sha2::{Sha256, Digest};
-        
+    /// Compute an HMAC over the buffer content with the given keyed MAC type
+    fn compute_hmac<M: hmac::Mac + hmac::digest::KeyInit>(&self, key: &[u8]) -> Result<Vec<u8>, String> {
         if !self.is_valid.load(Ordering::SeqCst) || key.is_empty() {
             return Err("Invalid buffer or key".to_string());
         }
 
-        // Simple HMAC implementation using SHA-256
-        let mut hasher = Sha256::new();
-        hasher.update(key);
-        unsafe {
-            hasher.update(std::slice::from_raw_parts(self.data, self.length));
-        }
-        let result = hasher.finalize();
-        Ok(hex::encode(result))
+        let mut mac = <M as hmac::Mac>::new_from_slice(key)
+            .map_err(|_| "Invalid HMAC key".to_string())?;
+        mac.update(self.content());
+        Ok(mac.finalize().into_bytes().to_vec())
     }
 
-    /// Generate HMAC in base64url format
+    /// Generate HMAC-SHA256 in hexadecimal format
+    pub fn hmac_hex(&self, key: &[u8]) -> Result<String, String> {
+        self.compute_hmac::<hmac::Hmac<sha2::Sha256>>(key).map(hex::encode)
+    }
+
+    /// Generate HMAC-SHA256 in base64url format
     pub fn hmac_base64url(&self, key: &[u8]) -> Result<String, String> {
-        use sha2::{Sha256, Digest};
         use base64::{Engine as _, engine::general_purpose};
-        
+        self.compute_hmac::<hmac::Hmac<sha2::Sha256>>(key)
+            .map(|mac| general_purpose::URL_SAFE_NO_PAD.encode(mac))
+    }
+
+    /// Generate HMAC-SHA512 in hexadecimal format
+    pub fn hmac_sha512_hex(&self, key: &[u8]) -> Result<String, String> {
+        self.compute_hmac::<hmac::Hmac<sha2::Sha512>>(key).map(hex::encode)
+    }
+
+    /// Generate HMAC-SHA512 in base64url format
+    pub fn hmac_sha512_base64url(&self, key: &[u8]) -> Result<String, String> {
+        use base64::{Engine as _, engine::general_purpose};
+        self.compute_hmac::<hmac::Hmac<sha2::Sha512>>(key)
+            .map(|mac| general_purpose::URL_SAFE_NO_PAD.encode(mac))
+    }
+
+    /// Pre-HMAC digest `SHA256(key || data)` formerly returned by `hmac_hex`.
+    /// Only for verifying stored values during migration; it is vulnerable to length extension.
+    #[cfg(feature = "legacy-digest")]
+    #[deprecated(note = "SHA256(key || data) is not an HMAC; use hmac_hex")]
+    pub fn legacy_digest_hex(&self, key: &[u8]) -> Result<String, String> {
+        use sha2::{Sha256, Digest};
+
         if !self.is_valid.load(Ordering::SeqCst) || key.is_empty() {
             return Err("Invalid buffer or key".to_string());
         }
 
-        // Simple HMAC implementation using SHA-256
         let mut hasher = Sha256::new();
         hasher.update(key);
-        unsafe {
-            hasher.update(std::slice::from_raw_parts(self.data, self.length));
-        }
-        let result = hasher.finalize();
-        Ok(general_purpose::URL_SAFE_NO_PAD.encode(result))
+        hasher.update(self.content());
+        Ok(hex::encode(hasher.finalize()))
     }
 
     /// Lock the buffer for exclusive access
@@ -449,9 +1011,9 @@ pub fn unlock(&mut self) -> Result<(), String> {
         }
     }
 
-    /// Perform integrity check on buffer
+    /// Perform integrity check on buffer (validity plus tamper checksum when enabled)
     pub fn integrity_check(&self) -> bool {
-        self.is_valid.load(Ordering::SeqCst)
+        !self.is_tampered()
     }
 
     /// Securely zeroize buffer contents
@@ -461,6 +1023,8 @@ pub fn zeroize(&mut self) {
                 memory::explicit_bzero(self.data, self.capacity);
             }
             self.length = 0;
+            self.refresh_tamper_checksum();
+            self.audit(AuditEventKind::Zeroize, self.capacity);
         }
     }
 
@@ -470,19 +1034,17 @@ pub fn destroy(&mut self) {
         self.is_valid.store(false, Ordering::SeqCst);
         
         if !self.data.is_null() {
+            if !self.canaries_intact() {
+                self.audit(AuditEventKind::CanaryViolation, self.capacity);
+            }
+            self.audit(AuditEventKind::Destroy, self.capacity);
             unsafe {
                 // Multiple-pass zeroization for extra security
                 memory::explicit_bzero(self.data, self.capacity);
-                memory::explicit_bzero(self.data, self.capacity);
-                
-                // Unlock memory if it was locked (prevent double-unlock)
-                if self.is_locked.swap(false, Ordering::SeqCst) {
-                    let _ = memory::unlock_memory(self.data, self.capacity);
-                }
-                
-                // Deallocate
-                let layout = Layout::from_size_align_unchecked(self.capacity, 32);
-                dealloc(self.data, layout);
+
+                // Unlock (prevent double-unlock), then zeroize again and free
+                let locked = self.is_locked.swap(false, Ordering::SeqCst);
+                release_region(self.data, self.capacity, locked, self.options);
             }
             
             // Clear pointers and sizes
@@ -503,6 +1065,19 @@ fn drop(&mut self) {
 unsafe impl Send for SecureBuffer {}
 unsafe impl Sync for SecureBuffer {}
 
+/// FFI status: success
+pub const SECURE_BUFFER_OK: c_int = 0;
+/// FFI status: null or otherwise invalid argument
+pub const SECURE_BUFFER_ERR_INVALID: c_int = -1;
+/// FFI status: output buffer is smaller than the stored content
+pub const SECURE_BUFFER_ERR_TOO_SMALL: c_int = -2;
+/// FFI status: offset/length outside the buffer bounds
+pub const SECURE_BUFFER_ERR_OUT_OF_BOUNDS: c_int = -3;
+/// FFI status: caller memory overlaps the secure region
+pub const SECURE_BUFFER_ERR_OVERLAP: c_int = -4;
+/// FFI status: sealed blob failed authentication (tampered or wrong KEK)
+pub const SECURE_BUFFER_ERR_AUTH: c_int = -5;
+
 // FFI-safe wrapper for C interop
 #[repr(C)]
 pub struct CSecureBuffer {
@@ -616,7 +1191,180 @@ pub extern "C" fn secure_buffer_new(capacity: usize) -> *mut CSecureBuffer {
     if buffer.is_null() {
         return -1;
     }
-    (*buffer).read(buf, buf_len)
+    (*buffer).read(buf, buf_len)
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `buffer` must be a valid pointer previously returned by `secure_buffer_new`. `out` must be
+/// writable for `out_cap` bytes and `out_written` must be a valid pointer. Copies the whole
+/// content or nothing: returns `SECURE_BUFFER_ERR_TOO_SMALL` with the required length in
+/// `out_written` if `out_cap` is too small, and `SECURE_BUFFER_ERR_OVERLAP` if `out` overlaps
+/// the secure region. `out` is left untouched on every error.
+pub unsafe extern "C" fn secure_buffer_read_exact(
+    buffer: *const CSecureBuffer,
+    out: *mut u8,
+    out_cap: usize,
+    out_written: *mut usize,
+) -> c_int {
+    if out_written.is_null() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    *out_written = 0;
+    if buffer.is_null() || (*buffer).inner.is_null() || out.is_null() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    let inner = &*(*buffer).inner;
+    if !inner.is_valid() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    let length = inner.length;
+    if out_cap < length {
+        *out_written = length;
+        return SECURE_BUFFER_ERR_TOO_SMALL;
+    }
+    let region = inner.data as usize..inner.data as usize + inner.capacity;
+    let out_start = out as usize;
+    if out_start < region.end && region.start < out_start.saturating_add(out_cap) {
+        return SECURE_BUFFER_ERR_OVERLAP;
+    }
+    std::ptr::copy_nonoverlapping(inner.data, out, length);
+    inner.audit(AuditEventKind::Read, length);
+    *out_written = length;
+    SECURE_BUFFER_OK
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `buffer` must be a valid pointer previously returned by `secure_buffer_new`, and `data`
+/// must point to `len` readable bytes. `data` may alias the buffer's own content. Writing
+/// starts at `offset`, which may not be past the current length; the write must fit within
+/// the capacity or `SECURE_BUFFER_ERR_OUT_OF_BOUNDS` is returned and nothing is modified.
+pub unsafe extern "C" fn secure_buffer_write_at(
+    buffer: *mut CSecureBuffer,
+    offset: usize,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    if buffer.is_null() || (*buffer).inner.is_null() || (data.is_null() && len != 0) {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    let inner = &mut *(*buffer).inner;
+    if !inner.is_valid() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    let slice = if len == 0 { &[][..] } else { std::slice::from_raw_parts(data, len) };
+    match inner.write_at(offset, slice) {
+        Ok(()) => SECURE_BUFFER_OK,
+        Err(_) => SECURE_BUFFER_ERR_OUT_OF_BOUNDS,
+    }
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `a` and `b` must be valid pointers previously returned by `secure_buffer_new`.
+/// Returns 1 if the contents are equal, 0 if not, and -1 on a null pointer. The
+/// comparison runs in constant time with respect to buffer contents.
+pub unsafe extern "C" fn secure_buffer_ct_eq(a: *const CSecureBuffer, b: *const CSecureBuffer) -> c_int {
+    if a.is_null() || b.is_null() || (*a).inner.is_null() || (*b).inner.is_null() {
+        return -1;
+    }
+    if (*(*a).inner).ct_eq(&*(*b).inner) { 1 } else { 0 }
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `buffer` must be a valid pointer previously returned by `secure_buffer_new`, and `data`
+/// must point to `len` readable bytes (it may be null when `len` is 0). Returns 1 if the
+/// contents equal `data`, 0 if not, and -1 on invalid arguments. Runs in constant time with
+/// respect to the buffer contents, so tokens can be checked without copying the secret out.
+pub unsafe extern "C" fn secure_buffer_ct_eq_slice(
+    buffer: *const CSecureBuffer,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    if buffer.is_null() || (*buffer).inner.is_null() || (data.is_null() && len != 0) {
+        return -1;
+    }
+    let other = if len == 0 { &[][..] } else { std::slice::from_raw_parts(data, len) };
+    if (*(*buffer).inner).ct_eq_slice(other) { 1 } else { 0 }
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `buffer` must be a valid pointer previously returned by `secure_buffer_new`, `kek` must
+/// point to 32 readable bytes, `out` must be writable for `out_cap` bytes and `out_written`
+/// must be a valid pointer. Writes the sealed blob (see `SecureBuffer::seal`) to `out`, or
+/// returns `SECURE_BUFFER_ERR_TOO_SMALL` with the required length in `out_written`.
+pub unsafe extern "C" fn secure_buffer_seal(
+    buffer: *const CSecureBuffer,
+    kek: *const u8,
+    out: *mut u8,
+    out_cap: usize,
+    out_written: *mut usize,
+) -> c_int {
+    if out_written.is_null() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    *out_written = 0;
+    if buffer.is_null() || (*buffer).inner.is_null() || kek.is_null() || out.is_null() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    let inner = &*(*buffer).inner;
+    if !inner.is_valid() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    let required = seal::sealed_len(inner.length);
+    if out_cap < required {
+        *out_written = required;
+        return SECURE_BUFFER_ERR_TOO_SMALL;
+    }
+    let kek = &*(kek as *const [u8; seal::SEAL_KEK_LEN]);
+    match inner.seal(kek) {
+        Ok(blob) => {
+            std::ptr::copy_nonoverlapping(blob.as_ptr(), out, blob.len());
+            *out_written = blob.len();
+            SECURE_BUFFER_OK
+        }
+        Err(_) => SECURE_BUFFER_ERR_INVALID,
+    }
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `blob` must point to `blob_len` readable bytes, `kek` to 32 readable bytes and `out` must
+/// be a valid pointer. On success `*out` receives a new buffer to be freed with
+/// `secure_buffer_destroy`. On error `*out` is null: `SECURE_BUFFER_ERR_AUTH` for a tampered
+/// blob or wrong KEK, `SECURE_BUFFER_ERR_INVALID` for a malformed or unsupported blob.
+pub unsafe extern "C" fn secure_buffer_unseal(
+    blob: *const u8,
+    blob_len: usize,
+    kek: *const u8,
+    out: *mut *mut CSecureBuffer,
+) -> c_int {
+    if out.is_null() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    *out = std::ptr::null_mut();
+    if blob.is_null() || kek.is_null() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    let blob = std::slice::from_raw_parts(blob, blob_len);
+    let kek = &*(kek as *const [u8; seal::SEAL_KEK_LEN]);
+    match SecureBuffer::unseal(blob, kek) {
+        Ok(buffer) => {
+            *out = Box::into_raw(Box::new(CSecureBuffer { inner: Box::into_raw(Box::new(buffer)) }));
+            SECURE_BUFFER_OK
+        }
+        Err(seal::SealError::Authentication) => SECURE_BUFFER_ERR_AUTH,
+        Err(_) => SECURE_BUFFER_ERR_INVALID,
+    }
 }
 
 #[no_mangle]
@@ -651,6 +1399,40 @@ pub enum UniversalBloomFilterError {
     ConcurrencyError = -6,
     NullPointer = -7,
     InvalidSize = -8,
+    IoError = -9,
+    FormatError = -10,
+    VersionMismatch = -11,
+    NetworkMismatch = -12,
+    BlockParseError = -13,
+}
+
+impl From<&bloom_filter::BloomFilterError> for UniversalBloomFilterError {
+    fn from(err: &bloom_filter::BloomFilterError) -> Self {
+        use bloom_filter::BloomFilterError as E;
+        match err {
+            E::InvalidConfiguration(_) => Self::InvalidConfiguration,
+            E::InvalidInput(_) => Self::InvalidInput,
+            E::HashComputationError => Self::HashComputationError,
+            E::SystemTimeError => Self::SystemTimeError,
+            E::MemoryError => Self::MemoryError,
+            E::ConcurrencyError => Self::ConcurrencyError,
+            E::Io(_) => Self::IoError,
+            E::Persistence(_) => Self::FormatError,
+            E::UnsupportedVersion(_) => Self::VersionMismatch,
+            E::NetworkMismatch { .. } => Self::NetworkMismatch,
+            E::InvalidBlock(_) => Self::BlockParseError,
+        }
+    }
+}
+
+/// Map a network name from the C API to its configuration
+fn network_config_from_name(name: &str) -> NetworkConfig {
+    match name {
+        "bitcoin" => NetworkConfig::bitcoin(),
+        "ethereum" => NetworkConfig::ethereum(),
+        "solana" => NetworkConfig::solana(),
+        _ => NetworkConfig::custom(name, 32, 600, 4_000_000, "pow"),
+    }
 }
 
 /// Create new Universal Bloom Filter with custom configuration
@@ -674,12 +1456,7 @@ pub enum UniversalBloomFilterError {
     }
 
     let network_str = unsafe { CStr::from_ptr(network_name) }.to_str().unwrap_or("bitcoin");
-    let network_config = match network_str {
-        "bitcoin" => NetworkConfig::bitcoin(),
-        "ethereum" => NetworkConfig::ethereum(),
-        "solana" => NetworkConfig::solana(),
-        _ => NetworkConfig::custom(network_str, 32, 600, 4_000_000, "pow"),
-    };
+    let network_config = network_config_from_name(network_str);
 
     let config = BloomConfig {
         network: network_config,
@@ -688,9 +1465,12 @@ pub enum UniversalBloomFilterError {
         tweak,
         flags,
         max_age_seconds,
+        generations: bloom_filter::DEFAULT_GENERATIONS,
         batch_size,
         enable_compression: false,
         enable_metrics: true,
+        provenance_capacity: 0,
+        hash_key: bloom_filter::generate_hash_key(),
     };
 
     match UniversalBloomFilter::new(Some(config)) {
@@ -712,6 +1492,57 @@ pub enum UniversalBloomFilterError {
     }
 }
 
+/// Save Universal Bloom Filter to disk in the versioned binary format
+#[no_mangle]
+/// # Safety
+///
+/// `filter` must be a valid handle and `path` a valid NUL-terminated C string.
+pub unsafe extern "C" fn universal_bloom_filter_save(filter: UniversalBloomFilterHandle, path: *const c_char) -> c_int {
+    if filter.is_null() || path.is_null() {
+        return UniversalBloomFilterError::NullPointer as c_int;
+    }
+
+    let filter_ref = unsafe { &*(filter as *const UniversalBloomFilter) };
+    let path_str = match unsafe { CStr::from_ptr(path) }.to_str() {
+        Ok(p) => p,
+        Err(_) => return UniversalBloomFilterError::InvalidInput as c_int,
+    };
+    match filter_ref.save_to_file(path_str) {
+        Ok(()) => UniversalBloomFilterError::Success as c_int,
+        Err(e) => UniversalBloomFilterError::from(&e) as c_int,
+    }
+}
+
+/// Load Universal Bloom Filter from disk, requiring it to match `network_name`
+#[no_mangle]
+/// # Safety
+///
+/// `path` and `network_name` must be valid NUL-terminated C strings and `out_filter` a valid
+/// writable pointer. On success `*out_filter` receives a handle that must be freed with
+/// `universal_bloom_filter_destroy`; on failure it is set to null.
+pub unsafe extern "C" fn universal_bloom_filter_load(
+    path: *const c_char,
+    network_name: *const c_char,
+    out_filter: *mut UniversalBloomFilterHandle,
+) -> c_int {
+    if path.is_null() || network_name.is_null() || out_filter.is_null() {
+        return UniversalBloomFilterError::NullPointer as c_int;
+    }
+    *out_filter = std::ptr::null_mut();
+
+    let (path_str, network_str) = match (CStr::from_ptr(path).to_str(), CStr::from_ptr(network_name).to_str()) {
+        (Ok(p), Ok(n)) => (p, n),
+        _ => return UniversalBloomFilterError::InvalidInput as c_int,
+    };
+    match UniversalBloomFilter::load_from_file(path_str, &network_config_from_name(network_str)) {
+        Ok(filter) => {
+            *out_filter = Box::into_raw(Box::new(filter)) as UniversalBloomFilterHandle;
+            UniversalBloomFilterError::Success as c_int
+        }
+        Err(e) => UniversalBloomFilterError::from(&e) as c_int,
+    }
+}
+
 /// Destroy Universal Bloom Filter and securely zeroize memory
 #[no_mangle]
 /// # Safety
@@ -742,13 +1573,45 @@ pub enum UniversalBloomFilterError {
     let filter_ref = unsafe { &*(filter as *const UniversalBloomFilter) };
     let txid_slice = unsafe { std::slice::from_raw_parts(txid_bytes, 32) };
 
-    let txid = TransactionId::from_bytes(txid_slice).unwrap_or_else(|| TransactionId::new("bitcoin", txid_slice));
+    let txid = TransactionId::new(filter_ref.network_name(), txid_slice);
     match filter_ref.insert_utxo(&txid, vout) {
         Ok(_) => UniversalBloomFilterError::Success as c_int,
         Err(_) => UniversalBloomFilterError::InvalidInput as c_int,
     }
 }
 
+/// Insert single UTXO for an explicit network into a mixed-use bloom filter.
+///
+/// Ids whose network differs from the filter's own are namespaced, so they must be
+/// queried with `universal_bloom_filter_contains_utxo_for_network` and the same name.
+#[no_mangle]
+/// # Safety
+///
+/// `filter` must be a valid handle, `txid_bytes` must point to at least 32 bytes and
+/// `network_name` must be a valid NUL-terminated C string.
+pub unsafe extern "C" fn universal_bloom_filter_insert_utxo_for_network(
+    filter: UniversalBloomFilterHandle,
+    txid_bytes: *const u8,
+    vout: u32,
+    network_name: *const c_char,
+) -> c_int {
+    if filter.is_null() || txid_bytes.is_null() || network_name.is_null() {
+        return UniversalBloomFilterError::NullPointer as c_int;
+    }
+
+    let filter_ref = unsafe { &*(filter as *const UniversalBloomFilter) };
+    let network_str = match unsafe { CStr::from_ptr(network_name) }.to_str() {
+        Ok(n) => n,
+        Err(_) => return UniversalBloomFilterError::InvalidInput as c_int,
+    };
+    let txid_slice = unsafe { std::slice::from_raw_parts(txid_bytes, 32) };
+
+    match filter_ref.insert_utxo(&TransactionId::new(network_str, txid_slice), vout) {
+        Ok(_) => UniversalBloomFilterError::Success as c_int,
+        Err(e) => UniversalBloomFilterError::from(&e) as c_int,
+    }
+}
+
 /// Insert batch of UTXOs into Universal Bloom Filter (maximum performance)
 #[no_mangle]
 /// # Safety
@@ -777,7 +1640,7 @@ pub enum UniversalBloomFilterError {
             return UniversalBloomFilterError::InvalidSize as c_int;
         }
 
-        let txid = TransactionId::from_bytes(&txids_slice[txid_start..txid_end]).unwrap_or_else(|| TransactionId::new("bitcoin", &txids_slice[txid_start..txid_end]));
+        let txid = TransactionId::new(filter_ref.network_name(), &txids_slice[txid_start..txid_end]);
         batch.push((txid, vout));
     }
 
@@ -804,7 +1667,7 @@ pub enum UniversalBloomFilterError {
     let filter_ref = unsafe { &*(filter as *const UniversalBloomFilter) };
     let txid_slice = unsafe { std::slice::from_raw_parts(txid_bytes, 32) };
 
-    let txid = TransactionId::from_bytes(txid_slice).unwrap_or_else(|| TransactionId::new("bitcoin", txid_slice));
+    let txid = TransactionId::new(filter_ref.network_name(), txid_slice);
     match filter_ref.contains_utxo(&txid, vout) {
         Ok(true) => 1, // Found
         Ok(false) => 0, // Not found
@@ -812,6 +1675,36 @@ pub enum UniversalBloomFilterError {
     }
 }
 
+/// Check if a UTXO inserted for an explicit network exists in the filter
+#[no_mangle]
+/// # Safety
+///
+/// `filter` must be a valid handle, `txid_bytes` must point to 32 bytes and
+/// `network_name` must be a valid NUL-terminated C string.
+pub unsafe extern "C" fn universal_bloom_filter_contains_utxo_for_network(
+    filter: UniversalBloomFilterHandle,
+    txid_bytes: *const u8,
+    vout: u32,
+    network_name: *const c_char,
+) -> c_int {
+    if filter.is_null() || txid_bytes.is_null() || network_name.is_null() {
+        return UniversalBloomFilterError::NullPointer as c_int;
+    }
+
+    let filter_ref = unsafe { &*(filter as *const UniversalBloomFilter) };
+    let network_str = match unsafe { CStr::from_ptr(network_name) }.to_str() {
+        Ok(n) => n,
+        Err(_) => return UniversalBloomFilterError::InvalidInput as c_int,
+    };
+    let txid_slice = unsafe { std::slice::from_raw_parts(txid_bytes, 32) };
+
+    match filter_ref.contains_utxo(&TransactionId::new(network_str, txid_slice), vout) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(e) => UniversalBloomFilterError::from(&e) as c_int,
+    }
+}
+
 /// Check batch of UTXOs in Universal Bloom Filter
 #[no_mangle]
 /// # Safety
@@ -842,7 +1735,7 @@ pub enum UniversalBloomFilterError {
             return UniversalBloomFilterError::InvalidSize as c_int;
         }
 
-        let txid = TransactionId::from_bytes(&txids_slice[txid_start..txid_end]).unwrap_or_else(|| TransactionId::new("bitcoin", &txids_slice[txid_start..txid_end]));
+        let txid = TransactionId::new(filter_ref.network_name(), &txids_slice[txid_start..txid_end]);
         batch.push((txid, vout));
     }
 
@@ -857,7 +1750,11 @@ pub enum UniversalBloomFilterError {
     }
 }
 
-/// Load entire block into Universal Bloom Filter
+/// Load a serialized Bitcoin block into Universal Bloom Filter
+///
+/// Returns `BlockParseError` if the bytes are not a valid block. With the
+/// `legacy-block-layout` feature, unparseable input falls back to the old ad-hoc
+/// layout (32-byte txid + u32 vout count + 8-byte outputs).
 #[no_mangle]
 /// # Safety
 ///
@@ -874,46 +1771,44 @@ pub enum UniversalBloomFilterError {
     let filter_ref = unsafe { &*(filter as *const UniversalBloomFilter) };
     let block_slice = unsafe { std::slice::from_raw_parts(block_data, block_size) };
 
-    // For now, create a simple BlockData from raw bytes
-    // In a full implementation, this would parse the block format
-    let mut transactions = Vec::new();
+    let block_data_struct = match BlockData::from_bitcoin_bytes(filter_ref.network_name(), block_slice) {
+        Ok(block) => block,
+        #[cfg(feature = "legacy-block-layout")]
+        Err(_) => legacy_block_data(filter_ref, block_slice),
+        #[cfg(not(feature = "legacy-block-layout"))]
+        Err(e) => return UniversalBloomFilterError::from(&e) as c_int,
+    };
+
+    match filter_ref.load_block(&block_data_struct) {
+        Ok(_) => UniversalBloomFilterError::Success as c_int,
+        Err(_) => UniversalBloomFilterError::InvalidInput as c_int,
+    }
+}
 
-    // Simple parsing: assume each transaction is 32 bytes (txid) + 4 bytes (vout count) + (vout count * 8 bytes for outputs)
+/// Parse the pre-wire-format layout: 32-byte txid + u32 vout count + 8-byte outputs
+#[cfg(feature = "legacy-block-layout")]
+fn legacy_block_data(filter_ref: &UniversalBloomFilter, block_slice: &[u8]) -> BlockData {
+    let block_size = block_slice.len();
+    let mut transactions = Vec::new();
     let mut offset = 0;
     while offset + 36 <= block_size {
         let txid_bytes = &block_slice[offset..offset + 32];
-        let txid = TransactionId::from_bytes(txid_bytes).unwrap_or_else(|| TransactionId::new("bitcoin", txid_bytes));
         offset += 32;
 
         let vout_count = u32::from_le_bytes(block_slice[offset..offset + 4].try_into().unwrap_or([0; 4]));
         offset += 4;
-
-        let mut outputs = Vec::new();
         for _ in 0..vout_count {
             if offset + 8 <= block_size {
-                outputs.push(block_slice[offset..offset + 8].to_vec());
                 offset += 8;
             }
         }
 
-        transactions.push(TransactionId {
-            network: "bitcoin".to_string(),
-            hash: txid.as_bytes().to_vec(),
-        });
+        transactions.push(TransactionId::new(filter_ref.network_name(), txid_bytes));
     }
 
-    let block_data_struct = BlockData {
-        network: "bitcoin".to_string(),
-        height: 0, // Unknown height
-        hash: block_slice[0..32].to_vec(), // Use first 32 bytes as block hash
-        transactions,
-        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
-    };
-
-    match filter_ref.load_block(&block_data_struct) {
-        Ok(_) => UniversalBloomFilterError::Success as c_int,
-        Err(_) => UniversalBloomFilterError::InvalidInput as c_int,
-    }
+    // Use first 32 bytes as block hash
+    let hash = &block_slice[..block_size.min(32)];
+    BlockData::new(filter_ref.network_name(), 0, hash, transactions)
 }
 
 /// Get Universal Bloom Filter statistics
@@ -930,10 +1825,11 @@ pub enum UniversalBloomFilterError {
     memory_usage_bytes: *mut usize,
     timestamp_entries: *mut usize,
     average_age_seconds: *mut c_double,
+    saturation_ratio: *mut c_double,
 ) -> c_int {
     if filter.is_null() || item_count.is_null() || false_positive_count.is_null() ||
        theoretical_fp_rate.is_null() || memory_usage_bytes.is_null() ||
-       timestamp_entries.is_null() || average_age_seconds.is_null() {
+       timestamp_entries.is_null() || average_age_seconds.is_null() || saturation_ratio.is_null() {
         return UniversalBloomFilterError::NullPointer as c_int;
     }
 
@@ -947,6 +1843,7 @@ pub enum UniversalBloomFilterError {
         *memory_usage_bytes = stats.memory_usage_bytes;
         *timestamp_entries = stats.timestamp_entries;
         *average_age_seconds = stats.average_age_seconds;
+        *saturation_ratio = stats.saturation_ratio;
     }
 
     UniversalBloomFilterError::Success as c_int
@@ -1287,18 +2184,87 @@ pub extern "C" fn get_cpu_temperature_c() -> f32 {
     filter.get_item_count()
 }
 
-/// C FFI: Get false positive rate
+/// C FFI: Get theoretical false positive rate.
+///
+/// Kept for existing callers; this used to return the raw false positive count.
+/// Prefer `bloom_filter_theoretical_fp_rate` or `bloom_filter_observed_fp_rate`.
 #[no_mangle]
 /// # Safety
 ///
 /// `filter` must be a pointer returned by `bloom_filter_new`.
 pub unsafe extern "C" fn bloom_filter_false_positive_rate(filter: *mut c_void) -> f64 {
+    bloom_filter_theoretical_fp_rate(filter)
+}
+
+/// C FFI: Get theoretical false positive rate from the current fill level
+#[no_mangle]
+/// # Safety
+///
+/// `filter` must be a pointer returned by `bloom_filter_new`.
+pub unsafe extern "C" fn bloom_filter_theoretical_fp_rate(filter: *mut c_void) -> f64 {
     if filter.is_null() {
         return 1.0;
     }
-    
+
+    let filter = &*(filter as *mut bloom_filter::UniversalBloomFilter);
+    filter.false_positive_rate()
+}
+
+/// C FFI: Get observed false positive rate (confirmed false positives / total queries)
+#[no_mangle]
+/// # Safety
+///
+/// `filter` must be a pointer returned by `bloom_filter_new`.
+pub unsafe extern "C" fn bloom_filter_observed_fp_rate(filter: *mut c_void) -> f64 {
+    if filter.is_null() {
+        return 1.0;
+    }
+
+    let filter = &*(filter as *mut bloom_filter::UniversalBloomFilter);
+    filter.observed_false_positive_rate()
+}
+
+/// C FFI: Get number of false positives confirmed via `bloom_filter_confirm_false_positive`
+#[no_mangle]
+/// # Safety
+///
+/// `filter` must be a pointer returned by `bloom_filter_new`.
+pub unsafe extern "C" fn bloom_filter_observed_fp_count(filter: *mut c_void) -> u64 {
+    if filter.is_null() {
+        return 0;
+    }
+
+    let filter = &*(filter as *mut bloom_filter::UniversalBloomFilter);
+    filter.confirmed_false_positive_count()
+}
+
+/// C FFI: Get number of lookups answered by the filter
+#[no_mangle]
+/// # Safety
+///
+/// `filter` must be a pointer returned by `bloom_filter_new`.
+pub unsafe extern "C" fn bloom_filter_query_count(filter: *mut c_void) -> u64 {
+    if filter.is_null() {
+        return 0;
+    }
+
+    let filter = &*(filter as *mut bloom_filter::UniversalBloomFilter);
+    filter.query_count()
+}
+
+/// C FFI: Report that a bloom hit was not present in the full index
+#[no_mangle]
+/// # Safety
+///
+/// `filter` must be a pointer returned by `bloom_filter_new`.
+pub unsafe extern "C" fn bloom_filter_confirm_false_positive(filter: *mut c_void) -> c_int {
+    if filter.is_null() {
+        return -1;
+    }
+
     let filter = &*(filter as *mut bloom_filter::UniversalBloomFilter);
-    filter.get_false_positive_count()
+    filter.confirm_false_positive();
+    0
 }
 
 /// C FFI: Free bloom filter
@@ -1700,3 +2666,680 @@ pub extern "C" fn get_cpu_temperature_c() -> f32 {
         let _ = Box::from_raw(buffer as *mut SecureBuffer);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resize_preserves_content_and_zeroizes_old_region() {
+        let mut buffer = SecureBuffer::new(16).unwrap();
+        buffer.write(b"secret").unwrap();
+        let old_ptr = buffer.data as usize;
+        test_hooks::take_releases();
+
+        buffer.resize(64).unwrap();
+        assert_eq!(buffer.capacity(), 64);
+        assert_eq!(buffer.as_slice().unwrap(), b"secret");
+        assert_eq!(test_hooks::take_releases(), vec![(old_ptr, true)]);
+    }
+
+    #[test]
+    fn test_append_grows_consistently() {
+        let mut buffer = SecureBuffer::new(4).unwrap();
+        buffer.set_growth_factor(1.5).unwrap();
+        let mut expected = Vec::new();
+        for chunk in [&b"abc"[..], b"defg", b"hijklmnop", b"q"] {
+            buffer.append(chunk).unwrap();
+            expected.extend_from_slice(chunk);
+            assert_eq!(buffer.len(), expected.len());
+            assert!(buffer.capacity() >= buffer.len());
+        }
+        assert_eq!(buffer.as_slice().unwrap(), &expected[..]);
+        assert!(test_hooks::take_releases().iter().all(|&(_, zeroed)| zeroed));
+    }
+
+    #[test]
+    fn test_resize_shrink_truncates() {
+        let mut buffer = SecureBuffer::new(32).unwrap();
+        buffer.write(b"0123456789").unwrap();
+        test_hooks::take_releases();
+
+        buffer.resize(4).unwrap();
+        assert_eq!(buffer.len(), 4);
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(buffer.as_slice().unwrap(), b"0123");
+        assert!(test_hooks::take_releases().iter().all(|&(_, zeroed)| zeroed));
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        let mut a = SecureBuffer::new(32).unwrap();
+        let mut b = SecureBuffer::new(64).unwrap();
+        a.write(b"api-key-123").unwrap();
+        b.write(b"api-key-123").unwrap();
+        assert!(a.ct_eq(&b));
+        assert!(a.ct_eq_slice(b"api-key-123"));
+
+        b.write(b"api-key-124").unwrap();
+        assert!(!a.ct_eq(&b));
+        assert!(!a.ct_eq_slice(b"api-key-124"));
+
+        // Different lengths, including a prefix and the empty slice
+        assert!(!a.ct_eq_slice(b"api-key-12"));
+        assert!(!a.ct_eq_slice(b"api-key-1234"));
+        assert!(!a.ct_eq_slice(b""));
+        let empty = SecureBuffer::new(8).unwrap();
+        assert!(empty.ct_eq_slice(b""));
+
+        b.destroy();
+        assert!(!a.ct_eq(&b));
+    }
+
+    #[test]
+    fn test_ct_eq_ffi() {
+        unsafe {
+            let a = secure_buffer_new(16);
+            let b = secure_buffer_new(16);
+            assert_eq!(secure_buffer_write(a, b"token".as_ptr(), 5), 0);
+            assert_eq!(secure_buffer_write(b, b"token".as_ptr(), 5), 0);
+            assert_eq!(secure_buffer_ct_eq(a, b), 1);
+            assert_eq!(secure_buffer_ct_eq_slice(a, b"token".as_ptr(), 5), 1);
+            assert_eq!(secure_buffer_ct_eq_slice(a, b"tokem".as_ptr(), 5), 0);
+            assert_eq!(secure_buffer_ct_eq_slice(a, std::ptr::null(), 5), -1);
+            assert_eq!(secure_buffer_ct_eq(a, std::ptr::null()), -1);
+            secure_buffer_destroy(a);
+            secure_buffer_destroy(b);
+        }
+    }
+
+    #[test]
+    fn test_read_exact_ffi() {
+        unsafe {
+            let buf = secure_buffer_new(32);
+            assert_eq!(secure_buffer_write(buf, b"secret-value".as_ptr(), 12), 0);
+
+            let mut written = 0usize;
+            let mut small = [0xAAu8; 4];
+            assert_eq!(
+                secure_buffer_read_exact(buf, small.as_mut_ptr(), small.len(), &mut written),
+                SECURE_BUFFER_ERR_TOO_SMALL
+            );
+            assert_eq!(written, 12);
+            assert_eq!(small, [0xAA; 4], "no content may leak on error");
+
+            let mut out = [0u8; 16];
+            assert_eq!(secure_buffer_read_exact(buf, out.as_mut_ptr(), out.len(), &mut written), SECURE_BUFFER_OK);
+            assert_eq!(&out[..written], b"secret-value");
+
+            assert_eq!(
+                secure_buffer_read_exact(std::ptr::null(), out.as_mut_ptr(), out.len(), &mut written),
+                SECURE_BUFFER_ERR_INVALID
+            );
+            assert_eq!(written, 0);
+            assert_eq!(
+                secure_buffer_read_exact(buf, std::ptr::null_mut(), 16, &mut written),
+                SECURE_BUFFER_ERR_INVALID
+            );
+            assert_eq!(
+                secure_buffer_read_exact(buf, out.as_mut_ptr(), out.len(), std::ptr::null_mut()),
+                SECURE_BUFFER_ERR_INVALID
+            );
+
+            // Reading into the secure region itself is refused
+            let internal = (*(*buf).inner).data;
+            assert_eq!(
+                secure_buffer_read_exact(buf, internal.add(16), 16, &mut written),
+                SECURE_BUFFER_ERR_OVERLAP
+            );
+            secure_buffer_destroy(buf);
+        }
+    }
+
+    #[test]
+    fn test_seal_unseal_ffi() {
+        unsafe {
+            let kek = [3u8; 32];
+            let buf = secure_buffer_new(32);
+            assert_eq!(secure_buffer_write(buf, b"secret-value".as_ptr(), 12), 0);
+
+            let mut written = 0usize;
+            let mut small = [0u8; 8];
+            assert_eq!(
+                secure_buffer_seal(buf, kek.as_ptr(), small.as_mut_ptr(), small.len(), &mut written),
+                SECURE_BUFFER_ERR_TOO_SMALL
+            );
+            assert_eq!(written, seal::sealed_len(12));
+
+            let mut blob = vec![0u8; written];
+            assert_eq!(
+                secure_buffer_seal(buf, kek.as_ptr(), blob.as_mut_ptr(), blob.len(), &mut written),
+                SECURE_BUFFER_OK
+            );
+
+            let mut restored = std::ptr::null_mut();
+            assert_eq!(secure_buffer_unseal(blob.as_ptr(), blob.len(), kek.as_ptr(), &mut restored), SECURE_BUFFER_OK);
+            assert_eq!(secure_buffer_ct_eq(buf, restored), 1);
+
+            let mut rejected = std::ptr::null_mut();
+            let wrong = [4u8; 32];
+            assert_eq!(
+                secure_buffer_unseal(blob.as_ptr(), blob.len(), wrong.as_ptr(), &mut rejected),
+                SECURE_BUFFER_ERR_AUTH
+            );
+            assert!(rejected.is_null());
+            blob[0] = 2;
+            assert_eq!(
+                secure_buffer_unseal(blob.as_ptr(), blob.len(), kek.as_ptr(), &mut rejected),
+                SECURE_BUFFER_ERR_INVALID
+            );
+            assert!(rejected.is_null());
+
+            secure_buffer_destroy(restored);
+            secure_buffer_destroy(buf);
+        }
+    }
+
+    #[test]
+    fn test_write_at_ffi() {
+        unsafe {
+            let buf = secure_buffer_new(16);
+            assert_eq!(secure_buffer_write(buf, b"hello world".as_ptr(), 11), 0);
+            assert_eq!(secure_buffer_write_at(buf, 6, b"rusty".as_ptr(), 5), SECURE_BUFFER_OK);
+            assert_eq!((*(*buf).inner).as_slice().unwrap(), b"hello rusty");
+
+            // Extending at the end is allowed, gaps and capacity overflow are not
+            assert_eq!(secure_buffer_write_at(buf, 11, b"!!".as_ptr(), 2), SECURE_BUFFER_OK);
+            assert_eq!(secure_buffer_write_at(buf, 14, b"x".as_ptr(), 1), SECURE_BUFFER_ERR_OUT_OF_BOUNDS);
+            assert_eq!(secure_buffer_write_at(buf, 10, b"0123456789".as_ptr(), 10), SECURE_BUFFER_ERR_OUT_OF_BOUNDS);
+            assert_eq!(secure_buffer_write_at(buf, usize::MAX, b"x".as_ptr(), 1), SECURE_BUFFER_ERR_OUT_OF_BOUNDS);
+            assert_eq!((*(*buf).inner).as_slice().unwrap(), b"hello rusty!!");
+
+            // Overlapping source inside the buffer's own storage
+            let internal = (*(*buf).inner).data;
+            assert_eq!(secure_buffer_write_at(buf, 2, internal, 5), SECURE_BUFFER_OK);
+            assert_eq!((*(*buf).inner).as_slice().unwrap(), b"hehellousty!!");
+
+            assert_eq!(secure_buffer_write_at(std::ptr::null_mut(), 0, b"x".as_ptr(), 1), SECURE_BUFFER_ERR_INVALID);
+            assert_eq!(secure_buffer_write_at(buf, 0, std::ptr::null(), 1), SECURE_BUFFER_ERR_INVALID);
+            secure_buffer_destroy(buf);
+        }
+    }
+
+    fn buffer_with(data: &[u8]) -> SecureBuffer {
+        let mut buffer = SecureBuffer::new(64).unwrap();
+        buffer.write(data).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_hmac_rfc4231_case1() {
+        let buffer = buffer_with(b"Hi There");
+        let key = [0x0bu8; 20];
+        assert_eq!(
+            buffer.hmac_hex(&key).unwrap(),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            buffer.hmac_sha512_hex(&key).unwrap(),
+            "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+             daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854"
+        );
+    }
+
+    #[test]
+    fn test_hmac_rfc4231_case2() {
+        let buffer = buffer_with(b"what do ya want for nothing?");
+        assert_eq!(
+            buffer.hmac_hex(b"Jefe").unwrap(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            buffer.hmac_sha512_hex(b"Jefe").unwrap(),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+
+    #[test]
+    fn test_hmac_base64url_matches_hex() {
+        use base64::{Engine as _, engine::general_purpose};
+        let buffer = buffer_with(b"Hi There");
+        let key = [0x0bu8; 20];
+        let b64 = buffer.hmac_base64url(&key).unwrap();
+        let decoded = general_purpose::URL_SAFE_NO_PAD.decode(b64).unwrap();
+        assert_eq!(hex::encode(decoded), buffer.hmac_hex(&key).unwrap());
+        let b64 = buffer.hmac_sha512_base64url(&key).unwrap();
+        let decoded = general_purpose::URL_SAFE_NO_PAD.decode(b64).unwrap();
+        assert_eq!(hex::encode(decoded), buffer.hmac_sha512_hex(&key).unwrap());
+        assert!(buffer.hmac_hex(b"").is_err());
+    }
+
+    #[cfg(feature = "legacy-digest")]
+    #[test]
+    #[allow(deprecated)]
+    fn test_legacy_digest_differs_from_hmac() {
+        use sha2::{Digest, Sha256};
+        let buffer = buffer_with(b"payload");
+        let expected = hex::encode(Sha256::digest(b"keypayload"));
+        assert_eq!(buffer.legacy_digest_hex(b"key").unwrap(), expected);
+        assert_ne!(buffer.hmac_hex(b"key").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_tamper_detection_tracks_api_mutations() {
+        let mut buffer = SecureBuffer::new(16).unwrap();
+        buffer.write(b"initial").unwrap();
+        buffer.enable_tamper_detection().unwrap();
+        assert!(!buffer.is_tampered());
+
+        buffer.write(b"rewritten").unwrap();
+        buffer.append(b" and grown past capacity").unwrap();
+        buffer.write_at(0, b"R").unwrap();
+        buffer.resize(8).unwrap();
+        assert!(!buffer.is_tampered());
+        assert!(buffer.integrity_check());
+        buffer.clear();
+        assert!(!buffer.is_tampered());
+    }
+
+    #[test]
+    fn test_tamper_detection_detects_out_of_band_changes() {
+        let mut buffer = SecureBuffer::new(32).unwrap();
+        buffer.write(b"api-secret").unwrap();
+        buffer.enable_tamper_detection().unwrap();
+
+        // Simulate memory corruption that bypasses the API
+        unsafe { *buffer.data.add(3) ^= 0x01 };
+        assert!(buffer.is_tampered());
+        assert!(!buffer.integrity_check());
+
+        unsafe {
+            let ffi_ptr = &mut buffer as *mut SecureBuffer as *mut c_void;
+            assert_eq!(securebuffer_is_tampered(ffi_ptr), 1);
+        }
+    }
+
+    #[test]
+    fn test_tamper_detection_ffi() {
+        unsafe {
+            let ptr = securebuffer_new_with_security_level(16, 0);
+            let buffer = &mut *(ptr as *mut SecureBuffer);
+            buffer.write(b"abc").unwrap();
+            assert_eq!(securebuffer_enable_tamper_detection(ptr), 0);
+            assert!(buffer.is_tamper_detection_enabled());
+            assert_eq!(securebuffer_is_tampered(ptr), 0);
+            *buffer.data ^= 0xFF;
+            assert_eq!(securebuffer_is_tampered(ptr), 1);
+            secure_buffer_free(ptr);
+        }
+    }
+
+    const ALL_OPTIONS: [SecureBufferOptions; 4] = [
+        SecureBufferOptions { guard_pages: false, canary: false },
+        SecureBufferOptions { guard_pages: false, canary: true },
+        SecureBufferOptions { guard_pages: true, canary: false },
+        SecureBufferOptions { guard_pages: true, canary: true },
+    ];
+
+    #[test]
+    fn test_options_keep_capacity_and_len_semantics() {
+        for options in ALL_OPTIONS {
+            let mut buffer = SecureBuffer::with_options(100, options).unwrap();
+            assert_eq!(buffer.options(), options);
+            assert_eq!(buffer.capacity(), 100);
+            assert!(buffer.is_empty());
+
+            buffer.write(&[7u8; 100]).unwrap();
+            assert_eq!(buffer.len(), 100);
+            assert!(buffer.write(&[7u8; 101]).is_err());
+            assert!(buffer.write_at(90, &[1u8; 11]).is_err());
+
+            buffer.append(b"grown").unwrap();
+            assert_eq!(buffer.len(), 105);
+            assert!(buffer.capacity() >= 105);
+            buffer.resize(10).unwrap();
+            assert_eq!((buffer.len(), buffer.capacity()), (10, 10));
+            assert_eq!(buffer.as_slice().unwrap(), &[7u8; 10]);
+            assert!(buffer.integrity_check(), "{:?}", options);
+
+            buffer.destroy();
+            assert_eq!((buffer.len(), buffer.capacity()), (0, 0));
+            assert!(!buffer.audit_events().iter().any(|e| e.kind == AuditEventKind::CanaryViolation));
+        }
+        assert!(SecureBuffer::with_options(0, ALL_OPTIONS[3]).is_err());
+    }
+
+    #[test]
+    fn test_canary_violation_is_detected() {
+        for guard_pages in [false, true] {
+            let options = SecureBufferOptions { guard_pages, canary: true };
+
+            // One byte past the capacity lands in the trailing canary
+            let mut buffer = SecureBuffer::with_options(32, options).unwrap();
+            buffer.write(b"key material").unwrap();
+            assert!(!buffer.is_tampered());
+            unsafe { *buffer.data.add(buffer.capacity()) ^= 0x01 };
+            assert!(buffer.is_tampered());
+            assert!(!buffer.integrity_check());
+            buffer.destroy();
+            let kinds: Vec<_> = buffer.audit_events().iter().map(|e| e.kind).collect();
+            assert!(kinds.ends_with(&[AuditEventKind::CanaryViolation, AuditEventKind::Destroy]), "{:?}", kinds);
+
+            // And one byte before the data in the leading one
+            let buffer = SecureBuffer::with_options(32, options).unwrap();
+            unsafe { *buffer.data.sub(1) ^= 0x80 };
+            assert!(buffer.is_tampered());
+        }
+    }
+
+    #[test]
+    fn test_canaries_are_random_per_buffer() {
+        let options = SecureBufferOptions { guard_pages: false, canary: true };
+        let a = SecureBuffer::with_options(8, options).unwrap();
+        let b = SecureBuffer::with_options(8, options).unwrap();
+        assert_ne!(a.canary, b.canary);
+        assert_eq!(a.data as usize % 32, 0);
+    }
+
+    #[test]
+    fn test_guarded_data_ends_at_trailing_guard_page() {
+        let page = memory::page_size();
+        for options in [ALL_OPTIONS[2], ALL_OPTIONS[3]] {
+            let mut buffer = SecureBuffer::with_options(100, options).unwrap();
+            let end = buffer.data as usize + buffer.capacity() + options.tail();
+            assert_eq!(end % page, 0);
+            buffer.resize(page + 1).unwrap();
+            let end = buffer.data as usize + buffer.capacity() + options.tail();
+            assert_eq!(end % page, 0);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_overrun_into_guard_page_faults() {
+        let options = SecureBufferOptions { guard_pages: true, canary: false };
+        let buffer = SecureBuffer::with_options(64, options).unwrap();
+        let past_end = unsafe { buffer.data.add(buffer.capacity()) };
+        unsafe {
+            let pid = libc::fork();
+            assert!(pid >= 0);
+            if pid == 0 {
+                std::ptr::write_volatile(past_end, 0x41);
+                // Only reached if the write did not fault
+                libc::_exit(0);
+            }
+            let mut status = 0;
+            assert_eq!(libc::waitpid(pid, &mut status, 0), pid);
+            assert!(libc::WIFSIGNALED(status));
+            assert_eq!(libc::WTERMSIG(status), libc::SIGSEGV);
+        }
+    }
+
+    fn audit_kinds(buffer: &SecureBuffer) -> Vec<AuditEventKind> {
+        buffer.audit_events().iter().map(|e| e.kind).collect()
+    }
+
+    #[test]
+    fn test_audit_write_then_zeroize() {
+        let mut buffer = SecureBuffer::new(32).unwrap();
+        buffer.write(b"top-secret").unwrap();
+        buffer.zeroize();
+        let kinds: Vec<_> = audit_kinds(&buffer)
+            .into_iter()
+            .filter(|k| *k != AuditEventKind::LockFailed)
+            .collect();
+        assert_eq!(kinds, vec![AuditEventKind::Created, AuditEventKind::Write, AuditEventKind::Zeroize]);
+        let events = buffer.audit_events();
+        assert_eq!(events.iter().find(|e| e.kind == AuditEventKind::Write).unwrap().bytes_affected, 10);
+
+        let json = buffer.get_security_audit_log();
+        let doc: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(doc["events"].is_array());
+        assert!(!json.contains("top-secret"));
+    }
+
+    #[test]
+    fn test_audit_ring_buffer_caps() {
+        let mut buffer = SecureBuffer::new(8).unwrap();
+        buffer.set_audit_capacity(3);
+        for _ in 0..10 {
+            buffer.write(b"x").unwrap();
+        }
+        let kinds = audit_kinds(&buffer);
+        assert_eq!(kinds, vec![AuditEventKind::Write; 3]);
+        let doc: serde_json::Value = serde_json::from_str(&buffer.get_security_audit_log()).unwrap();
+        assert_eq!(doc["capacity"], 3);
+        assert!(doc["dropped"].as_u64().unwrap() >= 8);
+
+        buffer.disable_audit_logging();
+        buffer.write(b"y").unwrap();
+        assert_eq!(buffer.audit_events().len(), 3);
+        assert!(!buffer.is_audit_logging_enabled());
+    }
+
+    #[test]
+    fn test_audit_sink_receives_events() {
+        use std::sync::{Arc, Mutex};
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink_seen = seen.clone();
+        let mut buffer = SecureBuffer::new(8).unwrap();
+        buffer.set_audit_sink(Box::new(move |event| sink_seen.lock().unwrap().push(event.kind)));
+        buffer.write(b"abc").unwrap();
+        let mut out = [0u8; 8];
+        buffer.read(&mut out).unwrap();
+        drop(buffer);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![AuditEventKind::Write, AuditEventKind::Read, AuditEventKind::Destroy]
+        );
+    }
+
+    #[test]
+    fn test_audit_sink_may_read_the_log() {
+        use std::sync::{Arc, Mutex, OnceLock, Weak};
+        let slot: Arc<OnceLock<Weak<SecureBuffer>>> = Arc::new(OnceLock::new());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (sink_slot, sink_seen) = (slot.clone(), seen.clone());
+        let mut buffer = SecureBuffer::new(8).unwrap();
+        buffer.write(b"abc").unwrap();
+        buffer.set_audit_sink(Box::new(move |_| {
+            if let Some(buffer) = sink_slot.get().and_then(Weak::upgrade) {
+                sink_seen.lock().unwrap().push(buffer.audit_events().len());
+            }
+        }));
+        let buffer = Arc::new(buffer);
+        slot.set(Arc::downgrade(&buffer)).unwrap();
+
+        let mut out = [0u8; 8];
+        buffer.read(&mut out).unwrap();
+        buffer.read(&mut out).unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![3, 4]);
+    }
+
+    #[test]
+    fn test_audit_log_ffi_returns_json() {
+        unsafe {
+            let ptr = securebuffer_new_with_security_level(16, 0);
+            let log = securebuffer_get_security_audit_log(ptr);
+            assert!(!log.is_null());
+            let json = CStr::from_ptr(log).to_str().unwrap().to_string();
+            securebuffer_free_cstr(log);
+            assert!(json.contains("\"created\""));
+            secure_buffer_free(ptr);
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter_save_load_ffi() {
+        let path = std::env::temp_dir().join(format!("ublf_ffi_{}.bin", std::process::id()));
+        let path_c = CString::new(path.to_str().unwrap()).unwrap();
+        let bitcoin = CString::new("bitcoin").unwrap();
+        let solana = CString::new("solana").unwrap();
+        let txid = [3u8; 32];
+        unsafe {
+            let filter = universal_bloom_filter_new_default();
+            assert_eq!(universal_bloom_filter_insert_utxo(filter, txid.as_ptr(), 1), 0);
+            assert_eq!(universal_bloom_filter_save(filter, path_c.as_ptr()), 0);
+
+            let mut loaded: UniversalBloomFilterHandle = std::ptr::null_mut();
+            assert_eq!(
+                universal_bloom_filter_load(path_c.as_ptr(), solana.as_ptr(), &mut loaded),
+                UniversalBloomFilterError::NetworkMismatch as c_int
+            );
+            assert!(loaded.is_null());
+            assert_eq!(universal_bloom_filter_load(path_c.as_ptr(), bitcoin.as_ptr(), &mut loaded), 0);
+            assert_eq!(universal_bloom_filter_contains_utxo(loaded, txid.as_ptr(), 1), 1);
+            assert_eq!(universal_bloom_filter_contains_utxo(loaded, txid.as_ptr(), 2), 0);
+
+            universal_bloom_filter_destroy(filter);
+            universal_bloom_filter_destroy(loaded);
+        }
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_bloom_filter_ffi_uses_filter_network() {
+        let ethereum = CString::new("ethereum").unwrap();
+        let solana = CString::new("solana").unwrap();
+        let hash = [0xabu8; 32];
+        unsafe {
+            let filter = universal_bloom_filter_new(65_536, 5, 0, 0, 86_400, 1024, ethereum.as_ptr());
+            assert!(!filter.is_null());
+            let filter_ref = &*(filter as *const UniversalBloomFilter);
+            assert_eq!(filter_ref.network_name(), "ethereum");
+
+            assert_eq!(universal_bloom_filter_insert_utxo(filter, hash.as_ptr(), 0), 0);
+            assert!(filter_ref.contains_utxo(&TransactionId::new("ethereum", &hash), 0).unwrap());
+            assert_eq!(universal_bloom_filter_contains_utxo(filter, hash.as_ptr(), 0), 1);
+
+            filter_ref.insert_utxo(&TransactionId::new("ethereum", &hash), 1).unwrap();
+            assert_eq!(universal_bloom_filter_contains_utxo(filter, hash.as_ptr(), 1), 1);
+
+            // Ids from another network live in their own namespace
+            assert_eq!(universal_bloom_filter_insert_utxo_for_network(filter, hash.as_ptr(), 2, solana.as_ptr()), 0);
+            assert_eq!(universal_bloom_filter_contains_utxo_for_network(filter, hash.as_ptr(), 2, solana.as_ptr()), 1);
+            assert!(filter_ref.contains_utxo(&TransactionId::new("solana", &hash), 2).unwrap());
+            assert_eq!(universal_bloom_filter_contains_utxo(filter, hash.as_ptr(), 2), 0);
+            assert_eq!(
+                universal_bloom_filter_contains_utxo_for_network(filter, hash.as_ptr(), 0, ethereum.as_ptr()),
+                1
+            );
+
+            universal_bloom_filter_destroy(filter);
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter_get_stats_ffi_reports_saturation() {
+        let bitcoin = CString::new("bitcoin").unwrap();
+        let (mut items, mut fps, mut memory, mut timestamps) = (0u64, 0u64, 0usize, 0usize);
+        let (mut theoretical, mut age, mut saturation) = (0.0, 0.0, -1.0);
+        unsafe {
+            let filter = universal_bloom_filter_new(1024, 3, 0, 0, 86_400, 1024, bitcoin.as_ptr());
+            assert!(!filter.is_null());
+            for i in 0u8..100 {
+                assert_eq!(universal_bloom_filter_insert_utxo(filter, [i; 32].as_ptr(), 0), 0);
+            }
+            assert_eq!(
+                universal_bloom_filter_get_stats(
+                    filter, &mut items, &mut fps, &mut theoretical, &mut memory, &mut timestamps, &mut age,
+                    &mut saturation,
+                ),
+                0
+            );
+            let filter_ref = &*(filter as *const UniversalBloomFilter);
+            assert_eq!(items, 100);
+            assert_eq!(saturation, filter_ref.stats().saturation_ratio);
+            assert!(saturation > 0.5);
+
+            assert_eq!(
+                universal_bloom_filter_get_stats(
+                    filter, &mut items, &mut fps, &mut theoretical, &mut memory, &mut timestamps, &mut age,
+                    std::ptr::null_mut(),
+                ),
+                UniversalBloomFilterError::NullPointer as c_int
+            );
+            universal_bloom_filter_destroy(filter);
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter_fp_metrics_ffi() {
+        unsafe {
+            let filter = bloom_filter_new(32_768, 5);
+            assert!(!filter.is_null());
+            for i in 0u32..100 {
+                assert_eq!(bloom_filter_insert(filter, i.to_le_bytes().as_ptr(), 4), 0);
+            }
+            let mut hits = 0;
+            for i in 0u32..200 {
+                if bloom_filter_contains(filter, i.to_le_bytes().as_ptr(), 4) == 1 {
+                    hits += 1;
+                    // Items 100.. were never inserted, so any hit there is a false positive
+                    if i >= 100 {
+                        assert_eq!(bloom_filter_confirm_false_positive(filter), 0);
+                    }
+                }
+            }
+            assert!(hits >= 100);
+            assert_eq!(bloom_filter_confirm_false_positive(filter), 0);
+
+            let confirmed = (hits - 100 + 1) as u64;
+            assert_eq!(bloom_filter_query_count(filter), 200);
+            assert_eq!(bloom_filter_observed_fp_count(filter), confirmed);
+            assert!((bloom_filter_observed_fp_rate(filter) - confirmed as f64 / 200.0).abs() < 1e-12);
+
+            let theoretical = bloom_filter_theoretical_fp_rate(filter);
+            assert!(theoretical > 0.0 && theoretical < 0.01);
+            assert_eq!(bloom_filter_false_positive_rate(filter), theoretical);
+
+            assert_eq!(bloom_filter_confirm_false_positive(std::ptr::null_mut()), -1);
+            bloom_filter_free(filter);
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter_load_block_ffi_parses_wire_format() {
+        // Testnet3 genesis block
+        let block = hex::decode(concat!(
+            "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e",
+            "67768f617fc81bc3888a51323a9fb8aa4b1e5e4adae5494dffff001d1aa4ae1801010000000100000000000000000000",
+            "00000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f",
+            "4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f75742066",
+            "6f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a6",
+            "7962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000",
+        ))
+        .unwrap();
+        let mut txid = hex::decode("4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b").unwrap();
+        txid.reverse();
+
+        unsafe {
+            let filter = universal_bloom_filter_new_default();
+            assert_eq!(universal_bloom_filter_load_block(filter, block.as_ptr(), block.len()), 0);
+            let filter_ref = &*(filter as *const UniversalBloomFilter);
+            assert!(filter_ref.contains_data(&txid).unwrap());
+
+            #[cfg(not(feature = "legacy-block-layout"))]
+            {
+                let garbage = [0x42u8; 100];
+                assert_eq!(
+                    universal_bloom_filter_load_block(filter, garbage.as_ptr(), garbage.len()),
+                    UniversalBloomFilterError::BlockParseError as c_int
+                );
+                assert_eq!(filter_ref.get_item_count(), 1);
+            }
+            universal_bloom_filter_destroy(filter);
+        }
+    }
+
+    #[test]
+    fn test_resize_rejects_invalid_arguments() {
+        let mut buffer = SecureBuffer::new(8).unwrap();
+        assert!(buffer.resize(0).is_err());
+        assert!(buffer.set_growth_factor(1.0).is_err());
+        assert!(buffer.set_growth_factor(f64::NAN).is_err());
+        buffer.destroy();
+        assert!(buffer.append(b"x").is_err());
+    }
+}
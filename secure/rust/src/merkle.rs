@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - SHA-256 Merkle trees for storage commitments
+
+//! Binary SHA-256 Merkle tree over chunk leaf hashes.
+//!
+//! Parents are `sha256(left || right)`. A level with an odd number of nodes pairs its
+//! last node with itself, so a proof always has one sibling per level. Each proof step
+//! records which side its sibling sits on; read bottom-up, those flags spell out the
+//! leaf index in binary.
+
+use sha2::{Digest, Sha256};
+
+/// One level of an inclusion proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofStep {
+    pub sibling: [u8; 32],
+    /// The sibling is the left input when hashing this level
+    pub is_left: bool,
+}
+
+/// Inclusion proof as supplied by a storage provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MerkleProof {
+    /// Sibling hashes (hex) from the leaf upwards, each with an is-left-sibling flag
+    Pairs(Vec<(String, bool)>),
+    /// Sibling hashes (hex) from the leaf upwards; bit `i` of `left_mask` is set when the
+    /// sibling at level `i` is on the left
+    Bitmask { hashes: Vec<String>, left_mask: u64 },
+    /// Deprecated: hashes only, always combined as `current || sibling`. This only
+    /// verifies leftmost paths and is accepted only when explicitly enabled.
+    Legacy(Vec<String>),
+}
+
+impl MerkleProof {
+    /// Encode proof steps in the pairs format
+    pub fn from_steps(steps: &[ProofStep]) -> Self {
+        MerkleProof::Pairs(steps.iter().map(|s| (hex::encode(s.sibling), s.is_left)).collect())
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, MerkleProof::Legacy(_))
+    }
+
+    /// Decode sibling hashes and their positions
+    pub fn steps(&self) -> Result<Vec<ProofStep>, String> {
+        match self {
+            MerkleProof::Pairs(pairs) => pairs
+                .iter()
+                .map(|(hash, is_left)| Ok(ProofStep { sibling: decode_hash(hash)?, is_left: *is_left }))
+                .collect(),
+            MerkleProof::Bitmask { hashes, left_mask } => {
+                if hashes.len() > 64 {
+                    return Err(format!("{} levels do not fit a 64-bit position mask", hashes.len()));
+                }
+                hashes
+                    .iter()
+                    .enumerate()
+                    .map(|(level, hash)| {
+                        Ok(ProofStep { sibling: decode_hash(hash)?, is_left: left_mask >> level & 1 == 1 })
+                    })
+                    .collect()
+            }
+            MerkleProof::Legacy(hashes) => hashes
+                .iter()
+                .map(|hash| Ok(ProofStep { sibling: decode_hash(hash)?, is_left: false }))
+                .collect(),
+        }
+    }
+}
+
+fn decode_hash(hash: &str) -> Result<[u8; 32], String> {
+    let bytes = hex::decode(hash.trim_start_matches("0x")).map_err(|_| format!("invalid hex in proof element: {}", hash))?;
+    bytes.try_into().map_err(|b: Vec<u8>| format!("proof element is {} bytes, expected 32", b.len()))
+}
+
+/// Leaf hash of a chunk
+pub fn hash_leaf(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+/// Number of proof steps for a tree with `leaf_count` leaves
+pub fn depth(leaf_count: u64) -> usize {
+    if leaf_count <= 1 {
+        0
+    } else {
+        (u64::BITS - (leaf_count - 1).leading_zeros()) as usize
+    }
+}
+
+/// Root of the tree over `leaves`, or `None` when there are no leaves
+pub fn build_root(leaves: &[[u8; 32]]) -> Option<[u8; 32]> {
+    if leaves.is_empty() {
+        return None;
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    Some(level[0])
+}
+
+/// Inclusion proof for `leaves[index]`, or `None` when the index is out of range
+pub fn generate_proof(leaves: &[[u8; 32]], index: usize) -> Option<Vec<ProofStep>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut steps = Vec::with_capacity(depth(leaves.len() as u64));
+    let mut level = leaves.to_vec();
+    let mut position = index;
+    while level.len() > 1 {
+        let is_left = position % 2 == 1;
+        let sibling = if is_left { level[position - 1] } else { *level.get(position + 1).unwrap_or(&level[position]) };
+        steps.push(ProofStep { sibling, is_left });
+        level = next_level(&level);
+        position /= 2;
+    }
+    Some(steps)
+}
+
+/// Leaf index encoded by the steps' position flags
+pub fn proof_index(steps: &[ProofStep]) -> u64 {
+    steps
+        .iter()
+        .enumerate()
+        .filter(|(_, step)| step.is_left)
+        .fold(0, |index, (level, _)| index | 1 << level)
+}
+
+/// Root reached by hashing `leaf` up through `steps`
+pub fn compute_root(leaf: [u8; 32], steps: &[ProofStep]) -> [u8; 32] {
+    steps.iter().fold(leaf, |current, step| {
+        if step.is_left {
+            hash_pair(&step.sibling, &current)
+        } else {
+            hash_pair(&current, &step.sibling)
+        }
+    })
+}
+
+/// Check that `leaf` sits at `index` in the `leaf_count`-leaf tree with the given root
+pub fn verify(root: &[u8; 32], leaf: [u8; 32], index: u64, leaf_count: u64, steps: &[ProofStep]) -> bool {
+    index < leaf_count
+        && steps.len() == depth(leaf_count)
+        && proof_index(steps) == index
+        && compute_root(leaf, steps) == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<[u8; 32]> {
+        (0..n).map(|i| hash_leaf(format!("chunk-{}", i).as_bytes())).collect()
+    }
+
+    #[test]
+    fn test_every_leaf_round_trips_for_odd_and_even_trees() {
+        for n in [1, 2, 3, 5, 8, 13] {
+            let leaves = leaves(n);
+            let root = build_root(&leaves).unwrap();
+            for (i, leaf) in leaves.iter().enumerate() {
+                let steps = generate_proof(&leaves, i).unwrap();
+                assert_eq!(steps.len(), depth(n as u64));
+                assert!(verify(&root, *leaf, i as u64, n as u64, &steps), "n={} i={}", n, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_root_of_pair_is_hash_of_concatenation() {
+        let leaves = leaves(2);
+        assert_eq!(build_root(&leaves).unwrap(), hash_pair(&leaves[0], &leaves[1]));
+        assert_eq!(build_root(&[]), None);
+        assert_eq!(generate_proof(&leaves, 2), None);
+    }
+
+    #[test]
+    fn test_pairs_and_bitmask_formats_decode_to_the_same_steps() {
+        let leaves = leaves(6);
+        let steps = generate_proof(&leaves, 5).unwrap();
+        let bitmask = MerkleProof::Bitmask {
+            hashes: steps.iter().map(|s| format!("0x{}", hex::encode(s.sibling))).collect(),
+            left_mask: proof_index(&steps),
+        };
+        assert_eq!(MerkleProof::from_steps(&steps).steps().unwrap(), steps);
+        assert_eq!(bitmask.steps().unwrap(), steps);
+        assert!(MerkleProof::Legacy(vec!["zz".to_string()]).steps().is_err());
+    }
+
+    #[test]
+    fn test_rejects_wrong_index_swapped_siblings_and_truncation() {
+        let leaves = leaves(7);
+        let root = build_root(&leaves).unwrap();
+        let steps = generate_proof(&leaves, 3).unwrap();
+
+        assert!(!verify(&root, leaves[3], 2, 7, &steps));
+        assert!(!verify(&root, leaves[2], 2, 7, &steps));
+
+        let mut swapped = steps.clone();
+        swapped[0].is_left = !swapped[0].is_left;
+        assert!(!verify(&root, leaves[3], 3, 7, &swapped));
+
+        assert!(!verify(&root, leaves[3], 3, 7, &steps[..steps.len() - 1]));
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Sealed SecureBuffer export
+
+//! Sealing a `SecureBuffer` under a key-encryption key (KEK).
+//!
+//! `seal` encrypts the buffer content with XChaCha20-Poly1305 under a fresh random
+//! 24-byte nonce. The blob is `version | nonce | length | ciphertext | tag`, where
+//! `length` is the plaintext length as a big-endian u64 and the header before the
+//! ciphertext is authenticated as associated data.
+//!
+//! `unseal` copies the ciphertext into a freshly allocated locked buffer and decrypts
+//! it there, so the plaintext never lands in ordinary heap memory. The tag is checked
+//! before any byte is decrypted; on failure the buffer is zeroized and dropped and the
+//! caller gets an error, never a partially populated buffer.
+
+use chacha20poly1305::aead::{AeadInPlace, KeyInit};
+use chacha20poly1305::{Key, Tag, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::audit::AuditEventKind;
+use crate::SecureBuffer;
+
+/// Version byte written at the start of every sealed blob
+pub const SEAL_VERSION: u8 = 1;
+/// Length of the KEK accepted by `seal` and `unseal`
+pub const SEAL_KEK_LEN: usize = 32;
+
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = 1 + NONCE_LEN + 8;
+const TAG_LEN: usize = 16;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SealError {
+    #[error("unsupported sealed blob version {0}")]
+    UnsupportedVersion(u8),
+    #[error("sealed blob is truncated or its length does not match the header")]
+    Malformed,
+    #[error("sealed blob failed authentication (tampered or wrong KEK)")]
+    Authentication,
+    #[error("buffer is not valid")]
+    InvalidBuffer,
+    #[error("secure buffer error: {0}")]
+    Buffer(String),
+}
+
+/// Total blob size for `plaintext_len` bytes of content
+pub fn sealed_len(plaintext_len: usize) -> usize {
+    HEADER_LEN + plaintext_len + TAG_LEN
+}
+
+impl SecureBuffer {
+    /// Encrypt the content under `kek` into a self-describing blob for `unseal`.
+    /// Fails only if the buffer has been destroyed.
+    pub fn seal(&self, kek: &[u8; SEAL_KEK_LEN]) -> Result<Vec<u8>, SealError> {
+        if !self.is_valid() {
+            return Err(SealError::InvalidBuffer);
+        }
+        let content = self.content();
+
+        let mut blob = Vec::with_capacity(sealed_len(content.len()));
+        blob.push(SEAL_VERSION);
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&(content.len() as u64).to_be_bytes());
+
+        // Encrypt a zeroizing copy so no plaintext outlives this call on the heap
+        let mut body = Zeroizing::new(content.to_vec());
+        let tag = XChaCha20Poly1305::new(Key::from_slice(kek))
+            .encrypt_in_place_detached(XNonce::from_slice(&nonce), &blob[..HEADER_LEN], body.as_mut_slice())
+            .map_err(|_| SealError::Malformed)?;
+        blob.extend_from_slice(&body);
+        blob.extend_from_slice(&tag);
+        self.audit(AuditEventKind::Read, content.len());
+        Ok(blob)
+    }
+
+    /// Decrypt a blob produced by `seal` into a new locked buffer sized to the content
+    pub fn unseal(blob: &[u8], kek: &[u8; SEAL_KEK_LEN]) -> Result<SecureBuffer, SealError> {
+        let version = *blob.first().ok_or(SealError::Malformed)?;
+        if version != SEAL_VERSION {
+            return Err(SealError::UnsupportedVersion(version));
+        }
+        if blob.len() < sealed_len(0) {
+            return Err(SealError::Malformed);
+        }
+        let (header, rest) = blob.split_at(HEADER_LEN);
+        let nonce = XNonce::from_slice(&header[1..1 + NONCE_LEN]);
+        let length = u64::from_be_bytes(header[1 + NONCE_LEN..].try_into().expect("8-byte length"));
+        let length = usize::try_from(length).map_err(|_| SealError::Malformed)?;
+        if rest.len() != length.checked_add(TAG_LEN).ok_or(SealError::Malformed)? {
+            return Err(SealError::Malformed);
+        }
+        let (ciphertext, tag) = rest.split_at(length);
+
+        // An empty secret still needs a non-zero capacity
+        let mut buffer = SecureBuffer::new(length.max(1)).map_err(SealError::Buffer)?;
+        let region = unsafe {
+            std::ptr::copy_nonoverlapping(ciphertext.as_ptr(), buffer.data, length);
+            std::slice::from_raw_parts_mut(buffer.data, length)
+        };
+        // Verifies the tag before decrypting; on error `buffer` still holds only ciphertext
+        // and is zeroized when it drops
+        XChaCha20Poly1305::new(Key::from_slice(kek))
+            .decrypt_in_place_detached(nonce, header, region, Tag::from_slice(tag))
+            .map_err(|_| SealError::Authentication)?;
+
+        buffer.length = length;
+        buffer.refresh_tamper_checksum();
+        buffer.audit(AuditEventKind::Write, length);
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEK: [u8; 32] = [7u8; 32];
+
+    fn sealed(content: &[u8]) -> Vec<u8> {
+        let mut buffer = SecureBuffer::new(64).unwrap();
+        buffer.write(content).unwrap();
+        buffer.seal(&KEK).unwrap()
+    }
+
+    #[test]
+    fn round_trip_restores_content() {
+        let blob = sealed(b"wallet-seed-material");
+        assert_eq!(blob.len(), sealed_len(20));
+        assert_eq!(blob[0], SEAL_VERSION);
+
+        let restored = SecureBuffer::unseal(&blob, &KEK).unwrap();
+        assert_eq!(restored.as_slice().unwrap(), b"wallet-seed-material");
+        assert_eq!(restored.capacity(), 20);
+
+        let empty = SecureBuffer::unseal(&sealed(b""), &KEK).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn nonces_are_fresh_per_seal() {
+        assert_ne!(sealed(b"same")[1..1 + NONCE_LEN], sealed(b"same")[1..1 + NONCE_LEN]);
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let blob = sealed(b"wallet-seed-material");
+        // Ciphertext, tag, nonce and length-header bytes are all covered
+        for index in [HEADER_LEN + 3, blob.len() - 1, 5] {
+            let mut tampered = blob.clone();
+            tampered[index] ^= 0x01;
+            assert_eq!(SecureBuffer::unseal(&tampered, &KEK).err(), Some(SealError::Authentication));
+        }
+
+        let mut resized = blob.clone();
+        resized[HEADER_LEN - 1] ^= 0x01;
+        assert_eq!(SecureBuffer::unseal(&resized, &KEK).err(), Some(SealError::Malformed));
+        assert_eq!(SecureBuffer::unseal(&blob[..blob.len() - 1], &KEK).err(), Some(SealError::Malformed));
+        assert_eq!(SecureBuffer::unseal(&[], &KEK).err(), Some(SealError::Malformed));
+    }
+
+    #[test]
+    fn wrong_kek_is_rejected() {
+        let blob = sealed(b"wallet-seed-material");
+        assert_eq!(SecureBuffer::unseal(&blob, &[8u8; 32]).err(), Some(SealError::Authentication));
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let mut blob = sealed(b"wallet-seed-material");
+        blob[0] = SEAL_VERSION + 1;
+        assert_eq!(
+            SecureBuffer::unseal(&blob, &KEK).err(),
+            Some(SealError::UnsupportedVersion(SEAL_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn destroyed_buffers_cannot_be_sealed() {
+        let mut buffer = SecureBuffer::new(8).unwrap();
+        buffer.destroy();
+        assert_eq!(buffer.seal(&KEK), Err(SealError::InvalidBuffer));
+    }
+}
@@ -1,13 +1,18 @@
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{SystemTime, Duration, Instant};
 use anyhow::{Result, Context, anyhow};
-use tokio::net::TcpStream;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::Mutex;
+use tokio::task::JoinSet;
 use tokio::time::{interval, Duration as TokioDuration};
 use tokio_rustls::{TlsConnector, client::TlsStream};
-use rustls::{ClientConfig, RootCertStore, ClientSessionMemoryCache, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use rustls::client::Resumption;
+use rustls::crypto::{aws_lc_rs, CryptoProvider};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::pki_types::pem::PemObject;
 use tracing::{info, warn, error, span, Level};
 use url::Url;
 use async_trait::async_trait;
@@ -15,9 +20,17 @@
 use tokio_metrics::TaskMonitor;
 use hdrhistogram::Histogram;
 use prometheus::{Encoder, TextEncoder, Histogram as PromHistogram, HistogramOpts, IntCounter, IntGauge, Registry};
-use hyper::{Body, Response, Server, StatusCode};
-use hyper::service::{make_service_fn, service_fn};
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
 use serde::Serialize;
 use std::sync::RwLock;
 
@@ -25,6 +38,14 @@
 static CIRCUIT_BREAKER_FAILURES: AtomicU64 = AtomicU64::new(0);
 static CIRCUIT_BREAKER_LAST_FAILURE: AtomicU64 = AtomicU64::new(0);
 
+/// Handshakes run at once while warming up the pool
+const WARM_UP_CONCURRENCY: usize = 4;
+
+/// Application-level liveness check run on an idle connection before it is handed out
+pub type PingFn = Arc<
+    dyn for<'a> Fn(&'a mut SecureChannel) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> + Send + Sync,
+>;
+
 /// Connection pool configuration
 #[derive(Clone)]
 struct PoolConfig {
@@ -40,6 +61,10 @@ struct PoolConfig {
     circuit_breaker_failure_threshold: u64,
     circuit_breaker_cooldown: Duration,
     metrics_auth_token: Option<String>,
+    liveness_probe_timeout: Duration,
+    ping: Option<PingFn>,
+    warm_up_on_build: bool,
+    warm_up_deadline: Duration,
 }
 
 impl Default for PoolConfig {
@@ -57,6 +82,10 @@ fn default() -> Self {
             circuit_breaker_failure_threshold: 5, // 5 consecutive failures
             circuit_breaker_cooldown: Duration::from_secs(60), // 1 minute cooldown
             metrics_auth_token: None, // No auth by default
+            liveness_probe_timeout: Duration::from_millis(1),
+            ping: None,
+            warm_up_on_build: false,
+            warm_up_deadline: Duration::from_secs(10),
         }
     }
 }
@@ -82,12 +111,21 @@ struct ConnectionStatus {
 struct PoolStatus {
     endpoint: String,
     active_connections: usize,
+    idle_connections: usize,
+    checked_out_connections: usize,
     total_reconnects: u64,
     total_errors: u64,
     pool_p95_latency_ms: u64,
     connections: Vec<ConnectionStatus>,
 }
 
+/// Outcome of pre-filling the pool with idle connections
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WarmUpSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
 #[derive(Serialize)]
 struct HealthStatus {
     status: String,
@@ -101,9 +139,9 @@ pub struct PoolMetrics {
     prom_active_connections: IntGauge,
     prom_total_reconnects: IntCounter,
     prom_total_errors: IntCounter,
+    prom_dead_connections: IntCounter,
     prom_latency: PromHistogram,
     registry: Arc<Registry>,
-    endpoint: String,
 }
 
 impl PoolMetrics {
@@ -129,6 +167,13 @@ fn new(registry: Arc<Registry>, endpoint: &str, namespace: &str) -> Result<Self>
             ).const_label("endpoint", endpoint)
         )?;
         
+        let prom_dead_connections = IntCounter::with_opts(
+            prometheus::Opts::new(
+                format!("{}_dead_connections_total", namespace),
+                "Total number of idle connections found dead at checkout"
+            ).const_label("endpoint", endpoint)
+        )?;
+        
         let prom_latency = PromHistogram::with_opts(
             HistogramOpts::new(
                 format!("{}_latency_ms", namespace),
@@ -141,15 +186,16 @@ fn new(registry: Arc<Registry>, endpoint: &str, namespace: &str) -> Result<Self>
         registry.register(Box::new(prom_active_connections.clone()))?;
         registry.register(Box::new(prom_total_reconnects.clone()))?;
         registry.register(Box::new(prom_total_errors.clone()))?;
+        registry.register(Box::new(prom_dead_connections.clone()))?;
         registry.register(Box::new(prom_latency.clone()))?;
 
         Ok(PoolMetrics {
             prom_active_connections,
             prom_total_reconnects,
             prom_total_errors,
+            prom_dead_connections,
             prom_latency,
             registry,
-            endpoint: endpoint.to_string(),
         })
     }
 
@@ -166,9 +212,18 @@ fn increment_errors(&self) {
         self.prom_total_errors.inc();
     }
 
+    fn increment_dead_connections(&self) {
+        self.prom_dead_connections.inc();
+    }
+
     fn set_active_connections(&self, count: usize) {
         self.prom_active_connections.set(count as i64);
     }
+
+    #[cfg(test)]
+    fn active_connections(&self) -> i64 {
+        self.prom_active_connections.get()
+    }
 }
 
 /// Per-connection metrics (lightweight, no Prometheus registration)
@@ -214,7 +269,7 @@ fn is_slow(&self, threshold_ms: u64) -> bool {
     }
 
     fn rotate_histogram_if_needed(&mut self, rotation_interval: Duration) {
-        if self.last_histogram_rotation.elapsed().map_or(false, |elapsed| elapsed > rotation_interval) {
+        if self.last_histogram_rotation.elapsed().is_ok_and(|elapsed| elapsed > rotation_interval) {
             if let Ok(mut hist) = self.latency_histogram.write() {
                 *hist = Histogram::<u64>::new_with_bounds(1, 60_000, 3)
                     .expect("Failed to create histogram");
@@ -261,10 +316,97 @@ pub struct SecureChannel {
     pool_metrics: Arc<PoolMetrics>,
 }
 
+/// A connection checked out of a SecureChannelPool
+///
+/// Derefs to the underlying SecureChannel and returns it to the pool's idle list when
+/// dropped. Call `poison()` after an I/O error so the connection is closed instead.
+pub struct PooledConnection {
+    conn: Option<SecureChannel>,
+    poisoned: bool,
+    idle: Arc<Mutex<Vec<SecureChannel>>>,
+    checked_out: Arc<AtomicUsize>,
+    pool_metrics: Arc<PoolMetrics>,
+}
+
+impl PooledConnection {
+    /// Mark the connection as broken so it is discarded rather than reused
+    pub fn poison(&mut self) {
+        self.poisoned = true;
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    pub fn connection_id(&self) -> usize {
+        self.metrics.connection_id
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = SecureChannel;
+
+    fn deref(&self) -> &SecureChannel {
+        self.conn.as_ref().expect("connection present until drop")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut SecureChannel {
+        self.conn.as_mut().expect("connection present until drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let conn = match self.conn.take() {
+            Some(conn) => conn,
+            None => return,
+        };
+
+        if self.poisoned {
+            warn!("Discarding poisoned connection {}", conn.metrics.connection_id);
+            let checked_out = self.checked_out.fetch_sub(1, Ordering::AcqRel) - 1;
+            if let Ok(idle) = self.idle.try_lock() {
+                self.pool_metrics.set_active_connections(idle.len() + checked_out);
+            }
+            return;
+        }
+
+        // The slot is released only after the connection is back in the idle list, so
+        // idle + checked out never undercounts what is open
+        match self.idle.try_lock() {
+            Ok(mut idle) => {
+                idle.push(conn);
+                let checked_out = self.checked_out.fetch_sub(1, Ordering::AcqRel) - 1;
+                self.pool_metrics.set_active_connections(idle.len() + checked_out);
+            }
+            Err(_) => match tokio::runtime::Handle::try_current() {
+                Ok(handle) => {
+                    let idle = self.idle.clone();
+                    let checked_out = self.checked_out.clone();
+                    let pool_metrics = self.pool_metrics.clone();
+                    handle.spawn(async move {
+                        let mut idle = idle.lock().await;
+                        idle.push(conn);
+                        let checked_out = checked_out.fetch_sub(1, Ordering::AcqRel) - 1;
+                        pool_metrics.set_active_connections(idle.len() + checked_out);
+                    });
+                }
+                Err(_) => {
+                    warn!("No runtime to return connection {} to the pool, closing it", conn.metrics.connection_id);
+                    self.checked_out.fetch_sub(1, Ordering::AcqRel);
+                }
+            },
+        }
+    }
+}
+
 /// Builder for SecureChannelPool configuration
 pub struct PoolBuilder {
     endpoint: String,
     root_store: Option<RootCertStore>,
+    client_cert: Option<(String, String)>,
     config: PoolConfig,
 }
 
@@ -274,6 +416,7 @@ pub fn new(endpoint: &str) -> Self {
         PoolBuilder {
             endpoint: endpoint.to_string(),
             root_store: None,
+            client_cert: None,
             config: PoolConfig::default(),
         }
     }
@@ -284,6 +427,12 @@ pub fn with_root_store(mut self, root_store: RootCertStore) -> Self {
         self
     }
 
+    /// Present a client certificate for mTLS to the upstream (PEM chain and PEM private key)
+    pub fn with_client_cert(mut self, cert_pem: &str, key_pem: &str) -> Self {
+        self.client_cert = Some((cert_pem.to_string(), key_pem.to_string()));
+        self
+    }
+
     /// Set the metrics namespace (default: "secure_channel")
     pub fn with_namespace(mut self, namespace: &str) -> Self {
         self.config.namespace = namespace.to_string();
@@ -356,8 +505,59 @@ pub fn with_circuit_breaker_cooldown(mut self, cooldown: Duration) -> Self {
         self
     }
 
+    /// Set how long checkout waits for an idle connection to show EOF or a reset (default: 1ms)
+    pub fn with_liveness_probe_timeout(mut self, timeout: Duration) -> Self {
+        self.config.liveness_probe_timeout = timeout;
+        self
+    }
+
+    /// Register an application-level ping (e.g. a protocol ping frame) run at checkout
+    pub fn with_ping<F>(mut self, ping: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut SecureChannel) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.config.ping = Some(Arc::new(ping));
+        self
+    }
+
+    /// Warm up min_idle connections when started with build_and_start (default: false)
+    pub fn with_warm_up_on_build(mut self, warm_up: bool) -> Self {
+        self.config.warm_up_on_build = warm_up;
+        self
+    }
+
+    /// Set how long warm-up may spend establishing connections (default: 10 seconds)
+    pub fn with_warm_up_deadline(mut self, deadline: Duration) -> Self {
+        self.config.warm_up_deadline = deadline;
+        self
+    }
+
+    /// Build the pool, warm it up if enabled, and spawn the cleanup and metrics tasks
+    pub async fn build_and_start(self) -> Result<Arc<SecureChannelPool>> {
+        let pool = Arc::new(self.build()?);
+
+        if pool.config.warm_up_on_build {
+            let summary = pool.warm_up().await;
+            info!("Warmed up pool for {}: {} connected, {} failed", pool.endpoint, summary.succeeded, summary.failed);
+        }
+
+        tokio::spawn(pool.clone().run_cleanup_task());
+        let metrics_pool = pool.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics_pool.run_metrics_task().await {
+                error!("Metrics server stopped: {:?}", e);
+            }
+        });
+
+        Ok(pool)
+    }
+
     /// Build the SecureChannelPool (no background tasks started)
     pub fn build(self) -> Result<SecureChannelPool> {
+        let tls_config = Arc::new(client_tls_config(self.root_store, self.client_cert.as_ref())?);
         let registry = Arc::new(Registry::new());
         let pool_metrics = Arc::new(PoolMetrics::new(
             registry.clone(),
@@ -367,9 +567,10 @@ pub fn build(self) -> Result<SecureChannelPool> {
 
         Ok(SecureChannelPool {
             connections: Arc::new(Mutex::new(Vec::new())),
+            checked_out: Arc::new(AtomicUsize::new(0)),
             config: self.config,
             endpoint: self.endpoint,
-            root_store: self.root_store,
+            tls_config,
             pool_metrics,
             next_connection_id: Arc::new(Mutex::new(0)),
         })
@@ -377,10 +578,14 @@ pub fn build(self) -> Result<SecureChannelPool> {
 }
 
 pub struct SecureChannelPool {
+    /// Idle connections ready for reuse
     connections: Arc<Mutex<Vec<SecureChannel>>>,
+    /// Connections currently held by callers
+    checked_out: Arc<AtomicUsize>,
     config: PoolConfig,
     endpoint: String,
-    root_store: Option<RootCertStore>,
+    /// Shared so every connection uses the same session store for resumption
+    tls_config: Arc<ClientConfig>,
     pool_metrics: Arc<PoolMetrics>,
     next_connection_id: Arc<Mutex<usize>>,
 }
@@ -389,9 +594,10 @@ impl Clone for SecureChannelPool {
     fn clone(&self) -> Self {
         SecureChannelPool {
             connections: self.connections.clone(),
+            checked_out: self.checked_out.clone(),
             config: self.config.clone(),
             endpoint: self.endpoint.clone(),
-            root_store: self.root_store.clone(),
+            tls_config: self.tls_config.clone(),
             pool_metrics: self.pool_metrics.clone(),
             next_connection_id: self.next_connection_id.clone(),
         }
@@ -420,6 +626,65 @@ pub fn new(endpoint: &str, root_store: Option<RootCertStore>, namespace: Option<
         builder.build()
     }
 
+    /// Establish idle connections up to min_idle, a few handshakes at a time, within the
+    /// warm-up deadline. Individual handshake failures are counted rather than returned.
+    pub async fn warm_up(&self) -> WarmUpSummary {
+        let _span = span!(Level::INFO, "warm_up", endpoint = self.endpoint);
+        let mut summary = WarmUpSummary::default();
+
+        // Reserve slots up front so concurrent checkouts cannot push the pool past its bound
+        let mut remaining = {
+            let connections = self.connections.lock().await;
+            let active = connections.len() + self.checked_out.load(Ordering::Acquire);
+            let wanted = self.config.min_idle.saturating_sub(connections.len());
+            let needed = wanted.min(self.config.max_connections.saturating_sub(active));
+            self.checked_out.fetch_add(needed, Ordering::AcqRel);
+            needed
+        };
+
+        let deadline = tokio::time::Instant::now() + self.config.warm_up_deadline;
+        let mut handshakes = JoinSet::new();
+        loop {
+            while remaining > 0 && handshakes.len() < WARM_UP_CONCURRENCY {
+                let pool = self.clone();
+                handshakes.spawn(async move { pool.create_connection().await });
+                remaining -= 1;
+            }
+
+            match tokio::time::timeout_at(deadline, handshakes.join_next()).await {
+                Ok(Some(Ok(Ok(conn)))) => {
+                    let mut connections = self.connections.lock().await;
+                    connections.push(conn);
+                    self.checked_out.fetch_sub(1, Ordering::AcqRel);
+                    summary.succeeded += 1;
+                }
+                Ok(Some(Ok(Err(e)))) => {
+                    warn!("Warm-up connection failed: {}", e);
+                    self.checked_out.fetch_sub(1, Ordering::AcqRel);
+                    summary.failed += 1;
+                }
+                Ok(Some(Err(e))) => {
+                    warn!("Warm-up task failed: {}", e);
+                    self.checked_out.fetch_sub(1, Ordering::AcqRel);
+                    summary.failed += 1;
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    let unfinished = handshakes.len() + remaining;
+                    warn!("Warm-up deadline reached with {} connections outstanding", unfinished);
+                    handshakes.abort_all();
+                    self.checked_out.fetch_sub(unfinished, Ordering::AcqRel);
+                    summary.failed += unfinished;
+                    break;
+                }
+            }
+        }
+
+        let idle = self.connections.lock().await.len();
+        self.update_active_connections(idle);
+        summary
+    }
+
     /// Explicit start of cleanup task - call this from your main()
     pub async fn run_cleanup_task(self: Arc<Self>) {
         self.run_background_cleanup().await;
@@ -431,36 +696,42 @@ pub async fn run_metrics_task(self: Arc<Self>) -> Result<()> {
     }
 
     /// Get or create a connection from the pool
-    pub async fn get_connection(&self) -> Result<SecureChannel> {
+    ///
+    /// The connection goes back to the pool when the returned guard is dropped.
+    pub async fn get_connection(&self) -> Result<PooledConnection> {
         let _span = span!(Level::INFO, "get_connection", endpoint = self.endpoint);
         
         // Check circuit breaker
         self.check_circuit_breaker()?;
-        
-        let mut connections = self.connections.lock().await;
-
-        // Enforce connection pool upper bound
-        if connections.len() >= self.config.max_connections {
-            return Err(anyhow!("Connection pool exhausted: {} connections active", connections.len()));
-        }
 
-        // Try to reuse an existing connection
-        while let Some(mut conn) = connections.pop() {
-            if conn.is_valid().await {
-                conn.metrics.rotate_histogram_if_needed(self.config.histogram_rotation_interval);
-                if !conn.metrics.is_slow(self.config.max_latency_ms) {
-                    self.pool_metrics.set_active_connections(connections.len() + 1);
-                    return Ok(conn);
-                } else {
-                    warn!("Dropping slow connection {}: p95={}ms", 
-                        conn.metrics.connection_id, 
-                        conn.metrics.get_p95_latency()
-                    );
-                    let _ = conn.shutdown().await; // Graceful shutdown
+        // Try to reuse an existing connection. Each candidate holds a checked-out slot while
+        // it is probed so the idle list stays unlocked and the pool bound still holds.
+        loop {
+            let candidate = {
+                let mut connections = self.connections.lock().await;
+                let candidate = connections.pop();
+                if candidate.is_none() {
+                    // Enforce connection pool upper bound across idle and checked-out connections
+                    let active = self.checked_out.load(Ordering::Acquire);
+                    if active >= self.config.max_connections {
+                        return Err(anyhow!("Connection pool exhausted: {} connections active", active));
+                    }
                 }
-            } else {
-                let _ = conn.shutdown().await; // Graceful shutdown of invalid connection
+                self.checked_out.fetch_add(1, Ordering::AcqRel);
+                candidate
+            };
+
+            let mut conn = match candidate {
+                Some(conn) => conn,
+                None => break,
+            };
+            if self.checkout_ready(&mut conn).await {
+                let idle = self.connections.lock().await.len();
+                self.update_active_connections(idle);
+                return Ok(self.guard(conn));
             }
+            let _ = conn.shutdown().await; // Graceful shutdown
+            self.checked_out.fetch_sub(1, Ordering::AcqRel);
         }
 
         // Create new connection with retry logic
@@ -469,15 +740,79 @@ pub async fn get_connection(&self) -> Result<SecureChannel> {
             ..Default::default()
         };
 
-        let conn = retry(backoff, || async {
-            self.create_connection().await
-        }).await.context("Failed to create connection after retries")?;
+        let conn = match retry(backoff, || async {
+            self.create_connection().await.map_err(backoff::Error::transient)
+        }).await.context("Failed to create connection after retries") {
+            Ok(conn) => conn,
+            Err(e) => {
+                self.checked_out.fetch_sub(1, Ordering::AcqRel);
+                return Err(e);
+            }
+        };
 
         // Reset circuit breaker on successful connection
         CIRCUIT_BREAKER_FAILURES.store(0, Ordering::Relaxed);
 
-        self.pool_metrics.set_active_connections(connections.len() + 1);
-        Ok(conn)
+        let idle = self.connections.lock().await.len();
+        self.update_active_connections(idle);
+        Ok(self.guard(conn))
+    }
+
+    /// Whether an idle connection can be handed out: within its lifetime, not slow, and
+    /// still answering the liveness probe and registered ping
+    async fn checkout_ready(&self, conn: &mut SecureChannel) -> bool {
+        if !conn.is_valid().await {
+            return false;
+        }
+
+        conn.metrics.rotate_histogram_if_needed(self.config.histogram_rotation_interval);
+        if conn.metrics.is_slow(self.config.max_latency_ms) {
+            warn!("Dropping slow connection {}: p95={}ms", 
+                conn.metrics.connection_id, 
+                conn.metrics.get_p95_latency()
+            );
+            return false;
+        }
+
+        if !conn.probe(self.config.liveness_probe_timeout).await {
+            warn!("Dropping dead connection {}: peer closed or reset", conn.metrics.connection_id);
+            self.pool_metrics.increment_dead_connections();
+            return false;
+        }
+
+        if let Some(ping) = &self.config.ping {
+            if let Err(e) = ping(conn).await {
+                warn!("Dropping dead connection {}: ping failed: {}", conn.metrics.connection_id, e);
+                self.pool_metrics.increment_dead_connections();
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Number of connections currently held by callers
+    pub fn checked_out_connections(&self) -> usize {
+        self.checked_out.load(Ordering::Acquire)
+    }
+
+    /// Number of connections waiting in the pool for reuse
+    pub async fn idle_connections(&self) -> usize {
+        self.connections.lock().await.len()
+    }
+
+    fn guard(&self, conn: SecureChannel) -> PooledConnection {
+        PooledConnection {
+            conn: Some(conn),
+            poisoned: false,
+            idle: self.connections.clone(),
+            checked_out: self.checked_out.clone(),
+            pool_metrics: self.pool_metrics.clone(),
+        }
+    }
+
+    fn update_active_connections(&self, idle: usize) {
+        self.pool_metrics.set_active_connections(idle + self.checked_out.load(Ordering::Acquire));
     }
 
     fn check_circuit_breaker(&self) -> Result<()> {
@@ -511,37 +846,8 @@ async fn create_connection(&self) -> Result<SecureChannel> {
         let port = endpoint_url.port_or_known_default().unwrap_or(443);
         let tcp_endpoint = format!("{}:{}", domain_str, port);
 
-        // Optimized TLS config with safe root cert loading
-        let root_store = self.root_store.clone().unwrap_or_else(|| {
-            let mut store = RootCertStore::empty();
-            match rustls_native_certs::load_native_certs() {
-                Ok(certs) => {
-                    for cert in certs {
-                        if let Err(e) = store.add(&rustls::Certificate(cert.0)) {
-                            warn!("Skipping invalid system cert: {:?}", e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    error!("Failed to load native certs: {:?}", e);
-                    // Continue with empty store - will fail TLS verification but won't crash
-                }
-            }
-            store
-        });
-
-        let config = ClientConfig::builder()
-            .with_safe_defaults()
-            .with_cipher_suites(&[
-                rustls::cipher_suite::TLS13_AES_256_GCM_SHA384,
-                rustls::cipher_suite::TLS13_CHACHA20_POLY1305_SHA256,
-            ])
-            .with_root_certificates(root_store)
-            .with_no_client_auth()
-            .with_client_session_cache(ClientSessionMemoryCache::new(256));
-
-        let connector = TlsConnector::from(Arc::new(config));
-        let server_name = ServerName::try_from(domain_str)
+        let connector = TlsConnector::from(self.tls_config.clone());
+        let server_name = ServerName::try_from(domain_str.to_string())
             .map_err(|_| anyhow!("Invalid DNS name: {}", domain_str))?;
 
         let stream = tokio::time::timeout(Duration::from_secs(5), TcpStream::connect(&tcp_endpoint))
@@ -553,7 +859,7 @@ async fn create_connection(&self) -> Result<SecureChannel> {
         let stream = TcpStream::from_std(stream)?;
 
         let tls_stream = connector.connect(server_name, stream).await
-            .map_err(|e| {
+            .inspect_err(|_| {
                 // Record circuit breaker failure
                 CIRCUIT_BREAKER_FAILURES.fetch_add(1, Ordering::Relaxed);
                 CIRCUIT_BREAKER_LAST_FAILURE.store(
@@ -561,7 +867,6 @@ async fn create_connection(&self) -> Result<SecureChannel> {
                         .unwrap_or_default().as_secs(),
                     Ordering::Relaxed
                 );
-                e
             })
             .context("TLS handshake failed")?;
 
@@ -604,7 +909,7 @@ async fn run_background_cleanup(&self) {
             // Gracefully shutdown and remove invalid connections
             let mut valid_connections = Vec::new();
             for mut conn in connections.drain(..) {
-                let is_valid = conn.last_rotated.elapsed().map_or(false, |elapsed| {
+                let is_valid = conn.last_rotated.elapsed().is_ok_and(|elapsed| {
                     elapsed < self.config.max_lifetime && !conn.metrics.is_slow(self.config.max_latency_ms)
                 });
                 
@@ -628,13 +933,15 @@ async fn run_background_cleanup(&self) {
             }
 
             // Reset CONNECTION_ESTABLISHED if pool is empty
-            if connections.is_empty() {
+            if connections.is_empty() && self.checked_out.load(Ordering::Acquire) == 0 {
                 CONNECTION_ESTABLISHED.store(false, Ordering::Relaxed);
                 info!("Connection pool empty - reset CONNECTION_ESTABLISHED flag");
             }
 
-            // Ensure minimum idle connections
-            while connections.len() < self.config.min_idle {
+            // Ensure minimum idle connections without exceeding the pool bound
+            while connections.len() < self.config.min_idle
+                && connections.len() + self.checked_out.load(Ordering::Acquire) < self.config.max_connections
+            {
                 match self.create_connection().await {
                     Ok(conn) => {
                         connections.push(conn);
@@ -648,7 +955,7 @@ async fn run_background_cleanup(&self) {
             }
 
             // Update pool metrics
-            self.pool_metrics.set_active_connections(connections.len());
+            self.update_active_connections(connections.len());
         }
     }
 
@@ -657,150 +964,154 @@ async fn run_metrics_server(&self) -> Result<()> {
         let addr: SocketAddr = format!("{}:{}", self.config.metrics_host, self.config.metrics_port)
             .parse()
             .context("Invalid metrics server address")?;
-        
-        let registry = self.pool_metrics.registry.clone();
-        let endpoint = self.endpoint.clone();
-        let connections = self.connections.clone();
-        let auth_token = self.config.metrics_auth_token.clone();
-
-        let make_service = make_service_fn(move |_| {
-            let registry = registry.clone();
-            let endpoint = endpoint.clone();
-            let connections = connections.clone();
-            let auth_token = auth_token.clone();
-            async move {
-                Ok::<_, hyper::Error>(service_fn(move |req: hyper::Request<Body>| {
-                    let registry = registry.clone();
-                    let endpoint = endpoint.clone();
-                    let connections = connections.clone();
-                    let auth_token = auth_token.clone();
-                    async move {
-                        // Check authentication for protected endpoints
-                        if let Some(expected_token) = &auth_token {
-                            if req.uri().path().starts_with("/metrics") || 
-                               req.uri().path().starts_with("/status") {
-                                if let Some(auth_header) = req.headers().get("X-Auth-Token") {
-                                    if let Ok(token) = auth_header.to_str() {
-                                        if token != expected_token {
-                                            return Ok::<_, hyper::Error>(
-                                                Response::builder()
-                                                    .status(StatusCode::UNAUTHORIZED)
-                                                    .body(Body::from("Unauthorized: Invalid token"))
-                                                    .expect("Failed to build response")
-                                            );
-                                        }
-                                    } else {
-                                        return Ok::<_, hyper::Error>(
-                                            Response::builder()
-                                                .status(StatusCode::UNAUTHORIZED)
-                                                .body(Body::from("Unauthorized: Invalid token format"))
-                                                .expect("Failed to build response")
-                                        );
-                                    }
-                                } else {
-                                    return Ok::<_, hyper::Error>(
-                                        Response::builder()
-                                            .status(StatusCode::UNAUTHORIZED)
-                                            .body(Body::from("Unauthorized: Missing X-Auth-Token header"))
-                                            .expect("Failed to build response")
-                                    );
-                                }
-                            }
-                        }
+        let listener = TcpListener::bind(addr).await.context("Failed to bind metrics server")?;
+        info!("Metrics server running on http://{}", listener.local_addr()?);
+        info!("Endpoints: /metrics (Prometheus), /status/connections (JSON), /healthz (Health)");
+        self.serve_metrics(listener).await
+    }
 
-                        match req.uri().path() {
-                            "/metrics" => {
-                                let encoder = TextEncoder::new();
-                                let metric_families = registry.gather();
-                                let mut buffer = vec![];
-                                encoder.encode(&metric_families, &mut buffer)
-                                    .expect("Failed to encode metrics");
-                                Ok::<_, hyper::Error>(Response::new(Body::from(buffer)))
-                            }
-                            "/status/connections" => {
-                                let connections = connections.lock().await;
-                                let connection_statuses: Vec<ConnectionStatus> = connections
-                                    .iter()
-                                    .map(|c| c.metrics.get_status())
-                                    .collect();
-                                
-                                let pool_p95 = if !connection_statuses.is_empty() {
-                                    connection_statuses.iter().map(|c| c.p95_latency_ms).max().unwrap_or(0)
-                                } else {
-                                    0
-                                };
-
-                                let total_reconnects = connection_statuses.iter().map(|c| c.reconnects).sum();
-                                // Calculate total errors from all connections (no double counting)
-                                let total_errors = connection_statuses.iter().map(|c| c.errors).sum();
-
-                                let status = PoolStatus {
-                                    endpoint: endpoint.clone(),
-                                    active_connections: connections.len(),
-                                    total_reconnects,
-                                    total_errors,
-                                    pool_p95_latency_ms: pool_p95,
-                                    connections: connection_statuses,
-                                };
-
-                                let json = serde_json::to_string(&status)
-                                    .expect("Failed to serialize status");
-                                Ok::<_, hyper::Error>(
-                                    Response::builder()
-                                        .status(StatusCode::OK)
-                                        .header("Content-Type", "application/json")
-                                        .body(Body::from(json))
-                                        .expect("Failed to build response")
-                                )
-                            }
-                            "/healthz" => {
-                                let connections = connections.lock().await;
-                                let pool_healthy = !connections.is_empty();
-                                let health = HealthStatus {
-                                    status: if pool_healthy { "healthy".to_string() } else { "unhealthy".to_string() },
-                                    timestamp: SystemTime::now(),
-                                    pool_healthy,
-                                    active_connections: connections.len(),
-                                };
-                                let json = serde_json::to_string(&health)
-                                    .expect("Failed to serialize health status");
-                                let status_code = if pool_healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
-                                Ok::<_, hyper::Error>(
-                                    Response::builder()
-                                        .status(status_code)
-                                        .header("Content-Type", "application/json")
-                                        .body(Body::from(json))
-                                        .expect("Failed to build response")
-                                )
-                            }
-                            _ => Ok::<_, hyper::Error>(
-                                Response::builder()
-                                    .status(StatusCode::NOT_FOUND)
-                                    .body(Body::empty())
-                                    .expect("Failed to build response")
-                            ),
-                        }
-                    }
-                }))
-            }
+    /// Serve the metrics endpoints on `listener`, one task per connection
+    async fn serve_metrics(&self, listener: TcpListener) -> Result<()> {
+        let state = Arc::new(MetricsState {
+            registry: self.pool_metrics.registry.clone(),
+            endpoint: self.endpoint.clone(),
+            connections: self.connections.clone(),
+            checked_out: self.checked_out.clone(),
+            auth_token: self.config.metrics_auth_token.clone(),
         });
 
-        let server = Server::bind(&addr).serve(make_service);
-        info!("Metrics server running on http://{}", addr);
-        info!("Endpoints: /metrics (Prometheus), /status/connections (JSON), /healthz (Health)");
-        
-        server.await.context("Metrics server failed")?;
-        Ok(())
+        loop {
+            let (stream, _) = listener.accept().await.context("Metrics server failed")?;
+            let state = state.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |req: Request<Incoming>| {
+                    let state = state.clone();
+                    async move { Ok::<_, Infallible>(state.respond(&req).await) }
+                });
+                if let Err(e) = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await {
+                    warn!("Metrics connection failed: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// What the metrics server reads from the pool
+struct MetricsState {
+    registry: Arc<Registry>,
+    endpoint: String,
+    connections: Arc<Mutex<Vec<SecureChannel>>>,
+    checked_out: Arc<AtomicUsize>,
+    auth_token: Option<String>,
+}
+
+impl MetricsState {
+    async fn respond(&self, req: &Request<Incoming>) -> Response<Full<Bytes>> {
+        let path = req.uri().path();
+
+        // Check authentication for protected endpoints
+        if let Some(expected_token) = &self.auth_token {
+            if path.starts_with("/metrics") || path.starts_with("/status") {
+                let rejection = match req.headers().get("X-Auth-Token").map(|v| v.to_str()) {
+                    None => Some("Unauthorized: Missing X-Auth-Token header"),
+                    Some(Err(_)) => Some("Unauthorized: Invalid token format"),
+                    Some(Ok(token)) if token != expected_token => Some("Unauthorized: Invalid token"),
+                    Some(Ok(_)) => None,
+                };
+                if let Some(message) = rejection {
+                    return response(StatusCode::UNAUTHORIZED, None, message);
+                }
+            }
+        }
+
+        let checked_out = self.checked_out.load(Ordering::Acquire);
+        match path {
+            "/metrics" => {
+                let encoder = TextEncoder::new();
+                let metric_families = self.registry.gather();
+                let mut buffer = vec![];
+                encoder.encode(&metric_families, &mut buffer)
+                    .expect("Failed to encode metrics");
+                response(StatusCode::OK, None, buffer)
+            }
+            "/status/connections" => {
+                let connections = self.connections.lock().await;
+                let connection_statuses: Vec<ConnectionStatus> = connections
+                    .iter()
+                    .map(|c| c.metrics.get_status())
+                    .collect();
+
+                let pool_p95 = connection_statuses.iter().map(|c| c.p95_latency_ms).max().unwrap_or(0);
+                let total_reconnects = connection_statuses.iter().map(|c| c.reconnects).sum();
+                // Calculate total errors from all connections (no double counting)
+                let total_errors = connection_statuses.iter().map(|c| c.errors).sum();
+
+                let status = PoolStatus {
+                    endpoint: self.endpoint.clone(),
+                    active_connections: connections.len() + checked_out,
+                    idle_connections: connections.len(),
+                    checked_out_connections: checked_out,
+                    total_reconnects,
+                    total_errors,
+                    pool_p95_latency_ms: pool_p95,
+                    connections: connection_statuses,
+                };
+
+                let json = serde_json::to_string(&status)
+                    .expect("Failed to serialize status");
+                response(StatusCode::OK, Some("application/json"), json)
+            }
+            "/healthz" => {
+                let connections = self.connections.lock().await;
+                let active_connections = connections.len() + checked_out;
+                let pool_healthy = active_connections > 0;
+                let health = HealthStatus {
+                    status: if pool_healthy { "healthy".to_string() } else { "unhealthy".to_string() },
+                    timestamp: SystemTime::now(),
+                    pool_healthy,
+                    active_connections,
+                };
+                let json = serde_json::to_string(&health)
+                    .expect("Failed to serialize health status");
+                let status_code = if pool_healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+                response(status_code, Some("application/json"), json)
+            }
+            _ => response(StatusCode::NOT_FOUND, None, Bytes::new()),
+        }
     }
 }
 
+fn response(status: StatusCode, content_type: Option<&str>, body: impl Into<Bytes>) -> Response<Full<Bytes>> {
+    let mut builder = Response::builder().status(status);
+    if let Some(content_type) = content_type {
+        builder = builder.header("Content-Type", content_type);
+    }
+    builder.body(Full::new(body.into())).expect("Failed to build response")
+}
+
 impl SecureChannel {
     async fn is_valid(&self) -> bool {
-        self.last_rotated.elapsed().map_or(false, |elapsed| {
+        self.last_rotated.elapsed().is_ok_and(|elapsed| {
             elapsed < Duration::from_secs(1800) // 30 minutes
         })
     }
 
+    /// Cheap liveness check for an idle connection. A read that stays pending for `timeout`
+    /// means the peer is quiet and the socket open; EOF, a reset or unsolicited data mean
+    /// the connection cannot be reused.
+    async fn probe(&mut self, timeout: Duration) -> bool {
+        let mut buf = [0u8; 1];
+        match tokio::time::timeout(timeout, self.stream.read(&mut buf)).await {
+            Err(_) => true,
+            Ok(Ok(0)) => false,
+            Ok(Ok(_)) => {
+                warn!("Unsolicited data on idle connection {}", self.metrics.connection_id);
+                false
+            }
+            Ok(Err(_)) => false,
+        }
+    }
+
     pub fn check_rotation(&mut self) -> Result<()> {
         if self.last_rotated.elapsed()? > Duration::from_secs(3600) {
             self.rotate_keys()?;
@@ -822,10 +1133,9 @@ pub async fn write(&mut self, buf: &[u8]) -> Result<usize> {
         let start = Instant::now();
         
         let result = self.stream.write(buf).await
-            .map_err(|e| {
+            .inspect_err(|_| {
                 self.metrics.increment_errors();
                 // Don't double-count pool errors - they are aggregated from connections
-                e
             })
             .context("Failed to write to secure channel");
         
@@ -839,10 +1149,9 @@ pub async fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
         let start = Instant::now();
         
         let result = self.stream.read(buf).await
-            .map_err(|e| {
+            .inspect_err(|_| {
                 self.metrics.increment_errors();
                 // Don't double-count pool errors - they are aggregated from connections
-                e
             })
             .context("Failed to read from secure channel");
         
@@ -859,10 +1168,9 @@ async fn write_all(&mut self, buf: &[u8]) -> Result<()> {
         let start = Instant::now();
         
         let result = self.stream.write_all(buf).await
-            .map_err(|e| {
+            .inspect_err(|_| {
                 self.metrics.increment_errors();
                 // Don't double-count pool errors - they are aggregated from connections
-                e
             })
             .context("Failed to write_all to secure channel");
         
@@ -876,10 +1184,10 @@ async fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
         let start = Instant::now();
         
         let result = self.stream.read_exact(buf).await
-            .map_err(|e| {
+            .map(|_| ())
+            .inspect_err(|_| {
                 self.metrics.increment_errors();
                 // Don't double-count pool errors - they are aggregated from connections
-                e
             })
             .context("Failed to read_exact from secure channel");
         
@@ -891,10 +1199,9 @@ async fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
     async fn shutdown(&mut self) -> Result<()> {
         let _span = self.monitor.instrument(span!(Level::TRACE, "shutdown", connection_id = self.metrics.connection_id));
         let result = self.stream.shutdown().await
-            .map_err(|e| {
+            .inspect_err(|_| {
                 self.metrics.increment_errors();
                 // Don't double-count pool errors - they are aggregated from connections
-                e
             })
             .context("Failed to shutdown secure channel");
         result
@@ -913,6 +1220,55 @@ fn drop(&mut self) {
     }
 }
 
+/// TLS 1.3-only client config restricted to AES-256-GCM and ChaCha20-Poly1305, with
+/// in-memory session resumption and optional mTLS client authentication
+fn client_tls_config(root_store: Option<RootCertStore>, client_cert: Option<&(String, String)>) -> Result<ClientConfig> {
+    // Safe root cert loading: fall back to the system store
+    let root_store = root_store.unwrap_or_else(|| {
+        let mut store = RootCertStore::empty();
+        let native = rustls_native_certs::load_native_certs();
+        for e in &native.errors {
+            error!("Failed to load native certs: {:?}", e);
+            // Continue with what loaded - an empty store fails TLS verification but won't crash
+        }
+        let (_, ignored) = store.add_parsable_certificates(native.certs);
+        if ignored > 0 {
+            warn!("Skipping {} invalid system certs", ignored);
+        }
+        store
+    });
+
+    let provider = CryptoProvider {
+        cipher_suites: vec![
+            aws_lc_rs::cipher_suite::TLS13_AES_256_GCM_SHA384,
+            aws_lc_rs::cipher_suite::TLS13_CHACHA20_POLY1305_SHA256,
+        ],
+        ..aws_lc_rs::default_provider()
+    };
+
+    let builder = ClientConfig::builder_with_provider(Arc::new(provider))
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .context("Unsupported TLS configuration")?
+        .with_root_certificates(root_store);
+
+    let mut config = match client_cert {
+        Some((cert_pem, key_pem)) => {
+            let certs = CertificateDer::pem_slice_iter(cert_pem.as_bytes())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| anyhow!("Invalid client certificate PEM: {:?}", e))?;
+            if certs.is_empty() {
+                return Err(anyhow!("Client certificate PEM contains no certificates"));
+            }
+            let key = PrivateKeyDer::from_pem_slice(key_pem.as_bytes())
+                .map_err(|e| anyhow!("Invalid client key PEM: {:?}", e))?;
+            builder.with_client_auth_cert(certs, key).context("Client certificate rejected")?
+        }
+        None => builder.with_no_client_auth(),
+    };
+    config.resumption = Resumption::in_memory_sessions(256);
+    Ok(config)
+}
+
 fn normalize_endpoint(endpoint: &str) -> Result<Url> {
     let endpoint_url_str = if !endpoint.contains("://") {
         format!("https://{}", endpoint)
@@ -1038,7 +1394,7 @@ async fn test_circuit_breaker_functionality() -> Result<()> {
         // Should fail due to circuit breaker
         let result = pool.get_connection().await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("Circuit breaker open"));
+        assert!(result.err().unwrap().to_string().contains("Circuit breaker open"));
 
         Ok(())
     }
@@ -1047,11 +1403,13 @@ async fn test_circuit_breaker_functionality() -> Result<()> {
     async fn test_connection_pool_upper_bound() -> Result<()> {
         let pool = SecureChannelPool::builder("example.com:443")
             .with_max_connections(0) // Force immediate exhaustion
+            // The breaker state is process-wide; don't let test_pool_builder's open breaker mask this
+            .with_circuit_breaker_failure_threshold(u64::MAX)
             .build()?;
 
         let result = pool.get_connection().await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("Connection pool exhausted"));
+        assert!(result.err().unwrap().to_string().contains("Connection pool exhausted"));
 
         Ok(())
     }
@@ -1071,4 +1429,325 @@ async fn test_metrics_auth_configuration() -> Result<()> {
         
         Ok(())
     }
+
+    /// Plain HTTP/1.1 GET against the metrics server, returning the status line and body
+    async fn http_get(addr: SocketAddr, path: &str, token: Option<&str>) -> Result<(String, String)> {
+        let mut stream = TcpStream::connect(addr).await?;
+        let auth = token.map(|t| format!("X-Auth-Token: {}\r\n", t)).unwrap_or_default();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n{}Connection: close\r\n\r\n", path, auth);
+        stream.write_all(request.as_bytes()).await?;
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).await?;
+        let (head, body) = raw.split_once("\r\n\r\n").unwrap_or((&raw, ""));
+        Ok((head.lines().next().unwrap_or_default().to_string(), body.to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_metrics_server_checks_token_and_reports_health() -> Result<()> {
+        let pool = Arc::new(SecureChannelPool::builder("example.com:443")
+            .with_namespace("metrics_http")
+            .with_metrics_auth_token("secret123")
+            .build()?);
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = pool.clone();
+        tokio::spawn(async move { server.serve_metrics(listener).await });
+
+        let (status, _) = http_get(addr, "/metrics", None).await?;
+        assert!(status.contains("401"), "{}", status);
+        let (status, _) = http_get(addr, "/status/connections", Some("wrong")).await?;
+        assert!(status.contains("401"), "{}", status);
+
+        let (status, body) = http_get(addr, "/metrics", Some("secret123")).await?;
+        assert!(status.contains("200"), "{}", status);
+        assert!(body.contains("metrics_http_active_connections"), "{}", body);
+
+        let (status, body) = http_get(addr, "/status/connections", Some("secret123")).await?;
+        assert!(status.contains("200"), "{}", status);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body)?["active_connections"], 0);
+
+        // An empty pool is unhealthy; /healthz needs no token
+        let (status, body) = http_get(addr, "/healthz", None).await?;
+        assert!(status.contains("503"), "{}", status);
+        assert!(body.contains("unhealthy"), "{}", body);
+
+        let (status, _) = http_get(addr, "/nope", None).await?;
+        assert!(status.contains("404"), "{}", status);
+        Ok(())
+    }
+
+    /// Local TLS endpoint with a self-signed localhost certificate that holds every
+    /// accepted connection open until the client closes it or `drop_connections` is called
+    struct TestTlsServer {
+        endpoint: String,
+        roots: RootCertStore,
+        generation: Arc<tokio::sync::watch::Sender<u64>>,
+    }
+
+    impl TestTlsServer {
+        /// Close every connection accepted so far; new connections are still served
+        fn drop_connections(&self) {
+            self.generation.send_modify(|generation| *generation += 1);
+        }
+    }
+
+    async fn spawn_tls_server() -> Result<TestTlsServer> {
+        spawn_tls_server_with_client_ca(None).await
+    }
+
+    /// As `spawn_tls_server`, but requiring a client certificate issued by `client_ca` when set
+    async fn spawn_tls_server_with_client_ca(client_ca: Option<CertificateDer<'static>>) -> Result<TestTlsServer> {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+        let cert_der = cert.cert.der().clone();
+        let key_der: PrivateKeyDer = rustls::pki_types::PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()).into();
+        // Both ring and aws-lc are in the build, so the process default provider is ambiguous
+        let server_builder = || rustls::ServerConfig::builder_with_provider(Arc::new(aws_lc_rs::default_provider()))
+            .with_safe_default_protocol_versions();
+        let server_config = match client_ca {
+            Some(ca) => {
+                let mut client_roots = RootCertStore::empty();
+                client_roots.add(ca)?;
+                let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(
+                    Arc::new(client_roots), Arc::new(aws_lc_rs::default_provider())).build()?;
+                server_builder()?
+                    .with_client_cert_verifier(verifier)
+                    .with_single_cert(vec![cert_der.clone()], key_der)?
+            }
+            None => server_builder()?
+                .with_no_client_auth()
+                .with_single_cert(vec![cert_der.clone()], key_der)?,
+        };
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server_config));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let (generation, _) = tokio::sync::watch::channel(0u64);
+        let generation = Arc::new(generation);
+        let accept_generation = generation.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                let mut killed = accept_generation.subscribe();
+                let accepted_in = *killed.borrow();
+                tokio::spawn(async move {
+                    if let Ok(mut tls) = acceptor.accept(stream).await {
+                        let mut buf = [0u8; 1024];
+                        tokio::select! {
+                            _ = async { while matches!(tls.read(&mut buf).await, Ok(n) if n > 0) {} } => {}
+                            _ = killed.wait_for(|generation| *generation > accepted_in) => {}
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert_der)?;
+        Ok(TestTlsServer { endpoint: format!("localhost:{}", port), roots, generation })
+    }
+
+    async fn local_pool(namespace: &str, max_connections: usize) -> Result<(SecureChannelPool, TestTlsServer)> {
+        let server = spawn_tls_server().await?;
+        let pool = local_pool_builder(&server, namespace)
+            .with_max_connections(max_connections)
+            .build()?;
+        Ok((pool, server))
+    }
+
+    fn local_pool_builder(server: &TestTlsServer, namespace: &str) -> PoolBuilder {
+        SecureChannelPool::builder(&server.endpoint)
+            .with_root_store(server.roots.clone())
+            .with_namespace(namespace)
+            // The breaker state is process-wide; keep other tests from tripping it here
+            .with_circuit_breaker_failure_threshold(u64::MAX)
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_is_reused() -> Result<()> {
+        let (pool, _server) = local_pool("reuse", 4).await?;
+
+        let conn = pool.get_connection().await?;
+        let first_id = conn.connection_id();
+        assert_eq!(pool.checked_out_connections(), 1);
+        assert_eq!(pool.pool_metrics.active_connections(), 1);
+        drop(conn);
+
+        assert_eq!(pool.checked_out_connections(), 0);
+        assert_eq!(pool.idle_connections().await, 1);
+        assert_eq!(pool.pool_metrics.active_connections(), 1);
+
+        let conn = pool.get_connection().await?;
+        assert_eq!(conn.connection_id(), first_id);
+        assert_eq!(pool.idle_connections().await, 0);
+        assert_eq!(pool.pool_metrics.active_connections(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_poisoned_connection_is_discarded() -> Result<()> {
+        let (pool, _server) = local_pool("poisoned", 4).await?;
+
+        let mut conn = pool.get_connection().await?;
+        let first_id = conn.connection_id();
+        conn.poison();
+        drop(conn);
+
+        assert_eq!(pool.idle_connections().await, 0);
+        assert_eq!(pool.pool_metrics.active_connections(), 0);
+
+        let conn = pool.get_connection().await?;
+        assert_ne!(conn.connection_id(), first_id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_connections_counts_checked_out_connections() -> Result<()> {
+        let (pool, _server) = local_pool("bounded", 1).await?;
+
+        let held = pool.get_connection().await?;
+        let result = pool.get_connection().await;
+        assert!(result.err().unwrap().to_string().contains("Connection pool exhausted"));
+
+        drop(held);
+        let conn = pool.get_connection().await?;
+        assert_eq!(pool.checked_out_connections(), 1);
+        drop(conn);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dead_connection_is_replaced_on_checkout() -> Result<()> {
+        let (pool, server) = local_pool("dead", 4).await?;
+
+        let conn = pool.get_connection().await?;
+        let first_id = conn.connection_id();
+        drop(conn);
+
+        server.drop_connections();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let mut conn = pool.get_connection().await?;
+        assert_ne!(conn.connection_id(), first_id);
+        conn.write_all(b"hello").await?;
+        assert_eq!(pool.pool_metrics.prom_dead_connections.get(), 1);
+        assert_eq!(pool.pool_metrics.active_connections(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_ping_replaces_connection() -> Result<()> {
+        let server = spawn_tls_server().await?;
+        let pool = local_pool_builder(&server, "ping")
+            .with_ping(|_conn| Box::pin(async { Err(anyhow!("no pong")) }))
+            .build()?;
+
+        let first_id = pool.get_connection().await?.connection_id();
+        let conn = pool.get_connection().await?;
+        assert_ne!(conn.connection_id(), first_id);
+        assert_eq!(pool.pool_metrics.prom_dead_connections.get(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tls_config_is_tls13_only_with_resumption() -> Result<()> {
+        let pool = SecureChannelPool::builder("example.com:443")
+            .with_root_store(RootCertStore::empty())
+            .build()?;
+
+        let suites: Vec<_> = pool.tls_config.crypto_provider().cipher_suites.iter().map(|s| s.suite()).collect();
+        assert_eq!(suites, vec![
+            rustls::CipherSuite::TLS13_AES_256_GCM_SHA384,
+            rustls::CipherSuite::TLS13_CHACHA20_POLY1305_SHA256,
+        ]);
+        // No TLS 1.2 suite is offered, so 1.2 cannot be negotiated
+        assert!(pool.tls_config.crypto_provider().cipher_suites.iter()
+            .all(|s| s.version().version == rustls::ProtocolVersion::TLSv1_3));
+        assert!(!pool.tls_config.client_auth_cert_resolver.has_certs());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mtls_config_loads_client_cert() -> Result<()> {
+        let client = rcgen::generate_simple_self_signed(vec!["client.local".to_string()])?;
+        let pool = SecureChannelPool::builder("example.com:443")
+            .with_root_store(RootCertStore::empty())
+            .with_client_cert(&client.cert.pem(), &client.key_pair.serialize_pem())
+            .build()?;
+        assert!(pool.tls_config.client_auth_cert_resolver.has_certs());
+
+        let invalid = SecureChannelPool::builder("example.com:443")
+            .with_root_store(RootCertStore::empty())
+            .with_client_cert("not a certificate", &client.key_pair.serialize_pem())
+            .build();
+        assert!(invalid.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mtls_handshake_with_self_signed_roots() -> Result<()> {
+        let client = rcgen::generate_simple_self_signed(vec!["client.local".to_string()])?;
+        let server = spawn_tls_server_with_client_ca(Some(client.cert.der().clone())).await?;
+        let pool = local_pool_builder(&server, "mtls")
+            .with_client_cert(&client.cert.pem(), &client.key_pair.serialize_pem())
+            .build()?;
+
+        let mut conn = pool.get_connection().await?;
+        conn.write_all(b"hello").await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_fills_min_idle() -> Result<()> {
+        let server = spawn_tls_server().await?;
+        let pool = local_pool_builder(&server, "warm_up")
+            .with_min_idle(6)
+            .build()?;
+
+        let summary = pool.warm_up().await;
+        assert_eq!(summary, WarmUpSummary { succeeded: 6, failed: 0 });
+        assert_eq!(pool.idle_connections().await, 6);
+        assert_eq!(pool.checked_out_connections(), 0);
+        assert_eq!(pool.pool_metrics.active_connections(), 6);
+
+        // Already warm: nothing more to do
+        assert_eq!(pool.warm_up().await, WarmUpSummary::default());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_respects_max_connections_and_counts_failures() -> Result<()> {
+        let server = spawn_tls_server().await?;
+        let bounded = local_pool_builder(&server, "warm_up_bounded")
+            .with_min_idle(5)
+            .with_max_connections(2)
+            .build()?;
+        assert_eq!(bounded.warm_up().await, WarmUpSummary { succeeded: 2, failed: 0 });
+
+        // Nothing listens on the discard port, so every handshake is refused
+        let unreachable = SecureChannelPool::builder("localhost:9")
+            .with_root_store(server.roots.clone())
+            .with_namespace("warm_up_unreachable")
+            .with_min_idle(3)
+            .build()?;
+        assert_eq!(unreachable.warm_up().await, WarmUpSummary { succeeded: 0, failed: 3 });
+        assert_eq!(unreachable.checked_out_connections(), 0);
+        assert_eq!(unreachable.pool_metrics.active_connections(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_build_and_start_warms_up() -> Result<()> {
+        let server = spawn_tls_server().await?;
+        let pool = local_pool_builder(&server, "build_and_start")
+            .with_min_idle(3)
+            .with_warm_up_on_build(true)
+            .with_metrics_host("127.0.0.1")
+            .with_metrics_port(0)
+            .build_and_start()
+            .await?;
+
+        assert_eq!(pool.idle_connections().await, 3);
+        assert_eq!(pool.pool_metrics.active_connections(), 3);
+        Ok(())
+    }
 }
@@ -1,8 +1,111 @@
 // SPDX-License-Identifier: MIT
 // Bitcoin Sprint - SecureBuffer Entropy Integration
 
-use crate::{SecureBuffer, CSecureBuffer};
+use crate::{SecureBuffer, CSecureBuffer, SECURE_BUFFER_ERR_INVALID, SECURE_BUFFER_OK};
 use crate::entropy;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::os::raw::c_int;
+use zeroize::{Zeroize, Zeroizing};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Entropy generator used by `fill_secure`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntropyKind {
+    /// OS RNG + timing jitter
+    Fast,
+    /// OS RNG + jitter, without caller-supplied headers
+    Hybrid,
+    /// Hybrid plus the system fingerprint
+    Enterprise,
+}
+
+impl EntropyKind {
+    /// Decode the `kind` argument of the FFI: 0 fast, 1 hybrid, 2 enterprise
+    pub fn from_c(kind: c_int) -> Option<Self> {
+        match kind {
+            0 => Some(EntropyKind::Fast),
+            1 => Some(EntropyKind::Hybrid),
+            2 => Some(EntropyKind::Enterprise),
+            _ => None,
+        }
+    }
+}
+
+/// HKDF salt for `derive_key_into`; fixed, as the IKM is fresh entropy every call
+const KDF_SALT: &[u8] = b"bitcoin-sprint/securebuffer/hkdf-sha256/v1";
+const SHA256_LEN: usize = 32;
+/// Longest HKDF-SHA256 output (RFC 5869: 255 blocks)
+pub const MAX_DERIVED_KEY_LEN: usize = 255 * SHA256_LEN;
+
+/// Write 32 bytes of `kind` entropy into `buffer`, replacing its content.
+/// The entropy only exists outside the buffer in a stack array that is wiped on return.
+pub fn fill_secure(buffer: &mut SecureBuffer, kind: EntropyKind) -> Result<(), String> {
+    if !buffer.is_valid() {
+        return Err("Buffer is not valid".to_string());
+    }
+    let material = Zeroizing::new(match kind {
+        EntropyKind::Fast => entropy::fast_entropy(),
+        EntropyKind::Hybrid => entropy::hybrid_entropy(&[]),
+        EntropyKind::Enterprise => entropy::enterprise_entropy(&[], &[]),
+    });
+    buffer.write(&material[..])
+}
+
+/// Derive a `length`-byte key into `buffer` with HKDF-SHA256: the PRK is extracted
+/// from fresh hybrid entropy and expanded with `context` as the info string, so keys
+/// for different contexts are independent. Output blocks go straight into the buffer;
+/// the PRK and each block are wiped as soon as they are used. On error the buffer is
+/// left empty.
+pub fn derive_key_into(buffer: &mut SecureBuffer, context: &[u8], length: usize) -> Result<(), String> {
+    if !buffer.is_valid() {
+        return Err("Buffer is not valid".to_string());
+    }
+    if length == 0 || length > MAX_DERIVED_KEY_LEN {
+        return Err(format!("Key length must be between 1 and {} bytes", MAX_DERIVED_KEY_LEN));
+    }
+    if length > buffer.capacity() {
+        return Err("Key length exceeds buffer capacity".to_string());
+    }
+
+    let ikm = Zeroizing::new(entropy::hybrid_entropy(&[]));
+    let prk = hmac_sha256(KDF_SALT, &[&ikm[..]])?;
+    buffer.clear();
+    let result = expand_into(buffer, &prk, context, length);
+    if result.is_err() {
+        buffer.clear();
+    }
+    result
+}
+
+/// HKDF-Expand (RFC 5869 section 2.3), appending each block to `buffer`
+fn expand_into(buffer: &mut SecureBuffer, prk: &[u8; SHA256_LEN], info: &[u8], length: usize) -> Result<(), String> {
+    let mut previous: Option<Zeroizing<[u8; SHA256_LEN]>> = None;
+    let mut written = 0;
+    for counter in 1..=length.div_ceil(SHA256_LEN) {
+        let previous_block: &[u8] = previous.as_ref().map_or(&[], |block| &block[..]);
+        let block = hmac_sha256(prk, &[previous_block, info, &[counter as u8]])?;
+        let take = std::cmp::min(SHA256_LEN, length - written);
+        buffer.write_at(written, &block[..take])?;
+        written += take;
+        previous = Some(block);
+    }
+    Ok(())
+}
+
+/// HMAC-SHA256 over the concatenation of `parts`, returned in a self-wiping array
+pub(crate) fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> Result<Zeroizing<[u8; SHA256_LEN]>, String> {
+    let mut mac = HmacSha256::new_from_slice(key).map_err(|e| format!("Invalid HMAC key: {}", e))?;
+    for part in parts {
+        mac.update(part);
+    }
+    let mut tag = mac.finalize().into_bytes();
+    let mut out = Zeroizing::new([0u8; SHA256_LEN]);
+    out.copy_from_slice(&tag);
+    tag.as_mut_slice().zeroize();
+    Ok(out)
+}
 
 impl SecureBuffer {
     /// Fill SecureBuffer with fast entropy (OS RNG + timing jitter)
@@ -413,6 +516,32 @@ pub fn mix_entropy(&mut self, headers: &[Vec<u8>]) -> Result<(), String> {
     }
 }
 
+#[no_mangle]
+/// Fill `buffer` with entropy of `kind` (0 fast, 1 hybrid, 2 enterprise).
+/// Returns `SECURE_BUFFER_OK`, or `SECURE_BUFFER_ERR_INVALID` for a null or destroyed
+/// buffer, an unknown kind, or a buffer smaller than 32 bytes.
+///
+/// # Safety
+///
+/// `buffer` must be null or a pointer returned by one of the `securebuffer_*`
+/// constructors that has not been freed. The caller must ensure exclusive access.
+pub unsafe extern "C" fn fill_secure_buffer_entropy_c(buffer: *mut CSecureBuffer, kind: c_int) -> c_int {
+    if buffer.is_null() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    let c_buffer = &mut *buffer;
+    if c_buffer.inner.is_null() {
+        return SECURE_BUFFER_ERR_INVALID;
+    }
+    let Some(kind) = EntropyKind::from_c(kind) else {
+        return SECURE_BUFFER_ERR_INVALID;
+    };
+    match fill_secure(&mut *c_buffer.inner, kind) {
+        Ok(()) => SECURE_BUFFER_OK,
+        Err(_) => SECURE_BUFFER_ERR_INVALID,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -469,6 +598,20 @@ fn test_refresh_entropy() {
         assert_ne!(initial_data, new_data);
     }
 
+    #[test]
+    fn test_seeded_source_makes_buffers_reproducible() {
+        use crate::entropy::{with_entropy_source, DeterministicSource};
+        use std::sync::Arc;
+
+        let fill = || {
+            with_entropy_source(Arc::new(DeterministicSource::from_seed(7)), || {
+                let buffer = SecureBuffer::new_with_fast_entropy(64).unwrap();
+                buffer.as_slice().unwrap().to_vec()
+            })
+        };
+        assert_eq!(fill(), fill());
+    }
+
     #[test]
     fn test_mix_entropy() {
         let mut buffer = SecureBuffer::new(32).unwrap();
@@ -486,4 +629,80 @@ fn test_mix_entropy() {
         // Should be different after mixing
         assert_ne!(initial_data, mixed_data);
     }
+
+    #[test]
+    fn test_fill_secure_kinds() {
+        for kind in [EntropyKind::Fast, EntropyKind::Hybrid, EntropyKind::Enterprise] {
+            let mut buffer = SecureBuffer::new(32).unwrap();
+            buffer.write(&[0u8; 32]).unwrap();
+            fill_secure(&mut buffer, kind).unwrap();
+            assert_eq!(buffer.len(), 32);
+            assert!(!buffer.ct_eq_slice(&[0u8; 32]));
+        }
+        let mut small = SecureBuffer::new(16).unwrap();
+        assert!(fill_secure(&mut small, EntropyKind::Fast).is_err());
+    }
+
+    #[test]
+    fn test_derive_key_into_lengths_and_contexts() {
+        let mut buffer = SecureBuffer::new(128).unwrap();
+        let initially_locked = buffer.is_locked();
+        for length in [1, 31, 32, 33, 64, 100] {
+            derive_key_into(&mut buffer, b"sprint/test", length).unwrap();
+            assert_eq!(buffer.len(), length);
+        }
+
+        let mut signing = SecureBuffer::new(64).unwrap();
+        let mut encryption = SecureBuffer::new(64).unwrap();
+        derive_key_into(&mut signing, b"sprint/signing", 64).unwrap();
+        derive_key_into(&mut encryption, b"sprint/encryption", 64).unwrap();
+        assert!(!signing.ct_eq(&encryption));
+        assert!(!signing.ct_eq_slice(&[0u8; 64]));
+
+        assert!(derive_key_into(&mut buffer, b"ctx", 0).is_err());
+        assert!(derive_key_into(&mut buffer, b"ctx", 129).is_err());
+        assert!(derive_key_into(&mut buffer, b"ctx", MAX_DERIVED_KEY_LEN + 1).is_err());
+        assert!(buffer.is_valid());
+        assert_eq!(buffer.is_locked(), initially_locked);
+        assert!(buffer.integrity_check());
+    }
+
+    #[test]
+    fn test_derive_key_matches_hkdf_for_seeded_entropy() {
+        use crate::entropy::{with_entropy_source, DeterministicSource};
+        use std::sync::Arc;
+
+        let seeded = || Arc::new(DeterministicSource::from_seed(11));
+        let derive = |context: &[u8]| {
+            with_entropy_source(seeded(), || {
+                let mut buffer = SecureBuffer::new(64).unwrap();
+                derive_key_into(&mut buffer, context, 64).unwrap();
+                buffer.as_slice().unwrap().to_vec()
+            })
+        };
+        let key = derive(b"sprint/a");
+        // Same seed, same context: same key; the context alone separates the keys
+        assert_eq!(derive(b"sprint/a"), key);
+        assert_ne!(derive(b"sprint/b"), key);
+
+        // RFC 5869 expansion by hand from the same IKM
+        let ikm = with_entropy_source(seeded(), || entropy::hybrid_entropy(&[]));
+        let prk = hmac_sha256(KDF_SALT, &[&ikm[..]]).unwrap();
+        let t1 = hmac_sha256(&prk[..], &[b"sprint/a", &[1]]).unwrap();
+        let t2 = hmac_sha256(&prk[..], &[&t1[..], b"sprint/a", &[2]]).unwrap();
+        assert_eq!(&key[..32], &t1[..]);
+        assert_eq!(&key[32..], &t2[..]);
+    }
+
+    #[test]
+    fn test_fill_secure_buffer_entropy_c() {
+        let handle = CSecureBuffer::new(32);
+        unsafe {
+            assert_eq!(fill_secure_buffer_entropy_c(handle, 1), SECURE_BUFFER_OK);
+            assert_eq!((*(*handle).inner).len(), 32);
+            assert_eq!(fill_secure_buffer_entropy_c(handle, 7), SECURE_BUFFER_ERR_INVALID);
+            assert_eq!(fill_secure_buffer_entropy_c(std::ptr::null_mut(), 0), SECURE_BUFFER_ERR_INVALID);
+            crate::secure_buffer_destroy(handle);
+        }
+    }
 }
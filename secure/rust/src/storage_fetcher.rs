@@ -0,0 +1,571 @@
+// SPDX-License-Identifier: MIT
+// Universal Sprint - Byte-range fetchers for decentralized storage networks
+
+//! Fetch byte ranges of stored content over HTTP gateways.
+//!
+//! Each network gets a [`StorageFetcher`] keyed by the protocol string carried on a
+//! [`StorageChallenge`](crate::storage_verifier::StorageChallenge). Ranges are requested
+//! with an HTTP `Range` header; a gateway that ignores it and answers `200` with the full
+//! body is still handled by skipping to the offset while streaming. Every transport
+//! failure, non-success status or empty range maps to
+//! [`StorageVerificationError::NetworkError`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use reqwest::{header, Client, StatusCode};
+
+use crate::storage_verifier::{GatewayStats, StorageVerificationError};
+
+const USER_AGENT: &str = "UniversalSprint/1.0";
+
+/// Per-request timeout used when a fetcher config does not set one
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Used when IPFS_GATEWAYS is unset
+pub const DEFAULT_IPFS_GATEWAYS: [&str; 3] = [
+    "https://ipfs.io/ipfs",
+    "https://cloudflare-ipfs.com/ipfs",
+    "https://gateway.pinata.cloud/ipfs",
+];
+
+/// Overall time budget for fetching one IPFS range across all gateways
+pub const DEFAULT_IPFS_DEADLINE: Duration = Duration::from_secs(15);
+
+/// Used when ARWEAVE_GATEWAY is unset
+pub const DEFAULT_ARWEAVE_GATEWAY: &str = "https://arweave.net";
+
+/// Used when FILECOIN_GATEWAY is unset: a local lotus/boost retrieval gateway
+pub const DEFAULT_FILECOIN_GATEWAY: &str = "http://127.0.0.1:7777";
+
+/// Reads a byte range of content addressed by a network-specific id
+#[async_trait]
+pub trait StorageFetcher: Send + Sync {
+    /// Protocol name matched against `StorageChallenge::protocol`, e.g. "ipfs"
+    fn protocol(&self) -> &'static str;
+
+    /// Up to `len` bytes starting at `offset`; fewer only when the content ends first
+    async fn fetch_range(&self, id: &str, offset: u64, len: u64) -> Result<Vec<u8>, StorageVerificationError>;
+}
+
+fn network_error(message: String) -> StorageVerificationError {
+    StorageVerificationError::NetworkError { source: message.into() }
+}
+
+fn build_client(timeout: Duration) -> Client {
+    Client::builder()
+        .timeout(timeout)
+        .user_agent(USER_AGENT)
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+/// Content ids end up in URL paths, so only plain id characters are accepted
+fn validate_id(id: &str) -> Result<(), StorageVerificationError> {
+    if id.is_empty() || id.len() > 128 || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(StorageVerificationError::InvalidInput {
+            field: "id".to_string(),
+            reason: "Invalid content id format".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_range(len: u64) -> Result<(), StorageVerificationError> {
+    if len == 0 {
+        return Err(StorageVerificationError::InvalidInput {
+            field: "len".to_string(),
+            reason: "Range length must be non-zero".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// GET `url` for `len` bytes at `offset`
+async fn fetch_http_range(
+    client: &Client,
+    url: &str,
+    bearer_token: Option<&str>,
+    offset: u64,
+    len: u64,
+) -> Result<Vec<u8>, StorageVerificationError> {
+    let last = offset.checked_add(len - 1).ok_or_else(|| StorageVerificationError::InvalidInput {
+        field: "offset".to_string(),
+        reason: "Range end overflows".to_string(),
+    })?;
+    let mut request = client.get(url).header(header::RANGE, format!("bytes={}-{}", offset, last));
+    if let Some(token) = bearer_token {
+        request = request.bearer_auth(token);
+    }
+    let mut response = request
+        .send()
+        .await
+        .map_err(|e| network_error(format!("request to {} failed: {}", url, e)))?;
+
+    // A 200 means the gateway ignored the Range header and is sending the whole body
+    let mut skip = match response.status() {
+        StatusCode::PARTIAL_CONTENT => 0,
+        StatusCode::OK => offset,
+        status => return Err(network_error(format!("HTTP {} from {}", status, url))),
+    };
+
+    let mut data = Vec::with_capacity(len.min(1 << 20) as usize);
+    while (data.len() as u64) < len {
+        let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| network_error(format!("failed to read response from {}: {}", url, e)))?
+        else {
+            break;
+        };
+        let skipped = skip.min(chunk.len() as u64) as usize;
+        skip -= skipped as u64;
+        let wanted = (len - data.len() as u64).min((chunk.len() - skipped) as u64) as usize;
+        data.extend_from_slice(&chunk[skipped..skipped + wanted]);
+    }
+
+    if data.is_empty() {
+        return Err(network_error(format!("no data at offset {} from {}", offset, url)));
+    }
+    Ok(data)
+}
+
+fn normalize_gateways<I: IntoIterator<Item = S>, S: AsRef<str>>(gateways: I) -> Vec<String> {
+    gateways
+        .into_iter()
+        .map(|g| g.as_ref().trim().trim_end_matches('/').to_string())
+        .filter(|g| !g.is_empty())
+        .collect()
+}
+
+fn gateway_from_env(var: &str, default: &str) -> String {
+    normalize_gateways(std::env::var(var).ok()).pop().unwrap_or_else(|| default.to_string())
+}
+
+/// IPFS gateway settings
+#[derive(Debug, Clone)]
+pub struct IpfsFetcherConfig {
+    /// Gateway base URLs such as `https://gw.example.com/ipfs`, in preference order
+    pub gateways: Vec<String>,
+    /// Budget for one `fetch_range` across every gateway tried
+    pub deadline: Duration,
+    /// Budget for a single gateway request
+    pub request_timeout: Duration,
+}
+
+impl IpfsFetcherConfig {
+    /// Gateways from IPFS_GATEWAYS (comma-separated), falling back to the public defaults
+    pub fn from_env() -> Self {
+        let configured = std::env::var("IPFS_GATEWAYS").unwrap_or_default();
+        let gateways = normalize_gateways(configured.split(','));
+        Self {
+            gateways: if gateways.is_empty() { normalize_gateways(DEFAULT_IPFS_GATEWAYS) } else { gateways },
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for IpfsFetcherConfig {
+    fn default() -> Self {
+        Self {
+            gateways: normalize_gateways(DEFAULT_IPFS_GATEWAYS),
+            deadline: DEFAULT_IPFS_DEADLINE,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+}
+
+/// Fetches from IPFS gateways, racing the first two and falling back to the rest in order
+pub struct IpfsFetcher {
+    client: Client,
+    // No overall timeout, so long downloads are not cut off; stalls are bounded by the caller
+    stream_client: Client,
+    config: IpfsFetcherConfig,
+    stats: Arc<Mutex<HashMap<String, GatewayStats>>>,
+}
+
+impl IpfsFetcher {
+    pub fn new(config: IpfsFetcherConfig) -> Self {
+        Self {
+            client: build_client(config.request_timeout),
+            stream_client: Client::builder()
+                .connect_timeout(config.request_timeout)
+                .user_agent(USER_AGENT)
+                .build()
+                .unwrap_or_else(|_| Client::new()),
+            config: IpfsFetcherConfig { gateways: normalize_gateways(&config.gateways), ..config },
+            stats: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn config(&self) -> &IpfsFetcherConfig {
+        &self.config
+    }
+
+    /// Per-gateway outcomes, keyed by gateway base URL
+    pub fn gateway_stats(&self) -> HashMap<String, GatewayStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn reset_gateway_stats(&self) {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Race the first two gateways, then try the rest one at a time
+    async fn race_gateways(&self, cid: &str, offset: u64, len: u64) -> Result<Vec<u8>, StorageVerificationError> {
+        let mut gateways = self.config.gateways.iter();
+        if let Some(first_gateway) = gateways.next() {
+            let mut first = Box::pin(self.fetch_via_gateway(first_gateway, cid, offset, len));
+            match gateways.next() {
+                Some(second_gateway) => {
+                    let mut second = Box::pin(self.fetch_via_gateway(second_gateway, cid, offset, len));
+                    let (mut first_live, mut second_live) = (true, true);
+                    while first_live || second_live {
+                        tokio::select! {
+                            result = &mut first, if first_live => match result {
+                                Ok(data) => return Ok(data),
+                                Err(_) => first_live = false,
+                            },
+                            result = &mut second, if second_live => match result {
+                                Ok(data) => return Ok(data),
+                                Err(_) => second_live = false,
+                            },
+                        }
+                    }
+                }
+                None => {
+                    if let Ok(data) = first.await {
+                        return Ok(data);
+                    }
+                }
+            }
+        }
+
+        for gateway in gateways {
+            if let Ok(data) = self.fetch_via_gateway(gateway, cid, offset, len).await {
+                return Ok(data);
+            }
+        }
+
+        Err(network_error("Failed to fetch from all IPFS gateways".to_string()))
+    }
+
+    /// Whole-content response from the first gateway that answers with a success status,
+    /// trying gateways in order. The body is left unread for the caller to stream.
+    pub async fn open_stream(&self, cid: &str) -> Result<reqwest::Response, StorageVerificationError> {
+        validate_id(cid)?;
+        for gateway in &self.config.gateways {
+            self.record_attempt(gateway);
+            let started = Instant::now();
+            let url = format!("{}/{}?format=raw", gateway, cid);
+            let result = match tokio::time::timeout(self.config.request_timeout, self.stream_client.get(&url).send()).await {
+                Ok(Ok(response)) if response.status().is_success() => Ok(response),
+                Ok(Ok(response)) => Err(network_error(format!("HTTP {} from {}", response.status(), url))),
+                Ok(Err(e)) => Err(network_error(format!("request to {} failed: {}", url, e))),
+                Err(_) => Err(network_error(format!("no response from {} within {:?}", url, self.config.request_timeout))),
+            };
+            self.record_outcome(gateway, started, &result);
+            if let Ok(response) = result {
+                return Ok(response);
+            }
+        }
+        Err(network_error("Failed to fetch from all IPFS gateways".to_string()))
+    }
+
+    /// One fetch attempt, recorded in the gateway's stats
+    async fn fetch_via_gateway(&self, gateway: &str, cid: &str, offset: u64, len: u64) -> Result<Vec<u8>, StorageVerificationError> {
+        self.record_attempt(gateway);
+        let started = Instant::now();
+        let url = format!("{}/{}?format=raw", gateway, cid);
+        let result = fetch_http_range(&self.client, &url, None, offset, len).await;
+        self.record_outcome(gateway, started, &result);
+        result
+    }
+
+    fn record_attempt(&self, gateway: &str) {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).entry(gateway.to_string()).or_default().attempts += 1;
+    }
+
+    fn record_outcome<T>(&self, gateway: &str, started: Instant, result: &Result<T, StorageVerificationError>) {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = stats.entry(gateway.to_string()).or_default();
+        match result {
+            Ok(_) => {
+                let latency = started.elapsed().as_secs_f64() * 1000.0;
+                stats.average_latency_ms = if stats.successes == 0 {
+                    latency
+                } else {
+                    0.2 * latency + 0.8 * stats.average_latency_ms
+                };
+                stats.successes += 1;
+            }
+            Err(e) => {
+                stats.failures += 1;
+                log::warn!("Failed to fetch from {}: {:?}", gateway, e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl StorageFetcher for IpfsFetcher {
+    fn protocol(&self) -> &'static str {
+        "ipfs"
+    }
+
+    /// Fails with `TimeoutExceeded` when no gateway answers within the deadline
+    async fn fetch_range(&self, cid: &str, offset: u64, len: u64) -> Result<Vec<u8>, StorageVerificationError> {
+        validate_id(cid)?;
+        validate_range(len)?;
+        let deadline = self.config.deadline;
+        tokio::time::timeout(deadline, self.race_gateways(cid, offset, len))
+            .await
+            .map_err(|_| StorageVerificationError::TimeoutExceeded { timeout_ms: deadline.as_millis() as u64 })?
+    }
+}
+
+/// Arweave gateway settings
+#[derive(Debug, Clone)]
+pub struct ArweaveFetcherConfig {
+    /// Gateway base URL; transactions are read from `{gateway}/{txid}`
+    pub gateway: String,
+    pub timeout: Duration,
+}
+
+impl ArweaveFetcherConfig {
+    /// Gateway from ARWEAVE_GATEWAY, falling back to arweave.net
+    pub fn from_env() -> Self {
+        Self { gateway: gateway_from_env("ARWEAVE_GATEWAY", DEFAULT_ARWEAVE_GATEWAY), ..Self::default() }
+    }
+}
+
+impl Default for ArweaveFetcherConfig {
+    fn default() -> Self {
+        Self { gateway: DEFAULT_ARWEAVE_GATEWAY.to_string(), timeout: DEFAULT_REQUEST_TIMEOUT }
+    }
+}
+
+/// Fetches transaction data from an Arweave gateway
+pub struct ArweaveFetcher {
+    client: Client,
+    gateway: String,
+}
+
+impl ArweaveFetcher {
+    pub fn new(config: ArweaveFetcherConfig) -> Self {
+        Self {
+            client: build_client(config.timeout),
+            gateway: config.gateway.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl StorageFetcher for ArweaveFetcher {
+    fn protocol(&self) -> &'static str {
+        "arweave"
+    }
+
+    async fn fetch_range(&self, txid: &str, offset: u64, len: u64) -> Result<Vec<u8>, StorageVerificationError> {
+        validate_id(txid)?;
+        validate_range(len)?;
+        fetch_http_range(&self.client, &format!("{}/{}", self.gateway, txid), None, offset, len).await
+    }
+}
+
+/// Filecoin retrieval gateway settings
+#[derive(Debug, Clone)]
+pub struct FilecoinFetcherConfig {
+    /// Gateway base URL; pieces are read from `{gateway}/piece/{piece_cid}`
+    pub gateway: String,
+    /// Sent as a bearer token when the gateway requires authorization
+    pub auth_token: Option<String>,
+    pub timeout: Duration,
+}
+
+impl FilecoinFetcherConfig {
+    /// Gateway from FILECOIN_GATEWAY and token from FILECOIN_GATEWAY_TOKEN
+    pub fn from_env() -> Self {
+        Self {
+            gateway: gateway_from_env("FILECOIN_GATEWAY", DEFAULT_FILECOIN_GATEWAY),
+            auth_token: std::env::var("FILECOIN_GATEWAY_TOKEN").ok().filter(|t| !t.is_empty()),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for FilecoinFetcherConfig {
+    fn default() -> Self {
+        Self { gateway: DEFAULT_FILECOIN_GATEWAY.to_string(), auth_token: None, timeout: DEFAULT_REQUEST_TIMEOUT }
+    }
+}
+
+/// Fetches piece data from a lotus/boost HTTP retrieval gateway
+pub struct FilecoinFetcher {
+    client: Client,
+    gateway: String,
+    auth_token: Option<String>,
+}
+
+impl FilecoinFetcher {
+    pub fn new(config: FilecoinFetcherConfig) -> Self {
+        Self {
+            client: build_client(config.timeout),
+            gateway: config.gateway.trim_end_matches('/').to_string(),
+            auth_token: config.auth_token,
+        }
+    }
+}
+
+#[async_trait]
+impl StorageFetcher for FilecoinFetcher {
+    fn protocol(&self) -> &'static str {
+        "filecoin"
+    }
+
+    async fn fetch_range(&self, piece_cid: &str, offset: u64, len: u64) -> Result<Vec<u8>, StorageVerificationError> {
+        validate_id(piece_cid)?;
+        validate_range(len)?;
+        let url = format!("{}/piece/{}", self.gateway, piece_cid);
+        fetch_http_range(&self.client, &url, self.auth_token.as_deref(), offset, len).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    fn arweave(server: &MockServer, timeout: Duration) -> ArweaveFetcher {
+        ArweaveFetcher::new(ArweaveFetcherConfig { gateway: server.uri(), timeout })
+    }
+
+    fn assert_network_error(result: Result<Vec<u8>, StorageVerificationError>) {
+        assert!(matches!(result, Err(StorageVerificationError::NetworkError { .. })), "got {:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_arweave_sends_inclusive_range_header() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/tx-abc_123"))
+            .and(header("Range", "bytes=10-19"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(&BODY[10..20]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let fetcher = arweave(&server, Duration::from_secs(5));
+        assert_eq!(fetcher.fetch_range("tx-abc_123", 10, 10).await.unwrap(), b"abcdefghij");
+    }
+
+    #[tokio::test]
+    async fn test_full_body_response_is_sliced_to_the_range() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+            .mount(&server)
+            .await;
+
+        let fetcher = arweave(&server, Duration::from_secs(5));
+        assert_eq!(fetcher.fetch_range("tx", 4, 6).await.unwrap(), b"456789");
+        // The last chunk of a file may be shorter than requested
+        assert_eq!(fetcher.fetch_range("tx", 30, 16).await.unwrap(), b"uvwxyz");
+        assert_network_error(fetcher.fetch_range("tx", 100, 8).await);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_partial_response_is_truncated() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(BODY))
+            .mount(&server)
+            .await;
+
+        let fetcher = arweave(&server, Duration::from_secs(5));
+        assert_eq!(fetcher.fetch_range("tx", 0, 4).await.unwrap(), b"0123");
+    }
+
+    #[tokio::test]
+    async fn test_http_failures_map_to_network_error() {
+        for status in [404, 500, 503] {
+            let server = MockServer::start().await;
+            Mock::given(method("GET")).respond_with(ResponseTemplate::new(status)).mount(&server).await;
+            assert_network_error(arweave(&server, Duration::from_secs(5)).fetch_range("tx", 0, 8).await);
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(206).set_delay(Duration::from_secs(30)))
+            .mount(&server)
+            .await;
+        assert_network_error(arweave(&server, Duration::from_millis(200)).fetch_range("tx", 0, 8).await);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_ids_that_would_escape_the_url_path() {
+        let server = MockServer::start().await;
+        let fetcher = arweave(&server, Duration::from_secs(5));
+        for id in ["", "../admin", "tx?x=1", "tx/other"] {
+            assert!(matches!(
+                fetcher.fetch_range(id, 0, 8).await,
+                Err(StorageVerificationError::InvalidInput { .. })
+            ));
+        }
+        assert!(matches!(fetcher.fetch_range("tx", 0, 0).await, Err(StorageVerificationError::InvalidInput { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_filecoin_reads_pieces_with_bearer_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/piece/baga6ea4seaq"))
+            .and(header("Range", "bytes=32-35"))
+            .and(header("Authorization", "Bearer secret"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(&BODY[32..36]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET")).respond_with(ResponseTemplate::new(401)).mount(&server).await;
+
+        let config = FilecoinFetcherConfig {
+            gateway: format!("{}/", server.uri()),
+            auth_token: Some("secret".to_string()),
+            timeout: Duration::from_secs(5),
+        };
+        assert_eq!(FilecoinFetcher::new(config.clone()).fetch_range("baga6ea4seaq", 32, 4).await.unwrap(), b"wxyz");
+
+        let anonymous = FilecoinFetcher::new(FilecoinFetcherConfig { auth_token: None, ..config });
+        assert_network_error(anonymous.fetch_range("baga6ea4seaq", 32, 4).await);
+    }
+
+    #[tokio::test]
+    async fn test_ipfs_requests_raw_block_range() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ipfs/bafycid"))
+            .and(header("Range", "bytes=8-15"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(&BODY[8..16]))
+            .mount(&server)
+            .await;
+
+        let fetcher = IpfsFetcher::new(IpfsFetcherConfig {
+            gateways: vec![format!("{}/ipfs/", server.uri())],
+            ..IpfsFetcherConfig::default()
+        });
+        assert_eq!(fetcher.fetch_range("bafycid", 8, 8).await.unwrap(), b"89abcdef");
+        let stats = fetcher.gateway_stats();
+        assert_eq!(stats[&format!("{}/ipfs", server.uri())].successes, 1);
+
+        let failing = IpfsFetcher::new(IpfsFetcherConfig {
+            gateways: vec![format!("{}/missing", server.uri())],
+            ..IpfsFetcherConfig::default()
+        });
+        assert_network_error(failing.fetch_range("bafycid", 8, 8).await);
+    }
+}
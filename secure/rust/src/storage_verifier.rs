@@ -2,14 +2,23 @@
 // Universal Sprint - Simplified Storage Verification with Optional IPFS
 // Enhanced Security, DoS Protection, and Network-Agnostic Design
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::{Signature, VerifyingKey};
+use crate::clock::{Clock, SystemClock};
+use crate::merkle::{self, MerkleProof};
+use std::time::Duration;
 use sha2::{Sha256, Digest};
 use rand::{thread_rng, RngCore, Rng};
 
+use futures::StreamExt;
 #[cfg(feature = "ipfs")]
-use reqwest::Client;
+use crate::storage_fetcher::{
+    ArweaveFetcher, ArweaveFetcherConfig, FilecoinFetcher, FilecoinFetcherConfig, IpfsFetcher, IpfsFetcherConfig,
+    StorageFetcher,
+};
 
 // NOTE: logging and async locks currently not used in this module; keep commented imports
 // use thiserror::Error;
@@ -18,96 +27,645 @@
 use hex;
 
 /// Commitment algorithms for file verification
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CommitmentAlg {
     Sha256Chunks,
     MerkleSha256 { root: [u8; 32], chunk_size: u32 }
 }
 
+/// Commitment metadata for a file: (alg, chunk_size, total_chunks)
+pub type ChunkMeta = (CommitmentAlg, u32, u64);
+
+/// Leaves written per `put_leaves_batch` call when registering a file
+const LEAF_BATCH_SIZE: usize = 4096;
+
+/// Tenant used by the tenant-less verifier methods, and the owner of commitments stored
+/// before tenants existed
+pub const DEFAULT_TENANT: &str = "default";
+
+/// Longest tenant id accepted by the `_for_tenant` methods
+pub const MAX_TENANT_ID_LEN: usize = 128;
+
+/// Persistence for registered file commitments, keyed by (tenant, file).
+///
+/// Registration writes every leaf batch before the metadata, so a file only becomes
+/// challengeable once all of its leaves are stored. `cleanup` removes the leaves of
+/// registrations that never got that far.
+#[async_trait]
+pub trait CommitmentBackend: Send + Sync {
+    async fn get_meta(&self, tenant_id: &str, file_id: &str) -> Result<Option<ChunkMeta>, StorageVerificationError>;
+    async fn get_leaf(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        chunk_index: u64,
+    ) -> Result<Option<[u8; 32]>, StorageVerificationError>;
+    async fn put_meta(&self, tenant_id: &str, file_id: &str, meta: ChunkMeta) -> Result<(), StorageVerificationError>;
+    async fn put_leaves_batch(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        first_index: u64,
+        leaves: &[[u8; 32]],
+    ) -> Result<(), StorageVerificationError>;
+    /// Drop leaves that belong to no registered file; returns how many were removed
+    async fn cleanup(&self) -> Result<u64, StorageVerificationError>;
+    /// Store a provider's ed25519 public key, replacing any previous one
+    async fn put_provider_key(&self, provider: &str, public_key: [u8; 32]) -> Result<(), StorageVerificationError>;
+    async fn get_provider_key(&self, provider: &str) -> Result<Option<[u8; 32]>, StorageVerificationError>;
+    /// Store a provider's reputation, replacing any previous record
+    async fn put_reputation(&self, reputation: &ProviderReputation) -> Result<(), StorageVerificationError>;
+    async fn get_reputation(&self, provider: &str) -> Result<Option<ProviderReputation>, StorageVerificationError>;
+    async fn list_reputations(&self) -> Result<Vec<ProviderReputation>, StorageVerificationError>;
+}
+
 /// Commitment store for file integrity verification
 #[derive(Clone, Default)]
 pub struct CommitmentStore {
-    // (file_id, chunk_index) -> leaf hash (sha256)
-    leaves: HashMap<(String, u64), [u8; 32]>,
-    meta: HashMap<String, (CommitmentAlg, u32, u64)>, // (alg, chunk_size, total_chunks)
-    beacon_timestamps: HashMap<String, u64>, // beacon -> timestamp for cleanup
+    // (tenant_id, file_id, chunk_index) -> leaf hash (sha256)
+    leaves: HashMap<(String, String, u64), [u8; 32]>,
+    // (tenant_id, file_id) -> metadata
+    meta: HashMap<(String, String), ChunkMeta>,
+    // provider -> ed25519 public key
+    provider_keys: HashMap<String, [u8; 32]>,
+    // provider -> reputation
+    reputations: HashMap<String, ProviderReputation>,
 }
 
 impl CommitmentStore {
     /// Register SHA256 chunks for a file
     pub fn register_sha256_chunks(
         &mut self,
+        tenant_id: &str,
         file_id: &str,
         chunk_size: u32,
         leaf_hashes: Vec<[u8; 32]>
     ) {
         let total = leaf_hashes.len() as u64;
+        self.put_leaves(tenant_id, file_id, 0, &leaf_hashes);
         self.meta.insert(
-            file_id.to_string(),
+            (tenant_id.to_string(), file_id.to_string()),
             (CommitmentAlg::Sha256Chunks, chunk_size, total)
         );
-        for (i, h) in leaf_hashes.into_iter().enumerate() {
-            self.leaves.insert((file_id.to_string(), i as u64), h);
-        }
     }
 
     /// Register Merkle root for a file
     pub fn register_merkle_root(
         &mut self,
+        tenant_id: &str,
         file_id: &str,
         root: [u8; 32],
         chunk_size: u32,
         total_chunks: u64
     ) {
         self.meta.insert(
-            file_id.to_string(),
+            (tenant_id.to_string(), file_id.to_string()),
             (CommitmentAlg::MerkleSha256 { root, chunk_size }, chunk_size, total_chunks)
         );
     }
 
+    /// Store leaf hashes starting at `first_index`
+    pub fn put_leaves(&mut self, tenant_id: &str, file_id: &str, first_index: u64, leaves: &[[u8; 32]]) {
+        for (i, h) in leaves.iter().enumerate() {
+            self.leaves.insert((tenant_id.to_string(), file_id.to_string(), first_index + i as u64), *h);
+        }
+    }
+
     /// Get chunk metadata for a file
-    pub fn get_chunk_meta(&self, file_id: &str) -> Option<(CommitmentAlg, u32, u64)> {
-        self.meta.get(file_id).cloned()
+    pub fn get_chunk_meta(&self, tenant_id: &str, file_id: &str) -> Option<ChunkMeta> {
+        self.meta.get(&(tenant_id.to_string(), file_id.to_string())).cloned()
     }
 
     /// Get expected leaf hash for a chunk
-    pub fn expected_leaf(&self, file_id: &str, chunk_index: u64) -> Option<[u8; 32]> {
-        self.leaves.get(&(file_id.to_string(), chunk_index)).copied()
+    pub fn expected_leaf(&self, tenant_id: &str, file_id: &str, chunk_index: u64) -> Option<[u8; 32]> {
+        self.leaves.get(&(tenant_id.to_string(), file_id.to_string(), chunk_index)).copied()
+    }
+
+    /// Remove leaves whose file has no metadata
+    pub fn remove_orphaned_leaves(&mut self) -> u64 {
+        let before = self.leaves.len();
+        let meta = &self.meta;
+        self.leaves.retain(|(tenant_id, file_id, _), _| meta.contains_key(&(tenant_id.clone(), file_id.clone())));
+        (before - self.leaves.len()) as u64
+    }
+}
+
+/// In-memory backend; commitments are lost on restart
+#[derive(Default)]
+pub struct MemoryCommitmentBackend {
+    store: std::sync::RwLock<CommitmentStore>,
+}
+
+impl MemoryCommitmentBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn lock_poisoned() -> StorageVerificationError {
+    StorageVerificationError::Backend { reason: "commitment store lock poisoned".to_string() }
+}
+
+#[async_trait]
+impl CommitmentBackend for MemoryCommitmentBackend {
+    async fn get_meta(&self, tenant_id: &str, file_id: &str) -> Result<Option<ChunkMeta>, StorageVerificationError> {
+        Ok(self.store.read().map_err(|_| lock_poisoned())?.get_chunk_meta(tenant_id, file_id))
+    }
+
+    async fn get_leaf(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        chunk_index: u64,
+    ) -> Result<Option<[u8; 32]>, StorageVerificationError> {
+        Ok(self.store.read().map_err(|_| lock_poisoned())?.expected_leaf(tenant_id, file_id, chunk_index))
+    }
+
+    async fn put_meta(&self, tenant_id: &str, file_id: &str, meta: ChunkMeta) -> Result<(), StorageVerificationError> {
+        let key = (tenant_id.to_string(), file_id.to_string());
+        self.store.write().map_err(|_| lock_poisoned())?.meta.insert(key, meta);
+        Ok(())
+    }
+
+    async fn put_leaves_batch(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        first_index: u64,
+        leaves: &[[u8; 32]],
+    ) -> Result<(), StorageVerificationError> {
+        self.store.write().map_err(|_| lock_poisoned())?.put_leaves(tenant_id, file_id, first_index, leaves);
+        Ok(())
+    }
+
+    async fn cleanup(&self) -> Result<u64, StorageVerificationError> {
+        Ok(self.store.write().map_err(|_| lock_poisoned())?.remove_orphaned_leaves())
+    }
+
+    async fn put_provider_key(&self, provider: &str, public_key: [u8; 32]) -> Result<(), StorageVerificationError> {
+        self.store.write().map_err(|_| lock_poisoned())?.provider_keys.insert(provider.to_string(), public_key);
+        Ok(())
+    }
+
+    async fn get_provider_key(&self, provider: &str) -> Result<Option<[u8; 32]>, StorageVerificationError> {
+        Ok(self.store.read().map_err(|_| lock_poisoned())?.provider_keys.get(provider).copied())
+    }
+
+    async fn put_reputation(&self, reputation: &ProviderReputation) -> Result<(), StorageVerificationError> {
+        let mut store = self.store.write().map_err(|_| lock_poisoned())?;
+        store.reputations.insert(reputation.provider.clone(), reputation.clone());
+        Ok(())
+    }
+
+    async fn get_reputation(&self, provider: &str) -> Result<Option<ProviderReputation>, StorageVerificationError> {
+        Ok(self.store.read().map_err(|_| lock_poisoned())?.reputations.get(provider).cloned())
+    }
+
+    async fn list_reputations(&self) -> Result<Vec<ProviderReputation>, StorageVerificationError> {
+        Ok(self.store.read().map_err(|_| lock_poisoned())?.reputations.values().cloned().collect())
+    }
+}
+
+/// SQLite backend; commitments survive restarts and leaves stay on disk
+#[cfg(feature = "rusqlite")]
+pub struct SqliteCommitmentBackend {
+    conn: Arc<std::sync::Mutex<rusqlite::Connection>>,
+}
+
+#[cfg(feature = "rusqlite")]
+impl SqliteCommitmentBackend {
+    pub fn open(path: &std::path::Path) -> Result<Self, StorageVerificationError> {
+        let conn = rusqlite::Connection::open(path).map_err(|e| StorageVerificationError::Backend {
+            reason: format!("failed to open {}: {}", path.display(), e),
+        })?;
+        migrate_to_tenants(&conn).map_err(sqlite_error)?;
+        conn.execute_batch(&format!(
+            "{}
+            CREATE TABLE IF NOT EXISTS provider_keys (
+                provider TEXT PRIMARY KEY,
+                public_key BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS provider_reputation (
+                provider TEXT PRIMARY KEY,
+                challenges_issued INTEGER NOT NULL,
+                proofs_verified INTEGER NOT NULL,
+                failures INTEGER NOT NULL,
+                average_latency_ms REAL NOT NULL,
+                last_seen INTEGER,
+                score REAL NOT NULL,
+                weighted_successes REAL NOT NULL,
+                weighted_outcomes REAL NOT NULL,
+                updated_at INTEGER NOT NULL
+            );",
+            COMMITMENT_TABLES
+        ))
+        .map_err(sqlite_error)?;
+        Ok(SqliteCommitmentBackend { conn: Arc::new(std::sync::Mutex::new(conn)) })
+    }
+
+    /// Run a query on the blocking pool so large batches do not stall the runtime
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, StorageVerificationError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&rusqlite::Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|_| lock_poisoned())?;
+            f(&conn).map_err(sqlite_error)
+        })
+        .await
+        .map_err(|e| StorageVerificationError::Backend { reason: e.to_string() })?
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+const COMMITMENT_TABLES: &str = "CREATE TABLE IF NOT EXISTS commitment_meta (
+    tenant_id TEXT NOT NULL,
+    file_id TEXT NOT NULL,
+    merkle_root BLOB,
+    chunk_size INTEGER NOT NULL,
+    total_chunks INTEGER NOT NULL,
+    PRIMARY KEY (tenant_id, file_id)
+);
+CREATE TABLE IF NOT EXISTS commitment_leaves (
+    tenant_id TEXT NOT NULL,
+    file_id TEXT NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    leaf BLOB NOT NULL,
+    PRIMARY KEY (tenant_id, file_id, chunk_index)
+) WITHOUT ROWID;";
+
+/// Move commitments from a store created before tenants existed into `DEFAULT_TENANT`
+#[cfg(feature = "rusqlite")]
+fn migrate_to_tenants(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    let column_count = |table: &str, column: &str| -> rusqlite::Result<i64> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+            rusqlite::params![table, column],
+            |row| row.get(0),
+        )
+    };
+    if column_count("commitment_meta", "file_id")? == 0 || column_count("commitment_meta", "tenant_id")? > 0 {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(&format!(
+        "ALTER TABLE commitment_meta RENAME TO commitment_meta_untenanted;
+        ALTER TABLE commitment_leaves RENAME TO commitment_leaves_untenanted;
+        {}
+        INSERT INTO commitment_meta (tenant_id, file_id, merkle_root, chunk_size, total_chunks)
+            SELECT '{tenant}', file_id, merkle_root, chunk_size, total_chunks FROM commitment_meta_untenanted;
+        INSERT INTO commitment_leaves (tenant_id, file_id, chunk_index, leaf)
+            SELECT '{tenant}', file_id, chunk_index, leaf FROM commitment_leaves_untenanted;
+        DROP TABLE commitment_meta_untenanted;
+        DROP TABLE commitment_leaves_untenanted;",
+        COMMITMENT_TABLES,
+        tenant = DEFAULT_TENANT
+    ))?;
+    tx.commit()?;
+    log::info!("Migrated commitment store to per-tenant keys under tenant '{}'", DEFAULT_TENANT);
+    Ok(())
+}
+
+#[cfg(feature = "rusqlite")]
+fn sqlite_error(e: rusqlite::Error) -> StorageVerificationError {
+    StorageVerificationError::Backend { reason: e.to_string() }
+}
+
+#[cfg(feature = "rusqlite")]
+fn bytes32_from_blob(blob: Vec<u8>) -> rusqlite::Result<[u8; 32]> {
+    let len = blob.len();
+    blob.try_into().map_err(|_| rusqlite::Error::InvalidColumnType(0, format!("{}-byte value", len), rusqlite::types::Type::Blob))
+}
+
+#[cfg(feature = "rusqlite")]
+const REPUTATION_COLUMNS: &str = "provider, challenges_issued, proofs_verified, failures, average_latency_ms, \
+    last_seen, score, weighted_successes, weighted_outcomes, updated_at";
+
+#[cfg(feature = "rusqlite")]
+fn reputation_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ProviderReputation> {
+    Ok(ProviderReputation {
+        provider: row.get(0)?,
+        challenges_issued: row.get::<_, i64>(1)? as u64,
+        proofs_verified: row.get::<_, i64>(2)? as u64,
+        failures: row.get::<_, i64>(3)? as u64,
+        average_latency_ms: row.get(4)?,
+        last_seen: row.get::<_, Option<i64>>(5)?.map(|t| t as u64),
+        score: row.get(6)?,
+        weighted_successes: row.get(7)?,
+        weighted_outcomes: row.get(8)?,
+        updated_at: row.get::<_, i64>(9)? as u64,
+    })
+}
+
+#[cfg(feature = "rusqlite")]
+#[async_trait]
+impl CommitmentBackend for SqliteCommitmentBackend {
+    async fn get_meta(&self, tenant_id: &str, file_id: &str) -> Result<Option<ChunkMeta>, StorageVerificationError> {
+        use rusqlite::OptionalExtension;
+        let (tenant_id, file_id) = (tenant_id.to_string(), file_id.to_string());
+        self.with_conn(move |conn| {
+            conn.query_row(
+                "SELECT merkle_root, chunk_size, total_chunks FROM commitment_meta WHERE tenant_id = ?1 AND file_id = ?2",
+                [tenant_id, file_id],
+                |row| {
+                    let chunk_size: u32 = row.get(1)?;
+                    let alg = match row.get::<_, Option<Vec<u8>>>(0)? {
+                        Some(root) => CommitmentAlg::MerkleSha256 { root: bytes32_from_blob(root)?, chunk_size },
+                        None => CommitmentAlg::Sha256Chunks,
+                    };
+                    Ok((alg, chunk_size, row.get::<_, i64>(2)? as u64))
+                },
+            )
+            .optional()
+        })
+        .await
+    }
+
+    async fn get_leaf(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        chunk_index: u64,
+    ) -> Result<Option<[u8; 32]>, StorageVerificationError> {
+        use rusqlite::OptionalExtension;
+        let (tenant_id, file_id) = (tenant_id.to_string(), file_id.to_string());
+        self.with_conn(move |conn| {
+            conn.query_row(
+                "SELECT leaf FROM commitment_leaves WHERE tenant_id = ?1 AND file_id = ?2 AND chunk_index = ?3",
+                rusqlite::params![tenant_id, file_id, chunk_index as i64],
+                |row| bytes32_from_blob(row.get(0)?),
+            )
+            .optional()
+        })
+        .await
+    }
+
+    async fn put_meta(&self, tenant_id: &str, file_id: &str, meta: ChunkMeta) -> Result<(), StorageVerificationError> {
+        let (tenant_id, file_id) = (tenant_id.to_string(), file_id.to_string());
+        let (alg, chunk_size, total_chunks) = meta;
+        let root = match alg {
+            CommitmentAlg::MerkleSha256 { root, .. } => Some(root.to_vec()),
+            CommitmentAlg::Sha256Chunks => None,
+        };
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO commitment_meta (tenant_id, file_id, merkle_root, chunk_size, total_chunks)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![tenant_id, file_id, root, chunk_size, total_chunks as i64],
+            )
+            .map(|_| ())
+        })
+        .await
+    }
+
+    async fn put_leaves_batch(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        first_index: u64,
+        leaves: &[[u8; 32]],
+    ) -> Result<(), StorageVerificationError> {
+        let (tenant_id, file_id) = (tenant_id.to_string(), file_id.to_string());
+        let leaves = leaves.to_vec();
+        self.with_conn(move |conn| {
+            let tx = conn.unchecked_transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO commitment_leaves (tenant_id, file_id, chunk_index, leaf)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )?;
+                for (i, leaf) in leaves.iter().enumerate() {
+                    stmt.execute(rusqlite::params![tenant_id, file_id, (first_index + i as u64) as i64, &leaf[..]])?;
+                }
+            }
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn cleanup(&self) -> Result<u64, StorageVerificationError> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM commitment_leaves WHERE NOT EXISTS (
+                    SELECT 1 FROM commitment_meta
+                    WHERE commitment_meta.tenant_id = commitment_leaves.tenant_id
+                      AND commitment_meta.file_id = commitment_leaves.file_id
+                )",
+                [],
+            )
+            .map(|removed| removed as u64)
+        })
+        .await
+    }
+
+    async fn put_provider_key(&self, provider: &str, public_key: [u8; 32]) -> Result<(), StorageVerificationError> {
+        let provider = provider.to_string();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO provider_keys (provider, public_key) VALUES (?1, ?2)",
+                rusqlite::params![provider, &public_key[..]],
+            )
+            .map(|_| ())
+        })
+        .await
+    }
+
+    async fn get_provider_key(&self, provider: &str) -> Result<Option<[u8; 32]>, StorageVerificationError> {
+        use rusqlite::OptionalExtension;
+        let provider = provider.to_string();
+        self.with_conn(move |conn| {
+            conn.query_row("SELECT public_key FROM provider_keys WHERE provider = ?1", [provider], |row| {
+                bytes32_from_blob(row.get(0)?)
+            })
+            .optional()
+        })
+        .await
     }
 
-    /// Store beacon timestamp for cleanup
-    pub fn store_beacon_timestamp(&mut self, beacon: &str, timestamp: u64) {
-        self.beacon_timestamps.insert(beacon.to_string(), timestamp);
+    async fn put_reputation(&self, reputation: &ProviderReputation) -> Result<(), StorageVerificationError> {
+        let r = reputation.clone();
+        self.with_conn(move |conn| {
+            conn.execute(
+                &format!("INSERT OR REPLACE INTO provider_reputation ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)", REPUTATION_COLUMNS),
+                rusqlite::params![
+                    r.provider,
+                    r.challenges_issued as i64,
+                    r.proofs_verified as i64,
+                    r.failures as i64,
+                    r.average_latency_ms,
+                    r.last_seen.map(|t| t as i64),
+                    r.score,
+                    r.weighted_successes,
+                    r.weighted_outcomes,
+                    r.updated_at as i64,
+                ],
+            )
+            .map(|_| ())
+        })
+        .await
+    }
+
+    async fn get_reputation(&self, provider: &str) -> Result<Option<ProviderReputation>, StorageVerificationError> {
+        use rusqlite::OptionalExtension;
+        let provider = provider.to_string();
+        self.with_conn(move |conn| {
+            conn.query_row(
+                &format!("SELECT {} FROM provider_reputation WHERE provider = ?1", REPUTATION_COLUMNS),
+                [provider],
+                reputation_from_row,
+            )
+            .optional()
+        })
+        .await
     }
 
-    /// Get beacon timestamp
-    pub fn get_beacon_timestamp(&self, beacon: &str) -> Option<u64> {
-        self.beacon_timestamps.get(beacon).copied()
+    async fn list_reputations(&self) -> Result<Vec<ProviderReputation>, StorageVerificationError> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!("SELECT {} FROM provider_reputation", REPUTATION_COLUMNS))?;
+            let rows = stmt.query_map([], reputation_from_row)?;
+            rows.collect()
+        })
+        .await
+    }
+}
+
+/// Where registered commitments are kept
+#[derive(Debug, Clone, Default)]
+pub enum CommitmentBackendConfig {
+    #[default]
+    Memory,
+    #[cfg(feature = "rusqlite")]
+    Sqlite { path: std::path::PathBuf },
+}
+
+/// Verifier construction options
+#[derive(Debug, Clone, Default)]
+pub struct StorageVerifierConfig {
+    pub rate_limit: RateLimitConfig,
+    pub backend: CommitmentBackendConfig,
+    /// Accept position-less `MerkleProof::Legacy` proofs (deprecated)
+    pub allow_legacy_merkle_proofs: bool,
+    /// Default for `StorageChallenge::require_signature` on generated challenges
+    pub require_signatures: bool,
+    /// How long a challenge may go unanswered; `None` keeps the 30 minute default
+    pub challenge_ttl: Option<Duration>,
+    /// Largest file `ingest_ipfs_and_register` will hash; `None` keeps the 1 GiB default
+    pub max_file_size: Option<u64>,
+    /// `verify_proof` calls allowed to run at once; `None` means twice the CPU count
+    pub max_concurrent_verifications: Option<usize>,
+    /// How long `verify_proof` waits for a slot before failing with `Overloaded`
+    pub verification_queue_timeout: Option<Duration>,
+    /// Largest accepted sample in a proof, in bytes; `None` keeps the 1 MiB default
+    pub max_proof_size: Option<usize>,
+    /// Break challenge and proof counters down per tenant in `VerificationMetrics::tenants`
+    pub tenant_metrics: bool,
+    /// How long before a provider's past outcomes count half as much; `None` keeps 7 days
+    pub reputation_half_life: Option<Duration>,
+    /// Reputation score at or below which providers get `MAX_DIFFICULTY` challenges;
+    /// `None` keeps `DEFAULT_REPUTATION_THRESHOLD`
+    pub reputation_threshold: Option<f64>,
+}
+
+/// Default for `StorageVerifierConfig::max_file_size`
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1 << 30;
+
+/// Default for `StorageVerifierConfig::verification_queue_timeout`
+pub const DEFAULT_VERIFICATION_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default for `StorageVerifierConfig::max_proof_size`
+pub const DEFAULT_MAX_PROOF_SIZE: usize = 1 << 20;
+
+/// Longest `Idempotency-Key` accepted by `generate_challenge_idempotent`
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Fresh nonces tried before challenge issuance gives up on finding an unused beacon
+const MAX_NONCE_ATTEMPTS: usize = 8;
+
+fn default_verification_permits() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get()) * 2
+}
+
+/// Challenge id: hex SHA-256 over the tenant, file, provider, nonce and beacon. The beacon
+/// is reserved before the id is derived, so ids stay unique within a single second.
+fn challenge_id(tenant_id: &str, file_id: &str, provider: &str, nonce: u64, beacon: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"UniversalSprint/challenge-id");
+    for field in [tenant_id.as_bytes(), file_id.as_bytes(), provider.as_bytes()] {
+        hasher.update((field.len() as u64).to_le_bytes());
+        hasher.update(field);
     }
+    hasher.update(nonce.to_le_bytes());
+    hasher.update(beacon.as_bytes());
+    hex::encode(hasher.finalize())
+}
 
-    /// Cleanup old beacons
-    pub fn cleanup_old_beacons(&mut self, max_age_secs: u64) {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-        self.beacon_timestamps.retain(|_, ts| now - *ts < max_age_secs);
+fn validate_tenant(tenant_id: &str) -> Result<(), StorageVerificationError> {
+    if tenant_id.is_empty() || tenant_id.len() > MAX_TENANT_ID_LEN {
+        return Err(StorageVerificationError::InvalidInput {
+            field: "tenant_id".to_string(),
+            reason: format!("Must be 1 to {} bytes", MAX_TENANT_ID_LEN),
+        });
     }
+    Ok(())
+}
+
+/// Progress of a streaming ingest, reported after each chunk is hashed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IngestProgress {
+    pub bytes_read: u64,
+    pub chunks_hashed: u64,
+    /// From the response's Content-Length, when the gateway sent one
+    pub total_bytes: Option<u64>,
 }
 
 /// Storage challenge with enhanced cryptographic security
 #[derive(Debug, Clone)]
 pub struct StorageChallenge {
     pub id: String,
+    pub tenant_id: String, // Owner of the commitments being sampled
     pub file_id: String,
     pub provider: String,
     pub nonce: u64,
     pub timestamp: u64,
     pub expiry: u64,
     pub beacon: String,
-    pub difficulty: u8, // Challenge difficulty level
+    pub difficulty: u8, // 1-5; number of chunks the proof must cover
     pub challenge_data: Vec<u8>, // Specific data to prove possession of
     pub sample_offset: u64, // Offset in file to sample
     pub sample_size: u32, // Size of sample to retrieve
-    pub chunk_index: u64, // Which chunk to verify
+    pub chunk_index: u64, // Which chunk to verify; always chunk_indices[0]
+    pub chunk_indices: Vec<u64>, // Every chunk the proof must cover
     pub commitment_alg: String, // "sha256_chunks" or "merkle_sha256"
+    pub require_signature: bool, // Proofs must carry a valid provider signature
+    pub protocol: Option<String>, // Network the content is fetched from, e.g. "arweave"
+}
+
+impl StorageChallenge {
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        now > self.expiry
+    }
+}
+
+/// Default lifetime of an unanswered challenge
+const DEFAULT_CHALLENGE_TTL: Duration = Duration::from_secs(1800);
+
+/// Called once for each challenge that expires without a proof having been submitted
+pub type ChallengeExpiredCallback = Box<dyn Fn(&StorageChallenge) + Send + Sync>;
+
+/// Registered callbacks are shared so the sweep can run them without holding the lock
+type ExpiryCallback = Arc<dyn Fn(&StorageChallenge) + Send + Sync>;
+
+/// (tenant, provider, idempotency key) a challenge was issued under
+type IdempotencyKey = (String, String, String);
+
+/// An outstanding challenge and whether a proof has been checked against it
+#[derive(Debug, Clone)]
+struct ChallengeEntry {
+    challenge: StorageChallenge,
+    answered: bool,
 }
 
 /// Storage proof with cryptographic verification data
@@ -118,8 +676,116 @@ pub struct StorageProof {
     pub provider: String,
     pub timestamp: u64,
     pub proof_data: Vec<u8>, // Actual data sample from storage
-    pub merkle_proof: Option<Vec<String>>, // Optional Merkle tree proof
-    pub signature: Option<String>, // Optional provider signature
+    pub merkle_proof: Option<MerkleProof>, // Required for merkle_sha256 commitments
+    pub signature: Option<String>, // Base64 ed25519 signature over `signing_message()`
+    pub extra_proof_data: Vec<Vec<u8>>, // Samples for chunk_indices[1..], in order
+}
+
+impl StorageProof {
+    /// Canonical bytes a provider signs:
+    /// `challenge_id || file_id || sha256(proof_data) || timestamp` (timestamp big-endian),
+    /// followed by `sha256` of each extra sample for multi-chunk challenges
+    pub fn signing_message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(self.challenge_id.len() + self.file_id.len() + 40);
+        message.extend_from_slice(self.challenge_id.as_bytes());
+        message.extend_from_slice(self.file_id.as_bytes());
+        message.extend_from_slice(&Sha256::digest(&self.proof_data));
+        message.extend_from_slice(&self.timestamp.to_be_bytes());
+        for sample in &self.extra_proof_data {
+            message.extend_from_slice(&Sha256::digest(sample));
+        }
+        message
+    }
+
+    /// Samples in `chunk_indices` order
+    pub fn samples(&self) -> impl Iterator<Item = &[u8]> {
+        std::iter::once(self.proof_data.as_slice()).chain(self.extra_proof_data.iter().map(Vec::as_slice))
+    }
+}
+
+/// Result of checking a proof, with the evidence behind it
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationOutcome {
+    pub verified: bool,
+    /// 0.0-1.0 weighted sum of `components`; never above `FAILED_PROOF_MAX_SCORE` unless verified
+    pub score: f64,
+    /// Per-criterion values in 0.0-1.0: chunk_hashes, merkle_proof, signature, latency and
+    /// provider_history
+    pub components: BTreeMap<String, f64>,
+}
+
+impl VerificationOutcome {
+    /// Proof rejected before any evidence was examined (wrong file or provider, expired)
+    fn rejected() -> Self {
+        Self { verified: false, score: 0.0, components: BTreeMap::new() }
+    }
+}
+
+/// Highest score a proof that fails verification can get
+pub const FAILED_PROOF_MAX_SCORE: f64 = 0.5;
+
+const CHUNK_HASH_WEIGHT: f64 = 0.5;
+const MERKLE_WEIGHT: f64 = 0.1;
+const SIGNATURE_WEIGHT: f64 = 0.15;
+const LATENCY_WEIGHT: f64 = 0.1;
+const HISTORY_WEIGHT: f64 = 0.15;
+/// Response time allowed per challenged chunk before the latency component reaches zero
+const LATENCY_BUDGET_PER_CHUNK_SECS: f64 = 60.0;
+
+/// What the cryptographic checks established about a proof
+#[derive(Debug, Clone, Copy)]
+struct ProofEvidence {
+    samples_match: bool,
+    /// `None` when the challenge was not checked against a Merkle proof
+    merkle_valid: Option<bool>,
+    /// `None` when the proof is unsigned
+    signature_valid: Option<bool>,
+    signature_required: bool,
+}
+
+impl ProofEvidence {
+    fn verified(&self) -> bool {
+        self.samples_match
+            && self.merkle_valid != Some(false)
+            && self.signature_valid.unwrap_or(!self.signature_required)
+    }
+
+    /// Weigh the evidence; `latency` and `history` are already in 0.0-1.0. A missing
+    /// optional proof element scores half of a valid one.
+    fn score(&self, latency: f64, history: f64) -> VerificationOutcome {
+        let merkle = match self.merkle_valid {
+            Some(true) => 1.0,
+            Some(false) => 0.0,
+            None => 0.5,
+        };
+        let signature = match self.signature_valid {
+            Some(true) => 1.0,
+            Some(false) => 0.0,
+            None if self.signature_required => 0.0,
+            None => 0.5,
+        };
+        let components = BTreeMap::from([
+            ("chunk_hashes".to_string(), if self.samples_match { 1.0 } else { 0.0 }),
+            ("merkle_proof".to_string(), merkle),
+            ("signature".to_string(), signature),
+            ("latency".to_string(), latency.clamp(0.0, 1.0)),
+            ("provider_history".to_string(), history.clamp(0.0, 1.0)),
+        ]);
+        let weighted = components["chunk_hashes"] * CHUNK_HASH_WEIGHT
+            + components["merkle_proof"] * MERKLE_WEIGHT
+            + components["signature"] * SIGNATURE_WEIGHT
+            + components["latency"] * LATENCY_WEIGHT
+            + components["provider_history"] * HISTORY_WEIGHT;
+        let verified = self.verified();
+        let score = if verified { weighted } else { weighted.min(FAILED_PROOF_MAX_SCORE) };
+        VerificationOutcome { verified, score: score.clamp(0.0, 1.0), components }
+    }
+}
+
+/// 1.0 for an instant answer, falling to 0.0 once the per-chunk budget is used up
+fn latency_component(response_secs: u64, chunks: usize) -> f64 {
+    let budget = LATENCY_BUDGET_PER_CHUNK_SECS * chunks.max(1) as f64;
+    (1.0 - response_secs as f64 / budget).clamp(0.0, 1.0)
 }
 
 /// Verification metrics for monitoring and analytics
@@ -130,8 +796,241 @@ pub struct VerificationMetrics {
     pub failed_proofs: u64,
     pub expired_challenges: u64,
     pub rate_limited_requests: u64,
+    /// Proofs turned away because no verification slot freed up in time
+    pub overloaded_rejections: u64,
+    /// Proofs rejected for carrying a sample over the size cap
+    pub oversized_proofs: u64,
     pub average_response_time_ms: f64,
     pub last_reset: u64,
+    /// IPFS gateway health, keyed by gateway base URL; filled in by `get_metrics`
+    pub gateways: HashMap<String, GatewayStats>,
+    /// Per-tenant counters, kept only when `StorageVerifierConfig::tenant_metrics` is set
+    pub tenants: HashMap<String, TenantMetrics>,
+}
+
+/// One tenant's share of the verifier counters
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TenantMetrics {
+    pub total_challenges: u64,
+    pub successful_proofs: u64,
+    pub failed_proofs: u64,
+    pub expired_challenges: u64,
+}
+
+/// Outcomes of fetches through one IPFS gateway. Attempts abandoned because another
+/// gateway answered first count as neither success nor failure.
+#[derive(Debug, Clone, Default)]
+pub struct GatewayStats {
+    pub attempts: u64,
+    pub successes: u64,
+    pub failures: u64,
+    /// Latency of successful fetches, exponential moving average
+    pub average_latency_ms: f64,
+}
+
+pub const MIN_DIFFICULTY: u8 = 1;
+pub const MAX_DIFFICULTY: u8 = 5;
+
+/// Default for `StorageVerifierConfig::reputation_half_life`
+pub const DEFAULT_REPUTATION_HALF_LIFE: Duration = Duration::from_secs(7 * 86400);
+
+/// Default for `StorageVerifierConfig::reputation_threshold`
+pub const DEFAULT_REPUTATION_THRESHOLD: f64 = 0.5;
+
+/// Perfect outcomes every score starts from; new and long-idle providers score 1.0, and a
+/// handful of failures is needed before the score moves far from it
+const REPUTATION_PRIOR_WEIGHT: f64 = 10.0;
+
+/// One provider's standing across every challenge it was issued. Outcomes are weighted by
+/// age, halving every half-life, so old failures fade and an idle provider drifts back
+/// towards the prior.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderReputation {
+    pub provider: String,
+    pub challenges_issued: u64,
+    pub proofs_verified: u64,
+    /// Rejected proofs plus challenges left to expire
+    pub failures: u64,
+    /// Time from challenge issue to proof, exponential moving average
+    pub average_latency_ms: f64,
+    /// When the provider last submitted a proof, Unix seconds
+    pub last_seen: Option<u64>,
+    /// Score in 0.0-1.0 as of the last update or read
+    pub score: f64,
+    /// Decayed successes and settled challenges as of `updated_at`
+    pub weighted_successes: f64,
+    pub weighted_outcomes: f64,
+    pub updated_at: u64,
+}
+
+impl ProviderReputation {
+    pub fn new(provider: &str, now: u64) -> Self {
+        Self {
+            provider: provider.to_string(),
+            challenges_issued: 0,
+            proofs_verified: 0,
+            failures: 0,
+            average_latency_ms: 0.0,
+            last_seen: None,
+            score: 1.0,
+            weighted_successes: 0.0,
+            weighted_outcomes: 0.0,
+            updated_at: now,
+        }
+    }
+
+    /// Decayed (successes, outcomes) at `now`
+    fn decayed(&self, now: u64, half_life: Duration) -> (f64, f64) {
+        let age = now.saturating_sub(self.updated_at) as f64;
+        let factor = 0.5f64.powf(age / half_life.as_secs_f64().max(1.0));
+        (self.weighted_successes * factor, self.weighted_outcomes * factor)
+    }
+
+    /// Score in 0.0-1.0 at `now`
+    pub fn score_at(&self, now: u64, half_life: Duration) -> f64 {
+        let (successes, outcomes) = self.decayed(now, half_life);
+        (successes + REPUTATION_PRIOR_WEIGHT) / (outcomes + REPUTATION_PRIOR_WEIGHT)
+    }
+
+    fn settle(&mut self, success: bool, now: u64, half_life: Duration) {
+        let (successes, outcomes) = self.decayed(now, half_life);
+        self.weighted_successes = successes + if success { 1.0 } else { 0.0 };
+        self.weighted_outcomes = outcomes + 1.0;
+        self.updated_at = self.updated_at.max(now);
+        if success {
+            self.proofs_verified += 1;
+        } else {
+            self.failures += 1;
+        }
+        self.score = self.score_at(now, half_life);
+    }
+}
+
+/// Challenge difficulty for a reputation score: a perfect score gets `MIN_DIFFICULTY`, and
+/// each step down towards `threshold` adds a chunk until providers at or below it get
+/// `MAX_DIFFICULTY`
+pub fn difficulty_for_score(score: f64, threshold: f64) -> u8 {
+    let shortfall = ((1.0 - score) / (1.0 - threshold).max(f64::EPSILON)).clamp(0.0, 1.0);
+    MIN_DIFFICULTY + (shortfall * (MAX_DIFFICULTY - MIN_DIFFICULTY) as f64).floor() as u8
+}
+
+/// Per-provider reputation, written through to the commitment backend so it survives
+/// restarts. Records are loaded from the backend the first time a provider is touched.
+pub struct ReputationTracker {
+    backend: Arc<dyn CommitmentBackend>,
+    half_life: Duration,
+    cache: tokio::sync::Mutex<HashMap<String, ProviderReputation>>,
+}
+
+impl ReputationTracker {
+    pub fn new(backend: Arc<dyn CommitmentBackend>, half_life: Duration) -> Self {
+        Self { backend, half_life, cache: tokio::sync::Mutex::new(HashMap::new()) }
+    }
+
+    pub fn half_life(&self) -> Duration {
+        self.half_life
+    }
+
+    /// A provider's reputation with its score brought up to `now`
+    pub async fn get(&self, provider: &str, now: u64) -> Result<Option<ProviderReputation>, StorageVerificationError> {
+        let mut cache = self.cache.lock().await;
+        let reputation = match cache.get(provider) {
+            Some(reputation) => Some(reputation.clone()),
+            None => {
+                let stored = self.backend.get_reputation(provider).await?;
+                if let Some(reputation) = &stored {
+                    cache.insert(provider.to_string(), reputation.clone());
+                }
+                stored
+            }
+        };
+        Ok(reputation.map(|mut reputation| {
+            reputation.score = reputation.score_at(now, self.half_life);
+            reputation
+        }))
+    }
+
+    /// Every known provider with its score brought up to `now`, best first
+    pub async fn list(&self, now: u64) -> Result<Vec<ProviderReputation>, StorageVerificationError> {
+        let cache = self.cache.lock().await;
+        let mut all: HashMap<String, ProviderReputation> = self
+            .backend
+            .list_reputations()
+            .await?
+            .into_iter()
+            .map(|reputation| (reputation.provider.clone(), reputation))
+            .collect();
+        // Cached records are at least as new as stored ones; a failed write leaves them ahead
+        all.extend(cache.iter().map(|(provider, reputation)| (provider.clone(), reputation.clone())));
+        let mut all: Vec<ProviderReputation> = all
+            .into_values()
+            .map(|mut reputation| {
+                reputation.score = reputation.score_at(now, self.half_life);
+                reputation
+            })
+            .collect();
+        all.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.provider.cmp(&b.provider)));
+        Ok(all)
+    }
+
+    /// Score at `now`; providers without a record score 1.0
+    pub async fn score(&self, provider: &str, now: u64) -> f64 {
+        match self.get(provider, now).await {
+            Ok(reputation) => reputation.map_or(1.0, |reputation| reputation.score),
+            Err(e) => {
+                log::warn!("Reputation for provider {} unavailable: {}", provider, e);
+                1.0
+            }
+        }
+    }
+
+    /// Apply `f` to the provider's record and write it back. Returns the score from before
+    /// the change, or `None` when the stored record could not be read and nothing changed.
+    async fn update<F: FnOnce(&mut ProviderReputation)>(&self, provider: &str, now: u64, f: F) -> Option<f64> {
+        let mut cache = self.cache.lock().await;
+        if !cache.contains_key(provider) {
+            let stored = match self.backend.get_reputation(provider).await {
+                Ok(stored) => stored,
+                Err(e) => {
+                    log::warn!("Reputation for provider {} not updated: {}", provider, e);
+                    return None;
+                }
+            };
+            cache.insert(provider.to_string(), stored.unwrap_or_else(|| ProviderReputation::new(provider, now)));
+        }
+        let reputation = cache.get_mut(provider)?;
+        let before = reputation.score_at(now, self.half_life);
+        f(reputation);
+        if let Err(e) = self.backend.put_reputation(reputation).await {
+            log::warn!("Reputation for provider {} not persisted: {}", provider, e);
+        }
+        Some(before)
+    }
+
+    async fn record_issued(&self, provider: &str, now: u64) {
+        self.update(provider, now, |reputation| reputation.challenges_issued += 1).await;
+    }
+
+    /// Record a checked proof; returns the score it was judged against
+    async fn record_proof(&self, provider: &str, success: bool, latency_ms: f64, now: u64) -> f64 {
+        let half_life = self.half_life;
+        self.update(provider, now, |reputation| {
+            reputation.average_latency_ms = if reputation.average_latency_ms == 0.0 {
+                latency_ms
+            } else {
+                0.2 * latency_ms + 0.8 * reputation.average_latency_ms
+            };
+            reputation.last_seen = Some(now);
+            reputation.settle(success, now, half_life);
+        })
+        .await
+        .unwrap_or(1.0)
+    }
+
+    async fn record_missed(&self, provider: &str, now: u64) {
+        let half_life = self.half_life;
+        self.update(provider, now, |reputation| reputation.settle(false, now, half_life)).await;
+    }
 }
 
 impl VerificationMetrics {
@@ -144,7 +1043,7 @@ pub fn success_rate(&self) -> f64 {
 
     pub fn reset_if_needed(&mut self, now: u64) {
         // Reset metrics daily
-        if now - self.last_reset > 86400 {
+        if now.saturating_sub(self.last_reset) > 86400 {
             *self = Self {
                 last_reset: now,
                 ..Default::default()
@@ -179,6 +1078,15 @@ pub enum StorageVerificationError {
     
     #[error("Provider authentication failed")]
     AuthenticationFailed,
+
+    #[error("Commitment backend error: {reason}")]
+    Backend { reason: String },
+
+    #[error("File exceeds the {limit} byte ingest limit")]
+    FileTooLarge { limit: u64 },
+
+    #[error("Verifier overloaded: no verification slot freed within {waited_ms}ms")]
+    Overloaded { waited_ms: u64 },
 }
 /// Rate limiting configuration
 #[derive(Debug, Clone)]
@@ -203,7 +1111,6 @@ fn default() -> Self {
 struct RequestTracker {
     minute_requests: Vec<u64>,
     hour_requests: Vec<u64>,
-    last_cleanup: u64,
 }
 
 impl RequestTracker {
@@ -211,7 +1118,6 @@ fn new() -> Self {
         Self {
             minute_requests: Vec::new(),
             hour_requests: Vec::new(),
-            last_cleanup: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
         }
     }
 
@@ -219,14 +1125,11 @@ fn cleanup(&mut self, now: u64) {
         // Remove old requests
         self.minute_requests.retain(|&ts| now - ts < 60);
         self.hour_requests.retain(|&ts| now - ts < 3600);
-        self.last_cleanup = now;
     }
 
     fn can_make_request(&mut self, now: u64, config: &RateLimitConfig) -> bool {
-        // Auto-cleanup if needed
-        if now - self.last_cleanup > config.cleanup_interval_secs {
-            self.cleanup(now);
-        }
+        // Prune against now on every check so a window reopens exactly when it ends
+        self.cleanup(now);
 
         self.minute_requests.len() < config.max_requests_per_minute as usize &&
         self.hour_requests.len() < config.max_requests_per_hour as usize
@@ -238,52 +1141,338 @@ fn record_request(&mut self, now: u64) {
     }
 }
 
-/// Enhanced storage verifier with cryptographic proofs and monitoring
-pub struct StorageVerifier {
-    challenges: Arc<tokio::sync::Mutex<HashMap<String, StorageChallenge>>>,
-    used_beacons: Arc<tokio::sync::Mutex<HashSet<String>>>,
-    request_trackers: Arc<tokio::sync::Mutex<HashMap<String, RequestTracker>>>,
-    metrics: Arc<tokio::sync::Mutex<VerificationMetrics>>,
-    commitments: Arc<tokio::sync::Mutex<CommitmentStore>>,
-    rate_limit_config: RateLimitConfig,
-    #[cfg(feature = "ipfs")]
-    http_client: Option<Client>,
+/// How long an issued beacon is refused as a replay
+pub const BEACON_REPLAY_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Width of each time bucket in `BeaconReplaySet`
+pub const BEACON_BUCKET_WIDTH: Duration = Duration::from_secs(300);
+
+/// Beacons issued within the replay window, grouped into fixed-width time buckets.
+///
+/// Every beacon is kept for at least `window` seconds. Expiry pops whole buckets off the
+/// front, so its cost does not grow with the number of resident beacons.
+#[derive(Debug)]
+pub struct BeaconReplaySet {
+    window: u64,
+    bucket_width: u64,
+    // (bucket start, beacons), oldest first
+    buckets: VecDeque<(u64, HashSet<String>)>,
+    len: usize,
 }
 
-impl StorageVerifier {
-    /// Create new verifier with default rate limiting
-    pub fn new() -> Self {
-        Self::with_config(RateLimitConfig::default())
+impl Default for BeaconReplaySet {
+    fn default() -> Self {
+        Self::new(BEACON_REPLAY_WINDOW, BEACON_BUCKET_WIDTH)
     }
+}
 
-    /// Create new verifier with custom rate limiting
-    pub fn with_config(config: RateLimitConfig) -> Self {
+impl BeaconReplaySet {
+    pub fn new(window: Duration, bucket_width: Duration) -> Self {
         Self {
-            challenges: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
-            used_beacons: Arc::new(tokio::sync::Mutex::new(HashSet::new())),
-            request_trackers: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
-            metrics: Arc::new(tokio::sync::Mutex::new(VerificationMetrics::default())),
-            commitments: Arc::new(tokio::sync::Mutex::new(CommitmentStore::default())),
-            rate_limit_config: config,
-            #[cfg(feature = "ipfs")]
-            http_client: Some(Client::builder()
-                .timeout(Duration::from_secs(10))
-                .user_agent("UniversalSprint/1.0")
-                .build()
-                .unwrap_or_else(|_| Client::new())
-            ),
+            window: window.as_secs(),
+            bucket_width: bucket_width.as_secs().max(1),
+            buckets: VecDeque::new(),
+            len: 0,
         }
     }
 
-    /// Generate secure storage challenge with cryptographic requirements
-    pub async fn generate_challenge(&self, file_id: &str, provider: &str) -> Result<StorageChallenge, StorageVerificationError> {
-        let start_time = SystemTime::now();
-        let now = start_time.duration_since(UNIX_EPOCH).unwrap().as_secs();
+    /// Whether a bucket starting at `start` still covers part of the window ending at `now`
+    fn is_live(&self, start: u64, now: u64) -> bool {
+        start + self.bucket_width + self.window > now
+    }
 
-        // Input validation
-        if file_id.is_empty() || provider.is_empty() {
-            return Err(StorageVerificationError::InvalidInput {
-                field: "file_id or provider".to_string(),
+    /// Whether `beacon` was issued within the replay window
+    pub fn contains(&self, beacon: &str, now: u64) -> bool {
+        self.buckets
+            .iter()
+            .rev()
+            .take_while(|(start, _)| self.is_live(*start, now))
+            .any(|(_, beacons)| beacons.contains(beacon))
+    }
+
+    /// Record `beacon` as issued at `now`; false if it is already within the replay window
+    pub fn insert(&mut self, beacon: String, now: u64) -> bool {
+        self.expire(now);
+        if self.contains(&beacon, now) {
+            return false;
+        }
+        let start = now - now % self.bucket_width;
+        match self.buckets.back_mut() {
+            // A clock that stepped backwards still lands in the newest bucket
+            Some((newest, beacons)) if *newest >= start => {
+                beacons.insert(beacon);
+            }
+            _ => self.buckets.push_back((start, HashSet::from([beacon]))),
+        }
+        self.len += 1;
+        true
+    }
+
+    /// Drop buckets that fell out of the window, returning how many beacons went with them
+    pub fn expire(&mut self, now: u64) -> usize {
+        let mut removed = 0;
+        while let Some((start, _)) = self.buckets.front() {
+            if self.is_live(*start, now) {
+                break;
+            }
+            removed += self.buckets.pop_front().map_or(0, |(_, beacons)| beacons.len());
+        }
+        self.len -= removed;
+        removed
+    }
+
+    /// Beacons currently held, including any in buckets not yet expired
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Enhanced storage verifier with cryptographic proofs and monitoring
+pub struct StorageVerifier {
+    challenges: Arc<tokio::sync::Mutex<HashMap<String, ChallengeEntry>>>,
+    expiry_callbacks: Arc<std::sync::RwLock<Vec<ExpiryCallback>>>,
+    challenge_ttl: Duration,
+    // Beacons issued within the replay window
+    used_beacons: Arc<tokio::sync::Mutex<BeaconReplaySet>>,
+    request_trackers: Arc<tokio::sync::Mutex<HashMap<String, RequestTracker>>>,
+    // Idempotency key -> challenge id
+    idempotency_keys: Arc<tokio::sync::Mutex<HashMap<IdempotencyKey, String>>>,
+    metrics: Arc<tokio::sync::Mutex<VerificationMetrics>>,
+    commitments: Arc<dyn CommitmentBackend>,
+    // Per-provider scores, persisted through `commitments`; they drive challenge difficulty
+    reputation: Arc<ReputationTracker>,
+    reputation_threshold: f64,
+    rate_limit_config: RateLimitConfig,
+    allow_legacy_merkle_proofs: bool,
+    require_signatures: bool,
+    verification_permits: Arc<tokio::sync::Semaphore>,
+    // Permits the semaphore was created with; a batch keeps at most this many proofs in flight
+    verification_parallelism: usize,
+    verification_queue_timeout: Duration,
+    max_proof_size: usize,
+    tenant_metrics: bool,
+    // Challenge expiry, rate windows and beacon cleanup all read this
+    clock: Arc<dyn Clock>,
+    #[cfg(feature = "ipfs")]
+    ipfs: Arc<IpfsFetcher>,
+    // protocol -> fetcher used by `verify_content`
+    #[cfg(feature = "ipfs")]
+    fetchers: HashMap<String, Arc<dyn StorageFetcher>>,
+    #[cfg(feature = "ipfs")]
+    max_file_size: u64,
+}
+
+impl StorageVerifier {
+    /// Create new verifier with default rate limiting
+    pub fn new() -> Self {
+        Self::with_config(RateLimitConfig::default())
+    }
+
+    /// Create new verifier with custom rate limiting
+    pub fn with_config(config: RateLimitConfig) -> Self {
+        Self::with_backend(config, Arc::new(MemoryCommitmentBackend::new()))
+    }
+
+    /// Create a verifier with the configured commitment backend
+    pub fn from_config(config: StorageVerifierConfig) -> Result<Self, StorageVerificationError> {
+        let backend: Arc<dyn CommitmentBackend> = match &config.backend {
+            CommitmentBackendConfig::Memory => Arc::new(MemoryCommitmentBackend::new()),
+            #[cfg(feature = "rusqlite")]
+            CommitmentBackendConfig::Sqlite { path } => Arc::new(SqliteCommitmentBackend::open(path)?),
+        };
+        let mut verifier = Self::with_backend(config.rate_limit, backend);
+        verifier.allow_legacy_merkle_proofs = config.allow_legacy_merkle_proofs;
+        verifier.require_signatures = config.require_signatures;
+        verifier.challenge_ttl = config.challenge_ttl.unwrap_or(DEFAULT_CHALLENGE_TTL);
+        verifier.max_proof_size = config.max_proof_size.unwrap_or(DEFAULT_MAX_PROOF_SIZE);
+        verifier.tenant_metrics = config.tenant_metrics;
+        if let Some(half_life) = config.reputation_half_life {
+            verifier.reputation = Arc::new(ReputationTracker::new(verifier.commitments.clone(), half_life));
+        }
+        verifier.reputation_threshold = config.reputation_threshold.unwrap_or(DEFAULT_REPUTATION_THRESHOLD);
+        let permits = config.max_concurrent_verifications.unwrap_or_else(default_verification_permits).max(1);
+        verifier.verification_permits = Arc::new(tokio::sync::Semaphore::new(permits));
+        verifier.verification_parallelism = permits;
+        verifier.verification_queue_timeout =
+            config.verification_queue_timeout.unwrap_or(DEFAULT_VERIFICATION_QUEUE_TIMEOUT);
+        #[cfg(feature = "ipfs")]
+        {
+            verifier.max_file_size = config.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE);
+        }
+        Ok(verifier)
+    }
+
+    /// Create a verifier over an existing commitment backend
+    pub fn with_backend(config: RateLimitConfig, backend: Arc<dyn CommitmentBackend>) -> Self {
+        #[cfg(feature = "ipfs")]
+        let ipfs = Arc::new(IpfsFetcher::new(IpfsFetcherConfig::from_env()));
+        #[cfg(feature = "ipfs")]
+        let fetchers = {
+            let defaults: [Arc<dyn StorageFetcher>; 3] = [
+                ipfs.clone(),
+                Arc::new(ArweaveFetcher::new(ArweaveFetcherConfig::from_env())),
+                Arc::new(FilecoinFetcher::new(FilecoinFetcherConfig::from_env())),
+            ];
+            defaults.into_iter().map(|f| (f.protocol().to_string(), f)).collect()
+        };
+        Self {
+            challenges: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            expiry_callbacks: Arc::new(std::sync::RwLock::new(Vec::new())),
+            challenge_ttl: DEFAULT_CHALLENGE_TTL,
+            used_beacons: Arc::new(tokio::sync::Mutex::new(BeaconReplaySet::default())),
+            request_trackers: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            idempotency_keys: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            metrics: Arc::new(tokio::sync::Mutex::new(VerificationMetrics::default())),
+            reputation: Arc::new(ReputationTracker::new(backend.clone(), DEFAULT_REPUTATION_HALF_LIFE)),
+            reputation_threshold: DEFAULT_REPUTATION_THRESHOLD,
+            commitments: backend,
+            rate_limit_config: config,
+            allow_legacy_merkle_proofs: false,
+            require_signatures: false,
+            verification_permits: Arc::new(tokio::sync::Semaphore::new(default_verification_permits())),
+            verification_parallelism: default_verification_permits(),
+            verification_queue_timeout: DEFAULT_VERIFICATION_QUEUE_TIMEOUT,
+            max_proof_size: DEFAULT_MAX_PROOF_SIZE,
+            tenant_metrics: false,
+            clock: Arc::new(SystemClock),
+            #[cfg(feature = "ipfs")]
+            ipfs,
+            #[cfg(feature = "ipfs")]
+            fetchers,
+            #[cfg(feature = "ipfs")]
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+
+    /// Run at most `permits` proof verifications at once, failing callers that wait longer
+    /// than `queue_timeout` for a slot with `Overloaded`
+    pub fn with_concurrency_limit(mut self, permits: usize, queue_timeout: Duration) -> Self {
+        self.verification_permits = Arc::new(tokio::sync::Semaphore::new(permits.max(1)));
+        self.verification_parallelism = permits.max(1);
+        self.verification_queue_timeout = queue_timeout;
+        self
+    }
+
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Largest sample `verify_proof` accepts, in bytes
+    pub fn max_proof_size(&self) -> usize {
+        self.max_proof_size
+    }
+
+    /// Generate secure storage challenge with cryptographic requirements
+    pub async fn generate_challenge(&self, file_id: &str, provider: &str) -> Result<StorageChallenge, StorageVerificationError> {
+        self.generate_challenge_with(file_id, provider, self.require_signatures).await
+    }
+
+    /// Generate a challenge, choosing whether its proof must be signed by the provider
+    pub async fn generate_challenge_with(
+        &self,
+        file_id: &str,
+        provider: &str,
+        require_signature: bool,
+    ) -> Result<StorageChallenge, StorageVerificationError> {
+        self.issue_challenge(DEFAULT_TENANT, file_id, provider, require_signature, None).await
+    }
+
+    /// Generate a challenge against a file registered by `tenant_id`. Files of other tenants
+    /// are reported exactly like files that were never registered.
+    pub async fn generate_challenge_for_tenant(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        provider: &str,
+        require_signature: bool,
+    ) -> Result<StorageChallenge, StorageVerificationError> {
+        self.issue_challenge(tenant_id, file_id, provider, require_signature, None).await
+    }
+
+    /// Generate a challenge, or return the unexpired one already issued to `provider` under
+    /// `idempotency_key`. Retries are answered without touching the provider's rate limit.
+    pub async fn generate_challenge_idempotent(
+        &self,
+        file_id: &str,
+        provider: &str,
+        idempotency_key: &str,
+    ) -> Result<StorageChallenge, StorageVerificationError> {
+        self.generate_challenge_idempotent_for_tenant(DEFAULT_TENANT, file_id, provider, idempotency_key).await
+    }
+
+    /// `generate_challenge_idempotent` for a file registered by `tenant_id`; keys are
+    /// scoped to the tenant
+    pub async fn generate_challenge_idempotent_for_tenant(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        provider: &str,
+        idempotency_key: &str,
+    ) -> Result<StorageChallenge, StorageVerificationError> {
+        if idempotency_key.is_empty() || idempotency_key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            return Err(StorageVerificationError::InvalidInput {
+                field: "idempotency_key".to_string(),
+                reason: format!("Must be 1 to {} bytes", MAX_IDEMPOTENCY_KEY_LEN),
+            });
+        }
+        let key = (tenant_id.to_string(), provider.to_string(), idempotency_key.to_string());
+
+        // Held while issuing so two concurrent retries of one key cannot both get a new challenge
+        let mut keys = self.idempotency_keys.lock().await;
+        if let Some(id) = keys.get(&key) {
+            let now = self.clock.now_unix();
+            let challenges = self.challenges.lock().await;
+            if let Some(entry) = challenges.get(id).filter(|entry| !entry.challenge.is_expired_at(now)) {
+                if entry.challenge.file_id != file_id {
+                    return Err(StorageVerificationError::InvalidInput {
+                        field: "idempotency_key".to_string(),
+                        reason: "Already used for a different file_id".to_string(),
+                    });
+                }
+                return Ok(entry.challenge.clone());
+            }
+        }
+
+        let challenge = self.issue_challenge(tenant_id, file_id, provider, self.require_signatures, None).await?;
+        keys.insert(key, challenge.id.clone());
+        if keys.len() > 1000 {
+            let challenges = self.challenges.lock().await;
+            keys.retain(|_, id| challenges.contains_key(id));
+        }
+        Ok(challenge)
+    }
+
+    /// Generate a challenge whose samples are read from `protocol` (e.g. "ipfs", "arweave")
+    pub async fn generate_protocol_challenge(
+        &self,
+        file_id: &str,
+        provider: &str,
+        protocol: &str,
+    ) -> Result<StorageChallenge, StorageVerificationError> {
+        self.issue_challenge(DEFAULT_TENANT, file_id, provider, self.require_signatures, Some(protocol.to_lowercase()))
+            .await
+    }
+
+    async fn issue_challenge(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        provider: &str,
+        require_signature: bool,
+        protocol: Option<String>,
+    ) -> Result<StorageChallenge, StorageVerificationError> {
+        let now = self.clock.now_unix();
+
+        // Input validation
+        if file_id.is_empty() || provider.is_empty() {
+            return Err(StorageVerificationError::InvalidInput {
+                field: "file_id or provider".to_string(),
                 reason: "Cannot be empty".to_string(),
             });
         }
@@ -293,15 +1482,21 @@ pub async fn generate_challenge(&self, file_id: &str, provider: &str) -> Result<
                 reason: "Too long".to_string(),
             });
         }
+        validate_tenant(tenant_id)?;
 
         // Check if file has commitments registered
-        let (alg, chunk_size, total_chunks) = {
-            let commitments = self.commitments.lock().await;
-            commitments.get_chunk_meta(file_id).ok_or_else(|| StorageVerificationError::InvalidInput {
+        let (alg, chunk_size, total_chunks) =
+            self.commitments.get_meta(tenant_id, file_id).await?.ok_or_else(|| StorageVerificationError::InvalidInput {
                 field: "file_id".to_string(),
                 reason: "No commitment registered for file_id. Register file commitments first.".to_string(),
-            })?
-        };
+            })?;
+        // Registration rejects these; metadata written to the backend by other means might not
+        if total_chunks == 0 || chunk_size == 0 {
+            return Err(StorageVerificationError::InvalidInput {
+                field: "file_id".to_string(),
+                reason: "Registered commitment has no chunks to challenge".to_string(),
+            });
+        }
 
         // Rate limiting check
         {
@@ -321,8 +1516,17 @@ pub async fn generate_challenge(&self, file_id: &str, provider: &str) -> Result<
 
         // Generate cryptographic challenge
         let mut rng = thread_rng();
-        let random_salt: u64 = rng.gen();
-        let chunk_index = rng.gen_range(0..total_chunks);
+        let difficulty = self.calculate_difficulty(provider, now).await;
+        // A Merkle proof carries a single inclusion path, so those challenges stay single-chunk
+        let wanted = match alg {
+            CommitmentAlg::MerkleSha256 { .. } => 1,
+            CommitmentAlg::Sha256Chunks => (difficulty as u64).min(total_chunks) as usize,
+        };
+        let chunk_indices: Vec<u64> = rand::seq::index::sample(&mut rng, total_chunks as usize, wanted)
+            .into_iter()
+            .map(|i| i as u64)
+            .collect();
+        let chunk_index = chunk_indices[0];
         let sample_offset = (chunk_index as u64) * (chunk_size as u64);
         let sample_size = chunk_size;
 
@@ -330,66 +1534,65 @@ pub async fn generate_challenge(&self, file_id: &str, provider: &str) -> Result<
         let mut challenge_data = vec![0u8; 32];
         rng.fill_bytes(&mut challenge_data);
 
-        let beacon = self.generate_beacon(file_id, provider, now, random_salt)?;
-
-        // Replay protection
-        {
+        // Replay protection: draw nonces until the beacon is unused, then reserve it
+        let (nonce, beacon) = {
             let mut used = self.used_beacons.lock().await;
-            if used.contains(&beacon) {
-                return Err(StorageVerificationError::CryptographicFailure {
-                    reason: "Beacon collision detected".to_string(),
-                });
-            }
-            used.insert(beacon.clone());
-
-            // Store beacon timestamp for cleanup
-            let mut commitments = self.commitments.lock().await;
-            commitments.store_beacon_timestamp(&beacon, now);
-
-            // Cleanup old beacons periodically
-            if used.len() > 10000 {
-                commitments.cleanup_old_beacons(3600); // 1 hour
-                used.retain(|b| {
-                    if let Some(ts) = commitments.get_beacon_timestamp(b) {
-                        now - ts < 3600
-                    } else {
-                        false
-                    }
-                });
+            let mut reserved = None;
+            for _ in 0..MAX_NONCE_ATTEMPTS {
+                let nonce: u64 = rng.gen();
+                let beacon = self.generate_beacon(file_id, provider, now, nonce)?;
+                if used.insert(beacon.clone(), now) {
+                    reserved = Some((nonce, beacon));
+                    break;
+                }
             }
-        }
+            reserved.ok_or_else(|| StorageVerificationError::CryptographicFailure {
+                reason: "Beacon collision detected".to_string(),
+            })?
+        };
 
-        let difficulty = self.calculate_difficulty(provider).await;
         let commitment_alg = match alg {
             CommitmentAlg::Sha256Chunks => "sha256_chunks".to_string(),
             CommitmentAlg::MerkleSha256 { .. } => "merkle_sha256".to_string(),
         };
 
         let challenge = StorageChallenge {
-            id: format!("chall_{}_{:x}", &file_id[..std::cmp::min(file_id.len(), 8)], now),
+            id: challenge_id(tenant_id, file_id, provider, nonce, &beacon),
+            tenant_id: tenant_id.to_string(),
             file_id: file_id.to_string(),
             provider: provider.to_string(),
-            nonce: random_salt,
+            nonce,
             timestamp: now,
-            expiry: now + 1800, // 30 minutes expiry
+            expiry: now + self.challenge_ttl.as_secs(),
             beacon,
             difficulty,
             challenge_data,
             sample_offset,
             sample_size,
             chunk_index,
+            chunk_indices,
             commitment_alg,
+            require_signature,
+            protocol,
         };
 
         // Store challenge with automatic cleanup
-        {
+        let crowded = {
             let mut challenges = self.challenges.lock().await;
-            challenges.insert(challenge.id.clone(), challenge.clone());
-
-            // Cleanup expired challenges
-            if challenges.len() > 1000 {
-                challenges.retain(|_, c| now < c.expiry);
+            match challenges.entry(challenge.id.clone()) {
+                std::collections::hash_map::Entry::Occupied(_) => {
+                    return Err(StorageVerificationError::CryptographicFailure {
+                        reason: "Challenge id collision detected".to_string(),
+                    });
+                }
+                std::collections::hash_map::Entry::Vacant(slot) => {
+                    slot.insert(ChallengeEntry { challenge: challenge.clone(), answered: false });
+                }
             }
+            challenges.len() > 1000
+        };
+        if crowded {
+            self.expire_challenges_at(now).await;
         }
 
         // Update metrics
@@ -397,18 +1600,93 @@ pub async fn generate_challenge(&self, file_id: &str, provider: &str) -> Result<
             let mut metrics = self.metrics.lock().await;
             metrics.reset_if_needed(now);
             metrics.total_challenges += 1;
+            if self.tenant_metrics {
+                metrics.tenants.entry(tenant_id.to_string()).or_default().total_challenges += 1;
+            }
         }
+        self.reputation.record_issued(provider, now).await;
 
-        log::info!("Generated challenge {} for provider {} file {} chunk {}", 
-                   challenge.id, provider, file_id, chunk_index);
+        log::info!("Generated challenge {} for provider {} file {} chunks {:?} (difficulty {})",
+                   challenge.id, provider, file_id, challenge.chunk_indices, difficulty);
 
         Ok(challenge)
     }
 
     /// Verify storage proof with enhanced cryptographic verification
     pub async fn verify_proof(&self, proof: StorageProof) -> Result<bool, StorageVerificationError> {
-        let start_time = SystemTime::now();
-        let now = start_time.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        Ok(self.verify_proof_scored(proof).await?.verified)
+    }
+
+    /// Verify many proofs concurrently, keeping at most as many in flight as there are
+    /// verification permits so a large batch queues instead of tripping `Overloaded`.
+    /// Results are in input order; a proof that fails does not affect the others.
+    pub async fn verify_proofs_batch(&self, proofs: Vec<StorageProof>) -> Vec<Result<bool, StorageVerificationError>> {
+        self.verify_proofs_batch_scored(proofs)
+            .await
+            .into_iter()
+            .map(|result| result.map(|outcome| outcome.verified))
+            .collect()
+    }
+
+    /// `verify_proofs_batch` with the scored outcome of each proof
+    pub async fn verify_proofs_batch_scored(
+        &self,
+        proofs: Vec<StorageProof>,
+    ) -> Vec<Result<VerificationOutcome, StorageVerificationError>> {
+        futures::stream::iter(proofs)
+            .map(|proof| self.verify_proof_scored(proof))
+            .buffered(self.verification_parallelism)
+            .collect()
+            .await
+    }
+
+    /// Verify a proof for a challenge issued to `tenant_id`; challenges of other tenants
+    /// are `ChallengeNotFound`
+    pub async fn verify_proof_for_tenant(&self, tenant_id: &str, proof: StorageProof) -> Result<bool, StorageVerificationError> {
+        Ok(self.verify_proof_scored_for_tenant(tenant_id, proof).await?.verified)
+    }
+
+    /// Verify a proof and score the evidence: sample hashes, Merkle path, signature,
+    /// response latency against the number of challenged chunks and the provider's history
+    pub async fn verify_proof_scored(&self, proof: StorageProof) -> Result<VerificationOutcome, StorageVerificationError> {
+        self.verify_proof_scored_for_tenant(DEFAULT_TENANT, proof).await
+    }
+
+    /// `verify_proof_scored` for a challenge issued to `tenant_id`
+    pub async fn verify_proof_scored_for_tenant(
+        &self,
+        tenant_id: &str,
+        proof: StorageProof,
+    ) -> Result<VerificationOutcome, StorageVerificationError> {
+        // Size cap first, before anything is hashed or a slot is taken
+        if let Some(size) = proof.samples().map(<[u8]>::len).find(|&len| len > self.max_proof_size) {
+            self.metrics.lock().await.oversized_proofs += 1;
+            return Err(StorageVerificationError::InvalidInput {
+                field: "proof_data".to_string(),
+                reason: format!("Sample of {} bytes exceeds the {} byte limit", size, self.max_proof_size),
+            });
+        }
+
+        let _permit = match tokio::time::timeout(self.verification_queue_timeout, self.verification_permits.acquire()).await {
+            Ok(permit) => permit.expect("verification semaphore is never closed"),
+            Err(_) => {
+                self.metrics.lock().await.overloaded_rejections += 1;
+                return Err(StorageVerificationError::Overloaded {
+                    waited_ms: self.verification_queue_timeout.as_millis() as u64,
+                });
+            }
+        };
+
+        self.verify_admitted_proof(tenant_id, proof).await
+    }
+
+    async fn verify_admitted_proof(
+        &self,
+        tenant_id: &str,
+        proof: StorageProof,
+    ) -> Result<VerificationOutcome, StorageVerificationError> {
+        let start_time = self.clock.now_instant();
+        let now = self.clock.now_unix();
 
         // Input validation
         if proof.challenge_id.is_empty() || proof.file_id.is_empty() || proof.provider.is_empty() {
@@ -418,24 +1696,31 @@ pub async fn verify_proof(&self, proof: StorageProof) -> Result<bool, StorageVer
             });
         }
 
-        let challenges = self.challenges.lock().await;
-        let challenge = challenges.get(&proof.challenge_id)
-            .ok_or_else(|| StorageVerificationError::ChallengeNotFound {
-                challenge_id: proof.challenge_id.clone(),
-            })?;
+        let challenge = {
+            let challenges = self.challenges.lock().await;
+            challenges.get(&proof.challenge_id)
+                .filter(|entry| entry.challenge.tenant_id == tenant_id)
+                .map(|entry| entry.challenge.clone())
+                .ok_or_else(|| StorageVerificationError::ChallengeNotFound {
+                    challenge_id: proof.challenge_id.clone(),
+                })?
+        };
+        let challenge = &challenge;
 
         // Basic metadata verification
         if proof.file_id != challenge.file_id || proof.provider != challenge.provider {
             let mut metrics = self.metrics.lock().await;
             metrics.failed_proofs += 1;
-            return Ok(false);
+            if self.tenant_metrics {
+                metrics.tenants.entry(challenge.tenant_id.clone()).or_default().failed_proofs += 1;
+            }
+            return Ok(VerificationOutcome::rejected());
         }
 
-        // Expiry check
-        if now > challenge.expiry {
-            let mut metrics = self.metrics.lock().await;
-            metrics.expired_challenges += 1;
-            return Ok(false);
+        // Expiry check; the sweep would have removed it, so handle it the same way here
+        if challenge.is_expired_at(now) {
+            self.expire_challenges_at(now).await;
+            return Ok(VerificationOutcome::rejected());
         }
 
         // Timestamp validation (allow some clock skew)
@@ -446,12 +1731,26 @@ pub async fn verify_proof(&self, proof: StorageProof) -> Result<bool, StorageVer
         }
 
         // Cryptographic proof verification
-        let is_valid = self.verify_cryptographic_proof(&proof, challenge).await?;
+        let evidence = self.verify_cryptographic_proof(&proof, challenge).await;
+        if let Some(entry) = self.challenges.lock().await.get_mut(&challenge.id) {
+            entry.answered = true;
+        }
+
+        // A malformed proof counts against the provider just like a wrong one. History is
+        // the score from before this proof, so it only reflects earlier challenges.
+        let response_secs = now.saturating_sub(challenge.timestamp);
+        let success = matches!(&evidence, Ok(evidence) if evidence.verified());
+        let history = self
+            .reputation
+            .record_proof(&challenge.provider, success, response_secs as f64 * 1000.0, now)
+            .await;
+        let outcome = evidence?.score(latency_component(response_secs, challenge.chunk_indices.len()), history);
+        let is_valid = outcome.verified;
 
         // Update metrics
         {
             let mut metrics = self.metrics.lock().await;
-            let elapsed = start_time.elapsed().unwrap_or_default().as_millis() as f64;
+            let elapsed = self.clock.now_instant().saturating_duration_since(start_time).as_millis() as f64;
 
             // Use Exponential Moving Average for response time
             let alpha = 0.2; // Smoothing factor
@@ -461,6 +1760,14 @@ pub async fn verify_proof(&self, proof: StorageProof) -> Result<bool, StorageVer
                 alpha * elapsed + (1.0 - alpha) * metrics.average_response_time_ms
             };
 
+            if self.tenant_metrics {
+                let tenant = metrics.tenants.entry(challenge.tenant_id.clone()).or_default();
+                if is_valid {
+                    tenant.successful_proofs += 1;
+                } else {
+                    tenant.failed_proofs += 1;
+                }
+            }
             if is_valid {
                 metrics.successful_proofs += 1;
                 log::info!("Proof verified successfully: {} for provider {}",
@@ -472,66 +1779,78 @@ pub async fn verify_proof(&self, proof: StorageProof) -> Result<bool, StorageVer
             }
         }
 
-        Ok(is_valid)
+        Ok(outcome)
     }
 
     /// Perform cryptographic verification of the storage proof
-    async fn verify_cryptographic_proof(&self, proof: &StorageProof, challenge: &StorageChallenge) -> Result<bool, StorageVerificationError> {
-        // Verify proof data is not empty
-        if proof.proof_data.is_empty() {
+    async fn verify_cryptographic_proof(&self, proof: &StorageProof, challenge: &StorageChallenge) -> Result<ProofEvidence, StorageVerificationError> {
+        // One sample per challenged chunk
+        if proof.extra_proof_data.len() + 1 != challenge.chunk_indices.len() {
             return Err(StorageVerificationError::CryptographicFailure {
-                reason: "Proof data cannot be empty".to_string(),
+                reason: format!("Expected {} chunk samples, got {}",
+                               challenge.chunk_indices.len(), proof.extra_proof_data.len() + 1),
             });
         }
 
-        // Verify proof data size is non-empty and does not exceed expected sample size.
+        // Verify each sample is non-empty and does not exceed expected sample size.
         // The final chunk may be smaller than the nominal chunk_size used for earlier chunks,
         // so accept proof sizes <= challenge.sample_size.
-        if proof.proof_data.is_empty() || proof.proof_data.len() > challenge.sample_size as usize {
-            return Err(StorageVerificationError::CryptographicFailure {
-                reason: format!("Proof data size {} is invalid; expected >0 and <= {}",
-                               proof.proof_data.len(), challenge.sample_size),
-            });
-        }
-
-        // Compute leaf hash of the returned chunk
-        let mut hasher = Sha256::new();
-        hasher.update(&proof.proof_data);
-        let computed_leaf = hasher.finalize();
-
-        // Get expected leaf hash from commitments
-        let expected_leaf = {
-            let commitments = self.commitments.lock().await;
-            commitments.expected_leaf(&challenge.file_id, challenge.chunk_index)
-                .ok_or_else(|| StorageVerificationError::CryptographicFailure {
-                    reason: format!("Missing chunk commitment for file {} chunk {}",
-                                   challenge.file_id, challenge.chunk_index),
-                })?
-        };
-
-        // Compare computed leaf with expected leaf
-        if computed_leaf.as_slice() != expected_leaf {
-            log::debug!("Leaf hash mismatch for file {} chunk {}: computed={}, expected={}",
-                       challenge.file_id, challenge.chunk_index,
-                       hex::encode(computed_leaf), hex::encode(expected_leaf));
-            return Ok(false);
+        for sample in proof.samples() {
+            if sample.is_empty() || sample.len() > challenge.sample_size as usize {
+                return Err(StorageVerificationError::CryptographicFailure {
+                    reason: format!("Proof data size {} is invalid; expected >0 and <= {}",
+                                   sample.len(), challenge.sample_size),
+                });
+            }
         }
 
-        // Optional: Verify Merkle proof if provided and algorithm supports it
-        if let Some(ref merkle_proof) = proof.merkle_proof {
-            if challenge.commitment_alg == "merkle_sha256" && !self.verify_merkle_proof(merkle_proof, &proof.proof_data, &challenge.file_id).await? {
-                return Ok(false);
+        let (samples_match, merkle_valid) = if challenge.commitment_alg == "merkle_sha256" {
+            // Only the root is committed; the proof carries the path to it
+            let merkle_proof = proof.merkle_proof.as_ref().ok_or_else(|| StorageVerificationError::CryptographicFailure {
+                reason: "Merkle proof required for merkle_sha256 commitment".to_string(),
+            })?;
+            let valid = self.verify_merkle_proof(merkle_proof, &proof.proof_data, challenge).await?;
+            (valid, Some(valid))
+        } else {
+            let mut all_match = true;
+            for (&chunk_index, sample) in challenge.chunk_indices.iter().zip(proof.samples()) {
+                // Get expected leaf hash from commitments
+                let expected_leaf = self.commitments.get_leaf(&challenge.tenant_id, &challenge.file_id, chunk_index).await?
+                    .ok_or_else(|| StorageVerificationError::CryptographicFailure {
+                        reason: format!("Missing chunk commitment for file {} chunk {}",
+                                       challenge.file_id, chunk_index),
+                    })?;
+
+                // Compare computed leaf with expected leaf
+                let computed_leaf = Sha256::digest(sample);
+                if computed_leaf.as_slice() != expected_leaf {
+                    log::debug!("Leaf hash mismatch for file {} chunk {}: computed={}, expected={}",
+                               challenge.file_id, chunk_index,
+                               hex::encode(computed_leaf), hex::encode(expected_leaf));
+                    all_match = false;
+                    break;
+                }
             }
-        }
+            (all_match, None)
+        };
 
-        // Optional: Verify provider signature if provided
-        if let Some(ref signature) = proof.signature {
-            if !self.verify_provider_signature(signature, &proof.proof_data, &proof.provider)? {
-                return Ok(false);
+        // A signature is always checked when present, and must be present when required
+        let signature_valid = match proof.signature {
+            Some(ref signature) => Some(self.verify_provider_signature(signature, proof).await?),
+            None => {
+                if challenge.require_signature {
+                    log::debug!("Unsigned proof for challenge {} that requires a signature", challenge.id);
+                }
+                None
             }
-        }
+        };
 
-        Ok(true)
+        Ok(ProofEvidence {
+            samples_match,
+            merkle_valid,
+            signature_valid,
+            signature_required: challenge.require_signature,
+        })
     }
 
     /// Register file commitments for verification
@@ -541,6 +1860,19 @@ pub async fn register_file_commitments(
         chunk_size: u32,
         leaf_hashes: Vec<[u8; 32]>
     ) -> Result<(), StorageVerificationError> {
+        self.register_file_commitments_for_tenant(DEFAULT_TENANT, file_id, chunk_size, leaf_hashes).await
+    }
+
+    /// Register file commitments owned by `tenant_id`; the same file_id may be registered
+    /// independently by every tenant
+    pub async fn register_file_commitments_for_tenant(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        chunk_size: u32,
+        leaf_hashes: Vec<[u8; 32]>
+    ) -> Result<(), StorageVerificationError> {
+        validate_tenant(tenant_id)?;
         if file_id.is_empty() {
             return Err(StorageVerificationError::InvalidInput {
                 field: "file_id".to_string(),
@@ -554,9 +1886,15 @@ pub async fn register_file_commitments(
             });
         }
 
-        let mut commitments = self.commitments.lock().await;
+        // Leaves first, metadata last: the file is not challengeable until every leaf is stored
         let leaf_count = leaf_hashes.len();
-        commitments.register_sha256_chunks(file_id, chunk_size, leaf_hashes);
+        for (batch, leaves) in leaf_hashes.chunks(LEAF_BATCH_SIZE).enumerate() {
+            let first_index = (batch * LEAF_BATCH_SIZE) as u64;
+            self.commitments.put_leaves_batch(tenant_id, file_id, first_index, leaves).await?;
+        }
+        self.commitments
+            .put_meta(tenant_id, file_id, (CommitmentAlg::Sha256Chunks, chunk_size, leaf_count as u64))
+            .await?;
 
         log::info!("Registered {} chunks for file {}", leaf_count, file_id);
         Ok(())
@@ -570,25 +1908,57 @@ pub async fn register_merkle_root(
         chunk_size: u32,
         total_chunks: u64
     ) -> Result<(), StorageVerificationError> {
+        self.register_merkle_root_for_tenant(DEFAULT_TENANT, file_id, root, chunk_size, total_chunks).await
+    }
+
+    /// Register a Merkle root owned by `tenant_id`
+    pub async fn register_merkle_root_for_tenant(
+        &self,
+        tenant_id: &str,
+        file_id: &str,
+        root: [u8; 32],
+        chunk_size: u32,
+        total_chunks: u64
+    ) -> Result<(), StorageVerificationError> {
+        validate_tenant(tenant_id)?;
         if file_id.is_empty() {
             return Err(StorageVerificationError::InvalidInput {
                 field: "file_id".to_string(),
                 reason: "Cannot be empty".to_string(),
             });
         }
+        // Challenges sample chunk indices below total_chunks at chunk_size byte offsets
+        if chunk_size == 0 {
+            return Err(StorageVerificationError::InvalidInput {
+                field: "chunk_size".to_string(),
+                reason: "Must be at least 1".to_string(),
+            });
+        }
+        if total_chunks == 0 {
+            return Err(StorageVerificationError::InvalidInput {
+                field: "total_chunks".to_string(),
+                reason: "Must be at least 1".to_string(),
+            });
+        }
 
-        let mut commitments = self.commitments.lock().await;
-        commitments.register_merkle_root(file_id, root, chunk_size, total_chunks);
+        self.commitments
+            .put_meta(tenant_id, file_id, (CommitmentAlg::MerkleSha256 { root, chunk_size }, chunk_size, total_chunks))
+            .await?;
 
         log::info!("Registered Merkle root for file {} with {} chunks", file_id, total_chunks);
         Ok(())
     }
 
-    /// Verify Merkle proof for file integrity
-    async fn verify_merkle_proof(&self, merkle_proof: &[String], proof_data: &[u8], file_id: &str) -> Result<bool, StorageVerificationError> {
+    /// Verify that the proof places the sampled chunk at `chunk_index` under the registered root
+    async fn verify_merkle_proof(
+        &self,
+        merkle_proof: &MerkleProof,
+        proof_data: &[u8],
+        challenge: &StorageChallenge,
+    ) -> Result<bool, StorageVerificationError> {
+        let (file_id, chunk_index) = (challenge.file_id.as_str(), challenge.chunk_index);
         // Get the stored Merkle root for this file
-        let commitments = self.commitments.lock().await;
-        let (alg, _chunk_size, _total_chunks) = match commitments.get_chunk_meta(file_id) {
+        let (alg, _chunk_size, total_chunks) = match self.commitments.get_meta(&challenge.tenant_id, file_id).await? {
             Some(meta) => meta,
             None => {
                 log::debug!("No commitment metadata found for file {}", file_id);
@@ -604,45 +1974,32 @@ async fn verify_merkle_proof(&self, merkle_proof: &[String], proof_data: &[u8],
             }
         };
 
-        // Compute leaf hash from proof data
-        let mut hasher = Sha256::new();
-        hasher.update(proof_data);
-        let mut leaf_hash = [0u8; 32];
-        leaf_hash.copy_from_slice(&hasher.finalize());
-
-        // Verify the Merkle proof
-        let mut current_hash = leaf_hash;
-
-        for proof_element in merkle_proof {
-            // Decode hex proof element
-            let proof_bytes = match hex::decode(proof_element.trim_start_matches("0x")) {
-                Ok(bytes) => bytes,
-                Err(_) => {
-                    log::debug!("Invalid hex in Merkle proof element: {}", proof_element);
-                    return Ok(false);
-                }
-            };
+        if merkle_proof.is_legacy() {
+            if !self.allow_legacy_merkle_proofs {
+                return Err(StorageVerificationError::InvalidInput {
+                    field: "merkle_proof".to_string(),
+                    reason: "Legacy proofs without sibling positions are not accepted".to_string(),
+                });
+            }
+            log::warn!("Deprecated Merkle proof format without sibling positions used for file {}", file_id);
+        }
 
-            if proof_bytes.len() != 32 {
-                log::debug!("Invalid proof element length: {} bytes", proof_bytes.len());
+        let steps = match merkle_proof.steps() {
+            Ok(steps) => steps,
+            Err(reason) => {
+                log::debug!("Malformed Merkle proof for file {}: {}", file_id, reason);
                 return Ok(false);
             }
+        };
 
-            let mut sibling_hash = [0u8; 32];
-            sibling_hash.copy_from_slice(&proof_bytes);
-
-            // Hash current with sibling (order depends on tree structure)
-            // For simplicity, we'll assume left-to-right ordering
-            let mut combined_hasher = Sha256::new();
-            combined_hasher.update(current_hash);
-            combined_hasher.update(sibling_hash);
-            current_hash = combined_hasher.finalize().into();
-        }
-
-        // Compare final hash with stored root
-        if current_hash != stored_root {
-            log::debug!("Merkle proof verification failed for file {}: computed={}, expected={}",
-                       file_id, hex::encode(current_hash), hex::encode(stored_root));
+        // Legacy proofs carry no positions, so only their root is checked
+        let valid = if merkle_proof.is_legacy() {
+            merkle::compute_root(merkle::hash_leaf(proof_data), &steps) == stored_root
+        } else {
+            merkle::verify(&stored_root, merkle::hash_leaf(proof_data), chunk_index, total_chunks, &steps)
+        };
+        if !valid {
+            log::debug!("Merkle proof verification failed for file {} chunk {}", file_id, chunk_index);
             return Ok(false);
         }
 
@@ -650,74 +2007,226 @@ async fn verify_merkle_proof(&self, merkle_proof: &[String], proof_data: &[u8],
         Ok(true)
     }
 
-    /// Verify provider signature for authentication
-    fn verify_provider_signature(&self, _signature: &str, _proof_data: &[u8], _provider: &str) -> Result<bool, StorageVerificationError> {
-        // Placeholder for digital signature verification
-        // In production, this would verify the provider's signature
-        log::debug!("Provider signature verification not yet implemented");
-        Ok(true)
+    /// Verify the provider's ed25519 signature over the proof's signing message
+    async fn verify_provider_signature(&self, signature: &str, proof: &StorageProof) -> Result<bool, StorageVerificationError> {
+        let public_key = self
+            .commitments
+            .get_provider_key(&proof.provider)
+            .await?
+            .ok_or(StorageVerificationError::AuthenticationFailed)?;
+        let Ok(public_key) = VerifyingKey::from_bytes(&public_key) else {
+            return Ok(false);
+        };
+        let signature = match general_purpose::STANDARD.decode(signature).ok().and_then(|b| Signature::from_slice(&b).ok()) {
+            Some(signature) => signature,
+            None => {
+                log::debug!("Malformed signature from provider {}", proof.provider);
+                return Ok(false);
+            }
+        };
+        Ok(public_key.verify_strict(&proof.signing_message(), &signature).is_ok())
     }
 
-    /// Get current verification metrics
-    pub async fn get_metrics(&self) -> VerificationMetrics {
-        let metrics = self.metrics.lock().await;
-        metrics.clone()
+    /// Register the ed25519 public key a provider signs proofs with
+    pub async fn register_provider_key(&self, provider_id: &str, pubkey_bytes: &[u8]) -> Result<(), StorageVerificationError> {
+        if provider_id.is_empty() || provider_id.len() > 64 {
+            return Err(StorageVerificationError::InvalidInput {
+                field: "provider_id".to_string(),
+                reason: "Must be 1-64 characters".to_string(),
+            });
+        }
+        let key: [u8; 32] = pubkey_bytes.try_into().map_err(|_| StorageVerificationError::InvalidInput {
+            field: "public_key".to_string(),
+            reason: format!("Expected 32 bytes, got {}", pubkey_bytes.len()),
+        })?;
+        VerifyingKey::from_bytes(&key).map_err(|_| StorageVerificationError::InvalidInput {
+            field: "public_key".to_string(),
+            reason: "Not a valid ed25519 public key".to_string(),
+        })?;
+
+        self.commitments.put_provider_key(provider_id, key).await?;
+        log::info!("Registered signing key for provider {}", provider_id);
+        Ok(())
     }
 
-    /// Reset metrics (useful for testing or periodic resets)
-    pub async fn reset_metrics(&self) {
-        let mut metrics = self.metrics.lock().await;
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-        *metrics = VerificationMetrics {
-            last_reset: now,
-            ..Default::default()
-        };
+    /// The provider's registered public key, if any
+    pub async fn provider_key(&self, provider_id: &str) -> Result<Option<[u8; 32]>, StorageVerificationError> {
+        self.commitments.get_provider_key(provider_id).await
     }
-    /// Generate secure beacon with enhanced entropy
-    fn generate_beacon(&self, file_id: &str, provider: &str, timestamp: u64, salt: u64) -> Result<String, StorageVerificationError> {
-        let mut hasher = Sha256::new();
-        hasher.update(file_id.as_bytes());
-        hasher.update(provider.as_bytes());
-        hasher.update(timestamp.to_le_bytes());
-        hasher.update(salt.to_le_bytes());
-        hasher.update(b"UniversalSprint"); // Domain separator
-        
-        Ok(hex::encode(hasher.finalize()))
+
+    /// Whether commitments have been registered for `file_id`
+    pub async fn has_commitments(&self, file_id: &str) -> Result<bool, StorageVerificationError> {
+        self.has_commitments_for_tenant(DEFAULT_TENANT, file_id).await
     }
 
-    /// Calculate challenge difficulty based on provider history
-    async fn calculate_difficulty(&self, _provider: &str) -> u8 {
-        // Simple static difficulty for now
-        // In production, this could be dynamic based on provider reputation
-        1
+    /// Whether `tenant_id` has registered commitments for `file_id`
+    pub async fn has_commitments_for_tenant(&self, tenant_id: &str, file_id: &str) -> Result<bool, StorageVerificationError> {
+        Ok(self.commitments.get_meta(tenant_id, file_id).await?.is_some())
     }
 
-    /// Cleanup expired data
-    pub async fn cleanup_expired(&self) {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    /// Look up an outstanding challenge
+    pub async fn get_challenge(&self, id: &str) -> Option<StorageChallenge> {
+        self.challenges.lock().await.get(id).map(|entry| entry.challenge.clone())
+    }
 
-        // Cleanup challenges
-        {
+    /// A provider's challenges, oldest first. Expired challenges are only listed until
+    /// the next sweep removes them.
+    pub async fn list_challenges(&self, provider: &str, include_expired: bool) -> Vec<StorageChallenge> {
+        let now = self.clock.now_unix();
+        let mut listed: Vec<StorageChallenge> = self
+            .challenges
+            .lock()
+            .await
+            .values()
+            .filter(|entry| entry.challenge.provider == provider)
+            .filter(|entry| include_expired || !entry.challenge.is_expired_at(now))
+            .map(|entry| entry.challenge.clone())
+            .collect();
+        listed.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+        listed
+    }
+
+    /// Register a callback for challenges that expire without a proof
+    pub fn on_challenge_expired(&self, callback: ChallengeExpiredCallback) {
+        self.expiry_callbacks.write().unwrap().push(Arc::from(callback));
+    }
+
+    /// Periodically run `cleanup_expired`, which sweeps expired challenges and fires the
+    /// expiry callbacks. The task stops once the verifier is dropped.
+    pub fn start_background_tasks(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let verifier = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(verifier) = verifier.upgrade() else { break };
+                verifier.cleanup_expired().await;
+            }
+        })
+    }
+
+    /// Remove every challenge expired at `now`. Each one that never saw a proof is counted
+    /// in `expired_challenges` and reported to the callbacks; removal makes that happen once.
+    async fn expire_challenges_at(&self, now: u64) -> usize {
+        let unanswered: Vec<StorageChallenge> = {
             let mut challenges = self.challenges.lock().await;
-            challenges.retain(|_, c| now < c.expiry);
+            let expired: Vec<String> = challenges
+                .iter()
+                .filter(|(_, entry)| entry.challenge.is_expired_at(now))
+                .map(|(id, _)| id.clone())
+                .collect();
+            expired
+                .iter()
+                .filter_map(|id| challenges.remove(id))
+                .filter(|entry| !entry.answered)
+                .map(|entry| entry.challenge)
+                .collect()
+        };
+        if unanswered.is_empty() {
+            return 0;
         }
 
-        // Cleanup beacons and beacon timestamps
         {
-            let mut beacons = self.used_beacons.lock().await;
-            let mut commitments = self.commitments.lock().await;
-
-            if beacons.len() > 5000 {
-                commitments.cleanup_old_beacons(3600); // 1 hour
-                beacons.retain(|b| {
-                    if let Some(ts) = commitments.get_beacon_timestamp(b) {
-                        now - ts < 3600
-                    } else {
-                        false
-                    }
-                });
+            let mut metrics = self.metrics.lock().await;
+            metrics.expired_challenges += unanswered.len() as u64;
+            if self.tenant_metrics {
+                for challenge in &unanswered {
+                    metrics.tenants.entry(challenge.tenant_id.clone()).or_default().expired_challenges += 1;
+                }
             }
         }
+        // A missed challenge counts against the provider like a failed proof
+        for challenge in &unanswered {
+            self.reputation.record_missed(&challenge.provider, now).await;
+        }
+        let callbacks = self.expiry_callbacks.read().unwrap().clone();
+        for challenge in &unanswered {
+            log::debug!("Challenge {} for provider {} expired unanswered", challenge.id, challenge.provider);
+            for callback in &callbacks {
+                callback(challenge);
+            }
+        }
+        unanswered.len()
+    }
+
+    /// Get current verification metrics
+    pub async fn get_metrics(&self) -> VerificationMetrics {
+        #[allow(unused_mut)]
+        let mut metrics = self.metrics.lock().await.clone();
+        #[cfg(feature = "ipfs")]
+        {
+            metrics.gateways = self.ipfs.gateway_stats();
+        }
+        metrics
+    }
+
+    /// A provider's reputation with its score decayed to now; `None` if it was never challenged
+    pub async fn get_reputation(&self, provider: &str) -> Result<Option<ProviderReputation>, StorageVerificationError> {
+        self.reputation.get(provider, self.clock.now_unix()).await
+    }
+
+    /// Providers scoring at least `min_score` now, best first
+    pub async fn list_reputations(&self, min_score: f64) -> Result<Vec<ProviderReputation>, StorageVerificationError> {
+        let mut reputations = self.reputation.list(self.clock.now_unix()).await?;
+        reputations.retain(|reputation| reputation.score >= min_score);
+        Ok(reputations)
+    }
+
+    /// Score at or below which providers get `MAX_DIFFICULTY` challenges
+    pub fn reputation_threshold(&self) -> f64 {
+        self.reputation_threshold
+    }
+
+    /// Counters for one tenant; all zero unless `StorageVerifierConfig::tenant_metrics` is set
+    pub async fn get_tenant_metrics(&self, tenant_id: &str) -> TenantMetrics {
+        self.metrics.lock().await.tenants.get(tenant_id).cloned().unwrap_or_default()
+    }
+
+    /// Reset metrics (useful for testing or periodic resets)
+    pub async fn reset_metrics(&self) {
+        let mut metrics = self.metrics.lock().await;
+        let now = self.clock.now_unix();
+        *metrics = VerificationMetrics {
+            last_reset: now,
+            ..Default::default()
+        };
+        #[cfg(feature = "ipfs")]
+        self.ipfs.reset_gateway_stats();
+    }
+    /// Generate secure beacon with enhanced entropy
+    fn generate_beacon(&self, file_id: &str, provider: &str, timestamp: u64, salt: u64) -> Result<String, StorageVerificationError> {
+        let mut hasher = Sha256::new();
+        hasher.update(file_id.as_bytes());
+        hasher.update(provider.as_bytes());
+        hasher.update(timestamp.to_le_bytes());
+        hasher.update(salt.to_le_bytes());
+        hasher.update(b"UniversalSprint"); // Domain separator
+        
+        Ok(hex::encode(hasher.finalize()))
+    }
+
+    /// Challenge difficulty earned by the provider's reputation
+    async fn calculate_difficulty(&self, provider: &str, now: u64) -> u8 {
+        difficulty_for_score(self.reputation.score(provider, now).await, self.reputation_threshold)
+    }
+
+    /// Cleanup expired data
+    pub async fn cleanup_expired(&self) {
+        let now = self.clock.now_unix();
+
+        // Cleanup challenges
+        self.expire_challenges_at(now).await;
+
+        // Cleanup beacons
+        self.used_beacons.lock().await.expire(now);
+
+        // Drop leaves left behind by interrupted registrations
+        match self.commitments.cleanup().await {
+            Ok(0) => {}
+            Ok(removed) => log::info!("Removed {} orphaned commitment leaves", removed),
+            Err(e) => log::warn!("Commitment cleanup failed: {}", e),
+        }
 
         // Cleanup request trackers
         {
@@ -729,116 +2238,97 @@ pub async fn cleanup_expired(&self) {
     }
 }
 
-// Optional IPFS functionality
+// Optional network fetching (IPFS, Arweave, Filecoin)
 #[cfg(feature = "ipfs")]
 impl StorageVerifier {
-    /// Fetch sample from IPFS with enhanced security
-    pub async fn fetch_ipfs_sample(&self, cid: &str, max_size: usize) -> Result<Vec<u8>, StorageVerificationError> {
-        // Input validation
-        if cid.is_empty() || cid.len() > 128 {
-            return Err(StorageVerificationError::InvalidInput {
-                field: "cid".to_string(),
-                reason: "Invalid CID format".to_string(),
-            });
-        }
-        
-        let safe_size = std::cmp::min(max_size, 8192); // Max 8KB sample
-        
-        let client = self.http_client.as_ref()
-            .ok_or_else(|| StorageVerificationError::NetworkError {
-                source: "HTTP client not available".to_string().into(),
-            })?;
-
-        // Use multiple IPFS gateways for redundancy
-        let gateways = [
-            "https://ipfs.io/ipfs",
-            "https://cloudflare-ipfs.com/ipfs",
-            "https://gateway.pinata.cloud/ipfs",
-        ];
-
-        for gateway in &gateways {
-            let url = format!("{}/{}?format=raw", gateway, cid);
-            
-            match self.try_fetch_from_gateway(&client, &url, safe_size).await {
-                Ok(data) => return Ok(data),
-                Err(e) => {
-                    log::warn!("Failed to fetch from {}: {:?}", gateway, e);
-                    continue;
-                }
-            }
-        }
+    /// Use these gateway base URLs (e.g. `https://gw.example.com/ipfs`) instead of IPFS_GATEWAYS
+    pub fn with_gateways<I: IntoIterator<Item = S>, S: AsRef<str>>(self, gateways: I) -> Self {
+        let config = IpfsFetcherConfig {
+            gateways: gateways.into_iter().map(|g| g.as_ref().to_string()).collect(),
+            ..self.ipfs.config().clone()
+        };
+        self.with_ipfs_config(config)
+    }
 
-        Err(StorageVerificationError::NetworkError {
-            source: "Failed to fetch from all IPFS gateways".to_string().into(),
-        })
+    /// Overall time budget for `fetch_ipfs_sample` across every gateway tried
+    pub fn with_ipfs_deadline(self, deadline: Duration) -> Self {
+        let config = IpfsFetcherConfig { deadline, ..self.ipfs.config().clone() };
+        self.with_ipfs_config(config)
     }
 
-    async fn try_fetch_from_gateway(&self, client: &Client, url: &str, size: usize) -> Result<Vec<u8>, StorageVerificationError> {
-        let resp = client
-            .get(url)
-            .header("Range", format!("bytes=0-{}", size - 1))
-            .send()
-            .await
-            .map_err(|e| StorageVerificationError::NetworkError {
-                source: format!("HTTP error: {}", e).into()
-            })?;        if !resp.status().is_success() {
-            return Err(StorageVerificationError::NetworkError {
-                source: format!("HTTP {}", resp.status()).into(),
-            });
-        }
+    fn with_ipfs_config(mut self, config: IpfsFetcherConfig) -> Self {
+        self.ipfs = Arc::new(IpfsFetcher::new(config));
+        self.fetchers.insert("ipfs".to_string(), self.ipfs.clone());
+        self
+    }
 
-        let bytes = resp
-            .bytes()
-            .await
-            .map_err(|e| StorageVerificationError::NetworkError {
-                source: format!("Failed to read response: {}", e).into(),
-            })?;
+    /// Refuse to ingest files larger than `limit` bytes
+    pub fn with_max_file_size(mut self, limit: u64) -> Self {
+        self.max_file_size = limit;
+        self
+    }
 
-        if bytes.len() > size {
-            return Err(StorageVerificationError::InvalidInput {
-                field: "response_size".to_string(),
-                reason: "Response too large".to_string(),
-            });
-        }
+    /// Fetch samples for `fetcher.protocol()` through `fetcher`, replacing any default
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn StorageFetcher>) -> Self {
+        self.fetchers.insert(fetcher.protocol().to_string(), fetcher);
+        self
+    }
 
-        Ok(bytes.to_vec())
+    pub fn supports_protocol(&self, protocol: &str) -> bool {
+        self.fetchers.contains_key(&protocol.to_lowercase())
     }
 
-    /// Verify IPFS content with comprehensive cryptographic checks
-    pub async fn verify_ipfs_content(&self, cid: &str, provider: &str, sample_size: Option<usize>) -> Result<bool, StorageVerificationError> {
-        let challenge = self.generate_challenge(cid, provider).await?;
-        let requested_size = sample_size.unwrap_or(challenge.sample_size as usize);
+    /// Fetch sample from IPFS with enhanced security
+    pub async fn fetch_ipfs_sample(&self, cid: &str, max_size: usize) -> Result<Vec<u8>, StorageVerificationError> {
+        let safe_size = std::cmp::min(max_size, 8192); // Max 8KB sample
+        self.ipfs.fetch_range(cid, 0, safe_size as u64).await
+    }
 
-        // Fetch sample with timeout
-        let sample = self.fetch_ipfs_sample(cid, requested_size).await
-            .map_err(|e| StorageVerificationError::NetworkError { source: Box::new(e) })?;
+    /// Challenge `file_id` on `protocol`, fetch every challenged chunk through that
+    /// protocol's fetcher and verify the samples against the registered commitments
+    pub async fn verify_content(&self, protocol: &str, file_id: &str, provider: &str) -> Result<bool, StorageVerificationError> {
+        Ok(self.verify_content_scored(protocol, file_id, provider).await?.verified)
+    }
 
-        if sample.is_empty() {
-            return Ok(false);
-        }
+    /// `verify_content`, returning the scored outcome
+    pub async fn verify_content_scored(
+        &self,
+        protocol: &str,
+        file_id: &str,
+        provider: &str,
+    ) -> Result<VerificationOutcome, StorageVerificationError> {
+        let fetcher = self.fetchers.get(&protocol.to_lowercase()).cloned().ok_or_else(|| {
+            StorageVerificationError::InvalidInput {
+                field: "protocol".to_string(),
+                reason: format!("No fetcher configured for protocol '{}'", protocol),
+            }
+        })?;
+        let challenge = self.generate_protocol_challenge(file_id, provider, protocol).await?;
 
-        // Verify sample size is non-empty and does not exceed challenge requirements.
-        // For files whose final chunk is smaller than the nominal chunk_size, the sample
-        // may be shorter than challenge.sample_size; allow that as long as it's > 0 and
-        // not larger than the nominal sample_size.
-        if sample.is_empty() || sample.len() > challenge.sample_size as usize {
-            return Err(StorageVerificationError::CryptographicFailure {
-                reason: format!("Sample size mismatch: got {}, expected >0 and <= {}",
-                               sample.len(), challenge.sample_size),
-            });
+        let sample_size = challenge.sample_size as u64;
+        let mut samples = Vec::with_capacity(challenge.chunk_indices.len());
+        for &chunk_index in &challenge.chunk_indices {
+            samples.push(fetcher.fetch_range(file_id, chunk_index * sample_size, sample_size).await?);
         }
+        let mut samples = samples.into_iter();
 
         let proof = StorageProof {
             challenge_id: challenge.id.clone(),
-            file_id: cid.to_string(),
+            file_id: file_id.to_string(),
             provider: provider.to_string(),
-            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-            proof_data: sample,
-            merkle_proof: None, // Could be implemented for additional verification
-            signature: None,    // Could be implemented for provider authentication
+            timestamp: self.clock.now_unix(),
+            proof_data: samples.next().unwrap_or_default(),
+            merkle_proof: None,
+            signature: None, // Fetched from the network, not submitted by the provider
+            extra_proof_data: samples.collect(),
         };
 
-        self.verify_proof(proof).await
+        self.verify_proof_scored(proof).await
+    }
+
+    /// Verify IPFS content with comprehensive cryptographic checks
+    pub async fn verify_ipfs_content(&self, cid: &str, provider: &str) -> Result<bool, StorageVerificationError> {
+        self.verify_content("ipfs", cid, provider).await
     }
 
     /// Ingest IPFS content and register commitments for future verification
@@ -847,100 +2337,132 @@ pub async fn ingest_ipfs_and_register(
         cid: &str,
         chunk_size: usize
     ) -> Result<(), StorageVerificationError> {
-        // Input validation
-        if cid.is_empty() || cid.len() > 128 {
+        self.ingest_ipfs_with_progress(cid, chunk_size, None).await
+    }
+
+    /// Stream IPFS content, hashing it chunk by chunk and storing leaves in batches as they
+    /// fill, so memory stays around one chunk whatever the file size. Fails with
+    /// `FileTooLarge` once more than `max_file_size` bytes arrive.
+    pub async fn ingest_ipfs_with_progress(
+        &self,
+        cid: &str,
+        chunk_size: usize,
+        on_progress: Option<&(dyn Fn(IngestProgress) + Send + Sync)>,
+    ) -> Result<(), StorageVerificationError> {
+        if chunk_size == 0 || chunk_size > u32::MAX as usize {
             return Err(StorageVerificationError::InvalidInput {
-                field: "cid".to_string(),
-                reason: "Invalid CID format".to_string(),
+                field: "chunk_size".to_string(),
+                reason: "Must be between 1 and u32::MAX".to_string(),
             });
         }
 
-        let client = self.http_client.as_ref()
-            .ok_or_else(|| StorageVerificationError::NetworkError {
-                source: "HTTP client not available".to_string().into(),
+        let response = self.ipfs.open_stream(cid).await?;
+        let limit = self.max_file_size;
+        let total_bytes = response.content_length();
+        if total_bytes.is_some_and(|len| len > limit) {
+            return Err(StorageVerificationError::FileTooLarge { limit });
+        }
+
+        let stall_timeout = self.ipfs.config().request_timeout;
+        let mut body = response.bytes_stream();
+        let mut chunk = Vec::with_capacity(chunk_size);
+        let mut leaves = LeafBatcher::new(self.commitments.as_ref(), DEFAULT_TENANT, cid);
+        let mut progress = IngestProgress { bytes_read: 0, chunks_hashed: 0, total_bytes };
+
+        loop {
+            let next = tokio::time::timeout(stall_timeout, body.next()).await.map_err(|_| {
+                StorageVerificationError::NetworkError {
+                    source: format!("IPFS stream for {} stalled for {:?}", cid, stall_timeout).into(),
+                }
             })?;
+            let Some(bytes) = next else { break };
+            let bytes = bytes.map_err(|e| StorageVerificationError::NetworkError {
+                source: format!("Failed to read IPFS stream for {}: {}", cid, e).into(),
+            })?;
+            progress.bytes_read += bytes.len() as u64;
+            if progress.bytes_read > limit {
+                return Err(StorageVerificationError::FileTooLarge { limit });
+            }
 
-        // Fetch the entire file to compute chunk hashes
-        let gateways = [
-            "https://ipfs.io/ipfs",
-            "https://cloudflare-ipfs.com/ipfs",
-            "https://gateway.pinata.cloud/ipfs",
-        ];
-
-        let mut file_data = None;
-        for gateway in &gateways {
-            let url = format!("{}/{}", gateway, cid);
-
-            match client
-                .get(&url)
-                .header("Range", "bytes=0-10485760") // Max 10MB for demo
-                .send()
-                .await
-            {
-                Ok(resp) if resp.status().is_success() => {
-                    match resp.bytes().await {
-                        Ok(bytes) => {
-                            file_data = Some(bytes.to_vec());
-                            break;
-                        }
-                        Err(e) => {
-                            log::warn!("Failed to read response from {}: {:?}", gateway, e);
-                            continue;
-                        }
+            let mut rest = &bytes[..];
+            while !rest.is_empty() {
+                let take = (chunk_size - chunk.len()).min(rest.len());
+                chunk.extend_from_slice(&rest[..take]);
+                rest = &rest[take..];
+                if chunk.len() == chunk_size {
+                    leaves.push(merkle::hash_leaf(&chunk)).await?;
+                    chunk.clear();
+                    progress.chunks_hashed += 1;
+                    if let Some(report) = on_progress {
+                        report(progress);
                     }
                 }
-                Ok(resp) => {
-                    log::warn!("HTTP error from {}: {}", gateway, resp.status());
-                    continue;
-                }
-                Err(e) => {
-                    log::warn!("Failed to fetch from {}: {:?}", gateway, e);
-                    continue;
-                }
+            }
+        }
+        if !chunk.is_empty() {
+            leaves.push(merkle::hash_leaf(&chunk)).await?;
+            progress.chunks_hashed += 1;
+            if let Some(report) = on_progress {
+                report(progress);
             }
         }
 
-        let file_data = file_data.ok_or_else(|| StorageVerificationError::NetworkError {
-            source: "Failed to fetch file from all IPFS gateways".to_string().into(),
-        })?;
-
-        if file_data.is_empty() {
+        if progress.chunks_hashed == 0 {
             return Err(StorageVerificationError::InvalidInput {
                 field: "file_data".to_string(),
                 reason: "File is empty".to_string(),
             });
         }
 
-        // Compute SHA256 hashes for each chunk
-        let mut leaf_hashes = Vec::new();
-        let mut offset = 0;
+        // Metadata last, as in `register_file_commitments`
+        let leaf_count = leaves.finish().await?;
+        self.commitments
+            .put_meta(DEFAULT_TENANT, cid, (CommitmentAlg::Sha256Chunks, chunk_size as u32, leaf_count))
+            .await?;
+
+        log::info!("Ingested IPFS file {} ({} bytes) with {} chunks of size {}", cid, progress.bytes_read, leaf_count, chunk_size);
+        Ok(())
+    }
+}
 
-        while offset < file_data.len() {
-            let end = std::cmp::min(offset + chunk_size, file_data.len());
-            let chunk = &file_data[offset..end];
+/// Buffers leaf hashes and writes them to the backend `LEAF_BATCH_SIZE` at a time
+#[cfg(feature = "ipfs")]
+struct LeafBatcher<'a> {
+    backend: &'a dyn CommitmentBackend,
+    tenant_id: &'a str,
+    file_id: &'a str,
+    pending: Vec<[u8; 32]>,
+    written: u64,
+}
 
-            let mut hasher = Sha256::new();
-            hasher.update(chunk);
-            let hash = hasher.finalize();
-            leaf_hashes.push(hash.into());
+#[cfg(feature = "ipfs")]
+impl<'a> LeafBatcher<'a> {
+    fn new(backend: &'a dyn CommitmentBackend, tenant_id: &'a str, file_id: &'a str) -> Self {
+        Self { backend, tenant_id, file_id, pending: Vec::with_capacity(LEAF_BATCH_SIZE), written: 0 }
+    }
 
-            offset = end;
+    async fn push(&mut self, leaf: [u8; 32]) -> Result<(), StorageVerificationError> {
+        self.pending.push(leaf);
+        if self.pending.len() == LEAF_BATCH_SIZE {
+            self.flush().await?;
         }
+        Ok(())
+    }
 
-        if leaf_hashes.is_empty() {
-            return Err(StorageVerificationError::InvalidInput {
-                field: "file_data".to_string(),
-                reason: "No chunks generated".to_string(),
-            });
+    async fn flush(&mut self) -> Result<(), StorageVerificationError> {
+        if !self.pending.is_empty() {
+            self.backend.put_leaves_batch(self.tenant_id, self.file_id, self.written, &self.pending).await?;
+            self.written += self.pending.len() as u64;
+            self.pending.clear();
         }
-
-        // Register the commitments
-        let leaf_count = leaf_hashes.len();
-        self.register_file_commitments(cid, chunk_size as u32, leaf_hashes).await?;
-
-        log::info!("Ingested IPFS file {} with {} chunks of size {}", cid, leaf_count, chunk_size);
         Ok(())
     }
+
+    /// Write any remaining leaves and return the total count
+    async fn finish(mut self) -> Result<u64, StorageVerificationError> {
+        self.flush().await?;
+        Ok(self.written)
+    }
 }
 
 impl Default for StorageVerifier {
@@ -952,6 +2474,7 @@ fn default() -> Self {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::ManualClock;
 
     #[tokio::test]
     async fn test_challenge_generation() {
@@ -1016,6 +2539,7 @@ async fn test_cryptographic_proof_verification() {
             proof_data,
             merkle_proof: None,
             signature: None,
+            extra_proof_data: Vec::new(),
         };
 
         // This should now succeed because we have the correct proof data
@@ -1070,6 +2594,32 @@ async fn test_rate_limiting_with_metrics() {
         assert_eq!(metrics.rate_limited_requests, 1); // Failed one due to rate limiting
     }
 
+    #[tokio::test]
+    async fn test_rate_window_resets_after_a_minute() {
+        let config = RateLimitConfig { max_requests_per_minute: 2, max_requests_per_hour: 3, cleanup_interval_secs: 1 };
+        let clock = Arc::new(ManualClock::new(1_700_000_000));
+        let verifier = StorageVerifier::with_config(config).with_clock(clock.clone());
+        let data = b"window chunk";
+        verifier.register_file_commitments("window_file", data.len() as u32, vec![Sha256::digest(data).into()]).await.unwrap();
+
+        for _ in 0..2 {
+            verifier.generate_challenge("window_file", "provider1").await.unwrap();
+        }
+        clock.advance(Duration::from_secs(59));
+        assert!(matches!(
+            verifier.generate_challenge("window_file", "provider1").await,
+            Err(StorageVerificationError::RateLimitExceeded { .. })
+        ));
+
+        clock.advance(Duration::from_secs(1));
+        verifier.generate_challenge("window_file", "provider1").await.unwrap();
+        // The hourly budget still counts the first window
+        clock.advance(Duration::from_secs(60));
+        assert!(verifier.generate_challenge("window_file", "provider1").await.is_err());
+        clock.advance(Duration::from_secs(3_600));
+        assert!(verifier.generate_challenge("window_file", "provider1").await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_beacon_uniqueness() {
         let verifier = StorageVerifier::new();
@@ -1101,6 +2651,88 @@ async fn test_beacon_uniqueness() {
         assert_ne!(challenge1.beacon, challenge2.beacon);
         // Nonces should also be different
         assert_ne!(challenge1.nonce, challenge2.nonce);
+    }
+
+    #[test]
+    fn test_beacon_replay_rejected_across_bucket_boundaries() {
+        let mut beacons = BeaconReplaySet::new(Duration::from_secs(3600), Duration::from_secs(300));
+        // Last second of the first bucket
+        assert!(beacons.insert("a".to_string(), 299));
+        assert!(!beacons.insert("a".to_string(), 299));
+        // Next bucket, and every later one still inside the window
+        assert!(!beacons.insert("a".to_string(), 300));
+        assert!(beacons.insert("b".to_string(), 300));
+        assert!(!beacons.insert("a".to_string(), 299 + 3600));
+        assert!(!beacons.insert("b".to_string(), 299 + 3600));
+        assert_eq!(beacons.len(), 2);
+
+        // The first bucket leaves the window whole once its last second is an hour old
+        assert!(beacons.contains("a", 3899));
+        assert!(!beacons.contains("a", 3900));
+        assert_eq!(beacons.expire(3900), 1);
+        assert!(beacons.insert("a".to_string(), 3900));
+        assert!(!beacons.insert("b".to_string(), 3900));
+
+        assert_eq!(beacons.expire(100_000), 2);
+        assert!(beacons.is_empty());
+    }
+
+    #[test]
+    fn test_beacon_replay_tolerates_clock_stepping_back() {
+        let mut beacons = BeaconReplaySet::default();
+        assert!(beacons.insert("a".to_string(), 1_000));
+        assert!(beacons.insert("b".to_string(), 400));
+        assert!(!beacons.insert("b".to_string(), 1_000));
+        assert!(!beacons.insert("a".to_string(), 400));
+    }
+
+    #[tokio::test]
+    async fn test_challenge_ids_unique_under_burst() {
+        let config = RateLimitConfig { max_requests_per_minute: 2_000, max_requests_per_hour: 2_000, cleanup_interval_secs: 300 };
+        // A frozen clock puts every challenge in the same second
+        let verifier = StorageVerifier::with_config(config).with_clock(Arc::new(ManualClock::new(1_700_000_000)));
+        let data = b"burst chunk";
+        verifier.register_file_commitments("burst_file", data.len() as u32, vec![Sha256::digest(data).into()]).await.unwrap();
+
+        let mut ids = std::collections::HashSet::new();
+        for _ in 0..1000 {
+            let challenge = verifier.generate_challenge("burst_file", "provider1").await.unwrap();
+            assert_eq!(challenge.id.len(), 64);
+            assert!(challenge.id.chars().all(|c| c.is_ascii_hexdigit()));
+            assert!(ids.insert(challenge.id));
+        }
+        assert_eq!(ids.len(), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_retry_returns_same_challenge() {
+        let config = RateLimitConfig { max_requests_per_minute: 1, max_requests_per_hour: 10, cleanup_interval_secs: 300 };
+        let clock = Arc::new(ManualClock::new(1_700_000_000));
+        let verifier = StorageVerifier::with_config(config).with_clock(clock.clone());
+        let data = b"idempotent chunk";
+        verifier.register_file_commitments("idem_file", data.len() as u32, vec![Sha256::digest(data).into()]).await.unwrap();
+
+        let first = verifier.generate_challenge_idempotent("idem_file", "provider1", "req-1").await.unwrap();
+        // The retry is served even though the per-minute budget is spent
+        let retry = verifier.generate_challenge_idempotent("idem_file", "provider1", "req-1").await.unwrap();
+        assert_eq!(retry.id, first.id);
+        assert_eq!(retry.beacon, first.beacon);
+        assert_eq!(verifier.get_metrics().await.total_challenges, 1);
+
+        assert!(matches!(
+            verifier.generate_challenge_idempotent("idem_file", "provider1", "req-2").await,
+            Err(StorageVerificationError::RateLimitExceeded { .. })
+        ));
+        assert!(matches!(
+            verifier.generate_challenge_idempotent("other_file", "provider1", "req-1").await,
+            Err(StorageVerificationError::InvalidInput { .. })
+        ));
+        assert!(verifier.generate_challenge_idempotent("idem_file", "provider1", "").await.is_err());
+
+        // Once the challenge expires the key issues a fresh one
+        clock.advance(DEFAULT_CHALLENGE_TTL + Duration::from_secs(1));
+        let fresh = verifier.generate_challenge_idempotent("idem_file", "provider1", "req-1").await.unwrap();
+        assert_ne!(fresh.id, first.id);
     }    #[tokio::test]
     async fn test_metrics_tracking() {
         let verifier = StorageVerifier::new();
@@ -1147,4 +2779,1143 @@ async fn test_metrics_tracking() {
         let metrics_after_reset = verifier.get_metrics().await;
         assert_eq!(metrics_after_reset.total_challenges, 0);
     }
+
+    #[cfg(feature = "rusqlite")]
+    #[tokio::test]
+    async fn test_sqlite_commitments_survive_restart() {
+        let path = std::env::temp_dir().join(format!("sprint-commitments-{:016x}.db", rand::random::<u64>()));
+        let config = StorageVerifierConfig {
+            rate_limit: RateLimitConfig::default(),
+            backend: CommitmentBackendConfig::Sqlite { path: path.clone() },
+            ..StorageVerifierConfig::default()
+        };
+
+        let test_data = b"Persisted commitments must outlive the verifier that registered them.";
+        let chunk_size = 8;
+        let leaf_hashes: Vec<[u8; 32]> = test_data.chunks(chunk_size).map(|chunk| Sha256::digest(chunk).into()).collect();
+
+        let signing_key = signing_key();
+        {
+            let verifier = StorageVerifier::from_config(config.clone()).unwrap();
+            verifier.register_file_commitments("disk_file", chunk_size as u32, leaf_hashes).await.unwrap();
+            verifier.register_provider_key("provider1", signing_key.verifying_key().as_bytes()).await.unwrap();
+        }
+
+        let verifier = StorageVerifier::from_config(config).unwrap();
+        let challenge = verifier.generate_challenge_with("disk_file", "provider1", true).await.unwrap();
+        let start = challenge.chunk_index as usize * chunk_size;
+        let end = std::cmp::min(start + chunk_size, test_data.len());
+        let proof = StorageProof {
+            challenge_id: challenge.id.clone(),
+            file_id: "disk_file".to_string(),
+            provider: "provider1".to_string(),
+            timestamp: challenge.timestamp + 1,
+            proof_data: test_data[start..end].to_vec(),
+            merkle_proof: None,
+            signature: None,
+            extra_proof_data: Vec::new(),
+        };
+        assert!(verifier.verify_proof(sign(proof, &signing_key)).await.unwrap());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "rusqlite")]
+    #[tokio::test]
+    async fn test_sqlite_reputation_survives_restart() {
+        let path = std::env::temp_dir().join(format!("sprint-reputation-{:016x}.db", rand::random::<u64>()));
+        let config = StorageVerifierConfig {
+            backend: CommitmentBackendConfig::Sqlite { path: path.clone() },
+            ..StorageVerifierConfig::default()
+        };
+        let clock = Arc::new(ManualClock::starting_now());
+
+        let before = {
+            let verifier = StorageVerifier::from_config(config.clone()).unwrap().with_clock(clock.clone());
+            verifier.reputation.record_issued("provider1", clock.now_unix()).await;
+            verifier.reputation.record_proof("provider1", false, 250.0, clock.now_unix()).await;
+            verifier.get_reputation("provider1").await.unwrap().unwrap()
+        };
+
+        let verifier = StorageVerifier::from_config(config).unwrap().with_clock(clock.clone());
+        assert_eq!(verifier.get_reputation("provider1").await.unwrap(), Some(before.clone()));
+        assert_eq!(verifier.list_reputations(0.0).await.unwrap(), vec![before]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "rusqlite")]
+    #[tokio::test]
+    async fn test_sqlite_store_without_tenants_migrates_to_default_tenant() {
+        let path = std::env::temp_dir().join(format!("sprint-untenanted-{:016x}.db", rand::random::<u64>()));
+        {
+            let conn = rusqlite::Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE commitment_meta (
+                    file_id TEXT PRIMARY KEY, merkle_root BLOB, chunk_size INTEGER NOT NULL, total_chunks INTEGER NOT NULL
+                );
+                CREATE TABLE commitment_leaves (
+                    file_id TEXT NOT NULL, chunk_index INTEGER NOT NULL, leaf BLOB NOT NULL,
+                    PRIMARY KEY (file_id, chunk_index)
+                ) WITHOUT ROWID;
+                INSERT INTO commitment_meta VALUES ('old_file', NULL, 4, 1);",
+            )
+            .unwrap();
+            conn.execute("INSERT INTO commitment_leaves VALUES ('old_file', 0, ?1)", [&[9u8; 32][..]]).unwrap();
+        }
+
+        let backend = SqliteCommitmentBackend::open(&path).unwrap();
+        assert_eq!(backend.get_meta(DEFAULT_TENANT, "old_file").await.unwrap(), Some((CommitmentAlg::Sha256Chunks, 4, 1)));
+        assert_eq!(backend.get_leaf(DEFAULT_TENANT, "old_file", 0).await.unwrap(), Some([9u8; 32]));
+        assert_eq!(backend.get_meta("tenant-b", "old_file").await.unwrap(), None);
+        drop(backend);
+
+        // Reopening a migrated store leaves its rows where they are
+        let backend = SqliteCommitmentBackend::open(&path).unwrap();
+        assert_eq!(backend.get_leaf(DEFAULT_TENANT, "old_file", 0).await.unwrap(), Some([9u8; 32]));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    async fn two_tenant_verifier() -> StorageVerifier {
+        let config = StorageVerifierConfig { tenant_metrics: true, ..StorageVerifierConfig::default() };
+        let verifier = StorageVerifier::from_config(config).unwrap();
+        for (tenant, data) in [("tenant-a", b"tenant a chunk"), ("tenant-b", b"tenant b chunk")] {
+            verifier
+                .register_file_commitments_for_tenant(tenant, "shared_name", data.len() as u32, vec![Sha256::digest(data).into()])
+                .await
+                .unwrap();
+        }
+        verifier
+    }
+
+    fn tenant_proof(challenge: &StorageChallenge, data: &[u8]) -> StorageProof {
+        StorageProof {
+            challenge_id: challenge.id.clone(),
+            file_id: challenge.file_id.clone(),
+            provider: challenge.provider.clone(),
+            timestamp: challenge.timestamp,
+            proof_data: data.to_vec(),
+            merkle_proof: None,
+            signature: None,
+            extra_proof_data: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identical_file_ids_under_two_tenants_do_not_collide() {
+        let verifier = two_tenant_verifier().await;
+
+        let a = verifier.generate_challenge_for_tenant("tenant-a", "shared_name", "provider1", false).await.unwrap();
+        let b = verifier.generate_challenge_for_tenant("tenant-b", "shared_name", "provider1", false).await.unwrap();
+        assert_eq!((a.tenant_id.as_str(), b.tenant_id.as_str()), ("tenant-a", "tenant-b"));
+
+        // Each tenant's challenge is answered with that tenant's data only
+        assert!(verifier.verify_proof_for_tenant("tenant-a", tenant_proof(&a, b"tenant a chunk")).await.unwrap());
+        assert!(!verifier.verify_proof_for_tenant("tenant-b", tenant_proof(&b, b"tenant a chunk")).await.unwrap());
+
+        assert_eq!(
+            verifier.get_tenant_metrics("tenant-a").await,
+            TenantMetrics { total_challenges: 1, successful_proofs: 1, ..TenantMetrics::default() }
+        );
+        assert_eq!(
+            verifier.get_tenant_metrics("tenant-b").await,
+            TenantMetrics { total_challenges: 1, failed_proofs: 1, ..TenantMetrics::default() }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tenant_cannot_challenge_or_verify_another_tenants_file() {
+        let verifier = two_tenant_verifier().await;
+        verifier
+            .register_file_commitments_for_tenant("tenant-b", "b_only", 14, vec![Sha256::digest(b"tenant b chunk").into()])
+            .await
+            .unwrap();
+
+        // Another tenant's file looks exactly like one that was never registered
+        let foreign = verifier.generate_challenge_for_tenant("tenant-a", "b_only", "provider1", false).await.unwrap_err();
+        let missing = verifier.generate_challenge_for_tenant("tenant-a", "no_such_file", "provider1", false).await.unwrap_err();
+        assert_eq!(foreign.to_string(), missing.to_string());
+        assert!(matches!(foreign, StorageVerificationError::InvalidInput { .. }));
+        assert!(!verifier.has_commitments_for_tenant("tenant-a", "b_only").await.unwrap());
+        assert!(!verifier.has_commitments("b_only").await.unwrap());
+
+        // Tenant B's challenge cannot be answered, or probed, under tenant A or the default tenant
+        let b = verifier.generate_challenge_for_tenant("tenant-b", "b_only", "provider1", false).await.unwrap();
+        assert!(matches!(
+            verifier.verify_proof_for_tenant("tenant-a", tenant_proof(&b, b"tenant b chunk")).await,
+            Err(StorageVerificationError::ChallengeNotFound { .. })
+        ));
+        assert!(matches!(
+            verifier.verify_proof(tenant_proof(&b, b"tenant b chunk")).await,
+            Err(StorageVerificationError::ChallengeNotFound { .. })
+        ));
+        assert!(verifier.verify_proof_for_tenant("tenant-b", tenant_proof(&b, b"tenant b chunk")).await.unwrap());
+
+        assert!(matches!(
+            verifier.generate_challenge_for_tenant("", "b_only", "provider1", false).await,
+            Err(StorageVerificationError::InvalidInput { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_removes_leaves_of_unfinished_registrations() {
+        let backend = Arc::new(MemoryCommitmentBackend::new());
+        let verifier = StorageVerifier::with_backend(RateLimitConfig::default(), backend.clone());
+
+        verifier.register_file_commitments("complete", 4, vec![[1u8; 32]; 3]).await.unwrap();
+        // Simulates a registration interrupted before its metadata was written
+        backend.put_leaves_batch(DEFAULT_TENANT, "partial", 0, &[[2u8; 32]; 2]).await.unwrap();
+        // Leaves of another tenant's file are not kept alive by this tenant's metadata
+        backend.put_leaves_batch("tenant-b", "complete", 0, &[[3u8; 32]; 1]).await.unwrap();
+
+        assert_eq!(backend.cleanup().await.unwrap(), 3);
+        assert_eq!(backend.get_leaf(DEFAULT_TENANT, "partial", 0).await.unwrap(), None);
+        assert_eq!(backend.get_leaf("tenant-b", "complete", 0).await.unwrap(), None);
+        assert_eq!(backend.get_leaf(DEFAULT_TENANT, "complete", 2).await.unwrap(), Some([1u8; 32]));
+    }
+
+    const MERKLE_DATA: &[u8] = b"Merkle committed file whose chunks are proven against the root alone.";
+    const MERKLE_CHUNK: usize = 8;
+
+    fn merkle_leaves() -> Vec<[u8; 32]> {
+        MERKLE_DATA.chunks(MERKLE_CHUNK).map(merkle::hash_leaf).collect()
+    }
+
+    fn merkle_proof_for(challenge: &StorageChallenge, index: usize, merkle_proof: MerkleProof) -> StorageProof {
+        StorageProof {
+            challenge_id: challenge.id.clone(),
+            file_id: challenge.file_id.clone(),
+            provider: challenge.provider.clone(),
+            timestamp: challenge.timestamp + 1,
+            proof_data: MERKLE_DATA.chunks(MERKLE_CHUNK).nth(index).unwrap().to_vec(),
+            merkle_proof: Some(merkle_proof),
+            signature: None,
+            extra_proof_data: Vec::new(),
+        }
+    }
+
+    async fn merkle_challenge(verifier: &StorageVerifier, leaves: &[[u8; 32]]) -> StorageChallenge {
+        let root = merkle::build_root(leaves).unwrap();
+        verifier.register_merkle_root("merkle_file", root, MERKLE_CHUNK as u32, leaves.len() as u64).await.unwrap();
+        verifier.generate_challenge("merkle_file", "provider1").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_merkle_proof_round_trip_against_registered_root() {
+        let verifier = StorageVerifier::new();
+        let leaves = merkle_leaves();
+        let challenge = merkle_challenge(&verifier, &leaves).await;
+        assert_eq!(challenge.commitment_alg, "merkle_sha256");
+
+        let index = challenge.chunk_index as usize;
+        let steps = merkle::generate_proof(&leaves, index).unwrap();
+        let pairs = merkle_proof_for(&challenge, index, MerkleProof::from_steps(&steps));
+        assert!(verifier.verify_proof(pairs).await.unwrap());
+
+        let bitmask = MerkleProof::Bitmask {
+            hashes: steps.iter().map(|s| hex::encode(s.sibling)).collect(),
+            left_mask: merkle::proof_index(&steps),
+        };
+        assert!(verifier.verify_proof(merkle_proof_for(&challenge, index, bitmask)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_merkle_proof_rejects_wrong_index_swapped_siblings_and_truncation() {
+        let verifier = StorageVerifier::new();
+        let leaves = merkle_leaves();
+        let challenge = merkle_challenge(&verifier, &leaves).await;
+        let index = challenge.chunk_index as usize;
+
+        // A valid proof, but for a chunk other than the one challenged
+        let other = (index + 1) % leaves.len();
+        let steps = merkle::generate_proof(&leaves, other).unwrap();
+        let wrong_index = merkle_proof_for(&challenge, other, MerkleProof::from_steps(&steps));
+        assert!(!verifier.verify_proof(wrong_index).await.unwrap());
+
+        let mut steps = merkle::generate_proof(&leaves, index).unwrap();
+        let mut swapped = steps.clone();
+        swapped[0].is_left = !swapped[0].is_left;
+        let swapped = merkle_proof_for(&challenge, index, MerkleProof::from_steps(&swapped));
+        assert!(!verifier.verify_proof(swapped).await.unwrap());
+
+        steps.pop();
+        let truncated = merkle_proof_for(&challenge, index, MerkleProof::from_steps(&steps));
+        assert!(!verifier.verify_proof(truncated).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_merkle_proofs_require_opt_in() {
+        // A single chunk has an empty proof, which the legacy format can express
+        let leaves = vec![merkle::hash_leaf(&MERKLE_DATA[..MERKLE_CHUNK])];
+
+        let strict = StorageVerifier::new();
+        let challenge = merkle_challenge(&strict, &leaves).await;
+        let legacy = merkle_proof_for(&challenge, 0, MerkleProof::Legacy(Vec::new()));
+        assert!(matches!(
+            strict.verify_proof(legacy).await,
+            Err(StorageVerificationError::InvalidInput { .. })
+        ));
+
+        let lenient = StorageVerifier::from_config(StorageVerifierConfig {
+            allow_legacy_merkle_proofs: true,
+            ..StorageVerifierConfig::default()
+        })
+        .unwrap();
+        let challenge = merkle_challenge(&lenient, &leaves).await;
+        let legacy = merkle_proof_for(&challenge, 0, MerkleProof::Legacy(Vec::new()));
+        assert!(lenient.verify_proof(legacy).await.unwrap());
+    }
+
+    fn signing_key() -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&rand::random::<[u8; 32]>())
+    }
+
+    fn sign(mut proof: StorageProof, key: &ed25519_dalek::SigningKey) -> StorageProof {
+        use ed25519_dalek::Signer;
+        proof.signature = Some(general_purpose::STANDARD.encode(key.sign(&proof.signing_message()).to_bytes()));
+        proof
+    }
+
+    async fn signed_challenge(verifier: &StorageVerifier) -> (StorageChallenge, StorageProof) {
+        let data = b"signed chunk";
+        verifier.register_file_commitments("signed_file", data.len() as u32, vec![Sha256::digest(data).into()]).await.unwrap();
+        let challenge = verifier.generate_challenge_with("signed_file", "provider1", true).await.unwrap();
+        let proof = StorageProof {
+            challenge_id: challenge.id.clone(),
+            file_id: "signed_file".to_string(),
+            provider: "provider1".to_string(),
+            timestamp: challenge.timestamp + 1,
+            proof_data: data.to_vec(),
+            merkle_proof: None,
+            signature: None,
+            extra_proof_data: Vec::new(),
+        };
+        (challenge, proof)
+    }
+
+    #[tokio::test]
+    async fn test_signed_proof_verifies_and_tampering_is_rejected() {
+        let verifier = StorageVerifier::new();
+        let key = signing_key();
+        verifier.register_provider_key("provider1", key.verifying_key().as_bytes()).await.unwrap();
+        let (challenge, proof) = signed_challenge(&verifier).await;
+        assert!(challenge.require_signature);
+
+        assert!(verifier.verify_proof(sign(proof.clone(), &key)).await.unwrap());
+
+        // The timestamp is covered by the signature
+        let mut tampered = sign(proof.clone(), &key);
+        tampered.timestamp += 1;
+        assert!(!verifier.verify_proof(tampered).await.unwrap());
+
+        // Signed by someone else
+        assert!(!verifier.verify_proof(sign(proof.clone(), &signing_key())).await.unwrap());
+
+        // Required but missing
+        assert!(!verifier.verify_proof(proof).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_signature_from_unknown_provider_fails_authentication() {
+        let verifier = StorageVerifier::new();
+        let (_, proof) = signed_challenge(&verifier).await;
+
+        assert!(matches!(
+            verifier.verify_proof(sign(proof, &signing_key())).await,
+            Err(StorageVerificationError::AuthenticationFailed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_register_provider_key_rejects_malformed_keys() {
+        let verifier = StorageVerifier::new();
+        assert!(matches!(
+            verifier.register_provider_key("provider1", &[7u8; 31]).await,
+            Err(StorageVerificationError::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            verifier.register_provider_key("", signing_key().verifying_key().as_bytes()).await,
+            Err(StorageVerificationError::InvalidInput { .. })
+        ));
+        assert_eq!(verifier.provider_key("provider1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_empty_merkle_commitments_are_rejected_not_challenged() {
+        let backend = Arc::new(MemoryCommitmentBackend::new());
+        let verifier = StorageVerifier::with_backend(RateLimitConfig::default(), backend.clone());
+        for (chunk_size, total_chunks) in [(1024, 0), (0, 4)] {
+            assert!(matches!(
+                verifier.register_merkle_root("empty", [1u8; 32], chunk_size, total_chunks).await,
+                Err(StorageVerificationError::InvalidInput { .. })
+            ));
+        }
+        assert!(verifier.generate_challenge("empty", "provider1").await.is_err());
+
+        // Metadata that bypassed registration fails the challenge instead of panicking
+        let meta = (CommitmentAlg::MerkleSha256 { root: [1u8; 32], chunk_size: 1024 }, 1024, 0);
+        backend.put_meta(DEFAULT_TENANT, "empty", meta).await.unwrap();
+        assert!(matches!(
+            verifier.generate_challenge("empty", "provider1").await,
+            Err(StorageVerificationError::InvalidInput { .. })
+        ));
+    }
+
+    async fn lifecycle_verifier() -> (Arc<StorageVerifier>, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::starting_now());
+        let verifier = Arc::new(StorageVerifier::new().with_clock(clock.clone()));
+        let data = b"lifecycle chunk";
+        verifier.register_file_commitments("lifecycle_file", data.len() as u32, vec![Sha256::digest(data).into()]).await.unwrap();
+        (verifier, clock)
+    }
+
+    fn answer(challenge: &StorageChallenge) -> StorageProof {
+        StorageProof {
+            challenge_id: challenge.id.clone(),
+            file_id: challenge.file_id.clone(),
+            provider: challenge.provider.clone(),
+            timestamp: challenge.timestamp,
+            proof_data: b"lifecycle chunk".to_vec(),
+            merkle_proof: None,
+            signature: None,
+            extra_proof_data: Vec::new(),
+        }
+    }
+
+    fn expired_log(verifier: &StorageVerifier) -> Arc<std::sync::Mutex<Vec<String>>> {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        verifier.on_challenge_expired(Box::new(move |c| sink.lock().unwrap().push(c.id.clone())));
+        seen
+    }
+
+    fn past_ttl(clock: &ManualClock) {
+        clock.advance(DEFAULT_CHALLENGE_TTL + Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_fires_callback_once_for_unanswered_challenges() {
+        let (verifier, _clock) = lifecycle_verifier().await;
+        let seen = expired_log(&verifier);
+
+        let answered = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        let unanswered = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        assert!(verifier.verify_proof(answer(&answered)).await.unwrap());
+
+        let later = unanswered.expiry + 1;
+        assert_eq!(verifier.expire_challenges_at(later).await, 1);
+        assert_eq!(verifier.expire_challenges_at(later).await, 0);
+
+        assert_eq!(*seen.lock().unwrap(), vec![unanswered.id.clone()]);
+        assert_eq!(verifier.get_metrics().await.expired_challenges, 1);
+        assert!(verifier.get_challenge(&answered.id).await.is_none());
+        assert!(matches!(
+            verifier.verify_proof(answer(&unanswered)).await,
+            Err(StorageVerificationError::ChallengeNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_late_proof_counts_expiry_once_then_challenge_is_gone() {
+        let (verifier, clock) = lifecycle_verifier().await;
+        let seen = expired_log(&verifier);
+        let challenge = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        clock.advance(DEFAULT_CHALLENGE_TTL);
+        assert!(verifier.get_challenge(&challenge.id).await.is_some());
+        verifier.cleanup_expired().await;
+        assert!(seen.lock().unwrap().is_empty(), "a challenge is still live at exactly its expiry");
+        clock.advance(Duration::from_secs(1));
+
+        assert!(!verifier.verify_proof(answer(&challenge)).await.unwrap());
+        verifier.cleanup_expired().await;
+
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        assert_eq!(verifier.get_metrics().await.expired_challenges, 1);
+        assert!(matches!(
+            verifier.verify_proof(answer(&challenge)).await,
+            Err(StorageVerificationError::ChallengeNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_background_task_sweeps_expired_challenges() {
+        let (verifier, clock) = lifecycle_verifier().await;
+        let seen = expired_log(&verifier);
+        let challenge = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        past_ttl(&clock);
+
+        let task = verifier.start_background_tasks(Duration::from_millis(10));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while seen.lock().unwrap().is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("expiry callback did not fire");
+
+        assert_eq!(*seen.lock().unwrap(), vec![challenge.id]);
+        drop(verifier);
+        tokio::time::timeout(Duration::from_secs(5), task).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_batch_reports_each_proof_in_input_order() {
+        let (verifier, clock) = lifecycle_verifier().await;
+        let expired = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        past_ttl(&clock);
+        let valid = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        let wrong = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+
+        let mut wrong_data = answer(&wrong);
+        wrong_data.proof_data = b"lifecycle chunK".to_vec();
+        let mut malformed = answer(&valid);
+        malformed.challenge_id = String::new();
+        let mut unknown = answer(&valid);
+        unknown.challenge_id = "chall_missing".to_string();
+
+        let results = verifier
+            .verify_proofs_batch(vec![answer(&valid), answer(&expired), malformed, wrong_data, unknown])
+            .await;
+        assert_eq!(results.len(), 5);
+        assert!(results[0].as_ref().unwrap());
+        assert!(!results[1].as_ref().unwrap());
+        assert!(matches!(results[2], Err(StorageVerificationError::InvalidInput { .. })));
+        assert!(!results[3].as_ref().unwrap());
+        assert!(matches!(results[4], Err(StorageVerificationError::ChallengeNotFound { .. })));
+        assert_eq!(verifier.get_metrics().await.successful_proofs, 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_larger_than_the_permits_queues_instead_of_overloading() {
+        let clock = Arc::new(ManualClock::starting_now());
+        let verifier = StorageVerifier::new()
+            .with_clock(clock)
+            .with_concurrency_limit(2, Duration::from_millis(50));
+        let data = b"lifecycle chunk";
+        verifier.register_file_commitments("lifecycle_file", data.len() as u32, vec![Sha256::digest(data).into()]).await.unwrap();
+        let mut proofs = Vec::new();
+        for _ in 0..20 {
+            proofs.push(answer(&verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap()));
+        }
+
+        let results = verifier.verify_proofs_batch(proofs).await;
+        assert!(results.iter().all(|result| matches!(result, Ok(true))), "{:?}", results);
+        assert_eq!(verifier.get_metrics().await.overloaded_rejections, 0);
+    }
+
+    #[tokio::test]
+    async fn test_list_challenges_filters_by_provider_and_expiry() {
+        let (verifier, clock) = lifecycle_verifier().await;
+        let first = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        past_ttl(&clock);
+        let second = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        verifier.generate_challenge("lifecycle_file", "provider2").await.unwrap();
+
+        let live: Vec<String> = verifier.list_challenges("provider1", false).await.into_iter().map(|c| c.id).collect();
+        assert_eq!(live, vec![second.id.clone()]);
+        let all: Vec<String> = verifier.list_challenges("provider1", true).await.into_iter().map(|c| c.id).collect();
+        assert_eq!(all.len(), 2);
+        assert!(all.contains(&first.id));
+        assert_eq!(verifier.get_challenge(&second.id).await.unwrap().provider, "provider1");
+    }
+
+    fn reputation_verifier(threshold: f64) -> (StorageVerifier, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::starting_now());
+        let config = StorageVerifierConfig {
+            reputation_threshold: Some(threshold),
+            reputation_half_life: Some(Duration::from_secs(86400)),
+            ..StorageVerifierConfig::default()
+        };
+        (StorageVerifier::from_config(config).unwrap().with_clock(clock.clone()), clock)
+    }
+
+    #[tokio::test]
+    async fn test_reputation_drops_below_threshold_after_failures_and_recovers() {
+        let (verifier, clock) = reputation_verifier(0.7);
+        let data = b"lifecycle chunk";
+        verifier.register_file_commitments("lifecycle_file", data.len() as u32, vec![Sha256::digest(data).into()]).await.unwrap();
+        assert_eq!(verifier.get_reputation("provider1").await.unwrap(), None);
+
+        // Three wrong proofs and two challenges left to expire
+        for _ in 0..3 {
+            let challenge = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+            let mut proof = answer(&challenge);
+            proof.proof_data = b"not the chunk".to_vec();
+            assert!(!verifier.verify_proof(proof).await.unwrap());
+        }
+        for _ in 0..2 {
+            verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        }
+        past_ttl(&clock);
+        assert_eq!(verifier.expire_challenges_at(clock.now_unix()).await, 2);
+
+        let reputation = verifier.get_reputation("provider1").await.unwrap().unwrap();
+        assert_eq!((reputation.challenges_issued, reputation.proofs_verified, reputation.failures), (5, 0, 5));
+        assert!(reputation.score < verifier.reputation_threshold(), "{}", reputation.score);
+        assert!(reputation.last_seen.is_some());
+        let challenge = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+        assert_eq!(challenge.difficulty, MAX_DIFFICULTY);
+        assert!(verifier.verify_proof(answer(&challenge)).await.unwrap());
+
+        for _ in 0..30 {
+            let challenge = verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap();
+            assert!(verifier.verify_proof(answer(&challenge)).await.unwrap());
+        }
+        let reputation = verifier.get_reputation("provider1").await.unwrap().unwrap();
+        assert_eq!((reputation.proofs_verified, reputation.failures), (31, 5));
+        assert!(reputation.score > verifier.reputation_threshold(), "{}", reputation.score);
+        assert!(verifier.generate_challenge("lifecycle_file", "provider1").await.unwrap().difficulty < MAX_DIFFICULTY);
+    }
+
+    #[tokio::test]
+    async fn test_reputation_decays_towards_the_prior() {
+        let (verifier, clock) = reputation_verifier(0.7);
+        let now = clock.now_unix();
+        for _ in 0..5 {
+            verifier.reputation.record_missed("provider1", now).await;
+        }
+        let failed = verifier.get_reputation("provider1").await.unwrap().unwrap().score;
+        assert!((failed - 10.0 / 15.0).abs() < 1e-9, "{}", failed);
+
+        // One half-life later the failures weigh half as much
+        clock.advance(Duration::from_secs(86400));
+        let halved = verifier.get_reputation("provider1").await.unwrap().unwrap().score;
+        assert!((halved - 10.0 / 12.5).abs() < 1e-9, "{}", halved);
+        assert!(halved > verifier.reputation_threshold());
+
+        clock.advance(Duration::from_secs(30 * 86400));
+        assert!(verifier.get_reputation("provider1").await.unwrap().unwrap().score > 0.999);
+    }
+
+    #[tokio::test]
+    async fn test_list_reputations_filters_and_ranks_by_score() {
+        let (verifier, clock) = reputation_verifier(0.7);
+        let now = clock.now_unix();
+        verifier.reputation.record_issued("steady", now).await;
+        verifier.reputation.record_proof("flaky", false, 100.0, now).await;
+        for _ in 0..5 {
+            verifier.reputation.record_missed("absent", now).await;
+        }
+
+        let ranked: Vec<String> = verifier.list_reputations(0.0).await.unwrap().into_iter().map(|r| r.provider).collect();
+        assert_eq!(ranked, vec!["steady", "flaky", "absent"]);
+        let passing: Vec<String> = verifier.list_reputations(0.7).await.unwrap().into_iter().map(|r| r.provider).collect();
+        assert_eq!(passing, vec!["steady", "flaky"]);
+    }
+
+    #[test]
+    fn test_difficulty_follows_score_within_bounds() {
+        assert_eq!(difficulty_for_score(1.0, 0.5), MIN_DIFFICULTY);
+        assert_eq!(difficulty_for_score(0.75, 0.5), 3);
+        assert_eq!(difficulty_for_score(0.5, 0.5), MAX_DIFFICULTY);
+        assert_eq!(difficulty_for_score(0.0, 0.5), MAX_DIFFICULTY);
+        assert_eq!(difficulty_for_score(0.2, 1.0), MAX_DIFFICULTY);
+    }
+
+    const MULTI_DATA: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMN";
+    const MULTI_CHUNK: usize = 5;
+
+    async fn multi_chunk_challenge(difficulty: u8) -> (StorageVerifier, StorageChallenge) {
+        let verifier = StorageVerifier::new();
+        let leaves = MULTI_DATA.chunks(MULTI_CHUNK).map(|c| Sha256::digest(c).into()).collect();
+        verifier.register_file_commitments("multi_file", MULTI_CHUNK as u32, leaves).await.unwrap();
+        // Pick decayed weights whose score maps to `difficulty` under the default threshold
+        let shortfall = (difficulty - MIN_DIFFICULTY) as f64 / (MAX_DIFFICULTY - MIN_DIFFICULTY) as f64;
+        let score = 1.0 - shortfall * (1.0 - DEFAULT_REPUTATION_THRESHOLD);
+        let mut reputation = ProviderReputation::new("provider1", verifier.clock.now_unix());
+        reputation.weighted_outcomes = 100.0;
+        reputation.weighted_successes = score * (100.0 + REPUTATION_PRIOR_WEIGHT) - REPUTATION_PRIOR_WEIGHT;
+        verifier.reputation.cache.lock().await.insert("provider1".to_string(), reputation);
+        let challenge = verifier.generate_challenge("multi_file", "provider1").await.unwrap();
+        (verifier, challenge)
+    }
+
+    fn multi_chunk_proof(challenge: &StorageChallenge) -> StorageProof {
+        let mut samples = challenge
+            .chunk_indices
+            .iter()
+            .map(|&i| MULTI_DATA.chunks(MULTI_CHUNK).nth(i as usize).unwrap().to_vec());
+        StorageProof {
+            challenge_id: challenge.id.clone(),
+            file_id: challenge.file_id.clone(),
+            provider: challenge.provider.clone(),
+            timestamp: challenge.timestamp,
+            proof_data: samples.next().unwrap(),
+            merkle_proof: None,
+            signature: None,
+            extra_proof_data: samples.collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_harder_challenges_demand_every_chunk() {
+        let (verifier, challenge) = multi_chunk_challenge(3).await;
+        assert_eq!(challenge.difficulty, 3);
+        assert_eq!(challenge.chunk_indices.len(), 3);
+        assert_eq!(challenge.chunk_index, challenge.chunk_indices[0]);
+        let distinct: std::collections::HashSet<u64> = challenge.chunk_indices.iter().copied().collect();
+        assert_eq!(distinct.len(), 3);
+
+        let mut missing = multi_chunk_proof(&challenge);
+        missing.extra_proof_data.pop();
+        assert!(matches!(
+            verifier.verify_proof(missing).await,
+            Err(StorageVerificationError::CryptographicFailure { .. })
+        ));
+
+        let mut wrong = multi_chunk_proof(&challenge);
+        wrong.extra_proof_data[1] = b"xxxxx".to_vec();
+        assert!(!verifier.verify_proof(wrong).await.unwrap());
+
+        assert!(verifier.verify_proof(multi_chunk_proof(&challenge)).await.unwrap());
+        let provider = verifier.get_reputation("provider1").await.unwrap().unwrap();
+        assert_eq!((provider.proofs_verified, provider.failures), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_difficulty_one_keeps_single_chunk_shape() {
+        let (verifier, challenge) = multi_chunk_challenge(MIN_DIFFICULTY).await;
+        assert_eq!(challenge.chunk_indices, vec![challenge.chunk_index]);
+        assert_eq!(challenge.sample_offset, challenge.chunk_index * MULTI_CHUNK as u64);
+
+        let proof = multi_chunk_proof(&challenge);
+        assert!(proof.extra_proof_data.is_empty());
+        assert!(verifier.verify_proof(proof).await.unwrap());
+    }
+
+    mod scoring {
+        use super::*;
+
+        // Each list runs from worst to best evidence
+        const MERKLE: [Option<bool>; 3] = [Some(false), None, Some(true)];
+        const SIGNATURE: [Option<bool>; 3] = [Some(false), None, Some(true)];
+        const LEVELS: [f64; 3] = [0.0, 0.5, 1.0];
+
+        fn evidence(samples_match: bool, merkle_valid: Option<bool>, signature_valid: Option<bool>) -> ProofEvidence {
+            ProofEvidence { samples_match, merkle_valid, signature_valid, signature_required: false }
+        }
+
+        #[test]
+        fn test_named_combinations() {
+            let hash_ok_unsigned = evidence(true, None, None).score(1.0, 1.0);
+            assert!(hash_ok_unsigned.verified);
+
+            let hash_ok_good_sig = evidence(true, None, Some(true)).score(1.0, 1.0);
+            assert!(hash_ok_good_sig.verified);
+            assert!(hash_ok_good_sig.score > hash_ok_unsigned.score);
+
+            let hash_ok_bad_sig = evidence(true, None, Some(false)).score(1.0, 1.0);
+            assert!(!hash_ok_bad_sig.verified);
+            assert!(hash_ok_bad_sig.score <= FAILED_PROOF_MAX_SCORE);
+
+            let hash_bad_good_sig = evidence(false, Some(true), Some(true)).score(1.0, 1.0);
+            assert!(!hash_bad_good_sig.verified);
+            assert!(hash_bad_good_sig.score <= FAILED_PROOF_MAX_SCORE);
+
+            let required_but_unsigned =
+                ProofEvidence { signature_required: true, ..evidence(true, Some(true), None) }.score(1.0, 1.0);
+            assert!(!required_but_unsigned.verified);
+            assert_eq!(required_but_unsigned.components["signature"], 0.0);
+
+            let everything = evidence(true, Some(true), Some(true)).score(1.0, 1.0);
+            assert!((everything.score - 1.0).abs() < 1e-9);
+            assert_eq!(everything.components.len(), 5);
+        }
+
+        #[test]
+        fn test_scores_are_bounded_monotonic_and_failed_hashes_stay_below_threshold() {
+            let score = |s: bool, m: usize, g: usize, l: usize, h: usize| {
+                evidence(s, MERKLE[m], SIGNATURE[g]).score(LEVELS[l], LEVELS[h])
+            };
+            for s in [false, true] {
+                for m in 0..3 {
+                    for g in 0..3 {
+                        for l in 0..3 {
+                            for h in 0..3 {
+                                let outcome = score(s, m, g, l, h);
+                                assert!((0.0..=1.0).contains(&outcome.score));
+                                if !outcome.verified {
+                                    assert!(outcome.score <= FAILED_PROOF_MAX_SCORE);
+                                }
+                                if !s {
+                                    assert!(!outcome.verified && outcome.score < 0.7);
+                                }
+
+                                // Better evidence on any one axis never lowers the score
+                                let better = [
+                                    (!s).then(|| score(true, m, g, l, h)),
+                                    (m < 2).then(|| score(s, m + 1, g, l, h)),
+                                    (g < 2).then(|| score(s, m, g + 1, l, h)),
+                                    (l < 2).then(|| score(s, m, g, l + 1, h)),
+                                    (h < 2).then(|| score(s, m, g, l, h + 1)),
+                                ];
+                                for improved in better.into_iter().flatten() {
+                                    assert!(improved.score >= outcome.score, "{:?} < {:?}", improved, outcome);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn test_latency_budget_scales_with_challenged_chunks() {
+            assert_eq!(latency_component(0, 1), 1.0);
+            assert_eq!(latency_component(30, 1), 0.5);
+            assert_eq!(latency_component(30, 2), 0.75);
+            assert_eq!(latency_component(600, 3), 0.0);
+        }
+
+        #[tokio::test]
+        async fn test_verify_proof_scored_reports_evidence() {
+            let verifier = StorageVerifier::new();
+            let data = b"scored sample chunk";
+            verifier.register_file_commitments("file", data.len() as u32, vec![merkle::hash_leaf(data)]).await.unwrap();
+
+            let proof_with = |challenge: &StorageChallenge, proof_data: &[u8]| StorageProof {
+                challenge_id: challenge.id.clone(),
+                file_id: "file".to_string(),
+                provider: "provider".to_string(),
+                timestamp: challenge.timestamp,
+                proof_data: proof_data.to_vec(),
+                merkle_proof: None,
+                signature: None,
+                extra_proof_data: Vec::new(),
+            };
+
+            let challenge = verifier.generate_challenge("file", "provider").await.unwrap();
+            let outcome = verifier.verify_proof_scored(proof_with(&challenge, data)).await.unwrap();
+            assert!(outcome.verified);
+            assert_eq!(outcome.components["chunk_hashes"], 1.0);
+            assert_eq!(outcome.components["signature"], 0.5);
+            assert_eq!(outcome.components["provider_history"], 1.0);
+
+            let challenge = verifier.generate_challenge("file", "provider").await.unwrap();
+            let outcome = verifier.verify_proof_scored(proof_with(&challenge, b"not the stored data")).await.unwrap();
+            assert!(!outcome.verified);
+            assert_eq!(outcome.components["chunk_hashes"], 0.0);
+            assert!(outcome.score <= FAILED_PROOF_MAX_SCORE);
+        }
+    }
+
+    mod verification_limits {
+        use super::*;
+
+        /// Memory backend whose leaf reads are slow enough to hold a verification slot
+        struct SlowLeaves(MemoryCommitmentBackend);
+
+        #[async_trait]
+        impl CommitmentBackend for SlowLeaves {
+            async fn get_meta(&self, tenant_id: &str, file_id: &str) -> Result<Option<ChunkMeta>, StorageVerificationError> {
+                self.0.get_meta(tenant_id, file_id).await
+            }
+            async fn get_leaf(
+                &self,
+                tenant_id: &str,
+                file_id: &str,
+                chunk_index: u64,
+            ) -> Result<Option<[u8; 32]>, StorageVerificationError> {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                self.0.get_leaf(tenant_id, file_id, chunk_index).await
+            }
+            async fn put_meta(&self, tenant_id: &str, file_id: &str, meta: ChunkMeta) -> Result<(), StorageVerificationError> {
+                self.0.put_meta(tenant_id, file_id, meta).await
+            }
+            async fn put_leaves_batch(
+                &self,
+                tenant_id: &str,
+                file_id: &str,
+                first_index: u64,
+                leaves: &[[u8; 32]],
+            ) -> Result<(), StorageVerificationError> {
+                self.0.put_leaves_batch(tenant_id, file_id, first_index, leaves).await
+            }
+            async fn cleanup(&self) -> Result<u64, StorageVerificationError> {
+                self.0.cleanup().await
+            }
+            async fn put_provider_key(&self, provider: &str, public_key: [u8; 32]) -> Result<(), StorageVerificationError> {
+                self.0.put_provider_key(provider, public_key).await
+            }
+            async fn get_provider_key(&self, provider: &str) -> Result<Option<[u8; 32]>, StorageVerificationError> {
+                self.0.get_provider_key(provider).await
+            }
+            async fn put_reputation(&self, reputation: &ProviderReputation) -> Result<(), StorageVerificationError> {
+                self.0.put_reputation(reputation).await
+            }
+            async fn get_reputation(&self, provider: &str) -> Result<Option<ProviderReputation>, StorageVerificationError> {
+                self.0.get_reputation(provider).await
+            }
+            async fn list_reputations(&self) -> Result<Vec<ProviderReputation>, StorageVerificationError> {
+                self.0.list_reputations().await
+            }
+        }
+
+        const DATA: &[u8] = b"sixteen byte chk";
+
+        fn proof_for(challenge: &StorageChallenge, proof_data: Vec<u8>) -> StorageProof {
+            StorageProof {
+                challenge_id: challenge.id.clone(),
+                file_id: challenge.file_id.clone(),
+                provider: challenge.provider.clone(),
+                timestamp: challenge.timestamp,
+                proof_data,
+                merkle_proof: None,
+                signature: None,
+                extra_proof_data: Vec::new(),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_verifications_beyond_the_permits_are_rejected_as_overloaded() {
+            let backend = Arc::new(SlowLeaves(MemoryCommitmentBackend::new()));
+            let verifier = Arc::new(
+                StorageVerifier::with_backend(RateLimitConfig::default(), backend)
+                    .with_concurrency_limit(2, Duration::from_millis(50)),
+            );
+            verifier.register_file_commitments("file", DATA.len() as u32, vec![merkle::hash_leaf(DATA)]).await.unwrap();
+
+            let mut handles = Vec::new();
+            for _ in 0..6 {
+                let challenge = verifier.generate_challenge("file", "provider").await.unwrap();
+                let verifier = verifier.clone();
+                handles.push(tokio::spawn(async move { verifier.verify_proof(proof_for(&challenge, DATA.to_vec())).await }));
+            }
+
+            let (mut verified, mut overloaded) = (0, 0);
+            for handle in handles {
+                match handle.await.unwrap() {
+                    Ok(true) => verified += 1,
+                    Err(StorageVerificationError::Overloaded { waited_ms: 50 }) => overloaded += 1,
+                    other => panic!("unexpected result {:?}", other),
+                }
+            }
+            assert!(verified >= 2, "verified {}", verified);
+            assert!(overloaded >= 1, "overloaded {}", overloaded);
+            assert_eq!(verifier.get_metrics().await.overloaded_rejections, overloaded);
+        }
+
+        #[tokio::test]
+        async fn test_oversized_samples_are_rejected_before_verification() {
+            let verifier = StorageVerifier::from_config(StorageVerifierConfig {
+                max_proof_size: Some(DATA.len()),
+                ..StorageVerifierConfig::default()
+            })
+            .unwrap();
+            verifier.register_file_commitments("file", DATA.len() as u32, vec![merkle::hash_leaf(DATA)]).await.unwrap();
+            let challenge = verifier.generate_challenge("file", "provider").await.unwrap();
+
+            let mut oversized = proof_for(&challenge, DATA.to_vec());
+            oversized.extra_proof_data.push(vec![0u8; DATA.len() + 1]);
+            match verifier.verify_proof(oversized).await {
+                Err(StorageVerificationError::InvalidInput { field, .. }) => assert_eq!(field, "proof_data"),
+                other => panic!("expected a size-cap rejection, got {:?}", other),
+            }
+            assert_eq!(verifier.get_metrics().await.oversized_proofs, 1);
+
+            // The rejected proof never reached the challenge, so it can still be answered
+            assert!(verifier.verify_proof(proof_for(&challenge, DATA.to_vec())).await.unwrap());
+        }
+    }
+
+    #[cfg(feature = "ipfs")]
+    mod ipfs_gateways {
+        use super::*;
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        async fn gateway(response: ResponseTemplate) -> (MockServer, String) {
+            let server = MockServer::start().await;
+            Mock::given(method("GET")).respond_with(response).mount(&server).await;
+            let base = format!("{}/ipfs", server.uri());
+            (server, base)
+        }
+
+        #[tokio::test]
+        async fn test_fast_gateway_wins_race_against_hanging_one() {
+            let (_hanging, hanging_url) = gateway(ResponseTemplate::new(200).set_delay(Duration::from_secs(30))).await;
+            let (_fast, fast_url) = gateway(ResponseTemplate::new(200).set_body_bytes(b"sample".to_vec())).await;
+            let verifier = StorageVerifier::new()
+                .with_gateways([&hanging_url, &fast_url])
+                .with_ipfs_deadline(Duration::from_secs(5));
+
+            let started = std::time::Instant::now();
+            let data = verifier.fetch_ipfs_sample("bafytestcid", 64).await.unwrap();
+            assert_eq!(data, b"sample");
+            assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+
+            let gateways = verifier.get_metrics().await.gateways;
+            let hanging = &gateways[&hanging_url];
+            assert_eq!((hanging.attempts, hanging.successes, hanging.failures), (1, 0, 0));
+            let fast = &gateways[&fast_url];
+            assert_eq!((fast.attempts, fast.successes, fast.failures), (1, 1, 0));
+            assert!(fast.average_latency_ms > 0.0);
+        }
+
+        #[tokio::test]
+        async fn test_falls_back_past_failed_racers() {
+            let (_bad_a, bad_a) = gateway(ResponseTemplate::new(502)).await;
+            let (_bad_b, bad_b) = gateway(ResponseTemplate::new(404)).await;
+            let (_good, good) = gateway(ResponseTemplate::new(200).set_body_bytes(b"third".to_vec())).await;
+            let verifier = StorageVerifier::new().with_gateways([&bad_a, &bad_b, &good]);
+
+            assert_eq!(verifier.fetch_ipfs_sample("bafytestcid", 64).await.unwrap(), b"third");
+            let gateways = verifier.get_metrics().await.gateways;
+            assert_eq!(gateways[&bad_a].failures, 1);
+            assert_eq!(gateways[&bad_b].failures, 1);
+            assert_eq!(gateways[&good].successes, 1);
+        }
+
+        #[tokio::test]
+        async fn test_deadline_bounds_total_fetch_time() {
+            let (_a, slow_a) = gateway(ResponseTemplate::new(200).set_delay(Duration::from_secs(30))).await;
+            let (_b, slow_b) = gateway(ResponseTemplate::new(200).set_delay(Duration::from_secs(30))).await;
+            let verifier = StorageVerifier::new()
+                .with_gateways([slow_a, slow_b])
+                .with_ipfs_deadline(Duration::from_millis(200));
+
+            assert!(matches!(
+                verifier.fetch_ipfs_sample("bafytestcid", 64).await,
+                Err(StorageVerificationError::TimeoutExceeded { timeout_ms: 200 })
+            ));
+        }
+    }
+
+    #[cfg(feature = "ipfs")]
+    mod content_fetchers {
+        use super::*;
+        use crate::storage_fetcher::{ArweaveFetcher, ArweaveFetcherConfig};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        const CHUNK: usize = 16;
+
+        async fn arweave_verifier(server: &MockServer, body: &[u8]) -> StorageVerifier {
+            let verifier = StorageVerifier::new().with_fetcher(Arc::new(ArweaveFetcher::new(ArweaveFetcherConfig {
+                gateway: server.uri(),
+                timeout: Duration::from_secs(5),
+            })));
+            let leaves = body.chunks(CHUNK).map(merkle::hash_leaf).collect();
+            verifier.register_file_commitments("arweave-tx", CHUNK as u32, leaves).await.unwrap();
+            verifier
+        }
+
+        #[tokio::test]
+        async fn test_verify_content_fetches_challenged_chunks_from_arweave() {
+            let body: Vec<u8> = (0..CHUNK * 4 + 5).map(|i| i as u8).collect();
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/arweave-tx"))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(body.clone()))
+                .mount(&server)
+                .await;
+            let verifier = arweave_verifier(&server, &body).await;
+
+            for _ in 0..10 {
+                assert!(verifier.verify_content("Arweave", "arweave-tx", "provider").await.unwrap());
+            }
+            let challenges = verifier.list_challenges("provider", true).await;
+            assert!(challenges.iter().all(|c| c.protocol.as_deref() == Some("arweave")));
+        }
+
+        #[tokio::test]
+        async fn test_verify_content_reports_unsupported_protocol_and_gateway_failure() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET")).respond_with(ResponseTemplate::new(500)).mount(&server).await;
+            let verifier = arweave_verifier(&server, &[7u8; CHUNK * 2]).await;
+
+            assert!(!verifier.supports_protocol("bitcoin"));
+            assert!(matches!(
+                verifier.verify_content("bitcoin", "arweave-tx", "provider").await,
+                Err(StorageVerificationError::InvalidInput { .. })
+            ));
+            assert!(matches!(
+                verifier.verify_content("arweave", "arweave-tx", "provider").await,
+                Err(StorageVerificationError::NetworkError { .. })
+            ));
+        }
+    }
+
+    #[cfg(feature = "ipfs")]
+    mod streaming_ingest {
+        use super::*;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        const CID: &str = "bafybigfile";
+        const CHUNK: usize = 16;
+        // Spans two leaf batches and ends in a short chunk
+        const LEAVES: usize = LEAF_BATCH_SIZE + 4;
+
+        async fn serve(body: Vec<u8>) -> (MockServer, StorageVerifier) {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path(format!("/ipfs/{}", CID)))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(body))
+                .mount(&server)
+                .await;
+            let verifier = StorageVerifier::new().with_gateways([format!("{}/ipfs", server.uri())]);
+            (server, verifier)
+        }
+
+        #[tokio::test]
+        async fn test_streams_every_chunk_and_late_chunk_proof_verifies() {
+            let body: Vec<u8> = (0..CHUNK * (LEAVES - 1) + 5).map(|i| (i % 251) as u8).collect();
+            let (_server, verifier) = serve(body.clone()).await;
+
+            let reports = std::sync::Mutex::new(Vec::new());
+            let report: &(dyn Fn(IngestProgress) + Send + Sync) = &|p| reports.lock().unwrap().push(p);
+            verifier.ingest_ipfs_with_progress(CID, CHUNK, Some(report)).await.unwrap();
+
+            let reports = reports.into_inner().unwrap();
+            assert_eq!(reports.len(), LEAVES);
+            assert_eq!(
+                reports[LEAVES - 1],
+                IngestProgress {
+                    bytes_read: body.len() as u64,
+                    chunks_hashed: LEAVES as u64,
+                    total_bytes: Some(body.len() as u64),
+                }
+            );
+            let (_, chunk_size, total_chunks) = verifier.commitments.get_meta(DEFAULT_TENANT, CID).await.unwrap().unwrap();
+            assert_eq!((chunk_size, total_chunks), (CHUNK as u32, LEAVES as u64));
+
+            // Point a challenge at the short final chunk, which sits in the second leaf batch
+            let late = (LEAVES - 1) as u64;
+            let mut challenge = verifier.generate_challenge(CID, "provider").await.unwrap();
+            challenge.chunk_index = late;
+            challenge.chunk_indices = vec![late];
+            challenge.sample_offset = late * CHUNK as u64;
+            verifier
+                .challenges
+                .lock()
+                .await
+                .insert(challenge.id.clone(), ChallengeEntry { challenge: challenge.clone(), answered: false });
+
+            let proof = StorageProof {
+                challenge_id: challenge.id.clone(),
+                file_id: CID.to_string(),
+                provider: "provider".to_string(),
+                timestamp: challenge.timestamp,
+                proof_data: body[late as usize * CHUNK..].to_vec(),
+                merkle_proof: None,
+                signature: None,
+                extra_proof_data: Vec::new(),
+            };
+            assert!(verifier.verify_proof(proof).await.unwrap());
+        }
+
+        #[tokio::test]
+        async fn test_rejects_files_over_the_size_limit() {
+            let (_server, verifier) = serve(vec![1u8; 4 * CHUNK]).await;
+            let verifier = verifier.with_max_file_size(3 * CHUNK as u64);
+
+            assert!(matches!(
+                verifier.ingest_ipfs_and_register(CID, CHUNK).await,
+                Err(StorageVerificationError::FileTooLarge { limit }) if limit == 3 * CHUNK as u64
+            ));
+            assert!(verifier.commitments.get_meta(DEFAULT_TENANT, CID).await.unwrap().is_none());
+        }
+    }
 }
@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Streaming read/write access to SecureBuffer
+
+use std::io;
+use std::sync::atomic::Ordering;
+
+use crate::audit::AuditEventKind;
+use crate::SecureBuffer;
+
+/// What a `SecureBufferWriter` does when a chunk does not fit the capacity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamOverflow {
+    /// Grow through `SecureBuffer::resize` using the buffer's growth factor
+    Grow,
+    /// Reject the chunk, keeping everything written before it
+    Fail,
+}
+
+/// `std::io::Write` adapter that appends chunks directly into the buffer's
+/// locked region, so large secrets never need to be staged in a plain `Vec`.
+///
+/// The tamper checksum and a single Write audit event covering the whole
+/// stream are produced by `finish()`, or on drop if `finish()` was not called.
+pub struct SecureBufferWriter<'a> {
+    buffer: &'a mut SecureBuffer,
+    policy: StreamOverflow,
+    written: usize,
+    finished: bool,
+}
+
+impl<'a> SecureBufferWriter<'a> {
+    pub(crate) fn new(buffer: &'a mut SecureBuffer, policy: StreamOverflow) -> Self {
+        Self {
+            buffer,
+            policy,
+            written: 0,
+            finished: false,
+        }
+    }
+
+    /// Bytes appended by this writer so far
+    pub fn bytes_written(&self) -> usize {
+        self.written
+    }
+
+    /// Complete the stream and return the number of bytes appended
+    pub fn finish(mut self) -> usize {
+        self.finalize();
+        self.written
+    }
+
+    fn finalize(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        self.buffer.refresh_tamper_checksum();
+        self.buffer.audit(AuditEventKind::Write, self.written);
+    }
+}
+
+impl io::Write for SecureBufferWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if !self.buffer.is_valid.load(Ordering::SeqCst) {
+            return Err(io::Error::other("Buffer is not valid"));
+        }
+        if data.is_empty() {
+            return Ok(0);
+        }
+
+        let required = self.buffer.length.checked_add(data.len())
+            .ok_or_else(|| io::Error::other("Data exceeds maximum buffer size"))?;
+        if required > self.buffer.capacity {
+            match self.policy {
+                StreamOverflow::Fail => {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero, "Data exceeds buffer capacity"));
+                }
+                StreamOverflow::Grow => {
+                    let new_capacity = self.buffer.grown_capacity(required);
+                    self.buffer.resize(new_capacity).map_err(io::Error::other)?;
+                }
+            }
+        }
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.buffer.data.add(self.buffer.length), data.len());
+        }
+        // Length advances per chunk so a resize mid-stream keeps everything written so far
+        self.buffer.length = required;
+        self.written += data.len();
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for SecureBufferWriter<'_> {
+    fn drop(&mut self) {
+        self.finalize();
+    }
+}
+
+/// `std::io::Read` adapter over the buffer's valid region, reading in place.
+/// One Read audit event with the total bytes consumed is recorded on drop.
+pub struct SecureBufferReader<'a> {
+    buffer: &'a SecureBuffer,
+    pos: usize,
+}
+
+impl<'a> SecureBufferReader<'a> {
+    pub(crate) fn new(buffer: &'a SecureBuffer) -> Self {
+        Self { buffer, pos: 0 }
+    }
+
+    /// Bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.buffer.content().len() - self.pos
+    }
+}
+
+impl io::Read for SecureBufferReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let rest = &self.buffer.content()[self.pos..];
+        let n = std::cmp::min(buf.len(), rest.len());
+        buf[..n].copy_from_slice(&rest[..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Drop for SecureBufferReader<'_> {
+    fn drop(&mut self) {
+        if self.pos > 0 {
+            self.buffer.audit(AuditEventKind::Read, self.pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+    use std::io::{Read, Write};
+
+    fn events_of(buffer: &SecureBuffer, kind: AuditEventKind) -> Vec<usize> {
+        buffer
+            .audit_events()
+            .iter()
+            .filter(|e| e.kind == kind)
+            .map(|e| e.bytes_affected)
+            .collect()
+    }
+
+    #[test]
+    fn test_io_copy_into_growing_writer() {
+        let payload: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let mut buffer = SecureBuffer::new(4096).unwrap();
+        buffer.enable_tamper_detection().unwrap();
+        let initially_locked = buffer.is_locked();
+        crate::test_hooks::take_releases();
+
+        let mut writer = buffer.begin_stream(StreamOverflow::Grow).unwrap();
+        let copied = io::copy(&mut &payload[..], &mut writer).unwrap();
+        assert_eq!(copied as usize, payload.len());
+        assert_eq!(writer.finish(), payload.len());
+
+        assert!(buffer.ct_eq_slice(&payload));
+        assert!(!buffer.is_tampered());
+        assert_eq!(events_of(&buffer, AuditEventKind::Write), vec![payload.len()]);
+
+        // Chunks only ever lived in buffer regions, and each outgrown region was
+        // zeroized before being freed
+        let releases = crate::test_hooks::take_releases();
+        assert!(!releases.is_empty());
+        assert!(releases.iter().all(|&(_, zeroed)| zeroed));
+        // Where the platform allows mlock, every region that held chunks was locked
+        if initially_locked {
+            assert!(buffer.is_locked());
+            assert!(events_of(&buffer, AuditEventKind::LockFailed).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_fail_policy_keeps_earlier_chunks() {
+        let mut buffer = SecureBuffer::new(16).unwrap();
+        buffer.write(b"key:").unwrap();
+        {
+            let mut writer = buffer.begin_stream(StreamOverflow::Fail).unwrap();
+            writer.write_all(b"0123456789").unwrap();
+            let err = writer.write(b"overflow").unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+            assert_eq!(writer.bytes_written(), 10);
+            // Dropped without finish(): still finalized
+        }
+        assert_eq!(buffer.capacity(), 16);
+        assert_eq!(buffer.as_slice().unwrap(), b"key:0123456789");
+        assert_eq!(events_of(&buffer, AuditEventKind::Write), vec![4, 10]);
+    }
+
+    #[test]
+    fn test_reader_feeds_hasher_in_place() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7) as u8).collect();
+        let mut buffer = SecureBuffer::new(data.len()).unwrap();
+        buffer.write(&data).unwrap();
+
+        let mut hasher = Sha256::new();
+        {
+            let mut reader = buffer.stream_reader().unwrap();
+            assert_eq!(reader.remaining(), data.len());
+            io::copy(&mut reader, &mut hasher).unwrap();
+            assert_eq!(reader.remaining(), 0);
+            assert_eq!(reader.read(&mut [0u8; 8]).unwrap(), 0);
+        }
+        assert_eq!(hasher.finalize(), Sha256::digest(&data));
+        assert_eq!(events_of(&buffer, AuditEventKind::Read), vec![data.len()]);
+    }
+
+    #[test]
+    fn test_streams_reject_destroyed_buffer() {
+        let mut buffer = SecureBuffer::new(8).unwrap();
+        buffer.destroy();
+        assert!(buffer.begin_stream(StreamOverflow::Grow).is_err());
+        assert!(buffer.stream_reader().is_err());
+    }
+}
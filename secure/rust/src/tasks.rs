@@ -0,0 +1,317 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Supervised background tasks
+
+//! Restarts background tasks that panic or fail and reports their health.
+//!
+//! Each run gets a `Heartbeat` to beat once per loop iteration. A task that keeps failing
+//! within the longest backoff is reported as crash looping; for critical tasks that makes
+//! the server report not ready.
+
+use chrono::{DateTime, Utc};
+use log::error;
+use prometheus::CounterVec;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+// Consecutive failed runs after which a supervised task counts as crash looping
+const CRASH_LOOP_FAILURES: u32 = 3;
+
+/// What a supervised run ends with: Ok stops the task, Err restarts it like a panic would
+pub type TaskResult = Result<(), String>;
+
+/// Handed to every run of a supervised task; loops beat once per iteration
+#[derive(Clone, Default)]
+pub struct Heartbeat(Arc<AtomicI64>);
+
+impl Heartbeat {
+    pub fn beat(&self) {
+        self.0.store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    pub fn last(&self) -> Option<DateTime<Utc>> {
+        use chrono::TimeZone;
+        match self.0.load(Ordering::Relaxed) {
+            0 => None,
+            millis => Utc.timestamp_millis_opt(millis).single(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    // Waiting out the backoff after a panic or error
+    Restarting,
+    // Returned Ok, or stopped for shutdown
+    Finished,
+}
+
+/// One entry of GET /admin/tasks
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskHealth {
+    pub name: String,
+    pub critical: bool,
+    pub state: TaskState,
+    pub restarts: u64,
+    pub crash_looping: bool,
+    pub last_error: Option<String>,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+}
+
+struct TaskRecord {
+    critical: bool,
+    state: TaskState,
+    restarts: u64,
+    // Reset once a run stays up for a full max backoff
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    running_since: Instant,
+    heartbeat: Heartbeat,
+}
+
+/// Restarts background tasks that panic or return Err, backing off exponentially
+/// up to `max_backoff` between runs, and keeps their health for /admin/tasks and /ready
+pub struct TaskSupervisor {
+    tasks: Mutex<BTreeMap<String, TaskRecord>>,
+    restarts: CounterVec,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    // No restarts once cancelled
+    shutdown: CancellationToken,
+}
+
+impl TaskSupervisor {
+    pub fn new(restarts: CounterVec, initial_backoff: Duration, max_backoff: Duration, shutdown: CancellationToken) -> Self {
+        TaskSupervisor { tasks: Mutex::new(BTreeMap::new()), restarts, initial_backoff, max_backoff, shutdown }
+    }
+
+    /// Run `factory`'s future, and a fresh one from it after every panic or Err
+    pub fn spawn_supervised<F, Fut>(self: &Arc<Self>, name: &str, factory: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(Heartbeat) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = TaskResult> + Send + 'static,
+    {
+        self.spawn(name, false, factory)
+    }
+
+    /// As `spawn_supervised`, but /ready reports not ready while the task is crash looping
+    pub fn spawn_critical<F, Fut>(self: &Arc<Self>, name: &str, factory: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(Heartbeat) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = TaskResult> + Send + 'static,
+    {
+        self.spawn(name, true, factory)
+    }
+
+    fn spawn<F, Fut>(self: &Arc<Self>, name: &str, critical: bool, factory: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(Heartbeat) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = TaskResult> + Send + 'static,
+    {
+        let heartbeat = Heartbeat::default();
+        self.tasks.lock().unwrap().insert(
+            name.to_string(),
+            TaskRecord {
+                critical,
+                state: TaskState::Running,
+                restarts: 0,
+                consecutive_failures: 0,
+                last_error: None,
+                running_since: Instant::now(),
+                heartbeat: heartbeat.clone(),
+            },
+        );
+        let supervisor = self.clone();
+        let name = name.to_string();
+        tokio::task::spawn(async move {
+            let mut backoff = supervisor.initial_backoff;
+            loop {
+                let started = Instant::now();
+                // A run of its own, so a panic surfaces here as a JoinError
+                let outcome = match tokio::task::spawn(factory(heartbeat.clone())).await {
+                    Ok(result) => result,
+                    Err(e) if e.is_panic() => Err(format!("panicked: {}", panic_message(e.into_panic()))),
+                    Err(e) => Err(e.to_string()),
+                };
+                let failure = match outcome {
+                    Ok(()) => None,
+                    Err(_) if supervisor.shutdown.is_cancelled() => None,
+                    Err(e) => Some(e),
+                };
+                let Some(failure) = failure else {
+                    supervisor.update(&name, |task| task.state = TaskState::Finished);
+                    return;
+                };
+
+                let recovered = started.elapsed() >= supervisor.max_backoff;
+                if recovered {
+                    backoff = supervisor.initial_backoff;
+                }
+                error!("Task {} failed, restarting in {:?}: {}", name, backoff, failure);
+                supervisor.restarts.with_label_values(&[&name]).inc();
+                supervisor.update(&name, |task| {
+                    task.state = TaskState::Restarting;
+                    task.restarts += 1;
+                    task.consecutive_failures = if recovered { 1 } else { task.consecutive_failures + 1 };
+                    task.last_error = Some(failure);
+                });
+
+                tokio::select! {
+                    _ = supervisor.shutdown.cancelled() => {
+                        supervisor.update(&name, |task| task.state = TaskState::Finished);
+                        return;
+                    }
+                    _ = tokio::time::sleep(backoff) => {}
+                }
+                backoff = (backoff * 2).min(supervisor.max_backoff);
+                supervisor.update(&name, |task| {
+                    task.state = TaskState::Running;
+                    task.running_since = Instant::now();
+                });
+            }
+        })
+    }
+
+    fn update(&self, name: &str, apply: impl FnOnce(&mut TaskRecord)) {
+        if let Some(task) = self.tasks.lock().unwrap().get_mut(name) {
+            apply(task);
+        }
+    }
+
+    fn is_crash_looping(&self, task: &TaskRecord) -> bool {
+        // A run that has outlasted the longest backoff is no longer part of the loop
+        let settled = task.state == TaskState::Running && task.running_since.elapsed() >= self.max_backoff;
+        task.consecutive_failures >= CRASH_LOOP_FAILURES && !settled
+    }
+
+    /// Every supervised task, by name
+    pub fn get_task_health(&self) -> Vec<TaskHealth> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, task)| TaskHealth {
+                name: name.clone(),
+                critical: task.critical,
+                state: task.state,
+                restarts: task.restarts,
+                crash_looping: self.is_crash_looping(task),
+                last_error: task.last_error.clone(),
+                last_heartbeat: task.heartbeat.last(),
+            })
+            .collect()
+    }
+
+    /// Critical tasks that keep failing, which make /ready report not ready
+    pub fn crash_looping_critical(&self) -> Vec<String> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, task)| task.critical && self.is_crash_looping(task))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    fn supervisor(initial_backoff: Duration) -> Arc<TaskSupervisor> {
+        let restarts = CounterVec::new(prometheus::Opts::new("task_restarts_total", "test"), &["task"]).unwrap();
+        Arc::new(TaskSupervisor::new(
+            restarts,
+            initial_backoff,
+            Duration::from_secs(60),
+            CancellationToken::new(),
+        ))
+    }
+
+    fn health(supervisor: &TaskSupervisor, name: &str) -> TaskHealth {
+        supervisor.get_task_health().into_iter().find(|task| task.name == name).unwrap()
+    }
+
+    // Panics on its first two runs, then beats until shutdown
+    async fn flaky(run: u32, heartbeat: Heartbeat) -> TaskResult {
+        if run < 2 {
+            panic!("boom {}", run);
+        }
+        loop {
+            heartbeat.beat();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    async fn wait_for(mut done: impl FnMut() -> bool) {
+        for _ in 0..500 {
+            if done() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("condition not reached");
+    }
+
+    #[tokio::test]
+    async fn test_task_that_panics_twice_is_restarted_until_healthy() {
+        let supervisor = supervisor(Duration::from_millis(1));
+        let runs = Arc::new(AtomicU32::new(0));
+        let counted = runs.clone();
+        supervisor.spawn_critical("flaky", move |heartbeat| flaky(counted.fetch_add(1, Ordering::SeqCst), heartbeat));
+
+        wait_for(|| health(&supervisor, "flaky").last_heartbeat.is_some()).await;
+        let task = health(&supervisor, "flaky");
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(task.restarts, 2);
+        assert_eq!(task.state, TaskState::Running);
+        assert!(!task.crash_looping);
+        assert!(task.critical);
+        assert_eq!(task.last_error.as_deref(), Some("panicked: boom 1"));
+        assert!(supervisor.crash_looping_critical().is_empty());
+        assert_eq!(supervisor.restarts.with_label_values(&["flaky"]).get(), 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_task_returning_ok_finishes_without_restart() {
+        let supervisor = supervisor(Duration::from_millis(1));
+        let handle = supervisor.spawn_supervised("once", |_heartbeat| async { Ok(()) });
+        handle.await.unwrap();
+        let task = health(&supervisor, "once");
+        assert_eq!(task.state, TaskState::Finished);
+        assert_eq!(task.restarts, 0);
+        assert_eq!(task.last_error, None);
+    }
+
+    #[tokio::test]
+    async fn test_errors_restart_and_shutdown_stops_the_loop() {
+        let supervisor = supervisor(Duration::from_millis(1));
+        let handle = supervisor.spawn_supervised("failing", |_heartbeat| async { Err("upstream gone".to_string()) });
+        wait_for(|| health(&supervisor, "failing").restarts >= 3).await;
+        let task = health(&supervisor, "failing");
+        assert!(task.crash_looping);
+        assert_eq!(task.last_error.as_deref(), Some("upstream gone"));
+        // Not critical, so readiness is unaffected
+        assert!(supervisor.crash_looping_critical().is_empty());
+
+        supervisor.shutdown.cancel();
+        handle.await.unwrap();
+        assert_eq!(health(&supervisor, "failing").state, TaskState::Finished);
+    }
+}
@@ -0,0 +1,433 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Per-key usage accounting
+
+//! Billable request counts per API key, chain and method for each UTC day.
+//!
+//! `UsageRecorder` counts in memory and a background task flushes the counters to a
+//! `UsageStore` in batches, so serving a request never waits on the database.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use log::{error, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::clock::{Clock, SystemClock};
+
+/// Requests and response bytes for one key on one UTC day, per chain and method
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageRow {
+    pub key_hash: String,
+    pub day: NaiveDate,
+    pub chain: String,
+    pub method: String,
+    pub requests: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UsageKey {
+    key_hash: String,
+    day: NaiveDate,
+    chain: String,
+    method: String,
+}
+
+impl UsageKey {
+    fn into_row(self, (requests, bytes): (u64, u64)) -> UsageRow {
+        UsageRow { key_hash: self.key_hash, day: self.day, chain: self.chain, method: self.method, requests, bytes }
+    }
+}
+
+impl UsageRow {
+    fn key(&self) -> UsageKey {
+        UsageKey {
+            key_hash: self.key_hash.clone(),
+            day: self.day,
+            chain: self.chain.clone(),
+            method: self.method.clone(),
+        }
+    }
+}
+
+pub trait UsageStore: Send + Sync {
+    /// Add each row's counts onto the stored row with the same key, day, chain and method
+    fn upsert(&self, rows: &[UsageRow]) -> Result<(), String>;
+    /// Stored rows for `key_hash` with `from <= day < until`
+    fn rows(&self, key_hash: &str, from: NaiveDate, until: NaiveDate) -> Result<Vec<UsageRow>, String>;
+}
+
+#[derive(Default)]
+pub struct MemoryUsageStore {
+    rows: Mutex<HashMap<UsageKey, (u64, u64)>>,
+}
+
+impl UsageStore for MemoryUsageStore {
+    fn upsert(&self, rows: &[UsageRow]) -> Result<(), String> {
+        let mut stored = self.rows.lock().map_err(|_| "Usage store lock poisoned".to_string())?;
+        for row in rows {
+            let counts = stored.entry(row.key()).or_default();
+            counts.0 += row.requests;
+            counts.1 += row.bytes;
+        }
+        Ok(())
+    }
+
+    fn rows(&self, key_hash: &str, from: NaiveDate, until: NaiveDate) -> Result<Vec<UsageRow>, String> {
+        let stored = self.rows.lock().map_err(|_| "Usage store lock poisoned".to_string())?;
+        let mut rows: Vec<UsageRow> = stored
+            .iter()
+            .filter(|(key, _)| key.key_hash == key_hash && from <= key.day && key.day < until)
+            .map(|(key, counts)| key.clone().into_row(*counts))
+            .collect();
+        rows.sort_by(|a, b| (a.day, &a.chain, &a.method).cmp(&(b.day, &b.chain, &b.method)));
+        Ok(rows)
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+pub struct SqliteUsageStore {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "rusqlite")]
+impl SqliteUsageStore {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let path = path.strip_prefix("sqlite://").unwrap_or(path);
+        let conn = rusqlite::Connection::open(path).map_err(|e| format!("Failed to open usage store {}: {}", path, e))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS api_usage (
+                key_hash TEXT NOT NULL,
+                day TEXT NOT NULL,
+                chain TEXT NOT NULL,
+                method TEXT NOT NULL,
+                requests INTEGER NOT NULL,
+                bytes INTEGER NOT NULL,
+                PRIMARY KEY (key_hash, day, chain, method)
+            )",
+        )
+        .map_err(|e| format!("Failed to initialise usage store: {}", e))?;
+        Ok(SqliteUsageStore { conn: Mutex::new(conn) })
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl UsageStore for SqliteUsageStore {
+    fn upsert(&self, rows: &[UsageRow]) -> Result<(), String> {
+        let mut conn = self.conn.lock().map_err(|_| "Usage store lock poisoned".to_string())?;
+        let tx = conn.transaction().map_err(|e| format!("Failed to store usage: {}", e))?;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT INTO api_usage (key_hash, day, chain, method, requests, bytes)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                     ON CONFLICT (key_hash, day, chain, method) DO UPDATE SET
+                        requests = requests + excluded.requests,
+                        bytes = bytes + excluded.bytes",
+                )
+                .map_err(|e| format!("Failed to store usage: {}", e))?;
+            for row in rows {
+                stmt.execute(rusqlite::params![
+                    row.key_hash,
+                    row.day,
+                    row.chain,
+                    row.method,
+                    row.requests as i64,
+                    row.bytes as i64,
+                ])
+                .map_err(|e| format!("Failed to store usage: {}", e))?;
+            }
+        }
+        // All or nothing, so a failed batch can be retried without double counting
+        tx.commit().map_err(|e| format!("Failed to store usage: {}", e))
+    }
+
+    fn rows(&self, key_hash: &str, from: NaiveDate, until: NaiveDate) -> Result<Vec<UsageRow>, String> {
+        let conn = self.conn.lock().map_err(|_| "Usage store lock poisoned".to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT key_hash, day, chain, method, requests, bytes FROM api_usage
+                 WHERE key_hash = ?1 AND day >= ?2 AND day < ?3 ORDER BY day, chain, method",
+            )
+            .map_err(|e| format!("Failed to load usage: {}", e))?;
+        let rows = stmt
+            .query_map(rusqlite::params![key_hash, from, until], |row| {
+                Ok(UsageRow {
+                    key_hash: row.get(0)?,
+                    day: row.get(1)?,
+                    chain: row.get(2)?,
+                    method: row.get(3)?,
+                    requests: row.get::<_, i64>(4)? as u64,
+                    bytes: row.get::<_, i64>(5)? as u64,
+                })
+            })
+            .map_err(|e| format!("Failed to load usage: {}", e))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| format!("Failed to load usage: {}", e))
+    }
+}
+
+/// First retry after a failed usage flush; doubles up to USAGE_RETRY_MAX
+const USAGE_RETRY_INITIAL: Duration = Duration::from_secs(1);
+const USAGE_RETRY_MAX: Duration = Duration::from_secs(5 * 60);
+
+/// Counts billable requests per API key. Counters live in memory until the next
+/// flush; a failed flush keeps them, and only rows past `max_pending` are dropped.
+pub struct UsageRecorder {
+    store: Arc<dyn UsageStore>,
+    pending: Mutex<HashMap<UsageKey, (u64, u64)>>,
+    max_pending: usize,
+    // Requests that found the buffer full
+    dropped: AtomicU64,
+    // Decides the day a request is counted on
+    clock: Arc<dyn Clock>,
+}
+
+impl UsageRecorder {
+    pub fn new(store: Arc<dyn UsageStore>, max_pending: usize) -> Self {
+        UsageRecorder {
+            store,
+            pending: Mutex::new(HashMap::new()),
+            max_pending,
+            dropped: AtomicU64::new(0),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The UTC day requests are currently counted on
+    pub fn today(&self) -> NaiveDate {
+        DateTime::from_timestamp(self.clock.now_unix() as i64, 0).unwrap_or_else(Utc::now).date_naive()
+    }
+
+    /// Add counts for `key`, unless that would grow the buffer past its cap
+    fn add(&self, pending: &mut HashMap<UsageKey, (u64, u64)>, key: UsageKey, requests: u64, bytes: u64) {
+        if pending.len() >= self.max_pending && !pending.contains_key(&key) {
+            let before = self.dropped.fetch_add(requests, Ordering::Relaxed);
+            if before == 0 || (before + requests).is_power_of_two() {
+                warn!("Usage buffer full ({} rows), {} requests not counted so far", self.max_pending, before + requests);
+            }
+            return;
+        }
+        let counts = pending.entry(key).or_default();
+        counts.0 += requests;
+        counts.1 += bytes;
+    }
+
+    /// Count one served request of `bytes` response bytes
+    pub fn record(&self, key_hash: &str, chain: &str, method: &str, bytes: u64) {
+        let key = UsageKey {
+            key_hash: key_hash.to_string(),
+            day: self.today(),
+            chain: chain.to_string(),
+            method: method.to_string(),
+        };
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        self.add(&mut pending, key, 1, bytes);
+    }
+
+    /// Write buffered counts as one batch, returning how many rows were written.
+    /// On failure the rows go back into the buffer for the next attempt.
+    pub async fn flush(&self) -> Result<usize, String> {
+        let batch = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        if batch.is_empty() {
+            return Ok(0);
+        }
+        let rows: Vec<UsageRow> = batch.into_iter().map(|(key, counts)| key.into_row(counts)).collect();
+        let store = self.store.clone();
+        let (rows, written) = tokio::task::spawn_blocking(move || {
+            let written = store.upsert(&rows);
+            (rows, written)
+        })
+        .await
+        .map_err(|e| format!("Usage flush task failed: {}", e))?;
+        match written {
+            Ok(()) => Ok(rows.len()),
+            Err(e) => {
+                let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+                for row in rows {
+                    let (requests, bytes) = (row.requests, row.bytes);
+                    self.add(&mut pending, row.key(), requests, bytes);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Stored rows for `key_hash` in `from..until`, plus what is still buffered
+    pub async fn usage(&self, key_hash: &str, from: NaiveDate, until: NaiveDate) -> Result<Vec<UsageRow>, String> {
+        let store = self.store.clone();
+        let owned = key_hash.to_string();
+        let mut rows = tokio::task::spawn_blocking(move || store.rows(&owned, from, until))
+            .await
+            .map_err(|e| format!("Usage query task failed: {}", e))??;
+        let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        for (key, (requests, bytes)) in pending.iter() {
+            if key.key_hash != key_hash || key.day < from || key.day >= until {
+                continue;
+            }
+            match rows.iter_mut().find(|row| row.key() == *key) {
+                Some(row) => {
+                    row.requests += requests;
+                    row.bytes += bytes;
+                }
+                None => rows.push(key.clone().into_row((*requests, *bytes))),
+            }
+        }
+        Ok(rows)
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Flush every `interval` until shutdown, then once more. Failed flushes are
+    /// retried sooner, backing off from USAGE_RETRY_INITIAL.
+    pub fn spawn_flusher(self: &Arc<Self>, interval: Duration, token: CancellationToken) -> tokio::task::JoinHandle<()> {
+        let recorder = self.clone();
+        tokio::task::spawn(async move {
+            let mut wait = interval;
+            let mut failures = 0;
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = tokio::time::sleep(wait) => {}
+                }
+                match recorder.flush().await {
+                    Ok(_) => {
+                        failures = 0;
+                        wait = interval;
+                    }
+                    Err(e) => {
+                        wait = (USAGE_RETRY_INITIAL * 2u32.saturating_pow(failures)).min(USAGE_RETRY_MAX);
+                        failures += 1;
+                        warn!("Usage flush failed, retrying in {:?}: {}", wait, e);
+                    }
+                }
+            }
+            if let Err(e) = recorder.flush().await {
+                error!("Final usage flush failed, buffered usage is lost: {}", e);
+            }
+        })
+    }
+}
+
+/// First day of `day`'s month and of the month after
+pub fn month_bounds(day: NaiveDate) -> (NaiveDate, NaiveDate) {
+    use chrono::Datelike;
+    let start = day.with_day(1).unwrap_or(day);
+    let next = match (day.year(), day.month()) {
+        (year, 12) => NaiveDate::from_ymd_opt(year + 1, 1, 1),
+        (year, month) => NaiveDate::from_ymd_opt(year, month + 1, 1),
+    };
+    (start, next.unwrap_or(day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::atomic::AtomicBool;
+
+    #[cfg(feature = "rusqlite")]
+    fn temp_db() -> String {
+        std::env::temp_dir()
+            .join(format!("sprint-usage-{:016x}.db", rand::random::<u64>()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Memory store that fails every write while `failing` is set
+    #[derive(Default)]
+    struct FlakyStore {
+        inner: MemoryUsageStore,
+        failing: AtomicBool,
+    }
+
+    impl UsageStore for FlakyStore {
+        fn upsert(&self, rows: &[UsageRow]) -> Result<(), String> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err("database is down".to_string());
+            }
+            self.inner.upsert(rows)
+        }
+
+        fn rows(&self, key_hash: &str, from: NaiveDate, until: NaiveDate) -> Result<Vec<UsageRow>, String> {
+            self.inner.rows(key_hash, from, until)
+        }
+    }
+
+    #[cfg(feature = "rusqlite")]
+    #[tokio::test]
+    async fn test_rows_aggregate_per_day_across_flushes() {
+        let path = temp_db();
+        let store = Arc::new(SqliteUsageStore::open(&path).unwrap());
+        // 2026-03-31 23:59:00 UTC
+        let clock = Arc::new(ManualClock::new(1_775_001_540));
+        let recorder = UsageRecorder::new(store.clone(), 100).with_clock(clock.clone());
+        assert_eq!(recorder.today(), date(2026, 3, 31));
+
+        for _ in 0..3 {
+            recorder.record("hash-a", "bitcoin", "getblockcount", 100);
+        }
+        recorder.record("hash-a", "ethereum", "eth_chainId", 40);
+        recorder.record("hash-b", "bitcoin", "getblockcount", 100);
+        assert_eq!(recorder.flush().await, Ok(3));
+        // Same key and day again: the stored row is added to, not replaced
+        recorder.record("hash-a", "bitcoin", "getblockcount", 50);
+        recorder.record("hash-a", "bitcoin", "getblockcount", 50);
+        clock.advance(Duration::from_secs(120));
+        assert_eq!(recorder.today(), date(2026, 4, 1));
+        recorder.record("hash-a", "bitcoin", "getblockcount", 70);
+        assert_eq!(recorder.flush().await, Ok(2));
+
+        let march = store.rows("hash-a", date(2026, 3, 1), date(2026, 4, 1)).unwrap();
+        assert_eq!(march.len(), 2);
+        assert_eq!((march[0].chain.as_str(), march[0].requests, march[0].bytes), ("bitcoin", 5, 400));
+        assert_eq!((march[1].chain.as_str(), march[1].requests, march[1].bytes), ("ethereum", 1, 40));
+        let april = store.rows("hash-a", date(2026, 4, 1), date(2026, 5, 1)).unwrap();
+        assert_eq!(april.len(), 1);
+        assert_eq!((april[0].day, april[0].requests, april[0].bytes), (date(2026, 4, 1), 1, 70));
+        assert_eq!(store.rows("hash-b", date(2026, 3, 1), date(2026, 4, 1)).unwrap()[0].requests, 1);
+
+        assert_eq!(month_bounds(date(2026, 12, 9)), (date(2026, 12, 1), date(2027, 1, 1)));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_keeps_rows_up_to_the_cap() {
+        let store = Arc::new(FlakyStore::default());
+        store.failing.store(true, Ordering::SeqCst);
+        let recorder = UsageRecorder::new(store.clone(), 3);
+        for method in ["getblockcount", "getblockhash", "getblock"] {
+            recorder.record("hash-a", "bitcoin", method, 10);
+        }
+        assert!(recorder.flush().await.is_err());
+
+        // Existing rows keep counting; a fourth row does not fit
+        recorder.record("hash-a", "bitcoin", "getblockcount", 10);
+        recorder.record("hash-a", "bitcoin", "getrawtransaction", 10);
+        assert_eq!(recorder.dropped(), 1);
+        assert!(recorder.flush().await.is_err());
+
+        store.failing.store(false, Ordering::SeqCst);
+        assert_eq!(recorder.flush().await, Ok(3));
+        let today = recorder.today();
+        let rows = store.rows("hash-a", today, today.succ_opt().unwrap()).unwrap();
+        let count = |method: &str| rows.iter().find(|row| row.method == method).map(|row| row.requests);
+        assert_eq!(count("getblockcount"), Some(2));
+        assert_eq!(count("getblockhash"), Some(1));
+        assert_eq!(count("getrawtransaction"), None);
+        assert_eq!(recorder.flush().await, Ok(0));
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Prometheus sink for TurboValidator
+
+//! `ValidatorMetrics` backed by Prometheus.
+//!
+//! Install with `TurboValidator::with_metrics` and register into the server's registry.
+//! Every block or transaction the validator checks lands in
+//! `sprint_validation_total{kind, outcome}` and
+//! `sprint_validation_duration_seconds{kind}`, where `outcome` is `ok` or the
+//! `ValidationError` variant name.
+
+use std::time::Duration;
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use turbo_validator::{ValidationKind, ValidationOutcome, ValidatorMetrics};
+
+/// Validation counters and latency, registered by the caller
+#[derive(Clone)]
+pub struct PrometheusValidatorMetrics {
+    total: IntCounterVec,
+    duration: HistogramVec,
+}
+
+impl PrometheusValidatorMetrics {
+    pub fn new() -> prometheus::Result<Self> {
+        Ok(PrometheusValidatorMetrics {
+            total: IntCounterVec::new(
+                Opts::new("sprint_validation_total", "Blocks and transactions validated by TurboValidator"),
+                &["kind", "outcome"],
+            )?,
+            // Header and transaction checks take microseconds; the default buckets start at 5ms
+            duration: HistogramVec::new(
+                HistogramOpts::new("sprint_validation_duration_seconds", "TurboValidator validation duration in seconds")
+                    .buckets(prometheus::exponential_buckets(0.00001, 4.0, 10)?),
+                &["kind"],
+            )?,
+        })
+    }
+
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.total.clone()))?;
+        registry.register(Box::new(self.duration.clone()))?;
+        Ok(())
+    }
+
+    pub fn validations_total(&self, kind: ValidationKind, outcome: &str) -> u64 {
+        self.total.with_label_values(&[kind.label(), outcome]).get()
+    }
+
+    pub fn duration_samples(&self, kind: ValidationKind) -> u64 {
+        self.duration.with_label_values(&[kind.label()]).get_sample_count()
+    }
+}
+
+impl ValidatorMetrics for PrometheusValidatorMetrics {
+    fn record_validation(&self, kind: ValidationKind, outcome: ValidationOutcome, duration: Duration) {
+        self.total.with_label_values(&[kind.label(), outcome.label()]).inc();
+        self.duration.with_label_values(&[kind.label()]).observe(duration.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use turbo_validator::TurboValidator;
+
+    #[test]
+    fn test_validator_reports_into_registry() {
+        let registry = Registry::new();
+        let metrics = Arc::new(PrometheusValidatorMetrics::new().unwrap());
+        metrics.register(&registry).unwrap();
+        assert!(metrics.register(&registry).is_err());
+
+        let validator = TurboValidator::default().with_metrics(metrics.clone());
+        assert!(validator.validate_transaction(&[]).is_err());
+        assert!(validator.validate_block(&[]).is_err());
+        assert!(validator.validate_transaction(&[0u8; 10]).is_ok());
+
+        assert_eq!(metrics.validations_total(ValidationKind::Tx, "InvalidTransaction"), 1);
+        assert_eq!(metrics.validations_total(ValidationKind::Tx, "ok"), 1);
+        assert_eq!(metrics.validations_total(ValidationKind::Block, "InvalidBlock"), 1);
+        assert_eq!(metrics.duration_samples(ValidationKind::Tx), 2);
+
+        let families = registry.gather();
+        let names: Vec<&str> = families.iter().map(|f| f.get_name()).collect();
+        assert!(names.contains(&"sprint_validation_total"));
+        assert!(names.contains(&"sprint_validation_duration_seconds"));
+    }
+}
@@ -14,11 +14,10 @@
 use std::sync::Mutex;
 use tokio::sync::Mutex as AsyncMutex;
 use std::time::{SystemTime, UNIX_EPOCH, Duration, Instant};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use log::{info, error, warn};
 use uuid::Uuid;
-#[cfg(feature = "hardened")]
-use lazy_static::lazy_static;
+use prometheus::{Counter, Encoder, Registry, TextEncoder};
 
 // Hardened Security Imports
 #[cfg(feature = "hardened")]
@@ -33,8 +32,11 @@
 // Re-export our storage verifier
 use crate::storage_verifier::{
     StorageVerifier, RateLimitConfig, StorageChallenge, StorageProof,
-    StorageVerificationError
+    StorageVerificationError, VerificationOutcome, ProviderReputation, MAX_DIFFICULTY
 };
+use crate::clock::{Clock, SystemClock};
+use crate::merkle::MerkleProof;
+use crate::webhooks::{WebhookConfig, WebhookDispatcher, WebhookEvent};
 
 // --- Request/Response Types ---
 #[derive(Serialize, Deserialize)]
@@ -52,6 +54,185 @@ pub struct VerifyResponse {
     pub signature: String,
     pub challenge_id: String,
     pub verification_score: f64,
+    /// Per-criterion scores from the verifier
+    pub score_components: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateChallengeRequest {
+    pub file_id: String,
+    pub provider: String,
+}
+
+/// A challenge as handed to the provider, who must answer it with `POST /proofs`
+#[derive(Serialize, Deserialize)]
+pub struct ChallengeResponse {
+    pub id: String,
+    pub file_id: String,
+    pub provider: String,
+    pub chunk_index: u64,
+    /// Every chunk the proof must cover; samples are submitted in this order
+    pub chunk_indices: Vec<u64>,
+    pub sample_offset: u64,
+    pub sample_size: u32,
+    pub commitment_alg: String,
+    pub require_signature: bool,
+    pub issued_at: u64,
+    pub expires_at: u64,
+}
+
+impl From<StorageChallenge> for ChallengeResponse {
+    fn from(c: StorageChallenge) -> Self {
+        Self {
+            id: c.id,
+            file_id: c.file_id,
+            provider: c.provider,
+            chunk_index: c.chunk_index,
+            chunk_indices: c.chunk_indices,
+            sample_offset: c.sample_offset,
+            sample_size: c.sample_size,
+            commitment_alg: c.commitment_alg,
+            require_signature: c.require_signature,
+            issued_at: c.timestamp,
+            expires_at: c.expiry,
+        }
+    }
+}
+
+/// One Merkle proof level: sibling hash (hex) and whether it is the left input
+#[derive(Serialize, Deserialize)]
+pub struct MerkleStep {
+    pub hash: String,
+    pub is_left: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SubmitProofRequest {
+    pub challenge_id: String,
+    pub file_id: String,
+    pub provider: String,
+    /// Unix seconds; part of the signed message
+    pub timestamp: u64,
+    /// Base64 sample of chunk `chunk_indices[0]`
+    pub proof_data: String,
+    /// Base64 samples of `chunk_indices[1..]`, in order
+    #[serde(default)]
+    pub extra_proof_data: Vec<String>,
+    /// Sibling hashes from the leaf upwards, for merkle_sha256 challenges
+    #[serde(default)]
+    pub merkle_proof: Option<Vec<MerkleStep>>,
+    /// Base64 ed25519 signature over `StorageProof::signing_message`
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProofResponse {
+    pub challenge_id: String,
+    pub verified: bool,
+    pub score: f64,
+    pub score_components: BTreeMap<String, f64>,
+    pub timestamp: u64,
+}
+
+/// Body of `POST /proofs/batch`
+#[derive(Serialize, Deserialize)]
+pub struct SubmitProofBatchRequest {
+    pub proofs: Vec<SubmitProofRequest>,
+}
+
+/// Outcome of one proof in a batch. `verified` and `score` are set when the proof was
+/// checked; `error` and `code` when it was refused, with the status `POST /proofs` would give.
+#[derive(Serialize, Deserialize)]
+pub struct BatchProofResult {
+    pub challenge_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u32>,
+}
+
+/// Results in the order the proofs were submitted
+#[derive(Serialize, Deserialize)]
+pub struct ProofBatchResponse {
+    pub results: Vec<BatchProofResult>,
+    pub verified: usize,
+    pub rejected: usize,
+    pub errors: usize,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RegisterProviderKeyRequest {
+    /// Base64-encoded 32-byte ed25519 public key
+    pub public_key: String,
+}
+
+/// Body of `POST /webhooks`
+#[derive(Serialize, Deserialize)]
+pub struct RegisterWebhookRequest {
+    pub url: String,
+    /// Shared secret the `X-Sprint-Signature` HMAC is keyed with
+    pub secret: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListChallengesQuery {
+    pub provider: String,
+    #[serde(default)]
+    pub include_expired: bool,
+}
+
+/// Query of `GET /providers/reputation`
+#[derive(Serialize, Deserialize)]
+pub struct ReputationQuery {
+    /// Leave out providers scoring below this, 0.0-1.0
+    #[serde(default)]
+    pub min_score: f64,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProviderReputationEntry {
+    pub provider: String,
+    pub score: f64,
+    pub challenges_issued: u64,
+    pub proofs_verified: u64,
+    pub failures: u64,
+    pub average_latency_ms: f64,
+    pub last_seen: Option<u64>,
+}
+
+impl From<ProviderReputation> for ProviderReputationEntry {
+    fn from(r: ProviderReputation) -> Self {
+        Self {
+            provider: r.provider,
+            score: r.score,
+            challenges_issued: r.challenges_issued,
+            proofs_verified: r.proofs_verified,
+            failures: r.failures,
+            average_latency_ms: r.average_latency_ms,
+            last_seen: r.last_seen,
+        }
+    }
+}
+
+/// One page of providers, best score first. `next_offset` is absent on the last page.
+#[derive(Serialize, Deserialize)]
+pub struct ReputationPage {
+    pub providers: Vec<ProviderReputationEntry>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub next_offset: Option<usize>,
+    pub timestamp: u64,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -70,98 +251,141 @@ pub struct Challenge {
     pub expires_at: Instant,
 }
 
-// --- Enhanced Monitoring with Histograms ---
-#[cfg(feature = "hardened")]
-lazy_static::lazy_static! {
-    static ref VERIFICATION_LATENCY_HISTOGRAM: prometheus::HistogramVec = prometheus::register_histogram_vec!(
-        "bitcoin_sprint_verification_latency_seconds",
-        "Verification request latency in seconds",
-        &["provider", "protocol"],
-        vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 2.0, 5.0]
-    ).unwrap();
-
-    static ref REQUEST_RATE_GAUGE: prometheus::GaugeVec = prometheus::register_gauge_vec!(
-        "bitcoin_sprint_request_rate_per_second",
-        "Request rate per second by provider",
-        &["provider"]
-    ).unwrap();
-
-    static ref ERROR_RATE_GAUGE: prometheus::GaugeVec = prometheus::register_gauge_vec!(
-        "bitcoin_sprint_error_rate_percentage",
-        "Error rate percentage by provider",
-        &["provider", "error_type"]
-    ).unwrap();
-
-    static ref CIRCUIT_BREAKER_TRIPS: prometheus::Counter = prometheus::register_counter!(
-        "bitcoin_sprint_circuit_breaker_trips_total",
-        "Total number of circuit breaker trips"
-    ).unwrap();
-}
-
-// --- Rate Limiting Counter (available without hardened feature) ---
-lazy_static::lazy_static! {
-    static ref REQUESTS_RATE_LIMITED: prometheus::Counter = prometheus::register_counter!(
-        "bitcoin_sprint_requests_rate_limited_total",
-        "Total number of rate limited requests"
-    ).unwrap();
+// --- Prometheus Metrics (owned by the server, not the default registry) ---
+struct WebServerMetrics {
+    registry: Registry,
+    requests_rate_limited: Counter,
+    proof_batch_size: prometheus::Histogram,
+    #[cfg(feature = "hardened")]
+    verification_latency: prometheus::HistogramVec,
+    #[cfg(feature = "hardened")]
+    circuit_breaker_trips: Counter,
+    #[cfg(feature = "hardened")]
+    rate_limit_fallbacks: Counter,
+}
+
+impl WebServerMetrics {
+    fn new(registry: &Registry) -> prometheus::Result<Self> {
+        let requests_rate_limited = Counter::new(
+            "bitcoin_sprint_requests_rate_limited_total",
+            "Total number of rate limited requests",
+        )?;
+        registry.register(Box::new(requests_rate_limited.clone()))?;
+
+        let proof_batch_size = prometheus::Histogram::with_opts(
+            prometheus::HistogramOpts::new("bitcoin_sprint_proof_batch_size", "Proofs per POST /proofs/batch request")
+                .buckets(vec![1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0]),
+        )?;
+        registry.register(Box::new(proof_batch_size.clone()))?;
+
+        #[cfg(feature = "hardened")]
+        let verification_latency = prometheus::HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "bitcoin_sprint_verification_latency_seconds",
+                "Verification request latency in seconds",
+            )
+            .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 2.0, 5.0]),
+            &["provider", "protocol"],
+        )?;
+        #[cfg(feature = "hardened")]
+        registry.register(Box::new(verification_latency.clone()))?;
+
+        #[cfg(feature = "hardened")]
+        let circuit_breaker_trips = Counter::new(
+            "bitcoin_sprint_circuit_breaker_trips_total",
+            "Total number of circuit breaker trips",
+        )?;
+        #[cfg(feature = "hardened")]
+        registry.register(Box::new(circuit_breaker_trips.clone()))?;
+
+        #[cfg(feature = "hardened")]
+        let rate_limit_fallbacks = Counter::new(
+            "sprint_rate_limit_fallback_total",
+            "Rate limit checks answered by the local limiter because Redis failed",
+        )?;
+        #[cfg(feature = "hardened")]
+        registry.register(Box::new(rate_limit_fallbacks.clone()))?;
+
+        Ok(WebServerMetrics {
+            registry: registry.clone(),
+            requests_rate_limited,
+            proof_batch_size,
+            #[cfg(feature = "hardened")]
+            verification_latency,
+            #[cfg(feature = "hardened")]
+            circuit_breaker_trips,
+            #[cfg(feature = "hardened")]
+            rate_limit_fallbacks,
+        })
+    }
 }
 
 // --- Redis-Backed Distributed Rate Limiter ---
+/// Sliding window over a sorted set of request timestamps (ms), evaluated atomically so
+/// concurrent replicas cannot both admit the last slot.
+/// KEYS[1] = window key; ARGV = now_ms, window_ms, limit, unique member. Returns 1 if admitted.
+#[cfg(feature = "hardened")]
+const SLIDING_WINDOW_SCRIPT: &str = r#"
+local now = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - window)
+if redis.call('ZCARD', KEYS[1]) >= tonumber(ARGV[3]) then
+    return 0
+end
+redis.call('ZADD', KEYS[1], now, ARGV[4])
+redis.call('PEXPIRE', KEYS[1], window)
+return 1
+"#;
+
 #[cfg(feature = "hardened")]
 #[derive(Clone)]
 struct RedisRateLimiter {
-    client: redis::Client,
+    conn: redis::aio::ConnectionManager,
+    script: redis::Script,
     max_requests: u32,
     window_seconds: u64,
 }
 
 #[cfg(feature = "hardened")]
 impl RedisRateLimiter {
-    async fn new(redis_url: &str, max_requests: u32, window_seconds: u64) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = redis::Client::open(redis_url)?;
+    /// Connect and PING, so an unreachable Redis is reported at startup rather than per request
+    async fn new(redis_url: &str, max_requests: u32, window_seconds: u64) -> redis::RedisResult<Self> {
+        let client = RedisClient::open(redis_url)?;
+        let mut conn = redis::aio::ConnectionManager::new(client).await?;
+        redis::cmd("PING").query_async::<_, String>(&mut conn).await?;
         Ok(Self {
-            client,
+            conn,
+            script: redis::Script::new(SLIDING_WINDOW_SCRIPT),
             max_requests,
             window_seconds,
         })
     }
 
-    async fn check_rate_limit(&self, key: &str) -> Result<bool, Box<dyn std::error::Error>> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-
-        // Use Redis sorted set for sliding window
-        let window_start = now - self.window_seconds;
-
-        // Remove old entries and count current requests
-        redis::cmd("ZREMRANGEBYSCORE")
-            .arg(&[key, "0", &window_start.to_string()])
-            .query_async(&mut conn)
-            .await?;
-
-        let count: i64 = redis::cmd("ZCARD")
-            .arg(key)
-            .query_async(&mut conn)
-            .await?;
-
-        if count >= self.max_requests as i64 {
-            return Ok(false);
-        }
-
-        // Add current request
-        redis::cmd("ZADD")
-            .arg(&[key, &now.to_string(), &Uuid::new_v4().to_string()])
-            .query_async(&mut conn)
+    /// Record a request under `key`; `Ok(false)` when the window is already full
+    async fn check_rate_limit(&self, key: &str) -> redis::RedisResult<bool> {
+        let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        let admitted: i64 = self
+            .script
+            .key(key)
+            .arg(now_ms)
+            .arg(self.window_seconds * 1000)
+            .arg(self.max_requests)
+            .arg(Uuid::new_v4().to_string())
+            .invoke_async(&mut self.conn.clone())
             .await?;
+        Ok(admitted == 1)
+    }
+}
 
-        // Set expiry on the key
-        redis::cmd("EXPIRE")
-            .arg(&[key, &self.window_seconds.to_string()])
-            .query_async(&mut conn)
-            .await?;
+/// Rate limit bucket for a caller: peer IP plus a digest of its API key, so keys never
+/// reach Redis in the clear
+#[cfg(feature = "hardened")]
+fn rate_limit_key(req: &HttpRequest) -> String {
+    use sha2::{Digest, Sha256};
 
-        Ok(true)
-    }
+    let ip = req.peer_addr().map(|addr| addr.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let api_key = req.headers().get("X-API-Key").map(|v| v.as_bytes()).unwrap_or_default();
+    format!("sprint:ratelimit:{}:{}", ip, &hex::encode(Sha256::digest(api_key))[..16])
 }
 
 // --- Circuit Breaker for External Providers ---
@@ -287,6 +511,7 @@ struct RateLimiter {
     entries: HashMap<String, RateLimitEntry>,
     max_requests: u32,
     window_duration: Duration,
+    clock: Arc<dyn Clock>,
 }
 
 impl RateLimiter {
@@ -295,12 +520,19 @@ fn new(max_requests: u32, window_seconds: u64) -> Self {
             entries: HashMap::new(),
             max_requests,
             window_duration: Duration::from_secs(window_seconds),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    #[cfg(test)]
+    fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     fn allow(&mut self) -> bool {
         let key = "global".to_string(); // Simple global rate limiting
-        let now = Instant::now();
+        let now = self.clock.now_instant();
 
         // Clean up old entries
         self.entries.retain(|_, entry| {
@@ -335,22 +567,84 @@ struct AppState {
     verifier: Arc<StorageVerifier>,
     rate_limiter: Arc<std::sync::Mutex<RateLimiter>>,
     active_challenges: Arc<AsyncMutex<HashMap<String, Challenge>>>,
+    metrics: Arc<WebServerMetrics>,
+    // Serve the deprecated /verify, which checks server-generated samples (ENABLE_SELF_TEST_VERIFY)
+    self_test_verify: bool,
+    // Most proofs one POST /proofs/batch may carry (PROOF_BATCH_MAX)
+    max_proof_batch: usize,
+    // verification.completed delivery; None leaves /webhooks unavailable
+    webhooks: Option<Arc<WebhookDispatcher>>,
+    // Webhook owner of each challenge created with an X-API-Key, until proved or expired
+    challenge_owners: Arc<Mutex<HashMap<String, String>>>,
     #[cfg(feature = "hardened")]
     redis_rate_limiter: Option<Arc<RedisRateLimiter>>,
     #[cfg(feature = "hardened")]
     circuit_breakers: Arc<AsyncMutex<HashMap<String, CircuitBreaker>>>,
 }
 
+impl AppState {
+    fn new(verifier: Arc<StorageVerifier>, self_test_verify: bool) -> prometheus::Result<Self> {
+        Ok(Self {
+            verifier,
+            rate_limiter: Arc::new(std::sync::Mutex::new(RateLimiter::new(10, 60))), // 10 req/min
+            active_challenges: Arc::new(AsyncMutex::new(HashMap::new())),
+            metrics: Arc::new(WebServerMetrics::new(&Registry::new())?),
+            self_test_verify,
+            max_proof_batch: DEFAULT_MAX_PROOF_BATCH,
+            webhooks: None,
+            challenge_owners: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "hardened")]
+            redis_rate_limiter: None, // Will be initialized if Redis is available
+            #[cfg(feature = "hardened")]
+            circuit_breakers: Arc::new(AsyncMutex::new(HashMap::new())),
+        })
+    }
+
+    fn with_max_proof_batch(mut self, max: usize) -> Self {
+        self.max_proof_batch = max.max(1);
+        self
+    }
+
+    /// Send verification.completed to the webhook registered by each challenge's creator
+    fn with_webhooks(mut self, webhooks: Arc<WebhookDispatcher>) -> Self {
+        let owners = self.challenge_owners.clone();
+        self.verifier.on_challenge_expired(Box::new(move |challenge| {
+            owners.lock().unwrap().remove(&challenge.id);
+        }));
+        self.webhooks = Some(webhooks);
+        self
+    }
+}
+
+/// Webhook owner for a caller: a digest of its X-API-Key, so keys are never held in the clear
+fn webhook_owner(req: &HttpRequest) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    let api_key = req.headers().get("X-API-Key").map(|v| v.as_bytes()).filter(|key| !key.is_empty())?;
+    Some(hex::encode(Sha256::digest(api_key)))
+}
+
 // --- Enhanced API Endpoint ---
 #[cfg(feature = "hardened")]
 async fn check_rate_limit_sync(
     req: &HttpRequest,
     state: &web::Data<AppState>,
 ) -> Result<(), HttpResponse> {
-    // For now, just use local rate limiter
-    let mut limiter = state.rate_limiter.lock().unwrap();
-    if !limiter.allow() {
-        REQUESTS_RATE_LIMITED.inc();
+    // Redis is shared by every replica; the local limiter only covers Redis outages
+    let shared = match &state.redis_rate_limiter {
+        Some(redis) => match redis.check_rate_limit(&rate_limit_key(req)).await {
+            Ok(allowed) => Some(allowed),
+            Err(e) => {
+                warn!("Redis rate limiter failed, using local limiter: {}", e);
+                state.metrics.rate_limit_fallbacks.inc();
+                None
+            }
+        },
+        None => None,
+    };
+    let allowed = shared.unwrap_or_else(|| state.rate_limiter.lock().unwrap().allow());
+    if !allowed {
+        state.metrics.requests_rate_limited.inc();
         return Err(HttpResponse::TooManyRequests().json(serde_json::json!({
             "error": "Rate limit exceeded",
             "retry_after": 60
@@ -371,7 +665,7 @@ async fn check_circuit_breaker_sync(
     });
 
     if !breaker.allow_request().await {
-        CIRCUIT_BREAKER_TRIPS.inc();
+        state.metrics.circuit_breaker_trips.inc();
         return Err(HttpResponse::ServiceUnavailable().json(serde_json::json!({
             "error": "Service temporarily unavailable",
             "service": service,
@@ -408,6 +702,18 @@ async fn verify(
     state: web::Data<AppState>,
 ) -> Result<impl Responder, actix_web::Error> {
     let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    #[cfg(feature = "hardened")]
+    let started = Instant::now();
+
+    // --- Deprecated Self-Test Gate ---
+    if !state.self_test_verify {
+        return Ok(HttpResponse::NotFound().json(ErrorResponse {
+            error: "/verify is a disabled self-test endpoint; use POST /challenges and POST /proofs".to_string(),
+            code: 404,
+            timestamp: now,
+        }));
+    }
+    warn!("/verify is deprecated: it checks server-generated samples, not provider data");
 
     // --- Input Validation ---
     if let Err(e) = validate_request(&payload) {
@@ -429,7 +735,7 @@ async fn verify(
     {
         let mut limiter = state.rate_limiter.lock().unwrap();
         if !limiter.allow() {
-            REQUESTS_RATE_LIMITED.inc();
+            state.metrics.requests_rate_limited.inc();
             return Ok(HttpResponse::TooManyRequests().json(ErrorResponse {
                 error: "Rate limit exceeded. Please try again later.".to_string(),
                 code: 429,
@@ -467,32 +773,8 @@ async fn verify(
               challenge_id, payload.file_id, payload.provider);
     }
 
-    // --- Generate Challenge using our StorageVerifier ---
-    let generated_challenge = match state.verifier.generate_challenge(&payload.file_id, &payload.provider).await {
-        Ok(c) => c,
-        Err(e) => {
-            error!("Challenge generation failed for {}: {:?}", payload.file_id, e);
-            return Ok(HttpResponse::InternalServerError().json(ErrorResponse {
-                error: "Failed to generate storage challenge".to_string(),
-                code: 500,
-                timestamp: now,
-            }));
-        }
-    };
-
-    // --- Enhanced Proof Creation ---
-    let proof = StorageProof {
-        challenge_id: challenge_id.clone(),
-        file_id: payload.file_id.clone(),
-        provider: payload.provider.clone(),
-        timestamp: now,
-        proof_data: generate_mock_samples(&payload.file_id, payload.file_size),
-        merkle_proof: Some(vec![format!("0x{}", hex::encode(&payload.file_id))]),
-        signature: Some(format!("sig_{}_{}", payload.provider, challenge_id)),
-    };
-
     // --- Enhanced Verification ---
-    let verification_result = match state.verifier.verify_proof(proof).await {
+    let verification_result = match verify_storage(&state.verifier, &payload).await {
         Ok(result) => result,
         Err(e) => {
             error!("Verification failed for challenge {}: {:?}", challenge_id, e);
@@ -523,32 +805,55 @@ async fn verify(
         }
     }
 
-    // --- Calculate Verification Score ---
-    let verification_score = calculate_verification_score(
-        verification_result,
-        payload.file_size,
-        &payload.protocol
-    );
-
     // --- Generate Signature ---
     let signature = format!("sig_{}_{}_{}", payload.provider, challenge_id, now);
 
     // --- Enhanced Response ---
     let response = VerifyResponse {
-        verified: verification_result && verification_score > 0.7,
+        verified: verification_result.verified,
         timestamp: now,
         signature,
         challenge_id,
-        verification_score,
+        verification_score: verification_result.score,
+        score_components: verification_result.components,
     };
 
     info!("Verification completed for {} - Score: {:.3}, Verified: {}",
-          payload.file_id, verification_score, response.verified);
+          payload.file_id, response.verification_score, response.verified);
+
+    #[cfg(feature = "hardened")]
+    state
+        .metrics
+        .verification_latency
+        .with_label_values(&[&payload.provider, &payload.protocol.to_lowercase()])
+        .observe(started.elapsed().as_secs_f64());
 
     Ok(HttpResponse::Ok().json(response))
 }
 
 // --- Helper Functions ---
+/// Sample the file from its storage network when the verifier has a fetcher for the
+/// protocol; otherwise prove against mock samples
+async fn verify_storage(verifier: &StorageVerifier, payload: &VerifyRequest) -> Result<VerificationOutcome, StorageVerificationError> {
+    #[cfg(feature = "ipfs")]
+    if verifier.supports_protocol(&payload.protocol) {
+        return verifier.verify_content_scored(&payload.protocol, &payload.file_id, &payload.provider).await;
+    }
+
+    let challenge = verifier.generate_challenge(&payload.file_id, &payload.provider).await?;
+    let proof = StorageProof {
+        challenge_id: challenge.id.clone(),
+        file_id: payload.file_id.clone(),
+        provider: payload.provider.clone(),
+        timestamp: challenge.timestamp,
+        proof_data: generate_mock_samples(&payload.file_id, payload.file_size),
+        merkle_proof: None,
+        signature: None, // Mock samples are not signed by the provider
+        extra_proof_data: Vec::new(),
+    };
+    verifier.verify_proof_scored(proof).await
+}
+
 fn generate_mock_samples(file_id: &str, file_size: u64) -> Vec<u8> {
     let sample_size = std::cmp::min(1024, file_size as usize); // Sample up to 1KB
     let mut sample = file_id.as_bytes().to_vec();
@@ -556,33 +861,412 @@ fn generate_mock_samples(file_id: &str, file_size: u64) -> Vec<u8> {
     sample
 }
 
-fn calculate_verification_score(
-    verified: bool,
-    file_size: u64,
-    protocol: &str
-) -> f64 {
-    let mut score = 0.0;
+// --- Challenge/Proof Flow ---
+/// HTTP status and message for a verifier error; internal details are logged, not returned
+fn verifier_error_code(e: &StorageVerificationError) -> (u32, String) {
+    match e {
+        StorageVerificationError::InvalidInput { .. } => (400, e.to_string()),
+        StorageVerificationError::ChallengeNotFound { .. } => (404, e.to_string()),
+        StorageVerificationError::CryptographicFailure { .. } => (422, e.to_string()),
+        StorageVerificationError::AuthenticationFailed => (401, e.to_string()),
+        StorageVerificationError::RateLimitExceeded { .. } => (429, e.to_string()),
+        StorageVerificationError::Overloaded { .. } => (503, e.to_string()),
+        _ => {
+            error!("Storage verifier error: {}", e);
+            (500, "Storage verification failed".to_string())
+        }
+    }
+}
+
+fn verifier_error_response(e: &StorageVerificationError, now: u64) -> HttpResponse {
+    let (code, error) = verifier_error_code(e);
+    let status = actix_web::http::StatusCode::from_u16(code as u16)
+        .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+    HttpResponse::build(status).json(ErrorResponse { error, code, timestamp: now })
+}
 
-    // Base verification score
-    if verified {
-        score += 0.6;
+async fn create_challenge(
+    req: HttpRequest,
+    payload: web::Json<CreateChallengeRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let issued = match req.headers().get("Idempotency-Key") {
+        Some(key) => match key.to_str() {
+            Ok(key) => state.verifier.generate_challenge_idempotent(&payload.file_id, &payload.provider, key).await,
+            Err(_) => {
+                return HttpResponse::BadRequest().json(ErrorResponse {
+                    error: "Idempotency-Key must be visible ASCII".to_string(),
+                    code: 400,
+                    timestamp: now,
+                })
+            }
+        },
+        None => state.verifier.generate_challenge(&payload.file_id, &payload.provider).await,
+    };
+    match issued {
+        Ok(challenge) => {
+            info!("Issued challenge {} for file {} to provider {}", challenge.id, challenge.file_id, challenge.provider);
+            if let (Some(_), Some(owner)) = (&state.webhooks, webhook_owner(&req)) {
+                state.challenge_owners.lock().unwrap().insert(challenge.id.clone(), owner);
+            }
+            HttpResponse::Created().json(ChallengeResponse::from(challenge))
+        }
+        Err(e) => verifier_error_response(&e, now),
     }
+}
 
-    // Protocol-specific bonuses
-    match protocol.to_lowercase().as_str() {
-        "ipfs" => score += 0.2,
-        "arweave" => score += 0.25,
-        "filecoin" => score += 0.3,
-        "bitcoin" => score += 0.35,
-        _ => {}
+/// Base64 length of the largest sample the verifier accepts
+fn max_encoded_sample_len(max_proof_size: usize) -> usize {
+    max_proof_size.div_ceil(3) * 4
+}
+
+/// Default for PROOF_BATCH_MAX
+const DEFAULT_MAX_PROOF_BATCH: usize = 256;
+
+/// Page size of `GET /providers/reputation` when no `limit` is given, and the largest accepted
+const REPUTATION_DEFAULT_LIMIT: usize = 50;
+const REPUTATION_MAX_LIMIT: usize = 500;
+
+/// JSON body limit for `POST /proofs/batch`; batches of large samples must be split
+const PROOF_BATCH_BODY_LIMIT: usize = 64 << 20;
+
+/// JSON body limit that fits a proof with every sample at the size cap
+fn proof_body_limit(max_proof_size: usize) -> usize {
+    max_encoded_sample_len(max_proof_size) * MAX_DIFFICULTY as usize + 64 * 1024
+}
+
+fn decode_sample(field: &str, encoded: &str, max_proof_size: usize) -> Result<Vec<u8>, String> {
+    // Refuse before decoding so oversized input is never allocated twice
+    if encoded.len() > max_encoded_sample_len(max_proof_size) {
+        return Err(format!("{} exceeds the {} byte sample limit", field, max_proof_size));
     }
+    use base64::{engine::general_purpose, Engine as _};
+    general_purpose::STANDARD.decode(encoded).map_err(|_| format!("{} must be base64", field))
+}
 
-    // File size factor (larger files get slight bonus)
-    let size_factor = (file_size as f64).log10() / 10.0;
-    score += size_factor.min(0.15);
+pub(crate) fn decode_proof(payload: SubmitProofRequest, max_proof_size: usize) -> Result<StorageProof, String> {
+    if payload.extra_proof_data.len() >= MAX_DIFFICULTY as usize {
+        return Err(format!("at most {} samples may be submitted", MAX_DIFFICULTY));
+    }
+    let proof_data = decode_sample("proof_data", &payload.proof_data, max_proof_size)?;
+    let extra_proof_data = payload
+        .extra_proof_data
+        .iter()
+        .map(|sample| decode_sample("extra_proof_data", sample, max_proof_size))
+        .collect::<Result<Vec<_>, _>>()?;
+    let merkle_proof = payload
+        .merkle_proof
+        .map(|steps| MerkleProof::Pairs(steps.into_iter().map(|s| (s.hash, s.is_left)).collect()));
+
+    Ok(StorageProof {
+        challenge_id: payload.challenge_id,
+        file_id: payload.file_id,
+        provider: payload.provider,
+        timestamp: payload.timestamp,
+        proof_data,
+        merkle_proof,
+        signature: payload.signature,
+        extra_proof_data,
+    })
+}
 
-    // Ensure score is between 0.0 and 1.0
-    score.max(0.0).min(1.0)
+/// Log a checked proof and notify the challenge creator's webhook, if any
+fn proof_completed(state: &AppState, challenge_id: &str, outcome: &VerificationOutcome) {
+    info!("Proof for challenge {} verified: {} (score {:.3})", challenge_id, outcome.verified, outcome.score);
+    let owner = state.challenge_owners.lock().unwrap().remove(challenge_id);
+    if let (Some(webhooks), Some(owner)) = (&state.webhooks, owner) {
+        let event = WebhookEvent::verification_completed(challenge_id, outcome.verified, outcome.score);
+        webhooks.enqueue(&owner, event);
+    }
+}
+
+async fn submit_proof(
+    payload: web::Json<SubmitProofRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let proof = match decode_proof(payload.into_inner(), state.verifier.max_proof_size()) {
+        Ok(proof) => proof,
+        Err(error) => return HttpResponse::BadRequest().json(ErrorResponse { error, code: 400, timestamp: now }),
+    };
+    let challenge_id = proof.challenge_id.clone();
+
+    match state.verifier.verify_proof_scored(proof).await {
+        Ok(outcome) => {
+            proof_completed(&state, &challenge_id, &outcome);
+            HttpResponse::Ok().json(ProofResponse {
+                challenge_id,
+                verified: outcome.verified,
+                score: outcome.score,
+                score_components: outcome.components,
+                timestamp: now,
+            })
+        }
+        Err(e) => verifier_error_response(&e, now),
+    }
+}
+
+/// Answer many challenges in one request. Each proof gets its own result, in input
+/// order, so a malformed or failing proof never affects the others.
+async fn submit_proof_batch(
+    payload: web::Json<SubmitProofBatchRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let requests = payload.into_inner().proofs;
+    if requests.len() > state.max_proof_batch {
+        return HttpResponse::PayloadTooLarge().json(ErrorResponse {
+            error: format!("at most {} proofs may be submitted per batch", state.max_proof_batch),
+            code: 413,
+            timestamp: now,
+        });
+    }
+    state.metrics.proof_batch_size.observe(requests.len() as f64);
+
+    let max_proof_size = state.verifier.max_proof_size();
+    let mut results: Vec<BatchProofResult> = Vec::with_capacity(requests.len());
+    let mut proofs = Vec::new();
+    // Slot in `results` of each proof handed to the verifier
+    let mut slots = Vec::new();
+    for request in requests {
+        let challenge_id = request.challenge_id.clone();
+        match decode_proof(request, max_proof_size) {
+            Ok(proof) => {
+                slots.push(results.len());
+                proofs.push(proof);
+                results.push(BatchProofResult { challenge_id, verified: None, score: None, error: None, code: None });
+            }
+            Err(error) => results.push(BatchProofResult {
+                challenge_id,
+                verified: None,
+                score: None,
+                error: Some(error),
+                code: Some(400),
+            }),
+        }
+    }
+
+    for (slot, outcome) in slots.into_iter().zip(state.verifier.verify_proofs_batch_scored(proofs).await) {
+        let result = &mut results[slot];
+        match outcome {
+            Ok(outcome) => {
+                proof_completed(&state, &result.challenge_id, &outcome);
+                result.verified = Some(outcome.verified);
+                result.score = Some(outcome.score);
+            }
+            Err(e) => {
+                let (code, error) = verifier_error_code(&e);
+                result.error = Some(error);
+                result.code = Some(code);
+            }
+        }
+    }
+
+    let verified = results.iter().filter(|r| r.verified == Some(true)).count();
+    let rejected = results.iter().filter(|r| r.verified == Some(false)).count();
+    let errors = results.iter().filter(|r| r.error.is_some()).count();
+    HttpResponse::Ok().json(ProofBatchResponse { results, verified, rejected, errors, timestamp: now })
+}
+
+/// Routes shared by `run_server` and the tests
+fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/verify", web::post().to(verify))
+        .route("/challenges", web::post().to(create_challenge))
+        .route("/challenges", web::get().to(list_challenges))
+        .route("/proofs", web::post().to(submit_proof))
+        .service(
+            web::resource("/proofs/batch")
+                .app_data(web::JsonConfig::default().limit(PROOF_BATCH_BODY_LIMIT))
+                .route(web::post().to(submit_proof_batch)),
+        )
+        .route("/providers/reputation", web::get().to(provider_reputation))
+        .route("/providers/{id}/key", web::post().to(register_provider_key))
+        .route("/webhooks", web::post().to(register_webhook))
+        .route("/webhooks", web::delete().to(delete_webhook))
+        .route("/health", web::get().to(health))
+        .route("/metrics", web::get().to(metrics))
+        .route("/metrics/prometheus", web::get().to(prometheus_metrics));
+}
+
+// --- Provider Reputation (requires X-API-Key) ---
+async fn provider_reputation(
+    req: HttpRequest,
+    query: web::Query<ReputationQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    if webhook_owner(&req).is_none() {
+        return HttpResponse::Unauthorized().json(ErrorResponse { error: "X-API-Key is required".to_string(), code: 401, timestamp: now });
+    }
+    if !(0.0..=1.0).contains(&query.min_score) {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            error: "min_score must be between 0 and 1".to_string(),
+            code: 400,
+            timestamp: now,
+        });
+    }
+    let limit = query.limit.unwrap_or(REPUTATION_DEFAULT_LIMIT).clamp(1, REPUTATION_MAX_LIMIT);
+
+    let reputations = match state.verifier.list_reputations(query.min_score).await {
+        Ok(reputations) => reputations,
+        Err(e) => return verifier_error_response(&e, now),
+    };
+    let total = reputations.len();
+    let providers: Vec<ProviderReputationEntry> =
+        reputations.into_iter().skip(query.offset).take(limit).map(Into::into).collect();
+    let end = query.offset.saturating_add(providers.len());
+    HttpResponse::Ok().json(ReputationPage {
+        providers,
+        total,
+        offset: query.offset,
+        limit,
+        next_offset: (end < total).then_some(end),
+        timestamp: now,
+    })
+}
+
+// --- Provider Key Registration ---
+async fn register_provider_key(
+    provider_id: web::Path<String>,
+    payload: web::Json<RegisterProviderKeyRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    use base64::{engine::general_purpose, Engine as _};
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let bad_request = |error: String| {
+        HttpResponse::BadRequest().json(ErrorResponse { error, code: 400, timestamp: now })
+    };
+
+    let key = match general_purpose::STANDARD.decode(payload.public_key.trim()) {
+        Ok(key) => key,
+        Err(_) => return bad_request("public_key must be base64".to_string()),
+    };
+
+    // Keys cannot be swapped through this unauthenticated route; re-registering the same key is a no-op
+    match state.verifier.provider_key(&provider_id).await {
+        Ok(Some(existing)) if existing[..] != key[..] => {
+            return HttpResponse::Conflict().json(ErrorResponse {
+                error: format!("provider {} already has a different key", provider_id),
+                code: 409,
+                timestamp: now,
+            });
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!("Loading key for provider {} failed: {}", provider_id, e);
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Failed to register provider key".to_string(),
+                code: 500,
+                timestamp: now,
+            });
+        }
+    }
+
+    match state.verifier.register_provider_key(&provider_id, &key).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "provider": provider_id.as_str(),
+            "registered": true,
+            "timestamp": now,
+        })),
+        Err(e @ StorageVerificationError::InvalidInput { .. }) => bad_request(e.to_string()),
+        Err(e) => {
+            error!("Registering key for provider {} failed: {}", provider_id, e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Failed to register provider key".to_string(),
+                code: 500,
+                timestamp: now,
+            })
+        }
+    }
+}
+
+// --- Webhook Registration (per X-API-Key) ---
+/// The dispatcher and the caller's owner id, or the response refusing the request
+fn webhook_caller(req: &HttpRequest, state: &AppState, now: u64) -> Result<(Arc<WebhookDispatcher>, String), HttpResponse> {
+    let webhooks = state.webhooks.clone().ok_or_else(|| {
+        HttpResponse::NotFound().json(ErrorResponse { error: "Webhooks are not enabled".to_string(), code: 404, timestamp: now })
+    })?;
+    let owner = webhook_owner(req).ok_or_else(|| {
+        HttpResponse::Unauthorized().json(ErrorResponse { error: "X-API-Key is required".to_string(), code: 401, timestamp: now })
+    })?;
+    Ok((webhooks, owner))
+}
+
+async fn register_webhook(
+    req: HttpRequest,
+    payload: web::Json<RegisterWebhookRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let (webhooks, owner) = match webhook_caller(&req, &state, now) {
+        Ok(caller) => caller,
+        Err(response) => return response,
+    };
+    match webhooks.register(&owner, &payload.url, payload.secret.as_bytes()).await {
+        Ok(()) => HttpResponse::Created().json(serde_json::json!({
+            "url": webhooks.registration(&owner),
+            "events": [crate::webhooks::VERIFICATION_COMPLETED],
+            "timestamp": now,
+        })),
+        Err(e) => HttpResponse::BadRequest().json(ErrorResponse { error: e.to_string(), code: 400, timestamp: now }),
+    }
+}
+
+async fn delete_webhook(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let (webhooks, owner) = match webhook_caller(&req, &state, now) {
+        Ok(caller) => caller,
+        Err(response) => return response,
+    };
+    if webhooks.unregister(&owner) {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::NotFound().json(ErrorResponse { error: "No webhook is registered".to_string(), code: 404, timestamp: now })
+    }
+}
+
+// --- Outstanding Challenges for a Provider ---
+async fn list_challenges(
+    query: web::Query<ListChallengesQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    if query.provider.is_empty() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            error: "provider cannot be empty".to_string(),
+            code: 400,
+            timestamp: now,
+        });
+    }
+
+    let challenges: Vec<_> = state
+        .verifier
+        .list_challenges(&query.provider, query.include_expired)
+        .await
+        .into_iter()
+        .map(|c| {
+            serde_json::json!({
+                "id": c.id,
+                "file_id": c.file_id,
+                "chunk_index": c.chunk_index,
+                "sample_offset": c.sample_offset,
+                "sample_size": c.sample_size,
+                "commitment_alg": c.commitment_alg,
+                "require_signature": c.require_signature,
+                "issued_at": c.timestamp,
+                "expires_at": c.expiry,
+                "expired": c.is_expired_at(now),
+            })
+        })
+        .collect();
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "provider": query.provider,
+        "challenges": challenges,
+        "timestamp": now,
+    }))
 }
 
 // --- Health Check Endpoint ---
@@ -609,10 +1293,23 @@ async fn metrics(state: web::Data<AppState>) -> impl Responder {
         "successful_proofs": verifier_metrics.successful_proofs,
         "failed_proofs": verifier_metrics.failed_proofs,
         "rate_limited_requests": verifier_metrics.rate_limited_requests,
+        "overloaded_rejections": verifier_metrics.overloaded_rejections,
+        "oversized_proofs": verifier_metrics.oversized_proofs,
         "timestamp": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
     }))
 }
 
+// --- Prometheus Exposition (this server's registry only) ---
+async fn prometheus_metrics(state: web::Data<AppState>) -> impl Responder {
+    let encoder = TextEncoder::new();
+    let mut buf = Vec::new();
+    if let Err(e) = encoder.encode(&state.metrics.registry.gather(), &mut buf) {
+        error!("Encoding metrics failed: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    HttpResponse::Ok().content_type(encoder.format_type()).body(buf)
+}
+
 // --- Enterprise-Grade Security Headers ---
 fn add_security_headers() -> middleware::DefaultHeaders {
     middleware::DefaultHeaders::new()
@@ -889,16 +1586,51 @@ pub async fn run_server() -> std::io::Result<()> {
     };
 
     let verifier = Arc::new(StorageVerifier::with_config(rate_config));
+    verifier.on_challenge_expired(Box::new(|challenge| {
+        warn!("Challenge {} for provider {} expired without a proof", challenge.id, challenge.provider);
+    }));
+    verifier.start_background_tasks(Duration::from_secs(60));
+
+    let self_test_verify = matches!(env::var("ENABLE_SELF_TEST_VERIFY").as_deref(), Ok("1") | Ok("true"));
+    if self_test_verify {
+        warn!("ENABLE_SELF_TEST_VERIFY is set: serving the deprecated mock-sample /verify endpoint");
+    }
+    let json_limit = proof_body_limit(verifier.max_proof_size());
+    let max_proof_batch = env::var("PROOF_BATCH_MAX")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_PROOF_BATCH);
+
+    let webhooks = WebhookDispatcher::new(WebhookConfig::default())
+        .map_err(std::io::Error::other)?;
+    webhooks.on_dead_letter(Box::new(|letter| {
+        warn!(
+            target: "audit",
+            "Webhook {} ({}) for {} dead-lettered after {} attempts: {}",
+            letter.event.id, letter.event.kind, letter.url, letter.attempts, letter.error
+        );
+    }));
+    let webhook_worker = webhooks.spawn_worker();
+
+    #[allow(unused_mut)]
+    let mut app_state = AppState::new(verifier, self_test_verify)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?
+        .with_max_proof_batch(max_proof_batch)
+        .with_webhooks(webhooks.clone());
 
-    let state = web::Data::new(AppState {
-        verifier,
-        rate_limiter: Arc::new(std::sync::Mutex::new(RateLimiter::new(10, 60))), // 10 req/min
-        active_challenges: Arc::new(AsyncMutex::new(HashMap::new())),
-        #[cfg(feature = "hardened")]
-        redis_rate_limiter: None, // Will be initialized if Redis is available
-        #[cfg(feature = "hardened")]
-        circuit_breakers: Arc::new(AsyncMutex::new(HashMap::new())),
-    });
+    #[cfg(feature = "hardened")]
+    match env::var("RUST_REDIS_URL") {
+        Ok(url) => match RedisRateLimiter::new(&url, 10, 60).await {
+            Ok(limiter) => {
+                info!("Distributed rate limiting via Redis enabled");
+                app_state.redis_rate_limiter = Some(Arc::new(limiter));
+            }
+            Err(e) => error!("Redis at RUST_REDIS_URL is unreachable ({}); rate limits are per replica", e),
+        },
+        Err(_) => warn!("RUST_REDIS_URL is not set; rate limits are per replica"),
+    }
+
+    let state = web::Data::new(app_state);
 
     info!(
         "Server configured - Rate limit: 10 req/min, Binding to 0.0.0.0:{}",
@@ -911,12 +1643,343 @@ pub async fn run_server() -> std::io::Result<()> {
             .wrap(middleware::Logger::default())
             .wrap(add_security_headers())
             .app_data(state.clone())
-            .route("/verify", web::post().to(verify))
-            .route("/health", web::get().to(health))
-            .route("/metrics", web::get().to(metrics))
+            .app_data(web::JsonConfig::default().limit(json_limit))
+            .configure(configure_routes)
     })
     .bind(("0.0.0.0", port))?
     .workers(4)
     .run()
-    .await
+    .await?;
+
+    // Give queued deliveries a bounded chance to go out
+    webhooks.close();
+    if tokio::time::timeout(Duration::from_secs(10), webhook_worker).await.is_err() {
+        warn!("Webhook deliveries still pending at shutdown were abandoned");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test;
+    use base64::{engine::general_purpose, Engine as _};
+
+    const CHUNK: usize = 32;
+
+    fn file_data() -> Vec<u8> {
+        (0..CHUNK * 8).map(|i| (i * 7 % 256) as u8).collect()
+    }
+
+    async fn app_state(self_test_verify: bool) -> web::Data<AppState> {
+        let verifier = Arc::new(StorageVerifier::new());
+        let leaves = file_data().chunks(CHUNK).map(crate::merkle::hash_leaf).collect();
+        verifier.register_file_commitments("file-1", CHUNK as u32, leaves).await.unwrap();
+        web::Data::new(AppState::new(verifier, self_test_verify).unwrap())
+    }
+
+    fn challenge_request() -> CreateChallengeRequest {
+        CreateChallengeRequest { file_id: "file-1".to_string(), provider: "provider-1".to_string() }
+    }
+
+    /// Answer `challenge` with samples read from `data`
+    fn proof_from(challenge: &ChallengeResponse, data: &[u8]) -> SubmitProofRequest {
+        let sample = |i: u64| general_purpose::STANDARD.encode(&data[i as usize * CHUNK..(i as usize + 1) * CHUNK]);
+        SubmitProofRequest {
+            challenge_id: challenge.id.clone(),
+            file_id: challenge.file_id.clone(),
+            provider: challenge.provider.clone(),
+            timestamp: challenge.issued_at,
+            proof_data: sample(challenge.chunk_indices[0]),
+            extra_proof_data: challenge.chunk_indices[1..].iter().map(|&i| sample(i)).collect(),
+            merkle_proof: None,
+            signature: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_challenge_then_proof_verifies_provider_samples() {
+        let app = test::init_service(App::new().app_data(app_state(false).await).configure(configure_routes)).await;
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post().uri("/challenges").set_json(challenge_request()).to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let challenge: ChallengeResponse = test::read_body_json(resp).await;
+        assert_eq!(challenge.sample_size, CHUNK as u32);
+        assert_eq!(challenge.sample_offset, challenge.chunk_index * CHUNK as u64);
+
+        let req = test::TestRequest::post().uri("/proofs").set_json(proof_from(&challenge, &file_data())).to_request();
+        let verdict: ProofResponse = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(verdict.challenge_id, challenge.id);
+        assert!(verdict.verified);
+    }
+
+    #[actix_web::test]
+    async fn test_proof_with_wrong_data_is_not_verified() {
+        let app = test::init_service(App::new().app_data(app_state(false).await).configure(configure_routes)).await;
+
+        let req = test::TestRequest::post().uri("/challenges").set_json(challenge_request()).to_request();
+        let challenge: ChallengeResponse = test::call_and_read_body_json(&app, req).await;
+
+        let tampered: Vec<u8> = file_data().iter().map(|b| b ^ 0xff).collect();
+        let req = test::TestRequest::post().uri("/proofs").set_json(proof_from(&challenge, &tampered)).to_request();
+        let verdict: ProofResponse = test::call_and_read_body_json(&app, req).await;
+        assert!(!verdict.verified);
+    }
+
+    #[actix_web::test]
+    async fn test_proof_batch_reports_each_proof_in_order() {
+        let clock = Arc::new(crate::clock::ManualClock::starting_now());
+        let verifier = Arc::new(StorageVerifier::new().with_clock(clock.clone()));
+        let leaves = file_data().chunks(CHUNK).map(crate::merkle::hash_leaf).collect();
+        verifier.register_file_commitments("file-1", CHUNK as u32, leaves).await.unwrap();
+        let state = web::Data::new(AppState::new(verifier, false).unwrap().with_max_proof_batch(4));
+        let app = test::init_service(App::new().app_data(state.clone()).configure(configure_routes)).await;
+
+        let create = || test::TestRequest::post().uri("/challenges").set_json(challenge_request()).to_request();
+        let expired: ChallengeResponse = test::call_and_read_body_json(&app, create()).await;
+        // Past the default 30 minute challenge TTL
+        clock.advance(Duration::from_secs(1801));
+        let valid: ChallengeResponse = test::call_and_read_body_json(&app, create()).await;
+        let wrong: ChallengeResponse = test::call_and_read_body_json(&app, create()).await;
+
+        let tampered: Vec<u8> = file_data().iter().map(|b| b ^ 0xff).collect();
+        let mut malformed = proof_from(&valid, &file_data());
+        malformed.challenge_id = "chall_malformed".to_string();
+        malformed.proof_data = "not base64!".to_string();
+        let batch = SubmitProofBatchRequest {
+            proofs: vec![
+                proof_from(&valid, &file_data()),
+                malformed,
+                proof_from(&expired, &file_data()),
+                proof_from(&wrong, &tampered),
+            ],
+        };
+        let req = test::TestRequest::post().uri("/proofs/batch").set_json(batch).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: ProofBatchResponse = test::read_body_json(resp).await;
+
+        let ids: Vec<&str> = body.results.iter().map(|r| r.challenge_id.as_str()).collect();
+        assert_eq!(ids, [valid.id.as_str(), "chall_malformed", expired.id.as_str(), wrong.id.as_str()]);
+        assert_eq!(body.results[0].verified, Some(true));
+        assert_eq!(body.results[1].code, Some(400));
+        assert_eq!(body.results[1].verified, None);
+        assert_eq!(body.results[2].verified, Some(false));
+        assert_eq!(body.results[3].verified, Some(false));
+        assert_eq!((body.verified, body.rejected, body.errors), (1, 2, 1));
+        assert_eq!(state.metrics.proof_batch_size.get_sample_count(), 1);
+        assert_eq!(state.metrics.proof_batch_size.get_sample_sum(), 4.0);
+
+        // Over the configured cap: refused whole, before any proof is checked
+        let oversized = SubmitProofBatchRequest { proofs: (0..5).map(|_| proof_from(&valid, &file_data())).collect() };
+        let req = test::TestRequest::post().uri("/proofs/batch").set_json(oversized).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(state.metrics.proof_batch_size.get_sample_count(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_provider_reputation_requires_a_key_and_pages_best_first() {
+        let state = app_state(false).await;
+        let app = test::init_service(App::new().app_data(state.clone()).configure(configure_routes)).await;
+
+        let tampered: Vec<u8> = file_data().iter().map(|b| b ^ 0xff).collect();
+        for (provider, data) in [("provider-1", file_data()), ("provider-2", tampered)] {
+            let request = CreateChallengeRequest { file_id: "file-1".to_string(), provider: provider.to_string() };
+            let req = test::TestRequest::post().uri("/challenges").set_json(request).to_request();
+            let challenge: ChallengeResponse = test::call_and_read_body_json(&app, req).await;
+            let req = test::TestRequest::post().uri("/proofs").set_json(proof_from(&challenge, &data)).to_request();
+            assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        }
+
+        let req = test::TestRequest::get().uri("/providers/reputation").to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+
+        let page = |uri: &str| test::TestRequest::get().uri(uri).insert_header(("X-API-Key", "k1")).to_request();
+        let first: ReputationPage = test::call_and_read_body_json(&app, page("/providers/reputation?limit=1")).await;
+        assert_eq!((first.total, first.next_offset), (2, Some(1)));
+        assert_eq!(first.providers[0].provider, "provider-1");
+        assert_eq!((first.providers[0].proofs_verified, first.providers[0].failures), (1, 0));
+
+        let second: ReputationPage =
+            test::call_and_read_body_json(&app, page("/providers/reputation?limit=1&offset=1")).await;
+        assert_eq!(second.providers[0].provider, "provider-2");
+        assert_eq!(second.providers[0].failures, 1);
+        assert!(second.providers[0].score < first.providers[0].score);
+        assert_eq!(second.next_offset, None);
+
+        let filtered: ReputationPage =
+            test::call_and_read_body_json(&app, page("/providers/reputation?min_score=0.99")).await;
+        let names: Vec<&str> = filtered.providers.iter().map(|p| p.provider.as_str()).collect();
+        assert_eq!(names, ["provider-1"]);
+
+        let resp = test::call_service(&app, page("/providers/reputation?min_score=2")).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_rejects_bad_base64_unknown_challenges_and_unregistered_files() {
+        let app = test::init_service(App::new().app_data(app_state(false).await).configure(configure_routes)).await;
+
+        let req = test::TestRequest::post().uri("/challenges").set_json(challenge_request()).to_request();
+        let challenge: ChallengeResponse = test::call_and_read_body_json(&app, req).await;
+
+        let mut bad_base64 = proof_from(&challenge, &file_data());
+        bad_base64.proof_data = "not base64!".to_string();
+        let resp = test::call_service(&app, test::TestRequest::post().uri("/proofs").set_json(bad_base64).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let mut unknown = proof_from(&challenge, &file_data());
+        unknown.challenge_id = "chall_missing".to_string();
+        let resp = test::call_service(&app, test::TestRequest::post().uri("/proofs").set_json(unknown).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let unregistered = CreateChallengeRequest { file_id: "file-2".to_string(), provider: "provider-1".to_string() };
+        let resp = test::call_service(&app, test::TestRequest::post().uri("/challenges").set_json(unregistered).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_idempotency_key_replays_the_issued_challenge() {
+        let app = test::init_service(App::new().app_data(app_state(false).await).configure(configure_routes)).await;
+        let issue = |key: &str| {
+            test::TestRequest::post()
+                .uri("/challenges")
+                .insert_header(("Idempotency-Key", key))
+                .set_json(challenge_request())
+                .to_request()
+        };
+
+        let first: ChallengeResponse = test::call_and_read_body_json(&app, issue("retry-1")).await;
+        let retry: ChallengeResponse = test::call_and_read_body_json(&app, issue("retry-1")).await;
+        assert_eq!(retry.id, first.id);
+        assert_eq!(retry.chunk_indices, first.chunk_indices);
+
+        let other: ChallengeResponse = test::call_and_read_body_json(&app, issue("retry-2")).await;
+        assert_ne!(other.id, first.id);
+    }
+
+    #[actix_web::test]
+    async fn test_rate_limiter_window_resets() {
+        let clock = Arc::new(crate::clock::ManualClock::new(0));
+        let mut limiter = RateLimiter::new(2, 60).with_clock(clock.clone());
+        assert!(limiter.allow() && limiter.allow());
+        clock.advance(Duration::from_secs(59));
+        assert!(!limiter.allow());
+        clock.advance(Duration::from_secs(1));
+        assert!(limiter.allow());
+    }
+
+    #[actix_web::test]
+    async fn test_self_test_verify_is_off_by_default() {
+        let app = test::init_service(App::new().app_data(app_state(false).await).configure(configure_routes)).await;
+        let request = VerifyRequest {
+            file_id: "file-1".to_string(),
+            provider: "provider-1".to_string(),
+            file_size: 1024,
+            protocol: "bitcoin".to_string(),
+        };
+        let resp = test::call_service(&app, test::TestRequest::post().uri("/verify").set_json(request).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_verification_is_pushed_to_the_challenge_creators_webhook() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let receiver = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(200)).mount(&receiver).await;
+
+        let webhooks = WebhookDispatcher::new(WebhookConfig { allow_private_destinations: true, ..WebhookConfig::default() }).unwrap();
+        let worker = webhooks.spawn_worker();
+        let state = app_state(false).await;
+        let state = web::Data::new(
+            AppState::new(state.verifier.clone(), false).unwrap().with_webhooks(webhooks.clone()),
+        );
+        let app = test::init_service(App::new().app_data(state).configure(configure_routes)).await;
+
+        let register = |key: Option<&str>| {
+            let body = RegisterWebhookRequest { url: receiver.uri(), secret: "whsec_0123456789abcdef".to_string() };
+            let req = test::TestRequest::post().uri("/webhooks").set_json(body);
+            match key {
+                Some(key) => req.insert_header(("X-API-Key", key)).to_request(),
+                None => req.to_request(),
+            }
+        };
+        assert_eq!(test::call_service(&app, register(None)).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(test::call_service(&app, register(Some("customer-key"))).await.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::post()
+            .uri("/challenges")
+            .insert_header(("X-API-Key", "customer-key"))
+            .set_json(challenge_request())
+            .to_request();
+        let challenge: ChallengeResponse = test::call_and_read_body_json(&app, req).await;
+        let req = test::TestRequest::post().uri("/proofs").set_json(proof_from(&challenge, &file_data())).to_request();
+        let verdict: ProofResponse = test::call_and_read_body_json(&app, req).await;
+        assert!(verdict.verified);
+
+        webhooks.close();
+        worker.await.unwrap();
+        let received = receiver.received_requests().await.unwrap();
+        assert_eq!(received.len(), 1);
+        let event: WebhookEvent = serde_json::from_slice(&received[0].body).unwrap();
+        assert_eq!(event.kind, crate::webhooks::VERIFICATION_COMPLETED);
+        assert_eq!(event.data["challenge_id"], challenge.id.as_str());
+        assert_eq!(event.data["verified"], true);
+
+        let delete = test::TestRequest::delete().uri("/webhooks").insert_header(("X-API-Key", "customer-key")).to_request();
+        assert_eq!(test::call_service(&app, delete).await.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[cfg(feature = "hardened")]
+    #[actix_web::test]
+    async fn test_unreachable_redis_is_reported_at_startup() {
+        assert!(RedisRateLimiter::new("redis://127.0.0.1:1", 3, 60).await.is_err());
+    }
+
+    #[cfg(feature = "redis-tests")]
+    mod redis_rate_limit {
+        use super::*;
+
+        async fn limiter(max_requests: u32) -> RedisRateLimiter {
+            let url = std::env::var("REDIS_TEST_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+            RedisRateLimiter::new(&url, max_requests, 60).await.unwrap()
+        }
+
+        fn fresh_key() -> String {
+            format!("sprint:ratelimit:test:{}", Uuid::new_v4())
+        }
+
+        #[actix_web::test]
+        async fn test_limit_is_shared_between_instances() {
+            let (a, b) = (limiter(3).await, limiter(3).await);
+            let key = fresh_key();
+
+            assert!(a.check_rate_limit(&key).await.unwrap());
+            assert!(b.check_rate_limit(&key).await.unwrap());
+            assert!(a.check_rate_limit(&key).await.unwrap());
+            assert!(!b.check_rate_limit(&key).await.unwrap());
+            assert!(!a.check_rate_limit(&key).await.unwrap());
+        }
+
+        #[actix_web::test]
+        async fn test_concurrent_checks_never_overshoot_the_limit() {
+            let (a, b) = (limiter(5).await, limiter(5).await);
+            let key = fresh_key();
+
+            let checks = (0..20).map(|i| {
+                let limiter = if i % 2 == 0 { a.clone() } else { b.clone() };
+                let key = key.clone();
+                async move { limiter.check_rate_limit(&key).await.unwrap() }
+            });
+            let admitted = futures::future::join_all(checks).await.into_iter().filter(|&ok| ok).count();
+            assert_eq!(admitted, 5);
+        }
+    }
 }
@@ -0,0 +1,772 @@
+// SPDX-License-Identifier: MIT
+// Bitcoin Sprint - Signed webhook delivery for customer endpoints
+
+//! Push events to URLs customers register per API key.
+//!
+//! Producers call [`WebhookDispatcher::enqueue`], which never blocks: an owner without a
+//! registration is skipped, and once `queue_capacity` deliveries are outstanding new
+//! events are dropped and counted. The worker started by
+//! [`WebhookDispatcher::spawn_worker`] POSTs each event as JSON with an
+//! `X-Sprint-Signature: sha256=<hex>` header, the HMAC-SHA256 of the body under the
+//! registration's secret, computed through [`SecureBuffer::hmac_hex`].
+//!
+//! Any non-2xx answer or transport error is retried with exponential backoff until
+//! `max_attempts` is spent, after which the event goes to the dead-letter callbacks.
+//! Every destination URL has its own [`CircuitBreaker`]: while it is open, attempts for
+//! that URL fail without a request, and deliveries only hold an HTTP slot for the
+//! duration of a request, never across a backoff, so a dead endpoint cannot starve
+//! healthy ones.
+//!
+//! Destinations must be https URLs whose host resolves only to public addresses and whose
+//! port is not in `denied_ports`. The check runs at registration and again whenever a
+//! delivery connects, so a name re-pointed at an internal address afterwards is refused.
+//! Redirects are not followed.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use crate::SecureBuffer;
+
+/// Header carrying `sha256=<hex HMAC of the body>`
+pub const SIGNATURE_HEADER: &str = "X-Sprint-Signature";
+/// Header carrying the event type
+pub const EVENT_HEADER: &str = "X-Sprint-Event";
+/// Header carrying the event id, stable across retries
+pub const DELIVERY_HEADER: &str = "X-Sprint-Delivery";
+
+/// A storage proof was checked
+pub const VERIFICATION_COMPLETED: &str = "verification.completed";
+/// A hybrid entropy receipt was linked onto the receipt chain
+pub const ENTROPY_RECEIPT_ISSUED: &str = "entropy.receipt.issued";
+
+/// Shortest secret `register` accepts
+pub const MIN_SECRET_LEN: usize = 16;
+
+const USER_AGENT: &str = "BitcoinSprint-Webhooks/1.0";
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum WebhookError {
+    #[error("invalid webhook url: {0}")]
+    InvalidUrl(String),
+    #[error("webhook secret must be at least {} bytes", MIN_SECRET_LEN)]
+    WeakSecret,
+    #[error("webhook secret: {0}")]
+    Secret(String),
+    #[error("webhook destination not allowed: {0}")]
+    ForbiddenDestination(String),
+    #[error("webhook http client: {0}")]
+    Client(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Deliveries queued or being retried before new events are dropped
+    pub queue_capacity: usize,
+    /// Attempts per event, the first included, before it is dead-lettered
+    pub max_attempts: u32,
+    /// Wait after the first failed attempt; doubles per attempt up to `max_backoff`
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub request_timeout: Duration,
+    /// Requests in flight at once across all destinations
+    pub max_in_flight: usize,
+    /// Applied to each destination URL separately
+    pub breaker: CircuitBreakerConfig,
+    /// Ports no destination may use, such as the server's own admin port
+    pub denied_ports: Vec<u16>,
+    /// Accept http and non-public addresses; only for tests against a local receiver
+    pub allow_private_destinations: bool,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            queue_capacity: 1024,
+            max_attempts: 6,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            request_timeout: Duration::from_secs(10),
+            max_in_flight: 32,
+            breaker: CircuitBreakerConfig {
+                failure_threshold: 5,
+                cooldown: Duration::from_secs(30),
+                half_open_max: 1,
+            },
+            denied_ports: Vec::new(),
+            allow_private_destinations: false,
+        }
+    }
+}
+
+/// The JSON body POSTed to a destination
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Unix seconds
+    pub created_at: u64,
+    pub data: Value,
+}
+
+impl WebhookEvent {
+    pub fn new(kind: &str, data: Value) -> Self {
+        WebhookEvent {
+            id: hex::encode(rand::random::<[u8; 16]>()),
+            kind: kind.to_string(),
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            data,
+        }
+    }
+
+    pub fn verification_completed(challenge_id: &str, verified: bool, score: f64) -> Self {
+        Self::new(
+            VERIFICATION_COMPLETED,
+            serde_json::json!({ "challenge_id": challenge_id, "verified": verified, "score": score }),
+        )
+    }
+
+    pub fn entropy_receipt_issued(receipt: Value) -> Self {
+        Self::new(ENTROPY_RECEIPT_ISSUED, serde_json::json!({ "receipt": receipt }))
+    }
+}
+
+/// An event given up on after `attempts` tries
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub owner: String,
+    pub url: String,
+    pub event: WebhookEvent,
+    pub attempts: u32,
+    pub error: String,
+}
+
+/// Called once for each dead-lettered event
+pub type DeadLetterCallback = Box<dyn Fn(&DeadLetter) + Send + Sync>;
+
+/// Registered callbacks are shared so a dead letter can be reported without holding the lock
+type SharedDeadLetterCallback = Arc<dyn Fn(&DeadLetter) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WebhookStats {
+    pub delivered: u64,
+    pub failed_attempts: u64,
+    pub dead_lettered: u64,
+    pub dropped: u64,
+}
+
+/// `sha256=<hex>` for `body` under `secret`
+pub fn sign(secret: &SecureBuffer, body: &[u8]) -> Result<String, String> {
+    let mut payload = SecureBuffer::new(body.len().max(1))?;
+    payload.write(body)?;
+    Ok(format!("sha256={}", payload.hmac_hex(secret.as_slice()?)?))
+}
+
+/// Whether `ip` is routable on the public internet: not loopback, private, link-local
+/// (cloud metadata included), carrier-grade NAT, multicast or otherwise reserved
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0 && v4.octets()[2] == 0)
+                || (a == 198 && (18..20).contains(&b)))
+        }
+        IpAddr::V6(v6) => match embedded_ipv4(v6) {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                let first = v6.segments()[0];
+                !(v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_multicast()
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// The IPv4 address carried by an IPv4-mapped (`::ffff:a.b.c.d`), IPv4-compatible
+/// (`::a.b.c.d`) or NAT64 (`64:ff9b::a.b.c.d`) address
+fn embedded_ipv4(v6: Ipv6Addr) -> Option<Ipv4Addr> {
+    match v6.segments() {
+        [0x64, 0xff9b, 0, 0, 0, 0, high, low] => Some(Ipv4Addr::from(((high as u32) << 16) | low as u32)),
+        _ => v6.to_ipv4(),
+    }
+}
+
+/// The host of `url` when it is an address rather than a name
+fn literal_ip(url: &Url) -> Option<IpAddr> {
+    url.host_str()?.trim_start_matches('[').trim_end_matches(']').parse().ok()
+}
+
+/// Resolver for deliveries that hands out only public addresses, failing when none are left
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let public: Vec<SocketAddr> = addrs.into_iter().filter(|addr| is_public(addr.ip())).collect();
+            if public.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            Ok(Box::new(public.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+struct Registration {
+    url: String,
+    secret: Arc<SecureBuffer>,
+}
+
+struct Delivery {
+    owner: String,
+    url: String,
+    secret: Arc<SecureBuffer>,
+    event: WebhookEvent,
+    // Held until the delivery succeeds or is dead-lettered
+    _slot: OwnedSemaphorePermit,
+}
+
+#[derive(Default)]
+struct Counters {
+    delivered: AtomicU64,
+    failed_attempts: AtomicU64,
+    dead_lettered: AtomicU64,
+    dropped: AtomicU64,
+}
+
+pub struct WebhookDispatcher {
+    config: WebhookConfig,
+    client: Client,
+    registrations: RwLock<HashMap<String, Registration>>,
+    breakers: Mutex<HashMap<String, Arc<CircuitBreaker>>>,
+    slots: Arc<Semaphore>,
+    in_flight: Arc<Semaphore>,
+    sender: Mutex<Option<mpsc::UnboundedSender<Delivery>>>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<Delivery>>>,
+    dead_letter_callbacks: RwLock<Vec<SharedDeadLetterCallback>>,
+    counters: Counters,
+}
+
+impl WebhookDispatcher {
+    /// Fails when the HTTP client cannot be built, rather than fall back to one without
+    /// the address checks
+    pub fn new(config: WebhookConfig) -> Result<Arc<Self>, WebhookError> {
+        let mut builder = Client::builder()
+            .timeout(config.request_timeout)
+            .user_agent(USER_AGENT)
+            .redirect(reqwest::redirect::Policy::none());
+        if !config.allow_private_destinations {
+            // A proxy would resolve the name itself, out of reach of the address check
+            builder = builder.dns_resolver(Arc::new(PublicResolver)).no_proxy();
+        }
+        let client = builder.build().map_err(|e| WebhookError::Client(e.to_string()))?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Ok(Arc::new(WebhookDispatcher {
+            slots: Arc::new(Semaphore::new(config.queue_capacity.max(1))),
+            in_flight: Arc::new(Semaphore::new(config.max_in_flight.max(1))),
+            config,
+            client,
+            registrations: RwLock::new(HashMap::new()),
+            breakers: Mutex::new(HashMap::new()),
+            sender: Mutex::new(Some(sender)),
+            receiver: Mutex::new(Some(receiver)),
+            dead_letter_callbacks: RwLock::new(Vec::new()),
+            counters: Counters::default(),
+        }))
+    }
+
+    /// Deliver `owner`'s events to `url`, replacing any earlier registration
+    pub async fn register(&self, owner: &str, url: &str, secret: &[u8]) -> Result<(), WebhookError> {
+        let parsed = Url::parse(url).map_err(|e| WebhookError::InvalidUrl(e.to_string()))?;
+        let allowed_scheme = match parsed.scheme() {
+            "https" => true,
+            "http" => self.config.allow_private_destinations,
+            _ => false,
+        };
+        if !allowed_scheme || parsed.host_str().is_none() {
+            return Err(WebhookError::InvalidUrl(format!("{} is not an https url", url)));
+        }
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(WebhookError::WeakSecret);
+        }
+        self.check_destination(&parsed).await?;
+        let mut stored = SecureBuffer::new(secret.len()).map_err(WebhookError::Secret)?;
+        stored.write(secret).map_err(WebhookError::Secret)?;
+
+        let registration = Registration { url: parsed.to_string(), secret: Arc::new(stored) };
+        self.registrations.write().unwrap().insert(owner.to_string(), registration);
+        Ok(())
+    }
+
+    /// Refuse denied ports and hosts with any non-public address
+    async fn check_destination(&self, url: &Url) -> Result<(), WebhookError> {
+        let port = url.port_or_known_default().unwrap_or(443);
+        if self.config.denied_ports.contains(&port) {
+            return Err(WebhookError::ForbiddenDestination(format!("port {} is reserved", port)));
+        }
+        if self.config.allow_private_destinations {
+            return Ok(());
+        }
+        let addrs: Vec<IpAddr> = match (literal_ip(url), url.host_str()) {
+            (Some(ip), _) => vec![ip],
+            (None, Some(domain)) => tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| WebhookError::InvalidUrl(format!("cannot resolve {}: {}", domain, e)))?
+                .map(|addr| addr.ip())
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+        match addrs.iter().find(|ip| !is_public(**ip)) {
+            Some(ip) => Err(WebhookError::ForbiddenDestination(format!("{} is not a public address", ip))),
+            None if addrs.is_empty() => Err(WebhookError::InvalidUrl(format!("{} has no addresses", url))),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop delivering to `owner`; events already queued are still sent. False if none.
+    pub fn unregister(&self, owner: &str) -> bool {
+        self.registrations.write().unwrap().remove(owner).is_some()
+    }
+
+    /// The URL `owner` registered
+    pub fn registration(&self, owner: &str) -> Option<String> {
+        self.registrations.read().unwrap().get(owner).map(|r| r.url.clone())
+    }
+
+    /// Register a callback for events that exhaust their attempts
+    pub fn on_dead_letter(&self, callback: DeadLetterCallback) {
+        self.dead_letter_callbacks.write().unwrap().push(Arc::from(callback));
+    }
+
+    /// Queue `event` for `owner`'s endpoint. False if the owner has no registration, the
+    /// dispatcher is closed or the queue is full; the last two count as dropped.
+    pub fn enqueue(&self, owner: &str, event: WebhookEvent) -> bool {
+        let (url, secret) = match self.registrations.read().unwrap().get(owner) {
+            Some(registration) => (registration.url.clone(), registration.secret.clone()),
+            None => return false,
+        };
+        let slot = match self.slots.clone().try_acquire_owned() {
+            Ok(slot) => slot,
+            Err(_) => {
+                self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+        };
+        let delivery = Delivery { owner: owner.to_string(), url, secret, event, _slot: slot };
+        let queued = match self.sender.lock().unwrap().as_ref() {
+            Some(sender) => sender.send(delivery).is_ok(),
+            None => false,
+        };
+        if !queued {
+            self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        queued
+    }
+
+    /// Start delivering queued events. The task ends after `close` once every delivery
+    /// has succeeded or been dead-lettered. Only the first call starts a worker.
+    pub fn spawn_worker(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let receiver = self.receiver.lock().unwrap().take();
+        let dispatcher = self.clone();
+        tokio::spawn(async move {
+            let Some(mut receiver) = receiver else {
+                return;
+            };
+            let mut deliveries = tokio::task::JoinSet::new();
+            loop {
+                tokio::select! {
+                    next = receiver.recv() => match next {
+                        Some(delivery) => {
+                            let dispatcher = dispatcher.clone();
+                            deliveries.spawn(async move { dispatcher.deliver(delivery).await });
+                        }
+                        None => break,
+                    },
+                    // Reap finished deliveries so the set does not grow without bound
+                    Some(_) = deliveries.join_next(), if !deliveries.is_empty() => {}
+                }
+            }
+            while deliveries.join_next().await.is_some() {}
+        })
+    }
+
+    /// Stop accepting events; the worker finishes what is queued and exits
+    pub fn close(&self) {
+        self.sender.lock().unwrap().take();
+    }
+
+    pub fn stats(&self) -> WebhookStats {
+        WebhookStats {
+            delivered: self.counters.delivered.load(Ordering::Relaxed),
+            failed_attempts: self.counters.failed_attempts.load(Ordering::Relaxed),
+            dead_lettered: self.counters.dead_lettered.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    fn breaker(&self, url: &str) -> Arc<CircuitBreaker> {
+        self.breakers
+            .lock()
+            .unwrap()
+            .entry(url.to_string())
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(self.config.breaker)))
+            .clone()
+    }
+
+    async fn deliver(&self, delivery: Delivery) {
+        let attempts = self.config.max_attempts.max(1);
+        let body = match serde_json::to_vec(&delivery.event) {
+            Ok(body) => body,
+            Err(e) => return self.dead_letter(&delivery, 0, format!("encoding event: {}", e)),
+        };
+        let signature = match sign(&delivery.secret, &body) {
+            Ok(signature) => signature,
+            Err(e) => return self.dead_letter(&delivery, 0, format!("signing event: {}", e)),
+        };
+        let breaker = self.breaker(&delivery.url);
+
+        let mut backoff = self.config.initial_backoff;
+        let mut last_error = String::new();
+        for attempt in 1..=attempts {
+            match breaker.try_acquire() {
+                Ok(()) => match self.post(&delivery, &body, &signature).await {
+                    Ok(()) => {
+                        breaker.record_success();
+                        self.counters.delivered.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    Err(e) => {
+                        breaker.record_failure();
+                        last_error = e;
+                    }
+                },
+                Err(retry_after) => last_error = format!("circuit open for another {:?}", retry_after),
+            }
+            self.counters.failed_attempts.fetch_add(1, Ordering::Relaxed);
+            if attempt < attempts {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(self.config.max_backoff);
+            }
+        }
+        self.dead_letter(&delivery, attempts, last_error);
+    }
+
+    async fn post(&self, delivery: &Delivery, body: &[u8], signature: &str) -> Result<(), String> {
+        // Names are checked by PublicResolver when connecting; literal addresses never reach it
+        if !self.config.allow_private_destinations {
+            let url = Url::parse(&delivery.url).map_err(|e| e.to_string())?;
+            if let Some(ip) = literal_ip(&url).filter(|ip| !is_public(*ip)) {
+                return Err(format!("{} is not a public address", ip));
+            }
+        }
+        let _permit = self.in_flight.acquire().await.map_err(|e| e.to_string())?;
+        let response = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .header(EVENT_HEADER, &delivery.event.kind)
+            .header(DELIVERY_HEADER, &delivery.event.id)
+            .body(body.to_vec())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("endpoint answered {}", response.status()))
+        }
+    }
+
+    fn dead_letter(&self, delivery: &Delivery, attempts: u32, error: String) {
+        self.counters.dead_lettered.fetch_add(1, Ordering::Relaxed);
+        let letter = DeadLetter {
+            owner: delivery.owner.clone(),
+            url: delivery.url.clone(),
+            event: delivery.event.clone(),
+            attempts,
+            error,
+        };
+        let callbacks = self.dead_letter_callbacks.read().unwrap().clone();
+        for callback in callbacks {
+            callback(&letter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    use wiremock::matchers::{header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SECRET: &[u8] = b"whsec_0123456789abcdef";
+
+    fn config() -> WebhookConfig {
+        WebhookConfig {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(40),
+            request_timeout: Duration::from_secs(2),
+            // The mock receivers listen on 127.0.0.1 over http
+            allow_private_destinations: true,
+            ..WebhookConfig::default()
+        }
+    }
+
+    async fn wait_for_requests(server: &MockServer, n: usize) -> Vec<wiremock::Request> {
+        for _ in 0..200 {
+            let received = server.received_requests().await.unwrap();
+            if received.len() >= n {
+                return received;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("expected {} requests", n);
+    }
+
+    async fn wait_for<F: Fn(WebhookStats) -> bool>(dispatcher: &WebhookDispatcher, done: F) -> WebhookStats {
+        for _ in 0..300 {
+            let stats = dispatcher.stats();
+            if done(stats) {
+                return stats;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("dispatcher never settled: {:?}", dispatcher.stats());
+    }
+
+    #[tokio::test]
+    async fn test_body_is_signed_with_the_registered_secret() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(header_exists(SIGNATURE_HEADER))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let dispatcher = WebhookDispatcher::new(config()).unwrap();
+        dispatcher.register("owner-1", &format!("{}/hook", server.uri()), SECRET).await.unwrap();
+        let worker = dispatcher.spawn_worker();
+        let event = WebhookEvent::verification_completed("challenge-1", true, 0.97);
+        assert!(dispatcher.enqueue("owner-1", event.clone()));
+
+        let request = wait_for_requests(&server, 1).await.remove(0);
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET).unwrap();
+        mac.update(&request.body);
+        let expected = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        assert_eq!(request.headers.get(SIGNATURE_HEADER).unwrap().to_str().unwrap(), expected);
+        assert_eq!(request.headers.get(EVENT_HEADER).unwrap().to_str().unwrap(), VERIFICATION_COMPLETED);
+        assert_eq!(request.headers.get(DELIVERY_HEADER).unwrap().to_str().unwrap(), event.id);
+        let sent: WebhookEvent = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(sent, event);
+        assert_eq!(sent.data["challenge_id"], "challenge-1");
+
+        dispatcher.close();
+        worker.await.unwrap();
+        assert_eq!(dispatcher.stats().delivered, 1);
+    }
+
+    #[tokio::test]
+    async fn test_server_error_is_retried_until_success() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(200)).mount(&server).await;
+
+        let dispatcher = WebhookDispatcher::new(config()).unwrap();
+        let letters = Arc::new(Mutex::new(Vec::new()));
+        let sink = letters.clone();
+        dispatcher.on_dead_letter(Box::new(move |letter| sink.lock().unwrap().push(letter.clone())));
+        dispatcher.register("owner-1", &server.uri(), SECRET).await.unwrap();
+        let worker = dispatcher.spawn_worker();
+        assert!(dispatcher.enqueue("owner-1", WebhookEvent::entropy_receipt_issued(serde_json::json!({"round": 7}))));
+
+        let stats = wait_for(&dispatcher, |s| s.delivered == 1).await;
+        assert_eq!(stats.failed_attempts, 2);
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 3);
+        // Every attempt carries the same event and signature
+        assert!(received.windows(2).all(|w| w[0].body == w[1].body
+            && w[0].headers.get(SIGNATURE_HEADER) == w[1].headers.get(SIGNATURE_HEADER)));
+        assert!(letters.lock().unwrap().is_empty());
+
+        dispatcher.close();
+        worker.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_event_is_dead_lettered_after_the_last_attempt() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(503)).mount(&server).await;
+
+        let dispatcher = WebhookDispatcher::new(config()).unwrap();
+        let letters = Arc::new(Mutex::new(Vec::new()));
+        let sink = letters.clone();
+        dispatcher.on_dead_letter(Box::new(move |letter| sink.lock().unwrap().push(letter.clone())));
+        dispatcher.register("owner-1", &server.uri(), SECRET).await.unwrap();
+        let worker = dispatcher.spawn_worker();
+        let event = WebhookEvent::verification_completed("challenge-2", false, 0.1);
+        assert!(dispatcher.enqueue("owner-1", event.clone()));
+
+        let stats = wait_for(&dispatcher, |s| s.dead_lettered == 1).await;
+        assert_eq!(stats.delivered, 0);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+        let letters = letters.lock().unwrap().clone();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].owner, "owner-1");
+        assert_eq!(letters[0].event, event);
+        assert_eq!(letters[0].attempts, 3);
+        assert!(letters[0].error.contains("503"), "{}", letters[0].error);
+
+        dispatcher.close();
+        worker.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_open_breaker_stops_requests_to_a_dead_endpoint_only() {
+        let dead = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(500)).mount(&dead).await;
+        let healthy = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(200)).mount(&healthy).await;
+
+        let mut config = config();
+        config.max_attempts = 4;
+        config.breaker = CircuitBreakerConfig { failure_threshold: 2, cooldown: Duration::from_secs(60), half_open_max: 1 };
+        let dispatcher = WebhookDispatcher::new(config).unwrap();
+        dispatcher.register("dead", &dead.uri(), SECRET).await.unwrap();
+        dispatcher.register("healthy", &healthy.uri(), SECRET).await.unwrap();
+        let worker = dispatcher.spawn_worker();
+
+        for i in 0..3 {
+            assert!(dispatcher.enqueue("dead", WebhookEvent::verification_completed(&format!("d{}", i), true, 1.0)));
+        }
+        assert!(dispatcher.enqueue("healthy", WebhookEvent::verification_completed("h", true, 1.0)));
+
+        let stats = wait_for(&dispatcher, |s| s.dead_lettered == 3 && s.delivered == 1).await;
+        assert_eq!(stats.failed_attempts, 12);
+        // Once two requests failed the breaker answered for the dead endpoint
+        assert!(dead.received_requests().await.unwrap().len() <= 3);
+        assert_eq!(healthy.received_requests().await.unwrap().len(), 1);
+
+        dispatcher.close();
+        worker.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_needs_a_registration_and_a_free_slot() {
+        let dispatcher = WebhookDispatcher::new(WebhookConfig { queue_capacity: 2, ..config() }).unwrap();
+        assert!(!dispatcher.enqueue("nobody", WebhookEvent::verification_completed("c", true, 1.0)));
+        assert_eq!(dispatcher.stats().dropped, 0);
+
+        dispatcher.register("owner-1", "http://127.0.0.1:9/hook", SECRET).await.unwrap();
+        assert!(dispatcher.enqueue("owner-1", WebhookEvent::verification_completed("c1", true, 1.0)));
+        assert!(dispatcher.enqueue("owner-1", WebhookEvent::verification_completed("c2", true, 1.0)));
+        assert!(!dispatcher.enqueue("owner-1", WebhookEvent::verification_completed("c3", true, 1.0)));
+        assert_eq!(dispatcher.stats().dropped, 1);
+
+        assert!(dispatcher.unregister("owner-1"));
+        assert!(!dispatcher.unregister("owner-1"));
+    }
+
+    #[tokio::test]
+    async fn test_registration_is_validated() {
+        let dispatcher = WebhookDispatcher::new(WebhookConfig { denied_ports: vec![8444], ..WebhookConfig::default() }).unwrap();
+        assert!(matches!(dispatcher.register("o", "not a url", SECRET).await, Err(WebhookError::InvalidUrl(_))));
+        assert!(matches!(dispatcher.register("o", "ftp://203.0.113.7/x", SECRET).await, Err(WebhookError::InvalidUrl(_))));
+        assert!(matches!(dispatcher.register("o", "http://203.0.113.7/x", SECRET).await, Err(WebhookError::InvalidUrl(_))));
+        assert_eq!(dispatcher.register("o", "https://203.0.113.7/x", b"short").await, Err(WebhookError::WeakSecret));
+        dispatcher.register("o", "https://203.0.113.7/x", SECRET).await.unwrap();
+        assert_eq!(dispatcher.registration("o").as_deref(), Some("https://203.0.113.7/x"));
+        // A NAT64 address is judged by the IPv4 address it carries
+        dispatcher.register("o", "https://[64:ff9b::203.0.113.7]/x", SECRET).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_internal_destinations_are_refused() {
+        let dispatcher = WebhookDispatcher::new(WebhookConfig { denied_ports: vec![8444], ..WebhookConfig::default() }).unwrap();
+        for url in [
+            "https://127.0.0.1/hook",
+            "https://localhost/hook",
+            "https://169.254.169.254/latest/meta-data/",
+            "https://10.1.2.3/hook",
+            "https://172.16.0.1/hook",
+            "https://192.168.1.1/hook",
+            "https://100.64.0.1/hook",
+            "https://0.0.0.0/hook",
+            "https://[::1]/hook",
+            "https://[fd00::1]/hook",
+            "https://[::ffff:127.0.0.1]/hook",
+            "https://[::127.0.0.1]/hook",
+            "https://[::10.1.2.3]/hook",
+            "https://[64:ff9b::169.254.169.254]/hook",
+            "https://[64:ff9b::192.168.1.1]/hook",
+            "https://203.0.113.7:8444/hook",
+        ] {
+            let refused = dispatcher.register("o", url, SECRET).await;
+            assert!(matches!(refused, Err(WebhookError::ForbiddenDestination(_))), "{} gave {:?}", url, refused);
+        }
+        assert!(dispatcher.registration("o").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_rechecks_the_address_it_connects_to() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(200)).mount(&server).await;
+
+        let dispatcher = WebhookDispatcher::new(WebhookConfig { allow_private_destinations: false, ..config() }).unwrap();
+        let letters = Arc::new(Mutex::new(Vec::new()));
+        let sink = letters.clone();
+        dispatcher.on_dead_letter(Box::new(move |letter| sink.lock().unwrap().push(letter.clone())));
+        // As if the names had resolved to a public address when they were registered
+        let port = server.address().port();
+        for (owner, url) in [("by-name", format!("http://localhost:{}/", port)), ("by-ip", format!("http://127.0.0.1:{}/", port))] {
+            let mut secret = SecureBuffer::new(SECRET.len()).unwrap();
+            secret.write(SECRET).unwrap();
+            let registration = Registration { url, secret: Arc::new(secret) };
+            dispatcher.registrations.write().unwrap().insert(owner.to_string(), registration);
+        }
+        let worker = dispatcher.spawn_worker();
+        assert!(dispatcher.enqueue("by-name", WebhookEvent::verification_completed("c1", true, 1.0)));
+        assert!(dispatcher.enqueue("by-ip", WebhookEvent::verification_completed("c2", true, 1.0)));
+
+        wait_for(&dispatcher, |s| s.dead_lettered == 2).await;
+        // The receiver answers 200, so nothing reaching it means both were stopped before connecting
+        assert!(server.received_requests().await.unwrap().is_empty());
+        let letters = letters.lock().unwrap().clone();
+        let by_ip = letters.iter().find(|letter| letter.owner == "by-ip").unwrap();
+        assert!(by_ip.error.contains("not a public address"), "{}", by_ip.error);
+
+        dispatcher.close();
+        worker.await.unwrap();
+    }
+}
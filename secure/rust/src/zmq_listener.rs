@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: MIT
+// bitcoind ZMQ subscriber: rawblock/rawtx payloads are validated by TurboValidator
+// and accepted blocks are loaded into the UniversalBloomFilter
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use prometheus::{IntCounter, IntCounterVec, Opts, Registry};
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+use turbo_validator::{TurboValidator, ValidationError};
+use zeromq::{Socket, SocketEvent, SocketRecv, SubSocket, ZmqMessage};
+
+use crate::bloom_filter::{BlockData, BloomFilterError, TransactionId, UniversalBloomFilter};
+
+pub const TOPIC_RAWBLOCK: &str = "rawblock";
+pub const TOPIC_RAWTX: &str = "rawtx";
+
+#[derive(Debug, Error)]
+pub enum ZmqListenerError {
+    #[error("Validation failed: {0}")]
+    Validation(#[from] ValidationError),
+    #[error("Payload rejected: {0}")]
+    Parse(#[from] BloomFilterError),
+}
+
+/// What a single notification turned into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZmqOutcome {
+    /// Block accepted; its txids were added to the bloom filter
+    Block { txids: usize },
+    Transaction,
+    /// Topic we did not subscribe to
+    Ignored,
+}
+
+/// Receives every transaction accepted from the rawtx topic
+pub trait TransactionSink: Send + Sync {
+    fn accept_transaction(&self, txid: &TransactionId, vsize: usize);
+}
+
+/// Receives every block accepted from the rawblock topic, after it was loaded into the bloom filter
+pub trait BlockSink: Send + Sync {
+    fn accept_block(&self, block: &BlockData, size: usize);
+}
+
+/// Counters for the listener, registered by the caller
+#[derive(Clone)]
+pub struct ZmqMetrics {
+    blocks: IntCounter,
+    invalid: IntCounterVec,
+}
+
+impl ZmqMetrics {
+    pub fn new() -> prometheus::Result<Self> {
+        Ok(ZmqMetrics {
+            blocks: IntCounter::with_opts(Opts::new(
+                "sprint_zmq_blocks_total",
+                "Blocks received over ZMQ that passed validation",
+            ))?,
+            invalid: IntCounterVec::new(
+                Opts::new("sprint_zmq_invalid_total", "ZMQ payloads rejected by validation"),
+                &["topic"],
+            )?,
+        })
+    }
+
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.blocks.clone()))?;
+        registry.register(Box::new(self.invalid.clone()))?;
+        Ok(())
+    }
+
+    pub fn blocks_total(&self) -> u64 {
+        self.blocks.get()
+    }
+
+    pub fn invalid_total(&self, topic: &str) -> u64 {
+        self.invalid.with_label_values(&[topic]).get()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ZmqListenerConfig {
+    /// bitcoind zmqpubrawblock/zmqpubrawtx endpoint, e.g. tcp://127.0.0.1:28332
+    pub endpoint: String,
+    /// First reconnect delay; doubles on every consecutive failure
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl ZmqListenerConfig {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        ZmqListenerConfig {
+            endpoint: endpoint.into(),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Exponential backoff capped at `max`
+#[derive(Debug)]
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    fn new(initial: Duration, max: Duration) -> Self {
+        Backoff { initial, max, current: initial }
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
+
+pub struct ZmqListener {
+    config: ZmqListenerConfig,
+    validator: Arc<TurboValidator>,
+    bloom: Arc<UniversalBloomFilter>,
+    metrics: ZmqMetrics,
+    tx_sink: Option<Arc<dyn TransactionSink>>,
+    block_sink: Option<Arc<dyn BlockSink>>,
+}
+
+impl ZmqListener {
+    pub fn new(
+        config: ZmqListenerConfig,
+        validator: Arc<TurboValidator>,
+        bloom: Arc<UniversalBloomFilter>,
+        metrics: ZmqMetrics,
+    ) -> Self {
+        ZmqListener { config, validator, bloom, metrics, tx_sink: None, block_sink: None }
+    }
+
+    /// Hand accepted transactions to `sink`, e.g. a mempool tracker
+    pub fn with_transaction_sink(mut self, sink: Arc<dyn TransactionSink>) -> Self {
+        self.tx_sink = Some(sink);
+        self
+    }
+
+    /// Hand accepted blocks to `sink`, e.g. a subscription broadcaster
+    pub fn with_block_sink(mut self, sink: Arc<dyn BlockSink>) -> Self {
+        self.block_sink = Some(sink);
+        self
+    }
+
+    /// Validate one notification and, for blocks, load its txids into the bloom filter.
+    /// Rejected payloads are counted under `sprint_zmq_invalid_total{topic}`.
+    pub fn handle_notification(&self, topic: &str, body: &[u8]) -> Result<ZmqOutcome, ZmqListenerError> {
+        let result = match topic {
+            TOPIC_RAWBLOCK => self.accept_block(body),
+            TOPIC_RAWTX => self.accept_transaction(body),
+            _ => return Ok(ZmqOutcome::Ignored),
+        };
+        if result.is_err() {
+            self.metrics.invalid.with_label_values(&[topic]).inc();
+        }
+        result
+    }
+
+    fn accept_block(&self, body: &[u8]) -> Result<ZmqOutcome, ZmqListenerError> {
+        self.validator.validate_block(body)?;
+        let block = BlockData::from_bitcoin_bytes(self.bloom.network_name(), body)?;
+        self.bloom.load_block(&block)?;
+        self.metrics.blocks.inc();
+        if let Some(sink) = &self.block_sink {
+            sink.accept_block(&block, body.len());
+        }
+        Ok(ZmqOutcome::Block { txids: block.transactions.len() })
+    }
+
+    fn accept_transaction(&self, body: &[u8]) -> Result<ZmqOutcome, ZmqListenerError> {
+        self.validator.validate_transaction(body)?;
+        let (txid, vsize) = TransactionId::from_bitcoin_tx_bytes(self.bloom.network_name(), body)?;
+        if let Some(sink) = &self.tx_sink {
+            sink.accept_transaction(&txid, vsize);
+        }
+        Ok(ZmqOutcome::Transaction)
+    }
+
+    /// Subscribe and process notifications until `shutdown` is cancelled, reconnecting
+    /// with exponential backoff whenever the connection cannot be made or is lost
+    pub async fn run(self, shutdown: CancellationToken) {
+        let mut backoff = Backoff::new(self.config.initial_backoff, self.config.max_backoff);
+        loop {
+            let session = tokio::select! {
+                _ = shutdown.cancelled() => return,
+                session = self.session(&mut backoff) => session,
+            };
+            let delay = backoff.next_delay();
+            match session {
+                Ok(()) => info!("ZMQ publisher at {} went away; reconnecting in {:?}", self.config.endpoint, delay),
+                Err(e) => warn!("ZMQ subscription to {} failed: {}; retrying in {:?}", self.config.endpoint, e, delay),
+            }
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+
+    /// One connection's lifetime. Returns Ok when the publisher disconnects.
+    async fn session(&self, backoff: &mut Backoff) -> Result<(), zeromq::ZmqError> {
+        let mut socket = SubSocket::new();
+        let mut events = socket.monitor();
+        socket.connect(&self.config.endpoint).await?;
+        socket.subscribe(TOPIC_RAWBLOCK).await?;
+        socket.subscribe(TOPIC_RAWTX).await?;
+        info!("Subscribed to bitcoind ZMQ at {}", self.config.endpoint);
+
+        loop {
+            tokio::select! {
+                message = socket.recv() => {
+                    self.dispatch(message?);
+                    // Only a working subscription earns a fresh backoff
+                    backoff.reset();
+                }
+                event = events.next() => match event {
+                    Some(SocketEvent::Disconnected(_)) | None => return Ok(()),
+                    Some(_) => {}
+                },
+            }
+        }
+    }
+
+    fn dispatch(&self, message: ZmqMessage) {
+        // bitcoind sends [topic, body, 4-byte sequence]
+        let (topic, body) = match (message.get(0), message.get(1)) {
+            (Some(topic), Some(body)) => (String::from_utf8_lossy(&topic[..]).into_owned(), &body[..]),
+            _ => {
+                debug!("Dropping ZMQ message with {} frames", message.len());
+                return;
+            }
+        };
+        match self.handle_notification(&topic, body) {
+            Ok(ZmqOutcome::Block { txids }) => debug!("Loaded block with {} txids from ZMQ", txids),
+            Ok(_) => {}
+            Err(e) => warn!("Rejected ZMQ {} payload: {}", topic, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bloom_filter::BlockchainHash;
+    use turbo_validator::{PQCPolicy, PqcKeyring};
+    use zeromq::{PubSocket, SocketSend};
+
+    /// Testnet3 genesis block; its only txid is 4a5e1e4b...
+    const TESTNET_GENESIS_BLOCK: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4adae5494dffff001d1aa4ae180101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    fn listener(endpoint: &str) -> (ZmqListener, Arc<UniversalBloomFilter>, ZmqMetrics) {
+        // bitcoind payloads carry no PQC envelope
+        let policy = PQCPolicy { kyber_enabled: false, dilithium_enabled: false, ..PQCPolicy::default() };
+        let validator = Arc::new(TurboValidator::with_pqc(policy, PqcKeyring::default()));
+        let bloom = Arc::new(UniversalBloomFilter::new(None).unwrap());
+        let metrics = ZmqMetrics::new().unwrap();
+        let mut config = ZmqListenerConfig::new(endpoint);
+        config.initial_backoff = Duration::from_millis(10);
+        config.max_backoff = Duration::from_millis(50);
+        (ZmqListener::new(config, validator, bloom.clone(), metrics.clone()), bloom, metrics)
+    }
+
+    fn notification(topic: &str, body: Vec<u8>, sequence: u32) -> ZmqMessage {
+        let mut message = ZmqMessage::from(topic);
+        message.push_back(body.into());
+        message.push_back(sequence.to_le_bytes().to_vec().into());
+        message
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_millis(350));
+        let delays: Vec<_> = (0..4).map(|_| backoff.next_delay().as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 350, 350]);
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    }
+
+    #[derive(Default)]
+    struct RecordingSink(std::sync::Mutex<Vec<(Vec<u8>, usize)>>);
+
+    impl TransactionSink for RecordingSink {
+        fn accept_transaction(&self, txid: &TransactionId, vsize: usize) {
+            self.0.lock().unwrap().push((txid.hash.clone(), vsize));
+        }
+    }
+
+    #[test]
+    fn test_rawtx_reaches_transaction_sink() {
+        let sink = Arc::new(RecordingSink::default());
+        let (listener, _bloom, metrics) = listener("tcp://127.0.0.1:1");
+        let listener = listener.with_transaction_sink(sink.clone());
+
+        // The genesis coinbase, i.e. the block minus its header and tx count
+        let coinbase = hex::decode(TESTNET_GENESIS_BLOCK).unwrap()[81..].to_vec();
+        assert_eq!(listener.handle_notification(TOPIC_RAWTX, &coinbase).unwrap(), ZmqOutcome::Transaction);
+        assert!(listener.handle_notification(TOPIC_RAWTX, &coinbase[..40]).is_err());
+        assert!(listener.handle_notification(TOPIC_RAWTX, &[]).is_err());
+
+        let recorded = sink.0.lock().unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].1, coinbase.len());
+        assert_eq!(metrics.invalid_total(TOPIC_RAWTX), 2);
+    }
+
+    #[test]
+    fn test_metrics_register_once() {
+        let registry = Registry::new();
+        let metrics = ZmqMetrics::new().unwrap();
+        metrics.register(&registry).unwrap();
+        assert!(metrics.register(&registry).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_publisher_feeds_validator_and_bloom() {
+        let mut publisher = PubSocket::new();
+        let endpoint = publisher.bind("tcp://127.0.0.1:0").await.unwrap().to_string();
+        let (listener, bloom, metrics) = listener(&endpoint);
+        let shutdown = CancellationToken::new();
+        let task = tokio::spawn(listener.run(shutdown.clone()));
+
+        let block = hex::decode(TESTNET_GENESIS_BLOCK).unwrap();
+        let txid = BlockData::from_bitcoin_bytes("bitcoin", &block).unwrap().transactions[0].clone();
+        assert!(!bloom.contains_data(txid.as_bytes()).unwrap());
+
+        // PUB drops messages until the subscription has propagated, so resend until seen
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        let mut sequence = 0;
+        while metrics.blocks_total() == 0 || metrics.invalid_total(TOPIC_RAWBLOCK) == 0 {
+            assert!(tokio::time::Instant::now() < deadline, "listener never processed the notifications");
+            if metrics.blocks_total() == 0 {
+                publisher.send(notification(TOPIC_RAWBLOCK, block.clone(), sequence)).await.unwrap();
+            }
+            if metrics.invalid_total(TOPIC_RAWBLOCK) == 0 {
+                publisher.send(notification(TOPIC_RAWBLOCK, Vec::new(), sequence + 1)).await.unwrap();
+            }
+            sequence += 2;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        assert_eq!(metrics.blocks_total(), 1);
+        assert_eq!(metrics.invalid_total(TOPIC_RAWBLOCK), 1);
+        assert_eq!(metrics.invalid_total(TOPIC_RAWTX), 0);
+        assert!(bloom.contains_data(txid.as_bytes()).unwrap());
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(2), task).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_publisher_restart() {
+        // Nothing listening yet: the listener keeps backing off until the publisher appears
+        let probe = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("tcp://{}", probe.local_addr().unwrap());
+        drop(probe);
+        let (listener, _bloom, metrics) = listener(&endpoint);
+        let shutdown = CancellationToken::new();
+        let task = tokio::spawn(listener.run(shutdown.clone()));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut publisher = PubSocket::new();
+        publisher.bind(&endpoint).await.unwrap();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while metrics.invalid_total(TOPIC_RAWTX) == 0 {
+            assert!(tokio::time::Instant::now() < deadline, "listener never reconnected");
+            publisher.send(notification(TOPIC_RAWTX, Vec::new(), 0)).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(2), task).await.unwrap().unwrap();
+    }
+}
@@ -7,6 +7,7 @@
 // Re-export key functions for easy access
 pub use netkit::{
     connect_happy,
+    connect_happy_with,
     connect_tls,
     connect_tuned,
     read_exact_deadline,
@@ -14,4 +15,11 @@
     pad_frame,
     TlsStream,
     tls_connector,
+    ConnectError,
+    ConnectOpts,
+    FrameCodec,
+    PooledTls,
+    TlsPool,
+    TlsPoolConfig,
+    TlsPoolStats,
 };
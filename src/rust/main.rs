@@ -4,7 +4,6 @@
 
 use bitcoin_sprint_storage_verifier::netkit;
 use std::time::Duration;
-use tokio;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -1,24 +1,31 @@
 // netkit.rs
 // Drop-in networking helpers for Bitcoin Sprint
-// - Happy-Eyeballs dial (fast IPv4/IPv6)
+// - Happy-Eyeballs dial (RFC 8305: interleaved IPv6/IPv4, staggered racing)
 // - Tuned TCP (nodelay, keepalive, user-timeout*)
 // - TLS connector (rustls, TLS1.3-only, ALPN, session cache)
 // - Read/Write deadlines (bound I/O)
+// - Per-host TLS keep-alive pool
+// - Length-prefixed, padded frame codec
 // SPDX-License-Identifier: MIT
 
 #![allow(clippy::needless_return)]
 
 use anyhow::{anyhow, Context, Result};
-use futures::{stream, StreamExt};
-use socket2::{Domain, Socket, TcpKeepalive, Type};
+use futures::stream::{FuturesUnordered, StreamExt};
+use socket2::{Domain, SockRef, Socket, TcpKeepalive, Type};
+use std::collections::HashMap;
+use std::fmt;
+use std::mem::MaybeUninit;
 use std::net::SocketAddr;
-use std::sync::Arc;
-use std::time::Duration;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::{lookup_host, TcpStream};
 
 // --- TLS (rustls + tokio-rustls) ---
-use rustls::{client::ClientSessionMemoryCache, ClientConfig, RootCertStore};
+use rustls::{client::Resumption, ClientConfig, RootCertStore};
 use rustls::pki_types::ServerName;
 use rustls_native_certs;
 use tokio_rustls::{client::TlsStream as TokioTlsStream, TlsConnector};
@@ -28,7 +35,65 @@
 // ------------------------------------------------------------
 // 1) Happy-Eyeballs connect with tuned socket options
 // ------------------------------------------------------------
+/// Dial options for `connect_happy_with`
+#[derive(Debug, Clone)]
+pub struct ConnectOpts {
+    stagger: Duration,
+    attempt_timeout: Duration,
+}
+
+impl Default for ConnectOpts {
+    fn default() -> Self {
+        Self {
+            stagger: Duration::from_millis(250),
+            attempt_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ConnectOpts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delay before starting the next address while earlier attempts are still pending
+    /// (RFC 8305 "Connection Attempt Delay", default 250ms)
+    pub fn with_stagger(mut self, stagger: Duration) -> Self {
+        self.stagger = stagger;
+        self
+    }
+
+    /// Bound on each individual connect attempt (default 10s)
+    pub fn with_attempt_timeout(mut self, timeout: Duration) -> Self {
+        self.attempt_timeout = timeout;
+        self
+    }
+}
+
+/// Every address tried by a failed happy-eyeballs dial, with its individual error
+#[derive(Debug)]
+pub struct ConnectError {
+    pub target: String,
+    pub attempts: Vec<(SocketAddr, anyhow::Error)>,
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "all {} connect attempts to {} failed", self.attempts.len(), self.target)?;
+        for (sa, e) in &self.attempts {
+            write!(f, "; {}: {:#}", sa, e)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConnectError {}
+
 pub async fn connect_happy(addr: &str, timeout: Duration) -> Result<TcpStream> {
+    connect_happy_with(addr, &ConnectOpts::default().with_attempt_timeout(timeout)).await
+}
+
+pub async fn connect_happy_with(addr: &str, opts: &ConnectOpts) -> Result<TcpStream> {
     // addr can be "host:port" or an IP:port
     let addrs: Vec<SocketAddr> =
         lookup_host(addr).await.with_context(|| format!("DNS lookup failed for {}", addr))?
@@ -38,18 +103,60 @@ pub async fn connect_happy(addr: &str, timeout: Duration) -> Result<TcpStream> {
         return Err(anyhow!("DNS returned no records for {}", addr));
     }
 
-    // Staggered parallel attempts (up to 4 in-flight)
-    let attempts = addrs.into_iter().map(move |sa| async move {
-        connect_tuned(sa, timeout).await
-    });
+    race_connect(addr, interleave_families(addrs), opts).await
+}
+
+/// Order addresses IPv6-first, alternating families (RFC 8305 section 4)
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let (v6, v4): (Vec<_>, Vec<_>) = addrs.into_iter().partition(|sa| sa.is_ipv6());
+    let mut out = Vec::with_capacity(v6.len() + v4.len());
+    let (mut v6, mut v4) = (v6.into_iter(), v4.into_iter());
+    loop {
+        match (v6.next(), v4.next()) {
+            (None, None) => break,
+            (a, b) => out.extend(a.into_iter().chain(b)),
+        }
+    }
+    out
+}
 
-    let mut s = stream::iter(attempts).buffer_unordered(4);
-    while let Some(res) = s.next().await {
-        if let Ok(tcp) = res {
-            return Ok(tcp);
+/// Start attempts in order, one per stagger interval (or immediately after a failure),
+/// return the first socket to connect and drop the rest.
+async fn race_connect(target: &str, addrs: Vec<SocketAddr>, opts: &ConnectOpts) -> Result<TcpStream> {
+    let attempt_timeout = opts.attempt_timeout;
+    let start = |sa: SocketAddr| async move { (sa, connect_tuned(sa, attempt_timeout).await) };
+
+    let mut pending = addrs.into_iter();
+    let mut in_flight = FuturesUnordered::new();
+    let mut failures = Vec::new();
+    in_flight.extend(pending.next().map(start));
+
+    let next_start = tokio::time::sleep(opts.stagger);
+    tokio::pin!(next_start);
+
+    loop {
+        tokio::select! {
+            Some((sa, res)) = in_flight.next(), if !in_flight.is_empty() => match res {
+                Ok(tcp) => return Ok(tcp),
+                Err(e) => {
+                    failures.push((sa, e));
+                    if let Some(sa) = pending.next() {
+                        in_flight.push(start(sa));
+                        next_start.as_mut().reset(tokio::time::Instant::now() + opts.stagger);
+                    } else if in_flight.is_empty() {
+                        break;
+                    }
+                }
+            },
+            _ = &mut next_start, if pending.len() > 0 => {
+                in_flight.extend(pending.next().map(start));
+                next_start.as_mut().reset(tokio::time::Instant::now() + opts.stagger);
+            }
+            else => break,
         }
     }
-    Err(anyhow!("All connect attempts failed"))
+
+    Err(ConnectError { target: target.to_string(), attempts: failures }.into())
 }
 
 /// Connect a single SocketAddr with tuned TCP options and a bounded timeout.
@@ -70,18 +177,13 @@ pub async fn connect_tuned(sa: SocketAddr, timeout: Duration) -> Result<TcpStrea
     #[cfg(any(target_os = "linux", target_os = "android"))]
     socket.set_tcp_user_timeout(Some(Duration::from_secs(20))).ok();
 
-    socket
-        .connect(&sa.into())
-        .or_else(|e| if e.kind() == std::io::ErrorKind::WouldBlock { Ok(()) } else { Err(e) })
-        .with_context(|| format!("connect initiate {}", sa))?;
-
-    let stream = TcpStream::from_std(socket.into()).context("adopt std socket")?;
-
-    // Wait until writable or timeout (connect completion)
-    tokio::time::timeout(timeout, stream.writable())
+    // Tokio drives the nonblocking connect: it treats EINPROGRESS as pending, waits for
+    // writability and then checks SO_ERROR, so refused/unreachable peers surface here
+    let socket = tokio::net::TcpSocket::from_std_stream(socket.into());
+    let stream = tokio::time::timeout(timeout, socket.connect(sa))
         .await
         .context("connect timeout")?
-        .context("connect not writable")?;
+        .with_context(|| format!("connect {}", sa))?;
 
     Ok(stream)
 }
@@ -97,16 +199,14 @@ pub fn tls_connector(custom_roots: Option<RootCertStore>) -> Result<TlsConnector
         load_native_roots().context("load native roots")?
     };
 
-    // rustls 0.22 config builder
-    #[cfg(feature = "rustls-ring")] // optional, but ring is default provider in many setups
+    // rustls 0.22 config builder; ring is its default provider.
+    // TLS 1.3 only, as this connector has always been: 0.22 made ClientConfig::versions
+    // private, so the restriction moves into the builder (use
+    // with_safe_default_protocol_versions if you must allow TLS 1.2)
     let provider = rustls::crypto::ring::default_provider();
-
-    #[cfg(not(feature = "rustls-ring"))]
-    let provider = rustls::crypto::ring::default_provider(); // keep ring as default even w/o feature
-
     let mut cfg = ClientConfig::builder_with_provider(provider.into())
-        .with_safe_default_protocol_versions()
-        .expect("safe default protocol versions")
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .context("TLS 1.3 with the ring provider")?
         .with_root_certificates(roots)
         .with_no_client_auth();
 
@@ -114,20 +214,21 @@ pub fn tls_connector(custom_roots: Option<RootCertStore>) -> Result<TlsConnector
     cfg.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
 
     // Session cache (resume → fewer handshakes)
-    cfg.session_storage = Arc::new(ClientSessionMemoryCache::new(256));
-
-    // TLS 1.3 only (comment out if you must allow TLS 1.2)
-    cfg.versions = vec![&rustls::version::TLS13];
+    cfg.resumption = Resumption::in_memory_sessions(256);
 
     Ok(TlsConnector::from(Arc::new(cfg)))
 }
 
 pub async fn connect_tls(domain: &str, port: u16, timeout: Duration) -> Result<TlsStream> {
+    let connector = tls_connector(None)?;
+    dial_tls(&connector, domain, port, timeout).await
+}
+
+async fn dial_tls(connector: &TlsConnector, domain: &str, port: u16, timeout: Duration) -> Result<TlsStream> {
     let addr = format!("{}:{}", domain, port);
     let tcp = connect_happy(&addr, timeout).await?;
     tcp.set_nodelay(true).ok();
 
-    let connector = tls_connector(None)?;
     let server_name = ServerName::try_from(domain.to_string())
         .map_err(|_| anyhow!("invalid DNS name for SNI: {}", domain))?;
 
@@ -142,10 +243,8 @@ pub async fn connect_tls(domain: &str, port: u16, timeout: Duration) -> Result<T
 fn load_native_roots() -> Result<RootCertStore> {
     let mut store = RootCertStore::empty();
     for cert in rustls_native_certs::load_native_certs().context("native certs")? {
-        // Some platforms need a conversion step; try_into handles both cases.
-        store
-            .add(cert.try_into().map_err(|_| anyhow!("bad cert der"))?)
-            .ok();
+        // Skip certificates rustls cannot parse rather than failing the whole store
+        store.add(cert).ok();
     }
     Ok(store)
 }
@@ -176,7 +275,7 @@ pub async fn write_all_deadline<S>(s: &mut S, buf: &[u8], t: Duration) -> Result
 }
 
 // ------------------------------------------------------------
-// 4) (Optional) frame padding helper to smooth traffic bursts
+// 4) Framing: padding helper to smooth traffic bursts, and a codec that uses it
 // ------------------------------------------------------------
 pub fn pad_frame(mut msg: Vec<u8>, multiple: usize) -> Vec<u8> {
     if multiple == 0 {
@@ -184,11 +283,361 @@ pub fn pad_frame(mut msg: Vec<u8>, multiple: usize) -> Vec<u8> {
     }
     let pad = (multiple - (msg.len() % multiple)) % multiple;
     if pad > 0 {
-        msg.extend(std::iter::repeat(0u8).take(pad));
+        msg.extend(std::iter::repeat_n(0u8, pad));
     }
     msg
 }
 
+const FRAME_HEADER_LEN: usize = 4;
+
+/// Frames are a u32 big-endian payload length, the payload, then zero padding so the
+/// whole frame (header included) is a multiple of `block_size`.
+#[derive(Debug, Clone)]
+pub struct FrameCodec {
+    block_size: usize,
+    max_frame_size: usize,
+    io_timeout: Duration,
+}
+
+impl Default for FrameCodec {
+    fn default() -> Self {
+        Self {
+            block_size: 128,
+            max_frame_size: 16 * 1024 * 1024,
+            io_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl FrameCodec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pad frames to a multiple of this many bytes (0 or 1 disables padding; default 128)
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    /// Largest payload accepted in either direction (default 16 MiB)
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Deadline for each read or write of a frame part (default 30s)
+    pub fn with_io_timeout(mut self, io_timeout: Duration) -> Self {
+        self.io_timeout = io_timeout;
+        self
+    }
+
+    pub async fn write_frame<S>(&self, s: &mut S, payload: &[u8]) -> Result<()>
+    where
+        S: AsyncWrite + Unpin,
+    {
+        if payload.len() > self.max_frame_size {
+            return Err(anyhow!("frame of {} bytes exceeds max {}", payload.len(), self.max_frame_size));
+        }
+        let len = u32::try_from(payload.len()).map_err(|_| anyhow!("frame too large for u32 length"))?;
+
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len() + self.block_size);
+        frame.extend_from_slice(&len.to_be_bytes());
+        frame.extend_from_slice(payload);
+        let frame = pad_frame(frame, self.block_size);
+
+        write_all_deadline(s, &frame, self.io_timeout).await?;
+        tokio::time::timeout(self.io_timeout, s.flush())
+            .await
+            .context("flush timeout")?
+            .context("flush failed")?;
+        Ok(())
+    }
+
+    pub async fn read_frame<S>(&self, s: &mut S) -> Result<Vec<u8>>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        read_exact_deadline(s, &mut header, self.io_timeout).await?;
+        let len = u32::from_be_bytes(header) as usize;
+
+        // Check before allocating: the length comes straight from the peer
+        if len > self.max_frame_size {
+            return Err(anyhow!("peer announced {} byte frame, max is {}", len, self.max_frame_size));
+        }
+
+        let body_len = self.padded_len(FRAME_HEADER_LEN + len) - FRAME_HEADER_LEN;
+        let mut body = vec![0u8; body_len];
+        read_exact_deadline(s, &mut body, self.io_timeout).await?;
+
+        if body[len..].iter().any(|&b| b != 0) {
+            return Err(anyhow!("non-zero frame padding"));
+        }
+        body.truncate(len);
+        Ok(body)
+    }
+
+    fn padded_len(&self, len: usize) -> usize {
+        if self.block_size <= 1 {
+            return len;
+        }
+        len.div_ceil(self.block_size) * self.block_size
+    }
+}
+
+// ------------------------------------------------------------
+// 5) Per-host TLS keep-alive pool
+// ------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub struct TlsPoolConfig {
+    /// Open connections (idle + checked out) allowed per (host, port)
+    pub max_per_host: usize,
+    /// Open connections allowed across all hosts
+    pub max_total: usize,
+    /// Idle connections older than this are closed instead of reused
+    pub idle_timeout: Duration,
+    /// Dial + handshake bound for new connections
+    pub connect_timeout: Duration,
+}
+
+impl Default for TlsPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_per_host: 8,
+            max_total: 64,
+            idle_timeout: Duration::from_secs(90),
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TlsPoolStats {
+    /// Checkouts served by an idle connection
+    pub hits: u64,
+    /// Checkouts that dialed a new connection
+    pub misses: u64,
+    /// Idle connections closed for age, a dead peer, or to make room under max_total
+    pub evictions: u64,
+}
+
+type HostKey = (String, u16);
+
+struct IdleTls {
+    stream: TlsStream,
+    since: Instant,
+}
+
+#[derive(Default)]
+struct PoolState {
+    idle: HashMap<HostKey, Vec<IdleTls>>,
+    /// Idle + checked out, per host
+    open: HashMap<HostKey, usize>,
+    total: usize,
+}
+
+impl PoolState {
+    fn release(&mut self, key: &HostKey) {
+        if let Some(n) = self.open.get_mut(key) {
+            *n -= 1;
+            if *n == 0 {
+                self.open.remove(key);
+            }
+        }
+        self.total -= 1;
+    }
+}
+
+struct PoolInner {
+    cfg: TlsPoolConfig,
+    connector: TlsConnector,
+    state: Mutex<PoolState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// Caches established TLS sessions per (host, port) and hands them out behind a guard
+/// that returns them on drop. Dials go through `connect_happy`.
+#[derive(Clone)]
+pub struct TlsPool {
+    inner: Arc<PoolInner>,
+}
+
+impl TlsPool {
+    pub fn new(cfg: TlsPoolConfig, custom_roots: Option<RootCertStore>) -> Result<Self> {
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                cfg,
+                connector: tls_connector(custom_roots)?,
+                state: Mutex::new(PoolState::default()),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                evictions: AtomicU64::new(0),
+            }),
+        })
+    }
+
+    /// Reuse an idle connection to domain:port, or dial a new one within the limits.
+    pub async fn get(&self, domain: &str, port: u16) -> Result<PooledTls> {
+        let key: HostKey = (domain.to_string(), port);
+        {
+            let mut state = self.inner.state.lock().unwrap();
+            self.evict_expired_locked(&mut state);
+
+            while let Some(conn) = state.idle.get_mut(&key).and_then(|idle| idle.pop()) {
+                if is_usable(&conn.stream) {
+                    self.inner.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(self.guard(key, conn.stream));
+                }
+                state.release(&key);
+                self.inner.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+
+            let per_host = state.open.get(&key).copied().unwrap_or(0);
+            if per_host >= self.inner.cfg.max_per_host {
+                return Err(anyhow!("TLS pool: {} connections open to {}:{}", per_host, domain, port));
+            }
+            if state.total >= self.inner.cfg.max_total && !self.evict_oldest_locked(&mut state) {
+                return Err(anyhow!("TLS pool: {} connections open in total", state.total));
+            }
+
+            // Reserve the slot before dialing so concurrent callers see it
+            *state.open.entry(key.clone()).or_insert(0) += 1;
+            state.total += 1;
+        }
+
+        self.inner.misses.fetch_add(1, Ordering::Relaxed);
+        match dial_tls(&self.inner.connector, domain, port, self.inner.cfg.connect_timeout).await {
+            Ok(stream) => Ok(self.guard(key, stream)),
+            Err(e) => {
+                self.inner.state.lock().unwrap().release(&key);
+                Err(e)
+            }
+        }
+    }
+
+    /// Close idle connections past the idle timeout; returns how many were closed.
+    pub fn evict_expired(&self) -> usize {
+        let mut state = self.inner.state.lock().unwrap();
+        self.evict_expired_locked(&mut state)
+    }
+
+    pub fn stats(&self) -> TlsPoolStats {
+        TlsPoolStats {
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+            evictions: self.inner.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Idle connections currently cached for domain:port
+    pub fn idle_count(&self, domain: &str, port: u16) -> usize {
+        let state = self.inner.state.lock().unwrap();
+        state.idle.get(&(domain.to_string(), port)).map_or(0, Vec::len)
+    }
+
+    fn guard(&self, key: HostKey, stream: TlsStream) -> PooledTls {
+        PooledTls { stream: Some(stream), key, pool: self.inner.clone(), poisoned: false }
+    }
+
+    fn evict_expired_locked(&self, state: &mut PoolState) -> usize {
+        let idle_timeout = self.inner.cfg.idle_timeout;
+        let mut expired = Vec::new();
+        for (key, conns) in state.idle.iter_mut() {
+            let before = conns.len();
+            conns.retain(|c| c.since.elapsed() < idle_timeout);
+            expired.extend(std::iter::repeat_n(key.clone(), before - conns.len()));
+        }
+        state.idle.retain(|_, conns| !conns.is_empty());
+        for key in &expired {
+            state.release(key);
+        }
+        self.inner.evictions.fetch_add(expired.len() as u64, Ordering::Relaxed);
+        expired.len()
+    }
+
+    /// Close the longest-idle connection of any host to make room under max_total
+    fn evict_oldest_locked(&self, state: &mut PoolState) -> bool {
+        let oldest = state
+            .idle
+            .iter()
+            .flat_map(|(key, conns)| conns.iter().enumerate().map(move |(i, c)| (c.since, key, i)))
+            .min_by_key(|(since, _, _)| *since)
+            .map(|(_, key, i)| (key.clone(), i));
+        let Some((key, i)) = oldest else { return false };
+
+        if let Some(conns) = state.idle.get_mut(&key) {
+            conns.remove(i);
+            if conns.is_empty() {
+                state.idle.remove(&key);
+            }
+        }
+        state.release(&key);
+        self.inner.evictions.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+}
+
+/// A pooled TLS connection; goes back to its host's idle list on drop unless poisoned
+/// or the peer has closed it.
+pub struct PooledTls {
+    stream: Option<TlsStream>,
+    key: HostKey,
+    pool: Arc<PoolInner>,
+    poisoned: bool,
+}
+
+impl PooledTls {
+    /// Close the connection on drop instead of reusing it (e.g. after an I/O error or a
+    /// response that leaves the stream mid-message)
+    pub fn poison(&mut self) {
+        self.poisoned = true;
+    }
+}
+
+impl Deref for PooledTls {
+    type Target = TlsStream;
+
+    fn deref(&self) -> &TlsStream {
+        self.stream.as_ref().expect("stream present until drop")
+    }
+}
+
+impl DerefMut for PooledTls {
+    fn deref_mut(&mut self) -> &mut TlsStream {
+        self.stream.as_mut().expect("stream present until drop")
+    }
+}
+
+impl Drop for PooledTls {
+    fn drop(&mut self) {
+        let Some(stream) = self.stream.take() else { return };
+        let mut state = self.pool.state.lock().unwrap();
+        if self.poisoned || !is_usable(&stream) {
+            state.release(&self.key);
+            return;
+        }
+        state
+            .idle
+            .entry(self.key.clone())
+            .or_default()
+            .push(IdleTls { stream, since: Instant::now() });
+    }
+}
+
+/// Non-blocking peek on the socket: EOF or an error means the peer is gone. Pending bytes
+/// (e.g. TLS session tickets) are left for rustls and do not disqualify the connection.
+fn is_usable(stream: &TlsStream) -> bool {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 1];
+    match SockRef::from(stream.get_ref().0).peek(&mut buf) {
+        Ok(0) => false,
+        Ok(_) => true,
+        Err(e) => e.kind() == std::io::ErrorKind::WouldBlock,
+    }
+}
+
 // ------------------------------------------------------------
 // Usage notes (keep for your VS Agent / future reader)
 // ------------------------------------------------------------
@@ -213,20 +662,245 @@ pub fn pad_frame(mut msg: Vec<u8>, multiple: usize) -> Vec<u8> {
 
 // 1) Replace direct connects in your P2P client:
 let tcp = netkit::connect_happy(&addr, self.cfg.connection_timeout).await?;
+let opts = netkit::ConnectOpts::new().with_stagger(Duration::from_millis(100));
+let tcp = netkit::connect_happy_with(&addr, &opts).await?;
 
 // 2) For HTTPS/TLS upstreams:
 let mut tls = netkit::connect_tls("api.example.com", 443, Duration::from_secs(10)).await?;
 netkit::write_all_deadline(&mut tls, request_bytes, Duration::from_secs(3)).await?;
 netkit::read_exact_deadline(&mut tls, &mut buf, Duration::from_secs(3)).await?;
 
-// 3) Optional: smooth frame sizes
+// 3) Keep-alive for hosts you hit repeatedly:
+let pool = netkit::TlsPool::new(netkit::TlsPoolConfig::default(), None)?;
+let mut tls = pool.get("gateway.example.com", 443).await?;
+netkit::write_all_deadline(&mut *tls, request_bytes, Duration::from_secs(3)).await?;
+drop(tls); // back to the pool for the next request
+
+// 4) Optional: smooth frame sizes
 let framed = netkit::pad_frame(payload, 128);
 
+// 5) Or let both ends speak the same padded, length-prefixed framing:
+let codec = netkit::FrameCodec::new().with_max_frame_size(1 << 20);
+codec.write_frame(&mut tls, &payload).await?;
+let reply = codec.read_frame(&mut tls).await?;
+
 --------------------------------------------------------------
 Why this single file helps immediately:
 
 - Lower p95/p99 connects → Happy-Eyeballs + tuned sockets
 - Fewer stalls → bounded read/write deadlines
 - Fewer TLS surprises → proper SNI, native roots, TLS1.3, ALPN
-- Faster repeat calls → session resumption
+- Faster repeat calls → session resumption, or no handshake at all with TlsPool
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::pki_types::{CertificateDer, PrivatePkcs8KeyDer};
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    /// TLS echo server on 127.0.0.1 with a self-signed certificate for "localhost"
+    async fn spawn_echo_server() -> Result<(u16, RootCertStore)> {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+        let cert_der: CertificateDer<'static> = cert.cert.der().to_vec().into();
+        let key_der = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
+        let server_cfg = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert_der.clone()], key_der.into())?;
+        let acceptor = TlsAcceptor::from(Arc::new(server_cfg));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        tokio::spawn(async move {
+            while let Ok((tcp, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let Ok(mut tls) = acceptor.accept(tcp).await else { return };
+                    let mut buf = [0u8; 1024];
+                    while let Ok(n) = tls.read(&mut buf).await {
+                        if n == 0 || tls.write_all(&buf[..n]).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert_der)?;
+        Ok((port, roots))
+    }
+
+    /// Echo one message and return the client's local port
+    async fn echo(pool: &TlsPool, port: u16) -> Result<u16> {
+        let mut tls = pool.get("localhost", port).await?;
+        write_all_deadline(&mut *tls, b"ping", Duration::from_secs(2)).await?;
+        let mut buf = [0u8; 4];
+        read_exact_deadline(&mut *tls, &mut buf, Duration::from_secs(2)).await?;
+        assert_eq!(&buf, b"ping");
+        Ok(tls.get_ref().0.local_addr()?.port())
+    }
+
+    #[test]
+    fn test_interleave_prefers_ipv6_and_alternates() {
+        let addrs: Vec<SocketAddr> = ["10.0.0.1:1", "10.0.0.2:1", "[::1]:1", "10.0.0.3:1", "[::2]:1"]
+            .iter()
+            .map(|a| a.parse().unwrap())
+            .collect();
+        let ordered: Vec<String> = interleave_families(addrs).iter().map(|a| a.to_string()).collect();
+        assert_eq!(ordered, ["[::1]:1", "10.0.0.1:1", "[::2]:1", "10.0.0.2:1", "10.0.0.3:1"]);
+    }
+
+    #[tokio::test]
+    async fn test_race_skips_unroutable_address_after_stagger() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let working = listener.local_addr()?;
+        // TEST-NET-1 (RFC 5737) never answers, so only the stagger lets the race move on
+        let unroutable: SocketAddr = "192.0.2.1:9".parse()?;
+        let stagger = Duration::from_millis(100);
+        let opts = ConnectOpts::new().with_stagger(stagger).with_attempt_timeout(Duration::from_secs(5));
+
+        let started = Instant::now();
+        let tcp = race_connect("test", vec![unroutable, working], &opts).await?;
+        assert_eq!(tcp.peer_addr()?, working);
+        assert!(started.elapsed() < stagger + Duration::from_millis(400), "took {:?}", started.elapsed());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_race_prefers_ipv6_when_both_listen() -> Result<()> {
+        // Skip where the host has no IPv6 loopback
+        let Ok(v6) = TcpListener::bind("[::1]:0").await else { return Ok(()) };
+        let v4 = TcpListener::bind("127.0.0.1:0").await?;
+        let addrs = interleave_families(vec![v4.local_addr()?, v6.local_addr()?]);
+
+        let tcp = race_connect("test", addrs, &ConnectOpts::default()).await?;
+        assert_eq!(tcp.peer_addr()?, v6.local_addr()?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_all_failed_attempts_are_reported() -> Result<()> {
+        // Bind then drop to get ports nothing listens on
+        let (a, b) = (TcpListener::bind("127.0.0.1:0").await?, TcpListener::bind("127.0.0.1:0").await?);
+        let (closed_a, closed_b) = (a.local_addr()?, b.local_addr()?);
+        drop((a, b));
+
+        let err = race_connect("closed", vec![closed_a, closed_b], &ConnectOpts::default())
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<ConnectError>().expect("structured connect error");
+        let tried: Vec<SocketAddr> = err.attempts.iter().map(|(sa, _)| *sa).collect();
+        assert_eq!(tried, vec![closed_a, closed_b]);
+        assert!(err.to_string().contains(&closed_b.to_string()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_frame_round_trip_over_duplex() -> Result<()> {
+        let codec = FrameCodec::new().with_block_size(16);
+        let (mut client, mut server) = tokio::io::duplex(64);
+        let payloads: Vec<Vec<u8>> = vec![b"hello".to_vec(), Vec::new(), vec![7u8; 12], vec![0xAB; 1000]];
+
+        let writer = async {
+            for p in &payloads {
+                codec.write_frame(&mut client, p).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+        let reader = async {
+            let mut got = Vec::new();
+            for _ in 0..payloads.len() {
+                got.push(codec.read_frame(&mut server).await?);
+            }
+            Ok::<_, anyhow::Error>(got)
+        };
+        let (written, read) = tokio::join!(writer, reader);
+        written?;
+        assert_eq!(read?, payloads);
+
+        // Every frame on the wire is a whole number of blocks
+        let mut wire = Vec::new();
+        codec.write_frame(&mut wire, b"hello").await?;
+        assert_eq!(wire.len(), 16);
+        assert_eq!(&wire[..4], &5u32.to_be_bytes());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_frame_over_max_size_is_rejected() -> Result<()> {
+        let codec = FrameCodec::new().with_max_frame_size(1024);
+
+        // A 4 GiB length prefix must fail without trying to allocate the body
+        let mut malicious: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF];
+        let err = codec.read_frame(&mut malicious).await.unwrap_err();
+        assert!(err.to_string().contains("max is 1024"), "{}", err);
+
+        let mut sink = Vec::new();
+        assert!(codec.write_frame(&mut sink, &[0u8; 1025]).await.is_err());
+        assert!(sink.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_torn_frame_is_an_error() -> Result<()> {
+        let codec = FrameCodec::new().with_block_size(16);
+        let mut wire = Vec::new();
+        codec.write_frame(&mut wire, &[1u8; 40]).await?;
+
+        // Peer closes after sending part of the body
+        let (mut client, mut server) = tokio::io::duplex(256);
+        client.write_all(&wire[..20]).await?;
+        drop(client);
+
+        let err = codec.read_frame(&mut server).await.unwrap_err();
+        let io = err.root_cause().downcast_ref::<std::io::Error>().expect("io error");
+        assert_eq!(io.kind(), std::io::ErrorKind::UnexpectedEof);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sequential_requests_reuse_socket() -> Result<()> {
+        let (port, roots) = spawn_echo_server().await?;
+        let pool = TlsPool::new(TlsPoolConfig::default(), Some(roots))?;
+
+        let first = echo(&pool, port).await?;
+        let second = echo(&pool, port).await?;
+        assert_eq!(first, second);
+        assert_eq!(pool.stats(), TlsPoolStats { hits: 1, misses: 1, evictions: 0 });
+        assert_eq!(pool.idle_count("localhost", port), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_idle_expiry_dials_fresh_connection() -> Result<()> {
+        let (port, roots) = spawn_echo_server().await?;
+        let cfg = TlsPoolConfig { idle_timeout: Duration::from_millis(50), ..Default::default() };
+        let pool = TlsPool::new(cfg, Some(roots))?;
+
+        let first = echo(&pool, port).await?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let second = echo(&pool, port).await?;
+        assert_ne!(first, second);
+        assert_eq!(pool.stats(), TlsPoolStats { hits: 0, misses: 2, evictions: 1 });
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_per_host_limit_and_poison() -> Result<()> {
+        let (port, roots) = spawn_echo_server().await?;
+        let cfg = TlsPoolConfig { max_per_host: 1, ..Default::default() };
+        let pool = TlsPool::new(cfg, Some(roots))?;
+
+        let mut held = pool.get("localhost", port).await?;
+        assert!(pool.get("localhost", port).await.is_err());
+
+        held.poison();
+        drop(held);
+        assert_eq!(pool.idle_count("localhost", port), 0);
+        echo(&pool, port).await?;
+        assert_eq!(pool.stats().misses, 2);
+        Ok(())
+    }
+}